@@ -0,0 +1,93 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! Removal of the helices and strands of a design that hold no nucleotide.
+
+use super::{Design, Domain};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+
+/// The identifiers of the helices and strands of a design that hold no nucleotide
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Orphans {
+    pub helices: Vec<usize>,
+    pub strands: Vec<usize>,
+}
+
+impl Orphans {
+    pub fn is_empty(&self) -> bool {
+        self.helices.is_empty() && self.strands.is_empty()
+    }
+}
+
+impl Design {
+    /// Return the strands that have no nucleotide on an helix, and the helices on which no
+    /// nucleotide lies once these strands are removed.
+    pub fn orphans(&self) -> Orphans {
+        let mut strands = Vec::new();
+        let mut used_helices = HashSet::new();
+        for (s_id, strand) in self.strands.iter() {
+            let helices: Vec<usize> = strand
+                .domains
+                .iter()
+                .filter_map(|d| match d {
+                    Domain::HelixDomain(interval) if interval.start < interval.end => {
+                        Some(interval.helix)
+                    }
+                    _ => None,
+                })
+                .collect();
+            if helices.is_empty() {
+                strands.push(*s_id);
+            }
+            used_helices.extend(helices);
+        }
+        let helices = self
+            .helices
+            .keys()
+            .filter(|h_id| !used_helices.contains(h_id))
+            .cloned()
+            .collect();
+        Orphans { helices, strands }
+    }
+
+    /// Remove the orphans of self, as well as the anchors, annotations and groups that refer to
+    /// them. Return the removed orphans.
+    pub fn remove_orphans(&mut self) -> Orphans {
+        let orphans = self.orphans();
+        for s_id in orphans.strands.iter() {
+            self.strands.remove(s_id);
+        }
+        if self.scaffold_id.map(|s_id| orphans.strands.contains(&s_id)) == Some(true) {
+            self.scaffold_id = None;
+        }
+
+        let mut helices = BTreeMap::clone(self.helices.as_ref());
+        let mut groups = BTreeMap::clone(self.groups.as_ref());
+        for h_id in orphans.helices.iter() {
+            helices.remove(h_id);
+            groups.remove(h_id);
+        }
+        self.helices = Arc::new(helices);
+        self.groups = Arc::new(groups);
+        let helices = &self.helices;
+        self.anchors.retain(|n| helices.contains_key(&n.helix));
+        self.annotations
+            .retain(|a| helices.contains_key(&a.nucl.helix));
+        orphans
+    }
+}
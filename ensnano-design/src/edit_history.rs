@@ -0,0 +1,122 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! Compact representation of the states of a design that preceded the one saved in a file.
+//!
+//! Each previous state is stored as a patch of the json representation of the state that follows
+//! it. Only the fields of json objects that differ between the two states are stored, so a patch is
+//! usually much smaller than a whole design.
+
+use super::Design;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// A modification of a json value.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum JsonPatch {
+    /// Replace the value by a new one
+    Set(Value),
+    /// Modify some fields of a json object. Fields mapped to `None` are removed.
+    Fields(BTreeMap<String, Option<JsonPatch>>),
+}
+
+impl JsonPatch {
+    /// Return the patch that transforms `from` into `to`, or `None` if they are equal.
+    pub fn between(from: &Value, to: &Value) -> Option<Self> {
+        if from == to {
+            return None;
+        }
+        match (from, to) {
+            (Value::Object(from), Value::Object(to)) => {
+                let mut fields = BTreeMap::new();
+                for key in from.keys().filter(|k| !to.contains_key(*k)) {
+                    fields.insert(key.clone(), None);
+                }
+                for (key, value) in to.iter() {
+                    if let Some(old_value) = from.get(key) {
+                        if let Some(patch) = Self::between(old_value, value) {
+                            fields.insert(key.clone(), Some(patch));
+                        }
+                    } else {
+                        fields.insert(key.clone(), Some(Self::Set(value.clone())));
+                    }
+                }
+                Some(Self::Fields(fields))
+            }
+            _ => Some(Self::Set(to.clone())),
+        }
+    }
+
+    /// Modify `value` according to the patch
+    pub fn apply(&self, value: &mut Value) {
+        match self {
+            Self::Set(new_value) => *value = new_value.clone(),
+            Self::Fields(fields) => {
+                if !value.is_object() {
+                    *value = Value::Object(Default::default());
+                }
+                if let Value::Object(object) = value {
+                    for (key, patch) in fields.iter() {
+                        if let Some(patch) = patch {
+                            patch.apply(object.entry(key.clone()).or_insert(Value::Null));
+                        } else {
+                            object.remove(key);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Design {
+    /// Return the patches that rebuild `history` from `self`, most recent state first.
+    ///
+    /// `history` must be sorted from the oldest state to the most recent one.
+    pub(super) fn history_to_patches(
+        &self,
+        history: &[Design],
+    ) -> Result<Vec<JsonPatch>, serde_json::Error> {
+        let mut next = serde_json::to_value(self)?;
+        let mut ret = Vec::with_capacity(history.len());
+        for design in history.iter().rev() {
+            let previous = serde_json::to_value(design)?;
+            ret.push(
+                JsonPatch::between(&next, &previous)
+                    .unwrap_or_else(|| JsonPatch::Fields(Default::default())),
+            );
+            next = previous;
+        }
+        Ok(ret)
+    }
+
+    /// Rebuild the states described by `patches` from `self`, and return them oldest first.
+    pub(super) fn patches_to_history(
+        &self,
+        patches: &[JsonPatch],
+    ) -> Result<Vec<Design>, serde_json::Error> {
+        let mut value = serde_json::to_value(self)?;
+        let mut ret = Vec::with_capacity(patches.len());
+        for patch in patches.iter() {
+            patch.apply(&mut value);
+            ret.push(serde_json::from_value(value.clone())?);
+        }
+        ret.reverse();
+        Ok(ret)
+    }
+}
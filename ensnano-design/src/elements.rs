@@ -171,7 +171,7 @@ impl ElementKey for DnaElementKey {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum DnaAttribute {
     Visible(bool),
     XoverGroup(Option<bool>),
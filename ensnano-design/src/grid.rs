@@ -29,6 +29,8 @@ pub struct Grid {
     pub parameters: Parameters,
     pub grid_type: GridType,
     pub invisible: bool,
+    /// The roll, in radians, given to the helices that are placed on the grid
+    pub twist_offset: f32,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Copy)]
@@ -38,6 +40,9 @@ pub struct GridDescriptor {
     pub grid_type: GridTypeDescr,
     #[serde(default)]
     pub invisible: bool, // by default grids are visible so we store a "negative attribute"
+    /// The roll, in radians, given to the helices that are placed on the grid
+    #[serde(default)]
+    pub twist_offset: f32,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
@@ -61,6 +66,7 @@ impl GridDescriptor {
             orientation,
             grid_type: hyperboloid.desc(),
             invisible: false,
+            twist_offset: 0.,
         }
     }
 
@@ -71,6 +77,7 @@ impl GridDescriptor {
             invisible: self.invisible,
             grid_type: self.grid_type.to_concrete(),
             parameters,
+            twist_offset: self.twist_offset,
         }
     }
 }
@@ -227,6 +234,7 @@ impl Grid {
             parameters,
             grid_type,
             invisible: false,
+            twist_offset: 0.,
         }
     }
 
@@ -334,6 +342,7 @@ impl Grid {
             orientation: self.orientation,
             grid_type: self.grid_type.descr(),
             invisible: self.invisible,
+            twist_offset: self.twist_offset,
         }
     }
 }
@@ -0,0 +1,285 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! Prediction of the hairpins that a single strand can form with itself, and construction of
+//! hairpins at the end of a strand.
+//!
+//! The minimum free energy structure is computed with a simplified Zuker algorithm: the energy
+//! of a structure is the sum of the nearest-neighbour free energies of its stacked pairs, of a
+//! penalty for each hairpin loop and of a penalty for each multi-loop. Bulges and interior loops
+//! are not considered.
+
+use super::staple_pools::{compl, nearest_neighbour_parameters};
+use super::{read_junctions, Domain, HelixInterval, Nucl, Strand};
+use std::borrow::Cow;
+
+/// The minimum number of unpaired bases in a hairpin loop
+const MIN_LOOP_LENGTH: usize = 3;
+/// The temperature in K at which the free energies are computed
+const TEMPERATURE: f64 = 310.15;
+/// The free energy in kcal/mol of the closure of a multi-loop
+const MULTI_LOOP_PENALTY: f64 = 3.4;
+
+/// A stem of consecutive stacked pairs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HairpinStem {
+    /// The position of the first base of the 5' arm of the stem
+    pub start: usize,
+    /// The position of the last base of the 3' arm of the stem
+    pub end: usize,
+    /// The number of base pairs of the stem
+    pub length: usize,
+}
+
+impl HairpinStem {
+    /// Return the 5' arm and the 3' arm of the stem in `sequence`
+    pub fn arms<'a>(&self, sequence: &'a str) -> (&'a str, &'a str) {
+        (
+            &sequence[self.start..self.start + self.length],
+            &sequence[self.end + 1 - self.length..=self.end],
+        )
+    }
+}
+
+/// Return the longest stem of the minimum free energy structure of `sequence`, or `None` if this
+/// structure has no base pair or if `sequence` contains characters that are not bases.
+///
+/// The complexity of the prediction is cubic in the length of the sequence.
+pub fn predict_longest_stem(sequence: &str) -> Option<HairpinStem> {
+    let bases: Vec<char> = sequence.chars().map(|c| c.to_ascii_uppercase()).collect();
+    if bases.iter().any(|b| compl(*b).is_none()) {
+        return None;
+    }
+    let mut pairs = mfe_pairs(&bases);
+    pairs.sort_unstable();
+    let mut ret: Option<HairpinStem> = None;
+    let mut current: Option<HairpinStem> = None;
+    for (i, j) in pairs {
+        current = match current {
+            Some(stem) if stem.start + stem.length == i && stem.end + 1 - stem.length == j + 1 => {
+                Some(HairpinStem {
+                    length: stem.length + 1,
+                    ..stem
+                })
+            }
+            _ => Some(HairpinStem {
+                start: i,
+                end: j,
+                length: 1,
+            }),
+        };
+        if current.map(|s| s.length) > ret.map(|s| s.length) {
+            ret = current;
+        }
+    }
+    ret
+}
+
+/// Return the domains of a hairpin that starts right after `prime3`.
+///
+/// The first arm of the stem continues `prime3` on its helix for `stem_length` nucleotides. It
+/// is followed by a loop of `loop_length` unpaired nucleotides, and by the second arm of the
+/// stem that comes back on the same nucleotides of the helix, in the opposite direction.
+pub fn hairpin_domains(prime3: Nucl, stem_length: usize, loop_length: usize) -> Vec<Domain> {
+    let (start, end) = if prime3.forward {
+        (
+            prime3.position + 1,
+            prime3.position + 1 + stem_length as isize,
+        )
+    } else {
+        (prime3.position - stem_length as isize, prime3.position)
+    };
+    let arm = |forward: bool| {
+        Domain::HelixDomain(HelixInterval {
+            helix: prime3.helix,
+            start,
+            end,
+            forward,
+            sequence: None,
+        })
+    };
+    let mut ret = vec![arm(prime3.forward)];
+    if loop_length > 0 {
+        ret.push(Domain::Insertion(loop_length));
+    }
+    ret.push(arm(!prime3.forward));
+    ret
+}
+
+impl Strand {
+    /// Extend the 3' end of self with a hairpin made of a stem of `stem_length` base pairs and
+    /// of a loop of `loop_length` nucleotides. See [hairpin_domains].
+    ///
+    /// If the sequence of self gives the bases of the first arm of the stem, the loop is made of
+    /// T and the second arm is given the complementary sequence.
+    ///
+    /// Return false, and leave self unchanged, if self is cyclic or does not end on an helix.
+    pub fn add_hairpin(&mut self, stem_length: usize, loop_length: usize) -> bool {
+        if self.cyclic || !matches!(self.domains.last(), Some(Domain::HelixDomain(_))) {
+            return false;
+        }
+        let prime3 = if let Some(nucl) = self.get_3prime() {
+            nucl
+        } else {
+            return false;
+        };
+        let length = self.length();
+        if let Some(sequence) = self.sequence.as_mut() {
+            let first_arm: Option<String> = sequence
+                .chars()
+                .skip(length)
+                .take(stem_length)
+                .collect::<Vec<char>>()
+                .into_iter()
+                .rev()
+                .map(|c| compl(c.to_ascii_uppercase()))
+                .collect();
+            if let Some(second_arm) = first_arm.filter(|arm| arm.len() == stem_length) {
+                let mut new_sequence: String =
+                    sequence.chars().take(length + stem_length).collect();
+                new_sequence.extend(std::iter::repeat('T').take(loop_length));
+                new_sequence.push_str(&second_arm);
+                *sequence = Cow::Owned(new_sequence);
+            }
+        }
+        self.domains
+            .extend(hairpin_domains(prime3, stem_length, loop_length));
+        self.merge_consecutive_domains();
+        self.junctions = read_junctions(&self.domains, false);
+        true
+    }
+}
+
+fn stack_energy(stack: (char, char)) -> f64 {
+    nearest_neighbour_parameters(stack)
+        .map(|(delta_h, delta_s)| delta_h - TEMPERATURE * delta_s / 1000.)
+        .unwrap_or(0.)
+}
+
+/// The free energy in kcal/mol of a hairpin loop of `length` unpaired bases
+fn hairpin_loop_energy(length: usize) -> f64 {
+    match length {
+        3 | 4 => 3.5,
+        5 => 3.3,
+        6 => 4.0,
+        7 => 4.2,
+        8 => 4.3,
+        9 => 4.5,
+        n => 4.6 + 1.75 * 0.616 * (n as f64 / 10.).ln(),
+    }
+}
+
+#[derive(Clone, Copy)]
+enum PairedChoice {
+    Hairpin,
+    Stack,
+    MultiLoop(usize),
+}
+
+#[derive(Clone, Copy)]
+enum FreeChoice {
+    Unpaired,
+    Paired,
+    Split(usize),
+}
+
+/// Return the base pairs of the minimum free energy structure of `bases`
+fn mfe_pairs(bases: &[char]) -> Vec<(usize, usize)> {
+    let n = bases.len();
+    if n < MIN_LOOP_LENGTH + 2 {
+        return Vec::new();
+    }
+    // paired[i][j] is the minimum energy of a structure of bases[i..=j] in which i and j are
+    // paired, free[i][j] is the minimum energy of any structure of bases[i..=j].
+    let mut paired = vec![vec![f64::INFINITY; n]; n];
+    let mut paired_choice = vec![vec![PairedChoice::Hairpin; n]; n];
+    let mut free = vec![vec![0f64; n]; n];
+    let mut free_choice = vec![vec![FreeChoice::Unpaired; n]; n];
+    let can_pair = |i: usize, j: usize| compl(bases[i]) == Some(bases[j]);
+
+    for span in 1..n {
+        for i in 0..(n - span) {
+            let j = i + span;
+            if span > MIN_LOOP_LENGTH && can_pair(i, j) {
+                let mut best = hairpin_loop_energy(span - 1);
+                let mut choice = PairedChoice::Hairpin;
+                if paired[i + 1][j - 1].is_finite() {
+                    let energy = paired[i + 1][j - 1] + stack_energy((bases[i], bases[i + 1]));
+                    if energy < best {
+                        best = energy;
+                        choice = PairedChoice::Stack;
+                    }
+                }
+                for k in (i + 1)..(j - 1) {
+                    let energy = MULTI_LOOP_PENALTY + free[i + 1][k] + free[k + 1][j - 1];
+                    if energy < best {
+                        best = energy;
+                        choice = PairedChoice::MultiLoop(k);
+                    }
+                }
+                paired[i][j] = best;
+                paired_choice[i][j] = choice;
+            }
+            let mut best = 0.;
+            let mut choice = FreeChoice::Unpaired;
+            if paired[i][j] < best {
+                best = paired[i][j];
+                choice = FreeChoice::Paired;
+            }
+            for k in i..j {
+                let energy = free[i][k] + free[k + 1][j];
+                if energy < best {
+                    best = energy;
+                    choice = FreeChoice::Split(k);
+                }
+            }
+            free[i][j] = best;
+            free_choice[i][j] = choice;
+        }
+    }
+
+    let mut ret = Vec::new();
+    // (i, j, paired) means that the interval bases[i..=j] must be read in paired if `paired` is
+    // true, and in free otherwise.
+    let mut to_read = vec![(0, n - 1, false)];
+    while let Some((i, j, is_paired)) = to_read.pop() {
+        if i >= j {
+            continue;
+        }
+        if is_paired {
+            ret.push((i, j));
+            match paired_choice[i][j] {
+                PairedChoice::Hairpin => (),
+                PairedChoice::Stack => to_read.push((i + 1, j - 1, true)),
+                PairedChoice::MultiLoop(k) => {
+                    to_read.push((i + 1, k, false));
+                    to_read.push((k + 1, j - 1, false));
+                }
+            }
+        } else {
+            match free_choice[i][j] {
+                FreeChoice::Unpaired => (),
+                FreeChoice::Paired => to_read.push((i, j, true)),
+                FreeChoice::Split(k) => {
+                    to_read.push((i, k, false));
+                    to_read.push((k + 1, j, false));
+                }
+            }
+        }
+    }
+    ret
+}
@@ -19,7 +19,7 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 /// All other format supported by ensnano are converted into this format and run-time manipulation
 /// of designs are performed on an `ensnano::Design` structure
 use std::borrow::Cow;
-use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
 use std::f32::consts::PI;
 use std::sync::Arc;
 
@@ -40,6 +40,23 @@ use elements::DnaElementKey;
 pub type EnsnTree = OrganizerTree<DnaElementKey>;
 pub mod group_attributes;
 use group_attributes::GroupAttribute;
+mod torus_knot;
+pub use torus_knot::TorusKnot;
+mod validation;
+pub use validation::{
+    check_scaffold_continuity, DesignIssue, ScaffoldBreak, ScaffoldContinuityResult,
+};
+mod hairpin;
+pub use hairpin::{hairpin_domains, predict_longest_stem, HairpinStem};
+mod staple_pools;
+pub use staple_pools::melting_temperature;
+mod cleanup;
+pub use cleanup::Orphans;
+mod modifications;
+pub use modifications::StrandModification;
+mod edit_history;
+pub use edit_history::JsonPatch;
+mod subdesign;
 
 mod formating;
 #[cfg(test)]
@@ -121,11 +138,46 @@ pub struct Design {
 
     #[serde(default, skip_serializing_if = "Option::is_none")]
     saved_camera: Option<Camera>,
+
+    /// Text labels attached to nucleotides of the design
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub annotations: Vec<Annotation>,
+
+    /// The identifiers of the helices, from the top row to the bottom row of the 2D view.
+    /// Helices that are not in this list are displayed after the ones that are.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub helix_display_order: Vec<usize>,
+
+    /// Sets of elements saved under a name by the user, to be selected again later
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub selection_groups: BTreeMap<String, Vec<DnaElementKey>>,
+
+    /// The states of the design that preceded the saved one, so that they can be restored by
+    /// undoing after the design is reopened. Each state is stored as a patch of the state that
+    /// follows it, most recent state first. This is only filled in saved files.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    edit_history: Vec<JsonPatch>,
+}
+
+/// A text label attached to a nucleotide.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Annotation {
+    pub nucl: Nucl,
+    pub label: String,
 }
 
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct CameraId(u64);
 
+/// The maximum number of cameras that can be saved in a design. Each of them can be reached with
+/// a number key.
+pub const MAX_NB_CAMERAS: usize = 10;
+
+/// The error returned when trying to save a camera in a design that already has
+/// `MAX_NB_CAMERAS` cameras.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TooManyCameras;
+
 /// A saved camera position. This can be use to register intresting point of views of the design.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Camera {
@@ -205,6 +257,10 @@ impl Design {
             cameras: Default::default(),
             favorite_camera: None,
             saved_camera: None,
+            annotations: Default::default(),
+            helix_display_order: Vec::new(),
+            selection_groups: BTreeMap::new(),
+            edit_history: Vec::new(),
         }
     }
 
@@ -285,6 +341,54 @@ impl Design {
         return Extremity::No;
     }
 
+    /// Return the helix domains of the scaffold, in the 5' -> 3' order.
+    ///
+    /// Each of them is a double stranded segment delimited by two consecutive cross-overs of the
+    /// scaffold. The position of a domain in the returned vector is used as its identifier.
+    pub fn scaffold_domains(&self) -> Vec<&HelixInterval> {
+        self.scaffold_id
+            .and_then(|s_id| self.strands.get(&s_id))
+            .map(|scaffold| {
+                scaffold
+                    .domains
+                    .iter()
+                    .filter_map(|d| match d {
+                        Domain::HelixDomain(interval) => Some(interval),
+                        Domain::Insertion(_) => None,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Return the identifier of the scaffold domain that contains `nucl` or its complement
+    pub fn get_scaffold_domain_id(&self, nucl: &Nucl) -> Option<usize> {
+        self.scaffold_domains().iter().position(|interval| {
+            interval.helix == nucl.helix
+                && nucl.position >= interval.start
+                && nucl.position < interval.end
+        })
+    }
+
+    /// Return the nucleotides of a scaffold domain, together with their complements.
+    ///
+    /// The complements are returned even if they do not belong to a strand.
+    pub fn get_scaffold_domain_nucls(&self, domain_id: usize) -> Option<Vec<Nucl>> {
+        let domains = self.scaffold_domains();
+        let interval = domains.get(domain_id)?;
+        let mut ret = Vec::with_capacity(2 * (interval.end - interval.start).max(0) as usize);
+        for position in interval.iter() {
+            let nucl = Nucl {
+                helix: interval.helix,
+                position,
+                forward: interval.forward,
+            };
+            ret.push(nucl);
+            ret.push(nucl.compl());
+        }
+        Some(ret)
+    }
+
     /// Return a list of tuples (n1, n2, M) where n1 and n2 are nuclotides that are not on the same
     /// helix and whose distance is at most `epsilon` and M is the middle of the segment between
     /// the two positions of n1 and n2.
@@ -357,7 +461,16 @@ impl Design {
         false
     }
 
-    pub fn add_camera(&mut self, position: Vec3, orientation: Rotor3) {
+    /// Save a new camera in self. Return an error if self already has `MAX_NB_CAMERAS`
+    /// cameras.
+    pub fn add_camera(
+        &mut self,
+        position: Vec3,
+        orientation: Rotor3,
+    ) -> Result<(), TooManyCameras> {
+        if self.cameras.len() >= MAX_NB_CAMERAS {
+            return Err(TooManyCameras);
+        }
         let cam_id = self
             .cameras
             .keys()
@@ -371,6 +484,7 @@ impl Design {
             id: cam_id,
         };
         self.cameras.insert(cam_id, new_camera);
+        Ok(())
     }
 
     pub fn rm_camera(&mut self, cam_id: CameraId) -> Result<(), ()> {
@@ -420,8 +534,22 @@ impl Design {
         self.cameras.iter()
     }
 
-    pub fn prepare_for_save(&mut self, saving_information: SavingInformation) {
+    pub fn prepare_for_save(
+        &mut self,
+        saving_information: SavingInformation,
+    ) -> Result<(), serde_json::Error> {
         self.saved_camera = saving_information.camera;
+        // The history that was read with the design must not be part of the patches
+        self.edit_history.clear();
+        self.edit_history = self.history_to_patches(&saving_information.edit_history)?;
+        Ok(())
+    }
+
+    /// Remove the edit history read from a saved file and return the states that it describes,
+    /// oldest first
+    pub fn take_edit_history(&mut self) -> Result<Vec<Design>, serde_json::Error> {
+        let patches = std::mem::take(&mut self.edit_history);
+        self.patches_to_history(&patches)
     }
 
     pub fn get_nucl_position(&self, nucl: Nucl) -> Option<Vec3> {
@@ -432,10 +560,107 @@ impl Design {
             nucl.forward,
         ))
     }
+
+    /// Attach `label` to `nucl`, replacing the previous label of `nucl` if any. An empty label
+    /// removes the annotation of `nucl`.
+    pub fn annotate_nucleotide(&mut self, nucl: Nucl, label: String) {
+        if label.is_empty() {
+            self.annotations.retain(|a| a.nucl != nucl);
+        } else if let Some(annotation) = self.annotations.iter_mut().find(|a| a.nucl == nucl) {
+            annotation.label = label;
+        } else {
+            self.annotations.push(Annotation { nucl, label });
+        }
+    }
+
+    pub fn get_annotation(&self, nucl: &Nucl) -> Option<&str> {
+        self.annotations
+            .iter()
+            .find(|a| a.nucl == *nucl)
+            .map(|a| a.label.as_str())
+    }
+
+    /// Return a shortest path from `from` to `to` in the graph whose nodes are the nucleotides of
+    /// the design and whose edges are the phosphodiester bonds and the Watson-Crick pairs.
+    ///
+    /// The returned path starts with `from` and ends with `to`. Return `None` if one of the two
+    /// nucleotides is not in the design or if they are not connected.
+    pub fn find_shortest_path(&self, from: Nucl, to: Nucl) -> Option<Vec<Nucl>> {
+        let bonds = self.phosphodiester_bonds();
+        if !bonds.contains_key(&from) || !bonds.contains_key(&to) {
+            return None;
+        }
+        let mut predecessors = HashMap::new();
+        predecessors.insert(from, from);
+        let mut queue = VecDeque::new();
+        queue.push_back(from);
+        while let Some(nucl) = queue.pop_front() {
+            if nucl == to {
+                let mut path = vec![to];
+                let mut current = to;
+                while current != from {
+                    current = predecessors[&current];
+                    path.push(current);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            let partner = Some(nucl.compl()).filter(|n| bonds.contains_key(n));
+            for neighbour in bonds[&nucl].iter().cloned().chain(partner) {
+                if !predecessors.contains_key(&neighbour) {
+                    predecessors.insert(neighbour, nucl);
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+        None
+    }
+
+    /// Map each nucleotide of the design to the nucleotides that it shares a phosphodiester bond
+    /// with.
+    fn phosphodiester_bonds(&self) -> HashMap<Nucl, Vec<Nucl>> {
+        let mut ret: HashMap<Nucl, Vec<Nucl>> = HashMap::new();
+        for s in self.strands.values() {
+            let nucls: Vec<Nucl> = s
+                .domains
+                .iter()
+                .filter_map(|d| {
+                    if let Domain::HelixDomain(interval) = d {
+                        Some(interval)
+                    } else {
+                        None
+                    }
+                })
+                .flat_map(|interval| {
+                    interval.iter().map(move |position| Nucl {
+                        helix: interval.helix,
+                        position,
+                        forward: interval.forward,
+                    })
+                })
+                .collect();
+            for n in nucls.iter() {
+                ret.entry(*n).or_default();
+            }
+            let mut add_bond = |n1: Nucl, n2: Nucl| {
+                ret.entry(n1).or_default().push(n2);
+                ret.entry(n2).or_default().push(n1);
+            };
+            for pair in nucls.windows(2) {
+                add_bond(pair[0], pair[1]);
+            }
+            if s.cyclic && nucls.len() > 2 {
+                add_bond(nucls[nucls.len() - 1], nucls[0]);
+            }
+        }
+        ret
+    }
 }
 
 pub struct SavingInformation {
     pub camera: Option<Camera>,
+    /// The states of the design to embed in the file, oldest first
+    pub edit_history: Vec<Design>,
 }
 
 impl Design {
@@ -465,19 +690,24 @@ impl Design {
             let helix = Helix::from_scadnano(h, &group_map, &groups, &mut helices_per_group)?;
             helices.insert(i, Arc::new(helix));
         }
+        // In scadnano, the modifications of the strands are keys of `modifications_in_design`
+        let modification = |key: &Option<String>| {
+            let modifications = scad.modifications_in_design.as_ref()?;
+            StrandModification::from_idt_code(&modifications.get(key.as_ref()?)?.idt_text)
+        };
         let mut strands = BTreeMap::new();
         for (i, s) in scad.strands.iter().enumerate() {
-            let strand = Strand::from_scadnano(s, &deletions)?;
+            let mut strand = Strand::from_scadnano(s, &deletions)?;
+            strand.modification_5prime = modification(&s.prime5_modification);
+            strand.modification_3prime = modification(&s.prime3_modification);
             strands.insert(i, strand);
         }
-        println!("grids {:?}", grids);
-        println!("helices {:?}", helices);
         Ok(Self {
             grids: Arc::new(grids),
             helices: Arc::new(helices),
             strands,
             small_spheres: Default::default(),
-            scaffold_id: None, //TODO determine this value
+            scaffold_id: scad.strands.iter().position(|s| s.is_scaffold),
             scaffold_sequence: None,
             scaffold_shift: None,
             groups: Default::default(),
@@ -541,6 +771,12 @@ pub struct Strand {
     /// will be given a name corresponding to the position of its 5' nucleotide
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub name: Option<Cow<'static, str>>,
+    /// The chemical modification attached to the 5' end of the strand, if any
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub modification_5prime: Option<StrandModification>,
+    /// The chemical modification attached to the 3' end of the strand, if any
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub modification_3prime: Option<StrandModification>,
 }
 
 /// Return a list of domains that validate the following condition:
@@ -634,6 +870,7 @@ impl Strand {
             cyclic,
             junctions,
             sequence,
+            name: scad.name.clone().map(Cow::Owned),
             ..Default::default()
         })
     }
@@ -1002,7 +1239,7 @@ impl Domain {
                 end,
                 forward,
                 insertions,
-                ..// TODO read insertion and deletion
+                .. // deletions are read beforehand by `ScadnanoStrand::read_deletions`
             } => {
                 let adjust = |n| n - deletions.get(helix).map(|s| count_leq(s, n)).unwrap_or(0);
 
@@ -1573,7 +1810,7 @@ impl Helix {
                 roll: 0f32,
             }),
             visible: true,
-            roll: 0f32,
+            roll: grid.twist_offset,
             locked_for_simulations: false,
         }
     }
@@ -0,0 +1,67 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! Chemical modifications that can be attached to the ends of a strand and that are emitted
+//! when ordering the strands.
+
+/// A chemical modification attached to the 5' or 3' end of a strand
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum StrandModification {
+    Phosphate,
+    Biotin,
+    Cy3,
+    Cy5,
+}
+
+impl StrandModification {
+    pub const ALL: [Self; 4] = [Self::Phosphate, Self::Biotin, Self::Cy3, Self::Cy5];
+
+    /// The code of the modification in IDT's ordering syntax when it is attached to the 5' end
+    /// (`prime5 == true`) or to the 3' end of the strand
+    pub fn idt_code(&self, prime5: bool) -> &'static str {
+        match (self, prime5) {
+            (Self::Phosphate, true) => "/5Phos/",
+            (Self::Phosphate, false) => "/3Phos/",
+            (Self::Biotin, true) => "/5Biosg/",
+            (Self::Biotin, false) => "/3Bio/",
+            (Self::Cy3, true) => "/5Cy3/",
+            (Self::Cy3, false) => "/3Cy3Sp/",
+            (Self::Cy5, true) => "/5Cy5/",
+            (Self::Cy5, false) => "/3Cy5Sp/",
+        }
+    }
+
+    /// The modification whose IDT code is `code`, at either end of the strand
+    pub fn from_idt_code(code: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .find(|m| m.idt_code(true) == code || m.idt_code(false) == code)
+            .cloned()
+    }
+}
+
+impl std::fmt::Display for StrandModification {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Phosphate => "Phosphate",
+            Self::Biotin => "Biotin",
+            Self::Cy3 => "Cy3",
+            Self::Cy5 => "Cy5",
+        };
+        write!(f, "{}", name)
+    }
+}
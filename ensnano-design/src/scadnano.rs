@@ -54,6 +54,7 @@ impl ScadnanoDesign {
             orientation: Rotor3::identity(),
             grid_type,
             invisible: false,
+            twist_offset: 0.,
         })
     }
 }
@@ -92,6 +93,7 @@ impl ScadnanoGroup {
             orientation,
             position: self.position,
             invisible: false,
+            twist_offset: 0.,
         })
     }
 }
@@ -127,6 +129,8 @@ pub struct ScadnanoStrand {
     pub prime3_modification: Option<String>,
     #[serde(default)]
     pub circular: bool,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub name: Option<String>,
 }
 
 impl ScadnanoStrand {
@@ -134,7 +138,8 @@ impl ScadnanoStrand {
         let color_str = &self.color[1..];
         let ret = u32::from_str_radix(color_str, 16);
         if let Ok(ret) = ret {
-            Ok(ret)
+            // scadnano colors have no alpha channel
+            Ok(0xFF_00_00_00 | ret)
         } else {
             Err(ScadnanoImportError::InvalidColor(color_str.to_string()))
         }
@@ -0,0 +1,227 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! Partition of the staples into pools of decreasing melting temperature, for step-wise
+//! annealing protocols.
+
+use super::{Design, Domain, Nucl};
+use std::collections::{BTreeMap, HashMap};
+
+/// The gas constant in cal/(K.mol)
+const GAS_CONSTANT: f64 = 1.987;
+/// The concentration of each staple in mol/L
+const STAPLE_CONCENTRATION: f64 = 100e-9;
+
+/// The nearest-neighbour parameters of SantaLucia (1998) in 1M NaCl, as (ΔH in kcal/mol,
+/// ΔS in cal/(K.mol)). The parameters of the other stacks are obtained from those of their
+/// complement.
+pub(crate) fn nearest_neighbour_parameters(stack: (char, char)) -> Option<(f64, f64)> {
+    match stack {
+        ('A', 'A') => Some((-7.9, -22.2)),
+        ('A', 'T') => Some((-7.2, -20.4)),
+        ('T', 'A') => Some((-7.2, -21.3)),
+        ('C', 'A') => Some((-8.5, -22.7)),
+        ('G', 'T') => Some((-8.4, -22.4)),
+        ('C', 'T') => Some((-7.8, -21.0)),
+        ('G', 'A') => Some((-8.2, -22.2)),
+        ('C', 'G') => Some((-10.6, -27.2)),
+        ('G', 'C') => Some((-9.8, -24.4)),
+        ('G', 'G') => Some((-8.0, -19.9)),
+        (a, b) => {
+            let compl = (compl(b)?, compl(a)?);
+            if compl != stack {
+                nearest_neighbour_parameters(compl)
+            } else {
+                None
+            }
+        }
+    }
+}
+
+/// The initiation parameters of a duplex whose terminal base pair contains `basis`
+fn initiation_parameters(basis: char) -> (f64, f64) {
+    match basis {
+        'G' | 'C' => (0.1, -2.8),
+        _ => (2.3, 4.1),
+    }
+}
+
+pub(crate) fn compl(basis: char) -> Option<char> {
+    match basis {
+        'A' => Some('T'),
+        'T' => Some('A'),
+        'G' => Some('C'),
+        'C' => Some('G'),
+        _ => None,
+    }
+}
+
+/// Return the melting temperature in °C of `sequence` bound to its complement, computed with
+/// the nearest-neighbour model.
+///
+/// Whitespaces are ignored. Return `None` if the sequence contains less than two bases, or a
+/// character that is not a base.
+pub fn melting_temperature(sequence: &str) -> Option<f64> {
+    let bases: Vec<char> = sequence
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    if bases.len() < 2 {
+        return None;
+    }
+    let (mut delta_h, mut delta_s) = (0., 0.);
+    for end in [bases[0], bases[bases.len() - 1]].iter() {
+        let (h, s) = initiation_parameters(*end);
+        delta_h += h;
+        delta_s += s;
+    }
+    for stack in bases.windows(2) {
+        let (h, s) = nearest_neighbour_parameters((stack[0], stack[1]))?;
+        delta_h += h;
+        delta_s += s;
+    }
+    Some(1000. * delta_h / (delta_s + GAS_CONSTANT * (STAPLE_CONCENTRATION / 4.).ln()) - 273.15)
+}
+
+impl Design {
+    /// Return the sequence of each staple. The bases that are not given by the sequence of the
+    /// staple itself are deduced from the sequence of the scaffold.
+    ///
+    /// Nucleotides whose basis is unknown, like those of insertions when the staple has no
+    /// sequence, are represented by '?'.
+    pub fn get_staple_sequences(&self) -> BTreeMap<usize, String> {
+        let scaffold_basis = self.scaffold_basis_map();
+        self.strands
+            .iter()
+            .filter(|(s_id, strand)| Some(**s_id) != self.scaffold_id && strand.length() > 0)
+            .map(|(s_id, strand)| {
+                let own_sequence: Vec<char> = strand
+                    .sequence
+                    .as_ref()
+                    .map(|s| s.chars().filter(|c| c.is_alphabetic()).collect())
+                    .unwrap_or_default();
+                let mut own_bases = own_sequence.into_iter();
+                let mut sequence = String::with_capacity(strand.length());
+                for domain in strand.domains.iter() {
+                    match domain {
+                        Domain::HelixDomain(interval) => {
+                            for position in interval.iter() {
+                                let nucl = Nucl {
+                                    helix: interval.helix,
+                                    position,
+                                    forward: interval.forward,
+                                };
+                                let basis = own_bases
+                                    .next()
+                                    .or_else(|| {
+                                        scaffold_basis.get(&nucl.compl()).and_then(|b| compl(*b))
+                                    })
+                                    .unwrap_or('?');
+                                sequence.push(basis);
+                            }
+                        }
+                        Domain::Insertion(n) => {
+                            for _ in 0..*n {
+                                sequence.push(own_bases.next().unwrap_or('?'));
+                            }
+                        }
+                    }
+                }
+                (*s_id, sequence)
+            })
+            .collect()
+    }
+
+    /// Map each nucleotide of the scaffold to its basis
+    pub fn scaffold_basis_map(&self) -> HashMap<Nucl, char> {
+        let mut ret = HashMap::new();
+        let sequence: Vec<char> = if let Some(sequence) = self.scaffold_sequence.as_ref() {
+            sequence
+                .chars()
+                .filter(|c| c.is_alphabetic())
+                .map(|c| c.to_ascii_uppercase())
+                .collect()
+        } else {
+            return ret;
+        };
+        let scaffold = if let Some(scaffold) = self.scaffold_id.and_then(|s| self.strands.get(&s)) {
+            scaffold
+        } else {
+            return ret;
+        };
+        if sequence.is_empty() {
+            return ret;
+        }
+        let nb_skip = sequence.len() - (self.scaffold_shift.unwrap_or(0) % sequence.len());
+        let mut bases = sequence.iter().cycle().skip(nb_skip);
+        for domain in scaffold.domains.iter() {
+            match domain {
+                Domain::HelixDomain(interval) => {
+                    for position in interval.iter() {
+                        let nucl = Nucl {
+                            helix: interval.helix,
+                            position,
+                            forward: interval.forward,
+                        };
+                        if let Some(basis) = bases.next() {
+                            ret.insert(nucl, *basis);
+                        }
+                    }
+                }
+                Domain::Insertion(n) => {
+                    for _ in 0..*n {
+                        bases.next();
+                    }
+                }
+            }
+        }
+        ret
+    }
+
+    /// Partition the staples in `n_steps` pools for a step-wise annealing protocol.
+    ///
+    /// The staples are sorted by decreasing melting temperature, so that the first pool contains
+    /// the staples that must be added first. Staples whose melting temperature cannot be computed
+    /// are put in the last pool. Empty pools are not returned.
+    pub fn generate_staple_pools(&self, n_steps: usize) -> Vec<Vec<usize>> {
+        if n_steps == 0 {
+            return Vec::new();
+        }
+        let mut staples: Vec<(usize, Option<f64>)> = self
+            .get_staple_sequences()
+            .into_iter()
+            .map(|(s_id, sequence)| (s_id, melting_temperature(&sequence)))
+            .collect();
+        staples.sort_by(|(_, tm_a), (_, tm_b)| match (tm_a, tm_b) {
+            (Some(a), Some(b)) => b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        });
+        let nb_staples = staples.len();
+        (0..n_steps)
+            .map(|i| {
+                staples[i * nb_staples / n_steps..(i + 1) * nb_staples / n_steps]
+                    .iter()
+                    .map(|(s_id, _)| *s_id)
+                    .collect::<Vec<_>>()
+            })
+            .filter(|pool| !pool.is_empty())
+            .collect()
+    }
+}
@@ -0,0 +1,240 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! Extraction of a module of a design, and insertion of a module in an other design.
+
+use super::{read_junctions, sanitize_domains, Design, Domain, Helix, Nucl, Strand};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Arc;
+use ultraviolet::{Rotor3, Vec3};
+
+impl Design {
+    /// Return a design made of the helices `helix_ids`, of the grids holding them, and of the
+    /// parts of the strands that lie on them.
+    ///
+    /// A strand that goes through helices that are not in `helix_ids` is cut each time it leaves
+    /// the selected helices.
+    pub fn sub_design(&self, helix_ids: &[usize]) -> Self {
+        let helix_ids: BTreeSet<usize> = helix_ids
+            .iter()
+            .cloned()
+            .filter(|h_id| self.helices.contains_key(h_id))
+            .collect();
+        let mut ret = Self::new();
+        ret.parameters = self.parameters;
+
+        // Map the identifiers of the grids of self to the identifiers of the grids of ret
+        let mut grid_ids: HashMap<usize, usize> = HashMap::new();
+        let mut grids = Vec::new();
+        let mut helices = BTreeMap::new();
+        for h_id in helix_ids.iter() {
+            let mut helix = Helix::clone(self.helices[h_id].as_ref());
+            if let Some(grid_position) = helix.grid_position.as_mut() {
+                if let Some(grid) = self.grids.get(grid_position.grid) {
+                    let new_id = *grid_ids.entry(grid_position.grid).or_insert_with(|| {
+                        grids.push(grid.clone());
+                        grids.len() - 1
+                    });
+                    grid_position.grid = new_id;
+                } else {
+                    helix.grid_position = None;
+                }
+            }
+            helices.insert(*h_id, Arc::new(helix));
+        }
+        ret.helices = Arc::new(helices);
+        ret.grids = Arc::new(grids);
+        ret.small_spheres = self
+            .small_spheres
+            .iter()
+            .filter_map(|g_id| grid_ids.get(g_id).cloned())
+            .collect();
+        ret.no_phantoms = self
+            .no_phantoms
+            .iter()
+            .filter_map(|g_id| grid_ids.get(g_id).cloned())
+            .collect();
+
+        for (s_id, strand) in self.strands.iter() {
+            if let Some(pieces) = split_on_helices(strand, &helix_ids) {
+                for domains in pieces {
+                    let new_id = ret.strands.len();
+                    let domains = sanitize_domains(&domains, false);
+                    let junctions = read_junctions(&domains, false);
+                    ret.strands.insert(
+                        new_id,
+                        Strand {
+                            domains,
+                            junctions,
+                            color: strand.color,
+                            ..Default::default()
+                        },
+                    );
+                }
+            } else {
+                let new_id = ret.strands.len();
+                let mut strand = strand.clone();
+                // The identifiers of the cross-overs are only valid in self
+                strand.junctions = read_junctions(&strand.domains, strand.cyclic);
+                ret.strands.insert(new_id, strand);
+                if self.scaffold_id == Some(*s_id) {
+                    ret.scaffold_id = Some(new_id);
+                    ret.scaffold_sequence = self.scaffold_sequence.clone();
+                    ret.scaffold_shift = self.scaffold_shift;
+                }
+            }
+        }
+
+        ret.anchors = self
+            .anchors
+            .iter()
+            .filter(|n| helix_ids.contains(&n.helix))
+            .cloned()
+            .collect();
+        ret.annotations = self
+            .annotations
+            .iter()
+            .filter(|a| helix_ids.contains(&a.nucl.helix))
+            .cloned()
+            .collect();
+        ret
+    }
+
+    /// Add the helices, grids and strands of `other` to self. The added elements are translated
+    /// by `offset` and are given new identifiers.
+    ///
+    /// If self has no scaffold, the scaffold of `other` becomes the scaffold of self.
+    pub fn merge(&mut self, other: Design, offset: Vec3) {
+        let first_grid = self.grids.len();
+        let mut grids = Vec::clone(self.grids.as_ref());
+        for grid in other.grids.iter() {
+            let mut grid = grid.clone();
+            grid.position += offset;
+            grids.push(grid);
+        }
+        self.grids = Arc::new(grids);
+        self.small_spheres
+            .extend(other.small_spheres.iter().map(|g_id| g_id + first_grid));
+        self.no_phantoms
+            .extend(other.no_phantoms.iter().map(|g_id| g_id + first_grid));
+
+        // Map the identifiers of the helices of other to the identifiers of the helices of self
+        let mut helix_ids: HashMap<usize, usize> = HashMap::new();
+        let mut new_id = self.helices.keys().max().map(|m| m + 1).unwrap_or(0);
+        let mut helices = BTreeMap::clone(self.helices.as_ref());
+        for (h_id, helix) in other.helices.iter() {
+            let mut helix = Helix::clone(helix.as_ref());
+            helix.position += offset;
+            if let Some(grid_position) = helix.grid_position.as_mut() {
+                grid_position.grid += first_grid;
+            }
+            // The 2d position of the helix will be chosen by the flatscene
+            helix.isometry2d = None;
+            helices.insert(new_id, Arc::new(helix));
+            helix_ids.insert(*h_id, new_id);
+            new_id += 1;
+        }
+        self.helices = Arc::new(helices);
+        let new_nucl = |nucl: &Nucl| {
+            helix_ids.get(&nucl.helix).map(|helix| Nucl {
+                helix: *helix,
+                ..*nucl
+            })
+        };
+
+        let mut new_id = self.strands.keys().max().map(|m| m + 1).unwrap_or(0);
+        let adopt_scaffold = self.scaffold_id.is_none();
+        for (s_id, mut strand) in other.strands.into_iter() {
+            for domain in strand.domains.iter_mut() {
+                if let Domain::HelixDomain(interval) = domain {
+                    if let Some(helix) = helix_ids.get(&interval.helix) {
+                        interval.helix = *helix;
+                    }
+                }
+            }
+            strand.domains = sanitize_domains(&strand.domains, strand.cyclic);
+            strand.junctions = read_junctions(&strand.domains, strand.cyclic);
+            if adopt_scaffold && other.scaffold_id == Some(s_id) {
+                self.scaffold_id = Some(new_id);
+                self.scaffold_sequence = other.scaffold_sequence.clone();
+                self.scaffold_shift = other.scaffold_shift;
+            }
+            self.strands.insert(new_id, strand);
+            new_id += 1;
+        }
+
+        self.anchors
+            .extend(other.anchors.iter().filter_map(new_nucl));
+        for annotation in other.annotations.into_iter() {
+            if let Some(nucl) = new_nucl(&annotation.nucl) {
+                self.annotate_nucleotide(nucl, annotation.label);
+            }
+        }
+    }
+
+    /// Rotate the helices and the grids of self by `rotation` around the origin
+    pub fn rotate(&mut self, rotation: Rotor3) {
+        let mut grids = Vec::clone(self.grids.as_ref());
+        for grid in grids.iter_mut() {
+            grid.position = rotation * grid.position;
+            grid.orientation = rotation * grid.orientation;
+        }
+        self.grids = Arc::new(grids);
+        let mut helices = BTreeMap::clone(self.helices.as_ref());
+        for helix in helices.values_mut() {
+            let helix = Arc::make_mut(helix);
+            helix.position = rotation * helix.position;
+            helix.orientation = rotation * helix.orientation;
+        }
+        self.helices = Arc::new(helices);
+    }
+}
+
+/// Split the domains of `strand` into maximal sequences of consecutive domains that lie on
+/// `helices`. Return `None` if the whole strand lies on `helices`.
+fn split_on_helices(strand: &Strand, helices: &BTreeSet<usize>) -> Option<Vec<Vec<Domain>>> {
+    let is_kept = |domain: &Domain| match domain {
+        Domain::HelixDomain(interval) => helices.contains(&interval.helix),
+        Domain::Insertion(_) => true,
+    };
+    let first_removed = strand.domains.iter().position(|d| !is_kept(d))?;
+    // Start right after a removed domain so that the piece that goes through the origin of a
+    // cyclic strand is not cut in two.
+    let domains: Vec<&Domain> = if strand.cyclic {
+        strand.domains[first_removed..]
+            .iter()
+            .chain(strand.domains[..first_removed].iter())
+            .collect()
+    } else {
+        strand.domains.iter().collect()
+    };
+    let mut ret = Vec::new();
+    let mut current = Vec::new();
+    for domain in domains {
+        if is_kept(domain) {
+            current.push(domain.clone());
+        } else if !current.is_empty() {
+            ret.push(std::mem::take(&mut current));
+        }
+    }
+    if !current.is_empty() {
+        ret.push(current);
+    }
+    // Pieces made only of insertions are not attached to any helix
+    ret.retain(|domains| domains.iter().any(|d| matches!(d, Domain::HelixDomain(_))));
+    Some(ret)
+}
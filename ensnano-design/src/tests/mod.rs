@@ -117,6 +117,48 @@ fn scadnano_import_one_loopout() {
     assert_good_strand(&strand, "[H0: 8 -> 15] [@5] [H1: 8 <- 15]")
 }
 
+#[test]
+fn scadnano_import_scaffold_and_modifications() {
+    let input = r##" {
+  "version": "0.15.0",
+  "grid": "square",
+  "helices": [
+    {"grid_position": [0, 0]}
+  ],
+  "modifications_in_design": {
+    "/5Biosg/": {"display_text": "B", "idt_text": "/5Biosg/", "location": "5'"}
+  },
+  "strands": [
+    {
+      "color": "#cc0000",
+      "name": "biotinylated",
+      "5prime_modification": "/5Biosg/",
+      "domains": [
+        {"helix": 0, "forward": false, "start": 0, "end": 16}
+      ]
+    },
+    {
+      "is_scaffold": true,
+      "color": "#0066cc",
+      "domains": [
+        {"helix": 0, "forward": true, "start": 0, "end": 16}
+      ]
+    }
+  ]
+      }"##;
+    let scadnano_design: super::scadnano::ScadnanoDesign =
+        serde_json::from_str(&input).expect("Failed to parse scadnano input");
+    let ensnano_design = Design::from_scadnano(&scadnano_design)
+        .ok()
+        .expect("Could not convert to ensnano");
+    assert_eq!(ensnano_design.scaffold_id, Some(1));
+    let staple = &ensnano_design.strands[&0];
+    assert_eq!(staple.color, 0xFF_CC_00_00);
+    assert_eq!(staple.name.as_deref(), Some("biotinylated"));
+    assert_eq!(staple.modification_5prime, Some(StrandModification::Biotin));
+    assert_eq!(staple.modification_3prime, None);
+}
+
 fn assert_good_strand<S: std::ops::Deref<Target = str>>(strand: &Strand, objective: S) {
     use regex::Regex;
     let re = Regex::new(r#"\[[^\]]*\]"#).unwrap();
@@ -647,3 +689,485 @@ fn check_formated_strand_with_insertion() {
     let strand = strand_with_insertion();
     assert_good_strand(&strand, formated_strand_with_insertion())
 }
+
+fn design_with_strands(strands: Vec<Strand>) -> Design {
+    let mut design = Design::new();
+    for (s_id, strand) in strands.into_iter().enumerate() {
+        design.strands.insert(s_id, strand);
+    }
+    design
+}
+
+fn strand_on_interval(helix: usize, start: isize, end: isize, forward: bool) -> Strand {
+    let mut strand = Strand::init(helix, start, forward, 0);
+    strand.domains = vec![Domain::HelixDomain(HelixInterval {
+        helix,
+        start,
+        end,
+        forward,
+        sequence: None,
+    })];
+    strand
+}
+
+#[test]
+fn shortest_path_uses_watson_crick_pairs() {
+    let design = design_with_strands(vec![
+        strand_on_interval(0, 0, 10, true),
+        strand_on_interval(0, 0, 10, false),
+    ]);
+    let from = Nucl::new(0, 0, true);
+    let to = Nucl::new(0, 9, true);
+    let path = design.find_shortest_path(from, to).unwrap();
+    assert_eq!(path.len(), 10);
+    assert_eq!(path.first(), Some(&from));
+    assert_eq!(path.last(), Some(&to));
+
+    let path = design
+        .find_shortest_path(from, Nucl::new(0, 5, false))
+        .unwrap();
+    assert_eq!(path.len(), 7);
+}
+
+#[test]
+fn shortest_path_between_disconnected_nucleotides() {
+    let design = design_with_strands(vec![
+        strand_on_interval(0, 0, 10, true),
+        strand_on_interval(1, 0, 10, true),
+    ]);
+    let from = Nucl::new(0, 0, true);
+    assert!(design
+        .find_shortest_path(from, Nucl::new(1, 0, true))
+        .is_none());
+    assert!(design
+        .find_shortest_path(from, Nucl::new(0, 20, true))
+        .is_none());
+}
+
+#[test]
+fn annotations_are_replaced_and_removed() {
+    let mut design = Design::new();
+    let nucl = Nucl::new(0, 3, true);
+    design.annotate_nucleotide(nucl, "first".to_owned());
+    design.annotate_nucleotide(nucl, "second".to_owned());
+    assert_eq!(design.annotations.len(), 1);
+    assert_eq!(design.get_annotation(&nucl), Some("second"));
+
+    let json = serde_json::to_string(&design).unwrap();
+    let read: Design = serde_json::from_str(&json).unwrap();
+    assert_eq!(read.get_annotation(&nucl), Some("second"));
+
+    design.annotate_nucleotide(nucl, String::new());
+    assert!(design.annotations.is_empty());
+}
+
+#[test]
+fn torus_knot_helices_form_a_closed_chain() {
+    let knot = TorusKnot {
+        p: 2,
+        q: 3,
+        radius: 20.,
+        tube_radius: 8.,
+    };
+    let parameters = Parameters::DEFAULT;
+    let helices = knot.to_helices(&parameters);
+    assert!(helices.len() >= 3);
+    for (i, (helix, length)) in helices.iter().enumerate() {
+        let (next, _) = &helices[(i + 1) % helices.len()];
+        let end = helix.axis_position(&parameters, *length as isize);
+        assert!((end - next.position).mag() < parameters.z_step);
+    }
+}
+
+#[test]
+fn scaffold_domains_are_identified_on_both_strands() {
+    let mut scaffold = strand_on_interval(0, 0, 10, true);
+    scaffold.domains.push(Domain::HelixDomain(HelixInterval {
+        helix: 1,
+        start: 0,
+        end: 10,
+        forward: false,
+        sequence: None,
+    }));
+    let mut design = design_with_strands(vec![scaffold, strand_on_interval(1, 0, 5, true)]);
+    assert!(design
+        .get_scaffold_domain_id(&Nucl::new(0, 3, true))
+        .is_none());
+
+    design.scaffold_id = Some(0);
+    assert_eq!(design.scaffold_domains().len(), 2);
+    assert_eq!(
+        design.get_scaffold_domain_id(&Nucl::new(0, 3, true)),
+        Some(0)
+    );
+    assert_eq!(
+        design.get_scaffold_domain_id(&Nucl::new(1, 3, true)),
+        Some(1)
+    );
+    assert_eq!(design.get_scaffold_domain_id(&Nucl::new(1, 10, true)), None);
+
+    let nucls = design.get_scaffold_domain_nucls(1).unwrap();
+    assert_eq!(nucls.len(), 20);
+    assert!(nucls.contains(&Nucl::new(1, 4, true)));
+    assert!(design.get_scaffold_domain_nucls(2).is_none());
+}
+
+fn scaffold_with_domains(domains: Vec<Domain>, cyclic: bool) -> Design {
+    let mut scaffold = Strand::init(0, 0, true, 0);
+    scaffold.junctions = read_junctions(&domains, cyclic);
+    scaffold.domains = domains;
+    scaffold.cyclic = cyclic;
+    let mut design = design_with_strands(vec![scaffold]);
+    design.scaffold_id = Some(0);
+    design
+}
+
+fn two_helices_loop() -> Vec<Domain> {
+    vec![
+        Domain::HelixDomain(HelixInterval {
+            helix: 0,
+            start: 0,
+            end: 10,
+            forward: true,
+            sequence: None,
+        }),
+        Domain::HelixDomain(HelixInterval {
+            helix: 1,
+            start: 0,
+            end: 10,
+            forward: false,
+            sequence: None,
+        }),
+    ]
+}
+
+#[test]
+fn cyclic_scaffold_is_continuous() {
+    let design = scaffold_with_domains(two_helices_loop(), true);
+    assert_eq!(
+        check_scaffold_continuity(&design),
+        ScaffoldContinuityResult::SingleCycle
+    );
+    assert!(design.validate().is_empty());
+}
+
+#[test]
+fn linear_scaffold_is_a_single_path() {
+    let design = scaffold_with_domains(two_helices_loop(), false);
+    assert_eq!(
+        check_scaffold_continuity(&design),
+        ScaffoldContinuityResult::SinglePath
+    );
+    assert!(design.validate().is_empty());
+}
+
+#[test]
+fn linear_scaffold_with_a_gap_is_broken() {
+    let mut design = scaffold_with_domains(two_helices_loop(), false);
+    let scaffold = design.strands.get_mut(&0).unwrap();
+    scaffold.junctions[0] = DomainJunction::Prime3;
+    let expected_break = ScaffoldBreak {
+        prime3: Nucl::new(0, 9, true),
+        prime5: Nucl::new(1, 9, false),
+    };
+    assert_eq!(
+        check_scaffold_continuity(&design),
+        ScaffoldContinuityResult::Broken(vec![expected_break])
+    );
+    assert_eq!(
+        design.validate(),
+        vec![DesignIssue::ScaffoldNotSingleCycle(vec![expected_break])]
+    );
+}
+
+#[test]
+fn adjacent_junction_between_distant_nucleotides_is_a_break() {
+    let mut design = scaffold_with_domains(two_helices_loop(), true);
+    let scaffold = design.strands.get_mut(&0).unwrap();
+    scaffold.junctions[0] = DomainJunction::Adjacent;
+    assert_eq!(
+        check_scaffold_continuity(&design),
+        ScaffoldContinuityResult::Broken(vec![ScaffoldBreak {
+            prime3: Nucl::new(0, 9, true),
+            prime5: Nucl::new(1, 9, false),
+        }])
+    );
+}
+
+#[test]
+fn no_scaffold_continuity_without_scaffold() {
+    let design = design_with_strands(vec![strand_on_interval(0, 0, 10, true)]);
+    assert_eq!(
+        check_scaffold_continuity(&design),
+        ScaffoldContinuityResult::NoScaffold
+    );
+    assert!(design.validate().is_empty());
+}
+
+#[test]
+fn melting_temperature_increases_with_gc_content() {
+    let at_rich = melting_temperature("ATTATAATTAAATTAT").unwrap();
+    let gc_rich = melting_temperature("GCCGGCGCCGGGCGCC").unwrap();
+    assert!(gc_rich > at_rich);
+    assert_eq!(
+        melting_temperature("ATTA ATTA"),
+        melting_temperature("ATTAATTA")
+    );
+    assert!(melting_temperature("ATT?").is_none());
+    assert!(melting_temperature("A").is_none());
+    assert_eq!(
+        melting_temperature("attaatta"),
+        melting_temperature("ATTAATTA")
+    );
+}
+
+#[test]
+fn melting_temperature_increases_with_length() {
+    let short = melting_temperature("ACGTTGCA").unwrap();
+    let long = melting_temperature("ACGTTGCAACGTTGCA").unwrap();
+    assert!(long > short);
+}
+
+#[test]
+fn staple_pools_are_sorted_by_decreasing_melting_temperature() {
+    let mut design = design_with_strands(vec![
+        strand_on_interval(0, 0, 24, true),
+        strand_on_interval(0, 0, 8, false),
+        strand_on_interval(0, 8, 16, false),
+        strand_on_interval(0, 16, 24, false),
+    ]);
+    design.scaffold_id = Some(0);
+    design.scaffold_sequence = Some("AAAAAAAAGGGGGGGGAGAGAGAG".to_owned());
+    let sequences = design.get_staple_sequences();
+    assert_eq!(sequences[&1], "TTTTTTTT");
+    assert_eq!(sequences[&2], "CCCCCCCC");
+
+    assert_eq!(
+        design.generate_staple_pools(3),
+        vec![vec![2], vec![3], vec![1]]
+    );
+    assert_eq!(design.generate_staple_pools(2), vec![vec![2], vec![3, 1]]);
+    assert_eq!(
+        design.generate_staple_pools(5),
+        vec![vec![2], vec![3], vec![1]]
+    );
+}
+
+#[test]
+fn staple_sequences_include_insertions() {
+    let mut staple = strand_on_interval(0, 4, 8, false);
+    staple.domains.push(Domain::Insertion(2));
+    staple.domains.push(Domain::HelixDomain(HelixInterval {
+        helix: 0,
+        start: 0,
+        end: 4,
+        forward: false,
+        sequence: None,
+    }));
+    let mut design = design_with_strands(vec![strand_on_interval(0, 0, 8, true), staple]);
+    design.scaffold_id = Some(0);
+    design.scaffold_sequence = Some("AAAAGGGG".to_owned());
+    assert_eq!(design.get_staple_sequences()[&1], "CCCC??TTTT");
+
+    design.strands.get_mut(&1).unwrap().sequence = Some("CCCCAGTTTT".into());
+    assert_eq!(design.get_staple_sequences()[&1], "CCCCAGTTTT");
+}
+
+#[test]
+fn sub_design_cuts_strands_leaving_the_selected_helices() {
+    let mut staple = strand_on_interval(0, 0, 8, true);
+    staple.domains.push(Domain::HelixDomain(HelixInterval {
+        helix: 1,
+        start: 0,
+        end: 8,
+        forward: false,
+        sequence: None,
+    }));
+    let mut design = design_with_strands(vec![
+        strand_on_interval(0, 0, 8, false),
+        staple,
+        strand_on_interval(1, 0, 8, true),
+    ]);
+    let mut helices = BTreeMap::new();
+    helices.insert(0, Arc::new(Helix::new(Vec3::zero(), Rotor3::identity())));
+    helices.insert(1, Arc::new(Helix::new(Vec3::unit_y(), Rotor3::identity())));
+    design.helices = Arc::new(helices);
+    design.scaffold_id = Some(0);
+
+    let module = design.sub_design(&[0]);
+    assert_eq!(module.helices.keys().collect::<Vec<_>>(), vec![&0]);
+    assert_eq!(module.strands.len(), 2);
+    assert!(module
+        .strands
+        .values()
+        .all(|s| s.domains.len() == 1 && s.length() == 8));
+    assert_eq!(module.scaffold_id, Some(0));
+}
+
+#[test]
+fn merged_design_elements_get_new_identifiers() {
+    let mut design = design_with_strands(vec![strand_on_interval(0, 0, 8, true)]);
+    let mut helices = BTreeMap::new();
+    helices.insert(0, Arc::new(Helix::new(Vec3::zero(), Rotor3::identity())));
+    design.helices = Arc::new(helices);
+    let module = design.sub_design(&[0]);
+
+    let offset = Vec3::new(10., 0., 0.);
+    design.merge(module, offset);
+    assert_eq!(design.helices.len(), 2);
+    assert_eq!(design.strands.len(), 2);
+    assert_eq!(design.helices[&1].position, offset);
+    assert_eq!(design.strands[&1].get_5prime(), Some(Nucl::new(1, 0, true)));
+}
+
+#[test]
+fn rotated_design_keeps_its_shape() {
+    let mut design = Design::new();
+    let mut helices = BTreeMap::new();
+    helices.insert(0, Arc::new(Helix::new(Vec3::unit_x(), Rotor3::identity())));
+    design.helices = Arc::new(helices);
+
+    let rotation = Rotor3::from_rotation_xy(std::f32::consts::FRAC_PI_2);
+    design.rotate(rotation);
+    let helix = &design.helices[&0];
+    assert!((helix.position - rotation * Vec3::unit_x()).mag() < 1e-5);
+    assert!((helix.orientation * Vec3::unit_x() - rotation * Vec3::unit_x()).mag() < 1e-5);
+}
+
+#[test]
+fn self_complementary_sequence_forms_a_hairpin() {
+    let sequence = "TTGCGCAGTTTTCTGCGCAATT";
+    let stem = predict_longest_stem(sequence).unwrap();
+    assert!(stem.length >= 5);
+    let (prime5, prime3) = stem.arms(sequence);
+    let reverse_complement: String = prime3
+        .chars()
+        .rev()
+        .map(|c| staple_pools::compl(c).unwrap())
+        .collect();
+    assert_eq!(prime5, reverse_complement);
+}
+
+#[test]
+fn no_hairpin_without_complementarity() {
+    assert!(predict_longest_stem("AAAAAAAAAAAAAAAAAAAA").is_none());
+    assert!(predict_longest_stem("ATTG?CAAT").is_none());
+}
+
+#[test]
+fn helices_placed_on_a_grid_take_its_twist_offset() {
+    let descriptor = GridDescriptor {
+        position: Vec3::zero(),
+        orientation: ultraviolet::Rotor3::identity(),
+        grid_type: grid::GridTypeDescr::Honeycomb,
+        invisible: false,
+        twist_offset: std::f32::consts::FRAC_PI_3,
+    };
+    let grid = descriptor.to_grid(Parameters::DEFAULT);
+    let helix = Helix::new_on_grid(&grid, 1, 0, 0);
+    assert!((helix.roll - std::f32::consts::FRAC_PI_3).abs() < 1e-6);
+    assert_eq!(grid.desc().twist_offset, descriptor.twist_offset);
+}
+
+#[test]
+fn hairpin_stem_comes_back_on_the_same_nucleotides() {
+    let mut strand = strand_on_interval(0, 0, 4, true);
+    strand.sequence = Some("AAAAGGCT".into());
+    assert!(strand.add_hairpin(4, 3));
+    assert_eq!(strand.length(), 4 + 4 + 3 + 4);
+    assert_eq!(strand.get_3prime(), Some(Nucl::new(0, 4, false)));
+    assert!(matches!(strand.domains[1], Domain::Insertion(3)));
+    assert_eq!(
+        strand.sequence.as_deref(),
+        Some(concat!("AAAA", "GGCT", "TTT", "AGCC"))
+    );
+    let mut cyclic = strand_on_interval(0, 0, 4, true);
+    cyclic.cyclic = true;
+    assert!(!cyclic.add_hairpin(4, 3));
+}
+
+#[test]
+fn orphans_are_removed_with_their_references() {
+    let mut design = design_with_strands(vec![
+        strand_on_interval(0, 0, 8, true),
+        strand_on_interval(1, 4, 4, true),
+    ]);
+    let mut helices = BTreeMap::new();
+    helices.insert(0, Arc::new(Helix::new(Vec3::zero(), Rotor3::identity())));
+    helices.insert(1, Arc::new(Helix::new(Vec3::unit_y(), Rotor3::identity())));
+    helices.insert(2, Arc::new(Helix::new(Vec3::unit_z(), Rotor3::identity())));
+    design.helices = Arc::new(helices);
+    design.scaffold_id = Some(1);
+    design.anchors.insert(Nucl::new(2, 0, true));
+    design.anchors.insert(Nucl::new(0, 0, true));
+
+    let removed = design.remove_orphans();
+    assert_eq!(removed.strands, vec![1]);
+    assert_eq!(removed.helices, vec![1, 2]);
+    assert_eq!(design.helices.keys().collect::<Vec<_>>(), vec![&0]);
+    assert_eq!(design.strands.keys().collect::<Vec<_>>(), vec![&0]);
+    assert_eq!(design.scaffold_id, None);
+    assert_eq!(design.anchors.len(), 1);
+    assert!(design.orphans().is_empty());
+}
+
+#[test]
+fn number_of_cameras_is_bounded() {
+    let mut design = Design::new();
+    for _ in 0..MAX_NB_CAMERAS {
+        assert!(design.add_camera(Vec3::zero(), Rotor3::identity()).is_ok());
+    }
+    assert_eq!(
+        design.add_camera(Vec3::zero(), Rotor3::identity()),
+        Err(TooManyCameras)
+    );
+    let first_camera = *design.get_cameras().next().unwrap().0;
+    design.rm_camera(first_camera).unwrap();
+    assert!(design.add_camera(Vec3::zero(), Rotor3::identity()).is_ok());
+}
+
+#[test]
+fn edit_history_is_saved_in_the_design_file() {
+    let mut oldest = Design::new();
+    oldest.scaffold_shift = Some(3);
+    oldest.scaffold_sequence = Some(String::from("ATTA"));
+    let mut previous = oldest.clone();
+    previous.scaffold_shift = Some(5);
+    let mut design = previous.clone();
+    design.scaffold_sequence = None;
+    design
+        .prepare_for_save(SavingInformation {
+            camera: None,
+            edit_history: vec![oldest, previous],
+        })
+        .unwrap();
+    let json = serde_json::to_string(&design).unwrap();
+    let mut read: Design = serde_json::from_str(&json).unwrap();
+    let history = read.take_edit_history().unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].scaffold_shift, Some(3));
+    assert_eq!(history[0].scaffold_sequence.as_deref(), Some("ATTA"));
+    assert_eq!(history[1].scaffold_shift, Some(5));
+    assert_eq!(history[1].scaffold_sequence.as_deref(), Some("ATTA"));
+    assert!(read.take_edit_history().unwrap().is_empty());
+}
+
+#[test]
+fn edit_history_only_stores_modified_fields() {
+    let previous = Design::new();
+    let mut design = previous.clone();
+    design.scaffold_shift = Some(3);
+    design
+        .prepare_for_save(SavingInformation {
+            camera: None,
+            edit_history: vec![previous],
+        })
+        .unwrap();
+    let json = serde_json::to_value(&design).unwrap();
+    let mut fields = BTreeMap::new();
+    fields.insert(String::from("scaffold_shift"), None);
+    assert_eq!(
+        json["edit_history"],
+        serde_json::to_value(vec![JsonPatch::Fields(fields)]).unwrap()
+    );
+}
@@ -0,0 +1,86 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! Torus knots are closed curves drawn on the surface of a torus. Since helices are straight, a
+//! torus knot is approximated by a closed chain of short helices.
+
+use super::{Helix, Parameters};
+use std::f32::consts::PI;
+use ultraviolet::{Rotor3, Vec3};
+
+/// The number of nucleotides on each helix of the chain approximating the knot
+const NUCL_PER_SEGMENT: usize = 8;
+/// The number of points used to approximate the length of the knot
+const LENGTH_SAMPLES: usize = 1000;
+
+/// A (p, q) torus knot. The knot winds `p` times around the axis of the torus and `q` times
+/// around the circle at the center of the torus's tube.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TorusKnot {
+    pub p: u32,
+    pub q: u32,
+    /// The distance between the center of the torus and the center of its tube, in nanometers
+    pub radius: f32,
+    /// The radius of the tube of the torus, in nanometers
+    pub tube_radius: f32,
+}
+
+impl TorusKnot {
+    /// The point of the knot at parameter `t`, where `t` goes from 0 to 2π.
+    pub fn point(&self, t: f32) -> Vec3 {
+        let p = self.p as f32;
+        let q = self.q as f32;
+        let r = self.radius + self.tube_radius * (q * t).cos();
+        Vec3::new(
+            r * (p * t).cos(),
+            r * (p * t).sin(),
+            self.tube_radius * (q * t).sin(),
+        )
+    }
+
+    /// An approximation of the length of the knot
+    pub fn length(&self) -> f32 {
+        (0..LENGTH_SAMPLES)
+            .map(|i| {
+                let t0 = 2. * PI * i as f32 / LENGTH_SAMPLES as f32;
+                let t1 = 2. * PI * (i + 1) as f32 / LENGTH_SAMPLES as f32;
+                (self.point(t1) - self.point(t0)).mag()
+            })
+            .sum()
+    }
+
+    /// Return the chain of helices approximating the knot, together with the number of
+    /// nucleotides on each of them.
+    ///
+    /// Each helix starts at the end of the previous one, and the end of the last helix is the
+    /// start of the first one. Nucleotides are spaced by the rise of the `parameters`.
+    pub fn to_helices(&self, parameters: &Parameters) -> Vec<(Helix, usize)> {
+        let nb_nucl = (self.length() / parameters.z_step).round() as usize;
+        let nb_segments = (nb_nucl / NUCL_PER_SEGMENT).max(3);
+        let points: Vec<Vec3> = (0..nb_segments)
+            .map(|k| self.point(2. * PI * k as f32 / nb_segments as f32))
+            .collect();
+        let mut ret = Vec::with_capacity(nb_segments);
+        for (k, origin) in points.iter().enumerate() {
+            let direction = points[(k + 1) % nb_segments] - *origin;
+            let length = ((direction.mag() / parameters.z_step).round() as usize).max(1);
+            let orientation = Rotor3::from_rotation_between(Vec3::unit_x(), direction.normalized());
+            ret.push((Helix::new(*origin, orientation), length));
+        }
+        ret
+    }
+}
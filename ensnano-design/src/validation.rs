@@ -0,0 +1,140 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! Checks performed on a design before it is sent to production.
+
+use super::{Design, Domain, DomainJunction, HelixInterval, Nucl};
+
+/// A problem in a design that prevents it from being produced
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DesignIssue {
+    /// The backbone of the scaffold is interrupted
+    ScaffoldNotSingleCycle(Vec<ScaffoldBreak>),
+}
+
+/// A missing bond in the scaffold: the backbone of the scaffold should go from `prime3` to
+/// `prime5`, but these two nucleotides are not linked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScaffoldBreak {
+    pub prime3: Nucl,
+    pub prime5: Nucl,
+}
+
+/// The outcome of `check_scaffold_continuity`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScaffoldContinuityResult {
+    /// No strand is set as the scaffold, or the scaffold has no nucleotide on a helix
+    NoScaffold,
+    /// The backbone of the scaffold is a single closed loop
+    SingleCycle,
+    /// The scaffold is linear and its backbone goes without interruption from its 5' end to its
+    /// 3' end
+    SinglePath,
+    /// The backbone of the scaffold is interrupted at each of the given points
+    Broken(Vec<ScaffoldBreak>),
+}
+
+/// Follow the backbone of the scaffold, from its 5' end to its 3' end, and check that it forms a
+/// single cycle, or a single path if the scaffold is linear.
+///
+/// The backbone is interrupted if two consecutive domains are declared as adjacent without being
+/// next to each other on the same helix. Insertions do not interrupt the backbone.
+pub fn check_scaffold_continuity(design: &Design) -> ScaffoldContinuityResult {
+    let scaffold = if let Some(scaffold) = design
+        .scaffold_id
+        .and_then(|s_id| design.strands.get(&s_id))
+    {
+        scaffold
+    } else {
+        return ScaffoldContinuityResult::NoScaffold;
+    };
+
+    // The helix domains of the scaffold, with the junction that links each of them to its 5'
+    // neighbour. The junction following an insertion is the junction between the two helix
+    // domains surrounding it.
+    let mut intervals: Vec<(&HelixInterval, Option<&DomainJunction>)> = Vec::new();
+    for (d_id, domain) in scaffold.domains.iter().enumerate() {
+        if let Domain::HelixDomain(interval) = domain {
+            let junction = d_id
+                .checked_sub(1)
+                .and_then(|prev_id| scaffold.junctions.get(prev_id));
+            intervals.push((interval, junction));
+        }
+    }
+    let first = if let Some((first, _)) = intervals.first() {
+        first
+    } else {
+        return ScaffoldContinuityResult::NoScaffold;
+    };
+
+    let mut breaks = Vec::new();
+    for pair in intervals.windows(2) {
+        let (prev, _) = pair[0];
+        let (next, junction) = pair[1];
+        if !is_bond(prev.prime3(), next.prime5(), junction) {
+            breaks.push(ScaffoldBreak {
+                prime3: prev.prime3(),
+                prime5: next.prime5(),
+            });
+        }
+    }
+
+    if scaffold.cyclic {
+        let (last, _) = intervals[intervals.len() - 1];
+        let closing_bond = ScaffoldBreak {
+            prime3: last.prime3(),
+            prime5: first.prime5(),
+        };
+        if !is_bond(
+            closing_bond.prime3,
+            closing_bond.prime5,
+            scaffold.junctions.last(),
+        ) {
+            breaks.push(closing_bond);
+        }
+    }
+
+    if !breaks.is_empty() {
+        ScaffoldContinuityResult::Broken(breaks)
+    } else if scaffold.cyclic {
+        ScaffoldContinuityResult::SingleCycle
+    } else {
+        ScaffoldContinuityResult::SinglePath
+    }
+}
+
+/// Return true if the junction between `prime3` and `prime5` is a valid bond of the backbone.
+fn is_bond(prime3: Nucl, prime5: Nucl, junction: Option<&DomainJunction>) -> bool {
+    match junction {
+        Some(DomainJunction::Prime3) => false,
+        Some(DomainJunction::Adjacent) => prime3.prime3() == prime5,
+        Some(DomainJunction::IdentifiedXover(_)) | Some(DomainJunction::UnindentifiedXover) => true,
+        // The junctions are not known, the two nucleotides are assumed to be linked
+        None => true,
+    }
+}
+
+impl Design {
+    /// Check that the design can be produced and return the problems that were found
+    pub fn validate(&self) -> Vec<DesignIssue> {
+        let mut ret = Vec::new();
+        if let ScaffoldContinuityResult::Broken(breaks) = check_scaffold_continuity(self) {
+            ret.push(DesignIssue::ScaffoldNotSingleCycle(breaks));
+        }
+        ret
+    }
+}
@@ -17,11 +17,12 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 */
 
 use super::graphics::*;
-use super::Selection;
+use super::{MirrorAxis, Selection};
 use ensnano_design::group_attributes::GroupPivot;
 use ensnano_design::Nucl;
 use iced_wgpu::wgpu;
 use iced_winit::winit;
+use std::path::PathBuf;
 use std::time::Duration;
 use ultraviolet::{Rotor3, Vec3};
 use winit::{
@@ -57,11 +58,75 @@ pub trait Application {
     fn get_camera(&self) -> Option<(Vec3, Rotor3)> {
         None
     }
+
+    /// The vertical field of view, in radians, of the camera of the application
+    fn get_fovy(&self) -> Option<f32> {
+        None
+    }
     fn get_current_selection_pivot(&self) -> Option<GroupPivot> {
         None
     }
 
+    /// Write a PNG image of the helices and strands of the application. The image measures
+    /// `size_mm` millimeters at a resolution of `dpi` dots per inch.
+    fn export_helix_map_png(
+        &mut self,
+        _dpi: u32,
+        _size_mm: (f32, f32),
+        _path: &PathBuf,
+    ) -> std::io::Result<()> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            "This view cannot export a helix map",
+        ))
+    }
+
+    /// Write a PNG image of `size` pixels of the application, as seen from its current point of
+    /// view.
+    fn export_scene_png(&mut self, _size: (u32, u32), _path: &PathBuf) -> std::io::Result<()> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            "This view cannot export a screenshot",
+        ))
+    }
+
+    /// The positions and orientations of the point of view of the application at each of the
+    /// `nb_frames` frames of `animation`
+    fn animation_viewpoints(
+        &self,
+        _animation: &CameraAnimation,
+        _nb_frames: u32,
+    ) -> Vec<(Vec3, Rotor3)> {
+        Vec::new()
+    }
+
+    /// Render an image of `size` pixels of the application, as seen from `viewpoint`, and return
+    /// its RGBA pixels. The point of view of the application is not modified.
+    fn render_viewpoint(
+        &mut self,
+        _viewpoint: (Vec3, Rotor3),
+        _size: (u32, u32),
+    ) -> std::io::Result<Vec<u8>> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            "This view cannot record an animation",
+        ))
+    }
+
+    /// Write a triangle mesh of the 3D representation of the designs in the OBJ format
+    fn export_mesh(&mut self, _path: &PathBuf) -> std::io::Result<()> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            "This view cannot export a mesh",
+        ))
+    }
+
     fn is_splited(&self) -> bool;
+
+    /// Return true if the application must receive the keyboard events before the key bindings
+    fn has_keyboard_priority(&self) -> bool {
+        false
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -79,18 +144,67 @@ pub enum Notification {
     /// The 3d camera must face a given target
     CameraTarget((Vec3, Vec3)),
     TeleportCamera(Vec3, Rotor3),
+    /// The vertical field of view, in radians, of the 3D camera has been modified
+    CameraFovy(f32),
     CameraRotation(f32, f32, f32),
+    /// The 3D camera must turn by the given angles around its pivot point
+    TurnCamera(f32, f32),
     Centering(Nucl, usize),
     CenterSelection(Selection, AppId),
+    /// The 2D view must briefly highlight the cross-over between two nucleotides
+    FlashXover(Nucl, Nucl),
     ShowTorsion(bool),
+    /// The 3D view must color the nucleotides according to the local twist of their helix
+    ShowLocalTwist(bool),
+    /// The 3D view must color the nucleotides according to the result of an analysis of the
+    /// design
+    AnalysisColoring(AnalysisColoring),
+    /// The 3D view must show/hide the identifier of each helix
+    ShowHelixNumbers(bool),
+    /// The 2D view must show/hide the nucleotide ruler
+    ShowRuler(bool),
+    /// The periods and colors of the nucleotide ruler of the 2D view were modified
+    RulerParameters(RulerParameters),
+    /// The 2D view must show/hide the lattice grid
+    ShowLatticeGrid(bool),
     ModifersChanged(ModifiersState),
     Split2d,
     Redim2dHelices(bool),
     Background3D(Background3D),
+    /// The colors of the 3D view chosen by the user
+    ColorTheme(ColorTheme),
     RenderingMode(RenderingMode),
     Fog(FogParameters),
+    /// The fog of the 3D view must be centered on the position of the selected elements
+    CenterFogOnSelection,
+    /// The fog of the 3D view must follow the pivot again
+    ResetFogCenter,
+    DepthOfField(DepthOfFieldParameters),
+    /// The 3D camera must enter or leave the first-person mode
+    FlyThrough(FlyThroughParameters),
+    EyeDomeLighting(EyeDomeLightingParameters),
+    /// The ambient occlusion of the 3D scene has been modified
+    AmbientOcclusion(AmbientOcclusionParameters),
+    /// The quality of the post-processing passes of the 3D scene has been modified
+    PostProcessingQuality(PostProcessingQuality),
+    /// The light sources of the 3D scene have been modified
+    Lighting(LightingParameters),
+    ToneMapping(ToneMappingParameters),
+    /// The audio feedback of the 3D scene has been modified
+    Sonification(SonificationMode),
+    /// Show the plane across which a grid is about to be mirrored, or hide it if `None`
+    ShowMirrorPlane(Option<MirrorAxis>),
     WindowFocusLost,
+    /// The selection has been modified in the 3D view. The 2D view must scroll to show the
+    /// selected elements
+    Sync3dSelection(Vec<Selection>),
     FlipSplitViews,
+    /// The 2D view must let the user draw a cross-over by clicking on its two extremities
+    Start2dXoverTool,
+    /// The 3D camera must go back to its previous viewpoint
+    UndoCameraMove,
+    /// The 3D camera must go back to the viewpoint that it left by undoing a camera move
+    RedoCameraMove,
 }
 
 #[derive(PartialEq, Debug, Clone, Copy)]
@@ -17,7 +17,8 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 */
 
 use iced_winit::winit;
-use ultraviolet::Vec3;
+use serde_derive::{Deserialize, Serialize};
+use ultraviolet::{Rotor3, Vec3};
 use winit::dpi::{PhysicalPosition, PhysicalSize};
 #[derive(Clone, Debug, PartialEq, Eq, Copy)]
 pub enum RenderingMode {
@@ -50,7 +51,7 @@ impl Default for Background3D {
 impl std::fmt::Display for Background3D {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let ret = match self {
-            Self::White => "White",
+            Self::White => "Plain",
             Self::Sky => "Sky",
         };
         write!(f, "{}", ret)
@@ -94,6 +95,229 @@ impl FogParameters {
     }
 }
 
+/// Parameters of the depth of field effect of the 3D scene
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DepthOfFieldParameters {
+    /// Distance between the camera and the plane that is in focus
+    pub focal_distance: f32,
+    /// The larger the aperture, the more blurred the objects far from the focal plane are. An
+    /// aperture of 0 disables the effect.
+    pub aperture: f32,
+}
+
+impl DepthOfFieldParameters {
+    pub fn is_enabled(&self) -> bool {
+        self.aperture > 0.
+    }
+}
+
+impl Default for DepthOfFieldParameters {
+    fn default() -> Self {
+        Self {
+            focal_distance: 20.,
+            aperture: 0.,
+        }
+    }
+}
+
+/// Parameters of the first-person mode of the 3D camera, in which the keyboard moves the camera
+/// forward and sideways and dragging the mouse turns its head.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FlyThroughParameters {
+    pub enabled: bool,
+    /// The speed of the camera, in nanometers per second
+    pub speed: f32,
+}
+
+impl Default for FlyThroughParameters {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            speed: 10.,
+        }
+    }
+}
+
+/// The movement of the 3D camera during a recorded animation, as chosen in the GUI
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnimationKind {
+    /// A full revolution around the point that the camera is looking at
+    Turntable,
+    /// A path through the cameras stored in the design, in order
+    Keyframes,
+}
+
+pub const ALL_ANIMATION_KIND: [AnimationKind; 2] =
+    [AnimationKind::Turntable, AnimationKind::Keyframes];
+
+impl Default for AnimationKind {
+    fn default() -> Self {
+        Self::Turntable
+    }
+}
+
+impl std::fmt::Display for AnimationKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ret = match self {
+            Self::Turntable => "Turntable",
+            Self::Keyframes => "Stored cameras",
+        };
+        write!(f, "{}", ret)
+    }
+}
+
+/// Parameters of the recording of an animation of the 3D view
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnimationParameters {
+    pub kind: AnimationKind,
+    pub nb_frames: u32,
+    /// The width and height in pixels of the frames
+    pub size: (u32, u32),
+}
+
+/// The path followed by the 3D camera during a recorded animation
+#[derive(Debug, Clone, PartialEq)]
+pub enum CameraAnimation {
+    /// A full revolution around the point that the camera is looking at
+    Turntable,
+    /// A path through the given positions and orientations of the camera, in order
+    Keyframes(Vec<(Vec3, Rotor3)>),
+}
+
+/// Parameters of the screen-space ambient occlusion of the 3D scene, which darkens the creases
+/// and the cavities of the designs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AmbientOcclusionParameters {
+    /// The darkness of the occluded regions. A strength of 0 disables the effect.
+    pub strength: f32,
+    /// The distance, in nanometers, up to which the objects occlude each other
+    pub radius: f32,
+}
+
+impl AmbientOcclusionParameters {
+    pub fn is_enabled(&self) -> bool {
+        self.strength > 0.
+    }
+}
+
+impl Default for AmbientOcclusionParameters {
+    fn default() -> Self {
+        Self {
+            strength: 0.,
+            radius: 2.,
+        }
+    }
+}
+
+/// The number of samples read by the post-processing passes of the 3D scene. A higher quality
+/// gives smoother results but is slower to render.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PostProcessingQuality {
+    Low,
+    Medium,
+    High,
+}
+
+pub const ALL_POST_PROCESSING_QUALITY: [PostProcessingQuality; 3] = [
+    PostProcessingQuality::Low,
+    PostProcessingQuality::Medium,
+    PostProcessingQuality::High,
+];
+
+impl Default for PostProcessingQuality {
+    fn default() -> Self {
+        Self::Medium
+    }
+}
+
+impl std::fmt::Display for PostProcessingQuality {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ret = match self {
+            Self::Low => "Low",
+            Self::Medium => "Medium",
+            Self::High => "High",
+        };
+        write!(f, "{}", ret)
+    }
+}
+
+/// Parameters of the eye-dome lighting of the 3D scene, a shading that only depends on the depth
+/// of the pixels and makes the shapes easier to perceive in regions of uniform colour.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EyeDomeLightingParameters {
+    /// The contrast of the shading. A strength of 0 disables the effect.
+    pub strength: f32,
+    /// The distance, in pixels, at which the depth of the neighbours of a pixel is read
+    pub radius: f32,
+}
+
+impl EyeDomeLightingParameters {
+    pub fn is_enabled(&self) -> bool {
+        self.strength > 0.
+    }
+}
+
+impl Default for EyeDomeLightingParameters {
+    fn default() -> Self {
+        Self {
+            strength: 0.,
+            radius: 1.5,
+        }
+    }
+}
+
+/// The operator used to map the colors of the 3D scene to the range of the screen
+#[derive(Clone, Debug, PartialEq, Eq, Copy)]
+pub enum ToneMappingOperator {
+    /// The colors are simply clamped
+    Linear,
+    Reinhard,
+    AcesFilmic,
+    Uncharted2,
+}
+
+pub const ALL_TONE_MAPPING_OPERATOR: [ToneMappingOperator; 4] = [
+    ToneMappingOperator::Linear,
+    ToneMappingOperator::Reinhard,
+    ToneMappingOperator::AcesFilmic,
+    ToneMappingOperator::Uncharted2,
+];
+
+impl Default for ToneMappingOperator {
+    fn default() -> Self {
+        Self::Linear
+    }
+}
+
+impl std::fmt::Display for ToneMappingOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ret = match self {
+            Self::Linear => "Linear",
+            Self::Reinhard => "Reinhard",
+            Self::AcesFilmic => "ACES filmic",
+            Self::Uncharted2 => "Uncharted 2",
+        };
+        write!(f, "{}", ret)
+    }
+}
+
+/// Parameters of the tone mapping pass of the 3D scene
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ToneMappingParameters {
+    pub operator: ToneMappingOperator,
+    /// The colors of the scene are multiplied by this factor before being tone mapped
+    pub exposure: f32,
+}
+
+impl Default for ToneMappingParameters {
+    fn default() -> Self {
+        Self {
+            operator: Default::default(),
+            exposure: 1.,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum SplitMode {
     Flat,
@@ -147,3 +371,295 @@ impl ElementType {
         }
     }
 }
+
+/// The maximum number of light sources of the 3D scene
+pub const MAX_NB_LIGHTS: usize = 4;
+
+/// A directional light source of the 3D scene. The direction of the light is given relatively to
+/// the camera, so that the lighting of the scene does not change when the camera moves.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LightConfig {
+    /// Horizontal angle in radians between the direction of the light and the viewing axis
+    pub azimuth: f32,
+    /// Vertical angle in radians between the direction of the light and the viewing axis
+    pub elevation: f32,
+    /// The (r, g, b) colour of the light, each component being between 0 and 1
+    pub color: [f32; 3],
+    /// The intensity of the light. A light of intensity 0 is turned off.
+    pub intensity: f32,
+}
+
+impl LightConfig {
+    /// A white light coming from the camera
+    pub const HEADLIGHT: Self = Self {
+        azimuth: 0.,
+        elevation: 0.,
+        color: [1., 1., 1.],
+        intensity: 1.,
+    };
+
+    pub const OFF: Self = Self {
+        intensity: 0.,
+        ..Self::HEADLIGHT
+    };
+
+    /// The direction towards the light in the referential of the camera, where the camera looks
+    /// towards negative z.
+    pub fn direction_from_camera(&self) -> Vec3 {
+        Vec3::new(
+            self.elevation.cos() * self.azimuth.sin(),
+            self.elevation.sin(),
+            self.elevation.cos() * self.azimuth.cos(),
+        )
+    }
+}
+
+/// The light sources of the 3D scene
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LightingParameters {
+    pub lights: [LightConfig; MAX_NB_LIGHTS],
+}
+
+impl Default for LightingParameters {
+    fn default() -> Self {
+        Self {
+            lights: [
+                LightConfig::HEADLIGHT,
+                LightConfig::OFF,
+                LightConfig::OFF,
+                LightConfig::OFF,
+            ],
+        }
+    }
+}
+
+/// Parameters of the continuous tone that gives an audio feedback on the state of the 3D scene
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SonificationMode {
+    pub enabled: bool,
+    /// The volume of the tone, between 0 and 1, when the mapping gives it its maximal volume
+    pub volume: f32,
+    pub map: SonifyMapping,
+}
+
+impl Default for SonificationMode {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            volume: 0.5,
+            map: Default::default(),
+        }
+    }
+}
+
+/// The way the state of the scene is mapped to the pitch and to the volume of the tone. The
+/// number of nucleotides of the design gives the pitch, and the kinetic energy of the running
+/// simulation gives the volume.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SonifyMapping {
+    /// The frequency, in Hz, of the tone for an empty design
+    pub min_frequency: f32,
+    /// The frequency, in Hz, of the tone for a design of `max_nucleotides` nucleotides or more
+    pub max_frequency: f32,
+    pub max_nucleotides: usize,
+    /// The relative volume of the tone when no simulation is running
+    pub min_volume: f32,
+    /// The kinetic energy for which the tone reaches its maximal volume
+    pub max_kinetic_energy: f32,
+}
+
+impl Default for SonifyMapping {
+    fn default() -> Self {
+        Self {
+            min_frequency: 110.,
+            max_frequency: 880.,
+            max_nucleotides: 20_000,
+            min_volume: 0.2,
+            max_kinetic_energy: 1_000.,
+        }
+    }
+}
+
+impl SonifyMapping {
+    /// The frequency of the tone for a design of `nb_nucleotides` nucleotides. The frequency
+    /// grows logarithmically with the number of nucleotides.
+    pub fn frequency(&self, nb_nucleotides: usize) -> f32 {
+        let t = ((1 + nb_nucleotides) as f32).ln() / ((1 + self.max_nucleotides) as f32).ln();
+        self.min_frequency * (self.max_frequency / self.min_frequency).powf(t.clamp(0., 1.))
+    }
+
+    /// The relative volume, between `self.min_volume` and 1, of the tone when the simulated
+    /// system has kinetic energy `kinetic_energy`
+    pub fn volume(&self, kinetic_energy: Option<f32>) -> f32 {
+        let t = kinetic_energy
+            .map(|e| (e / self.max_kinetic_energy).clamp(0., 1.))
+            .unwrap_or(0.);
+        self.min_volume + (1. - self.min_volume) * t
+    }
+}
+
+/// The property of the nucleotides that determines their color in the 3D view
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColorBy {
+    /// The color of the strand of the nucleotide
+    Strand,
+    /// A color of the categorical palette of the theme, chosen according to the helix
+    Helix,
+    /// A color of the categorical palette of the theme, chosen according to the domain of the
+    /// strand
+    Domain,
+    /// A color going from blue to red as the GC content of the strand increases
+    GcContent,
+}
+
+pub const ALL_COLOR_BY: [ColorBy; 4] = [
+    ColorBy::Strand,
+    ColorBy::Helix,
+    ColorBy::Domain,
+    ColorBy::GcContent,
+];
+
+impl Default for ColorBy {
+    fn default() -> Self {
+        Self::Strand
+    }
+}
+
+impl std::fmt::Display for ColorBy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ret = match self {
+            Self::Strand => "Strand",
+            Self::Helix => "Helix",
+            Self::Domain => "Domain",
+            Self::GcContent => "GC content",
+        };
+        write!(f, "{}", ret)
+    }
+}
+
+/// An analysis of the design whose result is shown by coloring the nucleotides of the 3D view
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnalysisColoring {
+    /// No analysis is shown
+    None,
+    /// The number of cross-overs near each nucleotide of a helix
+    CrossoverDensity,
+    /// The length of the portion of helix without cross-over that contains each nucleotide
+    UncrossedLength,
+    /// The melting temperature of the domain of each nucleotide of the staples
+    MeltingTemperature,
+}
+
+pub const ALL_ANALYSIS_COLORING: [AnalysisColoring; 4] = [
+    AnalysisColoring::None,
+    AnalysisColoring::CrossoverDensity,
+    AnalysisColoring::UncrossedLength,
+    AnalysisColoring::MeltingTemperature,
+];
+
+impl Default for AnalysisColoring {
+    fn default() -> Self {
+        Self::None
+    }
+}
+
+impl std::fmt::Display for AnalysisColoring {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ret = match self {
+            Self::None => "None",
+            Self::CrossoverDensity => "Cross-over density",
+            Self::UncrossedLength => "Uncrossed length",
+            Self::MeltingTemperature => "Melting temperature",
+        };
+        write!(f, "{}", ret)
+    }
+}
+
+/// The colors chosen by the user for the 3D view. Colors are encoded as `0xRRGGBB`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ColorTheme {
+    /// The color of the selected elements
+    pub selection: u32,
+    /// The color of the elements under the mouse cursor
+    pub candidate: u32,
+    /// The color of the plain background
+    pub background: u32,
+    /// The colors given to the helices or domains when coloring by helix or by domain
+    pub categorical: Vec<u32>,
+    pub color_by: ColorBy,
+    /// If true, the scaffold is colored with a gradient going from its 5' end to its 3' end so
+    /// that its routing can be followed
+    pub scaffold_route: bool,
+}
+
+impl Default for ColorTheme {
+    fn default() -> Self {
+        Self {
+            selection: 0xFF_00_00,
+            candidate: 0x00_FF_00,
+            background: 0xFF_FF_FF,
+            categorical: vec![
+                0x1F77B4, 0xFF7F0E, 0x2CA02C, 0xD62728, 0x9467BD, 0x8C564B, 0xE377C2, 0x7F7F7F,
+                0xBCBD22, 0x17BECF,
+            ],
+            color_by: Default::default(),
+            scaffold_route: false,
+        }
+    }
+}
+
+impl ColorTheme {
+    /// The `idx`-th color of the categorical palette, repeating the palette if needed
+    pub fn categorical_color(&self, idx: usize) -> Option<u32> {
+        if self.categorical.is_empty() {
+            None
+        } else {
+            Some(self.categorical[idx % self.categorical.len()])
+        }
+    }
+}
+
+/// The parameters of the nucleotide ruler of the 2D view. The ruler tints the columns of the 2D
+/// helices whose position is a multiple of a given period. With the default parameters, every
+/// 10th nucleotide gets a faint bar and every 21st nucleotide (two half turns of B-DNA) gets a
+/// stronger one. Colors are encoded as `0xAARRGGBB`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RulerParameters {
+    /// Columns whose position is a multiple of `minor_period` are tinted with `minor_color`
+    pub minor_period: usize,
+    pub minor_color: u32,
+    /// Columns whose position is a multiple of `major_period` are tinted with `major_color`
+    pub major_period: usize,
+    pub major_color: u32,
+}
+
+impl Default for RulerParameters {
+    fn default() -> Self {
+        Self {
+            minor_period: 10,
+            minor_color: 0x30_80_80_80,
+            major_period: 21,
+            major_color: 0x60_60_60_60,
+        }
+    }
+}
+
+impl RulerParameters {
+    /// Return the color of the bar drawn on the column at `position`, or `None` if no bar
+    /// must be drawn there. Major bars are drawn over minor ones.
+    pub fn color_of_position(&self, position: isize) -> Option<u32> {
+        if is_multiple(position, self.major_period) {
+            Some(self.major_color)
+        } else if is_multiple(position, self.minor_period) {
+            Some(self.minor_color)
+        } else {
+            None
+        }
+    }
+}
+
+/// A period of 0 disables the corresponding bars
+fn is_multiple(position: isize, period: usize) -> bool {
+    period > 0 && position.rem_euclid(period as isize) == 0
+}
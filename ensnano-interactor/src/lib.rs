@@ -23,9 +23,11 @@ use ensnano_design::{
     elements::{DnaAttribute, DnaElementKey},
     grid::{GridDescriptor, GridPosition, Hyperboloid},
     group_attributes::GroupPivot,
-    Nucl,
+    Nucl, Parameters,
 };
-use ultraviolet::{Isometry2, Rotor3, Vec2, Vec3};
+use std::collections::HashMap;
+use std::sync::Arc;
+use ultraviolet::{Isometry2, Mat4, Rotor3, Vec2, Vec3};
 pub mod graphics;
 mod selection;
 pub use selection::*;
@@ -38,6 +40,8 @@ use ensnano_organizer::GroupId;
 
 #[macro_use]
 extern crate log;
+#[macro_use]
+extern crate serde_derive;
 
 #[derive(Clone, Copy, Eq, PartialEq)]
 pub enum ObjectType {
@@ -83,7 +87,7 @@ impl Referential {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 /// An operation that can be perorfed on a design
 pub enum DesignOperation {
     /// Rotate an element of the design
@@ -99,6 +103,8 @@ pub enum DesignOperation {
     RmHelices {
         h_ids: Vec<usize>,
     },
+    /// Remove the strands that have no nucleotide and the helices on which no nucleotide lies
+    CleanupOrphans,
     RmXovers {
         xovers: Vec<(Nucl, Nucl)>,
     },
@@ -108,6 +114,11 @@ pub enum DesignOperation {
         nucl: Nucl,
         s_id: usize,
     },
+    /// Remove the bond that precedes `at` in the cyclic scaffold, turning it into a linear strand
+    /// whose 5' end is `at`
+    BreakScaffoldAt {
+        at: Nucl,
+    },
     /// Make a cross-over between two nucleotides, spliting the source and target strands if needed
     GeneralXover {
         source: Nucl,
@@ -134,8 +145,19 @@ pub enum DesignOperation {
     AddGrid(GridDescriptor),
     /// Remove a grid
     RmGrid(usize),
+    /// Add a copy of a grid and of all the helices that are attached to it, translated by
+    /// `offset`
+    DuplicateGrid {
+        grid_id: usize,
+        offset: Vec3,
+    },
     /// Pick a new color at random for all the strands that are not the scaffold
     RecolorStaples,
+    /// Color all the strands that are not the scaffold with the colors of a color-blind-safe
+    /// palette, so that strands that are next to each other get different colors
+    RecolorStrands {
+        palette: ColorBlindPalette,
+    },
     /// Set the sequence of a set of strands
     ChangeSequence {
         sequence: String,
@@ -184,6 +206,12 @@ pub enum DesignOperation {
         helix: usize,
         isometry: Isometry2,
     },
+    /// Set the order in which the helices are displayed in the 2D view, and move the helices to
+    /// their new row
+    SetHelixDisplayOrder {
+        order: Vec<usize>,
+        isometries: Vec<(usize, Isometry2)>,
+    },
     RequestStrandBuilders {
         nucls: Vec<Nucl>,
     },
@@ -202,17 +230,41 @@ pub enum DesignOperation {
     FlipAnchors {
         nucls: Vec<Nucl>,
     },
+    /// Fix the position of a nucleotide during simulations
+    AddAnchor(Nucl),
+    /// Release a nucleotide that was fixed during simulations
+    RemoveAnchor(Nucl),
     AttachHelix {
         helix: usize,
         grid: usize,
         x: isize,
         y: isize,
     },
+    /// Give each helix `(helix, position, orientation)` a new position and orientation, detaching
+    /// it from its grid. This is used to import the relaxed conformation of the design.
+    PlaceHelices(Vec<(usize, Vec3, Rotor3)>),
     SetOrganizerTree(ensnano_design::OrganizerTree<DnaElementKey>),
+    /// Save `elements` under `name`, replacing the group that had this name if any
+    SetSelectionGroup {
+        name: String,
+        elements: Vec<DnaElementKey>,
+    },
+    DeleteSelectionGroup(String),
     SetStrandName {
         s_id: usize,
         name: String,
     },
+    /// Set the sequence of the strand with a given id
+    SetStrandSequence {
+        s_id: usize,
+        sequence: String,
+    },
+    /// Set the chemical modification attached to one end of a strand
+    SetStrandModification {
+        s_id: usize,
+        end: StrandEnd,
+        modification: Option<ensnano_design::StrandModification>,
+    },
     SetGroupPivot {
         group_id: GroupId,
         pivot: GroupPivot,
@@ -240,6 +292,160 @@ pub enum DesignOperation {
         grid_id: usize,
         orientation: Rotor3,
     },
+    /// Set the roll, in radians, given to the helices that are added to a grid. The helices that
+    /// are already on the grid are not modified.
+    SetGridTwistOffset {
+        grid_id: usize,
+        twist_offset: f32,
+    },
+    /// Renumber the strands of the design in increasing order of the given key
+    ReorderStrands(SortKey),
+    /// Insert `length` unpaired nucleotides in the strand containing `at`, right after `at`
+    AddLoop {
+        at: Nucl,
+        length: usize,
+    },
+    /// Extend the strand whose 3' end is `at` with a hairpin made of a stem of `stem_length` base
+    /// pairs and of a loop of `loop_length` unpaired nucleotides
+    CreateHairpin {
+        at: Nucl,
+        stem_length: usize,
+        loop_length: usize,
+    },
+    /// Attach a text label to a nucleotide. An empty label removes the annotation.
+    AnnotateNucleotide {
+        nucl: Nucl,
+        label: String,
+    },
+    /// Join an end of `strand_a` to an end of `strand_b` with a phosphodiester bond. The two ends
+    /// must be close to each other in space.
+    ConnectStrands {
+        strand_a: usize,
+        end_a: StrandEnd,
+        strand_b: usize,
+        end_b: StrandEnd,
+    },
+    /// Add a (p, q) torus knot, approximated by a chain of helices, together with a cyclic
+    /// strand following it.
+    AddTorusKnot {
+        p: u32,
+        q: u32,
+        radius: f32,
+        tube_radius: f32,
+    },
+    /// Reverse the 5' -> 3' direction of a strand. The strand keeps the same positions on its
+    /// helices but moves to the opposite side of each of them.
+    FlipStrand {
+        strand_id: usize,
+    },
+    /// Add a new grid holding the mirror image of all the helices of a grid, reflected across the
+    /// plane `axis`
+    MirrorGrid {
+        grid_id: usize,
+        axis: MirrorAxis,
+    },
+    /// Fuse `design` with the current design. The helices and strands of the added design are
+    /// rotated by `orientation` around the origin, translated by `offset`, and given new
+    /// identifiers.
+    MergeDesign {
+        design: DesignToMerge,
+        offset: Vec3,
+        orientation: Rotor3,
+    },
+}
+
+/// A design that is fused with the current design by a `DesignOperation::MergeDesign`
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DesignToMerge(pub Box<ensnano_design::Design>);
+
+impl std::fmt::Debug for DesignToMerge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "DesignToMerge({} helices, {} strands)",
+            self.0.helices.len(),
+            self.0.strands.len()
+        )
+    }
+}
+
+/// A criterion used to order the strands of a design
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SortKey {
+    ByLength,
+    ByGcContent,
+    /// Order the strands by the helix, and then position, of their 5' end
+    ByHelixOrder,
+}
+
+impl SortKey {
+    pub const ALL: [SortKey; 3] = [
+        SortKey::ByLength,
+        SortKey::ByGcContent,
+        SortKey::ByHelixOrder,
+    ];
+}
+
+impl std::fmt::Display for SortKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ByLength => write!(f, "Length"),
+            Self::ByGcContent => write!(f, "GC content"),
+            Self::ByHelixOrder => write!(f, "Helix order"),
+        }
+    }
+}
+
+/// A plane, going through the origin, across which objects can be reflected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MirrorAxis {
+    XY,
+    XZ,
+    YZ,
+}
+
+impl MirrorAxis {
+    pub const ALL: [MirrorAxis; 3] = [MirrorAxis::XY, MirrorAxis::XZ, MirrorAxis::YZ];
+
+    /// A unit vector orthogonal to the plane
+    pub fn normal(&self) -> Vec3 {
+        match self {
+            Self::XY => Vec3::unit_z(),
+            Self::XZ => Vec3::unit_y(),
+            Self::YZ => Vec3::unit_x(),
+        }
+    }
+
+    /// The image of `point` by the reflection across the plane
+    pub fn reflect(&self, point: Vec3) -> Vec3 {
+        let normal = self.normal();
+        point - 2. * point.dot(normal) * normal
+    }
+
+    /// The orientation of the mirror image of an object oriented by `orientation`.
+    ///
+    /// A reflection does not preserve handedness, so the local x axis of the object is reversed
+    /// to obtain a direct frame that can be represented by a rotor.
+    pub fn reflect_orientation(&self, orientation: Rotor3) -> Rotor3 {
+        let frame = orientation.into_matrix();
+        ultraviolet::Mat3::new(
+            -self.reflect(frame.cols[0]),
+            self.reflect(frame.cols[1]),
+            self.reflect(frame.cols[2]),
+        )
+        .into_rotor3()
+        .normalized()
+    }
+}
+
+impl std::fmt::Display for MirrorAxis {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::XY => write!(f, "XY plane"),
+            Self::XZ => write!(f, "XZ plane"),
+            Self::YZ => write!(f, "YZ plane"),
+        }
+    }
 }
 
 /// An action performed on the application
@@ -248,7 +454,7 @@ pub enum AppOperation {
     Fit,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum HyperboloidOperation {
     New {
         request: HyperboloidRequest,
@@ -261,7 +467,7 @@ pub enum HyperboloidOperation {
 }
 
 /// A rotation on an element of a design.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DesignRotation {
     pub origin: Vec3,
     pub rotation: Rotor3,
@@ -271,7 +477,7 @@ pub struct DesignRotation {
 }
 
 /// A translation of an element of a design
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct DesignTranslation {
     pub translation: Vec3,
     pub target: IsometryTarget,
@@ -279,7 +485,7 @@ pub struct DesignTranslation {
 }
 
 /// A element on which an isometry must be applied
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum IsometryTarget {
     /// The view of the whole design
     Design,
@@ -299,7 +505,7 @@ pub struct GridHelixDescriptor {
     pub y: isize,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HyperboloidRequest {
     pub radius: usize,
     pub length: f32,
@@ -332,6 +538,9 @@ pub struct RigidBodyConstants {
     pub k_friction: f32,
     pub mass: f32,
     pub volume_exclusion: bool,
+    /// If true, the nucleotides that are not paired with a complement cannot move during the
+    /// simulation
+    pub freeze_ssdna: bool,
     pub brownian_motion: bool,
     pub brownian_rate: f32,
     pub brownian_amplitude: f32,
@@ -344,6 +553,7 @@ impl Default for RigidBodyConstants {
             k_spring: 1.,
             mass: 1.,
             volume_exclusion: false,
+            freeze_ssdna: false,
             brownian_amplitude: 1.,
             brownian_rate: 1.,
             brownian_motion: false,
@@ -351,6 +561,39 @@ impl Default for RigidBodyConstants {
     }
 }
 
+/// Staples longer than this length are more expensive and less reliable to synthesize
+pub const MAX_STAPLE_LENGTH: usize = 60;
+
+/// Properties of a staple that are listed in the analysis tab
+#[derive(Clone, Debug)]
+pub struct StapleStatistics {
+    pub strand_id: usize,
+    pub length: usize,
+    /// The number of domains of the staple that are on a helix
+    pub nb_domains: usize,
+    /// The highest melting temperature of the domains of the staple, in °C, if their sequence is
+    /// known
+    pub melting_temperature: Option<f32>,
+}
+
+impl StapleStatistics {
+    /// A short description of each potential problem of the staple. Staples shorter than
+    /// `min_length` are flagged as too short.
+    pub fn flags(&self, min_length: usize) -> Vec<&'static str> {
+        let mut ret = Vec::new();
+        if self.length < min_length {
+            ret.push("too short");
+        }
+        if self.length > MAX_STAPLE_LENGTH {
+            ret.push("too long");
+        }
+        if self.nb_domains == 1 {
+            ret.push("single domain");
+        }
+        ret
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct ScaffoldInfo {
     pub id: usize,
@@ -363,6 +606,7 @@ pub struct ScaffoldInfo {
 pub enum SimulationState {
     None,
     Rolling,
+    OptimizingRolls,
     RigidGrid,
     RigidHelices,
     Paused,
@@ -385,6 +629,14 @@ impl SimulationState {
         }
     }
 
+    pub fn is_optimizing_rolls(&self) -> bool {
+        if let Self::OptimizingRolls = self {
+            true
+        } else {
+            false
+        }
+    }
+
     pub fn simulating_grid(&self) -> bool {
         if let Self::RigidGrid = self {
             true
@@ -507,3 +759,342 @@ impl SuggestionParameters {
         ret
     }
 }
+
+/// Constraints on the strands that can be built by the user
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StrandBuildingParameters {
+    /// Building a strand shorter than this length is rejected
+    pub min_strand_length: usize,
+}
+
+impl Default for StrandBuildingParameters {
+    fn default() -> Self {
+        Self {
+            min_strand_length: 7,
+        }
+    }
+}
+
+impl StrandBuildingParameters {
+    pub fn with_min_strand_length(&self, min_strand_length: usize) -> Self {
+        let mut ret = self.clone();
+        ret.min_strand_length = min_strand_length;
+        ret
+    }
+}
+
+/// The kind of quantity measured when clicking on nucleotides in the measure mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MeasureKind {
+    /// The distance between two nucleotides
+    Distance,
+    /// The angle formed by three nucleotides
+    Angle,
+    /// The angle between the axes of the helices of two nucleotides
+    HelixAngle,
+}
+
+impl MeasureKind {
+    pub const ALL: [MeasureKind; 3] = [Self::Distance, Self::Angle, Self::HelixAngle];
+
+    /// The number of nucleotides that must be clicked to make a measurement
+    pub fn nb_nucls(&self) -> usize {
+        match self {
+            Self::Distance => 2,
+            Self::Angle => 3,
+            Self::HelixAngle => 2,
+        }
+    }
+}
+
+impl Default for MeasureKind {
+    fn default() -> Self {
+        Self::Distance
+    }
+}
+
+impl std::fmt::Display for MeasureKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Distance => "Distance",
+            Self::Angle => "Angle",
+            Self::HelixAngle => "Helix angle",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A distance or an angle measured in the 3D view
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Measurement {
+    /// The distance, in nanometers, between two nucleotides
+    Distance { nucls: (Nucl, Nucl), distance: f32 },
+    /// The angle, in degrees, formed at the second nucleotide by three nucleotides
+    Angle {
+        nucls: (Nucl, Nucl, Nucl),
+        angle: f32,
+    },
+    /// The angle, in degrees, between the axes of the helices of two nucleotides
+    HelixAngle { nucls: (Nucl, Nucl), angle: f32 },
+}
+
+impl Measurement {
+    /// The nucleotides that were clicked to make the measurement
+    pub fn nucls(&self) -> Vec<Nucl> {
+        match self {
+            Self::Distance { nucls, .. } | Self::HelixAngle { nucls, .. } => {
+                vec![nucls.0, nucls.1]
+            }
+            Self::Angle { nucls, .. } => vec![nucls.0, nucls.1, nucls.2],
+        }
+    }
+
+    /// The measured value, with its unit. Distances are also given in base pairs, i.e. the
+    /// number of base pairs that a helix must have to span the distance along its axis.
+    pub fn value_text(&self, parameters: &Parameters) -> String {
+        match self {
+            Self::Distance { distance, .. } => {
+                format!("{:.2} nm, {:.1} bp", distance, distance / parameters.z_step)
+            }
+            Self::Angle { angle, .. } | Self::HelixAngle { angle, .. } => {
+                format!("{:.1}°", angle)
+            }
+        }
+    }
+}
+
+/// The successive positions of the nucleotides of the design read from an oxDNA trajectory.
+///
+/// While a trajectory is loaded, the nucleotides are displayed in the 3D view at the positions
+/// of the current frame instead of the positions given by their helices.
+#[derive(Clone, Debug)]
+pub struct Trajectory {
+    /// The nucleotides whose positions are given by the frames, in the order of the frames
+    nucls: Arc<Vec<Nucl>>,
+    /// The index of each nucleotide in the frames
+    nucl_indices: Arc<HashMap<Nucl, usize>>,
+    frames: Arc<Vec<Vec<Vec3>>>,
+    /// The index of the frame that is displayed
+    pub current_frame: usize,
+    /// True if the frames are automatically displayed one after the other
+    pub playing: bool,
+}
+
+impl PartialEq for Trajectory {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.frames, &other.frames)
+            && self.current_frame == other.current_frame
+            && self.playing == other.playing
+    }
+}
+
+impl Trajectory {
+    /// A trajectory whose frames give the positions of `nucls`, in the same order
+    pub fn new(nucls: Vec<Nucl>, frames: Vec<Vec<Vec3>>) -> Self {
+        let nucl_indices = nucls.iter().enumerate().map(|(i, n)| (*n, i)).collect();
+        Self {
+            nucls: Arc::new(nucls),
+            nucl_indices: Arc::new(nucl_indices),
+            frames: Arc::new(frames),
+            current_frame: 0,
+            playing: false,
+        }
+    }
+
+    pub fn nucls(&self) -> &[Nucl] {
+        self.nucls.as_slice()
+    }
+
+    pub fn nb_frames(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// The position of `nucl` in the current frame, if it is in the trajectory
+    pub fn current_position(&self, nucl: &Nucl) -> Option<Vec3> {
+        let idx = self.nucl_indices.get(nucl)?;
+        self.frames.get(self.current_frame)?.get(*idx).cloned()
+    }
+
+    /// Start or stop the playback. A playback started on the last frame restarts from the first
+    /// one.
+    pub fn set_playing(&mut self, playing: bool) {
+        if playing && self.current_frame + 1 >= self.nb_frames() {
+            self.current_frame = 0;
+        }
+        self.playing = playing;
+    }
+
+    /// Display the next frame. The playback stops on the last frame.
+    pub fn step(&mut self) {
+        if self.current_frame + 1 < self.nb_frames() {
+            self.current_frame += 1;
+        } else {
+            self.playing = false;
+        }
+    }
+
+    /// Display the frame `frame`, or the last one if there are less frames
+    pub fn go_to_frame(&mut self, frame: usize) {
+        self.current_frame = frame.min(self.nb_frames().saturating_sub(1));
+    }
+}
+
+/// The position given to a design of the workspace in the 3D view
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WorkspaceTransform {
+    pub translation: Vec3,
+    /// The orientation given to the design when it was last docked on the current design
+    pub docked_orientation: Rotor3,
+    /// The angles, in degrees, of the rotations around the x, y and z axes. The rotations are
+    /// applied in this order, after the docked orientation.
+    pub angles: Vec3,
+}
+
+impl Default for WorkspaceTransform {
+    fn default() -> Self {
+        Self {
+            translation: Vec3::zero(),
+            docked_orientation: Rotor3::identity(),
+            angles: Vec3::zero(),
+        }
+    }
+}
+
+impl WorkspaceTransform {
+    pub fn rotor(&self) -> Rotor3 {
+        Rotor3::from_rotation_xy(self.angles.z.to_radians())
+            * Rotor3::from_rotation_xz(self.angles.y.to_radians())
+            * Rotor3::from_rotation_yz(self.angles.x.to_radians())
+            * self.docked_orientation
+    }
+
+    /// The model matrix that places the design in the 3D view
+    pub fn matrix(&self) -> Mat4 {
+        Mat4::from_translation(self.translation) * self.rotor().into_matrix().into_homogeneous()
+    }
+}
+
+/// A design opened next to the design being edited
+#[derive(Clone, Debug, PartialEq)]
+pub struct WorkspaceDesignInfo {
+    pub name: String,
+    pub visible: bool,
+    pub transform: WorkspaceTransform,
+    /// The identifiers of the helices of the design
+    pub helices: Vec<usize>,
+}
+
+/// The way a selection group is combined with the current selection when it is recalled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectionGroupCombination {
+    /// Replace the current selection by the group
+    Replace,
+    /// Add the elements of the group to the current selection
+    Union,
+    /// Remove the elements of the group from the current selection
+    Difference,
+}
+
+impl SelectionGroupCombination {
+    /// The selection obtained by combining `selection` with `group`
+    pub fn apply(&self, selection: &[Selection], group: &[Selection]) -> Vec<Selection> {
+        match self {
+            Self::Replace => group.to_vec(),
+            Self::Union => {
+                let mut ret = selection.to_vec();
+                for s in group.iter() {
+                    if !ret.contains(s) {
+                        ret.push(*s);
+                    }
+                }
+                ret
+            }
+            Self::Difference => selection
+                .iter()
+                .filter(|s| !group.contains(s))
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+/// A palette of colors that remain distinguishable by people with color vision deficiencies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColorBlindPalette {
+    /// The palette of Okabe and Ito, without black
+    OkabeIto,
+    /// Paul Tol's bright qualitative palette
+    TolBright,
+    /// Paul Tol's muted qualitative palette
+    TolMuted,
+}
+
+impl ColorBlindPalette {
+    pub const ALL: [ColorBlindPalette; 3] = [Self::OkabeIto, Self::TolBright, Self::TolMuted];
+
+    /// The RGB colors of the palette
+    pub fn colors(&self) -> &'static [u32] {
+        match self {
+            Self::OkabeIto => &[
+                0xE69F00, 0x56B4E9, 0x009E73, 0xF0E442, 0x0072B2, 0xD55E00, 0xCC79A7,
+            ],
+            Self::TolBright => &[
+                0x4477AA, 0xEE6677, 0x228833, 0xCCBB44, 0x66CCEE, 0xAA3377, 0xBBBBBB,
+            ],
+            Self::TolMuted => &[
+                0xCC6677, 0x332288, 0xDDCC77, 0x117733, 0x88CCEE, 0x882255, 0x44AA99, 0x999933,
+                0xAA4499,
+            ],
+        }
+    }
+}
+
+impl Default for ColorBlindPalette {
+    fn default() -> Self {
+        Self::OkabeIto
+    }
+}
+
+impl std::fmt::Display for ColorBlindPalette {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::OkabeIto => "Okabe-Ito",
+            Self::TolBright => "Tol bright",
+            Self::TolMuted => "Tol muted",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The layout of the table produced by the staple export
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderFormat {
+    /// ENSnano's own layout, with the length and the name of the strands
+    Ensnano,
+    /// IDT's plate upload format, with the end modifications written in the sequences
+    Idt,
+    /// Twist's plate format, with the end modifications in separate columns
+    Twist,
+}
+
+impl OrderFormat {
+    pub const ALL: [OrderFormat; 3] = [Self::Ensnano, Self::Idt, Self::Twist];
+}
+
+impl Default for OrderFormat {
+    fn default() -> Self {
+        Self::Ensnano
+    }
+}
+
+impl std::fmt::Display for OrderFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Ensnano => "ENSnano",
+            Self::Idt => "IDT",
+            Self::Twist => "Twist",
+        };
+        write!(f, "{}", name)
+    }
+}
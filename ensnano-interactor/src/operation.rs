@@ -673,6 +673,7 @@ impl Operation for CreateGrid {
             orientation: self.orientation,
             grid_type: self.grid_type,
             invisible: false,
+            twist_offset: 0.,
         })
     }
 
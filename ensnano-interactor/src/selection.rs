@@ -16,12 +16,12 @@ ENSnano, a 3d graphical application for DNA nanostructures.
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 use ensnano_design::grid::GridPosition;
-use ensnano_design::{Nucl, Strand};
+use ensnano_design::{Nucl, Orphans, ScaffoldContinuityResult, Strand};
 use std::collections::BTreeSet;
 
 pub const PHANTOM_RANGE: i32 = 1000;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Selection {
     Nucleotide(u32, Nucl),
     Bound(u32, Nucl, Nucl),
@@ -31,6 +31,9 @@ pub enum Selection {
     Helix(u32, u32),
     Grid(u32, usize),
     Phantom(PhantomElement),
+    /// A double stranded segment between two consecutive cross-overs of the scaffold, identified
+    /// by its position among the domains of the scaffold
+    Domain(u32, u32),
     Nothing,
 }
 
@@ -66,6 +69,7 @@ impl Selection {
             Selection::Phantom(pe) => Some(pe.design_id),
             Selection::Nothing => None,
             Selection::Xover(d, _) => Some(*d),
+            Selection::Domain(d, _) => Some(*d),
         }
     }
 
@@ -126,6 +130,10 @@ impl Selection {
                 Some(vec![n1.helix, n2.helix])
             }
             Self::Bound(_, n1, n2) => Some(vec![n1.helix, n2.helix]),
+            Self::Domain(_, domain_id) => {
+                let nucls = reader.get_scaffold_domain_nucls(*domain_id as usize)?;
+                Some(nucls.first().map(|n| n.helix).into_iter().collect())
+            }
             Self::Nothing => Some(vec![]),
         }
     }
@@ -316,6 +324,14 @@ pub fn list_of_helices(selection: &[Selection]) -> Option<(usize, Vec<usize>)> {
     Some((design_id as usize, helices.into_iter().collect()))
 }
 
+/// If the selection consists of exactly two nucleotides, return them
+pub fn pair_of_nucleotides(selection: &[Selection]) -> Option<(Nucl, Nucl)> {
+    match selection {
+        [Selection::Nucleotide(_, n1), Selection::Nucleotide(_, n2)] => Some((*n1, *n2)),
+        _ => None,
+    }
+}
+
 pub fn set_of_helices_containing_selection(
     selection: &[Selection],
     reader: &dyn DesignReader,
@@ -389,6 +405,43 @@ pub enum SelectionMode {
     Strand,
     Helix,
     Design,
+    /// Select the 5' or 3' end of the strands
+    StrandEnd(StrandEnd),
+    /// Select the double stranded segments between two consecutive cross-overs of the scaffold
+    Domain,
+}
+
+/// One of the two extremities of a strand
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum StrandEnd {
+    FivePrime,
+    ThreePrime,
+}
+
+impl StrandEnd {
+    /// Return the nucleotide at this extremity of `strand`
+    pub fn of_strand(&self, strand: &Strand) -> Option<Nucl> {
+        match self {
+            Self::FivePrime => strand.get_5prime(),
+            Self::ThreePrime => strand.get_3prime(),
+        }
+    }
+
+    pub fn other(&self) -> Self {
+        match self {
+            Self::FivePrime => Self::ThreePrime,
+            Self::ThreePrime => Self::FivePrime,
+        }
+    }
+}
+
+impl std::fmt::Display for StrandEnd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::FivePrime => write!(f, "5'"),
+            Self::ThreePrime => write!(f, "3'"),
+        }
+    }
 }
 
 impl Default for SelectionMode {
@@ -408,18 +461,24 @@ impl std::fmt::Display for SelectionMode {
                 SelectionMode::Nucleotide => "Nucleotide",
                 SelectionMode::Strand => "Strand",
                 SelectionMode::Helix => "Helix",
+                SelectionMode::StrandEnd(StrandEnd::FivePrime) => "5' end",
+                SelectionMode::StrandEnd(StrandEnd::ThreePrime) => "3' end",
+                SelectionMode::Domain => "Domain",
             }
         )
     }
 }
 
 impl SelectionMode {
-    pub const ALL: [SelectionMode; 5] = [
+    pub const ALL: [SelectionMode; 8] = [
         SelectionMode::Nucleotide,
         SelectionMode::Design,
         SelectionMode::Strand,
         SelectionMode::Helix,
         SelectionMode::Grid,
+        SelectionMode::StrandEnd(StrandEnd::FivePrime),
+        SelectionMode::StrandEnd(StrandEnd::ThreePrime),
+        SelectionMode::Domain,
     ];
 }
 
@@ -441,6 +500,8 @@ pub enum ActionMode {
     /// should "stick"
     /// Use can cut strands
     Cut,
+    /// User measures the distances or angles between the nucleotides they click on
+    Measure(MeasureKind),
 }
 
 impl Default for ActionMode {
@@ -461,6 +522,7 @@ impl std::fmt::Display for ActionMode {
                 ActionMode::Build(_) => "Build",
                 ActionMode::BuildHelix { .. } => "Build",
                 ActionMode::Cut => "Cut",
+                ActionMode::Measure(_) => "Measure",
             }
         )
     }
@@ -521,7 +583,7 @@ pub fn phantom_helix_decoder(id: u32) -> PhantomElement {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PhantomElement {
     pub design_id: u32,
     pub helix_id: u32,
@@ -547,6 +609,11 @@ pub trait DesignReader {
     fn get_strand_with_id(&self, id: usize) -> Option<&Strand>;
     fn get_helix_grid(&self, h_id: usize) -> Option<usize>;
     fn get_domain_ends(&self, s_id: usize) -> Option<Vec<Nucl>>;
+    /// Return the nucleotides of a scaffold domain and their complements
+    fn get_scaffold_domain_nucls(&self, domain_id: usize) -> Option<Vec<Nucl>>;
+    fn get_scaffold_continuity(&self) -> ScaffoldContinuityResult;
+    /// Return the strands that have no nucleotide and the helices on which no nucleotide lies
+    fn get_orphans(&self) -> Orphans;
 }
 
 pub trait SelectionConversion: Sized {
@@ -569,6 +636,7 @@ impl SelectionConversion for DnaElementKey {
                     forward: nucl.forward,
                 }),
                 Selection::Bound(_, _, _) => None,
+                Selection::Domain(_, _) => None,
                 Selection::Xover(_, xover_id) => Some(Self::CrossOver {
                     xover_id: *xover_id,
                 }),
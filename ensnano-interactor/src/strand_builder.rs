@@ -380,6 +380,30 @@ impl StrandBuilder {
         self.moving_end.position
     }
 
+    /// Return the nucleotides that the moving end would go through if it was moved to
+    /// `end_position`, without modifying the design.
+    ///
+    /// The bounds that are already known to the builder are taken into account, but the
+    /// neighbours that would be met on the way are not, so the actual movement may be shorter.
+    pub fn get_candidate_nucls(&self, end_position: isize) -> Vec<Nucl> {
+        let objective = end_position
+            .min(self.max_pos.unwrap_or(isize::MAX))
+            .max(self.min_pos.unwrap_or(isize::MIN));
+        let start = self.moving_end.position;
+        let positions: Vec<isize> = match objective.cmp(&start) {
+            Ordering::Greater => (start + 1..=objective).collect(),
+            Ordering::Less => (objective..start).rev().collect(),
+            Ordering::Equal => Vec::new(),
+        };
+        positions
+            .into_iter()
+            .map(|position| Nucl {
+                position,
+                ..self.moving_end
+            })
+            .collect()
+    }
+
     pub fn get_moving_end_nucl(&self) -> Nucl {
         self.moving_end
     }
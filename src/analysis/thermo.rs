@@ -0,0 +1,39 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! Coloring of the melting temperatures of the staples.
+//!
+//! The melting temperatures themselves are computed by `ensnano_design::melting_temperature`.
+
+/// The melting temperatures, in °C, below and above which `temperature_color` returns the most
+/// saturated colors
+const LOW_MELTING_TEMPERATURE: f32 = 20.;
+const HIGH_MELTING_TEMPERATURE: f32 = 60.;
+
+/// Return a color, encoded as `0xRRGGBB`, going from red for low melting temperatures to white
+/// and to blue for high melting temperatures
+pub fn temperature_color(tm: f32) -> u32 {
+    let t = ((tm - LOW_MELTING_TEMPERATURE) / (HIGH_MELTING_TEMPERATURE - LOW_MELTING_TEMPERATURE))
+        .clamp(0., 1.);
+    let (r, g, b) = if t < 0.5 {
+        (1., 2. * t, 2. * t)
+    } else {
+        (2. - 2. * t, 2. - 2. * t, 1.)
+    };
+    let component = |x: f32| (x * 255.).round() as u32;
+    (component(r) << 16) | (component(g) << 8) | component(b)
+}
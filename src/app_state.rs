@@ -29,6 +29,7 @@ use ensnano_interactor::{
     operation::Operation, ActionMode, CenterOfSelection, Selection, SelectionMode, WidgetBasis,
 };
 
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
 use std::sync::{Arc, RwLock};
 mod address_pointer;
@@ -36,21 +37,27 @@ mod design_interactor;
 use crate::apply_update;
 use crate::controller::SimulationRequest;
 use address_pointer::AddressPointer;
-use ensnano_design::Design;
-use ensnano_interactor::{DesignOperation, RigidBodyConstants, SuggestionParameters};
+use ensnano_design::{Design, Nucl};
+use ensnano_interactor::{
+    DesignOperation, Measurement, RigidBodyConstants, StrandBuildingParameters,
+    SuggestionParameters, Trajectory, WorkspaceTransform,
+};
 use ensnano_organizer::GroupId;
 
 pub use design_interactor::controller::ErrOperation;
 pub use design_interactor::{
-    CopyOperation, DesignReader, InteractorNotification, PastingStatus, ShiftOptimizationResult,
-    ShiftOptimizerReader, SimulationInterface, SimulationReader, SimulationTarget,
-    SimulationUpdate,
+    read_design_to_merge, CadnanoExportError, CopyOperation, DesignReader, InteractorNotification,
+    OxDnaTrajectoryReader, ParseDesignError, PastingStatus, ShiftOptimization, ShiftOptimizationOk,
+    ShiftOptimizationResult, ShiftOptimizerReader, SimulationInterface, SimulationReader,
+    SimulationTarget, SimulationUpdate,
 };
 use design_interactor::{DesignInteractor, InteractorResult};
 
 mod impl_app2d;
 mod impl_app3d;
 mod impl_gui;
+mod workspace;
+use workspace::Workspace;
 
 /// A structure containing the global state of the program.
 ///
@@ -101,6 +108,10 @@ impl AppState {
             // Set when the selection is modified, the center of selection is set to None. It is up
             // to the caller to set it to a certain value when applicable
             new_state.center_of_selection = None;
+            // The highlighted path is only shown until the selection changes
+            if !new_state.highlighted_path.is_empty() {
+                new_state.highlighted_path = Default::default();
+            }
             let mut ret = Self(AddressPointer::new(new_state));
             if selection_len > 0 {
                 ret = ret.notified(InteractorNotification::NewSelection)
@@ -131,16 +142,147 @@ impl AppState {
 
     pub fn with_selection_mode(&self, selection_mode: SelectionMode) -> Self {
         let mut new_state = (*self.0).clone();
+        if new_state.selection_mode != selection_mode {
+            new_state.selection_locked = false;
+        }
         new_state.selection_mode = selection_mode;
         Self(AddressPointer::new(new_state))
     }
 
+    pub fn with_selection_lock(&self, locked: bool) -> Self {
+        let mut new_state = (*self.0).clone();
+        new_state.selection_locked = locked;
+        Self(AddressPointer::new(new_state))
+    }
+
+    pub fn is_selection_locked(&self) -> bool {
+        self.0.selection_locked
+    }
+
     pub fn with_suggestion_parameters(&self, suggestion_parameters: SuggestionParameters) -> Self {
         let mut new_state = (*self.0).clone();
         new_state.suggestion_parameters = suggestion_parameters;
         Self(AddressPointer::new(new_state))
     }
 
+    /// Return a state in which the visible ranges of the helices of `ranges` are set in the 2D
+    /// view. The ranges of the other helices are kept.
+    pub fn with_helix_2d_ranges(&self, ranges: BTreeMap<usize, (isize, isize)>) -> Self {
+        let mut new_state = (*self.0).clone();
+        let mut helix_2d_ranges = self.0.helix_2d_ranges.clone_inner();
+        helix_2d_ranges.extend(ranges);
+        new_state.helix_2d_ranges = AddressPointer::new(helix_2d_ranges);
+        Self(AddressPointer::new(new_state))
+    }
+
+    pub fn with_highlighted_path(&self, path: Vec<Nucl>) -> Self {
+        let mut new_state = (*self.0).clone();
+        new_state.highlighted_path = AddressPointer::new(path);
+        Self(AddressPointer::new(new_state))
+    }
+
+    pub fn with_nucleotide_colors(&self, colors: HashMap<Nucl, [f32; 3]>) -> Self {
+        let mut new_state = (*self.0).clone();
+        new_state.nucleotide_colors = AddressPointer::new(colors);
+        Self(AddressPointer::new(new_state))
+    }
+
+    pub fn get_nucleotide_colors(&self) -> &HashMap<Nucl, [f32; 3]> {
+        &self.0.nucleotide_colors
+    }
+
+    pub fn with_measurements(&self, measurements: Vec<Measurement>) -> Self {
+        let mut new_state = (*self.0).clone();
+        new_state.measurements = AddressPointer::new(measurements);
+        Self(AddressPointer::new(new_state))
+    }
+
+    pub fn get_measurements(&self) -> &[Measurement] {
+        self.0.measurements.as_slice()
+    }
+
+    pub fn with_trajectory(&self, trajectory: Option<Trajectory>) -> Self {
+        let mut new_state = (*self.0).clone();
+        new_state.trajectory = trajectory;
+        Self(AddressPointer::new(new_state))
+    }
+
+    pub fn get_trajectory(&self) -> Option<&Trajectory> {
+        self.0.trajectory.as_ref()
+    }
+
+    /// Read the design stored in the file at `path` and add it to the workspace
+    pub fn with_design_in_workspace(
+        &self,
+        path: &PathBuf,
+    ) -> Result<Self, design_interactor::ParseDesignError> {
+        let design = DesignInteractor::new_with_path(path)?;
+        Ok(self.with_workspace(self.0.workspace.with_design(path.clone(), design)))
+    }
+
+    pub fn without_workspace_design(&self, design_id: usize) -> Self {
+        self.with_workspace(self.0.workspace.without_design(design_id))
+    }
+
+    pub fn with_workspace_design_visibility(&self, design_id: usize, visible: bool) -> Self {
+        self.with_workspace(self.0.workspace.with_visibility(design_id, visible))
+    }
+
+    pub fn with_workspace_design_transform(
+        &self,
+        design_id: usize,
+        transform: WorkspaceTransform,
+    ) -> Self {
+        self.with_workspace(self.0.workspace.with_transform(design_id, transform))
+    }
+
+    pub fn with_main_design_visibility(&self, visible: bool) -> Self {
+        self.with_workspace(self.0.workspace.with_main_design_visibility(visible))
+    }
+
+    /// Move the workspace design `design_id` so that its helix `helix` is parallel to the helix
+    /// `target_helix` of the design being edited, at the distance that separates two neighbour
+    /// helices. Return `None` if one of the helices does not exist.
+    pub fn with_workspace_design_docked(
+        &self,
+        design_id: usize,
+        helix: usize,
+        target_helix: usize,
+    ) -> Option<Self> {
+        let target = self.0.design.get_helix_axis(target_helix)?;
+        let parameters = self.0.design.get_dna_parameters();
+        let spacing = 2. * parameters.helix_radius + parameters.inter_helix_gap;
+        self.0
+            .workspace
+            .with_docked_design(design_id, helix, &target, spacing)
+            .map(|workspace| self.with_workspace(workspace))
+    }
+
+    /// The operation that adds the workspace design `design_id`, at its current position, to
+    /// the design being edited
+    pub fn get_workspace_merge_operation(&self, design_id: usize) -> Option<DesignOperation> {
+        self.0
+            .workspace
+            .get_design_to_merge(design_id)
+            .map(|(design, transform)| DesignOperation::MergeDesign {
+                design: ensnano_interactor::DesignToMerge(Box::new(design)),
+                offset: transform.translation,
+                orientation: transform.rotor(),
+            })
+    }
+
+    fn with_workspace(&self, workspace: Workspace) -> Self {
+        let mut new_state = (*self.0).clone();
+        new_state.workspace = AddressPointer::new(workspace);
+        Self(AddressPointer::new(new_state))
+    }
+
+    pub fn with_strand_building_parameters(&self, parameters: StrandBuildingParameters) -> Self {
+        let mut new_state = (*self.0).clone();
+        new_state.strand_building_parameters = parameters;
+        Self(AddressPointer::new(new_state))
+    }
+
     pub fn with_action_mode(&self, action_mode: ActionMode) -> Self {
         let mut new_state = (*self.0).clone();
         new_state.action_mode = action_mode;
@@ -192,10 +334,29 @@ impl AppState {
 
     pub fn import_design(path: &PathBuf) -> Result<Self, design_interactor::ParseDesignError> {
         let design_interactor = DesignInteractor::new_with_path(path)?;
-        Ok(Self(AddressPointer::new(AppState_ {
+        Ok(Self::with_design_interactor(design_interactor))
+    }
+
+    /// Import a design and the states that preceded it if they were saved in the file. The
+    /// previous states are returned oldest first.
+    pub fn import_design_with_history(
+        path: &PathBuf,
+    ) -> Result<(Self, Vec<Self>), design_interactor::ParseDesignError> {
+        let (design_interactor, history) = DesignInteractor::new_with_path_and_history(path)?;
+        Ok((
+            Self::with_design_interactor(design_interactor),
+            history
+                .into_iter()
+                .map(Self::with_design_interactor)
+                .collect(),
+        ))
+    }
+
+    fn with_design_interactor(design_interactor: DesignInteractor) -> Self {
+        Self(AddressPointer::new(AppState_ {
             design: AddressPointer::new(design_interactor),
             ..Default::default()
-        })))
+        }))
     }
 
     pub(super) fn update(&mut self) {
@@ -226,6 +387,10 @@ impl AppState {
 
     fn with_interactor(self, interactor: DesignInteractor) -> Self {
         let mut new_state = self.0.clone_inner();
+        // The positions of a trajectory are only meaningful for the design that was simulated
+        if interactor.has_different_design_than(&new_state.design) {
+            new_state.trajectory = None;
+        }
         new_state.design = AddressPointer::new(interactor);
         Self(AddressPointer::new(new_state))
     }
@@ -234,8 +399,38 @@ impl AppState {
         &mut self,
         op: DesignOperation,
     ) -> Result<Option<Self>, ErrOperation> {
+        let renumbering = if let DesignOperation::ReorderStrands(key) = op {
+            Some(self.0.design.strand_renumbering(key))
+        } else {
+            None
+        };
         let result = self.0.design.apply_operation(op);
-        self.handle_operation_result(result)
+        let ret = self.handle_operation_result(result)?;
+        if let Some(new_ids) = renumbering {
+            *self = self.with_strands_renumbered(&new_ids);
+        }
+        Ok(ret)
+    }
+
+    /// Update the identifiers of the strands in the selection
+    fn with_strands_renumbered(&self, new_ids: &HashMap<usize, usize>) -> Self {
+        let selection = self
+            .0
+            .selection
+            .selection
+            .iter()
+            .map(|s| match s {
+                Selection::Strand(d_id, s_id) => new_ids
+                    .get(&(*s_id as usize))
+                    .map(|new_id| Selection::Strand(*d_id, *new_id as u32))
+                    .unwrap_or(*s),
+                s => *s,
+            })
+            .collect();
+        let mut new_state = (*self.0).clone();
+        new_state.selection.selection = AddressPointer::new(selection);
+        new_state.candidates = Default::default();
+        Self(AddressPointer::new(new_state))
     }
 
     pub(super) fn apply_copy_operation(
@@ -295,6 +490,19 @@ impl AppState {
         }
     }
 
+    /// Reject the strands that are being built if one of them is shorter than the minimum strand
+    /// length. In that case the strands created by the building are removed from the design.
+    pub fn reject_short_built_strands(&mut self) -> Result<(), ErrOperation> {
+        let min_length = self.0.strand_building_parameters.min_strand_length;
+        if let Err(e) = self.0.design.check_built_strands(min_length) {
+            let result = self.0.design.reject_built_strands();
+            self.handle_operation_result(result)?;
+            Err(e)
+        } else {
+            Ok(())
+        }
+    }
+
     pub fn notified(&self, notification: InteractorNotification) -> Self {
         let new_interactor = self.0.design.notify(notification);
         self.clone().with_interactor(new_interactor)
@@ -320,6 +528,37 @@ impl AppState {
         self.get_design_reader().oxdna_export(target_dir)
     }
 
+    pub fn pdb_export(&self, path: &PathBuf) -> std::io::Result<()> {
+        self.get_design_reader().pdb_export(path)
+    }
+
+    pub fn export_subdesign(&self, helix_ids: &[usize], path: &PathBuf) -> std::io::Result<()> {
+        self.get_design_reader().export_subdesign(helix_ids, path)
+    }
+
+    /// A reader of the oxDNA trajectories of the current design
+    pub fn oxdna_trajectory_reader(&self) -> OxDnaTrajectoryReader {
+        self.get_design_reader().oxdna_trajectory_reader()
+    }
+
+    /// The placement of the helices that gives them the conformation of the current frame of
+    /// `trajectory`
+    pub fn helices_fitted_to_trajectory(
+        &self,
+        trajectory: &Trajectory,
+    ) -> Vec<(usize, ultraviolet::Vec3, ultraviolet::Rotor3)> {
+        self.get_design_reader()
+            .helices_fitted_to_trajectory(trajectory)
+    }
+
+    pub fn cadnano_export(&self, path: &PathBuf) -> Result<(), CadnanoExportError> {
+        self.get_design_reader().cadnano_export(path)
+    }
+
+    pub fn connectivity_graph_export(&self, path: &PathBuf) -> std::io::Result<()> {
+        self.get_design_reader().connectivity_graph_export(path)
+    }
+
     pub fn get_selection(&self) -> impl AsRef<[Selection]> {
         self.0.selection.selection.clone()
     }
@@ -332,7 +571,13 @@ impl AppState {
         *self = self.with_candidates(vec![]);
         *self = self.with_action_mode(source.0.action_mode.clone());
         *self = self.with_selection_mode(source.0.selection_mode.clone());
+        *self = self.with_selection_lock(source.0.selection_locked);
         *self = self.with_suggestion_parameters(source.0.suggestion_parameters.clone());
+        *self = self.with_strand_building_parameters(source.0.strand_building_parameters.clone());
+        // The workspace is not part of the edition history
+        let mut new_state = (*self.0).clone();
+        new_state.workspace = source.0.workspace.clone();
+        *self = Self(AddressPointer::new(new_state));
     }
 
     pub(super) fn is_pasting(&self) -> PastingStatus {
@@ -373,6 +618,10 @@ impl AppState {
         self.0.design.has_different_design_than(&other.0.design)
     }
 
+    pub fn get_strand_building_parameters(&self) -> &StrandBuildingParameters {
+        &self.0.strand_building_parameters
+    }
+
     fn get_strand_building_state(&self) -> Option<crate::gui::StrandBuildingStatus> {
         use crate::gui::StrandBuildingStatus;
         let builders = self.0.design.get_strand_builders();
@@ -460,6 +709,8 @@ struct AppState_ {
     /// The set of objects that are "one click away from beeing selected"
     candidates: AddressPointer<Vec<Selection>>,
     selection_mode: SelectionMode,
+    /// When true, clicking on empty space does not clear the selection
+    selection_locked: bool,
     /// A pointer to the design currently beign eddited. The pointed design is never mutatated.
     /// Instead, when a modification is requested, the design is cloned and the `design` pointer is
     /// replaced by a pointer to a modified `Design`.
@@ -469,6 +720,20 @@ struct AppState_ {
     strand_on_new_helix: Option<NewHelixStrand>,
     center_of_selection: Option<CenterOfSelection>,
     suggestion_parameters: SuggestionParameters,
+    strand_building_parameters: StrandBuildingParameters,
+    /// A path of nucleotides that is highlighted in the 3D scene
+    highlighted_path: AddressPointer<Vec<Nucl>>,
+    /// Colors of individual nucleotides set by external programs
+    nucleotide_colors: AddressPointer<HashMap<Nucl, [f32; 3]>>,
+    /// The distances between pairs of nucleotides measured in the 3D view
+    measurements: AddressPointer<Vec<Measurement>>,
+    /// The oxDNA trajectory whose frames are displayed in the 3D view
+    trajectory: Option<Trajectory>,
+    /// The designs displayed next to the design being edited
+    workspace: AddressPointer<Workspace>,
+    /// The visible ranges of the helices in the 2D view that were set by the user, identified by
+    /// helix
+    helix_2d_ranges: AddressPointer<BTreeMap<usize, (isize, isize)>>,
 }
 
 #[derive(Clone, Default)]
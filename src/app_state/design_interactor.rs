@@ -17,7 +17,7 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 */
 
 use super::AddressPointer;
-use ensnano_design::{group_attributes::GroupAttribute, Design, Parameters};
+use ensnano_design::{group_attributes::GroupAttribute, Axis, Design, Nucl, Parameters};
 use ensnano_interactor::{
     operation::Operation, DesignOperation, RigidBodyConstants, Selection, SimulationState,
     StrandBuilder, SuggestionParameters,
@@ -25,22 +25,24 @@ use ensnano_interactor::{
 
 mod presenter;
 use ensnano_organizer::GroupId;
-pub use presenter::SimulationUpdate;
 use presenter::{apply_simulation_update, update_presenter, Presenter};
+pub use presenter::{OxDnaTrajectoryReader, SimulationUpdate};
 pub(super) mod controller;
 use controller::Controller;
 pub use controller::{
-    CopyOperation, InteractorNotification, PastingStatus, RigidHelixState, ShiftOptimizationResult,
-    ShiftOptimizerReader, SimulationInterface, SimulationReader,
+    CopyOperation, InteractorNotification, PastingStatus, RigidHelixState, ShiftOptimization,
+    ShiftOptimizationOk, ShiftOptimizationResult, ShiftOptimizerReader, SimulationInterface,
+    SimulationReader,
 };
 
 use crate::{controller::SimulationRequest, gui::CurentOpState};
 pub(super) use controller::ErrOperation;
 use controller::{GridPresenter, HelixPresenter, OkOperation, RollPresenter};
 
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 mod file_parsing;
-pub use file_parsing::ParseDesignError;
+pub use file_parsing::{read_design_to_merge, CadnanoExportError, ParseDesignError};
 
 mod grid_data;
 
@@ -58,6 +60,29 @@ pub struct DesignInteractor {
     simulation_update: Option<Arc<dyn SimulationUpdate>>,
     current_operation: Option<Arc<dyn Operation>>,
     current_operation_id: usize,
+    /// Changes each time the content read by the `DesignReader` is modified.
+    design_generation: DesignGeneration,
+}
+
+/// An identifier of a version of the design.
+///
+/// The generations are drawn from a global counter so that two different versions of the design
+/// never share the same generation, even when an older version is restored by undoing an
+/// operation.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct DesignGeneration(u64);
+
+impl DesignGeneration {
+    fn next() -> Self {
+        static NEXT_GENERATION: AtomicU64 = AtomicU64::new(0);
+        Self(NEXT_GENERATION.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl Default for DesignGeneration {
+    fn default() -> Self {
+        Self::next()
+    }
 }
 
 impl DesignInteractor {
@@ -137,6 +162,13 @@ impl DesignInteractor {
                     target_helices,
                 }
             }
+            SimulationTarget::OptimizeRolls { target_helices } => {
+                controller::SimulationOperation::StartRollOptimization {
+                    presenter: self.presenter.as_ref(),
+                    reader,
+                    target_helices,
+                }
+            }
         };
         let result = self
             .controller
@@ -170,23 +202,35 @@ impl DesignInteractor {
                 let mut ret = self.clone();
                 ret.controller = AddressPointer::new(controller);
                 ret.design = AddressPointer::new(design);
+                ret.design_generation = DesignGeneration::next();
                 Ok(InteractorResult::Replace(ret))
             }
             Ok((OkOperation::Push(design), controller)) => {
                 let mut ret = self.clone();
                 ret.controller = AddressPointer::new(controller);
                 ret.design = AddressPointer::new(design);
+                ret.design_generation = DesignGeneration::next();
                 Ok(InteractorResult::Push(ret))
             }
             Ok((OkOperation::NoOp, controller)) => {
                 let mut ret = self.clone();
                 ret.controller = AddressPointer::new(controller);
+                ret.design_generation = DesignGeneration::next();
                 Ok(InteractorResult::Replace(ret))
             }
             Err(e) => Err(e),
         }
     }
 
+    pub(super) fn check_built_strands(&self, min_length: usize) -> Result<(), ErrOperation> {
+        self.controller
+            .check_built_strands(self.design.as_ref(), min_length)
+    }
+
+    pub(super) fn reject_built_strands(&self) -> Result<InteractorResult, ErrOperation> {
+        self.handle_operation_result(Ok(self.controller.reject_built_strands()))
+    }
+
     pub(super) fn get_curent_operation_state(&self) -> Option<CurentOpState> {
         self.current_operation.as_ref().map(|op| CurentOpState {
             operation_id: self.current_operation_id,
@@ -197,6 +241,7 @@ impl DesignInteractor {
     pub(super) fn notify(&self, notification: InteractorNotification) -> Self {
         let mut ret = self.clone();
         ret.controller = AddressPointer::new(ret.controller.notify(notification));
+        ret.design_generation = DesignGeneration::next();
         ret
     }
 
@@ -210,6 +255,9 @@ impl DesignInteractor {
         }
         let (new_presenter, new_design) =
             update_presenter(&self.presenter, self.design.clone(), suggestion_parameters);
+        if new_presenter != self.presenter {
+            self.design_generation = DesignGeneration::next();
+        }
         self.presenter = new_presenter;
         if cfg!(test) {
             print!("New design: ");
@@ -247,13 +295,24 @@ impl DesignInteractor {
         );
         self.presenter = new_presenter;
         self.design = new_design;
+        self.design_generation = DesignGeneration::next();
         self
     }
 
+    /// Return the identifier that each strand is given when the strands are renumbered according
+    /// to `key`
+    pub(super) fn strand_renumbering(
+        &self,
+        key: ensnano_interactor::SortKey,
+    ) -> HashMap<usize, usize> {
+        controller::strand_renumbering(&self.design, key)
+    }
+
     #[allow(dead_code)] //used in tests
     pub(super) fn with_updated_design(&self, design: Design) -> Self {
         let mut new_interactor = self.clone();
         new_interactor.design = AddressPointer::new(design);
+        new_interactor.design_generation = DesignGeneration::next();
         new_interactor
     }
 
@@ -265,6 +324,12 @@ impl DesignInteractor {
         self.design != other.design
     }
 
+    /// Return an identifier of the current version of the design. Two interactors with the
+    /// same generation give the same answers to the requests of the `DesignReader`.
+    pub(super) fn get_design_generation(&self) -> u64 {
+        self.design_generation.0
+    }
+
     pub(super) fn has_different_model_matrix_than(&self, other: &Self) -> bool {
         self.presenter
             .has_different_model_matrix_than(other.presenter.as_ref())
@@ -274,10 +339,40 @@ impl DesignInteractor {
         self.controller.get_simulation_state()
     }
 
+    /// The kinetic energy of the system of the running simulation, if any
+    pub(super) fn get_simulation_kinetic_energy(&self) -> Option<f32> {
+        self.simulation_update
+            .as_ref()
+            .and_then(|update| update.kinetic_energy())
+    }
+
+    pub(super) fn get_roll_optimization_iteration(&self) -> Option<usize> {
+        self.controller.get_roll_optimization_iteration()
+    }
+
     pub(super) fn get_dna_parameters(&self) -> Parameters {
         self.presenter.current_design.parameters.unwrap_or_default()
     }
 
+    pub(super) fn get_helix_ids(&self) -> Vec<usize> {
+        self.presenter
+            .current_design
+            .helices
+            .keys()
+            .cloned()
+            .collect()
+    }
+
+    /// The axis of the helix `h_id`, if it exists
+    pub(super) fn get_helix_axis(&self, h_id: usize) -> Option<Axis> {
+        let parameters = self.get_dna_parameters();
+        self.presenter
+            .current_design
+            .helices
+            .get(&h_id)
+            .map(|helix| helix.get_axis(&parameters))
+    }
+
     pub(super) fn is_changing_color(&self) -> bool {
         self.controller.is_changing_color()
     }
@@ -307,6 +402,7 @@ impl DesignInteractor {
         presenter.set_visibility_sieve(selection, compl);
         self.presenter = AddressPointer::new(presenter);
         self.design = AddressPointer::new(self.design.clone_inner());
+        self.design_generation = DesignGeneration::next();
         InteractorResult::Push(self)
     }
 }
@@ -341,7 +437,10 @@ pub struct DesignReader {
 }
 
 use crate::controller::SaveDesignError;
+use ensnano_interactor::Trajectory;
+use std::collections::HashMap;
 use std::path::PathBuf;
+use ultraviolet::{Rotor3, Vec3};
 impl DesignReader {
     pub fn save_design(
         &self,
@@ -350,21 +449,71 @@ impl DesignReader {
     ) -> Result<(), SaveDesignError> {
         use std::io::Write;
         let mut design = self.presenter.current_design.clone_inner();
-        design.prepare_for_save(saving_info);
+        design.prepare_for_save(saving_info)?;
         let json_content = serde_json::to_string_pretty(&design)?;
         let mut f = std::fs::File::create(path)?;
         f.write_all(json_content.as_bytes())?;
         Ok(())
     }
 
+    /// Return a copy of the design, to be embedded in the edit history of a saved file
+    pub fn get_design_snapshot(&self) -> Design {
+        self.presenter.current_design.clone_inner()
+    }
+
     pub fn oxdna_export(&self, target_dir: &PathBuf) -> std::io::Result<(PathBuf, PathBuf)> {
         self.presenter.oxdna_export(target_dir)
     }
 
+    pub fn pdb_export(&self, path: &PathBuf) -> std::io::Result<()> {
+        self.presenter.pdb_export(path)
+    }
+
+    pub fn export_subdesign(&self, helix_ids: &[usize], path: &PathBuf) -> std::io::Result<()> {
+        self.presenter.export_subdesign(helix_ids, path)
+    }
+
+    pub fn oxdna_trajectory_reader(&self) -> OxDnaTrajectoryReader {
+        self.presenter.oxdna_trajectory_reader()
+    }
+
+    pub fn helices_fitted_to_trajectory(
+        &self,
+        trajectory: &Trajectory,
+    ) -> Vec<(usize, Vec3, Rotor3)> {
+        self.presenter.helices_fitted_to_trajectory(trajectory)
+    }
+
+    pub fn cadnano_export(&self, path: &PathBuf) -> Result<(), CadnanoExportError> {
+        file_parsing::cadnano_export(self.presenter.current_design.as_ref(), path)
+    }
+
+    /// Write the graph whose nodes are the helices of the design and whose edges are its
+    /// cross-overs.
+    pub fn connectivity_graph_export(&self, path: &PathBuf) -> std::io::Result<()> {
+        file_parsing::connectivity_graph_export(self.presenter.current_design.as_ref(), path)
+    }
+
     pub fn get_strand_domain(&self, s_id: usize, d_id: usize) -> Option<&ensnano_design::Domain> {
         self.presenter.get_strand_domain(s_id, d_id)
     }
 
+    /// Return the `n`-th nucleotide of strand `s_id`, starting from its 5' end
+    pub fn get_nth_nucl_of_strand(&self, s_id: usize, n: usize) -> Option<Nucl> {
+        self.presenter
+            .current_design
+            .strands
+            .get(&s_id)
+            .and_then(|s| s.get_nth_nucl(n))
+    }
+
+    pub fn find_shortest_path(&self, from: Nucl, to: Nucl) -> Option<Vec<Nucl>> {
+        self.presenter
+            .current_design
+            .as_ref()
+            .find_shortest_path(from, to)
+    }
+
     pub fn get_group_attributes(&self, group_id: GroupId) -> Option<&GroupAttribute> {
         self.presenter
             .current_design
@@ -380,10 +529,11 @@ mod tests {
     use super::controller::CopyOperation;
     use super::*;
     use crate::scene::DesignReader as Reader3d;
+    use ensnano_design::elements::DnaElementKey;
     use ensnano_design::grid::GridPosition;
-    use ensnano_design::{grid::GridDescriptor, DomainJunction, Nucl, Strand};
+    use ensnano_design::{grid::GridDescriptor, Domain, DomainJunction, Nucl, Strand};
     use ensnano_interactor::operation::GridHelixCreation;
-    use ensnano_interactor::DesignReader;
+    use ensnano_interactor::{DesignReader, StrandEnd};
     use std::path::PathBuf;
     use ultraviolet::{Rotor3, Vec3};
 
@@ -556,6 +706,139 @@ mod tests {
         assert_eq!(xover_id, vec![(0, (n1, n2))]);
     }
 
+    #[test]
+    fn xover_while_building_ends_building() {
+        let mut app_state = one_xover();
+        app_state
+            .apply_design_op(DesignOperation::RequestStrandBuilders {
+                nucls: vec![Nucl {
+                    helix: 1,
+                    position: 10,
+                    forward: true,
+                }],
+            })
+            .unwrap();
+        app_state.update();
+        app_state
+            .apply_design_op(DesignOperation::MoveBuilders(12))
+            .unwrap();
+        app_state.update();
+        let source = Nucl {
+            helix: 1,
+            position: 12,
+            forward: true,
+        };
+        let target = Nucl {
+            helix: 2,
+            position: 3,
+            forward: false,
+        };
+        let undo_state = app_state
+            .apply_design_op(DesignOperation::GeneralXover { source, target })
+            .unwrap();
+        app_state.update();
+        // The building and the cross-over form a single undoable operation
+        assert!(undo_state.is_none());
+        assert!(app_state.is_in_stable_state());
+        let xovers: Vec<_> = app_state
+            .0
+            .design
+            .presenter
+            .junctions_ids
+            .get_all_elements()
+            .into_iter()
+            .map(|(_, xover)| xover)
+            .collect();
+        assert!(xovers.contains(&(source, target)));
+    }
+
+    #[test]
+    fn helix_display_order_is_saved_in_the_design() {
+        let mut app_state = pastable_design();
+        let mut order: Vec<usize> = app_state
+            .0
+            .design
+            .presenter
+            .current_design
+            .helices
+            .keys()
+            .cloned()
+            .collect();
+        order.reverse();
+        let isometry = ultraviolet::Isometry2::new(
+            ultraviolet::Vec2::new(0., 10.),
+            ultraviolet::Rotor2::identity(),
+        );
+        let old_state = app_state
+            .apply_design_op(DesignOperation::SetHelixDisplayOrder {
+                order: order.clone(),
+                isometries: vec![(order[0], isometry)],
+            })
+            .unwrap();
+        assert!(old_state.is_some(), "The operation must be undoable");
+        app_state.update();
+        let design = &app_state.0.design.presenter.current_design;
+        assert_eq!(design.helix_display_order, order);
+        assert_eq!(design.helices[&order[0]].isometry2d, Some(isometry));
+    }
+
+    #[test]
+    fn short_new_strand_is_rejected() {
+        let mut app_state = one_xover();
+        app_state
+            .apply_design_op(DesignOperation::RequestStrandBuilders {
+                nucls: vec![Nucl {
+                    helix: 1,
+                    position: 10,
+                    forward: true,
+                }],
+            })
+            .unwrap();
+        app_state.update();
+        app_state
+            .apply_design_op(DesignOperation::MoveBuilders(12))
+            .unwrap();
+        app_state.update();
+        let min_length = app_state.get_strand_building_parameters().min_strand_length;
+        assert!(matches!(
+            app_state.reject_short_built_strands(),
+            Err(ErrOperation::StrandTooShort { length: 3, min_length: l }) if l == min_length
+        ));
+        app_state.update();
+        assert!(app_state.is_in_stable_state());
+        assert_eq!(app_state.0.design.presenter.current_design.strands.len(), 1);
+    }
+
+    #[test]
+    fn shortening_existing_strand_is_not_rejected() {
+        let mut app_state = one_xover();
+        app_state
+            .apply_design_op(DesignOperation::RequestStrandBuilders {
+                nucls: vec![Nucl {
+                    helix: 1,
+                    position: 5,
+                    forward: true,
+                }],
+            })
+            .unwrap();
+        app_state.update();
+        app_state
+            .apply_design_op(DesignOperation::MoveBuilders(1))
+            .unwrap();
+        app_state.update();
+        assert!(app_state.reject_short_built_strands().is_ok());
+        app_state.update();
+        let strand = app_state
+            .0
+            .design
+            .presenter
+            .current_design
+            .strands
+            .get(&0)
+            .expect("No strand 0");
+        assert_good_strand(strand, "[H1: 0 -> 1] [H2: 0 <- 5]");
+    }
+
     #[test]
     fn add_grid() {
         let mut app_state = AppState::default();
@@ -565,6 +848,7 @@ mod tests {
                 orientation: Rotor3::identity(),
                 grid_type: ensnano_design::grid::GridTypeDescr::Square,
                 invisible: false,
+                twist_offset: 0.,
             }))
             .unwrap();
         app_state.update();
@@ -580,6 +864,7 @@ mod tests {
                 orientation: Rotor3::identity(),
                 grid_type: ensnano_design::grid::GridTypeDescr::Square,
                 invisible: false,
+                twist_offset: 0.,
             }))
             .unwrap();
         app_state.update();
@@ -606,6 +891,7 @@ mod tests {
                 orientation: Rotor3::identity(),
                 grid_type: ensnano_design::grid::GridTypeDescr::Square,
                 invisible: false,
+                twist_offset: 0.,
             }))
             .unwrap();
         app_state.update();
@@ -620,6 +906,403 @@ mod tests {
         assert_eq!(app_state.0.design.presenter.current_design.helices.len(), 1)
     }
 
+    #[test]
+    fn duplicate_grid_copies_helices() {
+        let mut app_state = AppState::default();
+        app_state
+            .apply_design_op(DesignOperation::AddGrid(GridDescriptor {
+                position: Vec3::zero(),
+                orientation: Rotor3::identity(),
+                grid_type: ensnano_design::grid::GridTypeDescr::Square,
+                invisible: false,
+                twist_offset: 0.,
+            }))
+            .unwrap();
+        app_state.update();
+        for x in 0..2 {
+            app_state
+                .apply_design_op(DesignOperation::AddGridHelix {
+                    position: GridPosition::from_grid_id_x_y(0, x, 0),
+                    start: 0,
+                    length: 0,
+                })
+                .unwrap();
+            app_state.update();
+        }
+        let offset = Vec3::new(0., 0., 10.);
+        app_state
+            .apply_design_op(DesignOperation::DuplicateGrid { grid_id: 0, offset })
+            .unwrap();
+        app_state.update();
+        let design = &app_state.0.design.presenter.current_design;
+        assert_eq!(design.grids.len(), 2);
+        assert_eq!(design.grids[1].position, offset);
+        assert_eq!(design.helices.len(), 4);
+        let on_new_grid = design
+            .helices
+            .values()
+            .filter(|h| h.grid_position.map(|pos| pos.grid) == Some(1))
+            .count();
+        assert_eq!(on_new_grid, 2);
+    }
+
+    #[test]
+    fn mirror_grid_reflects_grid_and_helices() {
+        let mut app_state = AppState::default();
+        let position = Vec3::new(1., 2., 5.);
+        app_state
+            .apply_design_op(DesignOperation::AddGrid(GridDescriptor {
+                position,
+                orientation: Rotor3::identity(),
+                grid_type: ensnano_design::grid::GridTypeDescr::Square,
+                invisible: false,
+                twist_offset: 0.,
+            }))
+            .unwrap();
+        app_state.update();
+        app_state
+            .apply_design_op(DesignOperation::AddGridHelix {
+                position: GridPosition::from_grid_id_x_y(0, 1, 0),
+                start: 0,
+                length: 0,
+            })
+            .unwrap();
+        app_state.update();
+        app_state
+            .apply_design_op(DesignOperation::MirrorGrid {
+                grid_id: 0,
+                axis: ensnano_interactor::MirrorAxis::XY,
+            })
+            .unwrap();
+        app_state.update();
+        let design = &app_state.0.design.presenter.current_design;
+        assert_eq!(design.grids.len(), 2);
+        assert_eq!(design.grids[1].position, Vec3::new(1., 2., -5.));
+        assert_eq!(design.helices.len(), 2);
+        let original = design.helices.values().next().unwrap();
+        let mirrored = design.helices.values().last().unwrap();
+        assert_eq!(mirrored.grid_position.map(|pos| pos.grid), Some(1));
+        assert!(
+            (mirrored.position - ensnano_interactor::MirrorAxis::XY.reflect(original.position))
+                .mag()
+                < 1e-5
+        );
+        // The helix axis, which lies in the mirror plane, is reversed in the new grid
+        let original_axis = Vec3::unit_x().rotated_by(design.grids[0].orientation);
+        let mirrored_axis = Vec3::unit_x().rotated_by(design.grids[1].orientation);
+        assert!((original_axis + mirrored_axis).mag() < 1e-5);
+    }
+
+    #[test]
+    fn reorder_strands_by_length() {
+        let mut app_state = design_for_sequence_testing();
+        let nb_strands = app_state.0.design.presenter.current_design.strands.len();
+        app_state
+            .apply_design_op(DesignOperation::ReorderStrands(
+                ensnano_interactor::SortKey::ByLength,
+            ))
+            .unwrap();
+        app_state.update();
+        let design = &app_state.0.design.presenter.current_design;
+        assert_eq!(design.strands.len(), nb_strands);
+        let ids: Vec<usize> = design.strands.keys().cloned().collect();
+        assert_eq!(ids, (0..nb_strands).collect::<Vec<_>>());
+        let lengths: Vec<usize> = design.strands.values().map(|s| s.length()).collect();
+        assert!(lengths.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn reorder_strands_renumbers_selection_and_groups() {
+        let mut app_state = design_for_sequence_testing();
+        let design = &app_state.0.design.presenter.current_design;
+        let longest = *design
+            .strands
+            .iter()
+            .max_by_key(|(_, s)| s.length())
+            .unwrap()
+            .0;
+        let last_id = design.strands.len() - 1;
+        let selection = vec![Selection::Strand(0, longest as u32)];
+        app_state = app_state.with_selection(selection, None);
+        app_state
+            .apply_design_op(DesignOperation::SetSelectionGroup {
+                name: String::from("longest"),
+                elements: vec![DnaElementKey::Strand(longest)],
+            })
+            .unwrap();
+        app_state
+            .apply_design_op(DesignOperation::ReorderStrands(
+                ensnano_interactor::SortKey::ByLength,
+            ))
+            .unwrap();
+        app_state.update();
+        let renumbered = vec![Selection::Strand(0, last_id as u32)];
+        assert_eq!(app_state.get_selection().as_ref(), renumbered.as_slice());
+        assert_eq!(
+            app_state.get_design_reader().get_selection_group("longest"),
+            Some(renumbered)
+        );
+    }
+
+    #[test]
+    fn recalled_selection_group_skips_deleted_elements() {
+        let mut app_state = design_for_sequence_testing();
+        let s_id = *app_state
+            .0
+            .design
+            .presenter
+            .current_design
+            .strands
+            .keys()
+            .next()
+            .unwrap();
+        app_state
+            .apply_design_op(DesignOperation::SetSelectionGroup {
+                name: String::from("group"),
+                elements: vec![DnaElementKey::Strand(s_id), DnaElementKey::Helix(1000)],
+            })
+            .unwrap();
+        app_state.update();
+        assert_eq!(
+            app_state.get_design_reader().get_selection_group("group"),
+            Some(vec![Selection::Strand(0, s_id as u32)])
+        );
+        app_state
+            .apply_design_op(DesignOperation::RmStrands {
+                strand_ids: vec![s_id],
+            })
+            .unwrap();
+        app_state.update();
+        assert_eq!(
+            app_state.get_design_reader().get_selection_group("group"),
+            Some(vec![])
+        );
+        assert_eq!(
+            app_state.get_design_reader().get_selection_group("unknown"),
+            None
+        );
+    }
+
+    #[test]
+    fn recolor_strands_gives_different_colors_to_neighbour_strands() {
+        let mut app_state = design_for_sequence_testing();
+        let palette = ensnano_interactor::ColorBlindPalette::OkabeIto;
+        app_state
+            .apply_design_op(DesignOperation::RecolorStrands { palette })
+            .unwrap();
+        app_state.update();
+        let design = &app_state.0.design.presenter.current_design;
+        let colors: Vec<u32> = design.strands.values().map(|s| s.color).collect();
+        assert_eq!(colors.len(), 3);
+        assert!(colors
+            .iter()
+            .all(|c| palette.colors().contains(&(c & 0xFFFFFF))));
+        assert_ne!(colors[0], colors[1]);
+        assert_ne!(colors[0], colors[2]);
+        assert_ne!(colors[1], colors[2]);
+    }
+
+    #[test]
+    fn add_loop_in_the_middle_of_a_domain() {
+        let mut app_state = one_xover();
+        app_state
+            .apply_design_op(DesignOperation::AddLoop {
+                at: Nucl {
+                    helix: 1,
+                    position: 2,
+                    forward: true,
+                },
+                length: 4,
+            })
+            .unwrap();
+        app_state.update();
+        let design = &app_state.0.design.presenter.current_design;
+        assert_eq!(design.strands.len(), 1);
+        let strand = design.strands.values().next().unwrap();
+        assert_eq!(strand.length(), 16);
+        assert_eq!(strand.domains.len(), 4);
+        assert_eq!(strand.junctions.len(), 4);
+        assert!(matches!(strand.domains[1], Domain::Insertion(4)));
+        assert_eq!(strand.xovers().len(), 1);
+    }
+
+    #[test]
+    fn add_loop_before_a_xover() {
+        let mut app_state = one_xover();
+        app_state
+            .apply_design_op(DesignOperation::AddLoop {
+                at: Nucl {
+                    helix: 1,
+                    position: 5,
+                    forward: true,
+                },
+                length: 3,
+            })
+            .unwrap();
+        app_state.update();
+        let design = &app_state.0.design.presenter.current_design;
+        let strand = design.strands.values().next().unwrap();
+        assert_eq!(strand.length(), 15);
+        assert_eq!(strand.domains.len(), 3);
+        assert!(matches!(strand.domains[1], Domain::Insertion(3)));
+        assert_eq!(strand.junctions[0], DomainJunction::Adjacent);
+        assert!(matches!(
+            strand.junctions[1],
+            DomainJunction::IdentifiedXover(_)
+        ));
+    }
+
+    /// Cut the strand of `one_xover` after each nucleotide of `positions` on helix 1
+    fn cut_one_xover(positions: &[isize]) -> AppState {
+        let mut app_state = one_xover();
+        for position in positions.iter() {
+            let nucl = Nucl {
+                helix: 1,
+                position: *position,
+                forward: true,
+            };
+            let s_id = app_state
+                .0
+                .design
+                .presenter
+                .current_design
+                .get_strand_nucl(&nucl)
+                .unwrap();
+            app_state
+                .apply_design_op(DesignOperation::Cut { nucl, s_id })
+                .unwrap();
+            app_state.update();
+        }
+        app_state
+    }
+
+    fn strand_of_nucl(app_state: &AppState, position: isize) -> usize {
+        app_state
+            .0
+            .design
+            .presenter
+            .current_design
+            .get_strand_nucl(&Nucl {
+                helix: 1,
+                position,
+                forward: true,
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn strand_modifications_follow_the_ends_when_cutting() {
+        use ensnano_design::StrandModification;
+        let mut app_state = one_xover();
+        let s_id = strand_of_nucl(&app_state, 2);
+        for &(end, modification) in [
+            (StrandEnd::FivePrime, StrandModification::Biotin),
+            (StrandEnd::ThreePrime, StrandModification::Cy3),
+        ]
+        .iter()
+        {
+            app_state
+                .apply_design_op(DesignOperation::SetStrandModification {
+                    s_id,
+                    end,
+                    modification: Some(modification),
+                })
+                .unwrap();
+            app_state.update();
+        }
+        let strand = &app_state.0.design.presenter.current_design.strands[&s_id];
+        let prime5 = strand.get_5prime();
+        let prime3 = strand.get_3prime();
+        let nucl = Nucl::new(1, 2, true);
+        app_state
+            .apply_design_op(DesignOperation::Cut { nucl, s_id })
+            .unwrap();
+        app_state.update();
+        let design = &app_state.0.design.presenter.current_design;
+        assert_eq!(design.strands.len(), 2);
+        for strand in design.strands.values() {
+            let expected_5prime =
+                Some(StrandModification::Biotin).filter(|_| strand.get_5prime() == prime5);
+            let expected_3prime =
+                Some(StrandModification::Cy3).filter(|_| strand.get_3prime() == prime3);
+            assert_eq!(strand.modification_5prime, expected_5prime);
+            assert_eq!(strand.modification_3prime, expected_3prime);
+        }
+        app_state
+            .apply_design_op(DesignOperation::SetStrandModification {
+                s_id,
+                end: StrandEnd::FivePrime,
+                modification: None,
+            })
+            .unwrap();
+        app_state.update();
+        let design = &app_state.0.design.presenter.current_design;
+        assert!(design.strands[&s_id].modification_5prime.is_none());
+    }
+
+    #[test]
+    fn connect_adjacent_strand_ends() {
+        let mut app_state = cut_one_xover(&[2]);
+        assert_eq!(app_state.0.design.presenter.current_design.strands.len(), 2);
+        let strand_a = strand_of_nucl(&app_state, 2);
+        let strand_b = strand_of_nucl(&app_state, 3);
+        app_state
+            .apply_design_op(DesignOperation::ConnectStrands {
+                strand_a: strand_b,
+                end_a: StrandEnd::FivePrime,
+                strand_b: strand_a,
+                end_b: StrandEnd::ThreePrime,
+            })
+            .unwrap();
+        app_state.update();
+        let design = &app_state.0.design.presenter.current_design;
+        assert_eq!(design.strands.len(), 1);
+        assert_eq!(design.strands.values().next().unwrap().length(), 12);
+    }
+
+    #[test]
+    fn connect_strand_ends_too_far_apart() {
+        let mut app_state = cut_one_xover(&[1, 4]);
+        assert_eq!(app_state.0.design.presenter.current_design.strands.len(), 3);
+        let strand_a = strand_of_nucl(&app_state, 1);
+        let strand_b = strand_of_nucl(&app_state, 5);
+        let result = app_state.apply_design_op(DesignOperation::ConnectStrands {
+            strand_a,
+            end_a: StrandEnd::ThreePrime,
+            strand_b,
+            end_b: StrandEnd::FivePrime,
+        });
+        assert!(matches!(result, Err(ErrOperation::EndsTooFarApart)));
+    }
+
+    #[test]
+    fn flip_strand_reverses_direction() {
+        let mut app_state = one_xover();
+        app_state
+            .apply_design_op(DesignOperation::FlipStrand { strand_id: 0 })
+            .unwrap();
+        app_state.update();
+        let strand = &app_state.0.design.presenter.current_design.strands[&0];
+        assert_eq!(strand.length(), 12);
+        assert_eq!(
+            strand.get_5prime(),
+            Some(Nucl {
+                helix: 2,
+                position: 0,
+                forward: true,
+            })
+        );
+        assert_eq!(
+            strand.get_3prime(),
+            Some(Nucl {
+                helix: 1,
+                position: 0,
+                forward: false,
+            })
+        );
+        assert_eq!(strand.xovers().len(), 1);
+    }
+
     #[test]
     fn copy_creates_clipboard() {
         let mut app_state = pastable_design();
@@ -933,6 +1616,41 @@ mod tests {
         assert_eq!(app_state.is_pasting(), PastingStatus::None);
     }
 
+    #[test]
+    fn breaking_the_scaffold_opens_it_at_the_given_nucleotide() {
+        let mut app_state = one_xover();
+        app_state
+            .apply_design_op(DesignOperation::Xover {
+                prime5_id: 0,
+                prime3_id: 0,
+            })
+            .unwrap();
+        app_state.update();
+        app_state
+            .apply_design_op(DesignOperation::SetScaffoldId(Some(0)))
+            .unwrap();
+        app_state.update();
+        let at = Nucl {
+            helix: 1,
+            position: 3,
+            forward: true,
+        };
+        app_state
+            .apply_design_op(DesignOperation::BreakScaffoldAt { at })
+            .unwrap();
+        app_state.update();
+        let design = &app_state.0.design.presenter.current_design;
+        let scaffold = design.strands.get(&0).unwrap();
+        assert!(!scaffold.cyclic);
+        assert_eq!(scaffold.get_5prime(), Some(at));
+        assert_eq!(scaffold.get_3prime(), Some(at.left()));
+        assert_eq!(scaffold.length(), 12);
+        assert_eq!(
+            ensnano_design::check_scaffold_continuity(design),
+            ensnano_design::ScaffoldContinuityResult::SinglePath
+        );
+    }
+
     #[test]
     fn correct_staples_no_scaffold_shift() {
         let mut app_state = design_for_sequence_testing();
@@ -996,10 +1714,92 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn set_strand_sequence() {
+        let mut app_state = one_xover();
+        let s_id = app_state
+            .get_design_reader()
+            .get_id_of_strand_containing_nucl(&Nucl {
+                helix: 1,
+                position: 0,
+                forward: true,
+            })
+            .unwrap();
+        app_state
+            .apply_design_op(DesignOperation::SetStrandSequence {
+                s_id,
+                sequence: "ATGC".to_string(),
+            })
+            .unwrap();
+        app_state.update();
+        let reader = app_state.get_design_reader();
+        let strand = reader.presenter.current_design.strands.get(&s_id).unwrap();
+        assert_eq!(strand.sequence.as_deref(), Some("ATGC"));
+    }
+
+    #[test]
+    fn set_sequence_of_missing_strand_fails() {
+        let mut app_state = one_xover();
+        assert!(app_state
+            .apply_design_op(DesignOperation::SetStrandSequence {
+                s_id: 1000,
+                sequence: "ATGC".to_string(),
+            })
+            .is_err());
+    }
+
+    #[test]
+    fn oxdna_export_can_be_read_back() {
+        let app_state = one_xover();
+        let directory = crate::utils::unique_temp_dir("ensnano_oxdna_trajectory_test").unwrap();
+        let (configuration, topology) = app_state.oxdna_export(&directory).unwrap();
+        let trajectory = app_state
+            .oxdna_trajectory_reader()
+            .read(&topology, &configuration)
+            .unwrap();
+        std::fs::remove_dir_all(&directory).unwrap();
+        assert_eq!(trajectory.nb_frames(), 1);
+        assert!(!trajectory.nucls().is_empty());
+        let reader = app_state.get_design_reader();
+        let design = &reader.presenter.current_design;
+        let parameters = design.parameters.unwrap_or_default();
+        for nucl in trajectory.nucls() {
+            let expected =
+                design.helices[&nucl.helix].space_pos(&parameters, nucl.position, nucl.forward);
+            let position = trajectory.current_position(nucl).unwrap();
+            assert!((position - expected).mag() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn helices_fitted_to_their_export_do_not_move() {
+        let app_state = one_xover();
+        let directory = crate::utils::unique_temp_dir("ensnano_relaxation_test").unwrap();
+        let (configuration, topology) = app_state.oxdna_export(&directory).unwrap();
+        let trajectory = app_state
+            .oxdna_trajectory_reader()
+            .read(&topology, &configuration)
+            .unwrap();
+        std::fs::remove_dir_all(&directory).unwrap();
+        let placements = app_state.helices_fitted_to_trajectory(&trajectory);
+        assert!(!placements.is_empty());
+        let reader = app_state.get_design_reader();
+        let design = &reader.presenter.current_design;
+        for (h_id, position, orientation) in placements {
+            let helix = &design.helices[&h_id];
+            assert!((position - helix.position).mag() < 1e-3);
+            for axis in [Vec3::unit_x(), Vec3::unit_y()].iter() {
+                let fitted_axis = axis.rotated_by(orientation);
+                assert!((fitted_axis - axis.rotated_by(helix.orientation)).mag() < 1e-3);
+            }
+        }
+    }
 }
 
 pub enum SimulationTarget {
     Grids,
     Helices,
     Roll { target_helices: Option<Vec<usize>> },
+    OptimizeRolls { target_helices: Option<Vec<usize>> },
 }
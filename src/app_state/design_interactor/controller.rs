@@ -23,22 +23,24 @@ use ensnano_design::{
     grid::{Edge, GridDescriptor, GridPosition, Hyperboloid},
     group_attributes::GroupPivot,
     mutate_in_arc, CameraId, Design, Domain, DomainJunction, Helix, Nucl, Strand,
+    StrandModification,
 };
 use ensnano_interactor::{operation::Operation, HyperboloidOperation, SimulationState};
 use ensnano_interactor::{
-    DesignOperation, DesignRotation, DesignTranslation, DomainIdentifier, IsometryTarget,
-    NeighbourDescriptor, NeighbourDescriptorGiver, Selection, StrandBuilder,
+    ColorBlindPalette, DesignOperation, DesignRotation, DesignTranslation, DomainIdentifier,
+    IsometryTarget, MirrorAxis, NeighbourDescriptor, NeighbourDescriptorGiver, Selection, SortKey,
+    StrandBuilder, StrandEnd,
 };
-use ensnano_organizer::GroupId;
+use ensnano_organizer::{GroupId, OrganizerTree};
 use std::borrow::Cow;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::sync::{Arc, Mutex};
 
 use clipboard::{PastedStrand, StrandClipboard};
 
 use self::simulations::{
     GridSystemInterface, GridsSystemThread, HelixSystemInterface, HelixSystemThread,
-    PhysicalSystem, RollInterface,
+    PhysicalSystem, RollInterface, RollOptimizerInterface, ScaffoldOptimizer,
 };
 
 use super::grid_data::GridManager;
@@ -50,7 +52,9 @@ pub use clipboard::CopyOperation;
 
 mod shift_optimization;
 use ahash::AHashMap;
-pub use shift_optimization::{ShiftOptimizationResult, ShiftOptimizerReader};
+pub use shift_optimization::{
+    ShiftOptimization, ShiftOptimizationOk, ShiftOptimizationResult, ShiftOptimizerReader,
+};
 
 mod simulations;
 pub use simulations::{
@@ -96,6 +100,9 @@ impl Controller {
         log::debug!("applicable");
         match operation {
             DesignOperation::RecolorStaples => Ok(self.ok_apply(Self::recolor_stapples, design)),
+            DesignOperation::RecolorStrands { palette } => {
+                Ok(self.ok_apply(|c, d| c.recolor_strands(d, palette), design))
+            }
             DesignOperation::SetScaffoldSequence { sequence, shift } => Ok(self.ok_apply(
                 |ctrl, design| ctrl.set_scaffold_sequence(design, sequence, shift),
                 design,
@@ -129,6 +136,10 @@ impl Controller {
             DesignOperation::SetIsometry { helix, isometry } => {
                 Ok(self.ok_apply(|c, d| c.set_isometry(d, helix, isometry), design))
             }
+            DesignOperation::SetHelixDisplayOrder { order, isometries } => Ok(self.ok_apply(
+                |c, d| c.set_helix_display_order(d, order, isometries),
+                design,
+            )),
             DesignOperation::RotateHelices {
                 helices,
                 center,
@@ -147,6 +158,9 @@ impl Controller {
                 self.apply(|c, d| c.move_strand_builders(d, n), design)
             }
             DesignOperation::Cut { nucl, .. } => self.apply(|c, d| c.cut(d, nucl), design),
+            DesignOperation::BreakScaffoldAt { at } => {
+                self.apply(|c, d| c.break_scaffold_at(d, at), design)
+            }
             DesignOperation::AddGridHelix {
                 position,
                 length,
@@ -174,6 +188,7 @@ impl Controller {
             DesignOperation::RmHelices { h_ids } => {
                 self.apply(|c, d| c.delete_helices(d, h_ids), design)
             }
+            DesignOperation::CleanupOrphans => self.apply(|c, d| c.cleanup_orphans(d), design),
             DesignOperation::RmXovers { xovers } => {
                 self.apply(|c, d| c.delete_xovers(d, &xovers), design)
             }
@@ -203,12 +218,92 @@ impl Controller {
             DesignOperation::FlipAnchors { nucls } => {
                 self.apply(|c, d| c.flip_anchors(d, nucls), design)
             }
+            DesignOperation::AddAnchor(nucl) => Ok(self.ok_apply(
+                |_, mut d| {
+                    d.anchors.insert(nucl);
+                    d
+                },
+                design,
+            )),
+            DesignOperation::RemoveAnchor(nucl) => Ok(self.ok_apply(
+                |_, mut d| {
+                    d.anchors.remove(&nucl);
+                    d
+                },
+                design,
+            )),
+            DesignOperation::DuplicateGrid { grid_id, offset } => {
+                self.apply(|c, d| c.duplicate_grid(d, grid_id, offset), design)
+            }
+            DesignOperation::AddLoop { at, length } => {
+                self.apply(|c, d| c.add_loop(d, at, length), design)
+            }
+            DesignOperation::CreateHairpin {
+                at,
+                stem_length,
+                loop_length,
+            } => self.apply(
+                |c, d| c.create_hairpin(d, at, stem_length, loop_length),
+                design,
+            ),
+            DesignOperation::ConnectStrands {
+                strand_a,
+                end_a,
+                strand_b,
+                end_b,
+            } => self.apply(
+                |c, d| c.connect_strands(d, (strand_a, end_a), (strand_b, end_b)),
+                design,
+            ),
+            DesignOperation::AddTorusKnot {
+                p,
+                q,
+                radius,
+                tube_radius,
+            } => self.apply(
+                |c, d| {
+                    c.add_torus_knot(
+                        d,
+                        TorusKnot {
+                            p,
+                            q,
+                            radius,
+                            tube_radius,
+                        },
+                    )
+                },
+                design,
+            ),
+            DesignOperation::FlipStrand { strand_id } => {
+                self.apply(|c, d| c.flip_strand(d, strand_id), design)
+            }
+            DesignOperation::MirrorGrid { grid_id, axis } => {
+                self.apply(|c, d| c.mirror_grid(d, grid_id, axis), design)
+            }
+            DesignOperation::MergeDesign {
+                design: merged,
+                offset,
+                orientation,
+            } => Ok(self.ok_apply(
+                |_, d| merge_design(d, *merged.0, offset, orientation),
+                design,
+            )),
+            DesignOperation::AnnotateNucleotide { nucl, label } => Ok(self.ok_apply(
+                |c, d| c.annotate_nucleotide(d, nucl, label),
+                design,
+            )),
+            DesignOperation::ReorderStrands(key) => {
+                Ok(self.ok_apply(|c, d| c.reorder_strands(d, key), design))
+            }
             DesignOperation::RmGrid(_) => Err(ErrOperation::NotImplemented), // TODO
             DesignOperation::ChangeSequence { .. } => Err(ErrOperation::NotImplemented), // TODO
             DesignOperation::CleanDesign => Err(ErrOperation::NotImplemented), // TODO
             DesignOperation::AttachHelix { helix, grid, x, y } => {
                 self.apply(|c, d| c.attach_helix(d, helix, grid, x, y), design)
             }
+            DesignOperation::PlaceHelices(placements) => {
+                self.apply(|c, d| c.place_helices(d, placements), design)
+            }
             DesignOperation::SetOrganizerTree(tree) => Ok(self.ok_apply(
                 |_, mut d| {
                     d.organizer_tree = Some(Arc::new(tree));
@@ -216,16 +311,41 @@ impl Controller {
                 },
                 design,
             )),
+            DesignOperation::SetSelectionGroup { name, elements } => Ok(self.ok_apply(
+                |_, mut d| {
+                    d.selection_groups.insert(name, elements);
+                    d
+                },
+                design,
+            )),
+            DesignOperation::DeleteSelectionGroup(name) => Ok(self.ok_apply(
+                |_, mut d| {
+                    d.selection_groups.remove(&name);
+                    d
+                },
+                design,
+            )),
             DesignOperation::SetStrandName { s_id, name } => {
                 self.apply(|c, d| c.change_strand_name(d, s_id, name), design)
             }
+            DesignOperation::SetStrandSequence { s_id, sequence } => {
+                self.apply(|c, d| c.change_strand_sequence(d, s_id, sequence), design)
+            }
+            DesignOperation::SetStrandModification {
+                s_id,
+                end,
+                modification,
+            } => self.apply(
+                |c, d| c.set_strand_modification(d, s_id, end, modification),
+                design,
+            ),
             DesignOperation::SetGroupPivot { group_id, pivot } => {
                 self.apply(|c, d| c.set_group_pivot(d, group_id, pivot), design)
             }
             DesignOperation::CreateNewCamera {
                 position,
                 orientation,
-            } => Ok(self.ok_apply(|c, d| c.create_camera(d, position, orientation), design)),
+            } => self.apply(|c, d| c.create_camera(d, position, orientation), design),
             DesignOperation::DeleteCamera(cam_id) => {
                 self.apply(|c, d| c.delete_camera(d, cam_id), design)
             }
@@ -253,6 +373,13 @@ impl Controller {
                 |c, d| c.set_grid_orientation(d, grid_id, orientation),
                 design,
             ),
+            DesignOperation::SetGridTwistOffset {
+                grid_id,
+                twist_offset,
+            } => self.apply(
+                |c, d| c.set_grid_twist_offset(d, grid_id, twist_offset),
+                design,
+            ),
         }
     }
 
@@ -382,6 +509,21 @@ impl Controller {
                     initial_design: AddressPointer::new(design.clone()),
                 };
             }
+            SimulationOperation::StartRollOptimization {
+                presenter,
+                target_helices,
+                reader,
+            } => {
+                if self.is_in_persistant_state().is_transitory() {
+                    return Err(ErrOperation::IncompatibleState);
+                }
+                let interface =
+                    ScaffoldOptimizer::minimise_crossover_strain(presenter, target_helices, reader);
+                ret.state = ControllerState::OptimizingRolls {
+                    interface,
+                    initial_design: AddressPointer::new(design.clone()),
+                };
+            }
             SimulationOperation::UpdateParameters { new_parameters } => {
                 if let ControllerState::Simulating { interface, .. } = &ret.state {
                     interface.lock().unwrap().parameters_update = Some(new_parameters);
@@ -407,6 +549,8 @@ impl Controller {
                     ret.state = ControllerState::Normal;
                 } else if let ControllerState::Rolling { .. } = &ret.state {
                     ret.state = ControllerState::Normal
+                } else if let ControllerState::OptimizingRolls { .. } = &ret.state {
+                    ret.state = ControllerState::Normal
                 }
             }
             SimulationOperation::Reset => {
@@ -420,6 +564,12 @@ impl Controller {
         Ok((self.return_design(design), ret))
     }
 
+    fn annotate_nucleotide(&mut self, mut design: Design, nucl: Nucl, label: String) -> Design {
+        self.update_state_and_design(&mut design);
+        design.annotate_nucleotide(nucl, label);
+        design
+    }
+
     fn change_strand_name(
         &mut self,
         mut design: Design,
@@ -435,6 +585,39 @@ impl Controller {
         Ok(design)
     }
 
+    fn change_strand_sequence(
+        &mut self,
+        mut design: Design,
+        s_id: usize,
+        sequence: String,
+    ) -> Result<Design, ErrOperation> {
+        let strand = design
+            .strands
+            .get_mut(&s_id)
+            .ok_or(ErrOperation::StrandDoesNotExist(s_id))?;
+        self.state = ControllerState::ChangingStrandSequence { strand_id: s_id };
+        strand.sequence = Some(Cow::Owned(sequence));
+        Ok(design)
+    }
+
+    fn set_strand_modification(
+        &mut self,
+        mut design: Design,
+        s_id: usize,
+        end: StrandEnd,
+        modification: Option<StrandModification>,
+    ) -> Result<Design, ErrOperation> {
+        let strand = design
+            .strands
+            .get_mut(&s_id)
+            .ok_or(ErrOperation::StrandDoesNotExist(s_id))?;
+        match end {
+            StrandEnd::FivePrime => strand.modification_5prime = modification,
+            StrandEnd::ThreePrime => strand.modification_3prime = modification,
+        }
+        Ok(design)
+    }
+
     fn add_hyperboloid_helices(
         &mut self,
         design: &mut Design,
@@ -477,6 +660,48 @@ impl Controller {
         design.helices = Arc::new(new_helices);
     }
 
+    fn add_torus_knot(
+        &mut self,
+        mut design: Design,
+        knot: TorusKnot,
+    ) -> Result<Design, ErrOperation> {
+        if knot.p == 0 || knot.q == 0 || knot.radius <= knot.tube_radius {
+            return Err(ErrOperation::InvalidTorusKnot);
+        }
+        let parameters = design.parameters.unwrap_or_default();
+        let mut key = design.helices.keys().max().map(|m| m + 1).unwrap_or(0);
+        let mut new_helices = BTreeMap::clone(design.helices.as_ref());
+        let mut domains = Vec::new();
+        for (h, length) in knot.to_helices(&parameters) {
+            new_helices.insert(key, Arc::new(h));
+            domains.push(Domain::HelixDomain(HelixInterval {
+                helix: key,
+                start: 0,
+                end: length as isize,
+                forward: true,
+                sequence: None,
+            }));
+            key += 1;
+        }
+        design.helices = Arc::new(new_helices);
+        let junctions = ensnano_design::read_junctions(&domains, true);
+        let s_id = design.strands.keys().max().map(|m| m + 1).unwrap_or(0);
+        design.strands.insert(
+            s_id,
+            Strand {
+                domains,
+                junctions,
+                cyclic: true,
+                color: crate::consts::SCAFFOLD_COLOR,
+                ..Default::default()
+            },
+        );
+        if design.scaffold_id.is_none() {
+            design.scaffold_id = Some(s_id);
+        }
+        Ok(design)
+    }
+
     fn set_roll_helices(
         &mut self,
         mut design: Design,
@@ -757,6 +982,46 @@ impl Controller {
         }
     }
 
+    /// Check that the strands created by the current building of strands are at least
+    /// `min_length` long. The strands that existed before the building are not checked.
+    pub fn check_built_strands(
+        &self,
+        design: &Design,
+        min_length: usize,
+    ) -> Result<(), ErrOperation> {
+        if let ControllerState::BuildingStrand { new_strands, .. } = &self.state {
+            let shortest = new_strands
+                .iter()
+                .filter_map(|s_id| design.strands.get(s_id))
+                .map(|strand| strand.length())
+                .min();
+            if let Some(length) = shortest.filter(|length| *length < min_length) {
+                return Err(ErrOperation::StrandTooShort { length, min_length });
+            }
+        }
+        Ok(())
+    }
+
+    /// Stop the current building of strands and remove the strands that it created
+    pub fn reject_built_strands(&self) -> (OkOperation, Self) {
+        let mut new_controller = self.clone();
+        if let ControllerState::BuildingStrand {
+            initial_design,
+            new_strands,
+            ..
+        } = &self.state
+        {
+            let mut design = initial_design.clone_inner();
+            for s_id in new_strands.iter() {
+                design.strands.remove(s_id);
+            }
+            new_controller.state = ControllerState::Normal;
+            (OkOperation::Replace(design), new_controller)
+        } else {
+            (OkOperation::NoOp, new_controller)
+        }
+    }
+
     pub fn notify(&self, notification: InteractorNotification) -> Self {
         let mut new_interactor = self.clone();
         match notification {
@@ -764,6 +1029,11 @@ impl Controller {
             InteractorNotification::NewSelection => {
                 new_interactor.state = self.state.acknowledge_new_selection()
             }
+            InteractorNotification::ShiftOptimizationAborted => {
+                if let ControllerState::OptimizingScaffoldPosition = self.state {
+                    new_interactor.state = ControllerState::Normal
+                }
+            }
         }
         new_interactor
     }
@@ -802,6 +1072,9 @@ impl Controller {
             ControllerState::BuildingStrand { initializing, .. } => {
                 if let DesignOperation::MoveBuilders(_) = operation {
                     OperationCompatibility::Compatible
+                } else if let DesignOperation::GeneralXover { .. } = operation {
+                    // A cross-over can end the building of a strand
+                    OperationCompatibility::Compatible
                 } else {
                     if initializing {
                         OperationCompatibility::FinishFirst
@@ -830,6 +1103,19 @@ impl Controller {
                     OperationCompatibility::FinishFirst
                 }
             }
+            ControllerState::ChangingStrandSequence {
+                strand_id: current_s_id,
+            } => {
+                if let DesignOperation::SetStrandSequence { s_id, .. } = operation {
+                    if current_s_id == *s_id {
+                        OperationCompatibility::Compatible
+                    } else {
+                        OperationCompatibility::FinishFirst
+                    }
+                } else {
+                    OperationCompatibility::FinishFirst
+                }
+            }
             _ => OperationCompatibility::Incompatible,
         }
     }
@@ -862,10 +1148,21 @@ impl Controller {
             ControllerState::WithPausedSimulation { .. } => SimulationState::Paused,
             ControllerState::SimulatingGrids { .. } => SimulationState::RigidGrid,
             ControllerState::Rolling { .. } => SimulationState::Rolling,
+            ControllerState::OptimizingRolls { .. } => SimulationState::OptimizingRolls,
             _ => SimulationState::None,
         }
     }
 
+    /// Return the number of iterations performed by the optimization of the helices' rolls if it
+    /// is running.
+    pub(super) fn get_roll_optimization_iteration(&self) -> Option<usize> {
+        if let ControllerState::OptimizingRolls { interface, .. } = &self.state {
+            Some(interface.lock().unwrap().iteration)
+        } else {
+            None
+        }
+    }
+
     pub(super) fn is_in_persistant_state(&self) -> StatePersitance {
         match self.state {
             ControllerState::Normal => StatePersitance::Persistant,
@@ -875,6 +1172,7 @@ impl Controller {
             ControllerState::WithPausedSimulation { .. } => StatePersitance::Persistant,
             ControllerState::SettingRollHelices { .. } => StatePersitance::NeedFinish,
             ControllerState::ChangingStrandName { .. } => StatePersitance::NeedFinish,
+            ControllerState::ChangingStrandSequence { .. } => StatePersitance::NeedFinish,
             _ => StatePersitance::Transitory,
         }
     }
@@ -955,11 +1253,121 @@ impl Controller {
         design
     }
 
-    fn create_camera(&mut self, mut design: Design, position: Vec3, orientation: Rotor3) -> Design {
-        design.add_camera(position, orientation);
+    fn duplicate_grid(
+        &mut self,
+        mut design: Design,
+        grid_id: usize,
+        offset: Vec3,
+    ) -> Result<Design, ErrOperation> {
+        let mut new_grid = design
+            .grids
+            .get(grid_id)
+            .cloned()
+            .ok_or(ErrOperation::GridDoesNotExist(grid_id))?;
+        new_grid.position += offset;
+        let new_grid_id = design.grids.len();
+        let mut new_grids = Vec::clone(design.grids.as_ref());
+        new_grids.push(new_grid);
+        design.grids = Arc::new(new_grids);
+
+        let mut new_helices = BTreeMap::clone(design.helices.as_ref());
+        let mut helix_id = new_helices.keys().last().map(|k| k + 1).unwrap_or(0);
+        for h in design.helices.values() {
+            if let Some(grid_position) = h.grid_position.filter(|pos| pos.grid == grid_id) {
+                let mut copy = Helix::clone(h.as_ref());
+                copy.position += offset;
+                copy.grid_position = Some(GridPosition {
+                    grid: new_grid_id,
+                    ..grid_position
+                });
+                // The 2d position of the copy will be chosen by the flatscene
+                copy.isometry2d = None;
+                new_helices.insert(helix_id, Arc::new(copy));
+                helix_id += 1;
+            }
+        }
+        design.helices = Arc::new(new_helices);
+        Ok(design)
+    }
+
+    fn mirror_grid(
+        &mut self,
+        mut design: Design,
+        grid_id: usize,
+        axis: MirrorAxis,
+    ) -> Result<Design, ErrOperation> {
+        let mut new_grid = design
+            .grids
+            .get(grid_id)
+            .cloned()
+            .ok_or(ErrOperation::GridDoesNotExist(grid_id))?;
+        new_grid.position = axis.reflect(new_grid.position);
+        new_grid.orientation = axis.reflect_orientation(new_grid.orientation);
+        let new_grid_id = design.grids.len();
+        let mut new_grids = Vec::clone(design.grids.as_ref());
+        new_grids.push(new_grid);
+        design.grids = Arc::new(new_grids);
+
+        let mut new_helices = BTreeMap::clone(design.helices.as_ref());
+        let mut helix_id = new_helices.keys().last().map(|k| k + 1).unwrap_or(0);
+        for h in design.helices.values() {
+            if let Some(grid_position) = h.grid_position.filter(|pos| pos.grid == grid_id) {
+                let mut copy = Helix::clone(h.as_ref());
+                copy.position = axis.reflect(copy.position);
+                copy.orientation = axis.reflect_orientation(copy.orientation);
+                // The axis of the new grid points in the opposite direction, so the position of
+                // the helix along it must be negated to obtain the mirror image.
+                copy.grid_position = Some(GridPosition {
+                    grid: new_grid_id,
+                    axis_pos: -grid_position.axis_pos,
+                    ..grid_position
+                });
+                copy.isometry2d = None;
+                new_helices.insert(helix_id, Arc::new(copy));
+                helix_id += 1;
+            }
+        }
+        design.helices = Arc::new(new_helices);
+        Ok(design)
+    }
+
+    fn reorder_strands(&mut self, mut design: Design, key: SortKey) -> Design {
+        let new_ids = strand_renumbering(&design, key);
+        let old_strands = std::mem::take(&mut design.strands);
+        design.strands = old_strands
+            .into_iter()
+            .map(|(s_id, s)| (new_ids[&s_id], s))
+            .collect();
+        design.scaffold_id = design
+            .scaffold_id
+            .and_then(|s_id| new_ids.get(&s_id).cloned());
+        if let Some(tree) = design.organizer_tree.as_ref() {
+            let mut tree = OrganizerTree::clone(tree);
+            renumber_strands_in_tree(&mut tree, &new_ids);
+            design.organizer_tree = Some(Arc::new(tree));
+        }
+        for element in design.selection_groups.values_mut().flatten() {
+            if let DnaElementKey::Strand(s_id) = element {
+                if let Some(new_id) = new_ids.get(s_id) {
+                    *s_id = *new_id;
+                }
+            }
+        }
         design
     }
 
+    fn create_camera(
+        &mut self,
+        mut design: Design,
+        position: Vec3,
+        orientation: Rotor3,
+    ) -> Result<Design, ErrOperation> {
+        design
+            .add_camera(position, orientation)
+            .map_err(|ensnano_design::TooManyCameras| ErrOperation::TooManyCameras)?;
+        Ok(design)
+    }
+
     fn delete_camera(&mut self, mut design: Design, id: CameraId) -> Result<Design, ErrOperation> {
         if design.rm_camera(id).is_err() {
             Err(ErrOperation::CameraDoesNotExist(id))
@@ -1087,6 +1495,28 @@ impl Controller {
         Ok(design)
     }
 
+    fn place_helices(
+        &mut self,
+        mut design: Design,
+        placements: Vec<(usize, Vec3, Rotor3)>,
+    ) -> Result<Design, ErrOperation> {
+        let mut new_helices = BTreeMap::clone(design.helices.as_ref());
+        for (h_id, position, orientation) in placements {
+            let helix = new_helices
+                .get_mut(&h_id)
+                .ok_or(ErrOperation::HelixDoesNotExists(h_id))?;
+            mutate_in_arc(helix, |h| {
+                // Otherwise the helix would be moved back to its grid position when the grids are
+                // modified
+                h.grid_position = None;
+                h.position = position;
+                h.orientation = orientation;
+            });
+        }
+        design.helices = Arc::new(new_helices);
+        Ok(design)
+    }
+
     fn attach_helix(
         &mut self,
         mut design: Design,
@@ -1331,9 +1761,28 @@ pub enum ErrOperation {
     HelixNotEmpty(usize),
     EmptyScaffoldSequence,
     NoScaffoldSet,
+    NuclNotOnScaffold(Nucl),
+    /// The operation can only be applied on a cyclic scaffold
+    ScaffoldNotCyclic,
     NoGrids,
     FinishFirst,
     CameraDoesNotExist(CameraId),
+    /// The design already has `ensnano_design::MAX_NB_CAMERAS` cameras
+    TooManyCameras,
+    /// The strand ends that should be ligated are too far apart
+    EndsTooFarApart,
+    /// A strand created by the user is shorter than the minimum strand length
+    StrandTooShort {
+        length: usize,
+        min_length: usize,
+    },
+    /// The flipped strand would occupy nucleotides that belong to an other strand
+    FlippedStrandOverlaps(usize),
+    /// The hairpin would occupy nucleotides that belong to an other strand
+    HairpinOverlaps(usize),
+    /// The torus knot has a null winding number or a radius that is not larger than its tube
+    /// radius
+    InvalidTorusKnot,
 }
 
 impl Controller {
@@ -1347,6 +1796,72 @@ impl Controller {
         design
     }
 
+    /// Color the staples with the colors of `palette`.
+    ///
+    /// Two staples are neighbours if they have nucleotides that are next to each other on the
+    /// same helix, or that are paired. The staples are colored greedily by decreasing number of
+    /// neighbours, each one getting the least used color among the ones that are not used by its
+    /// neighbours.
+    fn recolor_strands(&mut self, mut design: Design, palette: ColorBlindPalette) -> Design {
+        let mut nucl_to_strand = HashMap::new();
+        for (s_id, strand) in design.strands.iter() {
+            if Some(*s_id) != design.scaffold_id {
+                for domain in strand.domains.iter() {
+                    if let Domain::HelixDomain(interval) = domain {
+                        for position in interval.iter() {
+                            let nucl = Nucl::new(interval.helix, position, interval.forward);
+                            nucl_to_strand.insert(nucl, *s_id);
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut neighbours: BTreeMap<usize, BTreeSet<usize>> = BTreeMap::new();
+        for s_id in design.strands.keys() {
+            if Some(*s_id) != design.scaffold_id {
+                neighbours.insert(*s_id, BTreeSet::new());
+            }
+        }
+        for (nucl, s_id) in nucl_to_strand.iter() {
+            for other in [nucl.left(), nucl.right(), nucl.compl()].iter() {
+                if let Some(other_id) = nucl_to_strand.get(other).filter(|id| *id != s_id) {
+                    if let Some(set) = neighbours.get_mut(s_id) {
+                        set.insert(*other_id);
+                    }
+                }
+            }
+        }
+
+        let mut order: Vec<usize> = neighbours.keys().cloned().collect();
+        order.sort_by_key(|s_id| std::cmp::Reverse(neighbours[s_id].len()));
+
+        let colors = palette.colors();
+        let mut nb_uses = vec![0usize; colors.len()];
+        let mut color_of_strand: HashMap<usize, usize> = HashMap::new();
+        for s_id in order.iter() {
+            let forbidden: Vec<usize> = neighbours[s_id]
+                .iter()
+                .filter_map(|n| color_of_strand.get(n).cloned())
+                .collect();
+            let color_idx = (0..colors.len())
+                .filter(|c| !forbidden.contains(c))
+                .min_by_key(|c| nb_uses[*c])
+                .or_else(|| (0..colors.len()).min_by_key(|c| nb_uses[*c]));
+            if let Some(color_idx) = color_idx {
+                nb_uses[color_idx] += 1;
+                color_of_strand.insert(*s_id, color_idx);
+            }
+        }
+
+        for (s_id, color_idx) in color_of_strand.into_iter() {
+            if let Some(strand) = design.strands.get_mut(&s_id) {
+                strand.color = (0xFF << 24) | colors[color_idx];
+            }
+        }
+        design
+    }
+
     fn set_scaffold_sequence(
         &mut self,
         mut design: Design,
@@ -1442,6 +1957,19 @@ impl Controller {
         design
     }
 
+    fn set_helix_display_order(
+        &mut self,
+        mut design: Design,
+        order: Vec<usize>,
+        isometries: Vec<(usize, Isometry2)>,
+    ) -> Design {
+        for (h_id, isometry) in isometries {
+            design = self.set_isometry(design, h_id, isometry);
+        }
+        design.helix_display_order = order;
+        design
+    }
+
     fn rotate_helices(
         &mut self,
         mut design: Design,
@@ -1477,6 +2005,7 @@ impl Controller {
         nucls: Vec<Nucl>,
     ) -> Result<Design, ErrOperation> {
         let mut builders = Vec::with_capacity(nucls.len());
+        let old_strands: BTreeSet<usize> = design.strands.keys().cloned().collect();
         let ignored_domains: Vec<_> = nucls
             .iter()
             .filter_map(|nucl| {
@@ -1491,12 +2020,19 @@ impl Controller {
                     .ok_or(ErrOperation::CannotBuildOn(nucl))?,
             );
         }
+        let new_strands = design
+            .strands
+            .keys()
+            .filter(|s_id| !old_strands.contains(s_id))
+            .cloned()
+            .collect();
         self.state = ControllerState::BuildingStrand {
             builders,
             initializing: true,
             // The initial design is indeed the one AFTER adding the new strands
             initial_design: AddressPointer::new(design.clone()),
             ignored_domains,
+            new_strands,
         };
         Ok(design)
     }
@@ -1637,6 +2173,7 @@ impl Controller {
             builders,
             initializing,
             ignored_domains,
+            ..
         } = &mut self.state
         {
             let delta = builders
@@ -1699,11 +2236,136 @@ impl Controller {
         Ok(design)
     }
 
+    /// Insert `length` unpaired nucleotides in the strand containing `at`, between `at` and the
+    /// next nucleotide of the strand.
+    ///
+    /// The loop is represented by a `Domain::Insertion`. If the strand has a sequence, the
+    /// nucleotides of the loop are given the base T.
+    fn add_loop(
+        &mut self,
+        mut design: Design,
+        at: Nucl,
+        length: usize,
+    ) -> Result<Design, ErrOperation> {
+        if length == 0 {
+            return Ok(design);
+        }
+        let s_id = design
+            .get_strand_nucl(&at)
+            .ok_or(ErrOperation::NuclDoesNotExist(at))?;
+        let strand = design
+            .strands
+            .get_mut(&s_id)
+            .ok_or(ErrOperation::StrandDoesNotExist(s_id))?;
+        let position_on_strand = strand
+            .find_nucl(&at)
+            .ok_or(ErrOperation::NuclDoesNotExist(at))?;
+        let (d_id, n) = strand
+            .domains
+            .iter()
+            .enumerate()
+            .find_map(|(d_id, d)| d.has_nucl(&at).map(|n| (d_id, n)))
+            .ok_or(ErrOperation::NuclDoesNotExist(at))?;
+
+        if n + 1 < strand.domains[d_id].length() {
+            // at is in the middle of a domain, which is split around the loop.
+            let (prime5, prime3) = strand.domains[d_id]
+                .split(n)
+                .ok_or(ErrOperation::NuclDoesNotExist(at))?;
+            strand.domains[d_id] = prime5;
+            strand.domains.insert(d_id + 1, Domain::Insertion(length));
+            strand.domains.insert(d_id + 2, prime3);
+            // The junction of the original domain is now the one of its 3' half.
+            strand.junctions.insert(d_id, DomainJunction::Adjacent);
+            strand.junctions.insert(d_id, DomainJunction::Adjacent);
+        } else if let Some(Domain::Insertion(m)) = strand.domains.get_mut(d_id + 1) {
+            *m += length;
+        } else {
+            // The loop takes the junction that was following the domain
+            let junction = std::mem::replace(&mut strand.junctions[d_id], DomainJunction::Adjacent);
+            strand.domains.insert(d_id + 1, Domain::Insertion(length));
+            strand.junctions.insert(d_id + 1, junction);
+        }
+
+        if let Some(sequence) = strand.sequence.as_mut() {
+            let mut new_sequence: String = sequence.chars().take(position_on_strand + 1).collect();
+            new_sequence.extend(std::iter::repeat('T').take(length));
+            new_sequence.extend(sequence.chars().skip(position_on_strand + 1));
+            *sequence = Cow::Owned(new_sequence);
+        }
+        Ok(design)
+    }
+
+    fn create_hairpin(
+        &mut self,
+        mut design: Design,
+        at: Nucl,
+        stem_length: usize,
+        loop_length: usize,
+    ) -> Result<Design, ErrOperation> {
+        if stem_length == 0 {
+            return Ok(design);
+        }
+        let s_id = design
+            .get_strand_nucl(&at)
+            .ok_or(ErrOperation::NuclDoesNotExist(at))?;
+        let hairpin = ensnano_design::hairpin_domains(at, stem_length, loop_length);
+        for (other_id, other) in design.strands.iter() {
+            if other.intersect_domains(&hairpin) {
+                return Err(ErrOperation::HairpinOverlaps(*other_id));
+            }
+        }
+        let strand = design
+            .strands
+            .get_mut(&s_id)
+            .ok_or(ErrOperation::StrandDoesNotExist(s_id))?;
+        if strand.get_3prime() != Some(at) || !strand.add_hairpin(stem_length, loop_length) {
+            return Err(ErrOperation::CannotBuildOn(at));
+        }
+        Ok(design)
+    }
+
     fn cut(&mut self, mut design: Design, nucl: Nucl) -> Result<Design, ErrOperation> {
         let _ = Self::split_strand(&mut design, &nucl, None)?;
         Ok(design)
     }
 
+    fn break_scaffold_at(&mut self, mut design: Design, at: Nucl) -> Result<Design, ErrOperation> {
+        let s_id = design.scaffold_id.ok_or(ErrOperation::NoScaffoldSet)?;
+        if design.get_strand_nucl(&at) != Some(s_id) {
+            return Err(ErrOperation::NuclNotOnScaffold(at));
+        }
+        let scaffold = design
+            .strands
+            .remove(&s_id)
+            .ok_or(ErrOperation::StrandDoesNotExist(s_id))?;
+        if !scaffold.cyclic {
+            let already_open = scaffold.get_5prime() == Some(at);
+            design.strands.insert(s_id, scaffold);
+            return if already_open {
+                Ok(design)
+            } else {
+                Err(ErrOperation::ScaffoldNotCyclic)
+            };
+        }
+        // The sequence is read from the 5' end of the scaffold, so it must be shifted to stay on
+        // the same nucleotides.
+        let position = scaffold.find_nucl(&at).unwrap_or(0);
+        let sequence_length = design
+            .scaffold_sequence
+            .as_ref()
+            .map(|s| s.chars().filter(|c| c.is_alphabetic()).count())
+            .filter(|l| *l > 0);
+        if let Some(length) = sequence_length {
+            let shift = design.scaffold_shift.unwrap_or(0) % length;
+            design.scaffold_shift = Some((shift + length - position % length) % length);
+        }
+        design
+            .strands
+            .insert(s_id, Self::break_cycle(scaffold, at, Some(true)));
+        Ok(design)
+    }
+
     /// Split a strand at nucl, and return the id of the newly created strand
     ///
     /// The part of the strand that contains nucl is given the original
@@ -1825,6 +2487,8 @@ impl Controller {
             cyclic: false,
             sequence: seq_prim5,
             name: name.clone(),
+            modification_5prime: strand.modification_5prime,
+            modification_3prime: None,
         };
 
         let strand_3prime = Strand {
@@ -1834,6 +2498,8 @@ impl Controller {
             junctions: prime3_junctions,
             sequence: seq_prim3,
             name,
+            modification_5prime: None,
+            modification_3prime: strand.modification_3prime,
         };
         let new_id = (*design.strands.keys().max().unwrap_or(&0)).max(id) + 1;
         log::info!("new id {}, ; id {}", new_id, id);
@@ -2029,6 +2695,8 @@ impl Controller {
                 junctions,
                 cyclic: false,
                 name,
+                modification_5prime: strand5prime.modification_5prime,
+                modification_3prime: strand3prime.modification_3prime,
             };
             design.strands.insert(prime5, new_strand);
             Ok(())
@@ -2132,6 +2800,88 @@ impl Controller {
         Ok(design)
     }
 
+    /// Reverse the direction of a strand. Each domain of the strand is moved to the opposite side
+    /// of its helix and the order of the domains is reversed, so that the strand goes through the
+    /// same helix positions, from its former 3' end to its former 5' end.
+    fn flip_strand(&mut self, mut design: Design, s_id: usize) -> Result<Design, ErrOperation> {
+        let strand = design
+            .strands
+            .get(&s_id)
+            .ok_or(ErrOperation::StrandDoesNotExist(s_id))?;
+        let domains: Vec<Domain> = strand
+            .domains
+            .iter()
+            .rev()
+            .map(|d| match d {
+                Domain::HelixDomain(interval) => Domain::HelixDomain(HelixInterval {
+                    forward: !interval.forward,
+                    sequence: interval
+                        .sequence
+                        .as_ref()
+                        .map(|s| s.chars().rev().collect::<String>().into()),
+                    ..interval.clone()
+                }),
+                Domain::Insertion(n) => Domain::Insertion(*n),
+            })
+            .collect();
+        for (other_id, other) in design.strands.iter() {
+            if *other_id != s_id && other.intersect_domains(&domains) {
+                return Err(ErrOperation::FlippedStrandOverlaps(*other_id));
+            }
+        }
+        let strand = design.strands.get_mut(&s_id).unwrap();
+        strand.junctions = ensnano_design::read_junctions(&domains, strand.cyclic);
+        strand.domains = domains;
+        strand.sequence = strand
+            .sequence
+            .as_ref()
+            .map(|s| s.chars().rev().collect::<String>().into());
+        Ok(design)
+    }
+
+    /// Ligate the 3' end of one of the two strands with the 5' end of the other one, provided
+    /// that they are close enough.
+    fn connect_strands(
+        &mut self,
+        design: Design,
+        (strand_a, end_a): (usize, StrandEnd),
+        (strand_b, end_b): (usize, StrandEnd),
+    ) -> Result<Design, ErrOperation> {
+        let (prime5_id, prime3_id) = match (end_a, end_b) {
+            (StrandEnd::ThreePrime, StrandEnd::FivePrime) => (strand_a, strand_b),
+            (StrandEnd::FivePrime, StrandEnd::ThreePrime) => (strand_b, strand_a),
+            (StrandEnd::FivePrime, StrandEnd::FivePrime) => {
+                return Err(ErrOperation::XoverBetweenTwoPrime5)
+            }
+            (StrandEnd::ThreePrime, StrandEnd::ThreePrime) => {
+                return Err(ErrOperation::XoverBetweenTwoPrime3)
+            }
+        };
+        let end_nucl = |s_id: usize, end: StrandEnd| {
+            let strand = design
+                .strands
+                .get(&s_id)
+                .ok_or(ErrOperation::StrandDoesNotExist(s_id))?;
+            if strand.cyclic {
+                Err(ErrOperation::BadSelection)
+            } else {
+                end.of_strand(strand).ok_or(ErrOperation::BadSelection)
+            }
+        };
+        let prime3_nucl = end_nucl(prime5_id, StrandEnd::ThreePrime)?;
+        let prime5_nucl = end_nucl(prime3_id, StrandEnd::FivePrime)?;
+        let prime3_position = design
+            .get_nucl_position(prime3_nucl)
+            .ok_or(ErrOperation::NuclDoesNotExist(prime3_nucl))?;
+        let prime5_position = design
+            .get_nucl_position(prime5_nucl)
+            .ok_or(ErrOperation::NuclDoesNotExist(prime5_nucl))?;
+        if (prime3_position - prime5_position).mag() > crate::consts::MAX_LIGATION_DISTANCE {
+            return Err(ErrOperation::EndsTooFarApart);
+        }
+        self.apply_merge(design, prime5_id, prime3_id)
+    }
+
     /// Cut the target strand at nucl and the make a cross over from the source strand to the part
     /// that contains nucl
     fn cross_cut(
@@ -2199,6 +2949,11 @@ impl Controller {
         target_nucl: Nucl,
     ) -> Result<Design, ErrOperation> {
         self.general_cross_over(&mut design, source_nucl, target_nucl)?;
+        if let ControllerState::BuildingStrand { .. } = self.state {
+            // The strand builders are not valid anymore. Since the design was not pushed on the
+            // undo stack while building, the cross-over is undone together with the building.
+            self.state = ControllerState::Normal;
+        }
         Ok(design)
     }
 
@@ -2338,6 +3093,11 @@ impl Controller {
         Ok(design)
     }
 
+    fn cleanup_orphans(&mut self, mut design: Design) -> Result<Design, ErrOperation> {
+        design.remove_orphans();
+        Ok(design)
+    }
+
     fn set_grid_position(
         &mut self,
         mut design: Design,
@@ -2367,6 +3127,21 @@ impl Controller {
         design.grids = Arc::new(new_grids);
         Ok(design)
     }
+
+    fn set_grid_twist_offset(
+        &mut self,
+        mut design: Design,
+        grid_id: usize,
+        twist_offset: f32,
+    ) -> Result<Design, ErrOperation> {
+        let mut new_grids = Vec::clone(design.grids.as_ref());
+        let grid = new_grids
+            .get_mut(grid_id)
+            .ok_or(ErrOperation::GridDoesNotExist(grid_id))?;
+        grid.twist_offset = twist_offset;
+        design.grids = Arc::new(new_grids);
+        Ok(design)
+    }
 }
 
 fn nucl_pos_2d(design: &Design, nucl: &Nucl) -> Option<Vec2> {
@@ -2381,6 +3156,103 @@ fn nucl_pos_2d(design: &Design, nucl: &Nucl) -> Option<Vec2> {
     isometry.map(|i| i.into_homogeneous_matrix().transform_point2(local_position))
 }
 
+/// Add `module`, rotated by `orientation` around the origin and translated by `offset`, to
+/// `design`
+fn merge_design(
+    mut design: Design,
+    mut module: Design,
+    offset: Vec3,
+    orientation: Rotor3,
+) -> Design {
+    module.rotate(orientation);
+    design.merge(module, offset);
+    design
+}
+
+/// Return the identifier that each strand of `design` is given when the strands are renumbered
+/// according to `key`
+pub(super) fn strand_renumbering(design: &Design, key: SortKey) -> HashMap<usize, usize> {
+    let mut ids: Vec<usize> = design.strands.keys().cloned().collect();
+    match key {
+        SortKey::ByLength => ids.sort_by_key(|s_id| design.strands[s_id].length()),
+        SortKey::ByHelixOrder => ids.sort_by_key(|s_id| {
+            design.strands[s_id]
+                .get_5prime()
+                .map(|n| (n.helix, n.position, !n.forward))
+        }),
+        SortKey::ByGcContent => {
+            let scaffold_basis = design.scaffold_basis_map();
+            let gc_content: HashMap<usize, f64> = design
+                .strands
+                .iter()
+                .map(|(s_id, s)| (*s_id, gc_content(s, &scaffold_basis)))
+                .collect();
+            ids.sort_by(|a, b| {
+                gc_content[a]
+                    .partial_cmp(&gc_content[b])
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+        }
+    }
+    ids.into_iter()
+        .enumerate()
+        .map(|(new_id, old_id)| (old_id, new_id))
+        .collect()
+}
+
+/// Return the proportion of G and C in the sequence of a strand. The sequence of the strand is
+/// either its own sequence or, if it does not have one, the complement of the scaffold.
+fn gc_content(strand: &Strand, scaffold_basis: &HashMap<Nucl, char>) -> f64 {
+    let is_gc = |c: &char| matches!(c.to_ascii_uppercase(), 'G' | 'C');
+    if let Some(sequence) = strand.sequence.as_ref() {
+        let nb_basis = sequence.chars().filter(|c| c.is_alphabetic()).count();
+        let nb_gc = sequence.chars().filter(is_gc).count();
+        nb_gc as f64 / nb_basis.max(1) as f64
+    } else {
+        let mut nb_nucl = 0;
+        let mut nb_gc = 0;
+        for domain in strand.domains.iter() {
+            if let Domain::HelixDomain(dom) = domain {
+                for position in dom.iter() {
+                    let nucl = Nucl {
+                        helix: dom.helix,
+                        position,
+                        forward: dom.forward,
+                    };
+                    nb_nucl += 1;
+                    // The complement of a G or C basis is also a G or C basis
+                    let basis = scaffold_basis
+                        .get(&nucl)
+                        .or_else(|| scaffold_basis.get(&nucl.compl()));
+                    if basis.filter(|c| is_gc(c)).is_some() {
+                        nb_gc += 1;
+                    }
+                }
+            }
+        }
+        nb_gc as f64 / nb_nucl.max(1) as f64
+    }
+}
+
+fn renumber_strands_in_tree(
+    tree: &mut OrganizerTree<DnaElementKey>,
+    new_ids: &HashMap<usize, usize>,
+) {
+    match tree {
+        OrganizerTree::Leaf(DnaElementKey::Strand(s_id)) => {
+            if let Some(new_id) = new_ids.get(s_id) {
+                *s_id = *new_id;
+            }
+        }
+        OrganizerTree::Leaf(_) => (),
+        OrganizerTree::Node { childrens, .. } => {
+            for child in childrens.iter_mut() {
+                renumber_strands_in_tree(child, new_ids)
+            }
+        }
+    }
+}
+
 #[derive(Clone)]
 enum ControllerState {
     Normal,
@@ -2394,6 +3266,8 @@ enum ControllerState {
         initial_design: AddressPointer<Design>,
         initializing: bool,
         ignored_domains: Vec<DomainIdentifier>,
+        /// The strands that were created when the building started
+        new_strands: Vec<usize>,
     },
     ChangingColor,
     SettingRollHelices,
@@ -2451,9 +3325,16 @@ enum ControllerState {
         interface: Arc<Mutex<RollInterface>>,
         initial_design: AddressPointer<Design>,
     },
+    OptimizingRolls {
+        interface: Arc<Mutex<RollOptimizerInterface>>,
+        initial_design: AddressPointer<Design>,
+    },
     ChangingStrandName {
         strand_id: usize,
     },
+    ChangingStrandSequence {
+        strand_id: usize,
+    },
 }
 
 impl Default for ControllerState {
@@ -2483,8 +3364,10 @@ impl ControllerState {
             Self::SimulatingGrids { .. } => "Simulating Grids",
             Self::WithPausedSimulation { .. } => "WithPausedSimulation",
             Self::Rolling { .. } => "Rolling",
+            Self::OptimizingRolls { .. } => "OptimizingRolls",
             Self::SettingRollHelices => "SettingRollHelices",
             Self::ChangingStrandName { .. } => "ChangingStrandName",
+            Self::ChangingStrandSequence { .. } => "ChangingStrandSequence",
         }
     }
     fn update_pasting_position(
@@ -2587,8 +3470,10 @@ impl ControllerState {
             Self::SimulatingGrids { .. } => self.clone(),
             Self::WithPausedSimulation { .. } => self.clone(),
             Self::Rolling { .. } => Self::Normal,
+            Self::OptimizingRolls { .. } => Self::Normal,
             Self::SettingRollHelices => Self::Normal,
             Self::ChangingStrandName { .. } => Self::Normal,
+            Self::ChangingStrandSequence { .. } => Self::Normal,
         }
     }
 
@@ -2614,6 +3499,8 @@ impl ControllerState {
 pub enum InteractorNotification {
     FinishOperation,
     NewSelection,
+    /// The optimization of the scaffold position was canceled or failed
+    ShiftOptimizationAborted,
 }
 
 use ensnano_design::HelixInterval;
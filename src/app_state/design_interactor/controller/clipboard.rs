@@ -521,6 +521,8 @@ impl Controller {
                     sequence: None,
                     cyclic: false,
                     name: None,
+                    modification_5prime: None,
+                    modification_3prime: None,
                 };
                 let strand_id = if let Some(n) = design.strands.keys().max() {
                     n + 1
@@ -16,16 +16,7 @@ ENSnano, a 3d graphical application for DNA nanostructures.
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
-macro_rules! log_err {
-    ($x:expr) => {
-        if $x.is_err() {
-            log::error!("Unexpected error")
-        }
-    };
-}
-
 use super::*;
-use std::sync::mpsc;
 
 fn read_scaffold_seq(
     design: &Design,
@@ -86,51 +77,66 @@ pub fn optimize_shift(
     identifier_nucl: Arc<AHashMap<Nucl, u32>>,
     chanel_reader: &mut dyn ShiftOptimizerReader,
 ) {
-    let (progress_snd, progress_rcv) = std::sync::mpsc::channel();
-    let (result_snd, result_rcv) = std::sync::mpsc::channel();
-    chanel_reader.attach_result_chanel(result_rcv);
-    chanel_reader.attach_progress_chanel(progress_rcv);
-    std::thread::spawn(move || {
-        let result =
-            get_shift_optimization_result(design.as_ref(), progress_snd, identifier_nucl.as_ref());
-        log_err!(result_snd.send(result));
+    chanel_reader.spawn_shift_optimization(ShiftOptimization {
+        design,
+        identifier_nucl,
     });
 }
 
-fn get_shift_optimization_result(
-    design: &Design,
-    progress_channel: std::sync::mpsc::Sender<f32>,
-    identifier_nucl: &AHashMap<Nucl, u32>,
-) -> ShiftOptimizationResult {
-    let mut best_score = usize::MAX;
-    let mut best_shfit = 0;
-    let mut best_result = String::new();
-    let len = design
-        .scaffold_sequence
-        .as_ref()
-        .map(|s| s.len())
-        .ok_or(ErrOperation::NoScaffoldSet)?;
-    for shift in 0..len {
-        if shift % 100 == 0 {
-            log_err!(progress_channel.send(shift as f32 / len as f32))
-        }
-        let char_map = read_scaffold_seq(design, identifier_nucl, shift)?;
-        let (score, result) = evaluate_shift(design, &char_map);
-        if score < best_score {
-            println!("shift {} score {}", shift, score);
-            best_score = score;
-            best_shfit = shift;
-            best_result = result;
-        }
-        if score == 0 {
-            break;
+/// The search of the best scaffold position, that can be run in a separate thread
+pub struct ShiftOptimization {
+    design: Arc<Design>,
+    identifier_nucl: Arc<AHashMap<Nucl, u32>>,
+}
+
+impl ShiftOptimization {
+    /// Try all the positions of the scaffold and return the best one.
+    ///
+    /// The progress of the search is reported to `set_progress`. Return `None` if `is_canceled`
+    /// returned true before the end of the search.
+    pub fn run<P, C>(&self, set_progress: P, is_canceled: C) -> Option<ShiftOptimizationResult>
+    where
+        P: Fn(f32),
+        C: Fn() -> bool,
+    {
+        let design = self.design.as_ref();
+        let identifier_nucl = self.identifier_nucl.as_ref();
+        let mut best_score = usize::MAX;
+        let mut best_shfit = 0;
+        let mut best_result = String::new();
+        let len = match design.scaffold_sequence.as_ref().map(|s| s.len()) {
+            Some(len) => len,
+            None => return Some(Err(ErrOperation::NoScaffoldSet)),
+        };
+        for shift in 0..len {
+            if shift % 100 == 0 {
+                if is_canceled() {
+                    return None;
+                }
+                set_progress(shift as f32 / len as f32)
+            }
+            let char_map = match read_scaffold_seq(design, identifier_nucl, shift) {
+                Ok(char_map) => char_map,
+                Err(e) => return Some(Err(e)),
+            };
+            let (score, result) = evaluate_shift(design, &char_map);
+            if score < best_score {
+                log::info!("shift {} score {}", shift, score);
+                best_score = score;
+                best_shfit = shift;
+                best_result = result;
+            }
+            if score == 0 {
+                break;
+            }
         }
+        Some(Ok(ShiftOptimizationOk {
+            position: best_shfit,
+            score: best_result,
+        }))
     }
-    Ok(ShiftOptimizationOk {
-        position: best_shfit,
-        score: best_result,
-    })
 }
+
 /// Evaluate a scaffold position. The score of the position is given by
 /// score = nb((A|T)^7) + 10 nb(G^4 | C ^4) + 100 nb (G^5 | C^5) + 1000 nb (G^6 | C^6)
 fn evaluate_shift(design: &Design, basis_map: &BTreeMap<Nucl, char>) -> (usize, String) {
@@ -232,6 +238,6 @@ pub struct ShiftOptimizationOk {
 pub type ShiftOptimizationResult = Result<ShiftOptimizationOk, ErrOperation>;
 
 pub trait ShiftOptimizerReader: Send {
-    fn attach_progress_chanel(&mut self, chanel: mpsc::Receiver<f32>);
-    fn attach_result_chanel(&mut self, chanel: mpsc::Receiver<ShiftOptimizationResult>);
+    /// Run `optimization` in a separate thread and follow its progress
+    fn spawn_shift_optimization(&mut self, optimization: ShiftOptimization);
 }
@@ -33,6 +33,8 @@ use ultraviolet::{Bivec3, Mat3};
 
 mod roller;
 pub use roller::{PhysicalSystem, RollInterface, RollPresenter};
+mod roll_optimizer;
+pub use roll_optimizer::{RollOptimizerInterface, ScaffoldOptimizer};
 
 const MAX_DERIVATIVE_NORM: f32 = 1e4;
 
@@ -56,8 +58,8 @@ struct HelixSystem {
     time_span: (f32, f32),
     last_state: Option<Vector<f32>>,
     parameters: Parameters,
-    anchors: Vec<(RigidNucl, Vec3)>,
-    free_anchors: Vec<(usize, Vec3)>,
+    anchors: Vec<RigidNucl>,
+    free_anchors: Vec<usize>,
     current_time: f32,
     next_time: f32,
     brownian_heap: BinaryHeap<(Reverse<OrderedFloat<f32>>, usize)>,
@@ -173,7 +175,12 @@ impl ExplicitODE<f32> for HelixSystem {
                 ret.push(d_angular_momentum.y);
                 ret.push(d_angular_momentum.z);
             } else {
-                let mut d_position = linear_momentums[i] / (self.rigid_parameters.mass / 2.);
+                let mut d_position = if self.rigid_parameters.freeze_ssdna {
+                    // Single stranded nucleotides are held in place
+                    Vec3::zero()
+                } else {
+                    linear_momentums[i] / (self.rigid_parameters.mass / 2.)
+                };
                 bound_derivative!(d_position);
                 ret.push(d_position.x);
                 ret.push(d_position.y);
@@ -280,7 +287,6 @@ impl HelixSystem {
 
         const L0: f32 = 0.7;
         const C_VOLUME: f32 = 2f32;
-        let k_anchor = 1000. * self.rigid_parameters.k_spring;
 
         let point_conversion = |nucl: &RigidNucl| {
             let position = positions[nucl.helix]
@@ -350,32 +356,6 @@ impl HelixSystem {
             forces[self.helices.len() + *id_1] -= 10. * force;
         }
 
-        for (nucl, position) in self.anchors.iter() {
-            let point_0 = point_conversion(&nucl);
-            let len = (point_0 - *position).mag();
-            let force = if len > 1e-5 {
-                self.rigid_parameters.k_spring * k_anchor * -(point_0 - *position)
-            } else {
-                Vec3::zero()
-            };
-
-            forces[nucl.helix] += 10. * force;
-
-            let torque0 = (point_0 - positions[nucl.helix]).cross(force);
-
-            torques[nucl.helix] += torque0;
-        }
-        for (id, position) in self.free_anchors.iter() {
-            let point_0 = free_nucl_pos(id);
-            let len = (point_0 - *position).mag();
-            let force = if len > 1e-5 {
-                self.rigid_parameters.k_spring * k_anchor * -(point_0 - *position)
-            } else {
-                Vec3::zero()
-            };
-
-            forces[self.helices.len() + *id] += 10. * force;
-        }
         let segments: Vec<(Vec3, Vec3)> = (0..self.helices.len())
             .map(|n| {
                 let position =
@@ -427,6 +407,21 @@ impl HelixSystem {
             }
         }
 
+        // Anchors behave like springs of infinite stiffness: the elements that hold them are not
+        // allowed to move at all.
+        for nucl in self.anchors.iter() {
+            forces[nucl.helix] = Vec3::zero();
+            torques[nucl.helix] = Vec3::zero();
+        }
+        for id in self.free_anchors.iter() {
+            forces[self.helices.len() + *id] = Vec3::zero();
+        }
+        if self.rigid_parameters.freeze_ssdna {
+            for id in 0..self.free_nucls.len() {
+                forces[self.helices.len() + id] = Vec3::zero();
+            }
+        }
+
         (forces, torques)
     }
 }
@@ -472,6 +467,26 @@ impl HelixSystem {
         (positions, rotations, linear_momentums, angular_momentums)
     }
 
+    /// Return the kinetic energy of the helices and of the free nucleotides
+    fn kinetic_energy(&self, linear_momentums: &[Vec3], angular_momentums: &[Vec3]) -> f32 {
+        let mut ret = 0.;
+        for (i, (p, l)) in linear_momentums
+            .iter()
+            .zip(angular_momentums.iter())
+            .enumerate()
+        {
+            if let Some(helix) = self.helices.get(i) {
+                let velocity = *p / (helix.height() * self.rigid_parameters.mass);
+                let omega = helix.inertia_inverse * *l / self.rigid_parameters.mass;
+                ret += 0.5 * (p.dot(velocity) + l.dot(omega));
+            } else {
+                let velocity = *p / (self.rigid_parameters.mass / 2.);
+                ret += 0.5 * p.dot(velocity);
+            }
+        }
+        ret
+    }
+
     fn next_time(&mut self) {
         self.current_time = self.next_time;
         if let Some((t, _)) = self.brownian_heap.peek() {
@@ -499,7 +514,8 @@ impl HelixSystem {
             let gx: f32 = rnd.sample(StandardNormal);
             let gy: f32 = rnd.sample(StandardNormal);
             let gz: f32 = rnd.sample(StandardNormal);
-            if let Some(state) = self.last_state.as_mut() {
+            let frozen = self.rigid_parameters.freeze_ssdna;
+            if let Some(state) = self.last_state.as_mut().filter(|_| !frozen) {
                 let entry = 13 * (self.helices.len() + nucl_id);
                 *state.get_mut(entry) += self.rigid_parameters.brownian_amplitude * gx;
                 *state.get_mut(entry + 1) += self.rigid_parameters.brownian_amplitude * gy;
@@ -518,7 +534,7 @@ impl HelixSystem {
         let mut rnd = rand::thread_rng();
         let exp_law = Exp::new(self.rigid_parameters.brownian_rate).unwrap();
         for i in 0..self.free_nucls.len() {
-            if !self.free_anchors.iter().any(|(x, _)| *x == i) {
+            if !self.free_anchors.contains(&i) {
                 let t = rnd.sample(exp_law) + self.next_time;
                 self.brownian_heap.push((Reverse(t.into()), i));
             }
@@ -531,7 +547,11 @@ impl HelixSystem {
         let gy: f32 = rnd.sample(StandardNormal);
         let gz: f32 = rnd.sample(StandardNormal);
         let entry = match nucl {
+            // Anchored elements must not move at all
+            ShakeTarget::Helix(h_id) if self.anchors.iter().any(|a| a.helix == h_id) => return,
+            ShakeTarget::FreeNucl(n) if self.free_anchors.contains(&n) => return,
             ShakeTarget::Helix(h_id) => 13 * h_id,
+            ShakeTarget::FreeNucl(_) if self.rigid_parameters.freeze_ssdna => return,
             ShakeTarget::FreeNucl(n) => 13 * (self.helices.len() + n),
         };
         if let Some(state) = self.last_state.as_mut() {
@@ -845,6 +865,7 @@ pub struct RigidHelixState {
     center_of_mass_from_helix: Vec<Vec3>,
     ids: Vec<usize>,
     constants: Arc<RigidHelixConstants>,
+    kinetic_energy: f32,
 }
 
 #[derive(Debug)]
@@ -915,7 +936,8 @@ impl HelixSystemThread {
 
     fn get_state(&self) -> RigidHelixState {
         let state = self.helix_system.init_cond();
-        let (positions, orientations, _, _) = self.helix_system.read_state(&state);
+        let (positions, orientations, linear_momentums, angular_momentums) =
+            self.helix_system.read_state(&state);
         let ids = self.helix_system.helices.iter().map(|g| g.id).collect();
         let center_of_mass_from_helix = self
             .helix_system
@@ -929,6 +951,9 @@ impl HelixSystemThread {
             center_of_mass_from_helix,
             ids,
             constants: self.constants.clone(),
+            kinetic_energy: self
+                .helix_system
+                .kinetic_energy(&linear_momentums, &angular_momentums),
         }
     }
 }
@@ -939,6 +964,7 @@ pub struct GridSystemState {
     orientations: Vec<Rotor3>,
     center_of_mass_from_grid: Vec<Vec3>,
     ids: Vec<usize>,
+    kinetic_energy: f32,
 }
 
 pub(super) struct GridsSystemThread {
@@ -988,7 +1014,8 @@ impl GridsSystemThread {
 
     fn get_state(&self) -> GridSystemState {
         let state = self.grid_system.init_cond();
-        let (positions, orientations, _, _) = self.grid_system.read_state(&state);
+        let (positions, orientations, linear_momentums, angular_momentums) =
+            self.grid_system.read_state(&state);
         let ids = self.grid_system.grids.iter().map(|g| g.id).collect();
         let center_of_mass_from_grid = self
             .grid_system
@@ -1001,6 +1028,9 @@ impl GridsSystemThread {
             orientations,
             center_of_mass_from_grid,
             ids,
+            kinetic_energy: self
+                .grid_system
+                .kinetic_energy(&linear_momentums, &angular_momentums),
         }
     }
 }
@@ -1087,18 +1117,16 @@ fn make_flexible_helices_system(
     let mut anchors = vec![];
     let mut free_anchors = vec![];
     for anchor in presenter.get_design().anchors.iter() {
-        if let Some(position) = presenter.get_space_position(anchor) {
-            if let Some(free_nucl) = interval_results.nucl_map.get(anchor) {
-                if let Some(rigid_helix) = free_nucl.helix {
-                    let rigid_nucl = RigidNucl {
-                        helix: rigid_helix,
-                        position: anchor.position,
-                        forward: anchor.forward,
-                    };
-                    anchors.push((rigid_nucl, position));
-                } else if let Some(id) = interval_results.free_nucl_ids.get(free_nucl) {
-                    free_anchors.push((*id, position));
-                }
+        if let Some(free_nucl) = interval_results.nucl_map.get(anchor) {
+            if let Some(rigid_helix) = free_nucl.helix {
+                let rigid_nucl = RigidNucl {
+                    helix: rigid_helix,
+                    position: anchor.position,
+                    forward: anchor.forward,
+                };
+                anchors.push(rigid_nucl);
+            } else if let Some(id) = interval_results.free_nucl_ids.get(free_nucl) {
+                free_anchors.push(*id);
             }
         }
     }
@@ -1106,7 +1134,7 @@ fn make_flexible_helices_system(
     let mut brownian_heap = BinaryHeap::new();
     let exp_law = Exp::new(rigid_parameters.brownian_rate).unwrap();
     for i in 0..interval_results.free_nucls.len() {
-        if !free_anchors.iter().any(|(x, _)| *x == i) {
+        if !free_anchors.contains(&i) {
             let t = rnd.sample(exp_law);
             brownian_heap.push((Reverse(t.into()), i));
         }
@@ -1333,6 +1361,11 @@ pub enum SimulationOperation<'pres, 'reader> {
         reader: &'reader mut dyn SimulationReader,
         target_helices: Option<Vec<usize>>,
     },
+    StartRollOptimization {
+        presenter: &'pres dyn RollPresenter,
+        reader: &'reader mut dyn SimulationReader,
+        target_helices: Option<Vec<usize>>,
+    },
 }
 
 pub trait SimulationReader {
@@ -1362,6 +1395,10 @@ impl SimulationUpdate for RigidHelixState {
         // since update positions is implemented, we do not need to move the helices.
     }
 
+    fn kinetic_energy(&self) -> Option<f32> {
+        Some(self.kinetic_energy)
+    }
+
     fn update_positions(
         &self,
         identifier_nucl: &HashMap<Nucl, u32, ahash::RandomState>,
@@ -1664,6 +1701,22 @@ impl GridsSystem {
         }
         (positions, rotations, linear_momentums, angular_momentums)
     }
+
+    /// Return the kinetic energy of the grids
+    fn kinetic_energy(&self, linear_momentums: &[Vec3], angular_momentums: &[Vec3]) -> f32 {
+        let mut ret = 0.;
+        for ((grid, p), l) in self
+            .grids
+            .iter()
+            .zip(linear_momentums.iter())
+            .zip(angular_momentums.iter())
+        {
+            let velocity = *p / (grid.mass * self.parameters.mass);
+            let omega = grid.inertia_inverse * *l / self.parameters.mass;
+            ret += 0.5 * (p.dot(velocity) + l.dot(omega));
+        }
+        ret
+    }
 }
 
 #[derive(Debug)]
@@ -1825,4 +1878,8 @@ impl SimulationUpdate for GridSystemState {
         }
         design.grids = Arc::new(new_grids);
     }
+
+    fn kinetic_energy(&self) -> Option<f32> {
+        Some(self.kinetic_energy)
+    }
 }
@@ -0,0 +1,285 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! This modules defines the `ScaffoldOptimizer` struct that looks for the roll of each helix that
+//! minimises the torsional strain at the cross-overs of the design.
+//!
+//! Contrary to the `PhysicalSystem`, which follows the forces applied by the cross-overs, the
+//! optimizer does not need the gradient of the strain and explores the space of roll angles with
+//! the Nelder-Mead simplex method.
+use super::roller::{dist_ac, DesignData, RollPresenter, RollState};
+use super::SimulationReader;
+use std::collections::HashMap;
+use std::f32::consts::FRAC_PI_4;
+use std::sync::{Arc, Mutex, Weak};
+
+/// The size of the initial simplex along each roll axis.
+const INITIAL_STEP: f32 = FRAC_PI_4;
+/// The optimization stops once the strain of all the vertices of the simplex are this close.
+const STRAIN_TOLERANCE: f32 = 1e-6;
+const MAX_ITERATIONS: usize = 10_000;
+
+/// Coefficients of the Nelder-Mead method
+const REFLECTION: f32 = 1.;
+const EXPANSION: f32 = 2.;
+const CONTRACTION: f32 = 0.5;
+const SHRINK: f32 = 0.5;
+
+/// A structure that optimizes the roll of the helices of a design.
+pub struct ScaffoldOptimizer {
+    /// The data representing the design on which the optimization is performed
+    data: DesignData,
+    /// The indices in `data.helices` of the helices whose roll can be modified
+    free_helices: Vec<usize>,
+    /// The roll of the free helices before the optimization
+    initial_rolls: Vec<f32>,
+    interface: Weak<Mutex<RollOptimizerInterface>>,
+}
+
+impl ScaffoldOptimizer {
+    /// Spawn a thread that sets the roll of each helix so that the total torsional strain at the
+    /// cross-overs is minimal. If `target_helices` is `Some`, only the roll of these helices is
+    /// modified.
+    pub fn minimise_crossover_strain(
+        presenter: &dyn RollPresenter,
+        target_helices: Option<Vec<usize>>,
+        reader: &mut dyn SimulationReader,
+    ) -> Arc<Mutex<RollOptimizerInterface>> {
+        let helices = presenter.get_helices();
+        let parameters = presenter
+            .get_design()
+            .parameters
+            .clone()
+            .unwrap_or_default();
+        let mut helix_map = HashMap::new();
+        for (n, k) in helices.keys().enumerate() {
+            helix_map.insert(*k, n);
+        }
+        let free_helices: Vec<usize> = if let Some(targets) = target_helices {
+            targets
+                .iter()
+                .filter_map(|h| helix_map.get(h))
+                .cloned()
+                .collect()
+        } else {
+            (0..helices.len()).collect()
+        };
+        let data = DesignData {
+            helices: helices.values().cloned().collect(),
+            helix_map,
+            xovers: presenter.get_xovers_list(),
+            parameters,
+            intervals: Vec::new(),
+        };
+        let initial_rolls = free_helices.iter().map(|n| data.helices[*n].roll).collect();
+
+        let interface = Arc::new(Mutex::new(RollOptimizerInterface::default()));
+        let interface_dyn: Arc<Mutex<dyn super::SimulationInterface>> = interface.clone();
+        reader.attach_state(&interface_dyn);
+
+        let optimizer = Self {
+            data,
+            free_helices,
+            initial_rolls,
+            interface: Arc::downgrade(&interface),
+        };
+        optimizer.run();
+        interface
+    }
+
+    fn run(mut self) {
+        std::thread::spawn(move || {
+            let x0 = self.initial_rolls.clone();
+            let mut simplex = Simplex::new(x0, INITIAL_STEP, |rolls| self.strain(rolls));
+            let mut iteration = 0;
+            while let Some(interface_ptr) = self.interface.upgrade() {
+                simplex.step(|rolls| self.strain(rolls));
+                iteration += 1;
+                let converged = simplex.spread() < STRAIN_TOLERANCE || iteration >= MAX_ITERATIONS;
+                let (best_rolls, best_strain) = simplex.best();
+                log::trace!("iteration {}, strain {}", iteration, best_strain);
+                self.set_rolls(best_rolls);
+                let mut interface = interface_ptr.lock().unwrap();
+                interface.iteration = iteration;
+                interface.strain = best_strain;
+                interface.new_state = Some(self.data.get_simulation_state());
+                if converged {
+                    interface.converged = true;
+                    break;
+                }
+            }
+        });
+    }
+
+    fn set_rolls(&mut self, rolls: &[f32]) {
+        for (n, roll) in self.free_helices.iter().zip(rolls.iter()) {
+            self.data.helices[*n].set_roll(*roll);
+        }
+    }
+
+    /// The sum over all cross-overs of the squared difference between the length of the
+    /// cross-over and the distance between two consecutive nucleotides.
+    fn strain(&mut self, rolls: &[f32]) -> f32 {
+        self.set_rolls(rolls);
+        let parameters = &self.data.parameters;
+        let mut ret = 0.;
+        for (n1, n2) in self.data.xovers.iter() {
+            let h1 = &self.data.helices[self.data.helix_map[&n1.helix]];
+            let h2 = &self.data.helices[self.data.helix_map[&n2.helix]];
+            let pos1 = h1.space_pos(parameters, n1.position, n1.forward);
+            let pos2 = h2.space_pos(parameters, n2.position, n2.forward);
+            let delta = (pos1 - pos2).mag() - dist_ac(parameters);
+            ret += delta * delta;
+        }
+        ret
+    }
+}
+
+/// A simplex of the Nelder-Mead method. Each vertex is stored with the value of the minimised
+/// function at that vertex.
+struct Simplex {
+    vertices: Vec<(Vec<f32>, f32)>,
+}
+
+impl Simplex {
+    fn new<F: FnMut(&[f32]) -> f32>(x0: Vec<f32>, step: f32, mut f: F) -> Self {
+        let mut vertices = Vec::with_capacity(x0.len() + 1);
+        for i in 0..x0.len() {
+            let mut x = x0.clone();
+            x[i] += step;
+            let value = f(&x);
+            vertices.push((x, value));
+        }
+        let value = f(&x0);
+        vertices.push((x0, value));
+        let mut ret = Self { vertices };
+        ret.sort();
+        ret
+    }
+
+    fn sort(&mut self) {
+        self.vertices
+            .sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+    }
+
+    fn best(&self) -> (&[f32], f32) {
+        let (x, value) = &self.vertices[0];
+        (x.as_slice(), *value)
+    }
+
+    /// The difference between the worst and the best value of the function on the simplex
+    fn spread(&self) -> f32 {
+        self.vertices.last().map(|v| v.1).unwrap_or(0.) - self.vertices[0].1
+    }
+
+    /// Return `centroid + coeff * (centroid - worst)`
+    fn point_on_line(centroid: &[f32], worst: &[f32], coeff: f32) -> Vec<f32> {
+        centroid
+            .iter()
+            .zip(worst.iter())
+            .map(|(c, w)| c + coeff * (c - w))
+            .collect()
+    }
+
+    /// Perform one iteration of the Nelder-Mead method.
+    fn step<F: FnMut(&[f32]) -> f32>(&mut self, mut f: F) {
+        let nb_vertices = self.vertices.len();
+        if nb_vertices < 2 {
+            return;
+        }
+        let dim = nb_vertices - 1;
+        let mut centroid = vec![0.; dim];
+        for (x, _) in self.vertices[..dim].iter() {
+            for (c, x) in centroid.iter_mut().zip(x.iter()) {
+                *c += x / dim as f32;
+            }
+        }
+        let (worst, worst_value) = self.vertices[dim].clone();
+        let best_value = self.vertices[0].1;
+        let second_worst_value = self.vertices[dim - 1].1;
+
+        let reflected = Self::point_on_line(&centroid, &worst, REFLECTION);
+        let reflected_value = f(&reflected);
+        if reflected_value < best_value {
+            let expanded = Self::point_on_line(&centroid, &worst, EXPANSION);
+            let expanded_value = f(&expanded);
+            if expanded_value < reflected_value {
+                self.vertices[dim] = (expanded, expanded_value);
+            } else {
+                self.vertices[dim] = (reflected, reflected_value);
+            }
+        } else if reflected_value < second_worst_value {
+            self.vertices[dim] = (reflected, reflected_value);
+        } else {
+            let contracted = Self::point_on_line(&centroid, &worst, -CONTRACTION);
+            let contracted_value = f(&contracted);
+            if contracted_value < worst_value {
+                self.vertices[dim] = (contracted, contracted_value);
+            } else {
+                let best = self.vertices[0].0.clone();
+                for (x, value) in self.vertices[1..].iter_mut() {
+                    for (x, b) in x.iter_mut().zip(best.iter()) {
+                        *x = b + SHRINK * (*x - b);
+                    }
+                    *value = f(x);
+                }
+            }
+        }
+        self.sort();
+    }
+}
+
+#[derive(Default)]
+pub struct RollOptimizerInterface {
+    new_state: Option<RollState>,
+    /// The number of iterations performed by the optimizer
+    pub iteration: usize,
+    /// The total strain of the current configuration
+    pub strain: f32,
+    converged: bool,
+}
+
+impl super::SimulationInterface for RollOptimizerInterface {
+    fn get_simulation_state(&mut self) -> Option<Box<dyn crate::app_state::SimulationUpdate>> {
+        let s = self.new_state.take()?;
+        Some(Box::new(s))
+    }
+
+    fn still_valid(&self) -> bool {
+        !self.converged
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn simplex_finds_minimum_of_quadratic() {
+        let f = |x: &[f32]| (x[0] - 1.).powi(2) + (x[1] + 2.).powi(2);
+        let mut simplex = Simplex::new(vec![0., 0.], 1., f);
+        let mut nb_step = 0;
+        while simplex.spread() > 1e-8 && nb_step < 1000 {
+            simplex.step(f);
+            nb_step += 1;
+        }
+        let (x, value) = simplex.best();
+        assert!(value < 1e-4);
+        assert!((x[0] - 1.).abs() < 1e-2);
+        assert!((x[1] + 2.).abs() < 1e-2);
+    }
+}
@@ -106,7 +106,7 @@ fn angle_aoc2(p: &Parameters) -> f32 {
     2. * PI / p.bases_per_turn
 }
 
-fn dist_ac(p: &Parameters) -> f32 {
+pub(super) fn dist_ac(p: &Parameters) -> f32 {
     (dist_ac2(p) * dist_ac2(p) + p.z_step * p.z_step).sqrt()
 }
 
@@ -294,7 +294,7 @@ pub struct DesignData {
 }
 
 impl DesignData {
-    fn get_simulation_state(&self) -> RollState {
+    pub(super) fn get_simulation_state(&self) -> RollState {
         let mut ret = HashMap::new();
         for (k, n) in self.helix_map.iter() {
             ret.insert(*k, self.helices[*n].clone());
@@ -22,6 +22,9 @@ use ensnano_design::{codenano, scadnano, Nucl};
 use std::path::{Path, PathBuf};
 
 mod cadnano;
+pub use cadnano::CadnanoExportError;
+mod design_graph;
+use design_graph::DesignGraph;
 mod junctions;
 use junctions::StrandJunction;
 
@@ -30,8 +33,30 @@ impl DesignInteractor {
     /// * codenano
     /// * icednano
     pub fn new_with_path(json_path: &PathBuf) -> Result<Self, ParseDesignError> {
-        let mut xover_ids: IdGenerator<(Nucl, Nucl)> = Default::default();
+        let design = read_file(json_path)?;
+        Ok(Self::new_with_design(design))
+    }
+
+    /// Create a new data by reading a file, and the data of the states of the design embedded in
+    /// the file, oldest first.
+    pub fn new_with_path_and_history(
+        json_path: &PathBuf,
+    ) -> Result<(Self, Vec<Self>), ParseDesignError> {
         let mut design = read_file(json_path)?;
+        let history = design
+            .take_edit_history()
+            .unwrap_or_else(|e| {
+                log::error!("Could not read the edit history of {:?}: {}", json_path, e);
+                Vec::new()
+            })
+            .into_iter()
+            .map(Self::new_with_design)
+            .collect();
+        Ok((Self::new_with_design(design), history))
+    }
+
+    fn new_with_design(mut design: Design) -> Self {
+        let mut xover_ids: IdGenerator<(Nucl, Nucl)> = Default::default();
         design.update_version();
         design.remove_empty_domains();
         for s in design.strands.values_mut() {
@@ -44,21 +69,38 @@ impl DesignInteractor {
         let suggestion_parameters = SuggestionParameters::default();
         let (presenter, design_ptr) =
             Presenter::from_new_design(design, &xover_ids, suggestion_parameters);
-        let ret = Self {
+        Self {
             design: design_ptr,
             presenter: AddressPointer::new(presenter),
             ..Default::default()
-        };
-        Ok(ret)
+        }
     }
 }
 
+/// Read the design stored in the file at `path` so that it can be merged with an other design
+pub fn read_design_to_merge(path: &Path) -> Result<Design, ParseDesignError> {
+    let mut design = read_file(path)?;
+    design.update_version();
+    design.remove_empty_domains();
+    Ok(design)
+}
+
 /// Create a design by parsing a file
 use cadnano::{Cadnano, FromCadnano};
 fn read_file<P: AsRef<Path> + std::fmt::Debug>(path: P) -> Result<Design, ParseDesignError> {
     let json_str =
         std::fs::read_to_string(&path).unwrap_or_else(|_| panic!("File not found {:?}", path));
 
+    if is_scadnano_file(&path) {
+        // Do not fall back to the other formats so that the scadnano import errors are reported
+        let scadnano_design: scadnano::ScadnanoDesign =
+            serde_json::from_str(&json_str).map_err(|e| {
+                log::error!("{:?}", e);
+                ParseDesignError::UnrecognizedFileFormat
+            })?;
+        return Ok(Design::from_scadnano(&scadnano_design)?);
+    }
+
     let design: Result<Design, _> = serde_json::from_str(&json_str);
     // First try to read icednano format
     if let Ok(design) = design {
@@ -88,6 +130,44 @@ fn read_file<P: AsRef<Path> + std::fmt::Debug>(path: P) -> Result<Design, ParseD
     }
 }
 
+/// Return true if the extension of `path` is one of the scadnano extensions
+fn is_scadnano_file<P: AsRef<Path>>(path: P) -> bool {
+    path.as_ref()
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| crate::consts::SCADNANO_EXTENSIONS.contains(&e))
+        .unwrap_or(false)
+}
+
+/// Write the design in the cadnano v2 format
+pub(super) fn cadnano_export(design: &Design, path: &PathBuf) -> Result<(), CadnanoExportError> {
+    let name = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let cadnano = cadnano::to_cadnano(design, name)?;
+    let json_content = serde_json::to_string(&cadnano)?;
+    std::fs::write(path, json_content)?;
+    Ok(())
+}
+
+/// Write the connectivity graph of the design. The graph is written in the DOT format if the
+/// extension of `path` is `dot` or `gv`, and in json otherwise.
+pub(super) fn connectivity_graph_export(design: &Design, path: &PathBuf) -> std::io::Result<()> {
+    let graph = DesignGraph::from_design(design);
+    let is_dot = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext == "dot" || ext == "gv")
+        .unwrap_or(false);
+    let content = if is_dot {
+        graph.to_dot()
+    } else {
+        serde_json::to_string_pretty(&graph)?
+    };
+    std::fs::write(path, content)
+}
+
 use scadnano::ScadnanoImportError;
 pub enum ParseDesignError {
     UnrecognizedFileFormat,
@@ -119,4 +199,58 @@ mod tests {
         let design = interactor.design.as_ref();
         assert_eq!(design.helices.len(), 1);
     }
+
+    #[test]
+    fn cadnano_export_round_trip() {
+        let mut path = PathBuf::from(std::env!("CARGO_MANIFEST_DIR"));
+        path.push("tests");
+        path.push("one_xover.json");
+        let design = read_file(&path).ok().unwrap();
+        let export_path = crate::utils::unique_temp_dir("ensnano_cadnano_export_test")
+            .unwrap()
+            .join("export.json");
+        cadnano_export(&design, &export_path).unwrap();
+        let reimported = read_file(&export_path).ok().unwrap();
+        assert_eq!(reimported.helices.len(), 2);
+        assert_eq!(reimported.strands.len(), 1);
+        let strand = reimported.strands.values().next().unwrap();
+        assert_eq!(strand.length(), 12);
+        assert_eq!(strand.domains.len(), 2);
+    }
+
+    #[test]
+    fn cadnano_import_sets_scaffold_and_grid() {
+        let mut path = PathBuf::from(std::env!("CARGO_MANIFEST_DIR"));
+        path.push("tests");
+        path.push("one_xover.json");
+        let mut design = read_file(&path).ok().unwrap();
+        design.scaffold_id = design.strands.keys().next().cloned();
+        let export_path = crate::utils::unique_temp_dir("ensnano_cadnano_import_test")
+            .unwrap()
+            .join("export.json");
+        cadnano_export(&design, &export_path).unwrap();
+        let reimported = read_file(&export_path).ok().unwrap();
+        assert_eq!(reimported.grids.len(), 1);
+        assert!(reimported
+            .helices
+            .values()
+            .all(|h| h.grid_position.map(|p| p.grid) == Some(0)));
+        let scaffold_id = reimported.scaffold_id.expect("no scaffold");
+        assert_eq!(reimported.strands[&scaffold_id].length(), 12);
+    }
+
+    #[test]
+    fn connectivity_graph_one_xover() {
+        let mut path = PathBuf::from(std::env!("CARGO_MANIFEST_DIR"));
+        path.push("tests");
+        path.push("one_xover.json");
+        let design = read_file(&path).ok().unwrap();
+        let graph = DesignGraph::from_design(&design);
+        assert_eq!(graph.nodes.len(), 2);
+        assert_eq!(graph.edges.len(), 1);
+        let edge = &graph.edges[0];
+        assert_ne!(edge.helix_a, edge.helix_b);
+        let dot = graph.to_dot();
+        assert!(dot.contains(&format!("h{} -- h{}", edge.helix_a, edge.helix_b)));
+    }
 }
@@ -17,8 +17,9 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 */
 pub use cadnano_format::Cadnano;
 use cadnano_format::VStrand;
-use ensnano_design::grid::{Grid, GridType};
+use ensnano_design::grid::{Grid, GridType, GridTypeDescr};
 use ensnano_design::{Design, Domain, Helix, HelixInterval, Nucl, Strand};
+use serde_derive::Serialize;
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::Arc;
 use ultraviolet::{Rotor3, Vec3};
@@ -57,10 +58,11 @@ impl FromCadnano for Design {
                 GridType::square(),
             )
         };
+        // All the helices are attached to the single lattice of the cadnano design
+        design.grids = Arc::new(vec![grid.desc()]);
         for (i, v) in vstrands.iter().enumerate() {
             num_to_helix.insert(v.num, i);
-            let position = grid.position_helix(v.col, v.row);
-            let helix = Helix::new(position, Rotor3::identity());
+            let helix = Helix::new_on_grid(&grid, v.col, v.row, 0);
             helices.insert(i, Arc::new(helix));
             for (j, color) in v.stap_colors.iter() {
                 colors.insert((i, *j as usize), *color as usize);
@@ -68,24 +70,27 @@ impl FromCadnano for Design {
         }
         num_to_helix.insert(-1, NO_HELIX);
 
+        let mut scaffold_strands = Vec::new();
         for scaf in vec![false, true] {
             for i in 0..vstrands.len() {
                 let v = &vstrands[i];
                 for j in 0..v.stap.len() {
                     let result = if scaf { v.scaf[j] } else { v.stap[j] };
                     if seen.insert((i, j, scaf)) && result != (-1, -1, -1, -1) {
-                        println!("{}, {}, {}", scaf, i, j);
                         let end_5 = find_5_end(i, j, &vstrands, &num_to_helix, scaf);
-                        println!("end: {:?}", end_5);
                         let strand =
                             make_strand(end_5, &vstrands, &num_to_helix, &mut seen, scaf, &colors);
+                        if scaf {
+                            scaffold_strands.push((strand.length(), nb_strand));
+                        }
                         design.strands.insert(nb_strand, strand);
                         nb_strand += 1;
                     }
                 }
             }
         }
-        println!("color {:?}", colors);
+        // If the scaffold path is broken, the longest piece is chosen as the scaffold
+        design.scaffold_id = scaffold_strands.iter().max().map(|(_, s_id)| *s_id);
         design.helices = Arc::new(helices);
         design
     }
@@ -127,7 +132,6 @@ fn make_strand(
     scaf: bool,
     colors: &BTreeMap<(usize, usize), usize>,
 ) -> Strand {
-    println!("making strand {:?}", end_5);
     let cyclic = end_5.2;
     let (mut i, mut j) = (end_5.0, end_5.1);
     let mut ret = Strand {
@@ -137,6 +141,8 @@ fn make_strand(
         cyclic,
         color: crate::consts::SCAFFOLD_COLOR,
         name: None,
+        modification_5prime: None,
+        modification_3prime: None,
     };
 
     let mut insertions = Vec::new();
@@ -151,9 +157,9 @@ fn make_strand(
         while i == curent_helix && (i != end_5.0 || j != end_5.1 || !once) {
             once = true;
             curent_3 = j;
-            println!("nucl {}, {}", i, j);
             if let Some(color) = colors.get(&(i, j)).filter(|_| !scaf) {
-                ret.color = *color as u32;
+                // cadnano colors have no alpha channel
+                ret.color = 0xFF_00_00_00 | *color as u32;
             }
             seen.insert((i, j, scaf));
             let result = if scaf {
@@ -165,11 +171,9 @@ fn make_strand(
             if vstrands[i].loop_[j] > 0 {
                 insertions_on_dom.push((j, insertion_size));
             }
-            println!("result {:?}", result);
             i = num_to_helix[&result.2];
             j = result.3 as usize;
         }
-        println!("ready to build domain");
         let forward = curent_3 >= curent_5;
         let start = if forward {
             substract_skips(curent_5, curent_helix, vstrands)
@@ -192,7 +196,6 @@ fn make_strand(
             ));
         }
 
-        println!("pushing {} {} {} {}", curent_helix, start, end, forward);
         ret.domains.push(Domain::HelixDomain(HelixInterval {
             helix: curent_helix,
             start,
@@ -248,3 +251,251 @@ fn substract_skips(nucl: usize, helix: usize, vstrands: &Vec<VStrand>) -> isize
         .sum();
     nucl as isize + skips
 }
+
+/// The length of the helices of exported designs is a multiple of this number for honeycomb
+/// lattices
+const HONEYCOMB_STEP: usize = 21;
+/// The length of the helices of exported designs is a multiple of this number for square lattices
+const SQUARE_STEP: usize = 32;
+
+/// A cadnano v2 design, as written by `to_cadnano`
+#[derive(Serialize)]
+pub(super) struct CadnanoExport {
+    name: String,
+    vstrands: Vec<VStrandExport>,
+}
+
+/// An helix of a cadnano v2 design. Each nucleotide of the `scaf` and `stap` arrays is described
+/// by `[previous helix, previous position, next helix, next position]`, with -1 meaning that
+/// there is no such nucleotide.
+#[derive(Serialize)]
+struct VStrandExport {
+    row: isize,
+    col: isize,
+    num: isize,
+    scaf: Vec<[isize; 4]>,
+    stap: Vec<[isize; 4]>,
+    #[serde(rename = "loop")]
+    loop_: Vec<isize>,
+    skip: Vec<isize>,
+    #[serde(rename = "scafLoop")]
+    scaf_loop: Vec<isize>,
+    #[serde(rename = "stapLoop")]
+    stap_loop: Vec<isize>,
+    stap_colors: Vec<[isize; 2]>,
+}
+
+const NO_NUCL: [isize; 4] = [-1, -1, -1, -1];
+
+#[derive(Debug)]
+pub enum CadnanoExportError {
+    /// The design has no helices
+    EmptyDesign,
+    /// The helix is not attached to a grid
+    HelixNotOnGrid(usize),
+    /// The helices are not all on the same grid
+    SeveralGrids,
+    /// Only square and honeycomb grids can be exported
+    UnsupportedGrid,
+    /// The scaffold and the staples of the helix do not have the opposite directions that
+    /// cadnano expects
+    DirectionConflict(usize),
+    SerdeError(serde_json::Error),
+    IOError(std::io::Error),
+}
+
+impl From<serde_json::Error> for CadnanoExportError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::SerdeError(e)
+    }
+}
+
+impl From<std::io::Error> for CadnanoExportError {
+    fn from(e: std::io::Error) -> Self {
+        Self::IOError(e)
+    }
+}
+
+/// Convert a design whose helices all lie on the same square or honeycomb grid to the cadnano v2
+/// format.
+///
+/// Cadnano deduces the direction of the strands from the parity of the helix numbers, so the
+/// numbers are chosen so that the scaffold goes forward on even helices. Insertions are exported
+/// as loops on the nucleotide that precedes them.
+pub(super) fn to_cadnano(
+    design: &Design,
+    name: String,
+) -> Result<CadnanoExport, CadnanoExportError> {
+    let mut grid_id = None;
+    let mut min_x = isize::MAX;
+    let mut min_y = isize::MAX;
+    for (h_id, h) in design.helices.iter() {
+        let grid_position = h
+            .grid_position
+            .as_ref()
+            .ok_or(CadnanoExportError::HelixNotOnGrid(*h_id))?;
+        if *grid_id.get_or_insert(grid_position.grid) != grid_position.grid {
+            return Err(CadnanoExportError::SeveralGrids);
+        }
+        min_x = min_x.min(grid_position.x);
+        min_y = min_y.min(grid_position.y);
+    }
+    let grid_id = grid_id.ok_or(CadnanoExportError::EmptyDesign)?;
+    let step = match design.grids.get(grid_id).map(|g| g.grid_type) {
+        Some(GridTypeDescr::Square) => SQUARE_STEP,
+        Some(GridTypeDescr::Honeycomb) => HONEYCOMB_STEP,
+        _ => return Err(CadnanoExportError::UnsupportedGrid),
+    };
+    // Shift the coordinates by even numbers to preserve the parity of the honeycomb lattice
+    let min_x = min_x - min_x.rem_euclid(2);
+    let min_y = min_y - min_y.rem_euclid(2);
+
+    let intervals = design.strands.values().flat_map(|s| {
+        s.domains.iter().filter_map(|d| {
+            if let Domain::HelixDomain(dom) = d {
+                Some(dom)
+            } else {
+                None
+            }
+        })
+    });
+    let min_pos = intervals
+        .clone()
+        .map(|dom| dom.start)
+        .min()
+        .unwrap_or(0)
+        .min(0);
+    let max_end = intervals.map(|dom| dom.end).max().unwrap_or(0);
+    let offset = -min_pos;
+    let mut length = ((max_end + offset) as usize + step - 1) / step * step;
+    length = length.max(step);
+    if step == SQUARE_STEP && length % HONEYCOMB_STEP == 0 {
+        // The importer would mistake the design for a honeycomb one
+        length += SQUARE_STEP;
+    }
+
+    // Decide the direction of the scaffold on each helix
+    let mut scaffold_forward: HashMap<usize, bool> = HashMap::new();
+    for (s_id, s) in design.strands.iter() {
+        let is_scaffold = design.scaffold_id == Some(*s_id);
+        for d in s.domains.iter() {
+            if let Domain::HelixDomain(dom) = d {
+                if is_scaffold {
+                    scaffold_forward.insert(dom.helix, dom.forward);
+                } else {
+                    scaffold_forward.entry(dom.helix).or_insert(!dom.forward);
+                }
+            }
+        }
+    }
+
+    let mut vstrands = Vec::with_capacity(design.helices.len());
+    let mut helix_to_vstrand: HashMap<usize, usize> = HashMap::new();
+    let mut nb_even = 0;
+    let mut nb_odd = 0;
+    for (h_id, h) in design.helices.iter() {
+        // unwrap because we checked that all helices are on the grid
+        let grid_position = h.grid_position.as_ref().unwrap();
+        let num = if scaffold_forward.get(h_id).cloned().unwrap_or(true) {
+            nb_even += 2;
+            nb_even - 2
+        } else {
+            nb_odd += 2;
+            nb_odd - 1
+        };
+        helix_to_vstrand.insert(*h_id, vstrands.len());
+        vstrands.push(VStrandExport {
+            row: grid_position.y - min_y,
+            col: grid_position.x - min_x,
+            num,
+            scaf: vec![NO_NUCL; length],
+            stap: vec![NO_NUCL; length],
+            loop_: vec![0; length],
+            skip: vec![0; length],
+            scaf_loop: vec![],
+            stap_loop: vec![],
+            stap_colors: vec![],
+        });
+    }
+
+    for (s_id, s) in design.strands.iter() {
+        let is_scaffold = design.scaffold_id == Some(*s_id);
+        // The nucleotides of the strand from 5' to 3', as (vstrand, index) pairs
+        let mut nucls: Vec<(usize, usize)> = Vec::new();
+        let mut pending_insertion = 0;
+        for d in s.domains.iter() {
+            match d {
+                Domain::HelixDomain(dom) => {
+                    let v = helix_to_vstrand[&dom.helix];
+                    if scaffold_forward.get(&dom.helix) != Some(&(dom.forward == is_scaffold)) {
+                        return Err(CadnanoExportError::DirectionConflict(dom.helix));
+                    }
+                    let positions: Vec<isize> = if dom.forward {
+                        (dom.start..dom.end).collect()
+                    } else {
+                        (dom.start..dom.end).rev().collect()
+                    };
+                    for pos in positions {
+                        nucls.push((v, (pos + offset) as usize));
+                    }
+                    if pending_insertion > 0 {
+                        // The insertion is at the 5' end of the strand
+                        if let Some((v, idx)) = nucls.first() {
+                            vstrands[*v].loop_[*idx] += pending_insertion;
+                        }
+                        pending_insertion = 0;
+                    }
+                }
+                Domain::Insertion(n) => {
+                    if let Some((v, idx)) = nucls.last() {
+                        vstrands[*v].loop_[*idx] += *n as isize;
+                    } else {
+                        pending_insertion += *n as isize;
+                    }
+                }
+            }
+        }
+        if s.cyclic && nucls.len() > 1 && nucls.first() == nucls.last() {
+            nucls.pop();
+        }
+        let mut links: Vec<((usize, usize), (usize, usize))> =
+            nucls.windows(2).map(|w| (w[0], w[1])).collect();
+        if s.cyclic && nucls.len() > 1 {
+            links.push((nucls[nucls.len() - 1], nucls[0]));
+        }
+        for ((v1, idx1), (v2, idx2)) in links {
+            let num1 = vstrands[v1].num;
+            let num2 = vstrands[v2].num;
+            let array1 = if is_scaffold {
+                &mut vstrands[v1].scaf
+            } else {
+                &mut vstrands[v1].stap
+            };
+            array1[idx1][2] = num2;
+            array1[idx1][3] = idx2 as isize;
+            let array2 = if is_scaffold {
+                &mut vstrands[v2].scaf
+            } else {
+                &mut vstrands[v2].stap
+            };
+            array2[idx2][0] = num1;
+            array2[idx2][1] = idx1 as isize;
+        }
+        if nucls.len() == 1 {
+            log::warn!(
+                "Strand {} has a single nucleotide and cannot be exported",
+                s_id
+            );
+            continue;
+        }
+        if !is_scaffold {
+            if let Some((v, idx)) = nucls.first() {
+                vstrands[*v]
+                    .stap_colors
+                    .push([*idx as isize, (s.color & 0xFF_FF_FF) as isize]);
+            }
+        }
+    }
+
+    Ok(CadnanoExport { name, vstrands })
+}
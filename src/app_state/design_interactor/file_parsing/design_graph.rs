@@ -0,0 +1,104 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! The connectivity graph of a design, in which the nodes are the helices and the edges are the
+//! cross-overs. It can be written in json or in the DOT format to be analysed with external
+//! tools such as NetworkX or Graphviz.
+
+use ensnano_design::Design;
+use serde_derive::Serialize;
+use std::fmt::Write;
+
+#[derive(Serialize, Debug)]
+pub(super) struct DesignGraph {
+    pub nodes: Vec<HelixNode>,
+    pub edges: Vec<CrossoverEdge>,
+}
+
+#[derive(Serialize, Debug)]
+pub(super) struct HelixNode {
+    pub id: usize,
+    /// The position of the origin of the helix axis
+    pub position: [f32; 3],
+    /// The number of positions of the helix that are covered by a strand
+    pub length: usize,
+}
+
+#[derive(Serialize, Debug)]
+pub(super) struct CrossoverEdge {
+    /// The helix of the 3' end of the cross-over
+    pub helix_a: usize,
+    /// The helix of the 5' end of the cross-over
+    pub helix_b: usize,
+    pub position_a: isize,
+    pub position_b: isize,
+    pub strand_id: usize,
+}
+
+impl DesignGraph {
+    pub fn from_design(design: &Design) -> Self {
+        let intervals = design.get_intervals();
+        let nodes = design
+            .helices
+            .iter()
+            .map(|(h_id, h)| HelixNode {
+                id: *h_id,
+                position: [h.position.x, h.position.y, h.position.z],
+                length: intervals
+                    .get(h_id)
+                    .map(|(left, right)| (right - left + 1).max(0) as usize)
+                    .unwrap_or(0),
+            })
+            .collect();
+        let mut edges = Vec::new();
+        for (s_id, strand) in design.strands.iter() {
+            for (prime3, prime5) in strand.xovers() {
+                edges.push(CrossoverEdge {
+                    helix_a: prime3.helix,
+                    helix_b: prime5.helix,
+                    position_a: prime3.position,
+                    position_b: prime5.position,
+                    strand_id: *s_id,
+                })
+            }
+        }
+        Self { nodes, edges }
+    }
+
+    /// Return a description of the graph in the DOT language
+    pub fn to_dot(&self) -> String {
+        let mut ret = String::from("graph design {\n");
+        for node in self.nodes.iter() {
+            // Writing in a String cannot fail
+            let _ = writeln!(
+                ret,
+                "    h{} [length={}, x={}, y={}, z={}];",
+                node.id, node.length, node.position[0], node.position[1], node.position[2]
+            );
+        }
+        for edge in self.edges.iter() {
+            let _ = writeln!(
+                ret,
+                "    h{} -- h{} [position_a={}, position_b={}, strand_id={}];",
+                edge.helix_a, edge.helix_b, edge.position_a, edge.position_b, edge.strand_id
+            );
+        }
+        ret.push_str("}\n");
+        ret
+    }
+}
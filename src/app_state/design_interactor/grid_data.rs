@@ -307,6 +307,7 @@ impl GridManager {
                 orientation: square_grid.orientation,
                 grid_type: GridTypeDescr::Square,
                 invisible: square_grid.invisible,
+                twist_offset: square_grid.twist_offset,
             }
         } else {
             GridDescriptor {
@@ -314,6 +315,7 @@ impl GridManager {
                 orientation: hex_grid.orientation,
                 grid_type: GridTypeDescr::Honeycomb,
                 invisible: hex_grid.invisible,
+                twist_offset: hex_grid.twist_offset,
             }
         }
     }
@@ -20,9 +20,10 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 pub use self::design_content::Staple;
 
 use super::*;
-use ensnano_design::{Extremity, Nucl};
+use ensnano_design::{elements::DnaElementKey, Domain, Extremity, HelixInterval, Nucl};
 use ensnano_interactor::{
-    NeighbourDescriptor, NeighbourDescriptorGiver, ScaffoldInfo, Selection, SuggestionParameters,
+    NeighbourDescriptor, NeighbourDescriptorGiver, ScaffoldInfo, Selection, SelectionConversion,
+    StapleStatistics, SuggestionParameters,
 };
 use ultraviolet::Mat4;
 
@@ -34,6 +35,8 @@ mod impl_reader2d;
 mod impl_reader3d;
 mod impl_readergui;
 mod oxdna;
+pub use oxdna::OxDnaTrajectoryReader;
+mod pdb;
 use ahash::AHashMap;
 use design_content::DesignContent;
 use std::collections::{BTreeMap, HashSet};
@@ -77,7 +80,94 @@ impl Default for Presenter {
 impl Presenter {
     #[cfg(test)]
     pub(super) fn get_staples(&self) -> Vec<Staple> {
-        self.content.get_staples(&self.current_design)
+        self.content
+            .get_staples(&self.current_design, Default::default())
+    }
+
+    pub fn get_basis_of_nucl(&self, nucl: &Nucl) -> Option<char> {
+        self.content.basis_map.get(nucl).cloned()
+    }
+
+    /// Return the melting temperature of a domain, if its sequence is known
+    fn domain_melting_temperature(&self, domain: &HelixInterval) -> Option<f32> {
+        let sequence: Option<String> = domain
+            .iter()
+            .map(|position| {
+                let nucl = Nucl::new(domain.helix, position, domain.forward);
+                self.content.basis_map.get(&nucl)
+            })
+            .collect();
+        ensnano_design::melting_temperature(sequence?.as_str()).map(|tm| tm as f32)
+    }
+
+    /// Return, for each nucleotide of the staples, the melting temperature of its domain
+    pub fn get_staples_melting_temperatures(&self) -> HashMap<Nucl, f32> {
+        let design = &self.current_design;
+        let mut ret = HashMap::new();
+        for (s_id, strand) in design.strands.iter() {
+            if design.scaffold_id == Some(*s_id) {
+                continue;
+            }
+            for domain in strand.domains.iter() {
+                if let Domain::HelixDomain(dom) = domain {
+                    if let Some(tm) = self.domain_melting_temperature(dom) {
+                        for position in dom.iter() {
+                            ret.insert(Nucl::new(dom.helix, position, dom.forward), tm);
+                        }
+                    }
+                }
+            }
+        }
+        ret
+    }
+
+    /// Return the length, number of domains and melting temperature of each staple
+    pub fn get_staple_statistics(&self) -> Vec<StapleStatistics> {
+        let design = &self.current_design;
+        design
+            .strands
+            .iter()
+            .filter(|(s_id, _)| design.scaffold_id != Some(**s_id))
+            .map(|(s_id, strand)| {
+                let helix_domains: Vec<&HelixInterval> = strand
+                    .domains
+                    .iter()
+                    .filter_map(|d| match d {
+                        Domain::HelixDomain(dom) => Some(dom),
+                        _ => None,
+                    })
+                    .collect();
+                let melting_temperature = helix_domains
+                    .iter()
+                    .filter_map(|dom| self.domain_melting_temperature(dom))
+                    .fold(None, |max: Option<f32>, tm| {
+                        Some(max.map(|m| m.max(tm)).unwrap_or(tm))
+                    });
+                StapleStatistics {
+                    strand_id: *s_id,
+                    length: strand.length(),
+                    nb_domains: helix_domains.len(),
+                    melting_temperature,
+                }
+            })
+            .collect()
+    }
+
+    /// Write the helices `helix_ids`, with the strands that lie on them, in a design file at
+    /// `path`
+    pub fn export_subdesign(&self, helix_ids: &[usize], path: &PathBuf) -> std::io::Result<()> {
+        use std::io::{Error, ErrorKind};
+        let design = &self.current_design;
+        if let Some(h_id) = helix_ids.iter().find(|h| !design.helices.contains_key(*h)) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Helix {} does not exist", h_id),
+            ));
+        }
+        let module = design.sub_design(helix_ids);
+        let json_content = serde_json::to_string_pretty(&module)
+            .map_err(|e| Error::new(ErrorKind::Other, e.to_string()))?;
+        std::fs::write(path, json_content)
     }
 
     pub fn can_start_builder_at(&self, nucl: Nucl) -> bool {
@@ -284,6 +374,10 @@ impl Presenter {
                     }
                     Selection::Bound(_, n1, n2) => *n1 == nucl || *n2 == nucl,
                     Selection::Phantom(e) => e.to_nucl() == nucl,
+                    Selection::Domain(_, domain_id) => {
+                        self.current_design.get_scaffold_domain_id(&nucl)
+                            == Some(*domain_id as usize)
+                    }
                 };
         }
         ret
@@ -502,6 +596,38 @@ impl DesignReader {
             .map(|c| (c.position, c.orientation))
     }
 
+    /// The positions and orientations of all the cameras stored in the design, in order
+    pub fn get_all_camera_viewpoints(&self) -> Vec<(Vec3, ultraviolet::Rotor3)> {
+        self.presenter
+            .current_design
+            .get_cameras()
+            .map(|c| (c.1.position, c.1.orientation))
+            .collect()
+    }
+
+    /// The elements of the selection group `name` that still exist in the design
+    pub fn get_selection_group(&self, name: &str) -> Option<Vec<Selection>> {
+        let design = &self.presenter.current_design;
+        let exists = |element: &DnaElementKey| match element {
+            DnaElementKey::Grid(g_id) => *g_id < design.grids.len(),
+            DnaElementKey::Helix(h_id) => design.helices.contains_key(h_id),
+            DnaElementKey::Strand(s_id) => design.strands.contains_key(s_id),
+            DnaElementKey::Nucleotide { helix, .. } => design.helices.contains_key(helix),
+            DnaElementKey::CrossOver { xover_id } => self
+                .presenter
+                .junctions_ids
+                .get_element(*xover_id)
+                .is_some(),
+        };
+        design.selection_groups.get(name).map(|elements| {
+            elements
+                .iter()
+                .filter(|element| exists(element))
+                .map(|element| element.to_selection(0))
+                .collect()
+        })
+    }
+
     pub fn get_nth_camera(&self, n: u32) -> Option<(Vec3, ultraviolet::Rotor3)> {
         self.presenter
             .current_design
@@ -593,6 +719,11 @@ pub trait SimulationUpdate: Send + Sync {
     }
 
     fn update_design(&self, design: &mut Design);
+
+    /// The kinetic energy of the simulated system, if the simulation computes one
+    fn kinetic_energy(&self) -> Option<f32> {
+        None
+    }
 }
 
 #[derive(Clone)]
@@ -17,6 +17,7 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 */
 
 use super::*;
+use crate::controller::PlateSize;
 use crate::scene::GridInstance;
 use ahash::RandomState;
 use ensnano_design::elements::DnaElement;
@@ -168,7 +169,7 @@ impl DesignContent {
         None
     }
 
-    pub(super) fn get_staples(&self, design: &Design) -> Vec<Staple> {
+    pub(super) fn get_staples(&self, design: &Design, plate_size: PlateSize) -> Vec<Staple> {
         let mut ret = Vec::new();
         let mut sequences: BTreeMap<(usize, isize, usize, isize), StapleInfo> = Default::default();
         let basis_map = self.basis_map.as_ref();
@@ -206,26 +207,18 @@ impl DesignContent {
                     s_id: *s_id,
                     sequence,
                     strand_name: strand.name.clone(),
+                    modification_5prime: strand.modification_5prime,
+                    modification_3prime: strand.modification_3prime,
                 },
             );
         }
         for (n, ((h5, nt5, h3, nt3), staple_info)) in sequences.iter().enumerate() {
-            let plate = n / 96 + 1;
-            let row = (n % 96) / 8 + 1;
-            let column = match (n % 96) % 8 {
-                0 => 'A',
-                1 => 'B',
-                2 => 'C',
-                3 => 'D',
-                4 => 'E',
-                5 => 'F',
-                6 => 'G',
-                7 => 'H',
-                _ => unreachable!(),
-            };
+            let (plate, well) = plate_size.well_position(n);
             ret.push(Staple {
+                s_id: staple_info.s_id,
                 plate,
-                well: format!("{}{}", column, row.to_string()),
+                well,
+                length: staple_info.sequence.chars().filter(|c| *c != ' ').count(),
                 sequence: staple_info.sequence.clone(),
                 name: staple_info.strand_name.clone().unwrap_or_else(|| {
                     format!(
@@ -234,6 +227,9 @@ impl DesignContent {
                     )
                     .into()
                 }),
+                strand_name: staple_info.strand_name.clone(),
+                modification_5prime: staple_info.modification_5prime,
+                modification_3prime: staple_info.modification_3prime,
             });
         }
         ret
@@ -287,16 +283,25 @@ impl DesignContent {
 
 #[derive(Debug)]
 pub struct Staple {
+    pub s_id: usize,
     pub well: String,
     pub name: Cow<'static, str>,
     pub sequence: String,
+    /// The number of nucleotides of the staple
+    pub length: usize,
     pub plate: usize,
+    /// The name given to the strand by the user, if any
+    pub strand_name: Option<Cow<'static, str>>,
+    pub modification_5prime: Option<StrandModification>,
+    pub modification_3prime: Option<StrandModification>,
 }
 
 struct StapleInfo {
     s_id: usize,
     sequence: String,
     strand_name: Option<Cow<'static, str>>,
+    modification_5prime: Option<StrandModification>,
+    modification_3prime: Option<StrandModification>,
 }
 
 #[derive(Clone)]
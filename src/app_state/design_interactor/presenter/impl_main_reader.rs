@@ -17,7 +17,10 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 */
 
 use super::*;
-use crate::controller::{DownloadStappleError, DownloadStappleOk, StaplesDownloader};
+use crate::controller::{DownloadStappleError, DownloadStappleOk, PlateSize, StaplesDownloader};
+use ensnano_design::{DesignIssue, StrandModification};
+use ensnano_interactor::OrderFormat;
+use std::borrow::Cow;
 use std::path::PathBuf;
 
 impl StaplesDownloader for DesignReader {
@@ -29,6 +32,13 @@ impl StaplesDownloader for DesignReader {
         if self.presenter.current_design.scaffold_sequence.is_none() {
             return Err(DownloadStappleError::ScaffoldSequenceNotSet);
         }
+        for issue in self.presenter.current_design.validate() {
+            match issue {
+                DesignIssue::ScaffoldNotSingleCycle(breaks) => {
+                    return Err(DownloadStappleError::ScaffoldNotSingleCycle(breaks))
+                }
+            }
+        }
 
         if let Some(nucl) = self
             .presenter
@@ -64,27 +74,95 @@ impl StaplesDownloader for DesignReader {
         Ok(DownloadStappleOk { warnings })
     }
 
-    fn write_staples_xlsx(&self, xlsx_path: &PathBuf) {
-        use simple_excel_writer::{row, Row, Workbook};
+    fn write_staples_xlsx(
+        &self,
+        xlsx_path: &PathBuf,
+        plate_size: PlateSize,
+        order_format: OrderFormat,
+    ) {
+        use simple_excel_writer::{Row, Workbook};
         let stapples = self
             .presenter
             .content
-            .get_staples(&self.presenter.current_design);
+            .get_staples(&self.presenter.current_design, plate_size);
         let mut wb = Workbook::create(xlsx_path.to_str().unwrap());
         let mut sheets = BTreeMap::new();
 
         for stapple in stapples.iter() {
-            let sheet = sheets
-                .entry(stapple.plate)
-                .or_insert_with(|| vec![vec!["Well Position", "Name", "Sequence"]]);
-            sheet.push(vec![&stapple.well, &stapple.name, &stapple.sequence]);
+            let sheet = sheets.entry(stapple.plate).or_insert_with(Vec::new);
+            sheet.push(order_row(stapple, order_format));
         }
 
         for (sheet_id, rows) in sheets.iter() {
             let mut sheet = wb.create_sheet(&format!("Plate {}", sheet_id));
             wb.write_sheet(&mut sheet, |sw| {
-                for row in rows {
-                    sw.append_row(row![row[0], row[1], row[2]])?;
+                let mut header = Row::new();
+                for column in order_header(order_format) {
+                    header.add_cell(*column);
+                }
+                sw.append_row(header)?;
+                for cells in rows {
+                    let mut row = Row::new();
+                    for cell in cells {
+                        row.add_cell(cell.as_str());
+                    }
+                    sw.append_row(row)?;
+                }
+                Ok(())
+            })
+            .expect("write excel error!");
+        }
+        wb.close().expect("close excel error!");
+    }
+
+    fn write_staples_csv(
+        &self,
+        csv_path: &PathBuf,
+        plate_size: PlateSize,
+        order_format: OrderFormat,
+    ) -> std::io::Result<()> {
+        use std::io::Write;
+        let stapples = self
+            .presenter
+            .content
+            .get_staples(&self.presenter.current_design, plate_size);
+        let mut out = std::io::BufWriter::new(std::fs::File::create(csv_path)?);
+        writeln!(out, "Plate,{}", order_header(order_format).join(","))?;
+        for stapple in stapples.iter() {
+            let cells: Vec<String> = order_row(stapple, order_format)
+                .iter()
+                .map(|cell| csv_field(cell).into_owned())
+                .collect();
+            writeln!(out, "{},{}", stapple.plate, cells.join(","))?;
+        }
+        out.flush()
+    }
+
+    fn write_staple_pools_xlsx(&self, xlsx_path: &PathBuf, n_steps: usize) {
+        use simple_excel_writer::{row, Row, Workbook};
+        let staples: HashMap<usize, Staple> = self
+            .presenter
+            .content
+            .get_staples(&self.presenter.current_design, Default::default())
+            .into_iter()
+            .map(|staple| (staple.s_id, staple))
+            .collect();
+        let pools = self.presenter.current_design.generate_staple_pools(n_steps);
+        let mut wb = Workbook::create(xlsx_path.to_str().unwrap());
+
+        for (pool_id, pool) in pools.iter().enumerate() {
+            let mut sheet = wb.create_sheet(&format!("Pool {}", pool_id + 1));
+            wb.write_sheet(&mut sheet, |sw| {
+                sw.append_row(row!["Name", "Sequence", "Tm (°C)"])?;
+                for staple in pool.iter().filter_map(|s_id| staples.get(s_id)) {
+                    let tm = ensnano_design::melting_temperature(&staple.sequence)
+                        .map(|tm| format!("{:.1}", tm))
+                        .unwrap_or_else(|| "?".to_owned());
+                    sw.append_row(row![
+                        staple.name.as_ref(),
+                        staple.sequence.as_str(),
+                        tm.as_str()
+                    ])?;
                 }
                 Ok(())
             })
@@ -98,6 +176,78 @@ impl StaplesDownloader for DesignReader {
     }
 }
 
+/// The columns of the staple table in the layout of `order_format`
+fn order_header(order_format: OrderFormat) -> &'static [&'static str] {
+    match order_format {
+        OrderFormat::Ensnano => &[
+            "Well Position",
+            "Name",
+            "Sequence",
+            "Length",
+            "Strand name",
+            "5' Modification",
+            "3' Modification",
+        ],
+        OrderFormat::Idt => &["Well Position", "Name", "Sequence"],
+        OrderFormat::Twist => &[
+            "Well Position",
+            "Name",
+            "Sequence",
+            "5' Modification",
+            "3' Modification",
+        ],
+    }
+}
+
+/// The cells of the row describing `staple` in the layout of `order_format`
+fn order_row(staple: &Staple, order_format: OrderFormat) -> Vec<String> {
+    let modification_name = |modification: Option<StrandModification>| {
+        modification.map(|m| m.to_string()).unwrap_or_default()
+    };
+    let compact_sequence: String = staple.sequence.chars().filter(|c| *c != ' ').collect();
+    match order_format {
+        OrderFormat::Ensnano => vec![
+            staple.well.clone(),
+            staple.name.to_string(),
+            staple.sequence.clone(),
+            staple.length.to_string(),
+            staple.strand_name.as_deref().unwrap_or("").to_owned(),
+            modification_name(staple.modification_5prime),
+            modification_name(staple.modification_3prime),
+        ],
+        OrderFormat::Idt => {
+            let prime5 = staple.modification_5prime.map(|m| m.idt_code(true));
+            let prime3 = staple.modification_3prime.map(|m| m.idt_code(false));
+            vec![
+                staple.well.clone(),
+                staple.name.to_string(),
+                format!(
+                    "{}{}{}",
+                    prime5.unwrap_or(""),
+                    compact_sequence,
+                    prime3.unwrap_or("")
+                ),
+            ]
+        }
+        OrderFormat::Twist => vec![
+            staple.well.clone(),
+            staple.name.to_string(),
+            compact_sequence,
+            modification_name(staple.modification_5prime),
+            modification_name(staple.modification_3prime),
+        ],
+    }
+}
+
+/// Quote `field` if it contains characters that have a special meaning in csv files
+fn csv_field(field: &str) -> Cow<str> {
+    if field.contains(|c| c == ',' || c == '"' || c == '\n') {
+        format!("\"{}\"", field.replace('"', "\"\"")).into()
+    } else {
+        field.into()
+    }
+}
+
 fn warn_all_staples_not_paired(first_unpaired: Nucl) -> String {
     format!(
         "All staptes are not paired. First unpaired nucleotide: {}",
@@ -153,4 +303,18 @@ impl MainReader for DesignReader {
             .get(&s_id)
             .map(|s| s.domain_ends())
     }
+
+    fn get_scaffold_domain_nucls(&self, domain_id: usize) -> Option<Vec<Nucl>> {
+        self.presenter
+            .current_design
+            .get_scaffold_domain_nucls(domain_id)
+    }
+
+    fn get_scaffold_continuity(&self) -> ensnano_design::ScaffoldContinuityResult {
+        ensnano_design::check_scaffold_continuity(&self.presenter.current_design)
+    }
+
+    fn get_orphans(&self) -> ensnano_design::Orphans {
+        self.presenter.current_design.orphans()
+    }
 }
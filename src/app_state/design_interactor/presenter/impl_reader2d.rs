@@ -88,6 +88,10 @@ impl Reader2D for DesignReader {
         self.presenter.current_design.groups.clone()
     }
 
+    fn get_helix_display_order(&self) -> Vec<usize> {
+        self.presenter.current_design.helix_display_order.clone()
+    }
+
     fn get_insertions(&self, s_id: usize) -> Option<Vec<Nucl>> {
         self.presenter
             .current_design
@@ -120,6 +124,14 @@ impl Reader2D for DesignReader {
         self.presenter.content.get_helices_on_grid(g_id)
     }
 
+    fn get_grid_type(&self, g_id: usize) -> Option<ensnano_design::grid::GridTypeDescr> {
+        self.presenter
+            .current_design
+            .grids
+            .get(g_id)
+            .map(|grid| grid.grid_type)
+    }
+
     fn get_visibility_helix(&self, h_id: usize) -> Option<bool> {
         self.presenter
             .current_design
@@ -190,6 +202,14 @@ impl Reader2D for DesignReader {
             .flatten()
             .collect()
     }
+
+    fn get_scaffold_domain_id(&self, nucl: &Nucl) -> Option<usize> {
+        self.presenter.current_design.get_scaffold_domain_id(nucl)
+    }
+
+    fn get_melting_temperatures(&self) -> HashMap<Nucl, f32> {
+        self.presenter.get_staples_melting_temperatures()
+    }
 }
 
 #[cfg(test)]
@@ -18,9 +18,9 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 
 use super::*;
 use crate::scene::GridInstance;
-use ensnano_design::{grid::GridPosition, Nucl};
+use ensnano_design::{grid::GridPosition, Domain, Nucl};
 use ensnano_interactor::{ObjectType, Referential};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use ultraviolet::{Mat4, Rotor3, Vec3};
 
 use crate::scene::DesignReader as Reader3D;
@@ -55,6 +55,39 @@ impl Reader3D for DesignReader {
         self.presenter.content.suggestions.clone()
     }
 
+    fn get_anchors(&self) -> Vec<Nucl> {
+        self.presenter
+            .current_design
+            .anchors
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    fn get_annotations(&self) -> Vec<(Nucl, String)> {
+        self.presenter
+            .current_design
+            .annotations
+            .iter()
+            .map(|a| (a.nucl, a.label.clone()))
+            .collect()
+    }
+
+    fn get_insertions(&self) -> Vec<(Nucl, Nucl, usize, u32)> {
+        let mut ret = Vec::new();
+        for strand in self.presenter.current_design.strands.values() {
+            for window in strand.domains.windows(3) {
+                if let [prime5, Domain::Insertion(length), prime3] = window {
+                    if let (Some(prime5), Some(prime3)) = (prime5.prime3_end(), prime3.prime5_end())
+                    {
+                        ret.push((prime5, prime3, *length, strand.color));
+                    }
+                }
+            }
+        }
+        ret
+    }
+
     fn get_object_type(&self, id: u32) -> Option<ObjectType> {
         self.presenter.content.object_type.get(&id).cloned()
     }
@@ -114,6 +147,10 @@ impl Reader3D for DesignReader {
         self.presenter.content.identifier_nucl.get(nucl).cloned()
     }
 
+    fn get_dna_parameters(&self) -> ensnano_design::Parameters {
+        self.presenter.current_design.parameters.unwrap_or_default()
+    }
+
     fn get_position_of_nucl_on_helix(
         &self,
         nucl: Nucl,
@@ -293,6 +330,53 @@ impl Reader3D for DesignReader {
         self.prime3_of_which_strand(nucl)
     }
 
+    fn get_scaffold_domain_id(&self, nucl: &Nucl) -> Option<usize> {
+        self.presenter.current_design.get_scaffold_domain_id(nucl)
+    }
+
+    fn get_domains_of_nucls(&self) -> HashMap<Nucl, (usize, usize)> {
+        let mut ret = HashMap::new();
+        for (s_id, strand) in self.presenter.current_design.strands.iter() {
+            for (d_id, domain) in strand.domains.iter().enumerate() {
+                if let Domain::HelixDomain(interval) = domain {
+                    for position in interval.start..interval.end {
+                        let nucl = Nucl {
+                            helix: interval.helix,
+                            position,
+                            forward: interval.forward,
+                        };
+                        ret.insert(nucl, (*s_id, d_id));
+                    }
+                }
+            }
+        }
+        ret
+    }
+
+    fn get_scaffold_nucls(&self) -> Vec<Nucl> {
+        let design = &self.presenter.current_design;
+        let scaffold = design
+            .scaffold_id
+            .and_then(|s_id| design.strands.get(&s_id));
+        let mut ret = Vec::new();
+        for domain in scaffold.iter().flat_map(|s| s.domains.iter()) {
+            if let Domain::HelixDomain(interval) = domain {
+                for position in interval.iter() {
+                    ret.push(Nucl::new(interval.helix, position, interval.forward));
+                }
+            }
+        }
+        ret
+    }
+
+    fn get_xovers(&self) -> Vec<(Nucl, Nucl)> {
+        self.presenter.current_design.get_xovers()
+    }
+
+    fn get_melting_temperatures(&self) -> HashMap<Nucl, f32> {
+        self.presenter.get_staples_melting_temperatures()
+    }
+
     fn can_start_builder_at(&self, nucl: &Nucl) -> bool {
         self.presenter.can_start_builder_at(*nucl)
     }
@@ -16,10 +16,11 @@ ENSnano, a 3d graphical application for DNA nanostructures.
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
-use ensnano_design::{elements::DnaElement, CameraId};
+use ensnano_design::{elements::DnaElement, CameraId, StrandModification};
 
 use super::*;
 use crate::gui::DesignReader as ReaderGui;
+use ensnano_interactor::{StapleStatistics, StrandEnd};
 use ultraviolet::Rotor3;
 
 impl ReaderGui for DesignReader {
@@ -51,6 +52,35 @@ impl ReaderGui for DesignReader {
         self.presenter.current_design.anchors.contains(&nucl)
     }
 
+    fn nucleotide_annotation(&self, nucl: Nucl) -> Option<String> {
+        self.presenter
+            .current_design
+            .get_annotation(&nucl)
+            .map(String::from)
+    }
+
+    fn strand_end_of_nucl(&self, nucl: Nucl) -> Option<(usize, StrandEnd)> {
+        let end = match self.presenter.current_design.is_strand_end(&nucl) {
+            Extremity::Prime5 => StrandEnd::FivePrime,
+            Extremity::Prime3 => StrandEnd::ThreePrime,
+            Extremity::No => return None,
+        };
+        self.presenter
+            .current_design
+            .get_strand_nucl(&nucl)
+            .map(|s_id| (s_id, end))
+    }
+
+    fn nucl_is_on_cyclic_scaffold(&self, nucl: Nucl) -> bool {
+        let design = &self.presenter.current_design;
+        design
+            .scaffold_id
+            .filter(|s_id| design.get_strand_nucl(&nucl) == Some(*s_id))
+            .and_then(|s_id| design.strands.get(&s_id))
+            .map(|s| s.cyclic)
+            .unwrap_or(false)
+    }
+
     fn length_decomposition(&self, s_id: usize) -> String {
         self.presenter.decompose_length(s_id)
     }
@@ -74,6 +104,18 @@ impl ReaderGui for DesignReader {
             .unwrap_or(String::from("Unamed strand"))
     }
 
+    fn strand_modifications(
+        &self,
+        s_id: usize,
+    ) -> (Option<StrandModification>, Option<StrandModification>) {
+        self.presenter
+            .current_design
+            .strands
+            .get(&s_id)
+            .map(|s| (s.modification_5prime, s.modification_3prime))
+            .unwrap_or_default()
+    }
+
     fn get_all_cameras(&self) -> Vec<(CameraId, &str)> {
         //TODO this obviously needs to be updated to relate to the real content
         self.presenter
@@ -95,4 +137,52 @@ impl ReaderGui for DesignReader {
             .get(g_id)
             .map(|g| (g.position, g.orientation))
     }
+
+    fn get_grid_twist_offset(&self, g_id: usize) -> Option<f32> {
+        self.presenter
+            .current_design
+            .grids
+            .get(g_id)
+            .map(|g| g.twist_offset)
+    }
+
+    fn scaffold_domain_length(&self, domain_id: usize) -> Option<usize> {
+        self.presenter
+            .current_design
+            .scaffold_domains()
+            .get(domain_id)
+            .map(|interval| (interval.end - interval.start).max(0) as usize)
+    }
+
+    fn scaffold_domain_gc_content(&self, domain_id: usize) -> Option<f64> {
+        let nucls = self
+            .presenter
+            .current_design
+            .get_scaffold_domain_nucls(domain_id)?;
+        let bases: Vec<char> = nucls
+            .iter()
+            .filter_map(|n| self.presenter.get_basis_of_nucl(n))
+            .collect();
+        if bases.is_empty() {
+            return None;
+        }
+        let nb_gc = bases
+            .iter()
+            .filter(|c| matches!(c.to_ascii_uppercase(), 'G' | 'C'))
+            .count();
+        Some(nb_gc as f64 / bases.len() as f64)
+    }
+
+    fn get_staple_statistics(&self) -> Vec<StapleStatistics> {
+        self.presenter.get_staple_statistics()
+    }
+
+    fn get_selection_groups(&self) -> Vec<(String, usize)> {
+        self.presenter
+            .current_design
+            .selection_groups
+            .iter()
+            .map(|(name, elements)| (name.clone(), elements.len()))
+            .collect()
+    }
 }
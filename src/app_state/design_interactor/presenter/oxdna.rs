@@ -16,12 +16,16 @@ ENSnano, a 3d graphical application for DNA nanostructures.
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 use super::*;
-use ensnano_design::{Domain, Helix, Nucl, Parameters};
-use std::io::Write;
+use ensnano_design::{Domain, Helix, Nucl, Parameters, Strand};
+use ensnano_interactor::Trajectory;
+use std::io::{BufRead, Write};
 use std::path::Path;
-use ultraviolet::Vec3;
+use ultraviolet::{Rotor3, Vec3, Vec4};
 
 const BACKBONE_TO_CM: f32 = 0.34;
+/// The number of times the matrix is squared when computing the rotation that fits two sets of
+/// points
+const RIGID_FIT_SQUARINGS: usize = 32;
 
 struct OxDnaNucl {
     position: Vec3,
@@ -139,48 +143,39 @@ impl Presenter {
         let mut basis_map = (*self.content.basis_map.clone()).clone();
         let mut nb_strand = 0;
         let parameters = self.current_design.parameters.unwrap_or_default();
-        for (strand_id, s) in self.current_design.strands.values().enumerate() {
+        for (strand_id, (s, strand_nucls)) in self.oxdna_strands().enumerate() {
             nb_strand = strand_id + 1;
             let mut prev_nucl: Option<isize> = None;
             let first_strand_nucl = nucl_id;
-            for d in s.domains.iter() {
-                if let Domain::HelixDomain(dom) = d {
-                    for position in dom.iter() {
-                        let ox_nucl = self.current_design.helices[&dom.helix].ox_dna_nucl(
-                            position,
-                            dom.forward,
-                            &parameters,
-                        );
-                        boundaries[0] = boundaries[0].max(2. * ox_nucl.position.x.abs());
-                        boundaries[1] = boundaries[1].max(2. * ox_nucl.position.y.abs());
-                        boundaries[2] = boundaries[2].max(2. * ox_nucl.position.z.abs());
-                        nucls.push(ox_nucl);
-                        let nucl = Nucl {
-                            position,
-                            helix: dom.helix,
-                            forward: dom.forward,
-                        };
-                        let base = basis_map.get(&nucl).cloned().unwrap_or_else(|| {
-                            basis_map
-                                .get(&nucl.compl())
-                                .cloned()
-                                .unwrap_or_else(rand_base)
-                        });
-                        basis_map.insert(nucl.compl(), compl(base));
-                        let bound = OxDnaBound {
-                            base,
-                            strand_id,
-                            prime3: -1,
-                            prime5: prev_nucl.unwrap_or(-1),
-                        };
-                        bounds.push(bound);
-                        if let Some(prev) = prev_nucl {
-                            bounds.get_mut(prev as usize).unwrap().prime3 = nucl_id;
-                        }
-                        prev_nucl = Some(nucl_id);
-                        nucl_id += 1;
-                    }
+            for nucl in strand_nucls {
+                let ox_nucl = self.current_design.helices[&nucl.helix].ox_dna_nucl(
+                    nucl.position,
+                    nucl.forward,
+                    &parameters,
+                );
+                boundaries[0] = boundaries[0].max(2. * ox_nucl.position.x.abs());
+                boundaries[1] = boundaries[1].max(2. * ox_nucl.position.y.abs());
+                boundaries[2] = boundaries[2].max(2. * ox_nucl.position.z.abs());
+                nucls.push(ox_nucl);
+                let base = basis_map.get(&nucl).cloned().unwrap_or_else(|| {
+                    basis_map
+                        .get(&nucl.compl())
+                        .cloned()
+                        .unwrap_or_else(rand_base)
+                });
+                basis_map.insert(nucl.compl(), compl(base));
+                let bound = OxDnaBound {
+                    base,
+                    strand_id,
+                    prime3: -1,
+                    prime5: prev_nucl.unwrap_or(-1),
+                };
+                bounds.push(bound);
+                if let Some(prev) = prev_nucl {
+                    bounds.get_mut(prev as usize).unwrap().prime3 = nucl_id;
                 }
+                prev_nucl = Some(nucl_id);
+                nucl_id += 1;
             }
             if s.cyclic {
                 bounds.iter_mut().last().unwrap().prime3 = first_strand_nucl;
@@ -224,6 +219,276 @@ impl Presenter {
     }
 }
 
+/// An error that occured while reading an oxDNA trajectory
+#[derive(Debug)]
+pub enum OxDnaImportError {
+    /// The numbers of nucleotides and strands of the topology are not those of the current
+    /// design
+    TopologyMismatch,
+    /// The line with the given number could not be parsed
+    ParseError(usize),
+    /// The configuration with the given index does not have one line per nucleotide of the
+    /// topology
+    WrongNumberOfNucleotides(usize),
+    /// The file does not contain any configuration
+    EmptyTrajectory,
+    IOError(std::io::Error),
+}
+
+impl std::fmt::Display for OxDnaImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TopologyMismatch => write!(
+                f,
+                "The topology does not have the nucleotides and strands of the current design"
+            ),
+            Self::ParseError(line) => write!(f, "Could not parse line {}", line),
+            Self::WrongNumberOfNucleotides(configuration) => write!(
+                f,
+                "Configuration {} does not have one line per nucleotide",
+                configuration
+            ),
+            Self::EmptyTrajectory => write!(f, "The file does not contain any configuration"),
+            Self::IOError(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<std::io::Error> for OxDnaImportError {
+    fn from(e: std::io::Error) -> Self {
+        Self::IOError(e)
+    }
+}
+
+/// Read the backbone positions of the nucleotides in each configuration of an oxDNA trajectory.
+///
+/// A trajectory is a sequence of configurations that each start with a three lines header
+/// (time, box and energies) followed by one line per nucleotide.
+fn read_configurations<R: BufRead>(
+    reader: R,
+    nb_nucl: usize,
+) -> Result<Vec<Vec<Vec3>>, OxDnaImportError> {
+    let mut configurations: Vec<Vec<Vec3>> = Vec::new();
+    for (line_idx, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.starts_with('t') {
+            configurations.push(Vec::with_capacity(nb_nucl));
+        } else if line.is_empty() || line.starts_with('b') || line.starts_with('E') {
+            continue;
+        } else {
+            let parse_error = || OxDnaImportError::ParseError(line_idx + 1);
+            let values: Vec<f32> = line
+                .split_whitespace()
+                .take(6)
+                .map(|v| v.parse::<f32>())
+                .collect::<Result<_, _>>()
+                .map_err(|_| parse_error())?;
+            if values.len() < 6 {
+                return Err(parse_error());
+            }
+            let cm_position = Vec3::new(values[0], values[1], values[2]);
+            let a1 = Vec3::new(values[3], values[4], values[5]);
+            configurations
+                .last_mut()
+                .ok_or_else(parse_error)?
+                .push(cm_position - a1 * BACKBONE_TO_CM);
+        }
+    }
+    if configurations.is_empty() {
+        return Err(OxDnaImportError::EmptyTrajectory);
+    }
+    for (configuration, nucls) in configurations.iter().enumerate() {
+        if nucls.len() != nb_nucl {
+            return Err(OxDnaImportError::WrongNumberOfNucleotides(configuration));
+        }
+    }
+    Ok(configurations)
+}
+
+impl Presenter {
+    /// The nucleotides of each strand of the design, in the order in which they are written in
+    /// oxDNA files
+    fn oxdna_strands(&self) -> impl Iterator<Item = (&Strand, Vec<Nucl>)> {
+        self.current_design.strands.values().map(|s| {
+            let nucls = s
+                .domains
+                .iter()
+                .filter_map(|d| match d {
+                    Domain::HelixDomain(dom) => Some(dom),
+                    _ => None,
+                })
+                .flat_map(|dom| {
+                    dom.iter()
+                        .map(move |position| Nucl::new(dom.helix, position, dom.forward))
+                })
+                .collect();
+            (s, nucls)
+        })
+    }
+
+    /// A reader of the oxDNA trajectories of the current design
+    pub fn oxdna_trajectory_reader(&self) -> OxDnaTrajectoryReader {
+        OxDnaTrajectoryReader {
+            nucls: self
+                .oxdna_strands()
+                .flat_map(|(_, nucls)| nucls.into_iter())
+                .collect(),
+            nb_strand: self.current_design.strands.len(),
+        }
+    }
+}
+
+/// The nucleotides of a design, in the order in which they are written in oxDNA files.
+///
+/// It can be sent to a background job to read the oxDNA trajectories of the design while the
+/// design is being edited.
+#[derive(Clone)]
+pub struct OxDnaTrajectoryReader {
+    nucls: Vec<Nucl>,
+    nb_strand: usize,
+}
+
+impl OxDnaTrajectoryReader {
+    /// Read an oxDNA trajectory of the design, as exported by `oxdna_export`
+    pub fn read(
+        self,
+        topology: &Path,
+        configurations: &Path,
+    ) -> Result<Trajectory, OxDnaImportError> {
+        let topology = std::io::BufReader::new(std::fs::File::open(topology)?);
+        let header = topology
+            .lines()
+            .next()
+            .ok_or(OxDnaImportError::ParseError(1))??;
+        let counts: Vec<usize> = header
+            .split_whitespace()
+            .map(|v| v.parse::<usize>())
+            .collect::<Result<_, _>>()
+            .map_err(|_| OxDnaImportError::ParseError(1))?;
+        if counts != [self.nucls.len(), self.nb_strand] {
+            return Err(OxDnaImportError::TopologyMismatch);
+        }
+        let configurations = std::io::BufReader::new(std::fs::File::open(configurations)?);
+        let frames = read_configurations(configurations, self.nucls.len())?;
+        Ok(Trajectory::new(self.nucls, frames))
+    }
+
+    /// True if `trajectory` has one position per nucleotide of the design, in the order of
+    /// its oxDNA files
+    pub fn matches(&self, trajectory: &Trajectory) -> bool {
+        trajectory.nucls() == self.nucls.as_slice()
+    }
+}
+
+impl Presenter {
+    /// The position and orientation of each helix whose nucleotides are the closest to their
+    /// position in the current frame of `trajectory`, the shape of the helices being kept
+    pub fn helices_fitted_to_trajectory(
+        &self,
+        trajectory: &Trajectory,
+    ) -> Vec<(usize, Vec3, Rotor3)> {
+        let parameters = self.current_design.parameters.unwrap_or_default();
+        let mut positions: BTreeMap<usize, Vec<(Vec3, Vec3)>> = BTreeMap::new();
+        for nucl in trajectory.nucls() {
+            if let Some((helix, position)) = self
+                .current_design
+                .helices
+                .get(&nucl.helix)
+                .zip(trajectory.current_position(nucl))
+            {
+                let design_position = helix.space_pos(&parameters, nucl.position, nucl.forward);
+                positions
+                    .entry(nucl.helix)
+                    .or_default()
+                    .push((design_position, position));
+            }
+        }
+        positions
+            .into_iter()
+            .filter_map(|(h_id, pairs)| {
+                let helix = self.current_design.helices.get(&h_id)?;
+                let (rotation, from, to) = rigid_fit(&pairs)?;
+                Some((
+                    h_id,
+                    (helix.position - from).rotated_by(rotation) + to,
+                    (rotation * helix.orientation).normalized(),
+                ))
+            })
+            .collect()
+    }
+}
+
+/// The rotation around the centroid `from` of the first points of `pairs`, followed by the
+/// translation to the centroid `to` of their second points, that best maps the first point of
+/// each pair on its second point.
+///
+/// Return `None` if there are not enough points to determine the rotation.
+fn rigid_fit(pairs: &[(Vec3, Vec3)]) -> Option<(Rotor3, Vec3, Vec3)> {
+    if pairs.len() < 3 {
+        return None;
+    }
+    let nb_points = pairs.len() as f32;
+    let from = pairs.iter().fold(Vec3::zero(), |acc, (p, _)| acc + *p) / nb_points;
+    let to = pairs.iter().fold(Vec3::zero(), |acc, (_, q)| acc + *q) / nb_points;
+    // s[i][j] is the sum of the products of the i-th coordinate of the first points and of the
+    // j-th coordinate of the second points, relative to their centroids
+    let mut s = [[0f32; 3]; 3];
+    for (p, q) in pairs.iter() {
+        let a = *p - from;
+        let b = *q - to;
+        for (i, a_i) in [a.x, a.y, a.z].iter().enumerate() {
+            for (j, b_j) in [b.x, b.y, b.z].iter().enumerate() {
+                s[i][j] += a_i * b_j;
+            }
+        }
+    }
+    let [[xx, xy, xz], [yx, yy, yz], [zx, zy, zz]] = s;
+    // The best rotation is the unit quaternion (x, y, z, w) that is an eigenvector of the
+    // greatest eigenvalue of this symmetric matrix (B. K. P. Horn, Closed-form solution of
+    // absolute orientation using unit quaternions, 1987).
+    let mut matrix = [
+        [xx - yy - zz, xy + yx, zx + xz, yz - zy],
+        [xy + yx, -xx + yy - zz, yz + zy, zx - xz],
+        [zx + xz, yz + zy, -xx - yy + zz, xy - yx],
+        [yz - zy, zx - xz, xy - yx, xx + yy + zz],
+    ];
+    // Shifting the eigenvalues makes them positive so that the greatest one is also the greatest
+    // in absolute value. The columns of a high power of the matrix are then all colinear to its
+    // eigenvector.
+    let shift: f32 = matrix.iter().flatten().map(|v| v.abs()).sum();
+    for (i, row) in matrix.iter_mut().enumerate() {
+        row[i] += shift;
+    }
+    for _ in 0..RIGID_FIT_SQUARINGS {
+        let mut square = [[0f32; 4]; 4];
+        for (i, row) in square.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = (0..4).map(|k| matrix[i][k] * matrix[k][j]).sum();
+            }
+        }
+        let max = square
+            .iter()
+            .flatten()
+            .fold(0f32, |acc, v| acc.max(v.abs()));
+        if max <= 0. || !max.is_finite() {
+            return None;
+        }
+        for value in square.iter_mut().flatten() {
+            *value /= max;
+        }
+        matrix = square;
+    }
+    let eigenvector = matrix
+        .iter()
+        .map(|row| Vec4::new(row[0], row[1], row[2], row[3]))
+        .max_by(|a, b| a.mag_sq().partial_cmp(&b.mag_sq()).unwrap())?
+        .normalized();
+    let rotation =
+        Rotor3::from_quaternion_array([eigenvector.x, eigenvector.y, eigenvector.z, eigenvector.w]);
+    Some((rotation.normalized(), from, to))
+}
+
 fn rand_base() -> char {
     match rand::random::<u8>() % 4 {
         0 => 'A',
@@ -233,7 +498,7 @@ fn rand_base() -> char {
     }
 }
 
-fn compl(c: char) -> char {
+pub(super) fn compl(c: char) -> char {
     match c {
         'A' => 'T',
         'G' => 'C',
@@ -241,3 +506,38 @@ fn compl(c: char) -> char {
         _ => 'G',
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rigid_fit_finds_the_rotation_and_translation() {
+        let points = [
+            Vec3::new(1., 0., 0.),
+            Vec3::new(0., 2., 0.),
+            Vec3::new(0., 0., 3.),
+            Vec3::new(1., 1., -1.),
+        ];
+        let rotation = Rotor3::from_euler_angles(0.3, -0.5, 1.2);
+        let translation = Vec3::new(4., -2., 7.);
+        let pairs: Vec<(Vec3, Vec3)> = points
+            .iter()
+            .map(|p| (*p, p.rotated_by(rotation) + translation))
+            .collect();
+        let (fitted_rotation, from, to) = rigid_fit(&pairs).unwrap();
+        for (p, q) in pairs.iter() {
+            let fitted = (*p - from).rotated_by(fitted_rotation) + to;
+            assert!((fitted - *q).mag() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn rigid_fit_needs_three_points() {
+        let pairs = [
+            (Vec3::zero(), Vec3::unit_x()),
+            (Vec3::unit_y(), Vec3::unit_z()),
+        ];
+        assert!(rigid_fit(&pairs).is_none());
+    }
+}
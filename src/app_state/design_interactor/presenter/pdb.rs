@@ -0,0 +1,329 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! Export of a coarse atomic model of the design in the PDB format.
+//!
+//! Each nucleotide is represented by four atoms placed on the radius of the helix that goes
+//! through its backbone: the phosphorus, the C1' atom of the sugar, the atom of the base that
+//! is bound to the sugar and the atom of the base that is involved in the Watson-Crick pairing.
+//! The nucleotides of insertions are placed between the nucleotides that surround them.
+//! Coordinates are written in Ångströms.
+
+use super::oxdna::compl;
+use super::*;
+use ensnano_design::{Domain, Nucl, Parameters};
+use std::io::Write;
+use std::path::Path;
+use ultraviolet::Vec3;
+
+/// Distance, in nanometers, between the axis of the helix and the C1' atom
+const C1_PRIME_RADIUS: f32 = 0.59;
+/// Distance, in nanometers, between the axis of the helix and the base atom bound to the sugar
+const GLYCOSIDIC_RADIUS: f32 = 0.45;
+/// Distance, in nanometers, between the axis of the helix and the pairing atom of the base
+const PAIRING_RADIUS: f32 = 0.1;
+
+/// The PDB format has 5 digits for atom serial numbers and 4 digits for residue numbers. Larger
+/// numbers wrap around, as is customary for large structures.
+const MAX_ATOM_SERIAL: usize = 100_000;
+const MAX_RESIDUE_NUMBER: usize = 10_000;
+
+/// The characters used as chain identifiers, one chain per strand. With more than 62 strands,
+/// the chain identifiers are reused, and the strands are told apart by their segment identifier.
+const CHAIN_IDS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+
+/// The segment identifier of the `chain_idx`-th chain, written in base 36 so that its 4
+/// characters suffice for more than a million strands.
+fn segment_id(chain_idx: usize) -> String {
+    const DIGITS: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+    let mut n = chain_idx;
+    let mut ret = Vec::new();
+    loop {
+        ret.push(DIGITS[n % DIGITS.len()]);
+        n /= DIGITS.len();
+        if n == 0 {
+            break;
+        }
+    }
+    ret.reverse();
+    String::from_utf8(ret).unwrap_or_default()
+}
+
+struct PdbAtom {
+    name: &'static str,
+    element: &'static str,
+    position: Vec3,
+}
+
+struct PdbResidue {
+    /// The base of the nucleotide, 'N' if it is not known
+    base: char,
+    atoms: Vec<PdbAtom>,
+}
+
+impl PdbResidue {
+    fn new(base: char, axis: Vec3, backbone: Vec3, parameters: &Parameters) -> Self {
+        let radial = (backbone - axis).normalized();
+        let at_radius = |radius: f32| axis + radial * radius;
+        let purine = base == 'A' || base == 'G';
+        let (glycosidic, pairing) = if purine {
+            (" N9 ", " N1 ")
+        } else {
+            (" N1 ", " N3 ")
+        };
+        let atoms = vec![
+            PdbAtom {
+                name: " P  ",
+                element: "P",
+                position: at_radius(parameters.helix_radius),
+            },
+            PdbAtom {
+                name: " C1'",
+                element: "C",
+                position: at_radius(C1_PRIME_RADIUS),
+            },
+            PdbAtom {
+                name: glycosidic,
+                element: "N",
+                position: at_radius(GLYCOSIDIC_RADIUS),
+            },
+            PdbAtom {
+                name: pairing,
+                element: "N",
+                position: at_radius(PAIRING_RADIUS),
+            },
+        ];
+        Self { base, atoms }
+    }
+
+    fn residue_name(&self) -> &'static str {
+        match self.base {
+            'A' => "DA",
+            'C' => "DC",
+            'G' => "DG",
+            'T' => "DT",
+            _ => "DN",
+        }
+    }
+}
+
+fn atom_line(
+    serial: usize,
+    atom: &PdbAtom,
+    residue: &PdbResidue,
+    chain: char,
+    segment: &str,
+    seq: usize,
+) -> String {
+    // Nanometers to Ångströms
+    let position = atom.position * 10.;
+    format!(
+        "ATOM  {:>5} {:4} {:>3} {}{:>4}    {:>8.3}{:>8.3}{:>8.3}{:>6.2}{:>6.2}      {:<4}{:>2}",
+        serial % MAX_ATOM_SERIAL,
+        atom.name,
+        residue.residue_name(),
+        chain,
+        seq % MAX_RESIDUE_NUMBER,
+        position.x,
+        position.y,
+        position.z,
+        1.0,
+        0.0,
+        segment,
+        atom.element
+    )
+}
+
+/// A nucleotide of a strand, before it is expanded into a residue
+struct PdbNucleotide {
+    base: char,
+    axis: Vec3,
+    backbone: Vec3,
+}
+
+/// Place the `nb_insertions` nucleotides of an insertion evenly between `before` and `after`.
+/// When the insertion is at an end of the strand, its nucleotides are stacked on the only
+/// neighbour of the insertion.
+fn insertion_nucleotides(
+    before: Option<&PdbNucleotide>,
+    after: Option<&PdbNucleotide>,
+    nb_insertions: usize,
+) -> Vec<PdbNucleotide> {
+    let (start, end) = match (before.or(after), after.or(before)) {
+        (Some(start), Some(end)) => (start, end),
+        _ => return Vec::new(),
+    };
+    (1..=nb_insertions)
+        .map(|i| {
+            let t = i as f32 / (nb_insertions + 1) as f32;
+            PdbNucleotide {
+                base: 'N',
+                axis: start.axis + (end.axis - start.axis) * t,
+                backbone: start.backbone + (end.backbone - start.backbone) * t,
+            }
+        })
+        .collect()
+}
+
+impl Presenter {
+    /// The residues of each strand, from 5' to 3'
+    fn pdb_chains(&self) -> Vec<Vec<PdbResidue>> {
+        let parameters = self.current_design.parameters.unwrap_or_default();
+        let basis_map = self.content.basis_map.as_ref();
+        let mut chains = Vec::new();
+        for s in self.current_design.strands.values() {
+            let mut nucleotides: Vec<PdbNucleotide> = Vec::new();
+            // The insertion that is waiting for the nucleotide that follows it
+            let mut pending_insertion = None;
+            for d in s.domains.iter() {
+                match d {
+                    Domain::HelixDomain(dom) => {
+                        let helix = &self.current_design.helices[&dom.helix];
+                        for position in dom.iter() {
+                            let nucl = Nucl::new(dom.helix, position, dom.forward);
+                            let base = basis_map
+                                .get(&nucl)
+                                .cloned()
+                                .or_else(|| basis_map.get(&nucl.compl()).map(|b| compl(*b)))
+                                .unwrap_or('N');
+                            let nucleotide = PdbNucleotide {
+                                base,
+                                axis: helix.axis_position(&parameters, position),
+                                backbone: helix.space_pos(&parameters, position, dom.forward),
+                            };
+                            if let Some(nb_insertions) = pending_insertion.take() {
+                                let inserted = insertion_nucleotides(
+                                    nucleotides.last(),
+                                    Some(&nucleotide),
+                                    nb_insertions,
+                                );
+                                nucleotides.extend(inserted);
+                            }
+                            nucleotides.push(nucleotide);
+                        }
+                    }
+                    Domain::Insertion(nb_insertions) => {
+                        pending_insertion = Some(pending_insertion.unwrap_or(0) + *nb_insertions);
+                    }
+                }
+            }
+            if let Some(nb_insertions) = pending_insertion {
+                let inserted = insertion_nucleotides(nucleotides.last(), None, nb_insertions);
+                nucleotides.extend(inserted);
+            }
+            if !nucleotides.is_empty() {
+                chains.push(
+                    nucleotides
+                        .into_iter()
+                        .map(|n| PdbResidue::new(n.base, n.axis, n.backbone, &parameters))
+                        .collect(),
+                );
+            }
+        }
+        chains
+    }
+
+    /// Write a coarse atomic model of the design in the PDB format
+    pub fn pdb_export(&self, path: &Path) -> std::io::Result<()> {
+        let mut out = std::io::BufWriter::new(std::fs::File::create(path)?);
+        writeln!(
+            out,
+            "REMARK   1 COARSE MODEL EXPORTED BY ENSNANO: P, C1' AND TWO BASE ATOMS PER NUCLEOTIDE"
+        )?;
+        let mut serial = 1;
+        for (chain_idx, residues) in self.pdb_chains().iter().enumerate() {
+            let chain = CHAIN_IDS[chain_idx % CHAIN_IDS.len()] as char;
+            let segment = segment_id(chain_idx);
+            for (residue_idx, residue) in residues.iter().enumerate() {
+                for atom in residue.atoms.iter() {
+                    writeln!(
+                        out,
+                        "{}",
+                        atom_line(serial, atom, residue, chain, &segment, residue_idx + 1)
+                    )?;
+                    serial += 1;
+                }
+            }
+            if let Some(last) = residues.last() {
+                writeln!(
+                    out,
+                    "TER   {:>5}      {:>3} {}{:>4}",
+                    serial % MAX_ATOM_SERIAL,
+                    last.residue_name(),
+                    chain,
+                    residues.len() % MAX_RESIDUE_NUMBER
+                )?;
+                serial += 1;
+            }
+        }
+        writeln!(out, "END")?;
+        out.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn atom_lines_follow_the_pdb_columns() {
+        let residue = PdbResidue::new(
+            'G',
+            Vec3::zero(),
+            Vec3::new(0., 1., 0.),
+            &Parameters::DEFAULT,
+        );
+        let line = atom_line(12, &residue.atoms[0], &residue, 'B', "1Z", 3);
+        assert_eq!(line.len(), 78);
+        assert_eq!(&line[0..6], "ATOM  ");
+        assert_eq!(&line[6..11], "   12");
+        assert_eq!(&line[12..16], " P  ");
+        assert_eq!(&line[17..20], " DG");
+        assert_eq!(&line[21..22], "B");
+        assert_eq!(&line[22..26], "   3");
+        assert_eq!(&line[38..46], "  10.000");
+        assert_eq!(&line[72..76], "1Z  ");
+        assert_eq!(&line[76..78], " P");
+        assert_eq!(&residue.atoms[2].name[..], " N9 ");
+    }
+
+    #[test]
+    fn segment_ids_are_unique_beyond_the_chain_ids() {
+        assert_eq!(segment_id(0), "0");
+        assert_eq!(segment_id(62), "1Q");
+        assert_eq!(segment_id(36 * 36 * 36 * 36 - 1), "ZZZZ");
+    }
+
+    #[test]
+    fn insertions_are_placed_between_their_neighbours() {
+        let before = PdbNucleotide {
+            base: 'A',
+            axis: Vec3::zero(),
+            backbone: Vec3::unit_y(),
+        };
+        let after = PdbNucleotide {
+            base: 'C',
+            axis: Vec3::new(3., 0., 0.),
+            backbone: Vec3::new(3., 1., 0.),
+        };
+        let inserted = insertion_nucleotides(Some(&before), Some(&after), 2);
+        assert_eq!(inserted.len(), 2);
+        assert_eq!(inserted[0].axis, Vec3::new(1., 0., 0.));
+        assert_eq!(inserted[1].backbone, Vec3::new(2., 1., 0.));
+        assert_eq!(insertion_nucleotides(Some(&before), None, 3).len(), 3);
+    }
+}
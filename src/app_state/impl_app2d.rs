@@ -42,6 +42,10 @@ impl App2D for AppState {
         self.0.selection_mode
     }
 
+    fn is_selection_locked(&self) -> bool {
+        self.0.selection_locked
+    }
+
     fn get_design_reader(&self) -> Self::Reader {
         self.0.design.get_design_reader()
     }
@@ -65,6 +69,14 @@ impl App2D for AppState {
     fn get_building_state(&self) -> Option<ensnano_interactor::StrandBuildingStatus> {
         self.get_strand_building_state()
     }
+
+    fn get_helix_2d_ranges(&self) -> &BTreeMap<usize, (isize, isize)> {
+        self.0.helix_2d_ranges.as_ref()
+    }
+
+    fn helix_2d_ranges_were_updated(&self, other: &Self) -> bool {
+        self.0.helix_2d_ranges != other.0.helix_2d_ranges
+    }
 }
 
 #[cfg(test)]
@@ -91,4 +103,16 @@ mod tests {
         assert_eq!(state.get_selection_mode(), SelectionMode::Helix);
         assert!(!state.selection_was_updated(&old_state));
     }
+
+    #[test]
+    fn helix_2d_ranges_are_merged() {
+        let state = AppState::default()
+            .with_helix_2d_ranges(vec![(0, (0, 10)), (1, (-5, 5))].into_iter().collect());
+        let old_state = state.clone();
+        let state = state.with_helix_2d_ranges(vec![(1, (0, 20))].into_iter().collect());
+        assert!(state.helix_2d_ranges_were_updated(&old_state));
+        assert_eq!(state.get_helix_2d_ranges()[&0], (0, 10));
+        assert_eq!(state.get_helix_2d_ranges()[&1], (0, 20));
+        assert_eq!(old_state.get_helix_2d_ranges()[&1], (-5, 5));
+    }
 }
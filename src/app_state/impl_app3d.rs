@@ -18,6 +18,7 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 
 use crate::scene::AppState as App3D;
 use ensnano_interactor::StrandBuilder;
+use ultraviolet::Mat4;
 
 use super::*;
 
@@ -55,6 +56,10 @@ impl App3D for AppState {
         self.0.selection_mode
     }
 
+    fn is_selection_locked(&self) -> bool {
+        self.0.selection_locked
+    }
+
     fn get_action_mode(&self) -> (ActionMode, WidgetBasis) {
         (self.0.action_mode, self.0.widget_basis)
     }
@@ -100,6 +105,63 @@ impl App3D for AppState {
     fn suggestion_parameters_were_updated(&self, other: &Self) -> bool {
         self.0.suggestion_parameters != other.0.suggestion_parameters
     }
+
+    fn get_highlighted_path(&self) -> &[Nucl] {
+        self.0.highlighted_path.as_slice()
+    }
+
+    fn highlighted_path_was_updated(&self, other: &Self) -> bool {
+        self.0.highlighted_path != other.0.highlighted_path
+    }
+
+    fn get_nucleotide_colors(&self) -> &HashMap<Nucl, [f32; 3]> {
+        &self.0.nucleotide_colors
+    }
+
+    fn nucleotide_colors_were_updated(&self, other: &Self) -> bool {
+        self.0.nucleotide_colors != other.0.nucleotide_colors
+    }
+
+    fn get_measurements(&self) -> &[Measurement] {
+        self.0.measurements.as_slice()
+    }
+
+    fn measurements_were_updated(&self, other: &Self) -> bool {
+        self.0.measurements != other.0.measurements
+    }
+
+    fn get_trajectory(&self) -> Option<&Trajectory> {
+        self.0.trajectory.as_ref()
+    }
+
+    fn trajectory_was_updated(&self, other: &Self) -> bool {
+        self.0.trajectory != other.0.trajectory
+    }
+
+    fn get_simulation_kinetic_energy(&self) -> Option<f32> {
+        self.0.design.get_simulation_kinetic_energy()
+    }
+
+    fn get_design_generation(&self) -> u64 {
+        self.0.design.get_design_generation()
+    }
+
+    fn get_workspace_designs(&self) -> Vec<(DesignReader, Mat4)> {
+        self.0
+            .workspace
+            .get_visible_designs()
+            .into_iter()
+            .map(|(reader, transform)| (reader, transform.matrix()))
+            .collect()
+    }
+
+    fn is_main_design_visible(&self) -> bool {
+        self.0.workspace.is_main_design_visible()
+    }
+
+    fn workspace_was_updated(&self, other: &Self) -> bool {
+        self.0.workspace != other.0.workspace
+    }
 }
 
 #[cfg(test)]
@@ -139,6 +201,31 @@ mod tests {
         assert!(state.design_was_modified(&old_state));
     }
 
+    #[test]
+    fn new_design_has_a_new_generation() {
+        let mut state = AppState::default();
+        let old_state = state.clone();
+
+        assert_eq!(
+            state.get_design_generation(),
+            old_state.get_design_generation()
+        );
+        state.update_design(Default::default());
+        assert_ne!(
+            state.get_design_generation(),
+            old_state.get_design_generation()
+        );
+
+        // Modifying an older state, as after an undo, must not give back a generation that was
+        // already seen.
+        let mut other_state = old_state.clone();
+        other_state.update_design(Default::default());
+        assert_ne!(
+            other_state.get_design_generation(),
+            state.get_design_generation()
+        );
+    }
+
     #[test]
     fn new_selection_is_not_a_modification() {
         let mut state = AppState::default();
@@ -156,4 +243,35 @@ mod tests {
         state = state.with_candidates(vec![]);
         assert!(!state.design_was_modified(&old_state));
     }
+
+    #[test]
+    fn new_measurement_is_not_a_modification() {
+        let mut state = AppState::default();
+        let old_state = state.clone();
+
+        let nucl = Nucl {
+            helix: 0,
+            position: 0,
+            forward: true,
+        };
+        state = state.with_measurements(vec![Measurement::Distance {
+            nucls: (nucl, nucl.compl()),
+            distance: 2.,
+        }]);
+        assert!(state.measurements_were_updated(&old_state));
+        assert!(!state.design_was_modified(&old_state));
+        assert_eq!(state.get_measurements().len(), 1);
+    }
+
+    #[test]
+    fn workspace_is_kept_when_undoing() {
+        let state = AppState::default();
+        let current_state = state.with_main_design_visibility(false);
+        assert!(current_state.workspace_was_updated(&state));
+        assert!(!current_state.design_was_modified(&state));
+
+        let mut restored_state = state.clone();
+        restored_state.prepare_for_replacement(&current_state);
+        assert!(!restored_state.is_main_design_visible());
+    }
 }
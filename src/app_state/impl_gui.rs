@@ -19,13 +19,17 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 use super::*;
 use crate::gui::AppState as GuiState;
 use ensnano_design::{elements::DnaElementKey, Parameters};
-use ensnano_interactor::{ScaffoldInfo, SelectionConversion, SimulationState};
+use ensnano_interactor::{ScaffoldInfo, SelectionConversion, SimulationState, WorkspaceDesignInfo};
 
 impl GuiState for AppState {
     fn get_selection_mode(&self) -> SelectionMode {
         self.0.selection_mode
     }
 
+    fn is_selection_locked(&self) -> bool {
+        self.0.selection_locked
+    }
+
     fn get_action_mode(&self) -> ActionMode {
         self.0.action_mode
     }
@@ -38,6 +42,10 @@ impl GuiState for AppState {
         self.0.design.get_simulation_state()
     }
 
+    fn get_roll_optimization_iteration(&self) -> Option<usize> {
+        self.0.design.get_roll_optimization_iteration()
+    }
+
     fn get_dna_parameters(&self) -> Parameters {
         self.0.design.get_dna_parameters()
     }
@@ -114,6 +122,26 @@ impl GuiState for AppState {
     fn get_suggestion_parameters(&self) -> &SuggestionParameters {
         &self.0.suggestion_parameters
     }
+
+    fn get_strand_building_parameters(&self) -> &StrandBuildingParameters {
+        &self.0.strand_building_parameters
+    }
+
+    fn get_measurements(&self) -> &[Measurement] {
+        self.0.measurements.as_slice()
+    }
+
+    fn get_trajectory(&self) -> Option<&Trajectory> {
+        self.0.trajectory.as_ref()
+    }
+
+    fn get_workspace_designs(&self) -> Vec<WorkspaceDesignInfo> {
+        self.0.workspace.get_info()
+    }
+
+    fn is_main_design_visible(&self) -> bool {
+        self.0.workspace.is_main_design_visible()
+    }
 }
 
 #[cfg(test)]
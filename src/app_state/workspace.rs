@@ -0,0 +1,227 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! The workspace holds designs that are displayed next to the design being edited. They cannot
+//! be modified, but they can be moved, hidden, and merged into the design being edited.
+
+use super::address_pointer::AddressPointer;
+use super::design_interactor::DesignInteractor;
+use super::DesignReader;
+use ensnano_design::{Axis, Design};
+use ensnano_interactor::{WorkspaceDesignInfo, WorkspaceTransform};
+use std::path::{Path, PathBuf};
+use ultraviolet::{Rotor3, Vec3};
+
+#[derive(Clone)]
+struct WorkspaceDesign {
+    path: PathBuf,
+    design: AddressPointer<DesignInteractor>,
+    visible: bool,
+    transform: WorkspaceTransform,
+}
+
+#[derive(Clone, Default)]
+pub(super) struct Workspace {
+    designs: Vec<WorkspaceDesign>,
+    /// True if the design being edited is not displayed in the 3D view
+    main_design_hidden: bool,
+}
+
+impl Workspace {
+    pub fn with_design(&self, path: PathBuf, design: DesignInteractor) -> Self {
+        let mut ret = self.clone();
+        ret.designs.push(WorkspaceDesign {
+            path,
+            design: AddressPointer::new(design),
+            visible: true,
+            transform: Default::default(),
+        });
+        ret
+    }
+
+    pub fn without_design(&self, design_id: usize) -> Self {
+        let mut ret = self.clone();
+        if design_id < ret.designs.len() {
+            ret.designs.remove(design_id);
+        }
+        ret
+    }
+
+    pub fn with_visibility(&self, design_id: usize, visible: bool) -> Self {
+        let mut ret = self.clone();
+        if let Some(design) = ret.designs.get_mut(design_id) {
+            design.visible = visible;
+        }
+        ret
+    }
+
+    pub fn with_transform(&self, design_id: usize, transform: WorkspaceTransform) -> Self {
+        let mut ret = self.clone();
+        if let Some(design) = ret.designs.get_mut(design_id) {
+            design.transform = transform;
+        }
+        ret
+    }
+
+    pub fn with_main_design_visibility(&self, visible: bool) -> Self {
+        let mut ret = self.clone();
+        ret.main_design_hidden = !visible;
+        ret
+    }
+
+    pub fn is_main_design_visible(&self) -> bool {
+        !self.main_design_hidden
+    }
+
+    pub fn get_info(&self) -> Vec<WorkspaceDesignInfo> {
+        self.designs
+            .iter()
+            .map(|d| WorkspaceDesignInfo {
+                name: design_name(&d.path),
+                visible: d.visible,
+                transform: d.transform,
+                helices: d.design.get_helix_ids(),
+            })
+            .collect()
+    }
+
+    /// Move the design `design_id` so that its helix `helix` lies next to `target`, at distance
+    /// `spacing`. Return `None` if there is no such helix.
+    pub fn with_docked_design(
+        &self,
+        design_id: usize,
+        helix: usize,
+        target: &Axis,
+        spacing: f32,
+    ) -> Option<Self> {
+        let design = self.designs.get(design_id)?;
+        let axis = design.design.get_helix_axis(helix)?;
+        let transform = docked_transform(design.transform, &axis, target, spacing);
+        Some(self.with_transform(design_id, transform))
+    }
+
+    /// The content of the design `design_id`, as it is in memory, with its transform
+    pub fn get_design_to_merge(&self, design_id: usize) -> Option<(Design, WorkspaceTransform)> {
+        self.designs.get(design_id).map(|d| {
+            (
+                d.design.get_design_reader().get_design_snapshot(),
+                d.transform,
+            )
+        })
+    }
+
+    /// The readers of the visible designs, with their transforms
+    pub fn get_visible_designs(&self) -> Vec<(DesignReader, WorkspaceTransform)> {
+        self.designs
+            .iter()
+            .filter(|d| d.visible)
+            .map(|d| (d.design.get_design_reader(), d.transform))
+            .collect()
+    }
+}
+
+fn design_name(path: &Path) -> String {
+    path.file_stem()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_string_lossy().into_owned())
+}
+
+/// The transform that places `moving`, the axis of a helix of a workspace design, parallel to
+/// `target` and at distance `spacing` from it.
+///
+/// The design is rotated as little as possible, so antiparallel helices stay antiparallel, and it
+/// stays on the side of `target` where it was.
+fn docked_transform(
+    transform: WorkspaceTransform,
+    moving: &Axis,
+    target: &Axis,
+    spacing: f32,
+) -> WorkspaceTransform {
+    let rotor = transform.rotor();
+    let direction = (rotor * moving.direction).normalized();
+    let mut target_direction = target.direction.normalized();
+    if direction.dot(target_direction) < 0. {
+        target_direction = -target_direction;
+    }
+    let orientation = Rotor3::from_rotation_between(direction, target_direction) * rotor;
+
+    let origin = transform.translation + rotor * moving.origin;
+    let mut normal = origin - target.origin;
+    normal -= normal.dot(target_direction) * target_direction;
+    if normal.mag() < 1e-3 {
+        // The helices are on the same line, any side will do
+        normal = target_direction.cross(Vec3::unit_y());
+        if normal.mag() < 1e-3 {
+            normal = target_direction.cross(Vec3::unit_z());
+        }
+    }
+    let docked_origin = target.origin + normal.normalized() * spacing;
+    WorkspaceTransform {
+        translation: docked_origin - orientation * moving.origin,
+        docked_orientation: orientation,
+        angles: Vec3::zero(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: Vec3, b: Vec3) {
+        assert!((a - b).mag() < 1e-4, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn docked_helix_is_parallel_to_its_target() {
+        let moving = Axis {
+            origin: Vec3::new(1., 2., 3.),
+            direction: Vec3::unit_z(),
+        };
+        let target = Axis {
+            origin: Vec3::zero(),
+            direction: Vec3::unit_x(),
+        };
+        let transform = WorkspaceTransform {
+            translation: Vec3::new(0., 10., 0.),
+            ..Default::default()
+        };
+        let docked = docked_transform(transform, &moving, &target, 2.5);
+        let rotor = docked.rotor();
+        assert_close(rotor * moving.direction, target.direction);
+
+        // The docked helix is on the side of the target where it was before docking
+        let origin = docked.translation + rotor * moving.origin;
+        assert!(origin.dot(target.direction).abs() < 1e-4);
+        assert!((origin.mag() - 2.5).abs() < 1e-4);
+        assert!(origin.y > 0.);
+    }
+
+    #[test]
+    fn antiparallel_helices_are_not_flipped() {
+        let moving = Axis {
+            origin: Vec3::zero(),
+            direction: -Vec3::unit_x(),
+        };
+        let target = Axis {
+            origin: Vec3::zero(),
+            direction: Vec3::unit_x(),
+        };
+        let docked = docked_transform(Default::default(), &moving, &target, 2.5);
+        assert_close(docked.rotor() * moving.direction, -target.direction);
+    }
+}
@@ -0,0 +1,85 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! The named colors defined by the user.
+//!
+//! The palette is stored in the `"color_palette"` field of `~/.ensnano/preferences.json`. It is
+//! read at start up and written back to this file each time it is modified. Palettes can also be
+//! exchanged as json files containing only the list of named colors.
+
+use crate::preferences;
+use serde_derive::{Deserialize, Serialize};
+
+const PALETTE_FIELD: &str = "color_palette";
+
+/// A list of colors, encoded as `0xRRGGBB`, together with their names
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ColorPalette {
+    pub colors: Vec<(String, u32)>,
+}
+
+impl ColorPalette {
+    /// Read the palette saved by the user, or return an empty palette if there is none.
+    pub fn load() -> Self {
+        preferences::read_field(PALETTE_FIELD)
+    }
+
+    /// Save the palette with `preferences::write_field`
+    pub fn save(&self) -> std::io::Result<()> {
+        preferences::write_field(PALETTE_FIELD, self)
+    }
+
+    /// Add a color to the palette. If the palette already has a color with the same name, this
+    /// color is replaced.
+    pub fn add(&mut self, name: String, color: u32) {
+        if let Some(named) = self.colors.iter_mut().find(|(n, _)| *n == name) {
+            named.1 = color;
+        } else {
+            self.colors.push((name, color));
+        }
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| e.to_string())
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn color_palette_survives_json_round_trip() {
+        let mut palette = ColorPalette::default();
+        palette.add("scaffold blue".to_string(), 0x1F77B4);
+        palette.add("orange".to_string(), 0xFF7F0E);
+        let json = palette.to_json().unwrap();
+        assert_eq!(ColorPalette::from_json(&json).unwrap(), palette);
+    }
+
+    #[test]
+    fn adding_a_color_with_a_used_name_replaces_it() {
+        let mut palette = ColorPalette::default();
+        palette.add("accent".to_string(), 0xFF0000);
+        palette.add("accent".to_string(), 0x00FF00);
+        assert_eq!(palette.colors, vec![("accent".to_string(), 0x00FF00)]);
+    }
+}
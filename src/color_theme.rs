@@ -0,0 +1,36 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! The colors of the 3D view chosen by the user.
+//!
+//! The theme is stored in the `"color_theme"` field of `~/.ensnano/preferences.json`. It is read
+//! at start up and written back to this file each time it is modified.
+
+use crate::preferences;
+use ensnano_interactor::graphics::ColorTheme;
+
+const THEME_FIELD: &str = "color_theme";
+
+/// Read the theme saved by the user, or return the default theme if there is none.
+pub fn load() -> ColorTheme {
+    preferences::read_field(THEME_FIELD)
+}
+
+/// Save the theme with `preferences::write_field`
+pub fn save(theme: &ColorTheme) -> std::io::Result<()> {
+    preferences::write_field(THEME_FIELD, theme)
+}
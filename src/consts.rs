@@ -32,6 +32,24 @@ pub const SPHERE_RADIUS: f32 = 0.2;
 pub const NB_STACK_SPHERE: u16 = 12;
 pub const NB_SECTOR_SPHERE: u16 = 12;
 
+/// Number of rays of the tubes that are far away from the camera
+pub const NB_RAY_TUBE_LOW_POLY: usize = 4;
+/// Number of stacks of the spheres that are far away from the camera
+pub const NB_STACK_SPHERE_LOW_POLY: u16 = 4;
+/// Number of sectors of the spheres that are far away from the camera
+pub const NB_SECTOR_SPHERE_LOW_POLY: u16 = 6;
+/// Distance to the camera, in nanometers, beyond which spheres and tubes are drawn with low-poly
+/// meshes
+pub const LOW_POLY_DISTANCE: f32 = 40.;
+
+/// Minimal distance, in nanometers, that the camera can travel before the set of instances that
+/// may be visible is recomputed. The camera can travel further when it is far from its pivot, so
+/// that turning around the pivot by less than `CULLING_MARGIN_ANGLE` does not recompute the set.
+pub const CULLING_MARGIN: f32 = 10.;
+/// Angle, in radiants, by which the camera can rotate before the set of instances that may be
+/// visible is recomputed
+pub const CULLING_MARGIN_ANGLE: f32 = 0.25;
+
 pub const NB_SECTOR_CIRCLE: u16 = 36;
 
 pub const SELECT_SCALE_FACTOR: f32 = 1.3;
@@ -45,7 +63,13 @@ pub const FRONT_CIRCLE_ID: u32 = 5;
 pub const SPHERE_WIDGET_ID: u32 = 6;
 
 pub const BASIS_SYMBOLS: &[char] = &['A', 'T', 'G', 'C', '*'];
-pub const NB_BASIS_SYMBOLS: usize = BASIS_SYMBOLS.len();
+/// The characters that can be typed when editing the sequence of a strand in the 3D view
+pub const SEQUENCE_BASES: &str = "ATGCN";
+
+/// The characters that can be displayed in the labels attached to nucleotides
+pub const ANNOTATION_SYMBOLS: &str =
+    "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789'-_.,:;()#+/*°";
+pub const ANNOTATION_SCALE: f32 = 2.;
 
 pub const BASE_SCROLL_SENSITIVITY: f32 = 0.12;
 
@@ -53,14 +77,28 @@ pub const SAMPLE_COUNT: u32 = 4;
 
 pub const HELIX_BORDER_COLOR: u32 = 0xFF_101010;
 
+/// The transparency of the highlighted elements, whose colors are given by the color theme in the
+/// 3D view
+pub const HIGHLIGHT_ALPHA: u32 = 0xBF_00_00_00;
 pub const CANDIDATE_COLOR: u32 = 0xBF_00_FF_00;
 pub const SELECTED_COLOR: u32 = 0xBF_FF_00_00;
 pub const SUGGESTION_COLOR: u32 = 0xBF_FF_00_FF;
+pub const HIGHLIGHTED_PATH_COLOR: u32 = 0xBF_00_FF_FF;
+pub const ANCHOR_COLOR: u32 = 0xBF_FF_D7_00;
+/// The color of the nucleotides that are unpaired in a predicted secondary structure
+pub const UNPAIRED_NUCL_COLOR: [f32; 3] = [1., 0.4, 0.];
 pub const PIVOT_SPHERE_COLOR: u32 = 0xBF_FF_FF_00;
 pub const FREE_XOVER_COLOR: u32 = 0xBF_00_00_FF;
+/// The color of the ghost nucleotides showing the result of the strand being built
+pub const BUILDER_PREVIEW_COLOR: u32 = 0xBF_D0_D0_D0;
+/// The color of the nucleotides and segments showing the distances measured in the 3D view
+pub const MEASURE_COLOR: u32 = 0xBF_FF_80_00;
 
 pub const MAX_ZOOM_2D: f32 = 50.0;
 
+/// The maximum distance, in nm, between two strand ends that can be ligated
+pub const MAX_LIGATION_DISTANCE: f32 = 1.5;
+
 pub const CIRCLE2D_GREY: u32 = 0xFF_4D4D4D;
 pub const CIRCLE2D_BLUE: u32 = 0xFF_036992;
 pub const CIRCLE2D_RED: u32 = 0xFF_920303;
@@ -68,6 +106,13 @@ pub const CIRCLE2D_GREEN: u32 = 0xFF_0C9203;
 
 pub const SCAFFOLD_COLOR: u32 = 0xFF_3498DB;
 
+/// Maximal distance (in nm) between the moving end of a strand builder and a nucleotide of an
+/// other helix for a cross-over to be proposed
+pub const BUILDER_XOVER_MAX_DIST: f32 = 3.;
+pub const LATTICE_GRID_COLOR: u32 = 0x50_80_80_80;
+pub const LATTICE_LABEL_COLOR: u32 = 0xFF_60_60_60;
+pub const LATTICE_HELIX_LABEL_COLOR: u32 = 0xFF_00_00_00;
+
 pub const SELECTED_HELIX2D_COLOR: u32 = 0xFF_BF_1E_28;
 
 pub const ICON_PHYSICAL_ENGINE: char = '\u{e917}';
@@ -122,11 +167,23 @@ pub const CYM_HANDLE_COLORS: [u32; 3] = [0x00FFFF, 0xFF00FF, 0xFFFF00];
 
 pub const ENS_EXTENSION: &'static str = "ens";
 pub const ENS_BACKUP_EXTENSION: &'static str = "ensbackup";
+/// The extensions of the files that are read as scadnano designs
+pub const SCADNANO_EXTENSIONS: &'static [&'static str] = &["sc", "dna"];
 pub const ENS_UNAMED_FILE_NAME: &'static str = "Unamed_design";
 pub const CANNOT_OPEN_DEFAULT_DIR: &'static str = "Unable to open document or home directory.
 No backup will be saved for this unamed design";
 
 pub const NO_DESIGN_TITLE: &'static str = "New file";
 
+/// The maximum width and height in pixels of an exported image. This is the maximum size of a
+/// texture with the default limits of wgpu.
+pub const MAX_EXPORT_DIMENSION: u32 = 8192;
+
 pub const SEC_BETWEEN_BACKUPS: u64 = 60;
+/// The time during which each frame of a trajectory is displayed when it is played
+pub const TRAJECTORY_FRAME_DURATION_MS: u64 = 100;
 pub const SEC_PER_YEAR: u64 = 31_536_000;
+
+/// The directory, in the home directory of the user, in which the user's settings and plugins
+/// are stored
+pub const ENSNANO_USER_DIR: &'static str = ".ensnano";
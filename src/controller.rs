@@ -21,19 +21,45 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 use ensnano_design::Nucl;
 mod download_staples;
 use download_staples::*;
-pub use download_staples::{DownloadStappleError, DownloadStappleOk, StaplesDownloader};
+pub use download_staples::{DownloadStappleError, DownloadStappleOk, PlateSize, StaplesDownloader};
 mod quit;
 use ensnano_design::group_attributes::GroupPivot;
-use ensnano_interactor::{application::Notification, DesignOperation};
+use ensnano_interactor::{application::Notification, DesignOperation, DesignToMerge};
 use ensnano_interactor::{DesignReader, RigidBodyConstants, Selection};
 use quit::*;
 mod set_scaffold_sequence;
 use set_scaffold_sequence::*;
 pub use set_scaffold_sequence::{ScaffoldSetter, SetScaffoldSequenceError, SetScaffoldSequenceOk};
+mod camera_view;
+pub use camera_view::CameraViewError;
+use camera_view::*;
+mod design_module;
+use design_module::*;
+mod oxdna_trajectory;
+use oxdna_trajectory::*;
+mod background_job;
+pub use background_job::{BackgroundJob, JobError};
+mod job_manager;
+pub use job_manager::{FinishedJob, JobKind, JobOutput};
+mod relaxation;
+use relaxation::start_relaxation;
+pub use relaxation::RelaxationResult;
+mod color_palette;
+use color_palette::*;
 mod chanel_reader;
 mod messages;
+mod operation_macro;
+pub use operation_macro::{MacroRecorder, OperationMacro};
+use operation_macro::{PlayMacro, SaveMacro};
+mod session_log;
+use session_log::ReplaySession;
+pub use session_log::SessionLog;
+mod secondary_structure;
+pub use secondary_structure::{
+    secondary_structure_job, MfeError, SecondaryStructure, SecondaryStructurePrediction,
+};
 mod normal_state;
-pub use chanel_reader::{ChanelReader, ChanelReaderUpdate};
+pub use chanel_reader::{ChanelReader, ChanelReaderUpdate, NucleotideColor};
 pub use normal_state::Action;
 use normal_state::NormalState;
 
@@ -183,14 +209,109 @@ pub(crate) trait MainState: ScaffoldSetter {
     fn save_backup(&mut self) -> Result<(), SaveDesignError>;
     fn get_chanel_reader(&mut self) -> &mut ChanelReader;
     fn apply_operation(&mut self, operation: DesignOperation);
+    /// Apply `operation` and return the error that prevented it from being applied, if any
+    fn try_apply_operation(
+        &mut self,
+        operation: DesignOperation,
+    ) -> Result<(), crate::app_state::ErrOperation>;
+    /// Apply `operations` in order so that they are undone in one step
+    fn apply_operations(&mut self, operations: Vec<DesignOperation>);
     fn apply_silent_operation(&mut self, operation: DesignOperation);
+    /// Open the cyclic scaffold at `at` and return the continuity of the resulting scaffold
+    fn break_scaffold_at(
+        &mut self,
+        at: Nucl,
+    ) -> Result<ensnano_design::ScaffoldContinuityResult, crate::app_state::ErrOperation>;
     fn undo(&mut self);
     fn redo(&mut self);
+    /// Remove the strands that are being built if one of them is shorter than the minimum strand
+    /// length
+    fn reject_short_built_strands(&mut self) -> Result<(), crate::app_state::ErrOperation>;
     fn get_staple_downloader(&self) -> Box<dyn StaplesDownloader>;
     fn toggle_split_mode(&mut self, mode: SplitMode);
     fn oxdna_export(&mut self, path: &PathBuf) -> std::io::Result<(PathBuf, PathBuf)>;
+    /// Write a coarse atomic model of the design in the PDB format
+    fn pdb_export(&mut self, path: &PathBuf) -> std::io::Result<()>;
+    /// Write the helices `helix_ids`, with the strands that lie on them, in a design file
+    fn export_subdesign(&mut self, helix_ids: &[usize], path: &PathBuf) -> std::io::Result<()>;
+    /// A reader of the oxDNA trajectories of the current design
+    fn oxdna_trajectory_reader(&self) -> crate::app_state::OxDnaTrajectoryReader;
+    /// Start or stop the automatic playback of the loaded trajectory
+    fn play_trajectory(&mut self, playing: bool);
+    /// Display a frame of the loaded trajectory
+    fn set_trajectory_frame(&mut self, frame: usize);
+    /// Stop displaying the loaded trajectory
+    fn close_trajectory(&mut self);
+    /// Read the design stored in the file at `path` and display it next to the current design
+    fn open_design_in_workspace(&mut self, path: PathBuf) -> Result<(), LoadDesignError>;
+    /// Read the design stored in the file at `path` so that it can be merged with the current
+    /// design
+    fn read_design_to_merge(&self, path: &PathBuf) -> Result<DesignToMerge, LoadDesignError>;
+    fn set_main_design_visibility(&mut self, visible: bool);
+    fn set_workspace_design_visibility(&mut self, design_id: usize, visible: bool);
+    fn set_workspace_design_transform(
+        &mut self,
+        design_id: usize,
+        transform: ensnano_interactor::WorkspaceTransform,
+    );
+    fn close_workspace_design(&mut self, design_id: usize);
+    /// Add the content of a design of the workspace, at its current position, to the current
+    /// design and remove it from the workspace. The design stays in the workspace if the merge
+    /// fails.
+    fn merge_workspace_design(
+        &mut self,
+        design_id: usize,
+    ) -> Result<(), crate::app_state::ErrOperation>;
+    /// Move the design `design_id` of the workspace so that its helix `helix` lies next to the
+    /// selected helix of the current design. Return false if no helix of the current design is
+    /// selected.
+    fn dock_workspace_design(&mut self, design_id: usize, helix: usize) -> bool;
+    /// Return true if a job of kind `kind` is running
+    fn is_job_running(&self, kind: JobKind) -> bool;
+    /// Display the progress of `job` in the status bar and handle its result when it is over
+    fn attach_job(&mut self, kind: JobKind, job: BackgroundJob<JobOutput>);
+    /// Ask the job `job_id`, if it is still running, to stop
+    fn cancel_job(&mut self, job_id: usize);
+    fn cadnano_export(
+        &mut self,
+        path: &PathBuf,
+    ) -> Result<(), crate::app_state::CadnanoExportError>;
+    fn connectivity_graph_export(&mut self, path: &PathBuf) -> std::io::Result<()>;
+    fn export_helix_map_png(
+        &mut self,
+        dpi: u32,
+        size_mm: (f32, f32),
+        path: &PathBuf,
+    ) -> std::io::Result<()>;
+    fn export_scene_png(&mut self, size: (u32, u32), path: &PathBuf) -> std::io::Result<()>;
+    /// The positions and orientations of the camera at each frame of an animation of the 3D view
+    fn animation_viewpoints(
+        &mut self,
+        parameters: ensnano_interactor::graphics::AnimationParameters,
+    ) -> std::io::Result<Vec<(Vec3, Rotor3)>>;
+    /// Render an image of `size` pixels of the 3D view, as seen from `viewpoint`, and return its
+    /// RGBA pixels
+    fn render_scene_viewpoint(
+        &mut self,
+        viewpoint: (Vec3, Rotor3),
+        size: (u32, u32),
+    ) -> std::io::Result<Vec<u8>>;
+    fn export_mesh(&mut self, path: &PathBuf) -> std::io::Result<()>;
+    /// Show the actions that can be applied to the current selection in a floating menu at the
+    /// position of the cursor
+    fn open_context_menu(&mut self);
+    fn close_context_menu(&mut self);
     fn change_ui_size(&mut self, ui_size: UiSize);
     fn invert_scroll_y(&mut self, inverted: bool);
+    fn record_key_binding(
+        &mut self,
+        action: crate::key_bindings::HotkeyAction,
+        previous: Option<crate::key_bindings::KeyCombination>,
+    );
+    /// Stop waiting for the key combination of a new binding
+    fn cancel_key_binding_recording(&mut self);
+    fn update_key_bindings(&mut self, bindings: crate::key_bindings::KeyBindings);
+    fn trigger_plugin_action(&mut self, plugin_id: usize, name: String);
     fn notify_apps(&mut self, notificiation: Notification);
     fn get_selection(&mut self) -> Box<dyn AsRef<[Selection]>>;
     fn get_design_reader(&mut self) -> Box<dyn DesignReader>;
@@ -203,14 +324,33 @@ pub(crate) trait MainState: ScaffoldSetter {
     fn duplicate(&mut self);
     fn delete_selection(&mut self);
     fn scaffold_to_selection(&mut self);
+    /// Highlight a shortest path between the two selected nucleotides and return its length
+    fn highlight_shortest_path(&mut self) -> Option<usize>;
+    /// Select the cross-over between the `a.1`-th nucleotide of strand `a.0` and the `b.1`-th
+    /// nucleotide of strand `b.0`, center the views on it and highlight it in the 2D view.
+    /// Return false if there is no such cross-over
+    fn go_to_xover(&mut self, a: (usize, usize), b: (usize, usize)) -> bool;
+    /// Start predicting the secondary structure of a strand with `RNAfold` in a background job.
+    /// The unpaired nucleotides are colored when the job is over.
+    fn predict_secondary_structure(&mut self, s_id: usize) -> Result<(), MfeError>;
     fn start_helix_simulation(&mut self, parameters: RigidBodyConstants);
     fn start_grid_simulation(&mut self, parameters: RigidBodyConstants);
     fn start_roll_simulation(&mut self, target_helices: Option<Vec<usize>>);
+    fn optimize_rolls(&mut self, target_helices: Option<Vec<usize>>);
     fn update_simulation(&mut self, request: SimulationRequest);
     fn set_roll_of_selected_helices(&mut self, roll: f32);
     fn turn_selection_into_anchor(&mut self);
     fn set_visibility_sieve(&mut self, compl: bool);
     fn clear_visibility_sieve(&mut self);
+    fn add_measurement(&mut self, measurement: ensnano_interactor::Measurement);
+    fn clear_measurements(&mut self);
+    fn save_selection_group(&mut self, name: String);
+    fn recall_selection_group(
+        &mut self,
+        name: String,
+        combination: ensnano_interactor::SelectionGroupCombination,
+    );
+    fn delete_selection_group(&mut self, name: String);
     fn need_save(&self) -> bool;
     fn get_current_design_directory(&self) -> Option<&Path>;
     fn get_current_file_name(&self) -> Option<&Path>;
@@ -221,8 +361,30 @@ pub(crate) trait MainState: ScaffoldSetter {
     fn select_camera(&mut self, camera_id: ensnano_design::CameraId);
     fn select_favorite_camera(&mut self, n_camera: u32);
     fn update_camera(&mut self, camera_id: ensnano_design::CameraId);
+    /// Write the current point of view of the 3D scene in a json file
+    fn export_camera_view(&mut self, path: &PathBuf) -> Result<(), CameraViewError>;
+    /// Move the camera of the 3D scene to the point of view described in a json file
+    fn import_camera_view(&mut self, path: &PathBuf) -> Result<(), CameraViewError>;
+    /// Add a named color to the palette of the user, replacing the color with the same name
+    fn add_palette_color(&mut self, name: String, color: u32);
+    /// Save the colors of the 3D view chosen by the user and apply them
+    fn set_color_theme(&mut self, theme: ensnano_interactor::graphics::ColorTheme);
+    /// Set and save the number of undoable states embedded in the saved files
+    fn set_edit_history_size(&mut self, size: usize);
+    /// Start or stop logging the design operations, and save this choice
+    fn set_session_log_enabled(&mut self, enabled: bool);
+    /// Write the palette of named colors in a json file
+    fn export_color_palette(&mut self, path: &PathBuf) -> Result<(), String>;
+    /// Replace the palette of named colors by the one read from a json file
+    fn import_color_palette(&mut self, path: &PathBuf) -> Result<(), String>;
     fn need_backup(&self) -> bool;
     fn flip_split_views(&mut self);
+    fn start_2d_xover_tool(&mut self);
+    /// Start recording the design operations that are applied
+    fn start_recording_macro(&mut self);
+    /// Stop recording design operations and return the recorded macro, if a macro was being
+    /// recorded
+    fn stop_recording_macro(&mut self) -> Option<OperationMacro>;
 }
 
 pub struct LoadDesignError(String);
@@ -0,0 +1,163 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! This module defines the `BackgroundJob` struct, a handle on a long computation, for example an
+//! external program, that runs in a separate thread while the user keeps interacting with the
+//! application.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+
+/// The reason why a background job did not complete
+#[derive(Debug)]
+pub enum JobError {
+    /// The job was canceled by the user
+    Canceled,
+    /// The job could not be completed
+    Failed(String),
+    IOError(std::io::Error),
+}
+
+impl std::fmt::Display for JobError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Canceled => write!(f, "The job was canceled"),
+            Self::Failed(reason) => write!(f, "{}", reason),
+            Self::IOError(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<std::io::Error> for JobError {
+    fn from(e: std::io::Error) -> Self {
+        Self::IOError(e)
+    }
+}
+
+/// The interface through which a job reports its progress and learns that it has been canceled
+pub struct JobContext {
+    progress: mpsc::Sender<f32>,
+    canceled: Arc<AtomicBool>,
+}
+
+impl JobContext {
+    /// Report the fraction, between 0 and 1, of the job that has been done
+    pub fn set_progress(&self, progress: f32) {
+        // The handle may have been dropped, in which case nobody is listening
+        let _ = self.progress.send(progress.clamp(0., 1.));
+    }
+
+    pub fn is_canceled(&self) -> bool {
+        self.canceled.load(Ordering::Relaxed)
+    }
+}
+
+/// A handle on a computation running in a separate thread
+pub struct BackgroundJob<T> {
+    progress: mpsc::Receiver<f32>,
+    result: mpsc::Receiver<Result<T, JobError>>,
+    canceled: Arc<AtomicBool>,
+}
+
+impl<T: Send + 'static> BackgroundJob<T> {
+    /// Run `job` in a new thread.
+    ///
+    /// The job is expected to check regularly whether it has been canceled and to stop as soon as
+    /// possible when it is the case.
+    pub fn spawn<F>(job: F) -> Self
+    where
+        F: FnOnce(&JobContext) -> Result<T, JobError> + Send + 'static,
+    {
+        let (progress_snd, progress_rcv) = mpsc::channel();
+        let (result_snd, result_rcv) = mpsc::channel();
+        let canceled = Arc::new(AtomicBool::new(false));
+        let context = JobContext {
+            progress: progress_snd,
+            canceled: canceled.clone(),
+        };
+        std::thread::spawn(move || {
+            let result = job(&context);
+            let _ = result_snd.send(result);
+        });
+        Self {
+            progress: progress_rcv,
+            result: result_rcv,
+            canceled,
+        }
+    }
+}
+
+impl<T> BackgroundJob<T> {
+    /// Ask the job to stop. Its result will be `Err(JobError::Canceled)`.
+    pub fn cancel(&self) {
+        self.canceled.store(true, Ordering::Relaxed)
+    }
+
+    /// The most recent progress reported by the job since the last call to this method
+    pub fn get_progress(&self) -> Option<f32> {
+        self.progress.try_iter().last()
+    }
+
+    /// The result of the job if it is finished
+    pub fn get_result(&self) -> Option<Result<T, JobError>> {
+        match self.result.try_recv() {
+            Ok(result) => Some(result),
+            Err(mpsc::TryRecvError::Empty) => None,
+            Err(mpsc::TryRecvError::Disconnected) => Some(Err(JobError::Failed(
+                "The job stopped unexpectedly".to_string(),
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn wait_for_result<T>(job: &BackgroundJob<T>) -> Result<T, JobError> {
+        loop {
+            if let Some(result) = job.get_result() {
+                return result;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn job_reports_progress_and_result() {
+        let job = BackgroundJob::spawn(|context| {
+            context.set_progress(0.5);
+            Ok(42)
+        });
+        assert_eq!(wait_for_result(&job).ok(), Some(42));
+        assert_eq!(job.get_progress(), Some(0.5));
+    }
+
+    #[test]
+    fn canceled_job_stops() {
+        let job: BackgroundJob<()> = BackgroundJob::spawn(|context| loop {
+            if context.is_canceled() {
+                return Err(JobError::Canceled);
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        });
+        job.cancel();
+        assert!(matches!(wait_for_result(&job), Err(JobError::Canceled)));
+    }
+}
@@ -0,0 +1,118 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::{dialog, messages, MainState, NormalState, State, TransitionMessage};
+use dialog::PathInput;
+
+/// Write the current point of view of the 3D scene in a json file
+#[derive(Default)]
+pub(super) struct ExportCameraView {
+    file_getter: Option<PathInput>,
+}
+
+impl State for ExportCameraView {
+    fn make_progress(mut self: Box<Self>, main_state: &mut dyn MainState) -> Box<dyn State> {
+        if let Some(ref getter) = self.file_getter {
+            if let Some(path_opt) = getter.get() {
+                if let Some(ref path) = path_opt {
+                    match main_state.export_camera_view(path) {
+                        Err(err) => TransitionMessage::new(
+                            format!("Could not export view: {}", err),
+                            rfd::MessageLevel::Error,
+                            Box::new(NormalState),
+                        ),
+                        Ok(()) => TransitionMessage::new(
+                            messages::successfull_camera_view_export_msg(path),
+                            rfd::MessageLevel::Info,
+                            Box::new(NormalState),
+                        ),
+                    }
+                } else {
+                    TransitionMessage::new(
+                        messages::NO_FILE_RECIEVED_CAMERA_VIEW,
+                        rfd::MessageLevel::Error,
+                        Box::new(NormalState),
+                    )
+                }
+            } else {
+                self
+            }
+        } else {
+            let getter = dialog::save("json", main_state.get_current_design_directory(), None);
+            self.file_getter = Some(getter);
+            self
+        }
+    }
+}
+
+/// Read a point of view of the 3D scene from a json file and move the camera to it
+#[derive(Default)]
+pub(super) struct ImportCameraView {
+    file_getter: Option<PathInput>,
+}
+
+impl State for ImportCameraView {
+    fn make_progress(mut self: Box<Self>, main_state: &mut dyn MainState) -> Box<dyn State> {
+        if let Some(ref getter) = self.file_getter {
+            if let Some(path_opt) = getter.get() {
+                if let Some(ref path) = path_opt {
+                    if let Err(err) = main_state.import_camera_view(path) {
+                        TransitionMessage::new(
+                            format!("Could not import view: {}", err),
+                            rfd::MessageLevel::Error,
+                            Box::new(NormalState),
+                        )
+                    } else {
+                        Box::new(NormalState)
+                    }
+                } else {
+                    TransitionMessage::new(
+                        messages::NO_FILE_RECIEVED_CAMERA_VIEW,
+                        rfd::MessageLevel::Error,
+                        Box::new(NormalState),
+                    )
+                }
+            } else {
+                self
+            }
+        } else {
+            let getter = dialog::load(
+                main_state.get_current_design_directory(),
+                messages::CAMERA_VIEW_FILTERS,
+            );
+            self.file_getter = Some(getter);
+            self
+        }
+    }
+}
+
+/// An error that occured while reading or writing a camera view file
+#[derive(Debug)]
+pub struct CameraViewError(pub String);
+
+impl From<String> for CameraViewError {
+    fn from(s: String) -> Self {
+        Self(s)
+    }
+}
+
+impl std::fmt::Display for CameraViewError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
@@ -23,36 +23,56 @@ use std::sync::mpsc;
 use std::sync::{Arc, Mutex, Weak};
 
 use crate::app_state::{
-    ShiftOptimizationResult, ShiftOptimizerReader, SimulationInterface, SimulationReader,
+    ShiftOptimization, ShiftOptimizerReader, SimulationInterface, SimulationReader,
     SimulationUpdate,
 };
+use crate::gui::JobProgress;
+
+use super::background_job::{BackgroundJob, JobError};
+use super::job_manager::{FinishedJob, JobKind, JobManager, JobOutput};
+
+mod nucleotide_colors;
+pub use nucleotide_colors::NucleotideColor;
+
 #[derive(Default)]
 pub struct ChanelReader {
-    scaffold_shift_optimization_progress: Option<mpsc::Receiver<f32>>,
-    scaffold_shift_optimization_result: Option<mpsc::Receiver<ShiftOptimizationResult>>,
     simulation_interface: Option<Weak<Mutex<dyn SimulationInterface>>>,
+    nucleotide_colors: Option<mpsc::Receiver<NucleotideColor>>,
+    /// The long computations running in the background
+    jobs: JobManager,
+    /// Components that want to receive a copy of the updates, identified by a tag
+    subscribers: Vec<(String, mpsc::Sender<ChanelReaderUpdate>)>,
 }
 
 pub enum ChanelReaderUpdate {
-    /// Progress has been made in the optimization of the scaffold position
-    ScaffoldShiftOptimizationProgress(f32),
-    /// The optimum scaffold position has been found
-    ScaffoldShiftOptimizationResult(ShiftOptimizationResult),
     SimulationUpdate(Box<dyn SimulationUpdate>),
     SimulationExpired,
+    /// External programs have requested new colors for some nucleotides
+    NucleotideColors(Vec<NucleotideColor>),
+    /// The progress of the background jobs has changed
+    JobsProgress(Vec<JobProgress>),
+    /// A background job is over
+    JobFinished(FinishedJob),
+}
+
+impl ChanelReaderUpdate {
+    /// Return a copy of the update that can be sent to subscribers.
+    ///
+    /// The results of the background jobs and the simulation updates are consumed by the main
+    /// loop and are not broadcasted.
+    fn broadcast_copy(&self) -> Option<Self> {
+        match self {
+            Self::SimulationExpired => Some(Self::SimulationExpired),
+            Self::NucleotideColors(colors) => Some(Self::NucleotideColors(colors.clone())),
+            Self::JobsProgress(jobs) => Some(Self::JobsProgress(jobs.clone())),
+            Self::SimulationUpdate(_) | Self::JobFinished(_) => None,
+        }
+    }
 }
 
 impl ChanelReader {
     pub fn get_updates(&mut self) -> Vec<ChanelReaderUpdate> {
         let mut updates = Vec::new();
-        if let Some(progress) = self.get_scaffold_shift_optimization_progress() {
-            updates.push(ChanelReaderUpdate::ScaffoldShiftOptimizationProgress(
-                progress,
-            ));
-        }
-        if let Some(result) = self.get_scaffold_shift_optimization_result() {
-            updates.push(ChanelReaderUpdate::ScaffoldShiftOptimizationResult(result));
-        }
         let mut invalidated = false;
         if let Some(interface_ptr) = self.simulation_interface.as_ref() {
             if let Some(interface) = interface_ptr.upgrade() {
@@ -70,29 +90,92 @@ impl ChanelReader {
         if invalidated {
             self.simulation_interface = None;
         }
+        let colors = self.get_nucleotide_colors();
+        if !colors.is_empty() {
+            updates.push(ChanelReaderUpdate::NucleotideColors(colors));
+        }
+        let finished = self.jobs.poll_finished();
+        if let Some(progress) = self.jobs.poll_progress() {
+            updates.push(ChanelReaderUpdate::JobsProgress(progress));
+        }
+        updates.extend(finished.into_iter().map(ChanelReaderUpdate::JobFinished));
+        self.broadcast(&updates);
         updates
     }
 
-    fn get_scaffold_shift_optimization_progress(&self) -> Option<f32> {
-        self.scaffold_shift_optimization_progress
-            .as_ref()
-            .and_then(|chanel| chanel.try_recv().ok())
+    /// Return a chanel on which a copy of the broadcastable updates will be sent each time
+    /// `get_updates` is called.
+    ///
+    /// The `tag` is used to identify the subscriber in the logs.
+    pub fn subscribe(&mut self, tag: &str) -> mpsc::Receiver<ChanelReaderUpdate> {
+        let (snd, rcv) = mpsc::channel();
+        self.subscribers.push((tag.to_string(), snd));
+        rcv
+    }
+
+    fn broadcast(&mut self, updates: &[ChanelReaderUpdate]) {
+        if self.subscribers.is_empty() {
+            return;
+        }
+        for update in updates.iter() {
+            self.subscribers.retain(|(tag, snd)| {
+                if let Some(copy) = update.broadcast_copy() {
+                    let connected = snd.send(copy).is_ok();
+                    if !connected {
+                        log::info!("Subscriber {} stopped listening to chanel reader", tag);
+                    }
+                    connected
+                } else {
+                    true
+                }
+            });
+        }
+    }
+
+    /// Start listening to external programs that want to set the color of nucleotides, if the
+    /// user has enabled it
+    pub fn listen_to_nucleotide_colors(&mut self) {
+        if !nucleotide_colors::is_enabled() {
+            return;
+        }
+        match nucleotide_colors::listen() {
+            Ok(chanel) => self.nucleotide_colors = Some(chanel),
+            Err(e) => log::error!("Could not listen to nucleotide colors: {}", e),
+        }
+    }
+
+    /// Follow the progress of a background job
+    pub fn attach_job(&mut self, kind: JobKind, job: BackgroundJob<JobOutput>) {
+        self.jobs.attach(kind, job);
     }
 
-    fn get_scaffold_shift_optimization_result(&self) -> Option<ShiftOptimizationResult> {
-        self.scaffold_shift_optimization_result
+    pub fn is_job_running(&self, kind: JobKind) -> bool {
+        self.jobs.is_running(kind)
+    }
+
+    /// Ask the job `job_id`, if it is still running, to stop
+    pub fn cancel_job(&self, job_id: usize) {
+        self.jobs.cancel(job_id)
+    }
+
+    fn get_nucleotide_colors(&self) -> Vec<NucleotideColor> {
+        self.nucleotide_colors
             .as_ref()
-            .and_then(|chanel| chanel.try_recv().ok())
+            .map(|chanel| chanel.try_iter().collect())
+            .unwrap_or_default()
     }
 }
 
 impl ShiftOptimizerReader for ChanelReader {
-    fn attach_result_chanel(&mut self, chanel: mpsc::Receiver<ShiftOptimizationResult>) {
-        self.scaffold_shift_optimization_result = Some(chanel);
-    }
-
-    fn attach_progress_chanel(&mut self, chanel: mpsc::Receiver<f32>) {
-        self.scaffold_shift_optimization_progress = Some(chanel);
+    fn spawn_shift_optimization(&mut self, optimization: ShiftOptimization) {
+        let job = BackgroundJob::spawn(move |context| {
+            match optimization.run(|p| context.set_progress(p), || context.is_canceled()) {
+                Some(Ok(result)) => Ok(JobOutput::ScaffoldShift(result)),
+                Some(Err(e)) => Err(JobError::Failed(format!("{:?}", e))),
+                None => Err(JobError::Canceled),
+            }
+        });
+        self.attach_job(JobKind::ScaffoldShiftOptimization, job)
     }
 }
 
@@ -0,0 +1,148 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! Listen to external programs that want to set the color of individual nucleotides.
+//!
+//! Clients write newline-delimited json objects of the form
+//! `{"strand": 0, "pos": 5, "color": [r, g, b]}` where `pos` is the index of the nucleotide in
+//! the strand, starting from the 5' end, and the color components are between 0 and 1.
+//!
+//! The listener is disabled unless the `"nucleotide_colors_listener"` field of
+//! `~/.ensnano/preferences.json` is `true`.
+//!
+//! On unix systems, the listener is a unix socket named `ensnano_nucleotide_colors_<pid>.sock` in
+//! the temporary directory, where `<pid>` is the id of the ENSnano process. On other systems, it
+//! is a tcp socket bound to a port of the loopback interface chosen by the system. In both cases,
+//! the address of the listener is logged when it starts.
+
+use crate::preferences;
+use serde_derive::Deserialize;
+use std::io::{BufRead, BufReader, Read};
+use std::sync::mpsc;
+use std::thread;
+
+const ENABLED_FIELD: &str = "nucleotide_colors_listener";
+
+/// The address of the tcp socket used on systems that do not support unix sockets. The port is
+/// chosen by the system so that several instances of the program can listen at the same time.
+#[cfg(not(unix))]
+const SOCKET_ADDRESS: &str = "127.0.0.1:0";
+
+/// A request to set the color of one nucleotide
+#[derive(Debug, Clone, Deserialize)]
+pub struct NucleotideColor {
+    /// The identifier of the strand containing the nucleotide
+    pub strand: usize,
+    /// The index of the nucleotide in the strand, starting from the 5' end
+    pub pos: usize,
+    /// The red, green and blue components of the color, between 0 and 1
+    pub color: [f32; 3],
+}
+
+/// Return true if the user has allowed external programs to set the color of nucleotides
+pub fn is_enabled() -> bool {
+    preferences::read_field(ENABLED_FIELD)
+}
+
+/// Spawn a thread listening to nucleotide color requests and return the chanel on which these
+/// requests are forwarded.
+pub fn listen() -> std::io::Result<mpsc::Receiver<NucleotideColor>> {
+    let (snd, rcv) = mpsc::channel();
+    let listener = bind()?;
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let snd = snd.clone();
+                    thread::spawn(move || read_requests(stream, snd));
+                }
+                Err(e) => log::error!("Nucleotide color connection failed: {}", e),
+            }
+        }
+    });
+    Ok(rcv)
+}
+
+fn read_requests<S: Read>(stream: S, snd: mpsc::Sender<NucleotideColor>) {
+    for line in BufReader::new(stream).lines() {
+        match line {
+            Ok(line) if line.trim().is_empty() => (),
+            Ok(line) => match serde_json::from_str::<NucleotideColor>(&line) {
+                Ok(request) => {
+                    if snd.send(request).is_err() {
+                        // The receiver has been dropped, there is no point in reading further
+                        return;
+                    }
+                }
+                Err(e) => log::warn!("Invalid nucleotide color request {:?}: {}", line, e),
+            },
+            Err(e) => {
+                log::error!("Error while reading nucleotide color requests: {}", e);
+                return;
+            }
+        }
+    }
+}
+
+/// Bind the socket of this process. If a file already exists at the socket's path, the binding
+/// fails rather than taking over a socket that may belong to another program.
+#[cfg(unix)]
+fn bind() -> std::io::Result<std::os::unix::net::UnixListener> {
+    let path = std::env::temp_dir().join(format!(
+        "ensnano_nucleotide_colors_{}.sock",
+        std::process::id()
+    ));
+    let listener = std::os::unix::net::UnixListener::bind(&path)?;
+    log::info!(
+        "Listening to nucleotide colors on {}",
+        path.to_string_lossy()
+    );
+    Ok(listener)
+}
+
+#[cfg(not(unix))]
+fn bind() -> std::io::Result<std::net::TcpListener> {
+    let listener = std::net::TcpListener::bind(SOCKET_ADDRESS)?;
+    log::info!(
+        "Listening to nucleotide colors on {}",
+        listener.local_addr()?
+    );
+    Ok(listener)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_several_requests() {
+        let input = "{\"strand\": 0, \"pos\": 5, \"color\": [1.0, 0.5, 0.0]}\n\
+                     \n\
+                     not json\n\
+                     {\"strand\": 3, \"pos\": 0, \"color\": [0, 0, 1]}\n";
+        let (snd, rcv) = mpsc::channel();
+        read_requests(input.as_bytes(), snd);
+        let requests: Vec<NucleotideColor> = rcv.try_iter().collect();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].strand, 0);
+        assert_eq!(requests[0].pos, 5);
+        assert_eq!(requests[0].color, [1.0, 0.5, 0.0]);
+        assert_eq!(requests[1].strand, 3);
+        assert_eq!(requests[1].color, [0., 0., 1.]);
+    }
+}
@@ -0,0 +1,102 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::{dialog, messages, MainState, NormalState, State, TransitionMessage};
+use dialog::PathInput;
+
+/// Write the palette of named colors of the user in a json file
+#[derive(Default)]
+pub(super) struct ExportColorPalette {
+    file_getter: Option<PathInput>,
+}
+
+impl State for ExportColorPalette {
+    fn make_progress(mut self: Box<Self>, main_state: &mut dyn MainState) -> Box<dyn State> {
+        if let Some(ref getter) = self.file_getter {
+            if let Some(path_opt) = getter.get() {
+                if let Some(ref path) = path_opt {
+                    match main_state.export_color_palette(path) {
+                        Err(err) => TransitionMessage::new(
+                            messages::failed_to_save_msg(&err),
+                            rfd::MessageLevel::Error,
+                            Box::new(NormalState),
+                        ),
+                        Ok(()) => TransitionMessage::new(
+                            messages::successfull_color_palette_export_msg(path),
+                            rfd::MessageLevel::Info,
+                            Box::new(NormalState),
+                        ),
+                    }
+                } else {
+                    TransitionMessage::new(
+                        messages::NO_FILE_RECIEVED_PALETTE,
+                        rfd::MessageLevel::Error,
+                        Box::new(NormalState),
+                    )
+                }
+            } else {
+                self
+            }
+        } else {
+            let getter = dialog::save("json", main_state.get_current_design_directory(), None);
+            self.file_getter = Some(getter);
+            self
+        }
+    }
+}
+
+/// Replace the palette of named colors of the user by the one read from a json file
+#[derive(Default)]
+pub(super) struct ImportColorPalette {
+    file_getter: Option<PathInput>,
+}
+
+impl State for ImportColorPalette {
+    fn make_progress(mut self: Box<Self>, main_state: &mut dyn MainState) -> Box<dyn State> {
+        if let Some(ref getter) = self.file_getter {
+            if let Some(path_opt) = getter.get() {
+                if let Some(ref path) = path_opt {
+                    if let Err(err) = main_state.import_color_palette(path) {
+                        TransitionMessage::new(
+                            format!("Could not import color palette: {}", err),
+                            rfd::MessageLevel::Error,
+                            Box::new(NormalState),
+                        )
+                    } else {
+                        Box::new(NormalState)
+                    }
+                } else {
+                    TransitionMessage::new(
+                        messages::NO_FILE_RECIEVED_PALETTE,
+                        rfd::MessageLevel::Error,
+                        Box::new(NormalState),
+                    )
+                }
+            } else {
+                self
+            }
+        } else {
+            let getter = dialog::load(
+                main_state.get_current_design_directory(),
+                messages::COLOR_PALETTE_FILTERS,
+            );
+            self.file_getter = Some(getter);
+            self
+        }
+    }
+}
@@ -0,0 +1,171 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::{dialog, messages, MainState, NormalState, State, TransitionMessage};
+use dialog::PathInput;
+use ensnano_interactor::DesignOperation;
+use ultraviolet::{Rotor3, Vec3};
+
+/// Write the selected helices, with the strands that lie on them, in a design file
+pub(super) struct ExportModule {
+    helix_ids: Vec<usize>,
+    file_getter: Option<PathInput>,
+}
+
+impl ExportModule {
+    pub(super) fn new(helix_ids: Vec<usize>) -> Self {
+        Self {
+            helix_ids,
+            file_getter: None,
+        }
+    }
+}
+
+impl State for ExportModule {
+    fn make_progress(mut self: Box<Self>, main_state: &mut dyn MainState) -> Box<dyn State> {
+        if let Some(ref getter) = self.file_getter {
+            if let Some(path_opt) = getter.get() {
+                if let Some(ref path) = path_opt {
+                    match main_state.export_subdesign(&self.helix_ids, path) {
+                        Err(err) => TransitionMessage::new(
+                            messages::failed_to_save_msg(&err),
+                            rfd::MessageLevel::Error,
+                            Box::new(NormalState),
+                        ),
+                        Ok(()) => TransitionMessage::new(
+                            messages::successfull_export_msg(path),
+                            rfd::MessageLevel::Info,
+                            Box::new(NormalState),
+                        ),
+                    }
+                } else {
+                    TransitionMessage::new(
+                        messages::NO_FILE_RECIEVED_MODULE,
+                        rfd::MessageLevel::Error,
+                        Box::new(NormalState),
+                    )
+                }
+            } else {
+                self
+            }
+        } else {
+            let getter = dialog::save(
+                crate::consts::ENS_EXTENSION,
+                main_state.get_current_design_directory(),
+                None,
+            );
+            self.file_getter = Some(getter);
+            self
+        }
+    }
+}
+
+/// Add the content of a design file to the current design
+#[derive(Default)]
+pub(super) struct ImportModule {
+    file_getter: Option<PathInput>,
+}
+
+impl State for ImportModule {
+    fn make_progress(mut self: Box<Self>, main_state: &mut dyn MainState) -> Box<dyn State> {
+        if let Some(ref getter) = self.file_getter {
+            if let Some(path_opt) = getter.get() {
+                if let Some(path) = path_opt {
+                    // The module is read here so that the operation does not depend on the file
+                    // when it is replayed
+                    match main_state.read_design_to_merge(&path) {
+                        Ok(design) => {
+                            // The module is placed where a new grid would be created
+                            let offset = main_state
+                                .get_grid_creation_position()
+                                .map(|(position, _)| position)
+                                .unwrap_or_else(Vec3::zero);
+                            main_state.apply_operation(DesignOperation::MergeDesign {
+                                design,
+                                offset,
+                                orientation: Rotor3::identity(),
+                            });
+                            Box::new(NormalState)
+                        }
+                        Err(err) => TransitionMessage::new(
+                            format!("Error when loading design: {}", err.0),
+                            rfd::MessageLevel::Error,
+                            Box::new(NormalState),
+                        ),
+                    }
+                } else {
+                    TransitionMessage::new(
+                        messages::NO_FILE_RECIEVED_MODULE,
+                        rfd::MessageLevel::Error,
+                        Box::new(NormalState),
+                    )
+                }
+            } else {
+                self
+            }
+        } else {
+            let getter = dialog::load(
+                main_state.get_current_design_directory(),
+                messages::DESIGN_FILTERS,
+            );
+            self.file_getter = Some(getter);
+            self
+        }
+    }
+}
+
+/// Read a design file and display it next to the current design
+#[derive(Default)]
+pub(super) struct OpenInWorkspace {
+    file_getter: Option<PathInput>,
+}
+
+impl State for OpenInWorkspace {
+    fn make_progress(mut self: Box<Self>, main_state: &mut dyn MainState) -> Box<dyn State> {
+        if let Some(ref getter) = self.file_getter {
+            if let Some(path_opt) = getter.get() {
+                if let Some(path) = path_opt {
+                    if let Err(err) = main_state.open_design_in_workspace(path) {
+                        TransitionMessage::new(
+                            format!("Error when loading design: {}", err.0),
+                            rfd::MessageLevel::Error,
+                            Box::new(NormalState),
+                        )
+                    } else {
+                        Box::new(NormalState)
+                    }
+                } else {
+                    TransitionMessage::new(
+                        messages::NO_FILE_RECIEVED_LOAD,
+                        rfd::MessageLevel::Error,
+                        Box::new(NormalState),
+                    )
+                }
+            } else {
+                self
+            }
+        } else {
+            let getter = dialog::load(
+                main_state.get_current_design_directory(),
+                messages::DESIGN_FILTERS,
+            );
+            self.file_getter = Some(getter);
+            self
+        }
+    }
+}
@@ -16,10 +16,14 @@ ENSnano, a 3d graphical application for DNA nanostructures.
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
-use super::{messages, MainState, NormalState, State, TransitionMessage};
+use super::{
+    messages, BackgroundJob, JobKind, JobOutput, MainState, NormalState, State, TransitionMessage,
+};
 
 use crate::dialog;
-use dialog::{MustAckMessage, PathInput};
+use dialog::{MustAckMessage, PathInput, YesNoQuestion};
+use ensnano_design::ScaffoldBreak;
+use ensnano_interactor::OrderFormat;
 use std::path::{Path, PathBuf};
 
 #[derive(Default)]
@@ -27,23 +31,133 @@ pub(super) struct DownloadStaples {
     step: Step,
 }
 
+impl DownloadStaples {
+    /// Export the staples partitioned in `n_steps` pools of decreasing melting temperature
+    /// instead of sorting them by plates
+    pub(super) fn pools(n_steps: usize) -> Self {
+        Self {
+            step: Step::Init {
+                options: ExportOptions {
+                    pools: Some(n_steps),
+                    ..Default::default()
+                },
+            },
+        }
+    }
+
+    /// Export the staples sorted by plates in the layout of `order_format`, in a csv file if
+    /// `csv` is true and in an xlsx file otherwise
+    pub(super) fn plates(order_format: OrderFormat, csv: bool) -> Self {
+        Self {
+            step: Step::Init {
+                options: ExportOptions {
+                    csv,
+                    order_format,
+                    ..Default::default()
+                },
+            },
+        }
+    }
+}
+
+/// The format of the file produced by the staple export
+#[derive(Clone, Copy, Default)]
+struct ExportOptions {
+    /// If not None, the staples are partitioned in that many pools instead of being sorted by
+    /// plates
+    pools: Option<usize>,
+    /// Write a csv file instead of an xlsx file
+    csv: bool,
+    plate_size: PlateSize,
+    order_format: OrderFormat,
+}
+
+impl ExportOptions {
+    fn extension(&self) -> &'static str {
+        if self.csv {
+            "csv"
+        } else {
+            "xlsx"
+        }
+    }
+}
+
+/// The layout of the plates in which the staples are sorted
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlateSize {
+    /// 8 rows (A-H) of 12 columns
+    Wells96,
+    /// 16 rows (A-P) of 24 columns
+    Wells384,
+}
+
+impl Default for PlateSize {
+    fn default() -> Self {
+        Self::Wells96
+    }
+}
+
+impl PlateSize {
+    pub fn nb_rows(&self) -> usize {
+        match self {
+            Self::Wells96 => 8,
+            Self::Wells384 => 16,
+        }
+    }
+
+    pub fn nb_columns(&self) -> usize {
+        match self {
+            Self::Wells96 => 12,
+            Self::Wells384 => 24,
+        }
+    }
+
+    pub fn nb_wells(&self) -> usize {
+        self.nb_rows() * self.nb_columns()
+    }
+
+    /// Return the plate (starting at 1) and the well position of the `n`-th staple.
+    ///
+    /// Plates are filled column by column, i.e. A1, B1, ..., H1, A2, ... for 96-well plates.
+    pub fn well_position(&self, n: usize) -> (usize, String) {
+        let plate = n / self.nb_wells() + 1;
+        let idx = n % self.nb_wells();
+        let row = (b'A' + (idx % self.nb_rows()) as u8) as char;
+        let column = idx / self.nb_rows() + 1;
+        (plate, format!("{}{}", row, column))
+    }
+}
+
 enum Step {
     /// The staple downloading request has just started
-    Init,
+    Init { options: ExportOptions },
     /// Asking the user where to write the result
     AskingPath(AskingPath_),
+    /// Waiting for the user to chose between 96-well and 384-well plates
+    AskingPlateSize {
+        question: YesNoQuestion,
+        design_id: usize,
+        options: ExportOptions,
+    },
     /// The path was asked, waiting for user to chose it
     PathAsked {
         path_input: dialog::PathInput,
         design_id: usize,
+        options: ExportOptions,
+    },
+    /// The path was chosen, the staples can be written
+    Downloading {
+        design_id: usize,
+        path: PathBuf,
+        options: ExportOptions,
     },
-    /// Downloading
-    Downloading { design_id: usize, path: PathBuf },
 }
 
 impl Default for Step {
     fn default() -> Self {
-        Self::Init
+        Self::Init {
+            options: Default::default(),
+        }
     }
 }
 
@@ -51,26 +165,49 @@ impl State for DownloadStaples {
     fn make_progress(self: Box<Self>, main_state: &mut dyn MainState) -> Box<dyn State> {
         let downloader = main_state.get_staple_downloader();
         match self.step {
-            Step::Init => get_design_providing_staples(downloader.as_ref()),
+            Step::Init { options } => get_design_providing_staples(downloader.as_ref(), options),
             Step::AskingPath(state) => ask_path(state, main_state.get_current_design_directory()),
+            Step::AskingPlateSize {
+                question,
+                design_id,
+                options,
+            } => poll_plate_size(
+                question,
+                design_id,
+                options,
+                main_state.get_current_design_directory(),
+            ),
             Step::PathAsked {
                 path_input,
                 design_id,
-            } => poll_path(path_input, design_id),
-            Step::Downloading { design_id, path } => {
-                download_staples(downloader.as_ref(), design_id, path)
+                options,
+            } => poll_path(path_input, design_id, options),
+            Step::Downloading {
+                design_id: _,
+                path,
+                options,
+            } => {
+                main_state.attach_job(
+                    JobKind::StapleExport,
+                    staple_export_job(downloader, path, options),
+                );
+                Box::new(NormalState)
             }
         }
     }
 }
 
-fn get_design_providing_staples(downlader: &dyn StaplesDownloader) -> Box<dyn State> {
+fn get_design_providing_staples(
+    downlader: &dyn StaplesDownloader,
+    options: ExportOptions,
+) -> Box<dyn State> {
     let result = downlader.download_staples();
     match result {
         Ok(DownloadStappleOk { warnings }) => AskingPath_ {
             warnings,
             design_id: 0,
             warning_ack: None,
+            options,
         }
         .to_state(),
         Err(DownloadStappleError::NoScaffoldSet) => TransitionMessage::new(
@@ -83,6 +220,11 @@ fn get_design_providing_staples(downlader: &dyn StaplesDownloader) -> Box<dyn St
             rfd::MessageLevel::Error,
             Box::new(NormalState),
         ),
+        Err(DownloadStappleError::ScaffoldNotSingleCycle(breaks)) => TransitionMessage::new(
+            messages::scaffold_not_single_cycle_msg(&breaks),
+            rfd::MessageLevel::Error,
+            Box::new(NormalState),
+        ),
         Err(DownloadStappleError::SeveralDesignNoneSelected) => TransitionMessage::new(
             messages::NO_DESIGN_SELECTED,
             rfd::MessageLevel::Error,
@@ -105,21 +247,64 @@ fn ask_path<P: AsRef<Path>>(
     if let Some(msg) = state.warnings.pop() {
         let must_ack = dialog::blocking_message(msg.into(), rfd::MessageLevel::Warning);
         state.with_ack(must_ack)
-    } else {
-        let path_input = dialog::save("xlsx", starting_diectory, None);
+    } else if state.options.pools.is_none() {
+        let question = dialog::yes_no_dialog(messages::PLATE_SIZE_QUESTION.into());
         Box::new(DownloadStaples {
-            step: Step::PathAsked {
-                path_input,
+            step: Step::AskingPlateSize {
+                question,
                 design_id: state.design_id,
+                options: state.options,
+            },
+        })
+    } else {
+        path_asked(state.design_id, state.options, starting_diectory)
+    }
+}
+
+fn poll_plate_size<P: AsRef<Path>>(
+    question: YesNoQuestion,
+    design_id: usize,
+    mut options: ExportOptions,
+    starting_diectory: Option<P>,
+) -> Box<DownloadStaples> {
+    if let Some(use_384_wells) = question.answer() {
+        options.plate_size = if use_384_wells {
+            PlateSize::Wells384
+        } else {
+            PlateSize::Wells96
+        };
+        path_asked(design_id, options, starting_diectory)
+    } else {
+        Box::new(DownloadStaples {
+            step: Step::AskingPlateSize {
+                question,
+                design_id,
+                options,
             },
         })
     }
 }
 
+fn path_asked<P: AsRef<Path>>(
+    design_id: usize,
+    options: ExportOptions,
+    starting_diectory: Option<P>,
+) -> Box<DownloadStaples> {
+    let path_input = dialog::save(options.extension(), starting_diectory, None);
+    Box::new(DownloadStaples {
+        step: Step::PathAsked {
+            path_input,
+            design_id,
+            options,
+        },
+    })
+}
+
 struct AskingPath_ {
     warnings: Vec<String>,
     design_id: usize,
     warning_ack: Option<MustAckMessage>,
+    options: ExportOptions,
 }
 
 impl AskingPath_ {
@@ -135,11 +320,15 @@ impl AskingPath_ {
     }
 }
 
-fn poll_path(path_input: PathInput, design_id: usize) -> Box<dyn State> {
+fn poll_path(path_input: PathInput, design_id: usize, options: ExportOptions) -> Box<dyn State> {
     if let Some(result) = path_input.get() {
         if let Some(path) = result {
             Box::new(DownloadStaples {
-                step: Step::Downloading { path, design_id },
+                step: Step::Downloading {
+                    path,
+                    design_id,
+                    options,
+                },
             })
         } else {
             TransitionMessage::new(
@@ -153,24 +342,51 @@ fn poll_path(path_input: PathInput, design_id: usize) -> Box<dyn State> {
             step: Step::PathAsked {
                 path_input,
                 design_id,
+                options,
             },
         })
     }
 }
 
-fn download_staples(
-    downlader: &dyn StaplesDownloader,
-    _design_id: usize,
+/// Start writing the staples in the file at `path`
+fn staple_export_job(
+    downloader: Box<dyn StaplesDownloader>,
     path: PathBuf,
-) -> Box<dyn State> {
-    downlader.write_staples_xlsx(&path);
-    let msg = messages::successfull_staples_export_msg(&path);
-    TransitionMessage::new(msg, rfd::MessageLevel::Error, Box::new(NormalState))
+    options: ExportOptions,
+) -> BackgroundJob<JobOutput> {
+    BackgroundJob::spawn(move |_| {
+        if let Some(n_steps) = options.pools {
+            downloader.write_staple_pools_xlsx(&path, n_steps);
+        } else if options.csv {
+            downloader.write_staples_csv(&path, options.plate_size, options.order_format)?;
+        } else {
+            downloader.write_staples_xlsx(&path, options.plate_size, options.order_format);
+        }
+        Ok(JobOutput::StapleExport(path))
+    })
 }
 
-pub trait StaplesDownloader {
+/// An object that can write the staples of the design. The staples are written by a background
+/// job, to which the downloader is sent.
+pub trait StaplesDownloader: Send {
     fn download_staples(&self) -> Result<DownloadStappleOk, DownloadStappleError>;
-    fn write_staples_xlsx(&self, xlsx_path: &PathBuf);
+    /// Write the staples sorted by plates of size `plate_size`, one sheet per plate
+    fn write_staples_xlsx(
+        &self,
+        xlsx_path: &PathBuf,
+        plate_size: PlateSize,
+        order_format: OrderFormat,
+    );
+    /// Write the staples sorted by plates of size `plate_size` in a single csv table
+    fn write_staples_csv(
+        &self,
+        csv_path: &PathBuf,
+        plate_size: PlateSize,
+        order_format: OrderFormat,
+    ) -> std::io::Result<()>;
+    /// Write the staples partitioned in `n_steps` pools of decreasing melting temperature, one
+    /// sheet per pool
+    fn write_staple_pools_xlsx(&self, xlsx_path: &PathBuf, n_steps: usize);
     fn default_shift(&self) -> Option<usize>;
 }
 
@@ -182,8 +398,34 @@ pub enum DownloadStappleError {
     NoScaffoldSet,
     /// There is no sequence set for the scaffold
     ScaffoldSequenceNotSet,
+    /// The scaffold is interrupted at the given points
+    ScaffoldNotSingleCycle(Vec<ScaffoldBreak>),
 }
 
 pub struct DownloadStappleOk {
     pub warnings: Vec<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn well_positions_96() {
+        let plate_size = PlateSize::Wells96;
+        assert_eq!(plate_size.well_position(0), (1, "A1".to_owned()));
+        assert_eq!(plate_size.well_position(7), (1, "H1".to_owned()));
+        assert_eq!(plate_size.well_position(8), (1, "A2".to_owned()));
+        assert_eq!(plate_size.well_position(95), (1, "H12".to_owned()));
+        assert_eq!(plate_size.well_position(96), (2, "A1".to_owned()));
+    }
+
+    #[test]
+    fn well_positions_384() {
+        let plate_size = PlateSize::Wells384;
+        assert_eq!(plate_size.well_position(15), (1, "P1".to_owned()));
+        assert_eq!(plate_size.well_position(16), (1, "A2".to_owned()));
+        assert_eq!(plate_size.well_position(383), (1, "P24".to_owned()));
+        assert_eq!(plate_size.well_position(384), (2, "A1".to_owned()));
+    }
+}
@@ -0,0 +1,209 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! This module defines the `JobManager` struct which keeps track of the background jobs that are
+//! running, so that their progress can be displayed in the status bar and that they can be
+//! canceled by the user.
+
+use super::background_job::{BackgroundJob, JobError};
+use super::relaxation::RelaxationResult;
+use super::secondary_structure::SecondaryStructure;
+use crate::app_state::ShiftOptimizationOk;
+use crate::gui::JobProgress;
+use crate::plugins::PluginOutput;
+use ensnano_interactor::Trajectory;
+use std::path::PathBuf;
+
+/// The long operations that can run in the background
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobKind {
+    ScaffoldShiftOptimization,
+    Relaxation,
+    Plugin,
+    SecondaryStructure,
+    AnimationRecording,
+    TrajectoryLoading,
+    StapleExport,
+}
+
+impl JobKind {
+    /// The name of the job, as displayed in the status bar
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::ScaffoldShiftOptimization => "Optimizing scaffold position",
+            Self::Relaxation => "Relaxing with oxDNA",
+            Self::Plugin => "Running plugins",
+            Self::SecondaryStructure => "Predicting secondary structure",
+            Self::AnimationRecording => "Recording animation",
+            Self::TrajectoryLoading => "Reading trajectory",
+            Self::StapleExport => "Exporting staples",
+        }
+    }
+}
+
+/// The value computed by a background job
+pub enum JobOutput {
+    ScaffoldShift(ShiftOptimizationOk),
+    Relaxation(RelaxationResult),
+    Plugin(PluginOutput),
+    SecondaryStructure(SecondaryStructure),
+    /// The number of frames of an animation that were written, and the directory containing them
+    Animation {
+        nb_frames: u32,
+        directory: PathBuf,
+    },
+    Trajectory(Trajectory),
+    /// The path of the file in which the staples were written
+    StapleExport(PathBuf),
+}
+
+/// A job that is over, successfully or not
+pub struct FinishedJob {
+    pub kind: JobKind,
+    pub result: Result<JobOutput, JobError>,
+}
+
+struct RunningJob {
+    id: usize,
+    kind: JobKind,
+    progress: f32,
+    handle: BackgroundJob<JobOutput>,
+}
+
+#[derive(Default)]
+pub struct JobManager {
+    jobs: Vec<RunningJob>,
+    next_id: usize,
+    /// True if the progress of the jobs changed since the last call to `poll_progress`
+    progress_changed: bool,
+}
+
+impl JobManager {
+    /// Keep track of `job` until it is over and return its identifier
+    pub fn attach(&mut self, kind: JobKind, job: BackgroundJob<JobOutput>) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.jobs.push(RunningJob {
+            id,
+            kind,
+            progress: 0.,
+            handle: job,
+        });
+        self.progress_changed = true;
+        id
+    }
+
+    pub fn is_running(&self, kind: JobKind) -> bool {
+        self.jobs.iter().any(|job| job.kind == kind)
+    }
+
+    /// Ask the job `id`, if it is still running, to stop
+    pub fn cancel(&self, id: usize) {
+        if let Some(job) = self.jobs.iter().find(|job| job.id == id) {
+            job.handle.cancel()
+        }
+    }
+
+    /// Stop following the jobs that are over and return their result
+    pub fn poll_finished(&mut self) -> Vec<FinishedJob> {
+        let mut finished = Vec::new();
+        self.jobs.retain(|job| {
+            if let Some(result) = job.handle.get_result() {
+                finished.push(FinishedJob {
+                    kind: job.kind,
+                    result,
+                });
+                false
+            } else {
+                true
+            }
+        });
+        if !finished.is_empty() {
+            self.progress_changed = true;
+        }
+        finished
+    }
+
+    /// Return the progress of all the running jobs if it changed since the last call to this
+    /// method
+    pub fn poll_progress(&mut self) -> Option<Vec<JobProgress>> {
+        for job in self.jobs.iter_mut() {
+            if let Some(progress) = job.handle.get_progress() {
+                job.progress = progress;
+                self.progress_changed = true;
+            }
+        }
+        if self.progress_changed {
+            self.progress_changed = false;
+            Some(
+                self.jobs
+                    .iter()
+                    .map(|job| JobProgress {
+                        id: job.id,
+                        name: job.kind.name().to_string(),
+                        progress: job.progress,
+                    })
+                    .collect(),
+            )
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn wait_for_end(manager: &mut JobManager) -> Vec<FinishedJob> {
+        loop {
+            let finished = manager.poll_finished();
+            if !finished.is_empty() {
+                return finished;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn canceled_job_is_removed() {
+        let mut manager = JobManager::default();
+        let id = manager.attach(
+            JobKind::Relaxation,
+            BackgroundJob::spawn(|context| loop {
+                if context.is_canceled() {
+                    return Err(JobError::Canceled);
+                }
+                std::thread::sleep(Duration::from_millis(10));
+            }),
+        );
+        assert!(manager.is_running(JobKind::Relaxation));
+        let progress = manager.poll_progress().unwrap();
+        assert_eq!(progress.len(), 1);
+        assert_eq!(progress[0].id, id);
+
+        manager.cancel(id);
+        let finished = wait_for_end(&mut manager);
+        assert_eq!(finished[0].kind, JobKind::Relaxation);
+        assert!(matches!(finished[0].result, Err(JobError::Canceled)));
+        assert!(!manager.is_running(JobKind::Relaxation));
+        assert_eq!(manager.poll_progress().map(|p| p.len()), Some(0));
+        assert!(manager.poll_progress().is_none());
+    }
+}
@@ -16,12 +16,28 @@ ENSnano, a 3d graphical application for DNA nanostructures.
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+use crate::app_state::ErrOperation;
+use crate::key_bindings::HotkeyAction;
+use ensnano_design::{Orphans, ScaffoldBreak};
 use std::path::Path;
 pub const NO_FILE_RECIEVED_LOAD: &'static str = "Open canceled";
 pub const NO_FILE_RECIEVED_SAVE: &'static str = "Save canceled";
 pub const NO_FILE_RECIEVED_OXDNA: &'static str = "OxDNA export canceled";
+pub const NO_FILE_RECIEVED_CADNANO: &'static str = "Cadnano export canceled";
+pub const NO_FILE_RECIEVED_PDB: &'static str = "PDB export canceled";
+pub const NO_FILE_RECIEVED_TRAJECTORY: &'static str = "Trajectory loading canceled";
+pub const NO_FILE_RECIEVED_GRAPH: &'static str = "Graph export canceled";
+pub const NO_FILE_RECIEVED_HELIX_MAP: &'static str = "Helix map export canceled";
+pub const NO_FILE_RECIEVED_SCREENSHOT: &'static str = "Screenshot canceled";
+pub const NO_FILE_RECIEVED_ANIMATION: &'static str = "Animation recording canceled";
+pub const NO_FILE_RECIEVED_MESH: &'static str = "Mesh export canceled";
 pub const NO_FILE_RECIEVED_SCAFFOLD: &'static str = "Scaffold setting canceled";
 pub const NO_FILE_RECIEVED_STAPPLE: &'static str = "Staple export canceled";
+pub const NO_FILE_RECIEVED_CAMERA_VIEW: &'static str = "View export/import canceled";
+pub const NO_FILE_RECIEVED_MACRO: &'static str = "Macro saving/playing canceled";
+pub const NO_FILE_RECIEVED_SESSION_LOG: &'static str = "Session replay canceled";
+pub const NO_FILE_RECIEVED_MODULE: &'static str = "Module export/import canceled";
+pub const NO_FILE_RECIEVED_PALETTE: &'static str = "Color palette export/import canceled";
 
 pub fn succesfull_oxdna_export_msg<P: AsRef<Path>>(config: P, topo: P) -> String {
     format!(
@@ -37,6 +53,31 @@ pub fn failed_to_save_msg<D: std::fmt::Debug>(reason: &D) -> String {
     format!("Failed to save {:?}", reason)
 }
 
+pub fn failed_operation_msg(err: &ErrOperation) -> String {
+    match err {
+        ErrOperation::EndsTooFarApart => format!(
+            "The strand ends are too far apart to be ligated.\n\
+            They must be at most {} nm from each other.",
+            crate::consts::MAX_LIGATION_DISTANCE
+        ),
+        ErrOperation::InvalidTorusKnot => String::from(
+            "The torus knot could not be created.\n\
+            Its winding numbers must be positive and its radius larger than its tube radius.",
+        ),
+        err => format!("The operation could not be applied: {:?}", err),
+    }
+}
+
+pub fn failed_to_merge_design_msg<D: std::fmt::Debug>(reason: &D) -> String {
+    format!("Failed to merge the design: {:?}", reason)
+}
+
+pub const RELAXATION_ALREADY_RUNNING: &'static str = "A relaxation is already running";
+
+pub fn failed_to_start_relaxation_msg<D: std::fmt::Debug>(reason: &D) -> String {
+    format!("Failed to start the relaxation {:?}", reason)
+}
+
 pub const NO_SCAFFOLD_SET: &'static str = "No scaffold set. \n
                     Chose a strand and set it as the scaffold by checking the scaffold checkbox\
                     in the status bar";
@@ -44,12 +85,72 @@ pub const NO_SCAFFOLD_SET: &'static str = "No scaffold set. \n
 pub const NO_SCAFFOLD_SEQUENCE_SET: &'static str = "No sequence uploaded for scaffold. \n
                 Upload a sequence for the scaffold by pressing the \"Load scaffold\" button";
 
+pub const PLATE_SIZE_QUESTION: &'static str =
+    "Sort the staples in 384-well plates?\n(Otherwise 96-well plates are used)";
+
+/// The maximum number of scaffold breaks listed in an error message
+const MAX_SCAFFOLD_BREAKS_DISPLAYED: usize = 10;
+
+pub fn scaffold_not_single_cycle_msg(breaks: &[ScaffoldBreak]) -> String {
+    let mut ret = String::from(
+        "The scaffold does not form a single cycle. \n
+        Its backbone is interrupted between the following nucleotides (helix, position, forward):",
+    );
+    push_scaffold_breaks(&mut ret, breaks);
+    ret
+}
+
+pub fn scaffold_still_broken_msg(breaks: &[ScaffoldBreak]) -> String {
+    let mut ret = String::from(
+        "The scaffold was opened but it is still disconnected. \n
+        Its backbone is interrupted between the following nucleotides (helix, position, forward):",
+    );
+    push_scaffold_breaks(&mut ret, breaks);
+    ret
+}
+
+pub const NO_ORPHANS: &'static str = "There is no empty helix or strand to remove";
+
+pub fn removed_orphans_msg(orphans: &Orphans) -> String {
+    format!(
+        "Removed {} empty helices and {} empty strands",
+        orphans.helices.len(),
+        orphans.strands.len()
+    )
+}
+
+fn push_scaffold_breaks(ret: &mut String, breaks: &[ScaffoldBreak]) {
+    for b in breaks.iter().take(MAX_SCAFFOLD_BREAKS_DISPLAYED) {
+        ret.push_str(&format!("\n{} -> {}", b.prime3, b.prime5));
+    }
+    if breaks.len() > MAX_SCAFFOLD_BREAKS_DISPLAYED {
+        ret.push_str(&format!(
+            "\n... and {} more",
+            breaks.len() - MAX_SCAFFOLD_BREAKS_DISPLAYED
+        ));
+    }
+}
+
 pub const NO_DESIGN_SELECTED: &'static str =
     "No design selected, select a design by selecting one of its elements";
 
-pub fn successfull_staples_export_msg<P: AsRef<Path>>(file: P) -> String {
+pub fn successfull_export_msg<P: AsRef<Path>>(file: P) -> String {
+    format!(
+        "Successfully exported to {}",
+        file.as_ref().to_string_lossy()
+    )
+}
+
+pub fn successfull_camera_view_export_msg<P: AsRef<Path>>(file: P) -> String {
+    format!(
+        "Successfully wrote view in {}",
+        file.as_ref().to_string_lossy()
+    )
+}
+
+pub fn successfull_color_palette_export_msg<P: AsRef<Path>>(file: P) -> String {
     format!(
-        "Successfully wrote staples in {}",
+        "Successfully wrote color palette in {}",
         file.as_ref().to_string_lossy()
     )
 }
@@ -89,6 +190,7 @@ pub const DESIGN_FILTERS: Filters = &[
             crate::consts::ENS_BACKUP_EXTENSION,
             "json",
             "sc",
+            "dna",
         ],
     ),
     (
@@ -99,7 +201,57 @@ pub const DESIGN_FILTERS: Filters = &[
         ],
     ),
     ("json files", &["json"]),
-    ("scadnano files", &["sc"]),
+    ("scadnano files", crate::consts::SCADNANO_EXTENSIONS),
 ];
 
 pub const SEQUENCE_FILTERS: Filters = &[("Text files", &["txt"])];
+
+pub const CAMERA_VIEW_FILTERS: Filters = &[("json files", &["json"])];
+
+pub const COLOR_PALETTE_FILTERS: Filters = &[("json files", &["json"])];
+
+pub const MACRO_FILTERS: Filters = &[("json files", &["json"])];
+
+pub const SESSION_LOG_FILTERS: Filters = &[("session logs", &["log"])];
+
+pub const OXDNA_TOPOLOGY_FILTERS: Filters = &[("oxDNA topology", &["top"])];
+
+pub const OXDNA_CONFIGURATION_FILTERS: Filters = &[(
+    "oxDNA configurations and trajectories",
+    &["oxdna", "conf", "dat"],
+)];
+
+pub fn shortest_path_msg(length: usize) -> String {
+    format!(
+        "The shortest path between the selected nucleotides has {} nucleotides",
+        length
+    )
+}
+
+pub const NO_PATH_BETWEEN_NUCLEOTIDES: &'static str =
+    "There is no path between the selected nucleotides";
+
+pub const SECONDARY_STRUCTURE_ALREADY_RUNNING: &'static str =
+    "A secondary structure prediction is already running";
+
+pub const NO_HELIX_TO_DOCK_ON: &'static str =
+    "Select a helix of the current design to dock the design on";
+
+pub const NO_XOVER_BETWEEN_NUCLEOTIDES: &'static str =
+    "There is no cross-over between these nucleotides";
+
+pub fn key_binding_conflict_msg(action: HotkeyAction, unbound: HotkeyAction) -> String {
+    format!(
+        "This key combination was already bound to \"{}\".\n\
+        It is now bound to \"{}\" and \"{}\" lost this binding.",
+        unbound, action, unbound
+    )
+}
+
+pub fn strand_too_short_msg(length: usize, min_length: usize) -> String {
+    format!(
+        "Strand too short.\n\
+        The strand has {} nucleotides but the minimum strand length is {}.",
+        length, min_length
+    )
+}
@@ -17,9 +17,13 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 */
 
 use super::*;
+use crate::app_state::ErrOperation;
 use ensnano_design::group_attributes::GroupPivot;
-use ensnano_design::Nucl;
-use ensnano_interactor::{graphics::FogParameters, HyperboloidOperation};
+use ensnano_design::{Nucl, ScaffoldContinuityResult};
+use ensnano_interactor::{
+    graphics::{AnimationParameters, ColorTheme, FogParameters},
+    HyperboloidOperation, WorkspaceTransform,
+};
 
 /// User is interacting with graphical components.
 pub(super) struct NormalState;
@@ -40,7 +44,15 @@ impl State for NormalState {
                         save_as()
                     }
                 }
-                Action::DownloadStaplesRequest => Box::new(DownloadStaples::default()),
+                Action::DownloadStaplesRequest(order_format) => {
+                    Box::new(DownloadStaples::plates(order_format, false))
+                }
+                Action::DownloadStaplesCsvRequest(order_format) => {
+                    Box::new(DownloadStaples::plates(order_format, true))
+                }
+                Action::DownloadStaplePoolsRequest(n_steps) => {
+                    Box::new(DownloadStaples::pools(n_steps))
+                }
                 Action::SetScaffoldSequence { shift } => Box::new(SetScaffoldSequence::init(shift)),
                 Action::Exit => Quit::quit(main_state.need_save()),
                 Action::ToggleSplit(mode) => {
@@ -48,10 +60,59 @@ impl State for NormalState {
                     self
                 }
                 Action::OxDnaExport => oxdna_export(),
+                Action::PdbExport => Box::new(PdbExport::default()),
+                Action::CadnanoExport => Box::new(CadnanoExport::default()),
+                Action::ConnectivityGraphExport => Box::new(ConnectivityGraphExport::default()),
+                Action::ExportMesh => Box::new(MeshExport::default()),
+                Action::ExportHelixMapPng { dpi, size_mm } => {
+                    Box::new(HelixMapExport::new(dpi, size_mm))
+                }
+                Action::ExportScenePng { size } => Box::new(ScenePngExport::new(size)),
+                Action::RecordAnimation(parameters) => {
+                    Box::new(AnimationRecording::new(parameters))
+                }
+                Action::ExportCameraView => Box::new(ExportCameraView::default()),
+                Action::ImportCameraView => Box::new(ImportCameraView::default()),
+                Action::ExportSelectionAsModule => {
+                    if let Some((_, helices)) = ensnano_interactor::list_of_helices(
+                        main_state.get_selection().as_ref().as_ref(),
+                    ) {
+                        Box::new(ExportModule::new(helices))
+                    } else {
+                        self
+                    }
+                }
+                Action::ImportModule => Box::new(ImportModule::default()),
+                Action::AddPaletteColor { name, color } => {
+                    main_state.add_palette_color(name, color);
+                    self
+                }
+                Action::SetColorTheme(theme) => {
+                    main_state.set_color_theme(theme);
+                    self
+                }
+                Action::SetEditHistorySize(size) => {
+                    main_state.set_edit_history_size(size);
+                    self
+                }
+                Action::SetSessionLogEnabled(enabled) => {
+                    main_state.set_session_log_enabled(enabled);
+                    self
+                }
+                Action::ExportColorPalette => Box::new(ExportColorPalette::default()),
+                Action::ImportColorPalette => Box::new(ImportColorPalette::default()),
                 Action::CloseOverlay(_) | Action::OpenOverlay(_) => {
                     println!("unexpected action");
                     self
                 }
+                Action::OpenContextMenu => {
+                    main_state.open_context_menu();
+                    self
+                }
+                Action::CloseContextMenu => {
+                    main_state.close_context_menu();
+                    self
+                }
                 Action::ChangeUiSize(size) => {
                     main_state.change_ui_size(size);
                     self
@@ -60,13 +121,55 @@ impl State for NormalState {
                     main_state.invert_scroll_y(inverted);
                     self
                 }
+                Action::RecordKeyBinding { action, previous } => {
+                    main_state.record_key_binding(action, previous);
+                    self
+                }
+                Action::CancelKeyBindingRecording => {
+                    main_state.cancel_key_binding_recording();
+                    self
+                }
+                Action::KeyBindingsChanged { bindings, conflict } => {
+                    main_state.update_key_bindings(bindings);
+                    if let Some((action, unbound)) = conflict {
+                        TransitionMessage::new(
+                            messages::key_binding_conflict_msg(action, unbound),
+                            rfd::MessageLevel::Warning,
+                            Box::new(NormalState),
+                        )
+                    } else {
+                        self
+                    }
+                }
+                Action::PluginMenuAction { plugin_id, name } => {
+                    main_state.trigger_plugin_action(plugin_id, name);
+                    self
+                }
                 Action::ErrorMsg(msg) => {
                     TransitionMessage::new(msg, rfd::MessageLevel::Error, Box::new(NormalState))
                 }
+                Action::InfoMsg(msg) => {
+                    TransitionMessage::new(msg, rfd::MessageLevel::Info, Box::new(NormalState))
+                }
                 Action::DesignOperation(op) => {
                     main_state.apply_operation(op);
                     self.make_progress(main_state)
                 }
+                Action::TryDesignOperation(op) => {
+                    if let Err(err) = main_state.try_apply_operation(op) {
+                        TransitionMessage::new(
+                            messages::failed_operation_msg(&err),
+                            rfd::MessageLevel::Error,
+                            Box::new(NormalState),
+                        )
+                    } else {
+                        self.make_progress(main_state)
+                    }
+                }
+                Action::DesignOperations(ops) => {
+                    main_state.apply_operations(ops);
+                    self.make_progress(main_state)
+                }
                 Action::SilentDesignOperation(op) => {
                     main_state.apply_silent_operation(op);
                     self.make_progress(main_state)
@@ -85,10 +188,7 @@ impl State for NormalState {
                 }
                 Action::TurnSelectionIntoGrid => self.turn_selection_into_grid(main_state),
                 Action::AddGrid(descr) => self.add_grid(main_state, descr),
-                Action::ChangeSequence(_) => {
-                    println!("Sequence input is not yet implemented");
-                    self
-                }
+                Action::ChangeSequence(sequence) => self.change_sequence(main_state, sequence),
                 Action::ChangeColorStrand(color) => self.change_color(main_state, color),
                 Action::FinishChangingColor => {
                     main_state.finish_operation();
@@ -102,8 +202,18 @@ impl State for NormalState {
                 Action::LoadDesign(None) => Load::load(main_state.need_save()),
                 Action::SuspendOp => {
                     log::info!("Suspending operation");
+                    let rejection = main_state.reject_short_built_strands();
                     main_state.finish_operation();
-                    self
+                    if let Err(ErrOperation::StrandTooShort { length, min_length }) = rejection {
+                        log::info!("Rejecting strand of length {}", length);
+                        TransitionMessage::new(
+                            messages::strand_too_short_msg(length, min_length),
+                            rfd::MessageLevel::Warning,
+                            Box::new(NormalState),
+                        )
+                    } else {
+                        self
+                    }
                 }
                 Action::Copy => {
                     main_state.request_copy();
@@ -134,6 +244,71 @@ impl State for NormalState {
                     main_state.scaffold_to_selection();
                     self
                 }
+                Action::HighlightShortestPath => {
+                    let msg = if let Some(length) = main_state.highlight_shortest_path() {
+                        messages::shortest_path_msg(length)
+                    } else {
+                        messages::NO_PATH_BETWEEN_NUCLEOTIDES.to_string()
+                    };
+                    TransitionMessage::new(msg, rfd::MessageLevel::Info, Box::new(NormalState))
+                }
+                Action::PredictSecondaryStructure(s_id) => {
+                    if main_state.is_job_running(JobKind::SecondaryStructure) {
+                        TransitionMessage::new(
+                            messages::SECONDARY_STRUCTURE_ALREADY_RUNNING,
+                            rfd::MessageLevel::Error,
+                            Box::new(NormalState),
+                        )
+                    } else if let Err(err) = main_state.predict_secondary_structure(s_id) {
+                        TransitionMessage::new(
+                            err.to_string(),
+                            rfd::MessageLevel::Error,
+                            Box::new(NormalState),
+                        )
+                    } else {
+                        self
+                    }
+                }
+                Action::BreakScaffoldAt(at) => match main_state.break_scaffold_at(at) {
+                    Ok(ScaffoldContinuityResult::Broken(breaks)) => TransitionMessage::new(
+                        messages::scaffold_still_broken_msg(&breaks),
+                        rfd::MessageLevel::Warning,
+                        Box::new(NormalState),
+                    ),
+                    Ok(_) => self,
+                    Err(err) => {
+                        log::warn!("Could not open the scaffold: {:?}", err);
+                        self
+                    }
+                },
+                Action::CleanupOrphans => {
+                    let orphans = main_state.get_design_reader().get_orphans();
+                    if orphans.is_empty() {
+                        TransitionMessage::new(
+                            messages::NO_ORPHANS,
+                            rfd::MessageLevel::Info,
+                            Box::new(NormalState),
+                        )
+                    } else {
+                        main_state.apply_operation(DesignOperation::CleanupOrphans);
+                        TransitionMessage::new(
+                            messages::removed_orphans_msg(&orphans),
+                            rfd::MessageLevel::Info,
+                            Box::new(NormalState),
+                        )
+                    }
+                }
+                Action::GoToXover(a, b) => {
+                    if main_state.go_to_xover(a, b) {
+                        self
+                    } else {
+                        TransitionMessage::new(
+                            messages::NO_XOVER_BETWEEN_NUCLEOTIDES,
+                            rfd::MessageLevel::Warning,
+                            Box::new(NormalState),
+                        )
+                    }
+                }
                 Action::NewHyperboloid(request) => {
                     if let Some((position, orientation)) = main_state.get_grid_creation_position() {
                         main_state.apply_operation(DesignOperation::HyperboloidOperation(
@@ -174,6 +349,10 @@ impl State for NormalState {
                     main_state.start_roll_simulation(request.target_helices);
                     self
                 }
+                Action::OptimizeRolls(target_helices) => {
+                    main_state.optimize_rolls(target_helices);
+                    self
+                }
                 Action::Fog(fog) => {
                     main_state.notify_apps(Notification::Fog(fog));
                     self
@@ -194,6 +373,86 @@ impl State for NormalState {
                     main_state.clear_visibility_sieve();
                     self
                 }
+                Action::AddMeasurement(measurement) => {
+                    main_state.add_measurement(measurement);
+                    self
+                }
+                Action::ClearMeasurements => {
+                    main_state.clear_measurements();
+                    self
+                }
+                Action::LoadOxDnaTrajectory => Box::new(LoadOxDnaTrajectory::default()),
+                Action::PlayTrajectory(playing) => {
+                    main_state.play_trajectory(playing);
+                    self
+                }
+                Action::SetTrajectoryFrame(frame) => {
+                    main_state.set_trajectory_frame(frame);
+                    self
+                }
+                Action::CloseTrajectory => {
+                    main_state.close_trajectory();
+                    self
+                }
+                Action::OpenDesignInWorkspace => Box::new(OpenInWorkspace::default()),
+                Action::SetMainDesignVisibility(visible) => {
+                    main_state.set_main_design_visibility(visible);
+                    self
+                }
+                Action::SetWorkspaceDesignVisibility { design_id, visible } => {
+                    main_state.set_workspace_design_visibility(design_id, visible);
+                    self
+                }
+                Action::SetWorkspaceDesignTransform {
+                    design_id,
+                    transform,
+                } => {
+                    main_state.set_workspace_design_transform(design_id, transform);
+                    self
+                }
+                Action::CloseWorkspaceDesign(design_id) => {
+                    main_state.close_workspace_design(design_id);
+                    self
+                }
+                Action::MergeWorkspaceDesign(design_id) => {
+                    if let Err(err) = main_state.merge_workspace_design(design_id) {
+                        TransitionMessage::new(
+                            messages::failed_to_merge_design_msg(&err),
+                            rfd::MessageLevel::Error,
+                            Box::new(NormalState),
+                        )
+                    } else {
+                        self
+                    }
+                }
+                Action::DockWorkspaceDesign { design_id, helix } => {
+                    if main_state.dock_workspace_design(design_id, helix) {
+                        self
+                    } else {
+                        TransitionMessage::new(
+                            messages::NO_HELIX_TO_DOCK_ON,
+                            rfd::MessageLevel::Warning,
+                            Box::new(NormalState),
+                        )
+                    }
+                }
+                Action::RelaxDesign => start_relaxation(main_state),
+                Action::CancelJob(job_id) => {
+                    main_state.cancel_job(job_id);
+                    self
+                }
+                Action::SaveSelectionGroup(name) => {
+                    main_state.save_selection_group(name);
+                    self
+                }
+                Action::RecallSelectionGroup { name, combination } => {
+                    main_state.recall_selection_group(name, combination);
+                    self
+                }
+                Action::DeleteSelectionGroup(name) => {
+                    main_state.delete_selection_group(name);
+                    self
+                }
                 Action::ReloadFile => {
                     if let Some(path) = main_state.get_current_file_name() {
                         Load::init_reolad(main_state.need_save(), path.to_path_buf())
@@ -234,6 +493,23 @@ impl State for NormalState {
                     main_state.flip_split_views();
                     self
                 }
+                Action::Start2dXoverTool => {
+                    main_state.start_2d_xover_tool();
+                    self
+                }
+                Action::StartRecording => {
+                    main_state.start_recording_macro();
+                    self
+                }
+                Action::StopRecording => {
+                    if let Some(recorded) = main_state.stop_recording_macro() {
+                        Box::new(SaveMacro::new(recorded))
+                    } else {
+                        self
+                    }
+                }
+                Action::PlayMacro => Box::new(PlayMacro::default()),
+                Action::ReplaySession => Box::new(ReplaySession::default()),
                 action => {
                     println!("Not implemented {:?}", action);
                     self
@@ -269,6 +545,7 @@ impl NormalState {
                 position,
                 orientation,
                 invisible: false,
+                twist_offset: 0.,
             }))
         } else {
             println!("Could not get position and orientation for new grid");
@@ -284,6 +561,23 @@ impl NormalState {
         self
     }
 
+    fn change_sequence(
+        self: Box<Self>,
+        main_state: &mut dyn MainState,
+        sequence: String,
+    ) -> Box<Self> {
+        let strands = ensnano_interactor::extract_strands_from_selection(
+            main_state.get_selection().as_ref().as_ref(),
+        );
+        if let [s_id] = strands.as_slice() {
+            main_state.apply_operation(DesignOperation::SetStrandSequence {
+                s_id: *s_id,
+                sequence,
+            });
+        }
+        self
+    }
+
     fn toggle_small_spheres(
         self: Box<Self>,
         main_state: &mut dyn MainState,
@@ -348,9 +642,11 @@ fn oxdna_export() -> Box<dyn State> {
 
 use ensnano_design::grid::{GridDescriptor, GridTypeDescr};
 
+use crate::key_bindings::{HotkeyAction, KeyBindings, KeyCombination};
 use ensnano_interactor::HyperboloidRequest;
 use ensnano_interactor::{
-    application::Notification, DesignOperation, RigidBodyConstants, RollRequest,
+    application::Notification, DesignOperation, Measurement, OrderFormat, RigidBodyConstants,
+    RollRequest, SelectionGroupCombination,
 };
 /// An action to be performed at the end of an event loop iteration, and that will have an effect
 /// on the main application state, e.g. Closing the window, or toggling between 3D/2D views.
@@ -360,7 +656,12 @@ pub enum Action {
     NewDesign,
     SaveAs,
     QuickSave,
-    DownloadStaplesRequest,
+    /// Download the staples sorted by plates as an xlsx file
+    DownloadStaplesRequest(OrderFormat),
+    /// Download the staples sorted by plates as a csv file
+    DownloadStaplesCsvRequest(OrderFormat),
+    /// Download the staples partitioned in pools of decreasing melting temperature
+    DownloadStaplePoolsRequest(usize),
     /// Trigger the sequence of action that will set the scaffold of the sequence.
     SetScaffoldSequence {
         shift: usize,
@@ -368,12 +669,81 @@ pub enum Action {
     Exit,
     ToggleSplit(SplitMode),
     OxDnaExport,
+    /// Write a coarse atomic model of the design in the PDB format
+    PdbExport,
+    /// Write the design in the cadnano v2 format
+    CadnanoExport,
+    /// Write the graph of the helices connected by cross-overs in json or DOT format
+    ConnectivityGraphExport,
+    /// Write the spheres and tubes of the 3D view as a triangle mesh in the OBJ format
+    ExportMesh,
+    /// Write a PNG image of the 2D view, measuring `size_mm` millimeters at `dpi` dots per inch
+    ExportHelixMapPng {
+        dpi: u32,
+        size_mm: (f32, f32),
+    },
+    /// Write a PNG image of `size` pixels of the 3D view, as seen from its current camera
+    ExportScenePng {
+        size: (u32, u32),
+    },
+    /// Write the frames of an animation of the 3D view as numbered PNG images
+    RecordAnimation(AnimationParameters),
+    /// Write the current point of view of the 3D scene in a json file
+    ExportCameraView,
+    /// Move the camera of the 3D scene to a point of view read from a json file
+    ImportCameraView,
+    /// Write the selected helices, with the strands that lie on them, in a design file
+    ExportSelectionAsModule,
+    /// Add the content of a design file to the current design
+    ImportModule,
+    /// Add a named color to the palette of the user
+    AddPaletteColor {
+        name: String,
+        color: u32,
+    },
+    /// Save the colors of the 3D view chosen by the user and apply them
+    SetColorTheme(ColorTheme),
+    /// Set and save the number of undoable states embedded in the saved files
+    SetEditHistorySize(usize),
+    /// Start or stop logging the design operations, and save this choice
+    SetSessionLogEnabled(bool),
+    /// Write the palette of named colors in a json file
+    ExportColorPalette,
+    /// Replace the palette of named colors by the one read from a json file
+    ImportColorPalette,
     CloseOverlay(OverlayType),
     OpenOverlay(OverlayType),
+    /// Show the actions that can be applied to the current selection in a floating menu
+    OpenContextMenu,
+    CloseContextMenu,
     ChangeUiSize(UiSize),
     InvertScrollY(bool),
+    /// Bind `action` to the next key combination pressed by the user, replacing the binding
+    /// `previous` if it is `Some`
+    RecordKeyBinding {
+        action: HotkeyAction,
+        previous: Option<KeyCombination>,
+    },
+    /// Stop waiting for the key combination of a new binding
+    CancelKeyBindingRecording,
+    /// The key bindings were modified. If the new binding was already used, `conflict` contains
+    /// the rebound action and the action that lost its binding
+    KeyBindingsChanged {
+        bindings: KeyBindings,
+        conflict: Option<(HotkeyAction, HotkeyAction)>,
+    },
+    /// Trigger the action `name` of the plugin `plugin_id`
+    PluginMenuAction {
+        plugin_id: usize,
+        name: String,
+    },
     ErrorMsg(String),
+    InfoMsg(String),
     DesignOperation(DesignOperation),
+    /// Apply a design operation and tell the user why it failed, if it does
+    TryDesignOperation(DesignOperation),
+    /// Apply several design operations that are undone in one step
+    DesignOperations(Vec<DesignOperation>),
     SilentDesignOperation(DesignOperation),
     Undo,
     Redo,
@@ -388,6 +758,8 @@ pub enum Action {
     ToggleHelicesPersistance(bool),
     ToggleSmallSphere(bool),
     RollRequest(RollRequest),
+    /// Optimize the roll of the given helices, or of all helices if `None`
+    OptimizeRolls(Option<Vec<usize>>),
     StopSimulation,
     RollHelices(f32),
     Copy,
@@ -411,6 +783,63 @@ pub enum Action {
     },
     DeleteSelection,
     ScaffoldToSelection,
+    /// Highlight a shortest path between the two selected nucleotides
+    HighlightShortestPath,
+    /// Add a distance measured in the 3D view to the list of measurements
+    AddMeasurement(Measurement),
+    ClearMeasurements,
+    /// Read an oxDNA trajectory of the current design to display its frames in the 3D view
+    LoadOxDnaTrajectory,
+    /// Start or stop the automatic playback of the loaded trajectory
+    PlayTrajectory(bool),
+    /// Display a frame of the loaded trajectory
+    SetTrajectoryFrame(usize),
+    /// Stop displaying the loaded trajectory
+    CloseTrajectory,
+    /// Read a design file and display it next to the current design
+    OpenDesignInWorkspace,
+    SetMainDesignVisibility(bool),
+    SetWorkspaceDesignVisibility {
+        design_id: usize,
+        visible: bool,
+    },
+    SetWorkspaceDesignTransform {
+        design_id: usize,
+        transform: WorkspaceTransform,
+    },
+    /// Remove a design from the workspace
+    CloseWorkspaceDesign(usize),
+    /// Add the content of a design of the workspace to the current design and remove it from
+    /// the workspace
+    MergeWorkspaceDesign(usize),
+    /// Move a design of the workspace so that one of its helices lies next to the selected helix
+    /// of the current design
+    DockWorkspaceDesign {
+        design_id: usize,
+        helix: usize,
+    },
+    /// Relax the design with oxDNA and display the trajectory of the relaxation
+    RelaxDesign,
+    /// Stop a running background job
+    CancelJob(usize),
+    /// Save the current selection under a name
+    SaveSelectionGroup(String),
+    /// Combine a saved selection with the current selection
+    RecallSelectionGroup {
+        name: String,
+        combination: SelectionGroupCombination,
+    },
+    DeleteSelectionGroup(String),
+    /// Open the cyclic scaffold before the given nucleotide and warn if the scaffold is still
+    /// disconnected
+    BreakScaffoldAt(Nucl),
+    /// Remove the strands that have no nucleotide and the helices on which no nucleotide lies
+    CleanupOrphans,
+    /// Center the 2D view on the cross-over between two nucleotides given as (strand id,
+    /// position on strand) pairs
+    GoToXover((usize, usize), (usize, usize)),
+    /// Predict the secondary structure of a strand with `RNAfold`
+    PredictSecondaryStructure(usize),
     /// Remove empty domains and merge consecutive domains
     CleanDesign,
     SuspendOp,
@@ -426,4 +855,14 @@ pub enum Action {
     SelectFavoriteCamera(u32),
     UpdateCamera(ensnano_design::CameraId),
     FlipSplitViews,
+    /// Start drawing a cross-over in the 2D view
+    Start2dXoverTool,
+    /// Start recording the design operations in a macro
+    StartRecording,
+    /// Stop recording the current macro and save it in a json file
+    StopRecording,
+    /// Apply the operations of a macro read from a json file
+    PlayMacro,
+    /// Apply the operations of a session log, in the order in which they were logged
+    ReplaySession,
 }
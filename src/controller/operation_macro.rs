@@ -0,0 +1,197 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! Macros are sequences of design operations that were recorded while the user was editing the
+//! design, and that can be saved to a json file and replayed later.
+
+use super::{dialog, messages, MainState, NormalState, State, TransitionMessage};
+use dialog::PathInput;
+use ensnano_interactor::DesignOperation;
+use serde_derive::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Instant;
+
+/// A design operation of a macro, together with the time at which it was recorded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedOperation {
+    pub operation: DesignOperation,
+    /// The number of milliseconds elapsed between the start of the recording and the operation
+    pub time_ms: u64,
+}
+
+/// A sequence of recorded design operations
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct OperationMacro {
+    pub operations: Vec<RecordedOperation>,
+}
+
+impl OperationMacro {
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), MacroError> {
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, content).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, MacroError> {
+        let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&content).map_err(|e| MacroError(e.to_string()))
+    }
+}
+
+/// Records the design operations applied while a macro is being recorded.
+pub struct MacroRecorder {
+    start: Instant,
+    recorded: OperationMacro,
+}
+
+impl Default for MacroRecorder {
+    fn default() -> Self {
+        Self {
+            start: Instant::now(),
+            recorded: Default::default(),
+        }
+    }
+}
+
+impl MacroRecorder {
+    pub fn record(&mut self, operation: DesignOperation) {
+        let time_ms = self.start.elapsed().as_millis() as u64;
+        self.recorded
+            .operations
+            .push(RecordedOperation { operation, time_ms });
+    }
+
+    /// Record the new effect of a pending operation. If `replace_previous` is true, the effect
+    /// replaces the previously recorded one instead of being applied after it.
+    pub fn record_pending(&mut self, operation: DesignOperation, replace_previous: bool) {
+        if replace_previous {
+            self.recorded.operations.pop();
+        }
+        self.record(operation)
+    }
+
+    pub fn finish(self) -> OperationMacro {
+        self.recorded
+    }
+}
+
+/// Write a recorded macro in a json file
+pub(super) struct SaveMacro {
+    recorded: OperationMacro,
+    file_getter: Option<PathInput>,
+}
+
+impl SaveMacro {
+    pub fn new(recorded: OperationMacro) -> Self {
+        Self {
+            recorded,
+            file_getter: None,
+        }
+    }
+}
+
+impl State for SaveMacro {
+    fn make_progress(mut self: Box<Self>, main_state: &mut dyn MainState) -> Box<dyn State> {
+        if let Some(ref getter) = self.file_getter {
+            if let Some(path_opt) = getter.get() {
+                if let Some(ref path) = path_opt {
+                    match self.recorded.save(path) {
+                        Err(err) => TransitionMessage::new(
+                            messages::failed_to_save_msg(&err),
+                            rfd::MessageLevel::Error,
+                            Box::new(NormalState),
+                        ),
+                        Ok(()) => Box::new(NormalState),
+                    }
+                } else {
+                    TransitionMessage::new(
+                        messages::NO_FILE_RECIEVED_MACRO,
+                        rfd::MessageLevel::Error,
+                        Box::new(NormalState),
+                    )
+                }
+            } else {
+                self
+            }
+        } else {
+            let getter = dialog::save("json", main_state.get_current_design_directory(), None);
+            self.file_getter = Some(getter);
+            self
+        }
+    }
+}
+
+/// Read a macro from a json file and apply its operations in order
+#[derive(Default)]
+pub(super) struct PlayMacro {
+    file_getter: Option<PathInput>,
+}
+
+impl State for PlayMacro {
+    fn make_progress(mut self: Box<Self>, main_state: &mut dyn MainState) -> Box<dyn State> {
+        if let Some(ref getter) = self.file_getter {
+            if let Some(path_opt) = getter.get() {
+                if let Some(ref path) = path_opt {
+                    match OperationMacro::load(path) {
+                        Err(err) => TransitionMessage::new(
+                            format!("Could not play macro: {}", err.0),
+                            rfd::MessageLevel::Error,
+                            Box::new(NormalState),
+                        ),
+                        Ok(recorded) => {
+                            // The whole macro is undone in one step
+                            main_state.apply_operations(
+                                recorded
+                                    .operations
+                                    .into_iter()
+                                    .map(|op| op.operation)
+                                    .collect(),
+                            );
+                            Box::new(NormalState)
+                        }
+                    }
+                } else {
+                    TransitionMessage::new(
+                        messages::NO_FILE_RECIEVED_MACRO,
+                        rfd::MessageLevel::Error,
+                        Box::new(NormalState),
+                    )
+                }
+            } else {
+                self
+            }
+        } else {
+            let getter = dialog::load(
+                main_state.get_current_design_directory(),
+                messages::MACRO_FILTERS,
+            );
+            self.file_getter = Some(getter);
+            self
+        }
+    }
+}
+
+/// An error that occured while reading or writing a macro file
+#[derive(Debug)]
+pub struct MacroError(pub String);
+
+impl From<String> for MacroError {
+    fn from(s: String) -> Self {
+        Self(s)
+    }
+}
@@ -0,0 +1,91 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::{
+    dialog, messages, BackgroundJob, JobError, JobKind, JobOutput, MainState, NormalState, State,
+    TransitionMessage,
+};
+use crate::app_state::OxDnaTrajectoryReader;
+use dialog::PathInput;
+use std::path::PathBuf;
+
+/// Read an oxDNA topology file and a configuration or trajectory file of the current design, and
+/// display its frames in the 3D view.
+///
+/// The files are read by a background job, the trajectory is displayed when the job is over.
+#[derive(Default)]
+pub(super) struct LoadOxDnaTrajectory {
+    topology: Option<PathBuf>,
+    file_getter: Option<PathInput>,
+}
+
+impl State for LoadOxDnaTrajectory {
+    fn make_progress(mut self: Box<Self>, main_state: &mut dyn MainState) -> Box<dyn State> {
+        if let Some(ref getter) = self.file_getter {
+            if let Some(path_opt) = getter.get() {
+                if let Some(path) = path_opt {
+                    if let Some(topology) = self.topology.take() {
+                        let reader = main_state.oxdna_trajectory_reader();
+                        main_state.attach_job(
+                            JobKind::TrajectoryLoading,
+                            trajectory_loading_job(reader, topology, path),
+                        );
+                        Box::new(NormalState)
+                    } else {
+                        // The topology has been chosen, now ask for the configurations
+                        self.topology = Some(path);
+                        self.file_getter = Some(dialog::load(
+                            main_state.get_current_design_directory(),
+                            messages::OXDNA_CONFIGURATION_FILTERS,
+                        ));
+                        self
+                    }
+                } else {
+                    TransitionMessage::new(
+                        messages::NO_FILE_RECIEVED_TRAJECTORY,
+                        rfd::MessageLevel::Error,
+                        Box::new(NormalState),
+                    )
+                }
+            } else {
+                self
+            }
+        } else {
+            let getter = dialog::load(
+                main_state.get_current_design_directory(),
+                messages::OXDNA_TOPOLOGY_FILTERS,
+            );
+            self.file_getter = Some(getter);
+            self
+        }
+    }
+}
+
+/// Start reading the trajectory stored in `topology` and `configurations`
+fn trajectory_loading_job(
+    reader: OxDnaTrajectoryReader,
+    topology: PathBuf,
+    configurations: PathBuf,
+) -> BackgroundJob<JobOutput> {
+    BackgroundJob::spawn(move |_| {
+        reader
+            .read(&topology, &configurations)
+            .map(JobOutput::Trajectory)
+            .map_err(|e| JobError::Failed(e.to_string()))
+    })
+}
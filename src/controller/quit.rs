@@ -18,10 +18,15 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 
 use crate::controller::normal_state::NormalState;
 
+use super::background_job::{BackgroundJob, JobError};
+use super::job_manager::{JobKind, JobOutput};
 use super::{dialog, messages, MainState, State, TransitionMessage, YesNo};
 
 use dialog::PathInput;
+use ensnano_interactor::graphics::AnimationParameters;
 use std::path::Path;
+use std::sync::mpsc;
+use ultraviolet::{Rotor3, Vec3};
 
 pub(super) struct Quit {
     step: QuitStep,
@@ -385,3 +390,402 @@ impl State for OxDnaExport {
         }
     }
 }
+
+/// Write a coarse atomic model of the design in the PDB format
+#[derive(Default)]
+pub(super) struct PdbExport {
+    file_getter: Option<PathInput>,
+}
+
+impl State for PdbExport {
+    fn make_progress(mut self: Box<Self>, main_state: &mut dyn MainState) -> Box<dyn State> {
+        if let Some(ref getter) = self.file_getter {
+            if let Some(path_opt) = getter.get() {
+                if let Some(ref path) = path_opt {
+                    match main_state.pdb_export(path) {
+                        Err(err) => TransitionMessage::new(
+                            messages::failed_to_save_msg(&err),
+                            rfd::MessageLevel::Error,
+                            Box::new(NormalState),
+                        ),
+                        Ok(()) => TransitionMessage::new(
+                            messages::successfull_export_msg(path),
+                            rfd::MessageLevel::Info,
+                            Box::new(NormalState),
+                        ),
+                    }
+                } else {
+                    TransitionMessage::new(
+                        messages::NO_FILE_RECIEVED_PDB,
+                        rfd::MessageLevel::Error,
+                        Box::new(NormalState),
+                    )
+                }
+            } else {
+                self
+            }
+        } else {
+            let getter = dialog::save("pdb", main_state.get_current_design_directory(), None);
+            self.file_getter = Some(getter);
+            self
+        }
+    }
+}
+
+/// Write the design in the cadnano v2 format
+#[derive(Default)]
+pub(super) struct CadnanoExport {
+    file_getter: Option<PathInput>,
+}
+
+impl State for CadnanoExport {
+    fn make_progress(mut self: Box<Self>, main_state: &mut dyn MainState) -> Box<dyn State> {
+        if let Some(ref getter) = self.file_getter {
+            if let Some(path_opt) = getter.get() {
+                if let Some(ref path) = path_opt {
+                    match main_state.cadnano_export(path) {
+                        Err(err) => TransitionMessage::new(
+                            messages::failed_to_save_msg(&err),
+                            rfd::MessageLevel::Error,
+                            Box::new(NormalState),
+                        ),
+                        Ok(()) => TransitionMessage::new(
+                            messages::successfull_export_msg(path),
+                            rfd::MessageLevel::Info,
+                            Box::new(NormalState),
+                        ),
+                    }
+                } else {
+                    TransitionMessage::new(
+                        messages::NO_FILE_RECIEVED_CADNANO,
+                        rfd::MessageLevel::Error,
+                        Box::new(NormalState),
+                    )
+                }
+            } else {
+                self
+            }
+        } else {
+            let getter = dialog::save("json", main_state.get_current_design_directory(), None);
+            self.file_getter = Some(getter);
+            self
+        }
+    }
+}
+
+/// Write the 3D representation of the design as a triangle mesh
+#[derive(Default)]
+pub(super) struct MeshExport {
+    file_getter: Option<PathInput>,
+}
+
+impl State for MeshExport {
+    fn make_progress(mut self: Box<Self>, main_state: &mut dyn MainState) -> Box<dyn State> {
+        if let Some(ref getter) = self.file_getter {
+            if let Some(path_opt) = getter.get() {
+                if let Some(ref path) = path_opt {
+                    match main_state.export_mesh(path) {
+                        Err(err) => TransitionMessage::new(
+                            messages::failed_to_save_msg(&err),
+                            rfd::MessageLevel::Error,
+                            Box::new(NormalState),
+                        ),
+                        Ok(()) => TransitionMessage::new(
+                            messages::successfull_export_msg(path),
+                            rfd::MessageLevel::Info,
+                            Box::new(NormalState),
+                        ),
+                    }
+                } else {
+                    TransitionMessage::new(
+                        messages::NO_FILE_RECIEVED_MESH,
+                        rfd::MessageLevel::Error,
+                        Box::new(NormalState),
+                    )
+                }
+            } else {
+                self
+            }
+        } else {
+            let getter = dialog::save("obj", main_state.get_current_design_directory(), None);
+            self.file_getter = Some(getter);
+            self
+        }
+    }
+}
+
+/// Write the connectivity graph of the design
+#[derive(Default)]
+pub(super) struct ConnectivityGraphExport {
+    file_getter: Option<PathInput>,
+}
+
+impl State for ConnectivityGraphExport {
+    fn make_progress(mut self: Box<Self>, main_state: &mut dyn MainState) -> Box<dyn State> {
+        if let Some(ref getter) = self.file_getter {
+            if let Some(path_opt) = getter.get() {
+                if let Some(ref path) = path_opt {
+                    match main_state.connectivity_graph_export(path) {
+                        Err(err) => TransitionMessage::new(
+                            messages::failed_to_save_msg(&err),
+                            rfd::MessageLevel::Error,
+                            Box::new(NormalState),
+                        ),
+                        Ok(()) => TransitionMessage::new(
+                            messages::successfull_export_msg(path),
+                            rfd::MessageLevel::Info,
+                            Box::new(NormalState),
+                        ),
+                    }
+                } else {
+                    TransitionMessage::new(
+                        messages::NO_FILE_RECIEVED_GRAPH,
+                        rfd::MessageLevel::Error,
+                        Box::new(NormalState),
+                    )
+                }
+            } else {
+                self
+            }
+        } else {
+            let getter = dialog::save("json", main_state.get_current_design_directory(), None);
+            self.file_getter = Some(getter);
+            self
+        }
+    }
+}
+
+/// Write a PNG image of the 2D view of the design
+pub(super) struct HelixMapExport {
+    file_getter: Option<PathInput>,
+    dpi: u32,
+    size_mm: (f32, f32),
+}
+
+impl HelixMapExport {
+    pub fn new(dpi: u32, size_mm: (f32, f32)) -> Self {
+        Self {
+            file_getter: None,
+            dpi,
+            size_mm,
+        }
+    }
+}
+
+impl State for HelixMapExport {
+    fn make_progress(mut self: Box<Self>, main_state: &mut dyn MainState) -> Box<dyn State> {
+        if let Some(ref getter) = self.file_getter {
+            if let Some(path_opt) = getter.get() {
+                if let Some(ref path) = path_opt {
+                    match main_state.export_helix_map_png(self.dpi, self.size_mm, path) {
+                        Err(err) => TransitionMessage::new(
+                            messages::failed_to_save_msg(&err),
+                            rfd::MessageLevel::Error,
+                            Box::new(NormalState),
+                        ),
+                        Ok(()) => TransitionMessage::new(
+                            messages::successfull_export_msg(path),
+                            rfd::MessageLevel::Info,
+                            Box::new(NormalState),
+                        ),
+                    }
+                } else {
+                    TransitionMessage::new(
+                        messages::NO_FILE_RECIEVED_HELIX_MAP,
+                        rfd::MessageLevel::Error,
+                        Box::new(NormalState),
+                    )
+                }
+            } else {
+                self
+            }
+        } else {
+            let getter = dialog::save("png", main_state.get_current_design_directory(), None);
+            self.file_getter = Some(getter);
+            self
+        }
+    }
+}
+
+/// Write a PNG image of the 3D view of the design
+pub(super) struct ScenePngExport {
+    file_getter: Option<PathInput>,
+    size: (u32, u32),
+}
+
+impl ScenePngExport {
+    pub fn new(size: (u32, u32)) -> Self {
+        Self {
+            file_getter: None,
+            size,
+        }
+    }
+}
+
+impl State for ScenePngExport {
+    fn make_progress(mut self: Box<Self>, main_state: &mut dyn MainState) -> Box<dyn State> {
+        if let Some(ref getter) = self.file_getter {
+            if let Some(path_opt) = getter.get() {
+                if let Some(ref path) = path_opt {
+                    match main_state.export_scene_png(self.size, path) {
+                        Err(err) => TransitionMessage::new(
+                            messages::failed_to_save_msg(&err),
+                            rfd::MessageLevel::Error,
+                            Box::new(NormalState),
+                        ),
+                        Ok(()) => TransitionMessage::new(
+                            messages::successfull_export_msg(path),
+                            rfd::MessageLevel::Info,
+                            Box::new(NormalState),
+                        ),
+                    }
+                } else {
+                    TransitionMessage::new(
+                        messages::NO_FILE_RECIEVED_SCREENSHOT,
+                        rfd::MessageLevel::Error,
+                        Box::new(NormalState),
+                    )
+                }
+            } else {
+                self
+            }
+        } else {
+            let getter = dialog::save("png", main_state.get_current_design_directory(), None);
+            self.file_getter = Some(getter);
+            self
+        }
+    }
+}
+
+/// Write the frames of an animation of the 3D view in a directory chosen by the user.
+///
+/// One frame is rendered each time the state makes progress, so that the application keeps
+/// responding, and the frames are written in PNG images by a background job.
+pub(super) struct AnimationRecording {
+    file_getter: Option<PathInput>,
+    parameters: AnimationParameters,
+    /// The viewpoints of the frames that remain to be rendered
+    viewpoints: std::vec::IntoIter<(Vec3, Rotor3)>,
+    /// The channel through which the rendered frames are sent to the background job, once the
+    /// recording started
+    frames: Option<mpsc::Sender<Vec<u8>>>,
+}
+
+impl AnimationRecording {
+    pub fn new(parameters: AnimationParameters) -> Self {
+        Self {
+            file_getter: None,
+            parameters,
+            viewpoints: Vec::new().into_iter(),
+            frames: None,
+        }
+    }
+
+    fn render_next_frame(mut self: Box<Self>, main_state: &mut dyn MainState) -> Box<dyn State> {
+        let viewpoint = if let Some(viewpoint) = self.viewpoints.next() {
+            viewpoint
+        } else {
+            // Dropping the channel lets the job know that all the frames were sent
+            return Box::new(NormalState);
+        };
+        match main_state.render_scene_viewpoint(viewpoint, self.parameters.size) {
+            Err(err) => TransitionMessage::new(
+                messages::failed_to_save_msg(&err),
+                rfd::MessageLevel::Error,
+                Box::new(NormalState),
+            ),
+            Ok(pixels) => {
+                let sent = self
+                    .frames
+                    .as_ref()
+                    .map(|frames| frames.send(pixels).is_ok())
+                    .unwrap_or(false);
+                if sent {
+                    self
+                } else {
+                    // The job was canceled
+                    Box::new(NormalState)
+                }
+            }
+        }
+    }
+}
+
+impl State for AnimationRecording {
+    fn make_progress(mut self: Box<Self>, main_state: &mut dyn MainState) -> Box<dyn State> {
+        if self.frames.is_some() {
+            self.render_next_frame(main_state)
+        } else if let Some(ref getter) = self.file_getter {
+            if let Some(path_opt) = getter.get() {
+                if let Some(ref path) = path_opt {
+                    match main_state.animation_viewpoints(self.parameters) {
+                        Err(err) => TransitionMessage::new(
+                            messages::failed_to_save_msg(&err),
+                            rfd::MessageLevel::Error,
+                            Box::new(NormalState),
+                        ),
+                        Ok(viewpoints) => {
+                            let (frames_snd, frames_rcv) = mpsc::channel();
+                            main_state.attach_job(
+                                JobKind::AnimationRecording,
+                                frame_writing_job(
+                                    path.clone(),
+                                    viewpoints.len() as u32,
+                                    self.parameters.size,
+                                    frames_rcv,
+                                ),
+                            );
+                            self.viewpoints = viewpoints.into_iter();
+                            self.frames = Some(frames_snd);
+                            self
+                        }
+                    }
+                } else {
+                    TransitionMessage::new(
+                        messages::NO_FILE_RECIEVED_ANIMATION,
+                        rfd::MessageLevel::Error,
+                        Box::new(NormalState),
+                    )
+                }
+            } else {
+                self
+            }
+        } else {
+            let getter = dialog::get_dir();
+            self.file_getter = Some(getter);
+            self
+        }
+    }
+}
+
+/// Write the `nb_frames` RGBA images of `size` pixels received from `frames` as numbered PNG
+/// images in `directory`
+fn frame_writing_job(
+    directory: PathBuf,
+    nb_frames: u32,
+    size: (u32, u32),
+    frames: mpsc::Receiver<Vec<u8>>,
+) -> BackgroundJob<JobOutput> {
+    BackgroundJob::spawn(move |context| {
+        for n in 0..nb_frames {
+            if context.is_canceled() {
+                return Err(JobError::Canceled);
+            }
+            // The sender is dropped before the end if a frame could not be rendered
+            let pixels = frames.recv().map_err(|_| JobError::Canceled)?;
+            let image = image::RgbaImage::from_raw(size.0, size.1, pixels)
+                .ok_or_else(|| JobError::Failed("Could not read the rendered image".to_string()))?;
+            image
+                .save_with_format(
+                    directory.join(format!("frame_{:05}.png", n)),
+                    image::ImageFormat::Png,
+                )
+                .map_err(|e| JobError::Failed(e.to_string()))?;
+            context.set_progress((n + 1) as f32 / nb_frames as f32);
+        }
+        Ok(JobOutput::Animation {
+            nb_frames,
+            directory,
+        })
+    })
+}
@@ -0,0 +1,204 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! Relaxation of the design by oxDNA.
+//!
+//! The design is exported in the oxDNA format and a molecular dynamics simulation with bounded
+//! backbone forces is run by the `oxDNA` program. The last configuration of the trajectory that it
+//! produces is the relaxed conformation, to which the helices of the design are then fitted.
+
+use super::background_job::{BackgroundJob, JobContext, JobError};
+use super::job_manager::{JobKind, JobOutput};
+use super::{messages, MainState, NormalState, State, TransitionMessage};
+use crate::app_state::OxDnaTrajectoryReader;
+use ensnano_interactor::Trajectory;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+/// The environment variable that can be set to the path of the oxDNA executable
+const OXDNA_PROGRAM_VAR: &str = "ENSNANO_OXDNA";
+/// The name of the oxDNA executable when it is in the PATH
+const DEFAULT_OXDNA_PROGRAM: &str = "oxDNA";
+
+const RELAXATION_STEPS: u32 = 100_000;
+const RELAXATION_DT: f32 = 0.003;
+/// Number of steps between two configurations of the trajectory
+const CONF_INTERVAL: u32 = 10_000;
+/// Number of steps between two lines of the energy file, from which the progress is read
+const ENERGY_INTERVAL: u32 = 1_000;
+
+const INPUT_FILE: &str = "relax.input";
+const TRAJECTORY_FILE: &str = "trajectory.dat";
+const ENERGY_FILE: &str = "energy.dat";
+
+/// The time between two checks of the state of the oxDNA process
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// The result of a relaxation
+pub struct RelaxationResult {
+    /// The trajectory of the relaxation, whose last frame is the relaxed conformation. Its
+    /// nucleotides are those of the design at the start of the relaxation.
+    pub trajectory: Trajectory,
+}
+
+/// Export the design in the oxDNA format and start relaxing it
+pub(super) fn start_relaxation(main_state: &mut dyn MainState) -> Box<dyn State> {
+    if main_state.is_job_running(JobKind::Relaxation) {
+        return TransitionMessage::new(
+            messages::RELAXATION_ALREADY_RUNNING,
+            rfd::MessageLevel::Error,
+            Box::new(NormalState),
+        );
+    }
+    let directory = crate::utils::unique_temp_dir("ensnano_relaxation");
+    match directory.and_then(|directory| main_state.oxdna_export(&directory)) {
+        Ok((configuration, topology)) => {
+            let reader = main_state.oxdna_trajectory_reader();
+            main_state.attach_job(
+                JobKind::Relaxation,
+                relax_with_oxdna(reader, topology, configuration),
+            );
+            Box::new(NormalState)
+        }
+        Err(err) => TransitionMessage::new(
+            messages::failed_to_start_relaxation_msg(&err),
+            rfd::MessageLevel::Error,
+            Box::new(NormalState),
+        ),
+    }
+}
+
+/// Start the relaxation of the design exported in `topology` and `configuration`. The output
+/// files are written in the directory of the topology, which is removed once the trajectory has
+/// been read by `reader`.
+fn relax_with_oxdna(
+    reader: OxDnaTrajectoryReader,
+    topology: PathBuf,
+    configuration: PathBuf,
+) -> BackgroundJob<JobOutput> {
+    BackgroundJob::spawn(move |context| {
+        let directory = topology.parent().map(Path::to_path_buf).unwrap_or_default();
+        let result = run_oxdna(&directory, &topology, &configuration, context).and_then(|_| {
+            reader
+                .read(&topology, &directory.join(TRAJECTORY_FILE))
+                .map_err(|e| JobError::Failed(e.to_string()))
+        });
+        if let Err(e) = std::fs::remove_dir_all(&directory) {
+            log::error!("Could not remove {}: {}", directory.to_string_lossy(), e);
+        }
+        result.map(|trajectory| JobOutput::Relaxation(RelaxationResult { trajectory }))
+    })
+}
+
+/// Run oxDNA in `directory` until the end of the relaxation
+fn run_oxdna(
+    directory: &Path,
+    topology: &Path,
+    configuration: &Path,
+    context: &JobContext,
+) -> Result<(), JobError> {
+    write_input(&directory.join(INPUT_FILE), topology, configuration)?;
+    let program =
+        std::env::var(OXDNA_PROGRAM_VAR).unwrap_or_else(|_| DEFAULT_OXDNA_PROGRAM.to_string());
+    let mut child = Command::new(&program)
+        .arg(INPUT_FILE)
+        .current_dir(directory)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| JobError::Failed(format!("Could not launch {}: {}", program, e)))?;
+    let energy_file = directory.join(ENERGY_FILE);
+    let total_time = RELAXATION_STEPS as f32 * RELAXATION_DT;
+    loop {
+        if context.is_canceled() {
+            child.kill()?;
+            child.wait()?;
+            return Err(JobError::Canceled);
+        }
+        if let Some(status) = child.try_wait()? {
+            return if status.success() {
+                Ok(())
+            } else {
+                Err(JobError::Failed(format!(
+                    "{} exited with {}",
+                    program, status
+                )))
+            };
+        }
+        if let Some(time) = last_energy_time(&energy_file) {
+            context.set_progress(time / total_time);
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+fn write_input(path: &Path, topology: &Path, configuration: &Path) -> std::io::Result<()> {
+    let mut file = std::fs::File::create(path)?;
+    writeln!(file, "backend = CPU")?;
+    writeln!(file, "sim_type = MD")?;
+    writeln!(file, "interaction_type = DNA2")?;
+    writeln!(file, "salt_concentration = 0.5")?;
+    writeln!(file, "T = 20C")?;
+    writeln!(file, "steps = {}", RELAXATION_STEPS)?;
+    writeln!(file, "dt = {}", RELAXATION_DT)?;
+    writeln!(file, "thermostat = john")?;
+    writeln!(file, "newtonian_steps = 103")?;
+    writeln!(file, "diff_coeff = 2.5")?;
+    // Bounding the backbone forces prevents the long bonds of the initial design from
+    // destabilizing the simulation
+    writeln!(file, "max_backbone_force = 5")?;
+    writeln!(file, "max_backbone_force_far = 10")?;
+    writeln!(file, "topology = {}", topology.to_string_lossy())?;
+    writeln!(file, "conf_file = {}", configuration.to_string_lossy())?;
+    writeln!(file, "trajectory_file = {}", TRAJECTORY_FILE)?;
+    writeln!(file, "lastconf_file = last_conf.dat")?;
+    writeln!(file, "energy_file = {}", ENERGY_FILE)?;
+    writeln!(file, "print_conf_interval = {}", CONF_INTERVAL)?;
+    writeln!(file, "print_energy_every = {}", ENERGY_INTERVAL)?;
+    writeln!(file, "time_scale = linear")?;
+    writeln!(file, "restart_step_counter = 1")?;
+    writeln!(file, "refresh_vel = 1")?;
+    Ok(())
+}
+
+/// The simulation time of the last line of an oxDNA energy file
+fn last_energy_time(path: &Path) -> Option<f32> {
+    let content = std::fs::read_to_string(path).ok()?;
+    parse_energy_time(content.lines().rev().find(|l| !l.trim().is_empty())?)
+}
+
+/// The simulation time, which is the first value of a line of an oxDNA energy file
+fn parse_energy_time(line: &str) -> Option<f32> {
+    line.split_whitespace().next()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn energy_time_is_the_first_value() {
+        assert_eq!(
+            parse_energy_time("150.000 -1.4320 0.0890 -1.3430"),
+            Some(150.)
+        );
+        assert_eq!(parse_energy_time("not a number"), None);
+    }
+}
@@ -0,0 +1,194 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! Secondary structure prediction of strands, using the `RNAfold` program of the ViennaRNA
+//! package.
+
+use super::{BackgroundJob, JobError, JobOutput};
+use ensnano_design::Nucl;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+const RNAFOLD: &str = "RNAfold";
+
+#[derive(Debug)]
+pub enum MfeError {
+    /// The `RNAfold` binary could not be found on the `PATH`
+    RnaFoldNotFound,
+    Io(std::io::Error),
+    /// The output of `RNAfold` could not be parsed
+    BadOutput(String),
+    /// Some nucleotides of the strand have no sequence
+    NoSequence,
+    StrandNotFound(usize),
+}
+
+impl From<std::io::Error> for MfeError {
+    fn from(e: std::io::Error) -> Self {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            Self::RnaFoldNotFound
+        } else {
+            Self::Io(e)
+        }
+    }
+}
+
+impl std::fmt::Display for MfeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::RnaFoldNotFound => write!(
+                f,
+                "Could not find {}. Please install the ViennaRNA package and make sure that {} is in your PATH",
+                RNAFOLD, RNAFOLD
+            ),
+            Self::Io(e) => write!(f, "Error while running {}: {}", RNAFOLD, e),
+            Self::BadOutput(output) => {
+                write!(f, "Could not read the output of {}:\n{}", RNAFOLD, output)
+            }
+            Self::NoSequence => write!(f, "The selected strand does not have a sequence"),
+            Self::StrandNotFound(s_id) => write!(f, "There is no strand with id {}", s_id),
+        }
+    }
+}
+
+/// A summary of the prediction of the secondary structure of a strand
+pub struct SecondaryStructurePrediction {
+    pub nb_pairs: usize,
+    /// The number of predicted pairs that are Watson-Crick bonds of the design
+    pub nb_pairs_in_design: usize,
+    pub nb_unpaired: usize,
+}
+
+impl std::fmt::Display for SecondaryStructurePrediction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Predicted secondary structure: {} pairs ({} of which are bonds of the design), {} unpaired nucleotides",
+            self.nb_pairs, self.nb_pairs_in_design, self.nb_unpaired
+        )
+    }
+}
+
+/// The minimum free energy structure of a strand, as computed by a background job
+pub struct SecondaryStructure {
+    /// The nucleotides of the strand, from its 5' end to its 3' end
+    pub nucls: Vec<Nucl>,
+    /// The pairs of indices in `nucls` of the nucleotides that are paired
+    pub pairs: Vec<(usize, usize)>,
+    /// The indices in `nucls` of the nucleotides that are not paired
+    pub unpaired: Vec<usize>,
+}
+
+/// Start predicting the secondary structure of the strand made of `nucls`, whose sequence is
+/// `sequence`
+pub fn secondary_structure_job(nucls: Vec<Nucl>, sequence: String) -> BackgroundJob<JobOutput> {
+    BackgroundJob::spawn(move |_| {
+        let structure =
+            predict_secondary_structure(&sequence).map_err(|e| JobError::Failed(e.to_string()))?;
+        if structure.chars().count() != nucls.len() {
+            return Err(JobError::Failed(MfeError::BadOutput(structure).to_string()));
+        }
+        let pairs = dot_bracket_pairs(&structure).map_err(|e| JobError::Failed(e.to_string()))?;
+        let unpaired = structure
+            .chars()
+            .enumerate()
+            .filter(|(_, c)| *c == '.')
+            .map(|(i, _)| i)
+            .collect();
+        Ok(JobOutput::SecondaryStructure(SecondaryStructure {
+            nucls,
+            pairs,
+            unpaired,
+        }))
+    })
+}
+
+/// Return the minimum free energy structure of `sequence` in dot-bracket notation.
+fn predict_secondary_structure(sequence: &str) -> Result<String, MfeError> {
+    let mut child = Command::new(RNAFOLD)
+        .arg("--noPS")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        writeln!(stdin, "{}", sequence)?;
+    }
+    let output = child.wait_with_output()?;
+    let output = String::from_utf8_lossy(&output.stdout);
+    read_rnafold_output(&output)
+}
+
+/// Extract the dot-bracket structure from the output of `RNAfold`.
+///
+/// The output consists of the folded sequence, followed by a line containing the structure and
+/// its free energy, e.g. `((....)) ( -1.20)`.
+fn read_rnafold_output(output: &str) -> Result<String, MfeError> {
+    output
+        .lines()
+        .filter(|l| !l.starts_with('>'))
+        .nth(1)
+        .and_then(|l| l.split_whitespace().next())
+        .filter(|s| s.chars().all(|c| c == '(' || c == ')' || c == '.'))
+        .map(|s| s.to_string())
+        .ok_or_else(|| MfeError::BadOutput(output.to_string()))
+}
+
+/// Return the pairs of positions that are paired in a dot-bracket structure
+fn dot_bracket_pairs(structure: &str) -> Result<Vec<(usize, usize)>, MfeError> {
+    let mut opened = Vec::new();
+    let mut pairs = Vec::new();
+    for (i, c) in structure.chars().enumerate() {
+        match c {
+            '(' => opened.push(i),
+            ')' => {
+                let j = opened
+                    .pop()
+                    .ok_or_else(|| MfeError::BadOutput(structure.to_string()))?;
+                pairs.push((j, i));
+            }
+            _ => (),
+        }
+    }
+    if opened.is_empty() {
+        Ok(pairs)
+    } else {
+        Err(MfeError::BadOutput(structure.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_structure_from_rnafold_output() {
+        let output = "GGGAAAUCCC\n(((....))) ( -1.20)\n";
+        assert_eq!(read_rnafold_output(output).unwrap(), "(((....)))");
+        let output = ">my_strand\nGGGAAAUCCC\n(((....))) ( -1.20)\n";
+        assert_eq!(read_rnafold_output(output).unwrap(), "(((....)))");
+        assert!(read_rnafold_output("GGGAAAUCCC\n").is_err());
+    }
+
+    #[test]
+    fn pairs_of_dot_bracket() {
+        assert_eq!(dot_bracket_pairs("((..)).").unwrap(), vec![(1, 4), (0, 5)]);
+        assert!(dot_bracket_pairs("((..)").is_err());
+        assert!(dot_bracket_pairs("(..))").is_err());
+    }
+}
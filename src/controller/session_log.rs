@@ -0,0 +1,331 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! The session log records every design operation successfully applied during a session, so
+//! that the steps that led to a design can be reproduced.
+//!
+//! The log is written in `~/.ensnano/session_YYYYMMDD_HHMMSS.log`, with one json object per line
+//! holding the operation and the time at which it was applied. When a log file becomes too large,
+//! the log is continued in a new file, and only the most recent log files are kept. A log can be
+//! replayed on an other design.
+//!
+//! The user can disable the session logs. This choice is stored in the `"session_log_disabled"`
+//! field of `~/.ensnano/preferences.json`.
+
+use super::{dialog, messages, MainState, NormalState, State, TransitionMessage};
+use crate::consts::ENSNANO_USER_DIR;
+use crate::preferences;
+use dialog::PathInput;
+use ensnano_interactor::DesignOperation;
+use serde_derive::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{LineWriter, Write};
+use std::path::{Path, PathBuf};
+
+const SESSION_LOG_DISABLED_FIELD: &str = "session_log_disabled";
+const SESSION_LOG_PREFIX: &str = "session_";
+const SESSION_LOG_EXTENSION: &str = ".log";
+
+/// The number of log files that are kept in `~/.ensnano`. The oldest ones are deleted when a new
+/// log file is created.
+const MAX_NB_SESSION_LOGS: usize = 20;
+
+/// The size, in bytes, above which the log is continued in a new file
+const MAX_SESSION_LOG_SIZE: u64 = 10_000_000;
+
+/// A line of the session log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LoggedOperation {
+    /// The date at which the operation was applied, in the RFC 3339 format
+    timestamp: String,
+    operation: DesignOperation,
+}
+
+/// Appends the design operations to the log file of the current session
+pub struct SessionLog {
+    /// The directory in which the log files are created
+    directory: PathBuf,
+    /// The current log file, or `None` if it could not be created, if the log was closed or if
+    /// the user disabled the session logs
+    writer: Option<LineWriter<File>>,
+    /// The number of bytes written in the current log file
+    size: u64,
+}
+
+impl SessionLog {
+    /// Return false if the user disabled the session logs
+    pub fn is_enabled() -> bool {
+        !preferences::read_field::<bool>(SESSION_LOG_DISABLED_FIELD)
+    }
+
+    /// Write the choice of the user in `~/.ensnano/preferences.json`, keeping the other
+    /// preferences stored in this file
+    pub fn save_enabled(enabled: bool) -> std::io::Result<()> {
+        preferences::write_field(SESSION_LOG_DISABLED_FIELD, &!enabled)
+    }
+
+    /// Create the log file of a session starting now, unless the user disabled the session logs.
+    /// If the file cannot be created, the operations of the session are not logged.
+    pub fn open() -> Self {
+        if let Some(home) = dirs::home_dir() {
+            Self::open_in(home.join(ENSNANO_USER_DIR), Self::is_enabled())
+        } else {
+            log::error!("Could not create session log: Could not find the home directory");
+            Self::open_in(PathBuf::new(), false)
+        }
+    }
+
+    fn open_in(directory: PathBuf, enabled: bool) -> Self {
+        let mut ret = Self {
+            directory,
+            writer: None,
+            size: 0,
+        };
+        if enabled {
+            ret.start_new_file();
+        }
+        ret
+    }
+
+    /// Close the current log file and continue the log in a new one
+    fn start_new_file(&mut self) {
+        self.close();
+        let result = std::fs::create_dir_all(&self.directory)
+            .and_then(|()| remove_old_logs(&self.directory))
+            .and_then(|()| create_log_file(&self.directory));
+        match result {
+            Ok(file) => {
+                self.writer = Some(LineWriter::new(file));
+                self.size = 0;
+            }
+            Err(e) => log::error!("Could not create session log: {}", e),
+        }
+    }
+
+    pub fn record(&mut self, operation: &DesignOperation) {
+        if self.writer.is_some() && self.size >= MAX_SESSION_LOG_SIZE {
+            self.start_new_file();
+        }
+        if let Some(writer) = self.writer.as_mut() {
+            let line = LoggedOperation {
+                timestamp: chrono::Local::now().to_rfc3339(),
+                operation: operation.clone(),
+            };
+            let result = serde_json::to_string(&line)
+                .map_err(|e| e.to_string())
+                .and_then(|json| {
+                    writeln!(writer, "{}", json)
+                        .map(|()| json.len() as u64 + 1)
+                        .map_err(|e| e.to_string())
+                });
+            match result {
+                Ok(nb_bytes) => self.size += nb_bytes,
+                Err(e) => log::error!("Could not write in session log: {}", e),
+            }
+        }
+    }
+
+    /// Start or stop logging the operations. Stopping closes the current log file, and starting
+    /// again creates a new one.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if !enabled {
+            self.close()
+        } else if self.writer.is_none() {
+            self.start_new_file()
+        }
+    }
+
+    /// Flush and close the log file. The operations applied after this call are not logged.
+    pub fn close(&mut self) {
+        if let Some(mut writer) = self.writer.take() {
+            if let Err(e) = writer.flush() {
+                log::error!("Could not write in session log: {}", e);
+            }
+        }
+    }
+}
+
+/// Create a new log file in `directory`, named after the current date
+fn create_log_file(directory: &Path) -> std::io::Result<File> {
+    let date = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
+    let mut part = 0;
+    loop {
+        // A log may have been rotated in the same second
+        let file_name = if part == 0 {
+            format!("{}{}{}", SESSION_LOG_PREFIX, date, SESSION_LOG_EXTENSION)
+        } else {
+            format!(
+                "{}{}_{}{}",
+                SESSION_LOG_PREFIX, date, part, SESSION_LOG_EXTENSION
+            )
+        };
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(directory.join(file_name))
+        {
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => part += 1,
+            result => return result,
+        }
+    }
+}
+
+/// Delete the oldest log files of `directory` so that a new one can be created without exceeding
+/// `MAX_NB_SESSION_LOGS`
+fn remove_old_logs(directory: &Path) -> std::io::Result<()> {
+    let mut logs = Vec::new();
+    for entry in std::fs::read_dir(directory)? {
+        let path = entry?.path();
+        let is_log = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .map(|name| {
+                name.starts_with(SESSION_LOG_PREFIX) && name.ends_with(SESSION_LOG_EXTENSION)
+            })
+            .unwrap_or(false);
+        if is_log {
+            logs.push(path);
+        }
+    }
+    // The names start with the date of creation of the logs, so they are sorted from oldest to
+    // newest
+    logs.sort();
+    let nb_removed = (logs.len() + 1).saturating_sub(MAX_NB_SESSION_LOGS);
+    for path in logs.iter().take(nb_removed) {
+        std::fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+/// Read the operations of a session log, in the order in which they were applied
+fn read_session_log<P: AsRef<Path>>(path: P) -> Result<Vec<DesignOperation>, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .enumerate()
+        .map(|(n, line)| {
+            serde_json::from_str::<LoggedOperation>(line)
+                .map(|logged| logged.operation)
+                .map_err(|e| format!("line {}: {}", n + 1, e))
+        })
+        .collect()
+}
+
+/// Read a session log and apply its operations in order
+#[derive(Default)]
+pub(super) struct ReplaySession {
+    file_getter: Option<PathInput>,
+}
+
+impl State for ReplaySession {
+    fn make_progress(mut self: Box<Self>, main_state: &mut dyn MainState) -> Box<dyn State> {
+        if let Some(ref getter) = self.file_getter {
+            if let Some(path_opt) = getter.get() {
+                if let Some(ref path) = path_opt {
+                    match read_session_log(path) {
+                        Err(err) => TransitionMessage::new(
+                            format!("Could not replay session: {}", err),
+                            rfd::MessageLevel::Error,
+                            Box::new(NormalState),
+                        ),
+                        Ok(operations) => {
+                            // The whole session is undone in one step
+                            main_state.apply_operations(operations);
+                            Box::new(NormalState)
+                        }
+                    }
+                } else {
+                    TransitionMessage::new(
+                        messages::NO_FILE_RECIEVED_SESSION_LOG,
+                        rfd::MessageLevel::Error,
+                        Box::new(NormalState),
+                    )
+                }
+            } else {
+                self
+            }
+        } else {
+            let getter = dialog::load(
+                dirs::home_dir().map(|home| home.join(ENSNANO_USER_DIR)),
+                messages::SESSION_LOG_FILTERS,
+            );
+            self.file_getter = Some(getter);
+            self
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn logged_operations_are_read_back_in_order() {
+        let operations = vec![
+            DesignOperation::RmHelices { h_ids: vec![1, 2] },
+            DesignOperation::CleanupOrphans,
+        ];
+        let directory = crate::utils::unique_temp_dir("ensnano_session_log_test").unwrap();
+        let mut log = SessionLog::open_in(directory.clone(), true);
+        for op in operations.iter() {
+            log.record(op);
+        }
+        log.close();
+        log.record(&DesignOperation::CleanupOrphans);
+
+        let paths: Vec<_> = std::fs::read_dir(&directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(paths.len(), 1);
+        let read = read_session_log(&paths[0]).unwrap();
+        assert_eq!(read.len(), 2);
+        assert!(matches!(read[0], DesignOperation::RmHelices { ref h_ids } if h_ids == &[1, 2]));
+        assert!(matches!(read[1], DesignOperation::CleanupOrphans));
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn only_the_most_recent_logs_are_kept() {
+        let directory = crate::utils::unique_temp_dir("ensnano_session_log_test").unwrap();
+        for i in 0..MAX_NB_SESSION_LOGS {
+            std::fs::write(directory.join(format!("session_{:08}_000000.log", i)), "").unwrap();
+        }
+        std::fs::write(directory.join("preferences.json"), "{}").unwrap();
+        let mut log = SessionLog::open_in(directory.clone(), true);
+        log.close();
+
+        let remaining: Vec<_> = std::fs::read_dir(&directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        assert_eq!(remaining.len(), MAX_NB_SESSION_LOGS + 1);
+        assert!(remaining.contains(&"preferences.json".to_string()));
+        assert!(!remaining.contains(&"session_00000000_000000.log".to_string()));
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn disabled_log_creates_no_file() {
+        let directory = crate::utils::unique_temp_dir("ensnano_session_log_test").unwrap();
+        let mut log = SessionLog::open_in(directory.clone(), false);
+        log.record(&DesignOperation::CleanupOrphans);
+        assert_eq!(std::fs::read_dir(&directory).unwrap().count(), 0);
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,38 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! The number of undoable states that are embedded in the saved design files.
+//!
+//! The size is stored in the `"edit_history_size"` field of `~/.ensnano/preferences.json`. A size
+//! of 0, the default, means that no history is saved.
+
+use crate::preferences;
+
+const HISTORY_SIZE_FIELD: &str = "edit_history_size";
+
+/// The largest number of states that can be embedded in a saved file
+pub const MAX_EDIT_HISTORY_SIZE: usize = 100;
+
+/// Read the history size chosen by the user
+pub fn load_size() -> usize {
+    preferences::read_field::<usize>(HISTORY_SIZE_FIELD).min(MAX_EDIT_HISTORY_SIZE)
+}
+
+/// Save the history size with `preferences::write_field`
+pub fn save_size(size: usize) -> std::io::Result<()> {
+    preferences::write_field(HISTORY_SIZE_FIELD, &size)
+}
@@ -18,6 +18,7 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 //! This module handles the 2D view
 
 //use crate::design::{DesignNotification, DesignNotificationContent, Nucl, StrandBuilder};
+use crate::controller::ChanelReaderUpdate;
 use crate::{utils::camera2d::FitRectangle, DrawArea, Duration, PhySize, WindowEvent};
 use ensnano_design::Nucl;
 use ensnano_interactor::{
@@ -29,9 +30,10 @@ use ensnano_interactor::{
 use iced_wgpu::wgpu;
 use iced_winit::winit;
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
 use std::rc::Rc;
-use std::sync::{Arc, Mutex};
+use std::sync::{mpsc, Arc, Mutex};
 use wgpu::{Device, Queue};
 use winit::dpi::PhysicalPosition;
 
@@ -40,6 +42,7 @@ mod controller;
 mod data;
 mod flattypes;
 mod view;
+use crate::consts::MAX_EXPORT_DIMENSION;
 use camera::{Camera, Globals};
 use controller::Controller;
 use data::Data;
@@ -48,6 +51,8 @@ use flattypes::*;
 use std::time::Instant;
 use view::View;
 
+const MM_PER_INCH: f32 = 25.4;
+
 type ViewPtr = Rc<RefCell<View>>;
 type DataPtr = Rc<RefCell<Data>>;
 type CameraPtr = Rc<RefCell<Camera>>;
@@ -72,6 +77,8 @@ pub struct FlatScene<S: AppState> {
     splited: bool,
     old_state: S,
     requests: Arc<Mutex<dyn Requests>>,
+    /// The updates broadcasted by the main state's `ChanelReader`
+    chanel_updates: Option<mpsc::Receiver<ChanelReaderUpdate>>,
 }
 
 impl<S: AppState> FlatScene<S> {
@@ -96,11 +103,30 @@ impl<S: AppState> FlatScene<S> {
             splited: false,
             old_state: initial_state.clone(),
             requests: requests.clone(),
+            chanel_updates: None,
         };
         ret.add_design(initial_state.get_design_reader(), requests);
         ret
     }
 
+    /// Start receiving the updates broadcasted by the main state's `ChanelReader`
+    pub fn subscribe_to_chanel_reader(&mut self, chanel: mpsc::Receiver<ChanelReaderUpdate>) {
+        self.chanel_updates = Some(chanel);
+    }
+
+    /// Return true if an update that can modify the appearance of the scene was received
+    fn read_chanel_updates(&self) -> bool {
+        let mut ret = false;
+        if let Some(chanel) = self.chanel_updates.as_ref() {
+            for update in chanel.try_iter() {
+                if let ChanelReaderUpdate::NucleotideColors(_) = update {
+                    ret = true;
+                }
+            }
+        }
+        ret
+    }
+
     /// Add a design to the scene. This creates a new `View`, a new `Data` and a new `Controller`
     fn add_design(&mut self, reader: S::Reader, requests: Arc<Mutex<dyn Requests>>) {
         let height = if self.splited {
@@ -157,6 +183,44 @@ impl<S: AppState> FlatScene<S> {
         }
     }
 
+    /// Write a PNG image of the helices and strands of the current design. The image measures
+    /// `size_mm` millimeters at a resolution of `dpi` dots per inch.
+    pub fn export_helix_map_png(
+        &mut self,
+        dpi: u32,
+        size_mm: (f32, f32),
+        path: &PathBuf,
+    ) -> std::io::Result<()> {
+        use std::io::{Error, ErrorKind};
+        let to_pixels = |mm: f32| (mm / MM_PER_INCH * dpi as f32).round();
+        let (width, height) = (to_pixels(size_mm.0), to_pixels(size_mm.1));
+        if !(width >= 1. && height >= 1.) {
+            return Err(Error::new(ErrorKind::InvalidInput, "The image is empty"));
+        }
+        if width > MAX_EXPORT_DIMENSION as f32 || height > MAX_EXPORT_DIMENSION as f32 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "The image would measure {}x{} pixels, the maximum is {}x{}",
+                    width, height, MAX_EXPORT_DIMENSION, MAX_EXPORT_DIMENSION
+                ),
+            ));
+        }
+        let size = PhySize::new(width as u32, height as u32);
+        let (view, data) = self
+            .view
+            .get(self.selected_design)
+            .zip(self.data.get(self.selected_design))
+            .ok_or_else(|| Error::new(ErrorKind::Other, "No design to export"))?;
+        let rectangle = data.borrow().get_fit_rectangle();
+        let pixels = view.borrow_mut().render_to_rgba(size, rectangle)?;
+        let image = image::RgbaImage::from_raw(size.width, size.height, pixels)
+            .ok_or_else(|| Error::new(ErrorKind::Other, "Could not read the rendered image"))?;
+        image
+            .save_with_format(path, image::ImageFormat::Png)
+            .map_err(|e| Error::new(ErrorKind::Other, e.to_string()))
+    }
+
     /// This function must be called when the drawing area of the flatscene is modified
     fn resize(&mut self, window_size: PhySize, area: DrawArea) {
         self.window_size = window_size;
@@ -199,6 +263,7 @@ impl<S: AppState> FlatScene<S> {
                         design_id: self.selected_design,
                     }))
             }
+            Consequence::GeneralXover(source, target) => self.attempt_xover(source, target),
             Consequence::Cut(nucl) => {
                 let strand_id = self.data[self.selected_design].borrow().get_strand_id(nucl);
                 if let Some(strand_id) = strand_id {
@@ -296,6 +361,12 @@ impl<S: AppState> FlatScene<S> {
             Consequence::Built => {
                 self.requests.lock().unwrap().suspend_op();
             }
+            Consequence::BuiltWithXover(source, target) => {
+                // The cross-over is applied before finishing the building so that both are undone
+                // at once
+                self.attempt_xover(source, target);
+                self.requests.lock().unwrap().suspend_op();
+            }
             Consequence::FlipVisibility(helix, apply_to_other) => self.data[self.selected_design]
                 .borrow_mut()
                 .flip_visibility(helix, apply_to_other),
@@ -358,7 +429,9 @@ impl<S: AppState> FlatScene<S> {
                 self.requests.lock().unwrap().new_selection(selection);
             }
             Consequence::ClearSelection => {
-                self.requests.lock().unwrap().new_selection(vec![]);
+                if !self.old_state.is_selection_locked() {
+                    self.requests.lock().unwrap().new_selection(vec![]);
+                }
             }
             Consequence::DoubleClick(click) => {
                 let selection = self.data[self.selected_design]
@@ -371,6 +444,7 @@ impl<S: AppState> FlatScene<S> {
                         .request_center_selection(selection, AppId::FlatScene)
                 }
             }
+            Consequence::OpenContextMenu => self.requests.lock().unwrap().open_context_menu(),
             Consequence::Helix2DMvmtEnded => self.requests.lock().unwrap().suspend_op(),
             Consequence::Snap {
                 pivots,
@@ -458,7 +532,8 @@ impl<S: AppState> FlatScene<S> {
                 .borrow_mut()
                 .perform_update(&new_state, &self.old_state);
             self.old_state = new_state;
-            let ret = view.borrow().needs_redraw();
+            let colors_changed = self.read_chanel_updates();
+            let ret = view.borrow().needs_redraw() || colors_changed;
             if ret {
                 log::debug!("Flatscene requests redraw");
             }
@@ -479,6 +554,19 @@ impl<S: AppState> FlatScene<S> {
         }
     }
 
+    /// Scroll the view so that the element that was last selected in the 3D view is visible
+    fn sync_3d_selection(&mut self, selection: Vec<Selection>) {
+        if let Some(last) = selection.last() {
+            let flat_selection = {
+                let data = self.data[self.selected_design].borrow();
+                data.xover_to_nuclpair(data.convert_to_flat(*last))
+            };
+            self.view[self.selected_design]
+                .borrow_mut()
+                .make_selection_visible(flat_selection);
+        }
+    }
+
     fn split_and_center(&mut self, n1: FlatNucl, n2: FlatNucl) {
         self.splited = true;
         for v in self.view.iter_mut() {
@@ -507,6 +595,28 @@ impl<S: AppState> Application for FlatScene<S> {
                     v.borrow_mut().set_show_torsion(b);
                 }
             }
+            Notification::ShowLocalTwist(_) => (),
+            Notification::AnalysisColoring(coloring) => {
+                for d in self.data.iter() {
+                    d.borrow_mut().set_analysis_coloring(coloring);
+                }
+            }
+            Notification::ShowHelixNumbers(_) => (),
+            Notification::ShowRuler(b) => {
+                for v in self.view.iter() {
+                    v.borrow_mut().set_show_ruler(b);
+                }
+            }
+            Notification::RulerParameters(parameters) => {
+                for v in self.view.iter() {
+                    v.borrow_mut().set_ruler_parameters(parameters);
+                }
+            }
+            Notification::ShowLatticeGrid(b) => {
+                for v in self.view.iter() {
+                    v.borrow_mut().set_show_lattice_grid(b);
+                }
+            }
             Notification::CameraTarget(_) => (),
             Notification::NewSensitivity(_) => (),
             Notification::ClearDesigns => (),
@@ -529,6 +639,7 @@ impl<S: AppState> Application for FlatScene<S> {
                 }
             }
             Notification::CameraRotation(_, _, _) => (),
+            Notification::TurnCamera(_, _) => (),
             Notification::ModifersChanged(modifiers) => {
                 for c in self.controller.iter_mut() {
                     c.update_modifiers(modifiers.clone())
@@ -547,10 +658,42 @@ impl<S: AppState> Application for FlatScene<S> {
             }
             Notification::RenderingMode(_) => (),
             Notification::Background3D(_) => (),
+            Notification::ColorTheme(theme) => {
+                for d in self.data.iter() {
+                    d.borrow_mut().set_color_theme(&theme);
+                }
+            }
             Notification::Fog(_) => (),
+            Notification::CenterFogOnSelection => (),
+            Notification::ResetFogCenter => (),
+            Notification::DepthOfField(_) => (),
+            Notification::FlyThrough(_) => (),
+            Notification::EyeDomeLighting(_) => (),
+            Notification::AmbientOcclusion(_) => (),
+            Notification::PostProcessingQuality(_) => (),
+            Notification::Lighting(_) => (),
+            Notification::ToneMapping(_) => (),
+            Notification::Sonification(_) => (),
+            Notification::ShowMirrorPlane(_) => (),
+            Notification::Sync3dSelection(selection) => self.sync_3d_selection(selection),
             Notification::WindowFocusLost => (),
             Notification::TeleportCamera(_, _) => (),
+            Notification::CameraFovy(_) => (),
             Notification::FlipSplitViews => self.controller[0].flip_split_views(),
+            Notification::Start2dXoverTool => self.controller[0].start_xover_tool(),
+            Notification::UndoCameraMove | Notification::RedoCameraMove => (),
+            Notification::FlashXover(n1, n2) => {
+                let flat_xover = {
+                    let data = self.data[self.selected_design].borrow();
+                    FlatNucl::from_real(&n1, data.id_map())
+                        .zip(FlatNucl::from_real(&n2, data.id_map()))
+                };
+                if let Some((flat_1, flat_2)) = flat_xover {
+                    self.view[self.selected_design]
+                        .borrow_mut()
+                        .flash_xover(flat_1, flat_2);
+                }
+            }
         }
     }
 
@@ -585,6 +728,15 @@ impl<S: AppState> Application for FlatScene<S> {
     fn is_splited(&self) -> bool {
         self.splited
     }
+
+    fn export_helix_map_png(
+        &mut self,
+        dpi: u32,
+        size_mm: (f32, f32),
+        path: &PathBuf,
+    ) -> std::io::Result<()> {
+        FlatScene::export_helix_map_png(self, dpi, size_mm, path)
+    }
 }
 
 pub trait AppState: Clone {
@@ -594,12 +746,17 @@ pub trait AppState: Clone {
     fn get_selection(&self) -> &[Selection];
     fn get_candidates(&self) -> &[Selection];
     fn get_selection_mode(&self) -> SelectionMode;
+    /// True if clicking on empty space must not clear the selection
+    fn is_selection_locked(&self) -> bool;
     fn get_design_reader(&self) -> Self::Reader;
     fn get_strand_builders(&self) -> &[StrandBuilder];
     fn design_was_updated(&self, other: &Self) -> bool;
     fn is_changing_color(&self) -> bool;
     fn is_pasting(&self) -> bool;
     fn get_building_state(&self) -> Option<StrandBuildingStatus>;
+    /// The visible ranges of the helices set by the user, identified by their real identifier
+    fn get_helix_2d_ranges(&self) -> &BTreeMap<usize, (isize, isize)>;
+    fn helix_2d_ranges_were_updated(&self, other: &Self) -> bool;
 }
 
 use ultraviolet::Isometry2;
@@ -612,9 +769,20 @@ pub trait Requests {
     fn request_centering_on_nucl(&mut self, nucl: Nucl, design_id: usize);
     fn update_opperation(&mut self, operation: Arc<dyn Operation>);
     fn set_isometry(&mut self, helix: usize, isometry: Isometry2);
+    /// Set the order in which the helices are displayed, and move the helices to their new row
+    fn set_helix_display_order(&mut self, order: Vec<usize>, isometries: Vec<(usize, Isometry2)>);
     fn set_visibility_helix(&mut self, helix: usize, visibility: bool);
     fn flip_group(&mut self, helix: usize);
     fn suspend_op(&mut self);
     fn apply_design_operation(&mut self, op: DesignOperation);
     fn set_paste_candidate(&mut self, candidate: Option<Nucl>);
+    /// Set the visible ranges of some helices, identified by their real identifier, in an undoable
+    /// way. `old` are the ranges of these helices before the modification.
+    fn set_helix_2d_ranges(
+        &mut self,
+        old: BTreeMap<usize, (isize, isize)>,
+        new: BTreeMap<usize, (isize, isize)>,
+    );
+    /// Show the actions that can be applied to the current selection in a floating menu
+    fn open_context_menu(&mut self);
 }
@@ -32,7 +32,7 @@ use std::cell::RefCell;
 use ultraviolet::Vec2;
 
 mod automata;
-use automata::{ControllerState, NormalState, Transition};
+use automata::{AddingXover, ControllerState, NormalState, Transition};
 
 pub struct Controller<S: AppState> {
     #[allow(dead_code)]
@@ -55,6 +55,8 @@ pub enum Consequence {
     GlobalsChanged,
     Nothing,
     Xover(FlatNucl, FlatNucl),
+    /// A cross-over between two nucleotides that are not necessarily strand extremities
+    GeneralXover(FlatNucl, FlatNucl),
     Cut(FlatNucl),
     CutCross(FlatNucl, FlatNucl),
     FreeEnd(Option<FreeEnd>),
@@ -65,6 +67,8 @@ pub enum Consequence {
     RmHelix(FlatHelix),
     FlipVisibility(FlatHelix, bool),
     Built,
+    /// The strand being built must be linked by a cross-over to a nucleotide of an other helix
+    BuiltWithXover(FlatNucl, FlatNucl),
     FlipGroup(FlatHelix),
     FollowingSuggestion(FlatNucl, bool),
     Centering(FlatNucl, bool),
@@ -87,6 +91,8 @@ pub enum Consequence {
         center: Vec2,
         angle: f32,
     },
+    /// Ctrl+right click in the flat scene
+    OpenContextMenu,
 }
 
 impl<S: AppState> Controller<S> {
@@ -231,7 +237,8 @@ impl<S: AppState> Controller<S> {
         } else {
             None
         };
-        self.view.borrow_mut().set_hovered_nucl(nucl);
+        let strand_name = nucl.and_then(|n| self.data.borrow().get_name_of_strand_containing(n));
+        self.view.borrow_mut().set_hovered_nucl(nucl, strand_name);
     }
 
     pub fn process_keyboard(&self, event: &WindowEvent) {
@@ -298,6 +305,17 @@ impl<S: AppState> Controller<S> {
         transition.consequences
     }
 
+    /// Enter the mode in which cross-overs are drawn by clicking on their two extremities
+    pub fn start_xover_tool(&mut self) {
+        let mouse_position = PhysicalPosition::new(-1., -1.);
+        self.state.borrow().transition_from(&self);
+        self.state = RefCell::new(Box::new(AddingXover {
+            mouse_position,
+            source: None,
+        }));
+        self.state.borrow().transition_to(&self);
+    }
+
     pub fn flip_split_views(&mut self) {
         self.camera_bottom
             .borrow_mut()
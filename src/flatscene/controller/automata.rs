@@ -97,6 +97,13 @@ impl<S: AppState> ControllerState<S> for NormalState {
                 })),
                 consequences: Consequence::Nothing,
             },
+            WindowEvent::MouseInput {
+                button: MouseButton::Right,
+                state: ElementState::Pressed,
+                ..
+            } if ctrl(&controller.modifiers) => {
+                Transition::consequence(Consequence::OpenContextMenu)
+            }
             WindowEvent::MouseInput {
                 button: MouseButton::Right,
                 state: ElementState::Pressed,
@@ -184,6 +191,10 @@ impl<S: AppState> ControllerState<S> for NormalState {
                         new_state: Some(Box::new(TranslatingHandle::new(h_id, handle, position))),
                         consequences: Consequence::Nothing,
                     },
+                    ClickResult::ReorderHandle { h_id } => Transition {
+                        new_state: Some(Box::new(ReorderingHelix { h_id })),
+                        consequences: Consequence::Nothing,
+                    },
                     ClickResult::Nucl(nucl)
                         if controller.data.borrow().is_suggested(&nucl)
                             && ctrl(&controller.modifiers) =>
@@ -236,6 +247,8 @@ impl<S: AppState> ControllerState<S> for NormalState {
                                             mouse_position: self.mouse_position,
                                             nucl,
                                             can_attach: false,
+                                            moving_end: nucl,
+                                            crossing_to: None,
                                         })),
                                         consequences: Consequence::InitBuilding(nucl),
                                     }
@@ -695,6 +708,10 @@ impl<S: AppState> ControllerState<S> for ReleasedPivot {
                         new_state: Some(Box::new(TranslatingHandle::new(h_id, handle, position))),
                         consequences: Consequence::Nothing,
                     },
+                    ClickResult::ReorderHandle { h_id } => Transition {
+                        new_state: Some(Box::new(ReorderingHelix { h_id })),
+                        consequences: Consequence::Nothing,
+                    },
                     ClickResult::Nucl(nucl)
                         if controller.data.borrow().can_make_auto_xover(nucl).is_some() =>
                     {
@@ -733,6 +750,8 @@ impl<S: AppState> ControllerState<S> for ReleasedPivot {
                                             mouse_position: self.mouse_position,
                                             nucl,
                                             can_attach: false,
+                                            moving_end: nucl,
+                                            crossing_to: None,
                                         })),
                                         consequences: Consequence::InitBuilding(nucl),
                                     }
@@ -1494,6 +1513,11 @@ impl<S: AppState> ControllerState<S> for InitBuilding {
                                         mouse_position: self.mouse_position,
                                         nucl: self.nucl,
                                         can_attach: true,
+                                        moving_end: FlatNucl {
+                                            position,
+                                            ..self.nucl
+                                        },
+                                        crossing_to: None,
                                     })),
                                     consequences: Consequence::MoveBuilders(position),
                                 }
@@ -1716,11 +1740,16 @@ struct Building {
     mouse_position: PhysicalPosition<f64>,
     nucl: FlatNucl,
     can_attach: bool,
+    /// The nucleotide at the moving end of the builder
+    moving_end: FlatNucl,
+    /// A nucleotide of a neighbouring helix that will be linked to the moving end by a
+    /// cross-over when the mouse button is released
+    crossing_to: Option<FlatNucl>,
 }
 
 impl<S: AppState> ControllerState<S> for Building {
-    fn transition_from(&self, _controller: &Controller<S>) {
-        ()
+    fn transition_from(&self, controller: &Controller<S>) {
+        controller.view.borrow_mut().set_xover_rubber_band(None);
     }
 
     fn transition_to(&self, _controller: &Controller<S>) {
@@ -1751,6 +1780,14 @@ impl<S: AppState> ControllerState<S> for Building {
                 if *state == ElementState::Pressed {
                     return Transition::nothing();
                 }
+                if let Some(target) = self.crossing_to {
+                    return Transition {
+                        new_state: Some(Box::new(NormalState {
+                            mouse_position: self.mouse_position,
+                        })),
+                        consequences: Consequence::BuiltWithXover(self.moving_end, target),
+                    };
+                }
                 if self.can_attach {
                     if let Some(attachement) =
                         controller.data.borrow().attachable_neighbour(self.nucl)
@@ -1776,6 +1813,32 @@ impl<S: AppState> ControllerState<S> for Building {
                     .get_camera(position.y)
                     .borrow()
                     .screen_to_world(self.mouse_position.x as f32, self.mouse_position.y as f32);
+                let click_result =
+                    controller
+                        .data
+                        .borrow()
+                        .get_click(x, y, &controller.get_camera(position.y));
+                let crossing_to = match click_result {
+                    ClickResult::Nucl(target)
+                        if controller
+                            .data
+                            .borrow()
+                            .can_cross_while_building(self.moving_end, target) =>
+                    {
+                        Some(target)
+                    }
+                    _ => None,
+                };
+                if crossing_to != self.crossing_to {
+                    self.crossing_to = crossing_to;
+                    controller.view.borrow_mut().set_xover_rubber_band(
+                        crossing_to.map(|_| (self.moving_end, Vec2::new(x, y))),
+                    );
+                }
+                if crossing_to.is_some() {
+                    // The builder stays where it is while the cross-over is proposed
+                    return Transition::nothing();
+                }
                 let nucl =
                     controller
                         .data
@@ -1788,6 +1851,7 @@ impl<S: AppState> ControllerState<S> for Building {
                     FlatNucl {
                         helix, position, ..
                     } if helix == self.nucl.helix => {
+                        self.moving_end.position = position;
                         controller.data.borrow_mut().notify_update();
                         Transition::consequence(Consequence::MoveBuilders(position))
                     }
@@ -2317,6 +2381,8 @@ impl<S: AppState> ControllerState<S> for FollowingSuggestion {
                                     mouse_position: self.mouse_position,
                                     nucl: self.nucl,
                                     can_attach: false,
+                                    moving_end: self.nucl,
+                                    crossing_to: None,
                                 })),
                                 consequences: Consequence::InitBuilding(self.nucl),
                             }
@@ -3039,6 +3105,7 @@ impl<S: AppState> ControllerState<S> for TranslatingHandle {
                         .borrow_mut()
                         .auto_redim_helix(self.h_id, self.handle)
                 }
+                controller.data.borrow_mut().end_layout_change();
                 Transition {
                     new_state: Some(Box::new(NormalState {
                         mouse_position: position,
@@ -3088,6 +3155,67 @@ impl<S: AppState> ControllerState<S> for TranslatingHandle {
     }
 }
 
+/// The user is dragging the handle of a helix to move it to an other row of the 2D view
+struct ReorderingHelix {
+    h_id: FlatHelix,
+}
+
+impl<S: AppState> ControllerState<S> for ReorderingHelix {
+    fn display(&self) -> String {
+        String::from("Reordering helix state")
+    }
+    fn input(
+        &mut self,
+        event: &WindowEvent,
+        position: PhysicalPosition<f64>,
+        controller: &Controller<S>,
+        _: &S,
+    ) -> Transition<S> {
+        match event {
+            WindowEvent::MouseInput {
+                button: MouseButton::Left,
+                state: ElementState::Released,
+                ..
+            } => {
+                let (_, y) = controller
+                    .get_camera(position.y)
+                    .borrow()
+                    .screen_to_world(position.x as f32, position.y as f32);
+                controller
+                    .data
+                    .borrow()
+                    .move_helix_in_display_order(self.h_id, y);
+                Transition {
+                    new_state: Some(Box::new(NormalState {
+                        mouse_position: position,
+                    })),
+                    consequences: Consequence::Nothing,
+                }
+            }
+            WindowEvent::KeyboardInput { .. } => {
+                controller.process_keyboard(event);
+                Transition::nothing()
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                controller
+                    .get_camera(position.y)
+                    .borrow_mut()
+                    .process_scroll(delta, position);
+                Transition::nothing()
+            }
+            _ => Transition::nothing(),
+        }
+    }
+
+    fn transition_from(&self, _controller: &Controller<S>) {
+        ()
+    }
+
+    fn transition_to(&self, _controller: &Controller<S>) {
+        ()
+    }
+}
+
 fn position_difference(a: PhysicalPosition<f64>, b: PhysicalPosition<f64>) -> f64 {
     (a.x - b.x).abs().max((a.y - b.y).abs())
 }
@@ -3099,3 +3227,129 @@ fn ctrl(modifiers: &ModifiersState) -> bool {
         modifiers.ctrl()
     }
 }
+
+/// The user is drawing a cross-over by clicking on its two extremities. Once the source has been
+/// chosen, a line is drawn from it to the cursor until the target is clicked.
+pub struct AddingXover {
+    pub mouse_position: PhysicalPosition<f64>,
+    pub source: Option<FlatNucl>,
+}
+
+impl AddingXover {
+    fn cancel<S: AppState>(&self) -> Transition<S> {
+        Transition {
+            new_state: Some(Box::new(NormalState {
+                mouse_position: self.mouse_position,
+            })),
+            consequences: Consequence::Nothing,
+        }
+    }
+}
+
+impl<S: AppState> ControllerState<S> for AddingXover {
+    fn transition_from(&self, controller: &Controller<S>) {
+        controller.view.borrow_mut().set_xover_rubber_band(None);
+    }
+
+    fn transition_to(&self, _controller: &Controller<S>) {
+        ()
+    }
+
+    fn display(&self) -> String {
+        String::from("Adding Xover")
+    }
+
+    fn input(
+        &mut self,
+        event: &WindowEvent,
+        position: PhysicalPosition<f64>,
+        controller: &Controller<S>,
+        _: &S,
+    ) -> Transition<S> {
+        match event {
+            WindowEvent::MouseInput {
+                button: MouseButton::Left,
+                state: ElementState::Pressed,
+                ..
+            } => {
+                self.mouse_position = position;
+                let (x, y) = controller
+                    .get_camera(position.y)
+                    .borrow()
+                    .screen_to_world(position.x as f32, position.y as f32);
+                let click_result =
+                    controller
+                        .data
+                        .borrow()
+                        .get_click(x, y, &controller.get_camera(position.y));
+                match (self.source, click_result) {
+                    (None, ClickResult::Nucl(nucl)) if controller.data.borrow().has_nucl(nucl) => {
+                        self.source = Some(nucl);
+                        controller
+                            .view
+                            .borrow_mut()
+                            .set_xover_rubber_band(Some((nucl, Vec2::new(x, y))));
+                        Transition::nothing()
+                    }
+                    (Some(source), ClickResult::Nucl(target))
+                        if controller
+                            .data
+                            .borrow()
+                            .can_make_general_xover(source, target) =>
+                    {
+                        Transition {
+                            new_state: Some(Box::new(NormalState {
+                                mouse_position: self.mouse_position,
+                            })),
+                            consequences: Consequence::GeneralXover(source, target),
+                        }
+                    }
+                    // Clicking on a nucleotide that cannot be the target of the cross-over does
+                    // nothing, so that the user can try again.
+                    (_, ClickResult::Nucl(_)) => Transition::nothing(),
+                    _ => self.cancel(),
+                }
+            }
+            WindowEvent::MouseInput {
+                button: MouseButton::Right,
+                state: ElementState::Pressed,
+                ..
+            } => self.cancel(),
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        virtual_keycode: Some(VirtualKeyCode::Escape),
+                        state: ElementState::Pressed,
+                        ..
+                    },
+                ..
+            } => self.cancel(),
+            WindowEvent::CursorMoved { .. } => {
+                self.mouse_position = position;
+                if let Some(source) = self.source {
+                    let (x, y) = controller
+                        .get_camera(position.y)
+                        .borrow()
+                        .screen_to_world(position.x as f32, position.y as f32);
+                    controller
+                        .view
+                        .borrow_mut()
+                        .set_xover_rubber_band(Some((source, Vec2::new(x, y))));
+                }
+                Transition::nothing()
+            }
+            WindowEvent::KeyboardInput { .. } => {
+                controller.process_keyboard(event);
+                Transition::nothing()
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                controller
+                    .get_camera(position.y)
+                    .borrow_mut()
+                    .process_scroll(delta, self.mouse_position);
+                Transition::nothing()
+            }
+            _ => Transition::nothing(),
+        }
+    }
+}
@@ -20,12 +20,15 @@ use super::{
     Requests, ViewPtr,
 };
 use ensnano_design::Nucl;
-use ensnano_interactor::{Selection, SelectionMode};
+use ensnano_interactor::{
+    graphics::{AnalysisColoring, ColorTheme},
+    Selection, SelectionMode,
+};
 use std::sync::{Arc, Mutex};
 use ultraviolet::Vec2;
 
 mod helix;
-pub use helix::{GpuVertex, Helix, HelixHandle, HelixModel, Shift};
+pub use helix::{GpuVertex, Helix, HelixHandle, HelixModel, Shift, REORDER_HANDLE_CHAR};
 mod strand;
 pub use strand::{FreeEnd, Strand, StrandVertex};
 mod design;
@@ -50,6 +53,16 @@ pub struct Data {
     id: u32,
     requests: Arc<Mutex<dyn Requests>>,
     last_click: LastClick,
+    /// The visible ranges of the helices before the modification of the layout that is being made
+    ranges_before_change: Option<BTreeMap<usize, (isize, isize)>>,
+    /// The identifiers of the helices, from the top row to the bottom row of the 2D view
+    display_order: Vec<u32>,
+    /// The analysis of the design whose result is shown around the strands
+    analysis_coloring: AnalysisColoring,
+    /// The color of the selected elements, with the transparency of highlighted elements
+    selected_color: u32,
+    /// The color of the candidate elements, with the transparency of highlighted elements
+    candidate_color: u32,
 }
 
 impl Data {
@@ -71,14 +84,42 @@ impl Data {
             id,
             requests,
             last_click: Default::default(),
+            ranges_before_change: None,
+            display_order: Vec::new(),
+            analysis_coloring: Default::default(),
+            selected_color: SELECTED_COLOR,
+            candidate_color: CANDIDATE_COLOR,
         }
     }
 
+    /// Show the result of an analysis of the design around the strands. Only the melting
+    /// temperature of the domains is shown in the 2D view.
+    pub fn set_analysis_coloring(&mut self, coloring: AnalysisColoring) {
+        self.instance_update |= self.analysis_coloring != coloring;
+        self.analysis_coloring = coloring;
+    }
+
+    /// Highlight the selected and candidate elements with the colors of `theme`
+    pub fn set_color_theme(&mut self, theme: &ColorTheme) {
+        let selected_color = HIGHLIGHT_ALPHA | theme.selection;
+        let candidate_color = HIGHLIGHT_ALPHA | theme.candidate;
+        self.instance_update |=
+            self.selected_color != selected_color || self.candidate_color != candidate_color;
+        self.selected_color = selected_color;
+        self.candidate_color = candidate_color;
+        self.view
+            .borrow_mut()
+            .set_highlight_colors(selected_color, candidate_color);
+    }
+
     pub fn perform_update<S: AppState>(&mut self, new_state: &S, old_state: &S) {
         if self.instance_reset {
             self.view.borrow_mut().reset();
             self.instance_reset = false;
         }
+        if new_state.helix_2d_ranges_were_updated(old_state) {
+            self.set_visible_ranges(new_state.get_helix_2d_ranges());
+        }
         if new_state.design_was_updated(old_state)
             || new_state.selection_was_updated(old_state)
             || new_state.candidate_was_updated(old_state)
@@ -95,6 +136,14 @@ impl Data {
             self.view
                 .borrow_mut()
                 .update_pasted_strand(self.design.get_pasted_strand(), &self.helices);
+            let design_rectangle = self.get_fit_rectangle();
+            self.view.borrow_mut().update_lattice_grid(
+                self.design.get_lattice_grid(),
+                Vec2::new(
+                    design_rectangle.min_x.unwrap_or(0.),
+                    design_rectangle.min_y.unwrap_or(0.),
+                ),
+            );
             self.update_highlight(new_state);
             self.update_strand_building_info(new_state.get_building_state());
         }
@@ -177,17 +226,20 @@ impl Data {
         let mut candidate_highlight = Vec::new();
         for s in self.design.get_strands().iter() {
             if selected_strands.contains(&s.id) {
-                selection_highlight.push(s.highlighted(SELECTED_COLOR));
+                selection_highlight.push(s.highlighted(self.selected_color));
             }
             if candidate_strands.contains(&s.id) {
-                candidate_highlight.push(s.highlighted(CANDIDATE_COLOR));
+                candidate_highlight.push(s.highlighted(self.candidate_color));
             }
         }
         for xover in selected_xovers.iter() {
-            selection_highlight.push(self.design.strand_from_xover(xover, SELECTED_COLOR));
+            selection_highlight.push(self.design.strand_from_xover(xover, self.selected_color));
         }
         for xover in candidate_xovers.iter() {
-            candidate_highlight.push(self.design.strand_from_xover(xover, CANDIDATE_COLOR));
+            candidate_highlight.push(self.design.strand_from_xover(xover, self.candidate_color));
+        }
+        if self.analysis_coloring == AnalysisColoring::MeltingTemperature {
+            selection_highlight.extend(self.design.melting_temperature_strands());
         }
         self.view
             .borrow_mut()
@@ -243,6 +295,7 @@ impl Data {
                 self.nb_helices_created += 1;
             }
         }
+        self.update_display_order(design.get_helix_display_order());
         let suggestions = self.design.suggestions();
         self.update_suggestion(&suggestions);
         self.view
@@ -256,6 +309,80 @@ impl Data {
             .update_maps(design.get_group_map(), design.get_basis_map());
     }
 
+    /// Read the display order of the design, without the deleted helices, and insert the helices
+    /// that are not in it according to their position in the 2D view.
+    fn update_display_order(&mut self, design_order: Vec<usize>) {
+        let helices = &self.helices;
+        self.display_order = design_order.into_iter().map(|id| id as u32).collect();
+        self.display_order
+            .retain(|id| helices.iter().any(|h| h.real_id == *id as usize));
+        let mut new_helices: Vec<&Helix> = helices
+            .iter()
+            .filter(|h| !self.display_order.contains(&(h.real_id as u32)))
+            .collect();
+        new_helices.sort_by(|a, b| {
+            a.isometry
+                .translation
+                .y
+                .partial_cmp(&b.isometry.translation.y)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        self.display_order
+            .extend(new_helices.iter().map(|h| h.real_id as u32));
+    }
+
+    /// Move `helix` to the row of the display order that is the closest to the ordinate `y`.
+    ///
+    /// The rows keep their position in the 2D view, the helices are moved from one row to
+    /// another. This does not modify the identifier of the helices. The new order is saved in
+    /// the design by an undoable operation.
+    pub fn move_helix_in_display_order(&self, helix: FlatHelix, y: f32) {
+        let rows: Vec<FlatIdx> = self
+            .display_order
+            .iter()
+            .filter_map(|id| FlatHelix::from_real(*id as usize, self.id_map()))
+            .map(|h| h.flat)
+            .collect();
+        let row_center = |flat: FlatIdx| self.helices[flat].isometry.translation.y + 1.;
+        let target = rows
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| {
+                (row_center(**a) - y)
+                    .abs()
+                    .partial_cmp(&(row_center(**b) - y).abs())
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .map(|(i, _)| i);
+        let source = rows.iter().position(|flat| *flat == helix.flat);
+        if let Some((source, target)) = source.zip(target) {
+            if source == target {
+                return;
+            }
+            let translations: Vec<Vec2> = rows
+                .iter()
+                .map(|flat| self.helices[*flat].isometry.translation)
+                .collect();
+            let mut new_rows = rows.clone();
+            let moved = new_rows.remove(source);
+            new_rows.insert(target, moved);
+            let mut isometries = Vec::new();
+            for (flat, translation) in new_rows.iter().zip(translations.iter()) {
+                let h = &self.helices[*flat];
+                if h.isometry.translation != *translation {
+                    let mut isometry = h.isometry;
+                    isometry.translation = *translation;
+                    isometries.push((h.real_id, isometry));
+                }
+            }
+            let order = new_rows
+                .iter()
+                .map(|flat| self.helices[*flat].real_id)
+                .collect();
+            self.design.set_helix_display_order(order, isometries);
+        }
+    }
+
     fn update_suggestion(&mut self, suggestion: &[(FlatNucl, FlatNucl)]) {
         self.suggestions.clear();
         for (n1, n2) in suggestion.iter() {
@@ -271,6 +398,11 @@ impl Data {
                 return ClickResult::CircleWidget { translation_pivot };
             }
         }
+        for h in self.helices.iter() {
+            if h.click_on_reorder_handle(x, y) {
+                return ClickResult::ReorderHandle { h_id: h.flat_id };
+            }
+        }
         for h in self.helices.iter() {
             if let Some(handle) = h.click_on_handle(x, y) {
                 return ClickResult::HelixHandle {
@@ -398,18 +530,21 @@ impl Data {
     }*/
 
     pub fn move_handle(&mut self, helix: FlatHelix, handle: HelixHandle, position: Vec2) {
+        self.begin_layout_change();
         let (left, right) = self.helices[helix.flat].move_handle(handle, position);
         self.design.update_helix(helix, left, right);
         self.instance_update = true;
     }
 
     pub fn auto_redim_helix(&mut self, helix: FlatHelix, handle: HelixHandle) {
+        self.begin_layout_change();
         let (left, right) = self.helices[helix.flat].reset_handle(handle);
         self.design.update_helix(helix, left, right);
     }
 
     /// Shrink the selected helices if selection is Some, or all helices if selection is None.
     pub fn redim_helices(&mut self, selection: Option<&[Selection]>) {
+        self.begin_layout_change();
         if let Some(selection) = selection {
             let mut ids = Vec::new();
             for s in selection.iter() {
@@ -431,6 +566,48 @@ impl Data {
                 self.design.update_helix(h.flat_id, left, right);
             }
         }
+        self.end_layout_change();
+        self.notify_update();
+    }
+
+    /// The visible range of each helix, identified by its real identifier
+    fn visible_ranges(&self) -> BTreeMap<usize, (isize, isize)> {
+        self.helices
+            .iter()
+            .map(|h| (h.flat_id.real, (h.get_left(), h.get_right())))
+            .collect()
+    }
+
+    /// Remember the visible ranges of the helices, so that the modification that is about to be
+    /// made can be undone. Does nothing if a modification is already in progress.
+    pub fn begin_layout_change(&mut self) {
+        if self.ranges_before_change.is_none() {
+            self.ranges_before_change = Some(self.visible_ranges());
+        }
+    }
+
+    /// Record the modification of the visible ranges of the helices made since the last call to
+    /// `begin_layout_change` in the application state, so that it can be undone.
+    pub fn end_layout_change(&mut self) {
+        if let Some(mut old) = self.ranges_before_change.take() {
+            let mut new = self.visible_ranges();
+            new.retain(|h_id, range| old.get(h_id) != Some(range));
+            old.retain(|h_id, _| new.contains_key(h_id));
+            if !new.is_empty() {
+                self.requests.lock().unwrap().set_helix_2d_ranges(old, new);
+            }
+        }
+    }
+
+    fn set_visible_ranges(&mut self, ranges: &BTreeMap<usize, (isize, isize)>) {
+        for (h_id, (left, right)) in ranges.iter() {
+            if let Some(helix) = FlatHelix::from_real(*h_id, self.id_map()) {
+                if let Some(h) = self.helices.get_mut(helix.flat.0) {
+                    h.set_range(*left, *right);
+                    self.design.update_helix(helix, *left, *right);
+                }
+            }
+        }
         self.notify_update();
     }
 
@@ -515,6 +692,26 @@ impl Data {
         can_merge && self.design.has_nucl(to.to_real())
     }
 
+    /// Return true iff a cross-over can be made between two nucleotides that are not necessarily
+    /// strand extremities
+    pub fn can_make_general_xover(&self, source: FlatNucl, target: FlatNucl) -> bool {
+        source.helix != target.helix
+            && source.forward != target.forward
+            && self.has_nucl(source)
+            && self.has_nucl(target)
+    }
+
+    /// Return true iff the moving end of a strand builder at `source` can be linked to `target`
+    /// by a cross-over
+    pub fn can_cross_while_building(&self, source: FlatNucl, target: FlatNucl) -> bool {
+        self.can_make_general_xover(source, target)
+            && self
+                .design
+                .get_dist(source.to_real(), target.to_real())
+                .map(|dist| dist < BUILDER_XOVER_MAX_DIST)
+                .unwrap_or(false)
+    }
+
     pub fn has_nucl(&self, nucl: FlatNucl) -> bool {
         self.design.has_nucl(nucl.to_real())
     }
@@ -524,6 +721,12 @@ impl Data {
         self.design.get_strand_id(nucl)
     }
 
+    /// Return the name of the strand containing `nucl`, if this strand has a name
+    pub fn get_name_of_strand_containing(&self, nucl: FlatNucl) -> Option<String> {
+        let s_id = self.get_strand_id(nucl)?;
+        self.design.get_strand_name(s_id)
+    }
+
     /// Return the strand ids and the value of target_3prime to construct a CrossCut operation
     pub fn cut_cross(&self, from: FlatNucl, to: FlatNucl) -> Option<(usize, usize, bool)> {
         // After the cut, the target will be the 3' end of the merge iff the source nucl is the
@@ -853,6 +1056,7 @@ impl Data {
                 }
             }
             ClickResult::HelixHandle { .. } => None,
+            ClickResult::ReorderHandle { .. } => None,
             ClickResult::Nothing => None,
         }
     }
@@ -887,6 +1091,26 @@ impl Data {
                         }
                     }
                 }
+                SelectionMode::StrandEnd(end) => {
+                    if let Some(nucl) = self.design.get_strand_end(nucl.to_real(), end) {
+                        let selection = Selection::Nucleotide(self.id, nucl);
+                        if let Some(pos) = new_selection.iter().position(|x| *x == selection) {
+                            new_selection.remove(pos);
+                        } else {
+                            new_selection.push(selection);
+                        }
+                    }
+                }
+                SelectionMode::Domain => {
+                    if let Some(domain_id) = self.design.get_scaffold_domain_id(nucl.to_real()) {
+                        let selection = Selection::Domain(self.id, domain_id as u32);
+                        if let Some(pos) = new_selection.iter().position(|x| *x == selection) {
+                            new_selection.remove(pos);
+                        } else {
+                            new_selection.push(selection);
+                        }
+                    }
+                }
                 _ => {
                     self.last_click.click_on(nucl);
                     let mut selection_pool = vec![Selection::Nucleotide(self.id, nucl.to_real())];
@@ -909,6 +1133,7 @@ impl Data {
                 }
             },
             ClickResult::HelixHandle { .. } => (),
+            ClickResult::ReorderHandle { .. } => (),
             ClickResult::Nothing => (),
         }
     }
@@ -996,6 +1221,14 @@ impl Data {
                 SelectionMode::Nucleotide => {
                     Some(Selection::Nucleotide(phantom.design_id, phantom.to_nucl()))
                 }
+                SelectionMode::StrandEnd(end) => self
+                    .design
+                    .get_strand_end(phantom.to_nucl(), end)
+                    .map(|nucl| Selection::Nucleotide(phantom.design_id, nucl)),
+                SelectionMode::Domain => self
+                    .design
+                    .get_scaffold_domain_id(phantom.to_nucl())
+                    .map(|d_id| Selection::Domain(phantom.design_id, d_id as u32)),
             }
         } else {
             None
@@ -1071,6 +1304,10 @@ pub enum ClickResult {
         h_id: FlatHelix,
         handle: HelixHandle,
     },
+    /// The handle used to move a helix in the display order
+    ReorderHandle {
+        h_id: FlatHelix,
+    },
     Nothing,
 }
 
@@ -18,11 +18,13 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::sync::{Arc, Mutex};
 
+use super::super::view::{LatticeGrid, LatticeKind};
 use super::super::{FlatHelix, FlatIdx, FlatNucl, Requests};
 use super::{Flat, HelixVec, Nucl, Strand};
 use ahash::RandomState;
+use ensnano_design::grid::GridTypeDescr;
 use ensnano_design::{Extremity, Helix as DesignHelix, Strand as StrandDesign};
-use ensnano_interactor::{torsion::Torsion, Referential};
+use ensnano_interactor::{torsion::Torsion, Referential, StrandEnd};
 use ultraviolet::{Isometry2, Rotor2, Vec2, Vec3};
 
 pub(super) struct Design2d {
@@ -262,6 +264,13 @@ impl Design2d {
             .set_isometry(helix.real, isometry);
     }
 
+    pub fn set_helix_display_order(&self, order: Vec<usize>, isometries: Vec<(usize, Isometry2)>) {
+        self.requests
+            .lock()
+            .unwrap()
+            .set_helix_display_order(order, isometries);
+    }
+
     pub fn flip_visibility(&mut self, flat_helix: FlatHelix, apply_to_other: bool) {
         if apply_to_other {
             let visibility = if self.last_flip_other == Some(flat_helix) {
@@ -324,6 +333,13 @@ impl Design2d {
         self.design.get_id_of_strand_containing_nucl(&nucl)
     }
 
+    pub fn get_strand_name(&self, s_id: usize) -> Option<String> {
+        self.design
+            .get_raw_strand(s_id)?
+            .name
+            .map(|name| name.into_owned())
+    }
+
     pub fn get_dist(&self, nucl1: Nucl, nucl2: Nucl) -> Option<f32> {
         let pos1 = self
             .design
@@ -366,10 +382,65 @@ impl Design2d {
         Strand::new(0, flat_nucls, vec![], 0, false).highlighted(color)
     }
 
+    /// Return, for each domain of the staples, a highlighted strand whose color indicates the
+    /// melting temperature of the domain
+    pub fn melting_temperature_strands(&self) -> Vec<Strand> {
+        let temperatures = self.design.get_melting_temperatures();
+        let mut ret = Vec::new();
+        for s_id in self.design.get_all_strand_ids() {
+            let points = self.design.get_strand_points(s_id).unwrap_or_default();
+            for domain in points.chunks_exact(2) {
+                if let Some(tm) = temperatures.get(&domain[0]) {
+                    let color = crate::consts::HIGHLIGHT_ALPHA
+                        | crate::analysis::thermo::temperature_color(*tm);
+                    let flat_nucls = domain
+                        .iter()
+                        .filter_map(|n| FlatNucl::from_real(n, self.id_map()))
+                        .collect();
+                    ret.push(Strand::new(0, flat_nucls, vec![], s_id, false).highlighted(color));
+                }
+            }
+        }
+        ret
+    }
+
     pub fn get_nucl_id(&self, nucl: Nucl) -> Option<u32> {
         self.design.get_identifier_nucl(&nucl)
     }
 
+    /// Return the lattice holding the largest number of helices, if it is a square or honeycomb
+    /// lattice
+    pub fn get_lattice_grid(&self) -> Option<LatticeGrid> {
+        let mut helices_per_grid: BTreeMap<usize, Vec<(isize, isize, usize)>> = BTreeMap::new();
+        for (h_id, helix) in self.design.get_helices_map().iter() {
+            if let Some(position) = helix.grid_position.as_ref() {
+                helices_per_grid
+                    .entry(position.grid)
+                    .or_default()
+                    .push((position.x, position.y, *h_id));
+            }
+        }
+        let (g_id, helices) = helices_per_grid
+            .into_iter()
+            .max_by_key(|(_, helices)| helices.len())?;
+        let kind = match self.design.get_grid_type(g_id)? {
+            GridTypeDescr::Square => LatticeKind::Square,
+            GridTypeDescr::Honeycomb => LatticeKind::Honeycomb,
+            GridTypeDescr::Hyperboloid { .. } => return None,
+        };
+        Some(LatticeGrid { kind, helices })
+    }
+
+    /// Return the nucleotide at the `end` extremity of the strand containing `nucl`
+    pub fn get_strand_end(&self, nucl: Nucl, end: StrandEnd) -> Option<Nucl> {
+        let s_id = self.get_strand_id(nucl)?;
+        end.of_strand(&self.design.get_raw_strand(s_id)?)
+    }
+
+    pub fn get_scaffold_domain_id(&self, nucl: Nucl) -> Option<usize> {
+        self.design.get_scaffold_domain_id(&nucl)
+    }
+
     pub fn get_strand_from_eid(&self, element_id: u32) -> Option<usize> {
         self.design.get_id_of_strand_containing_elt(element_id)
     }
@@ -467,7 +538,14 @@ pub trait DesignReader: 'static {
     fn get_id_of_of_helix_containing_elt(&self, e_id: u32) -> Option<usize>;
     fn get_xover_with_id(&self, xover_id: usize) -> Option<(Nucl, Nucl)>;
     fn get_helices_on_grid(&self, g_id: usize) -> Option<HashSet<usize>>;
+    fn get_grid_type(&self, g_id: usize) -> Option<GridTypeDescr>;
     fn get_basis_map(&self) -> Arc<HashMap<Nucl, char, RandomState>>;
     fn get_group_map(&self) -> Arc<BTreeMap<usize, bool>>;
+    /// The identifiers of the helices, from the top row to the bottom row of the 2D view
+    fn get_helix_display_order(&self) -> Vec<usize>;
     fn get_strand_ends(&self) -> Vec<Nucl>;
+    /// Return the identifier of the scaffold domain that contains `nucl` or its complement
+    fn get_scaffold_domain_id(&self, nucl: &Nucl) -> Option<usize>;
+    /// Return, for each nucleotide of the staples, the melting temperature of its domain
+    fn get_melting_temperatures(&self) -> HashMap<Nucl, f32>;
 }
@@ -15,9 +15,9 @@ ENSnano, a 3d graphical application for DNA nanostructures.
     You should have received a copy of the GNU General Public License
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
-use super::super::view::{CharInstance, CircleInstance, InsertionInstance};
+use super::super::view::{CharInstance, CircleInstance, InsertionInstance, RulerParameters};
 use super::super::{CameraPtr, Flat, FlatHelix, FlatIdx};
-use super::{FlatNucl, Helix2d};
+use super::{FlatNucl, Helix2d, StrandVertex};
 use crate::consts::*;
 use crate::flatscene::view::EditionInfo;
 use crate::utils::instance::Instance;
@@ -35,9 +35,20 @@ use std::sync::Arc;
 use ultraviolet::{Isometry2, Mat2, Rotor2, Vec2, Vec4};
 
 type Vertices = lyon::tessellation::VertexBuffers<GpuVertex, u16>;
+type StrandVertices = lyon::tessellation::VertexBuffers<StrandVertex, u16>;
 
 const CIRCLE_WIDGET_RADIUS: f32 = 1.5;
+/// Distance between the left end of the helix and the center of its reordering handle. The
+/// handle is far enough to never overlap the circle widget, even when it is enlarged.
+const REORDER_HANDLE_SHIFT: f32 = 2. + 4. * CIRCLE_WIDGET_RADIUS;
+const REORDER_HANDLE_HALF_WIDTH: f32 = 0.75;
+pub const REORDER_HANDLE_CHAR: char = '≡';
 const ZOOM_THRESHOLD: f32 = 7.0;
+/// Above this zoom level, the nucleotides are large enough to display their basis
+const SEQUENCE_ZOOM_THRESHOLD: f32 = 8.0;
+/// Depth shift of the ruler bars. The background of the helices is drawn at depth +0.5e-3 and
+/// their border at depth +0.25e-3.
+const RULER_DEPTH_SHIFT: f32 = 0.4e-3;
 
 #[derive(Debug, Clone)]
 pub struct Helix {
@@ -178,6 +189,41 @@ impl Helix {
         vertices
     }
 
+    /// Return the vertices of the ruler bars that tint the columns of the helix whose position is
+    /// a multiple of one of the ruler's periods.
+    pub fn ruler_vertices(&self, ruler: &RulerParameters) -> StrandVertices {
+        let mut vertices = StrandVertices::new();
+        // The bars must be above the background of the helix but below its border
+        let depth = self.get_depth() + RULER_DEPTH_SHIFT;
+        let right = self.right.max(self.left + 1) + 1;
+        for x in self.left..right {
+            if let Some(color) = ruler.color_of_position(x) {
+                let color = Instance::color_from_au32(color);
+                let color = [color.x, color.y, color.z, color.w];
+                let first_idx = vertices.vertices.len() as u16;
+                for corner in [(0., 0.), (1., 0.), (1., 2.), (0., 2.)].iter() {
+                    let local_position = Vec2::new(x as f32 + corner.0, corner.1);
+                    let position = self
+                        .isometry
+                        .into_homogeneous_matrix()
+                        .transform_point2(self.scale * local_position);
+                    vertices
+                        .vertices
+                        .push(StrandVertex::filled(position, color, depth));
+                }
+                vertices.indices.extend_from_slice(&[
+                    first_idx,
+                    first_idx + 1,
+                    first_idx + 2,
+                    first_idx,
+                    first_idx + 2,
+                    first_idx + 3,
+                ]);
+            }
+        }
+        vertices
+    }
+
     pub fn model(&self) -> HelixModel {
         HelixModel {
             color: Instance::color_from_u32(self.color),
@@ -301,6 +347,11 @@ impl Helix {
         (self.left, self.right)
     }
 
+    pub fn set_range(&mut self, left: isize, right: isize) {
+        self.left = left;
+        self.right = right;
+    }
+
     pub fn redim_zero(&mut self) -> (isize, isize) {
         let (left, right) = (self.right - 1, self.left + 1);
         self.left = left;
@@ -328,6 +379,24 @@ impl Helix {
         }
     }
 
+    /// Return the position of the center of the handle used to change the position of the helix
+    /// in the display order.
+    pub fn reorder_handle_position(&self) -> Vec2 {
+        self.x_position(self.left as f32 - REORDER_HANDLE_SHIFT, HelixLine::Middle)
+    }
+
+    /// Return true if (x, y) is on the handle used to change the position of the helix in the
+    /// display order.
+    pub fn click_on_reorder_handle(&self, x: f32, y: f32) -> bool {
+        let click = {
+            let ret = Vec2::new(x, y);
+            let iso = self.isometry.inversed().into_homogeneous_matrix();
+            iso.transform_point2(ret)
+        };
+        let center_x = self.left as f32 - REORDER_HANDLE_SHIFT;
+        click.y > 0. && click.y < 2. && (click.x - center_x).abs() < REORDER_HANDLE_HALF_WIDTH
+    }
+
     /// Project a click on the helix's axis, and return the corresponding nucleotide
     /// Do not take the left and right bound into account.
     pub fn get_click_unbounded(&self, x: f32, y: f32) -> (isize, bool) {
@@ -667,17 +736,20 @@ impl Helix {
         &self,
         camera: &CameraPtr,
         char_map: &mut HashMap<char, Vec<CharInstance>>,
-        char_drawers: &HashMap<char, crate::utils::chars2d::CharDrawer>,
+        char_drawer: &crate::utils::chars2d::CharDrawer,
         groups: &BTreeMap<usize, bool>,
         basis_map: &HashMap<Nucl, char, RandomState>,
+        nucl_colors: &HashMap<Nucl, u32>,
         show_seq: bool,
         edition_info: &Option<EditionInfo>,
         hovered_nucl: &Option<FlatNucl>,
+        hovered_strand_name: Option<&str>,
     ) {
         let candidate_pos: Option<isize> = hovered_nucl
             .filter(|n| n.helix == self.flat_id)
             .map(|n| n.position);
-        let show_seq = show_seq && camera.borrow().get_globals().zoom >= ZOOM_THRESHOLD;
+        // `show_seq` forces the display of the sequence regardless of the zoom level
+        let show_seq = show_seq || camera.borrow().get_globals().zoom > SEQUENCE_ZOOM_THRESHOLD;
         let size_id = 3.;
         let size_pos = 1.4;
         let circle = self.get_circle(camera, groups);
@@ -685,8 +757,8 @@ impl Helix {
             let nb_chars = self.real_id.to_string().len(); // ok to use len because digits are ascii
             let scale = size_id / nb_chars as f32;
             let mut advances =
-                crate::utils::chars2d::char_positions_x(&self.real_id.to_string(), char_drawers);
-            let mut height = crate::utils::chars2d::height(&self.real_id.to_string(), char_drawers);
+                crate::utils::chars2d::char_positions_x(&self.real_id.to_string(), char_drawer);
+            let mut height = crate::utils::chars2d::height(&self.real_id.to_string(), char_drawer);
             if camera.borrow().get_globals().zoom < ZOOM_THRESHOLD {
                 height *= 2.;
                 for x in advances.iter_mut() {
@@ -707,6 +779,22 @@ impl Helix {
             }
         }
 
+        if let Some(instances) = char_map.get_mut(&REORDER_HANDLE_CHAR) {
+            let handle = REORDER_HANDLE_CHAR.to_string();
+            let advances = crate::utils::chars2d::char_positions_x(&handle, char_drawer);
+            let height = crate::utils::chars2d::height(&handle, char_drawer);
+            let scale = size_id / 2.;
+            instances.push(CharInstance {
+                center: self.reorder_handle_position()
+                    - advances[1] / 2. * scale * Vec2::unit_x()
+                    - scale * height / 2. * Vec2::unit_y(),
+                rotation: self.isometry.rotation.into_matrix(),
+                size: scale,
+                z_index: self.flat_id.flat.0 as i32,
+                color: [0.4, 0.4, 0.4, 1.].into(),
+            })
+        }
+
         let moving_pos = edition_info
             .as_ref()
             .filter(|info| info.nucl.helix == self.flat_id)
@@ -715,8 +803,8 @@ impl Helix {
             let nb_chars = pos.to_string().len(); // ok to use len because digits are ascii
             let scale = size_pos;
             let mut advances =
-                crate::utils::chars2d::char_positions_x(&pos.to_string(), char_drawers);
-            let mut height = crate::utils::chars2d::height(&pos.to_string(), char_drawers);
+                crate::utils::chars2d::char_positions_x(&pos.to_string(), char_drawer);
+            let mut height = crate::utils::chars2d::height(&pos.to_string(), char_drawer);
             if camera.borrow().get_globals().zoom < ZOOM_THRESHOLD {
                 height *= 2.;
                 for x in advances.iter_mut() {
@@ -766,9 +854,9 @@ impl Helix {
                 .find('/')
                 .map(|n| 2 * n + 1)
                 .unwrap_or_else(|| info.len()); // ok to use len because the str contains only ascii chars
-            let mut advances = crate::utils::chars2d::char_positions_x(info, char_drawers);
-            let mut height = crate::utils::chars2d::height(info, char_drawers);
-            let mut pos_y = crate::utils::chars2d::char_positions_y(info, char_drawers);
+            let mut advances = crate::utils::chars2d::char_positions_x(info, char_drawer);
+            let mut height = crate::utils::chars2d::height(info, char_drawer);
+            let mut pos_y = crate::utils::chars2d::char_positions_y(info, char_drawer);
             if camera.borrow().get_globals().zoom < ZOOM_THRESHOLD {
                 height *= 2.;
                 for x in advances.iter_mut() {
@@ -798,6 +886,13 @@ impl Helix {
             if building.nucl.helix == self.flat_id {
                 print_info(building.nucl.position, &building.to_string());
             }
+        } else if let Some((pos, name)) = candidate_pos.zip(hovered_strand_name) {
+            // Characters that cannot be drawn are replaced by '?'
+            let name: String = name
+                .chars()
+                .map(|c| if char_drawer.can_draw(c) { c } else { '?' })
+                .collect();
+            print_info(pos, &name);
         }
 
         let mut print_basis = |position: isize, forward: bool| {
@@ -809,8 +904,8 @@ impl Helix {
             };
             if let Some(c) = basis_map.get(&nucl) {
                 let advances =
-                    crate::utils::chars2d::char_positions_x(&pos.to_string(), char_drawers);
-                let height = crate::utils::chars2d::height(&c.to_string(), char_drawers);
+                    crate::utils::chars2d::char_positions_x(&pos.to_string(), char_drawer);
+                let height = crate::utils::chars2d::height(&c.to_string(), char_drawer);
                 let center = if forward {
                     self.char_position_top(position, advances[1] * scale, height * scale)
                 } else {
@@ -822,7 +917,7 @@ impl Helix {
                     rotation: self.isometry.rotation.into_matrix(),
                     size: scale,
                     z_index: self.flat_id.flat.0 as i32,
-                    color: [0., 0., 0., 1.].into(),
+                    color: letter_color(nucl_colors.get(&nucl)),
                 })
             }
         };
@@ -1073,3 +1168,19 @@ pub enum HelixHandle {
     Left,
     Right,
 }
+
+/// The color of a basis letter drawn over a nucleotide of color `background`. Letters are white
+/// on dark strands and black on light strands or on empty nucleotides.
+fn letter_color(background: Option<&u32>) -> Vec4 {
+    let dark = background
+        .map(|c| {
+            let color = Instance::color_from_u32(*c);
+            0.299 * color.x + 0.587 * color.y + 0.114 * color.z < 0.5
+        })
+        .unwrap_or(false);
+    if dark {
+        [1., 1., 1., 1.].into()
+    } else {
+        [0., 0., 0., 1.].into()
+    }
+}
@@ -18,6 +18,7 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 use super::super::view::InsertionInstance;
 use super::helix::{Helix, Shift};
 use super::{CameraPtr, FlatNucl};
+use ensnano_design::Nucl;
 use lyon::math::Point;
 use lyon::path::path::BuilderWithAttributes;
 use lyon::path::Path;
@@ -45,6 +46,23 @@ pub struct Strand {
 }
 
 impl Strand {
+    /// Return the color of each nucleotide covered by the strand
+    pub fn nucl_colors(&self) -> impl Iterator<Item = (Nucl, u32)> + '_ {
+        self.points.chunks_exact(2).flat_map(move |domain| {
+            let (start, end) = (domain[0], domain[1]);
+            let left = start.position.min(end.position);
+            let right = start.position.max(end.position);
+            (left..=right).map(move |position| {
+                let nucl = Nucl {
+                    helix: start.helix.real,
+                    position,
+                    forward: start.forward,
+                };
+                (nucl, self.color)
+            })
+        })
+    }
+
     pub fn new(
         color: u32,
         points: Vec<FlatNucl>,
@@ -201,6 +219,20 @@ pub struct StrandVertex {
     width: f32,
 }
 
+impl StrandVertex {
+    /// A vertex that is not offset by the stroke width. This is used to draw filled shapes with
+    /// the strand pipeline.
+    pub fn filled(position: Vec2, color: [f32; 4], depth: f32) -> Self {
+        Self {
+            position: [position.x, position.y],
+            normal: [0., 0.],
+            color,
+            depth,
+            width: 1.,
+        }
+    }
+}
+
 pub struct WithAttributes {
     color: [f32; 4],
     highlight: bool,
@@ -207,6 +207,8 @@ impl FlatSelection {
                 }
                 Selection::Grid(d, g_id) => Self::Grid(*d as usize, *g_id),
                 Selection::Phantom(pe) => Self::Phantom(pe.clone()),
+                // Domains are highlighted nucleotide by nucleotide
+                Selection::Domain(_, _) => Self::Nothing,
                 Selection::Nothing => Self::Nothing,
             }
         } else {
@@ -20,21 +20,25 @@ use super::data::{
 };
 use super::{CameraPtr, FlatIdx, FlatNucl};
 use crate::utils::bindgroup_manager::{DynamicBindGroup, UniformBindGroup};
+use crate::utils::camera2d::{Camera, FitRectangle, Globals};
 use crate::utils::texture::Texture;
 use crate::utils::Ndc;
 use crate::{DrawArea, PhySize};
 use ensnano_design::Nucl;
 use iced_wgpu::wgpu;
 use std::rc::Rc;
+use ultraviolet::Vec2;
 use wgpu::{Device, Queue, RenderPipeline};
 
 mod helix_view;
-use helix_view::{HelixView, StrandView};
+use helix_view::{HelixView, LatticeGridView, RulerView, StrandView};
 mod background;
 mod insertion;
+mod lattice_grid;
 mod rectangle;
 use super::FlatSelection;
-use crate::consts::SAMPLE_COUNT;
+use crate::consts::{CANDIDATE_COLOR, SAMPLE_COUNT, SELECTED_COLOR};
+use crate::text::GlyphAtlas;
 use crate::utils::{chars2d as chars, circles2d as circles};
 use ahash::RandomState;
 use background::Background;
@@ -42,16 +46,22 @@ use chars::CharDrawer;
 pub use chars::CharInstance;
 pub use circles::CircleInstance;
 use circles::{CircleDrawer, CircleKind};
+pub use ensnano_interactor::graphics::RulerParameters;
 use iced_winit::winit::dpi::PhysicalPosition;
 use insertion::InsertionDrawer;
 pub use insertion::InsertionInstance;
+pub use lattice_grid::{LatticeGrid, LatticeKind};
 use rectangle::Rectangle;
 use std::{
     collections::{BTreeMap, BTreeSet, HashMap},
     sync::Arc,
+    time::{Duration, Instant},
 };
 
 const SHOW_SUGGESTION: bool = false;
+const XOVER_FLASH_COLOR: u32 = 0xFF_FF_EA_00;
+const XOVER_FLASH_DURATION: Duration = Duration::from_secs(2);
+const XOVER_RUBBER_BAND_COLOR: u32 = 0xFF_A0_A0_A0;
 
 pub struct View {
     device: Rc<Device>,
@@ -60,6 +70,7 @@ pub struct View {
     helices: Vec<Helix>,
     helices_view: Vec<HelixView>,
     helices_background: Vec<HelixView>,
+    rulers: Vec<RulerView>,
     strands: Vec<StrandView>,
     pasted_strands: Vec<StrandView>,
     helices_model: Vec<HelixModel>,
@@ -81,8 +92,8 @@ pub struct View {
     nucl_highlighter_bottom: CircleDrawer,
     rotation_widget: CircleDrawer,
     insertion_drawer: InsertionDrawer,
-    char_drawers_top: HashMap<char, CharDrawer>,
-    char_drawers_bottom: HashMap<char, CharDrawer>,
+    char_drawer_top: CharDrawer,
+    char_drawer_bottom: CharDrawer,
     char_map_top: HashMap<char, Vec<CharInstance>>,
     char_map_bottom: HashMap<char, Vec<CharInstance>>,
     show_sec: bool,
@@ -94,14 +105,33 @@ pub struct View {
     candidate_helices: Vec<FlatIdx>,
     candidate_nucl: Vec<FlatNucl>,
     selected_nucl: Vec<FlatNucl>,
+    /// The colors of the selected and candidate nucleotides
+    selected_color: u32,
+    candidate_color: u32,
     suggestion_candidate: Option<(FlatNucl, FlatNucl)>,
     torsions: HashMap<(FlatNucl, FlatNucl), FlatTorsion>,
     show_torsion: bool,
+    show_ruler: bool,
+    ruler_parameters: RulerParameters,
+    show_lattice_grid: bool,
+    /// The lattice of the design and the position at which it is drawn
+    lattice_grid: Option<(LatticeGrid, Vec2)>,
+    lattice_grid_view: LatticeGridView,
     rectangle: Rectangle,
     groups: Arc<BTreeMap<usize, bool>>,
     basis_map: Arc<HashMap<Nucl, char, RandomState>>,
+    /// The color of the strand covering each nucleotide, used to pick the color of the basis
+    /// letters
+    nucl_colors: HashMap<Nucl, u32>,
     edition_info: Option<EditionInfo>,
     hovered_nucl: Option<FlatNucl>,
+    /// The name of the strand containing `hovered_nucl`, displayed next to it
+    hovered_strand_name: Option<String>,
+    /// A cross-over that is being highlighted, and the moment at which the highlight started
+    flashed_xover: Option<(FlatNucl, FlatNucl, Instant)>,
+    /// The line drawn from the source of a cross-over to the cursor while using the cross-over
+    /// tool
+    xover_rubber_band: Option<StrandView>,
 }
 
 pub struct EditionInfo {
@@ -192,21 +222,35 @@ impl View {
         let rectangle = Rectangle::new(&device, queue.clone());
         let chars = [
             'A', 'T', 'G', 'C', '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', '-', 'n', 't',
-            'm', '.', '/', ' ', '(', ')',
+            'm', '.', '/', ' ', '(', ')', '_', '?',
         ];
-        let mut char_drawers_top = HashMap::new();
+        // Letters are needed to display the names of the strands
+        let letters = ('a'..='z').chain('A'..='Z');
+        let chars: Vec<char> = letters
+            .filter(|c| !chars.contains(c))
+            .chain(chars.iter().cloned())
+            .chain(std::iter::once(super::data::REORDER_HANDLE_CHAR))
+            .collect();
+        let glyph_atlas = Rc::new(GlyphAtlas::new(
+            chars.iter().cloned(),
+            device.as_ref(),
+            queue.as_ref(),
+        ));
+        let char_drawer_top = CharDrawer::new(
+            device.clone(),
+            queue.clone(),
+            globals_top.get_layout(),
+            glyph_atlas.clone(),
+        );
+        let char_drawer_bottom = CharDrawer::new(
+            device.clone(),
+            queue.clone(),
+            globals_top.get_layout(),
+            glyph_atlas,
+        );
         let mut char_map_top = HashMap::new();
-        let mut char_drawers_bottom = HashMap::new();
         let mut char_map_bottom = HashMap::new();
         for c in chars.iter() {
-            char_drawers_top.insert(
-                *c,
-                CharDrawer::new(device.clone(), queue.clone(), globals_top.get_layout(), *c),
-            );
-            char_drawers_bottom.insert(
-                *c,
-                CharDrawer::new(device.clone(), queue.clone(), globals_top.get_layout(), *c),
-            );
             char_map_top.insert(*c, Vec::new());
             char_map_bottom.insert(*c, Vec::new());
         }
@@ -228,6 +272,7 @@ impl View {
             pasted_strands: Vec::new(),
             helices_model: Vec::new(),
             helices_background: Vec::new(),
+            rulers: Vec::new(),
             models,
             globals_top,
             globals_bottom,
@@ -245,9 +290,9 @@ impl View {
             nucl_highlighter_top,
             nucl_highlighter_bottom,
             rotation_widget,
-            char_drawers_top,
+            char_drawer_top,
             char_map_top,
-            char_drawers_bottom,
+            char_drawer_bottom,
             char_map_bottom,
             show_sec: false,
             suggestions: vec![],
@@ -259,14 +304,25 @@ impl View {
             suggestion_candidate: None,
             torsions: HashMap::new(),
             show_torsion: false,
+            show_ruler: false,
+            ruler_parameters: Default::default(),
+            show_lattice_grid: false,
+            lattice_grid: None,
+            lattice_grid_view: LatticeGridView::new(device.clone(), queue.clone()),
             rectangle,
             insertion_drawer,
             groups: Default::default(),
             basis_map: Default::default(),
+            nucl_colors: Default::default(),
             edition_info: Default::default(),
             selected_nucl: vec![],
             candidate_nucl: vec![],
+            selected_color: SELECTED_COLOR,
+            candidate_color: CANDIDATE_COLOR,
             hovered_nucl: None,
+            hovered_strand_name: None,
+            flashed_xover: None,
+            xover_rubber_band: None,
         }
     }
 
@@ -280,6 +336,46 @@ impl View {
         self.was_updated = true;
     }
 
+    pub fn set_show_ruler(&mut self, show: bool) {
+        self.show_ruler = show;
+        self.was_updated = true;
+    }
+
+    pub fn set_show_lattice_grid(&mut self, show: bool) {
+        self.show_lattice_grid = show;
+        self.was_updated = true;
+    }
+
+    /// Update the lattice grid, drawn on the left of `corner`, the top left corner of the
+    /// helices
+    pub fn update_lattice_grid(&mut self, grid: Option<LatticeGrid>, corner: Vec2) {
+        let lattice_grid = grid.map(|grid| {
+            let origin = grid.origin_left_of(corner);
+            (grid, origin)
+        });
+        if lattice_grid != self.lattice_grid {
+            self.lattice_grid = lattice_grid;
+            let origin = self
+                .lattice_grid
+                .as_ref()
+                .map(|(_, origin)| *origin)
+                .unwrap_or(corner);
+            self.lattice_grid_view
+                .update(self.lattice_grid.as_ref().map(|(grid, _)| grid), origin);
+            self.was_updated = true;
+        }
+    }
+
+    pub fn set_ruler_parameters(&mut self, parameters: RulerParameters) {
+        if parameters != self.ruler_parameters {
+            self.ruler_parameters = parameters;
+            for (ruler, helix) in self.rulers.iter_mut().zip(self.helices.iter()) {
+                ruler.update(helix, &self.ruler_parameters);
+            }
+            self.was_updated = true;
+        }
+    }
+
     pub fn set_splited(&mut self, splited: bool) {
         self.was_updated = true;
         self.splited = splited;
@@ -311,8 +407,11 @@ impl View {
             self.queue.clone(),
             true,
         ));
+        self.rulers
+            .push(RulerView::new(self.device.clone(), self.queue.clone()));
         self.helices_view[id_helix as usize].update(&helix);
         self.helices_background[id_helix as usize].update(&helix);
+        self.rulers[id_helix as usize].update(&helix, &self.ruler_parameters);
         self.helices_model.push(helix.model());
         self.models.update(self.helices_model.as_slice());
     }
@@ -325,6 +424,7 @@ impl View {
         for h in helices.iter().rev() {
             self.helices.remove(h.0);
             self.helices_background.remove(h.0);
+            self.rulers.remove(h.0);
             self.helices_view.remove(h.0);
             self.helices_model.remove(h.0);
         }
@@ -342,6 +442,7 @@ impl View {
         for (i, h) in self.helices_view.iter_mut().enumerate() {
             self.helices_model[i] = helices[i].model();
             self.helices_background[i].update(&helices[i]);
+            self.rulers[i].update(&helices[i], &self.ruler_parameters);
             h.update(&helices[i])
         }
         for helix in helices.iter().skip(self.helices_view.len()) {
@@ -374,7 +475,9 @@ impl View {
         self.helices_model.clear();
         self.helices_view.clear();
         self.strands.clear();
+        self.nucl_colors.clear();
         self.helices_background.clear();
+        self.rulers.clear();
     }
 
     pub fn update_strands(&mut self, strands: &[Strand], helices: &[Helix]) {
@@ -405,6 +508,7 @@ impl View {
             }
         }
         self.insertion_drawer.new_instances(insertions);
+        self.nucl_colors = strands.iter().flat_map(|s| s.nucl_colors()).collect();
         self.was_updated = true;
     }
 
@@ -458,6 +562,10 @@ impl View {
     }
 
     pub fn needs_redraw(&self) -> bool {
+        if self.flashed_xover.is_some() {
+            // Keep redrawing until the highlight expires
+            return true;
+        }
         if self.splited {
             self.camera_top.borrow().was_updated()
                 | self.was_updated
@@ -475,6 +583,12 @@ impl View {
         self.candidate_helices = selection;
     }
 
+    pub fn set_highlight_colors(&mut self, selected_color: u32, candidate_color: u32) {
+        self.selected_color = selected_color;
+        self.candidate_color = candidate_color;
+        self.was_updated = true;
+    }
+
     pub fn center_selection(&mut self, selection: FlatSelection) -> Option<(FlatNucl, FlatNucl)> {
         self.camera_top.borrow_mut().zoom_closer();
         self.was_updated = true;
@@ -518,6 +632,53 @@ impl View {
         }
     }
 
+    /// Move the camera to show `selection` if it is not visible. Contrary to `center_selection`,
+    /// the zoom level is not modified.
+    pub fn make_selection_visible(&mut self, selection: FlatSelection) {
+        match selection {
+            FlatSelection::Nucleotide(_, nucl) | FlatSelection::Bound(_, nucl, _) => {
+                self.helices[nucl.helix].make_visible(nucl.position, self.camera_top.clone());
+                self.was_updated = true;
+            }
+            _ => (),
+        }
+    }
+
+    /// Highlight the cross-over between `n1` and `n2` for a short time
+    pub fn flash_xover(&mut self, n1: FlatNucl, n2: FlatNucl) {
+        self.flashed_xover = Some((n1, n2, Instant::now()));
+        self.was_updated = true;
+    }
+
+    /// Draw a line from `source` to `point`, or remove it if `rubber_band` is `None`
+    pub fn set_xover_rubber_band(&mut self, rubber_band: Option<(FlatNucl, Vec2)>) {
+        self.xover_rubber_band = rubber_band.map(|(source, point)| {
+            let strand = Strand::new(
+                XOVER_RUBBER_BAND_COLOR,
+                vec![source, source],
+                vec![],
+                usize::MAX,
+                true,
+            );
+            let free_end = Some(FreeEnd {
+                strand_id: usize::MAX,
+                point,
+                prime3: true,
+                candidates: vec![],
+            });
+            let mut strand_view = StrandView::new(self.device.clone(), self.queue.clone());
+            strand_view.update(
+                &strand,
+                &self.helices,
+                &free_end,
+                &self.camera_top,
+                &self.camera_bottom,
+            );
+            strand_view
+        });
+        self.was_updated = true;
+    }
+
     pub fn center_split(&mut self, n1: FlatNucl, n2: FlatNucl) {
         let zoom = self.camera_top.borrow().get_globals().zoom;
         self.camera_bottom.borrow_mut().set_zoom(zoom);
@@ -568,6 +729,12 @@ impl View {
         target: &wgpu::TextureView,
         _area: DrawArea,
     ) {
+        if let Some((_, _, start)) = self.flashed_xover {
+            if start.elapsed() > XOVER_FLASH_DURATION {
+                self.flashed_xover = None;
+                self.was_updated = true;
+            }
+        }
         let mut need_new_circles = false;
         if let Some(globals) = self.camera_top.borrow_mut().update() {
             log::debug!("new camera globals: {:?}", globals);
@@ -662,12 +829,23 @@ impl View {
         render_pass.set_bind_group(0, self.globals_top.get_bindgroup(), &[]);
         render_pass.set_bind_group(1, self.models.get_bindgroup(), &[]);
         self.background.draw(&mut render_pass);
+        if self.show_lattice_grid {
+            render_pass.set_pipeline(&self.strand_pipeline);
+            self.lattice_grid_view.draw(&mut render_pass);
+        }
 
         render_pass.set_pipeline(&self.helices_pipeline);
 
         for background in self.helices_background.iter() {
             background.draw(&mut render_pass);
         }
+        if self.show_ruler {
+            render_pass.set_pipeline(&self.strand_pipeline);
+            for ruler in self.rulers.iter() {
+                ruler.draw(&mut render_pass);
+            }
+            render_pass.set_pipeline(&self.helices_pipeline);
+        }
         for helix in self.helices_view.iter() {
             helix.draw(&mut render_pass);
         }
@@ -709,9 +887,7 @@ impl View {
         render_pass.set_bind_group(0, self.globals_top.get_bindgroup(), &[]);
         render_pass.set_bind_group(1, self.models.get_bindgroup(), &[]);
         self.circle_drawer_top.draw(&mut render_pass);
-        for drawer in self.char_drawers_top.values_mut() {
-            drawer.draw(&mut render_pass);
-        }
+        self.char_drawer_top.draw(&mut render_pass);
         self.insertion_drawer.draw(&mut render_pass);
         render_pass.set_pipeline(&self.strand_pipeline);
         for strand in self.strands.iter() {
@@ -729,6 +905,9 @@ impl View {
         for highlight in self.candidate_strands.iter() {
             highlight.draw(&mut render_pass, bottom);
         }
+        if let Some(rubber_band) = self.xover_rubber_band.as_ref() {
+            rubber_band.draw(&mut render_pass, bottom);
+        }
         render_pass.set_pipeline(&self.helices_pipeline);
         self.nucl_highlighter_top.draw(&mut render_pass);
         drop(render_pass);
@@ -785,6 +964,9 @@ impl View {
         for highlight in self.candidate_strands.iter() {
             highlight.draw_split(&mut render_pass, bottom);
         }
+        if let Some(rubber_band) = self.xover_rubber_band.as_ref() {
+            rubber_band.draw_split(&mut render_pass, bottom);
+        }
 
         drop(render_pass);
         if self.splited {
@@ -828,12 +1010,23 @@ impl View {
             render_pass.set_bind_group(0, self.globals_bottom.get_bindgroup(), &[]);
             render_pass.set_bind_group(1, self.models.get_bindgroup(), &[]);
             self.background.draw(&mut render_pass);
+            if self.show_lattice_grid {
+                render_pass.set_pipeline(&self.strand_pipeline);
+                self.lattice_grid_view.draw(&mut render_pass);
+            }
 
             render_pass.set_pipeline(&self.helices_pipeline);
 
             for background in self.helices_background.iter() {
                 background.draw(&mut render_pass);
             }
+            if self.show_ruler {
+                render_pass.set_pipeline(&self.strand_pipeline);
+                for ruler in self.rulers.iter() {
+                    ruler.draw(&mut render_pass);
+                }
+                render_pass.set_pipeline(&self.helices_pipeline);
+            }
             for helix in self.helices_view.iter() {
                 helix.draw(&mut render_pass);
             }
@@ -878,9 +1071,7 @@ impl View {
             render_pass.set_bind_group(0, self.globals_bottom.get_bindgroup(), &[]);
             render_pass.set_bind_group(1, self.models.get_bindgroup(), &[]);
             self.circle_drawer_bottom.draw(&mut render_pass);
-            for drawer in self.char_drawers_bottom.values_mut() {
-                drawer.draw(&mut render_pass);
-            }
+            self.char_drawer_bottom.draw(&mut render_pass);
             self.insertion_drawer.draw(&mut render_pass);
             render_pass.set_pipeline(&self.strand_pipeline);
             for strand in self.strands.iter() {
@@ -898,6 +1089,9 @@ impl View {
             for highlight in self.candidate_strands.iter() {
                 highlight.draw(&mut render_pass, bottom);
             }
+            if let Some(rubber_band) = self.xover_rubber_band.as_ref() {
+                rubber_band.draw(&mut render_pass, bottom);
+            }
             render_pass.set_pipeline(&self.helices_pipeline);
             self.nucl_highlighter_bottom.draw(&mut render_pass);
             drop(render_pass);
@@ -957,6 +1151,9 @@ impl View {
             for highlight in self.candidate_strands.iter() {
                 highlight.draw_split(&mut render_pass, bottom);
             }
+            if let Some(rubber_band) = self.xover_rubber_band.as_ref() {
+                rubber_band.draw_split(&mut render_pass, bottom);
+            }
         }
         let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: None,
@@ -1074,9 +1271,8 @@ impl View {
     /// Collect the candidate/selection circles
     fn collect_nucl_highlight(&self, circles: &mut Vec<CircleInstance>) {
         for n in self.candidate_nucl.iter() {
-            let candidate_color = crate::consts::CANDIDATE_COLOR;
             if let Some(h1) = self.helices.get(n.helix.flat.0) {
-                let mut c = h1.get_circle_nucl(n.position, n.forward, candidate_color);
+                let mut c = h1.get_circle_nucl(n.position, n.forward, self.candidate_color);
                 c.set_radius(std::f32::consts::FRAC_1_SQRT_2);
                 circles.push(c)
             } else {
@@ -1085,15 +1281,22 @@ impl View {
         }
 
         for n in self.selected_nucl.iter() {
-            let selected_color = crate::consts::SELECTED_COLOR;
             if let Some(h1) = self.helices.get(n.helix.flat.0) {
-                let mut c = h1.get_circle_nucl(n.position, n.forward, selected_color);
+                let mut c = h1.get_circle_nucl(n.position, n.forward, self.selected_color);
                 c.set_radius(std::f32::consts::FRAC_1_SQRT_2);
                 circles.push(c)
             } else {
                 log::error!("Could not get flat helix {}", n.helix.flat.0);
             }
         }
+
+        if let Some((n1, n2, _)) = self.flashed_xover.as_ref() {
+            for n in [n1, n2].iter() {
+                if let Some(h) = self.helices.get(n.helix.flat.0) {
+                    circles.push(h.get_circle_nucl(n.position, n.forward, XOVER_FLASH_COLOR));
+                }
+            }
+        }
     }
 
     /// Collect the torsion indications.
@@ -1134,9 +1337,65 @@ impl View {
         }
     }
 
-    pub fn set_hovered_nucl(&mut self, hovered_nucl: Option<FlatNucl>) {
+    /// Draw the helices and strands, fitted in an image of `size` pixels, and return the pixels
+    /// of the image in RGBA format.
+    ///
+    /// The selection and the candidates are not drawn, and the view is left as it was before the
+    /// call.
+    pub fn render_to_rgba(
+        &mut self,
+        size: PhySize,
+        rectangle: FitRectangle,
+    ) -> std::io::Result<Vec<u8>> {
+        let mut export_camera = Camera::new(
+            Globals::default([size.width as f32, size.height as f32]),
+            false,
+        );
+        export_camera.fit(rectangle);
+        self.camera_top.borrow_mut().swap(&mut export_camera);
+        let old_area_size = self.area_size;
+        let old_splited = self.splited;
+        let selected_strands = std::mem::take(&mut self.selected_strands);
+        let candidate_strands = std::mem::take(&mut self.candidate_strands);
+        self.splited = false;
+        self.resize(DrawArea {
+            position: PhysicalPosition::new(0, 0),
+            size,
+        });
+
+        let device = self.device.clone();
+        let queue = self.queue.clone();
+        let pixels = crate::utils::render_to_rgba(&device, &queue, size, |encoder, target| {
+            self.draw(
+                encoder,
+                target,
+                DrawArea {
+                    position: PhysicalPosition::new(0, 0),
+                    size,
+                },
+            )
+        });
+
+        self.camera_top.borrow_mut().swap(&mut export_camera);
+        self.selected_strands = selected_strands;
+        self.candidate_strands = candidate_strands;
+        self.splited = old_splited;
+        self.resize(DrawArea {
+            position: PhysicalPosition::new(0, 0),
+            size: old_area_size,
+        });
+        pixels
+    }
+
+    pub fn set_hovered_nucl(
+        &mut self,
+        hovered_nucl: Option<FlatNucl>,
+        strand_name: Option<String>,
+    ) {
         self.was_updated |= hovered_nucl != self.hovered_nucl;
+        self.was_updated |= strand_name != self.hovered_strand_name;
         self.hovered_nucl = hovered_nucl;
+        self.hovered_strand_name = strand_name;
     }
 
     pub fn set_candidate_suggestion(
@@ -1162,41 +1421,44 @@ impl View {
             v.clear();
         }
 
+        if self.show_lattice_grid {
+            if let Some((grid, origin)) = self.lattice_grid.as_ref() {
+                for label in grid.labels(*origin) {
+                    label.add_char_instances(&mut self.char_map_top, &self.char_drawer_top);
+                    label.add_char_instances(&mut self.char_map_bottom, &self.char_drawer_bottom);
+                }
+            }
+        }
+
         for h in self.helices.iter() {
             h.add_char_instances(
                 &self.camera_top,
                 &mut self.char_map_top,
-                &self.char_drawers_top,
+                &self.char_drawer_top,
                 self.groups.as_ref(),
                 self.basis_map.as_ref(),
+                &self.nucl_colors,
                 self.show_sec,
                 &self.edition_info,
                 &self.hovered_nucl,
+                self.hovered_strand_name.as_deref(),
             );
             h.add_char_instances(
                 &self.camera_bottom,
                 &mut self.char_map_bottom,
-                &self.char_drawers_bottom,
+                &self.char_drawer_bottom,
                 self.groups.as_ref(),
                 self.basis_map.as_ref(),
+                &self.nucl_colors,
                 self.show_sec,
                 &self.edition_info,
                 &self.hovered_nucl,
+                self.hovered_strand_name.as_deref(),
             )
         }
 
-        for (c, v) in self.char_map_top.iter() {
-            self.char_drawers_top
-                .get_mut(c)
-                .unwrap()
-                .new_instances(Rc::new(v.clone()))
-        }
-        for (c, v) in self.char_map_bottom.iter() {
-            self.char_drawers_bottom
-                .get_mut(c)
-                .unwrap()
-                .new_instances(Rc::new(v.clone()))
-        }
+        self.char_drawer_top.new_instances(&self.char_map_top);
+        self.char_drawer_bottom.new_instances(&self.char_map_bottom);
     }
 
     pub fn set_wheels(&mut self, wheels: Vec<CircleInstance>) {
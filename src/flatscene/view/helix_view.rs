@@ -15,9 +15,10 @@ ENSnano, a 3d graphical application for DNA nanostructures.
     You should have received a copy of the GNU General Public License
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
-use super::{CameraPtr, FlatNucl, FreeEnd, Helix, Strand};
+use super::{CameraPtr, FlatNucl, FreeEnd, Helix, LatticeGrid, RulerParameters, Strand};
 use iced_wgpu::wgpu;
 use std::rc::Rc;
+use ultraviolet::Vec2;
 use wgpu::{Buffer, Device, Queue, RenderPass};
 
 pub struct HelixView {
@@ -59,6 +60,82 @@ impl HelixView {
     }
 }
 
+/// The bars of the nucleotide ruler drawn on one helix. They are drawn with the strand pipeline.
+pub struct RulerView {
+    vertex_buffer: DynamicBuffer,
+    index_buffer: DynamicBuffer,
+    num_instance: u32,
+}
+
+impl RulerView {
+    pub fn new(device: Rc<Device>, queue: Rc<Queue>) -> Self {
+        Self {
+            vertex_buffer: DynamicBuffer::new(
+                device.clone(),
+                queue.clone(),
+                wgpu::BufferUsages::VERTEX,
+            ),
+            index_buffer: DynamicBuffer::new(device, queue, wgpu::BufferUsages::INDEX),
+            num_instance: 0,
+        }
+    }
+
+    pub fn update(&mut self, helix: &Helix, parameters: &RulerParameters) {
+        let vertices = helix.ruler_vertices(parameters);
+        self.vertex_buffer.update(vertices.vertices.as_slice());
+        self.index_buffer.update(vertices.indices.as_slice());
+        self.num_instance = vertices.indices.len() as u32;
+    }
+
+    pub fn draw<'a>(&'a self, render_pass: &mut RenderPass<'a>) {
+        if self.num_instance > 0 {
+            render_pass.set_index_buffer(self.index_buffer.get_slice(), wgpu::IndexFormat::Uint16);
+            render_pass.set_vertex_buffer(0, self.vertex_buffer.get_slice());
+            render_pass.draw_indexed(0..self.num_instance, 0, 0..1);
+        }
+    }
+}
+
+/// The lines of the lattice grid. They are drawn with the strand pipeline.
+pub struct LatticeGridView {
+    vertex_buffer: DynamicBuffer,
+    index_buffer: DynamicBuffer,
+    num_instance: u32,
+}
+
+impl LatticeGridView {
+    pub fn new(device: Rc<Device>, queue: Rc<Queue>) -> Self {
+        Self {
+            vertex_buffer: DynamicBuffer::new(
+                device.clone(),
+                queue.clone(),
+                wgpu::BufferUsages::VERTEX,
+            ),
+            index_buffer: DynamicBuffer::new(device, queue, wgpu::BufferUsages::INDEX),
+            num_instance: 0,
+        }
+    }
+
+    pub fn update(&mut self, grid: Option<&LatticeGrid>, origin: Vec2) {
+        if let Some(grid) = grid {
+            let vertices = grid.vertices(origin);
+            self.vertex_buffer.update(vertices.vertices.as_slice());
+            self.index_buffer.update(vertices.indices.as_slice());
+            self.num_instance = vertices.indices.len() as u32;
+        } else {
+            self.num_instance = 0;
+        }
+    }
+
+    pub fn draw<'a>(&'a self, render_pass: &mut RenderPass<'a>) {
+        if self.num_instance > 0 {
+            render_pass.set_index_buffer(self.index_buffer.get_slice(), wgpu::IndexFormat::Uint16);
+            render_pass.set_vertex_buffer(0, self.vertex_buffer.get_slice());
+            render_pass.draw_indexed(0..self.num_instance, 0, 0..1);
+        }
+    }
+}
+
 pub struct StrandView {
     vertex_buffer_top: DynamicBuffer,
     index_buffer_top: DynamicBuffer,
@@ -0,0 +1,253 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! A schematic of the lattice on which the helices of the design are placed. It is drawn on the
+//! left of the 2D helices, below them, with one line per row and column of a square lattice, or
+//! the edges of a honeycomb lattice. Rows, columns and occupied positions are labeled.
+
+use super::super::data::StrandVertex;
+use super::{CharDrawer, CharInstance};
+use crate::consts::*;
+use crate::utils::chars2d as chars;
+use crate::utils::instance::Instance;
+use std::collections::HashMap;
+use ultraviolet::{Mat2, Vec2};
+
+type LatticeVertices = lyon::tessellation::VertexBuffers<StrandVertex, u16>;
+
+/// Distance between two neighbouring lattice positions. This is the vertical spacing between
+/// two consecutive 2D helices in the default layout.
+const LATTICE_SPACING: f32 = 5.;
+/// Width of the grid lines
+const LATTICE_LINE_WIDTH: f32 = 0.1;
+/// Depth of the lines, so that they stay behind the helices
+const LATTICE_DEPTH: f32 = 900.;
+const LATTICE_LABEL_SIZE: f32 = 1.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LatticeKind {
+    Square,
+    Honeycomb,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LatticeGrid {
+    pub kind: LatticeKind,
+    /// The lattice positions `(x, y)` occupied by a helix and the identifier of that helix
+    pub helices: Vec<(isize, isize, usize)>,
+}
+
+/// A text to be drawn in the 2D view, centered on `position`
+pub struct LatticeLabel {
+    pub text: String,
+    pub position: Vec2,
+    pub color: u32,
+}
+
+impl LatticeGrid {
+    /// Return the smallest and largest x and y coordinates of the occupied positions
+    fn bounds(&self) -> Option<(isize, isize, isize, isize)> {
+        let min_x = self.helices.iter().map(|h| h.0).min()?;
+        let max_x = self.helices.iter().map(|h| h.0).max()?;
+        let min_y = self.helices.iter().map(|h| h.1).min()?;
+        let max_y = self.helices.iter().map(|h| h.1).max()?;
+        Some((min_x, max_x, min_y, max_y))
+    }
+
+    /// The position at which the grid must be drawn so that it is on the left of `corner`, the
+    /// top left corner of the 2D helices
+    pub fn origin_left_of(&self, corner: Vec2) -> Vec2 {
+        let width = self
+            .bounds()
+            .map(|(min_x, max_x, _, _)| self.relative_position(max_x - min_x, 0, 0, 0).x)
+            .unwrap_or(0.);
+        corner + Vec2::new(-width - 2. * LATTICE_SPACING, LATTICE_SPACING)
+    }
+
+    /// The position of the lattice position `(x, y)` relatively to the position
+    /// `(x - dx, y - dy)` which is drawn on `(0, 0)`.
+    fn relative_position(&self, dx: isize, dy: isize, x: isize, y: isize) -> Vec2 {
+        match self.kind {
+            LatticeKind::Square => LATTICE_SPACING * Vec2::new(dx as f32, dy as f32),
+            LatticeKind::Honeycomb => {
+                // Same layout as the honeycomb grids of the 3D view, with the y axis pointing
+                // downward
+                let r = LATTICE_SPACING / 2.;
+                let lower = if x.abs() % 2 != y.abs() % 2 { r } else { 0. };
+                Vec2::new(dx as f32 * r * 3f32.sqrt(), 3. * r * dy as f32 + lower)
+            }
+        }
+    }
+
+    fn position(&self, origin: Vec2, x: isize, y: isize) -> Option<Vec2> {
+        let (min_x, _, min_y, _) = self.bounds()?;
+        Some(origin + self.relative_position(x - min_x, y - min_y, x, y))
+    }
+
+    pub fn vertices(&self, origin: Vec2) -> LatticeVertices {
+        let mut vertices = LatticeVertices::new();
+        let (min_x, max_x, min_y, max_y) = if let Some(bounds) = self.bounds() {
+            bounds
+        } else {
+            return vertices;
+        };
+        let color = Instance::color_from_au32(LATTICE_GRID_COLOR);
+        let color = [color.x, color.y, color.z, color.w];
+        let mut add_line = |from: Vec2, to: Vec2| {
+            let direction = (to - from).normalized();
+            let normal = Vec2::new(-direction.y, direction.x) * LATTICE_LINE_WIDTH / 2.;
+            let first_idx = vertices.vertices.len() as u16;
+            for corner in [from - normal, to - normal, to + normal, from + normal].iter() {
+                vertices
+                    .vertices
+                    .push(StrandVertex::filled(*corner, color, LATTICE_DEPTH));
+            }
+            vertices.indices.extend_from_slice(&[
+                first_idx,
+                first_idx + 1,
+                first_idx + 2,
+                first_idx,
+                first_idx + 2,
+                first_idx + 3,
+            ]);
+        };
+        let margin = LATTICE_SPACING / 2.;
+        match self.kind {
+            LatticeKind::Square => {
+                for y in min_y..=max_y {
+                    if let Some((left, right)) = self
+                        .position(origin, min_x, y)
+                        .zip(self.position(origin, max_x, y))
+                    {
+                        add_line(
+                            left - margin * Vec2::unit_x(),
+                            right + margin * Vec2::unit_x(),
+                        );
+                    }
+                }
+                for x in min_x..=max_x {
+                    if let Some((top, bottom)) = self
+                        .position(origin, x, min_y)
+                        .zip(self.position(origin, x, max_y))
+                    {
+                        add_line(
+                            top - margin * Vec2::unit_y(),
+                            bottom + margin * Vec2::unit_y(),
+                        );
+                    }
+                }
+            }
+            LatticeKind::Honeycomb => {
+                for x in min_x..=max_x {
+                    for y in min_y..=max_y {
+                        let lower = x.abs() % 2 != y.abs() % 2;
+                        let mut neighbours = vec![];
+                        if x < max_x {
+                            neighbours.push((x + 1, y));
+                        }
+                        if lower && y < max_y {
+                            neighbours.push((x, y + 1));
+                        }
+                        for (n_x, n_y) in neighbours {
+                            if let Some((from, to)) = self
+                                .position(origin, x, y)
+                                .zip(self.position(origin, n_x, n_y))
+                            {
+                                add_line(from, to);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        vertices
+    }
+
+    /// The indices of the rows on the left of the grid, the indices of the columns above it, and
+    /// the identifier of each helix on its lattice position
+    pub fn labels(&self, origin: Vec2) -> Vec<LatticeLabel> {
+        let mut ret = Vec::new();
+        let (min_x, max_x, min_y, max_y) = if let Some(bounds) = self.bounds() {
+            bounds
+        } else {
+            return ret;
+        };
+        for y in min_y..=max_y {
+            if let Some(position) = self.position(origin, min_x, y) {
+                let row_center = match self.kind {
+                    LatticeKind::Square => position.y,
+                    LatticeKind::Honeycomb => {
+                        origin.y
+                            + self.relative_position(0, y - min_y, 0, 0).y
+                            + LATTICE_SPACING / 4.
+                    }
+                };
+                ret.push(LatticeLabel {
+                    text: y.to_string(),
+                    position: Vec2::new(position.x - LATTICE_SPACING, row_center),
+                    color: LATTICE_LABEL_COLOR,
+                });
+            }
+        }
+        for x in min_x..=max_x {
+            if let Some(position) = self.position(origin, x, min_y) {
+                ret.push(LatticeLabel {
+                    text: x.to_string(),
+                    position: Vec2::new(position.x, origin.y - LATTICE_SPACING),
+                    color: LATTICE_LABEL_COLOR,
+                });
+            }
+        }
+        for (x, y, h_id) in self.helices.iter() {
+            if let Some(position) = self.position(origin, *x, *y) {
+                ret.push(LatticeLabel {
+                    text: h_id.to_string(),
+                    position,
+                    color: LATTICE_HELIX_LABEL_COLOR,
+                });
+            }
+        }
+        ret
+    }
+}
+
+impl LatticeLabel {
+    pub fn add_char_instances(
+        &self,
+        char_map: &mut HashMap<char, Vec<CharInstance>>,
+        char_drawer: &CharDrawer,
+    ) {
+        let advances = chars::char_positions_x(&self.text, char_drawer);
+        let height = chars::height(&self.text, char_drawer);
+        let nb_chars = self.text.chars().count();
+        let x_shift = -advances[nb_chars] / 2. * LATTICE_LABEL_SIZE;
+        let color = Instance::color_from_au32(self.color);
+        for (c_idx, c) in self.text.chars().enumerate() {
+            if let Some(instances) = char_map.get_mut(&c) {
+                instances.push(CharInstance {
+                    center: self.position
+                        + (x_shift + advances[c_idx] * LATTICE_LABEL_SIZE) * Vec2::unit_x()
+                        - LATTICE_LABEL_SIZE * height / 2. * Vec2::unit_y(),
+                    rotation: Mat2::identity(),
+                    size: LATTICE_LABEL_SIZE,
+                    z_index: 0,
+                    color,
+                })
+            }
+        }
+    }
+}
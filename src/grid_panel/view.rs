@@ -23,6 +23,7 @@ use wgpu::{Device, Queue, RenderPipeline};
 use super::{DrawArea, CameraPtr};
 use crate::PhySize;
 use crate::consts::*;
+use crate::text::GlyphAtlas;
 use crate::utils::bindgroup_manager::{DynamicBindGroup, UniformBindGroup};
 use crate::utils::texture::Texture;
 use crate::utils::{chars2d as chars, circles2d as circles};
@@ -38,7 +39,7 @@ pub struct View {
     area_size: PhySize,
     depth_texture: Texture,
     circle_drawer: CircleDrawer,
-    char_drawers: HashMap<char, CharDrawer>,
+    char_drawer: CharDrawer,
     char_map: HashMap<char, Vec<CharInstance>>,
     globals: UniformBindGroup,
 }
@@ -73,13 +74,14 @@ impl View {
         let chars = [
             'A', 'T', 'G', 'C', '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', '-',
         ];
-        let mut char_drawers = HashMap::new();
+        let atlas = Rc::new(GlyphAtlas::new(
+            chars.iter().cloned(),
+            device.as_ref(),
+            queue.as_ref(),
+        ));
+        let char_drawer = CharDrawer::new(device.clone(), queue.clone(), globals.get_layout(), atlas);
         let mut char_map = HashMap::new();
         for c in chars.iter() {
-            char_drawers.insert(
-                *c,
-                CharDrawer::new(device.clone(), queue.clone(), globals.get_layout(), *c),
-            );
             char_map.insert(*c, Vec::new());
         }
         Self {
@@ -90,7 +92,7 @@ impl View {
             depth_texture,
             circle_drawer,
             char_map,
-            char_drawers,
+            char_drawer,
             globals,
         }
     }
@@ -164,9 +166,7 @@ impl View {
         self.circle_drawer.draw(&mut render_pass);
 
         // Draw the helices numbers
-        for drawer in self.char_drawers.values_mut() {
-            drawer.draw(&mut render_pass);
-        }
+        self.char_drawer.draw(&mut render_pass);
         
     }
 
@@ -178,12 +178,7 @@ impl View {
 
     pub fn update_circles(&mut self, circles: Vec<CircleInstance>) {
         self.circle_drawer.new_instances(Rc::new(circles));
-        for (c, v) in self.char_map.iter() {
-            self.char_drawers
-                .get_mut(c)
-                .unwrap()
-                .new_instances(Rc::new(v.clone()))
-        }
+        self.char_drawer.new_instances(&self.char_map);
     }
 
     pub fn get_char_map(&mut self) -> &mut HashMap<char, Vec<CharInstance>> {
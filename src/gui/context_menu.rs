@@ -0,0 +1,169 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! A floating menu, opened by a control-right click in the 2D view, that lists the actions that
+//! can be applied to the current selection.
+
+use super::{AppState, Requests, UiSize};
+use ensnano_interactor::{Selection, StrandEnd};
+use iced::{container, Background, Container};
+use iced_winit::winit::dpi::LogicalSize;
+use iced_winit::{button, Button, Color, Column, Command, Element, Length, Program, Text};
+use std::sync::{Arc, Mutex};
+
+/// The logical width of the context menu
+const CONTEXT_MENU_WIDTH: f64 = 180.;
+const PADDING: u16 = 3;
+const SPACING: u16 = 2;
+
+/// An action of the context menu
+#[derive(Debug, Clone, PartialEq)]
+pub enum ContextMenuEntry {
+    /// Join two strand ends, given as (strand id, end) pairs, with a phosphodiester bond
+    LigateEnds((usize, StrandEnd), (usize, StrandEnd)),
+    /// Write the selected helices, with the strands that lie on them, in a design file
+    ExportSelectionAsModule,
+    /// Reverse the direction of the strand with the given identifier
+    FlipStrand(usize),
+}
+
+impl ContextMenuEntry {
+    /// The entries that can be applied to the current selection
+    pub fn for_selection<S: AppState>(app_state: &S) -> Vec<Self> {
+        let mut ret = Vec::new();
+        if let Some((end_a, end_b)) = selected_strand_ends(app_state) {
+            ret.push(Self::LigateEnds(end_a, end_b));
+        }
+        if let [Selection::Strand(_, s_id)] = app_state.get_selection() {
+            ret.push(Self::FlipStrand(*s_id as usize));
+        }
+        let helices = ensnano_interactor::list_of_helices(app_state.get_selection());
+        if helices.map_or(false, |(_, helices)| !helices.is_empty()) {
+            ret.push(Self::ExportSelectionAsModule);
+        }
+        ret
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::LigateEnds(..) => "Ligate Ends",
+            Self::ExportSelectionAsModule => "Export Selection as Module",
+            Self::FlipStrand(_) => "Flip Direction",
+        }
+    }
+
+    fn trigger<R: Requests>(&self, requests: &mut R) {
+        match self {
+            Self::LigateEnds(end_a, end_b) => requests.connect_strands(*end_a, *end_b),
+            Self::ExportSelectionAsModule => requests.export_selection_as_module(),
+            Self::FlipStrand(s_id) => requests.flip_strand(*s_id),
+        }
+    }
+}
+
+/// If the selection consists of two strand ends, return these ends
+pub(super) fn selected_strand_ends<S: AppState>(
+    app_state: &S,
+) -> Option<((usize, StrandEnd), (usize, StrandEnd))> {
+    let (n1, n2) = ensnano_interactor::pair_of_nucleotides(app_state.get_selection())?;
+    let reader = app_state.get_reader();
+    let end_a = reader.strand_end_of_nucl(n1)?;
+    let end_b = reader.strand_end_of_nucl(n2)?;
+    Some((end_a, end_b))
+}
+
+pub struct ContextMenu<R: Requests> {
+    requests: Arc<Mutex<R>>,
+    entries: Vec<(ContextMenuEntry, button::State)>,
+    ui_size: UiSize,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    EntryPressed(usize),
+}
+
+impl<R: Requests> ContextMenu<R> {
+    pub fn new(requests: Arc<Mutex<R>>, entries: Vec<ContextMenuEntry>, ui_size: UiSize) -> Self {
+        Self {
+            requests,
+            entries: entries
+                .into_iter()
+                .map(|entry| (entry, Default::default()))
+                .collect(),
+            ui_size,
+        }
+    }
+
+    /// The logical size of a context menu with `nb_entries` entries
+    pub fn logical_size(nb_entries: usize, ui_size: UiSize) -> LogicalSize<f64> {
+        let entry_height = (ui_size.button() + SPACING) as f64;
+        LogicalSize::new(
+            CONTEXT_MENU_WIDTH,
+            nb_entries as f64 * entry_height + 2. * PADDING as f64,
+        )
+    }
+}
+
+impl<R: Requests> Program for ContextMenu<R> {
+    type Renderer = iced_wgpu::Renderer;
+    type Message = Message;
+
+    fn update(&mut self, message: Message) -> Command<Message> {
+        match message {
+            Message::EntryPressed(n) => {
+                let mut requests = self.requests.lock().unwrap();
+                if let Some((entry, _)) = self.entries.get(n) {
+                    entry.trigger(&mut *requests);
+                }
+                requests.close_context_menu();
+            }
+        }
+        Command::none()
+    }
+
+    fn view(&mut self) -> Element<Message, iced_wgpu::Renderer> {
+        let ui_size = self.ui_size;
+        let mut column = Column::new().spacing(SPACING).padding(PADDING);
+        for (n, (entry, state)) in self.entries.iter_mut().enumerate() {
+            column = column.push(
+                Button::new(state, Text::new(entry.label()).size(ui_size.main_text()))
+                    .height(Length::Units(ui_size.button()))
+                    .width(Length::Fill)
+                    .on_press(Message::EntryPressed(n)),
+            );
+        }
+        Container::new(column)
+            .style(MenuStyle)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+    }
+}
+
+struct MenuStyle;
+impl container::StyleSheet for MenuStyle {
+    fn style(&self) -> container::Style {
+        container::Style {
+            background: Some(Background::Color(super::left_panel::BACKGROUND)),
+            text_color: Some(Color::WHITE),
+            border_width: 1.,
+            border_color: Color::BLACK,
+            ..container::Style::default()
+        }
+    }
+}
@@ -34,11 +34,16 @@ use ultraviolet::Vec3;
 
 use ensnano_design::{
     elements::{DnaElement, DnaElementKey},
-    CameraId,
+    CameraId, Nucl, StrandModification,
 };
 use ensnano_interactor::{
-    graphics::{Background3D, RenderingMode},
-    ActionMode, SelectionConversion, SelectionMode, SuggestionParameters,
+    graphics::{
+        AnalysisColoring, AnimationKind, AnimationParameters, Background3D, ColorBy, ColorTheme,
+        PostProcessingQuality, RenderingMode, ToneMappingOperator,
+    },
+    ActionMode, ColorBlindPalette, MeasureKind, MirrorAxis, OrderFormat, Selection,
+    SelectionConversion, SelectionGroupCombination, SelectionMode, SortKey,
+    StrandBuildingParameters, StrandEnd, SuggestionParameters, WorkspaceTransform,
 };
 
 use super::{
@@ -59,10 +64,15 @@ use crate::consts::*;
 mod contextual_panel;
 use contextual_panel::{ContextualPanel, ValueKind};
 
+use crate::color_palette::ColorPalette;
+use crate::key_bindings::{HotkeyAction, KeyBindings, KeyCombination};
+use crate::plugins::PluginMenuAction;
+use ensnano_design::TorusKnot;
 use ensnano_interactor::HyperboloidRequest;
 use material_icons::{icon_to_char, Icon as MaterialIcon, FONT as MATERIALFONT};
 use tabs::{
-    CameraShortcut, CameraTab, EditionTab, GridTab, ParametersTab, SequenceTab, SimulationTab,
+    AnalysisTab, CameraShortcut, CameraTab, EditionTab, GridTab, MeasureTab, ParametersTab,
+    RulerField, SequenceTab, SimulationTab, ThemeColor, TrajectoryTab, WorkspaceTab,
 };
 
 const ICONFONT: iced::Font = iced::Font::External {
@@ -89,11 +99,10 @@ pub struct LeftPanel<R: Requests, S: AppState> {
     logical_position: LogicalPosition<f64>,
     #[allow(dead_code)]
     open_color: button::State,
-    sequence_input: SequenceInput,
     requests: Arc<Mutex<R>>,
     #[allow(dead_code)]
     show_torsion: bool,
-    selected_tab: usize,
+    selected_tab: TabId,
     organizer: Organizer<DnaElement>,
     ui_size: UiSize,
     grid_tab: GridTab,
@@ -101,12 +110,54 @@ pub struct LeftPanel<R: Requests, S: AppState> {
     camera_tab: CameraTab,
     simulation_tab: SimulationTab<S>,
     sequence_tab: SequenceTab,
+    measure_tab: MeasureTab,
+    analysis_tab: AnalysisTab,
+    trajectory_tab: TrajectoryTab,
+    workspace_tab: WorkspaceTab,
     parameters_tab: ParametersTab,
     contextual_panel: ContextualPanel<S>,
     camera_shortcut: CameraShortcut,
     application_state: S,
 }
 
+/// The tabs of the left panel, in the order in which they are displayed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TabId {
+    Grid,
+    Edition,
+    Camera,
+    Simulation,
+    Sequence,
+    Measure,
+    Analysis,
+    Trajectory,
+    Workspace,
+    Parameters,
+}
+
+impl TabId {
+    const ALL: [Self; 10] = [
+        Self::Grid,
+        Self::Edition,
+        Self::Camera,
+        Self::Simulation,
+        Self::Sequence,
+        Self::Measure,
+        Self::Analysis,
+        Self::Trajectory,
+        Self::Workspace,
+        Self::Parameters,
+    ];
+
+    fn from_index(index: usize) -> Option<Self> {
+        Self::ALL.get(index).cloned()
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum Message<S> {
     Resized(LogicalSize<f64>, LogicalPosition<f64>),
@@ -115,9 +166,15 @@ pub enum Message<S> {
     MakeGrids,
     SequenceChanged(String),
     SequenceFileRequested,
+    CheckHairpin(bool),
     ColorPicked(Color),
     HsvSatValueChanged(f64, f64),
     StrandNameChanged(usize, String),
+    /// Open the editor of the label attached to a nucleotide
+    EditNucleotideAnnotation(Nucl),
+    NucleotideAnnotationChanged(Nucl, String),
+    NucleotideAnnotationSubmitted(Nucl),
+    StrandModificationPicked(usize, StrandEnd, Option<StrandModification>),
     FinishChangingColor,
     HueChanged(f64),
     NewGrid(GridTypeDescr),
@@ -126,8 +183,26 @@ pub enum Message<S> {
     PositionHelicesChanged(String),
     LengthHelicesChanged(String),
     ScaffoldPositionInput(String),
+    StaplePoolStepsInput(String),
+    StaplePoolsRequested(usize),
+    GridDuplicationOffsetInput(usize, String),
+    DuplicateGrid {
+        grid_id: usize,
+        offset: Vec3,
+    },
+    MirrorAxisPicked(MirrorAxis),
+    GridTwistOffset {
+        grid_id: usize,
+        offset: f32,
+    },
+    GridTwistOffsetReleased,
+    MirrorGrid {
+        grid_id: usize,
+        axis: MirrorAxis,
+    },
     #[allow(dead_code)]
     ShowTorsion(bool),
+    ShowLocalTwist(bool),
     FogRadius(f32),
     FogLength(f32),
     SimRequest,
@@ -138,6 +213,7 @@ pub enum Message<S> {
     },
     NewHyperboloid,
     FinalizeHyperboloid,
+    NewTorusKnot,
     RollTargeted(bool),
     RigidGridSimulation(bool),
     RigidHelicesSimulation(bool),
@@ -147,16 +223,107 @@ pub enum Message<S> {
     ModifiersChanged(ModifiersState),
     UiSizeChanged(UiSize),
     UiSizePicked(UiSize),
-    StapplesRequested,
+    StapplesRequested(OrderFormat),
+    StaplesCsvRequested(OrderFormat),
+    OrderFormatPicked(OrderFormat),
+    PredictSecondaryStructure(usize),
     ToggleText(bool),
+    ShowHelixNumbers(bool),
+    ShowRuler(bool),
+    RulerInput(RulerField, String),
+    ShowLatticeGrid(bool),
     #[allow(dead_code)]
     CleanRequested,
     AddDoubleStrandHelix(bool),
     ToggleVisibility(bool),
     AllVisible,
     Redim2dHelices(bool),
+    HighlightShortestPath,
+    MeasureMode(bool),
+    MeasureKindPicked(MeasureKind),
+    ClearMeasurements,
+    AnalysisColoringPicked(AnalysisColoring),
+    LoadTrajectory,
+    PlayTrajectory(bool),
+    TrajectoryFrame(u32),
+    CloseTrajectory,
+    RelaxDesign,
+    OpenDesignInWorkspace,
+    MainDesignVisibility(bool),
+    WorkspaceDesignVisibility(usize, bool),
+    /// Edit the position of a design of the workspace
+    SelectWorkspaceDesign(usize),
+    MergeWorkspaceDesign(usize),
+    CloseWorkspaceDesign(usize),
+    DockedHelixPicked(usize),
+    DockWorkspaceDesign {
+        design_id: usize,
+        helix: usize,
+    },
+    /// Select the strand with the given identifier
+    SelectStrand(usize),
+    /// Show the given page of the staple list of the analysis tab
+    StaplePageChanged(usize),
+    SelectStrandEnds,
+    SelectDomains,
+    StrandSortKeyPicked(SortKey),
+    ReorderStrands(SortKey),
+    StrandPalettePicked(ColorBlindPalette),
+    RecolorStrands(ColorBlindPalette),
+    ToggleStrandEnd,
+    GoToXoverInput(usize, String),
+    /// Center the 2D view on the cross-over between the nucleotides given as
+    /// (strand id, position on strand) pairs
+    GoToXover((usize, usize), (usize, usize)),
+    SelectionGroupNameInput(String),
+    /// Save the current selection under the given name
+    SaveSelectionGroup(String),
+    RecallSelectionGroup(String, SelectionGroupCombination),
+    DeleteSelectionGroup(String),
+    LoopLengthInput(String),
+    /// Insert `length` unpaired nucleotides after `at`
+    InsertLoop {
+        at: Nucl,
+        length: usize,
+    },
+    HairpinStemLengthInput(String),
+    HairpinLoopLengthInput(String),
+    CreateHairpin {
+        at: Nucl,
+        stem_length: usize,
+        loop_length: usize,
+    },
+    /// Open the cyclic scaffold so that the nucleotide becomes its 5' end
+    BreakScaffoldAt(Nucl),
+    Start2dXoverTool,
+    /// Join two strand ends, given as (strand id, end) pairs, with a phosphodiester bond
+    LigateEnds((usize, StrandEnd), (usize, StrandEnd)),
+    FlipStrand(usize),
+    OptimizeRolls,
+    LockSelection(bool),
+    /// Enter or leave the action mode in which helices are added by clicking on grids
+    BuildHelixMode(bool),
     InvertScroll(bool),
+    SonificationEnabled(bool),
+    SonificationVolume(f32),
+    PostProcessingQualityPicked(PostProcessingQuality),
+    KeyBindingsChanged(KeyBindings),
+    RecordKeyBinding(HotkeyAction, Option<KeyCombination>),
+    CancelKeyBindingRecording,
+    PluginActionsChanged(Vec<PluginMenuAction>),
+    ColorPaletteChanged(ColorPalette),
+    EditHistorySizeLoaded(usize),
+    SessionLogEnabled(bool),
+    SessionLogEnabledLoaded(bool),
+    StrandBuildingParametersLoaded(StrandBuildingParameters),
+    PaletteColorNameInput(String),
+    /// Add the color of the color picker to the palette of named colors
+    AddPaletteColor,
+    ExportColorPalette,
+    ImportColorPalette,
+    PluginMenuAction(usize, String),
     BrownianMotion(bool),
+    FreezeSsdna(bool),
     Nothing,
     CancelHyperboloid,
     SelectionValueChanged(usize, String),
@@ -168,9 +335,31 @@ pub enum Message<S> {
     ShowTutorial,
     RenderingMode(RenderingMode),
     Background3D(Background3D),
+    /// The color theme saved in the preferences of the user was read
+    ColorThemeLoaded(ColorTheme),
+    ColorByPicked(ColorBy),
+    ShowScaffoldRoute(bool),
+    ThemeColorInput(ThemeColor, String),
+    DofFocalDistance(f32),
+    DofAperture(f32),
+    FlyThrough(bool),
+    FlySpeed(f32),
+    EdlStrength(f32),
+    EdlRadius(f32),
+    AoStrength(f32),
+    AoRadius(f32),
+    ToneMappingOperator(ToneMappingOperator),
+    ToneMappingExposure(f32),
+    SelectLight(tabs::LightId),
+    LightAzimuth(f32),
+    LightElevation(f32),
+    LightHue(f32),
+    LightSaturation(f32),
+    LightIntensity(f32),
     OpenLink(&'static str),
     NewApplicationState(S),
     FogChoice(tabs::FogChoice),
+    CenterFogOnSelection,
     SetScaffoldSeqButtonPressed,
     ResetSimulation,
     EditCameraName(String),
@@ -180,6 +369,22 @@ pub enum Message<S> {
     DeleteCamera(CameraId),
     SelectCamera(CameraId),
     NewCustomCamera,
+    ExportCameraView,
+    /// Write the graph of the helices connected by cross-overs
+    ExportConnectivityGraph,
+    CleanupOrphans,
+    ImportCameraView,
+    ScreenshotWidthInput(String),
+    ScreenshotHeightInput(String),
+    /// Write a PNG image of `size` pixels of the 3D view
+    Screenshot {
+        size: (u32, u32),
+    },
+    AnimationKindPicked(AnimationKind),
+    AnimationFramesInput(String),
+    /// Write the frames of an animation of the 3D view as numbered PNG images
+    RecordAnimation(AnimationParameters),
+    ExportSelectionAsModule,
     UpdateCamera(CameraId),
     NewSuggestionParameters(SuggestionParameters),
     ContextualValueChanged(ValueKind, usize, String),
@@ -203,14 +408,17 @@ impl<R: Requests, S: AppState> LeftPanel<R, S> {
         logical_position: LogicalPosition<f64>,
         first_time: bool,
     ) -> Self {
-        let selected_tab = if first_time { 0 } else { 5 };
+        let selected_tab = if first_time {
+            TabId::Grid
+        } else {
+            TabId::Parameters
+        };
         let mut organizer = Organizer::new();
         organizer.set_width(logical_size.width as u16);
         Self {
             logical_size,
             logical_position,
             open_color: Default::default(),
-            sequence_input: SequenceInput::new(),
             requests,
             show_torsion: false,
             selected_tab,
@@ -221,6 +429,10 @@ impl<R: Requests, S: AppState> LeftPanel<R, S> {
             camera_tab: CameraTab::new(),
             simulation_tab: SimulationTab::new(),
             sequence_tab: SequenceTab::new(),
+            measure_tab: MeasureTab::new(),
+            analysis_tab: AnalysisTab::new(),
+            trajectory_tab: TrajectoryTab::new(),
+            workspace_tab: WorkspaceTab::new(),
             parameters_tab: ParametersTab::new(),
             contextual_panel: ContextualPanel::new(logical_size.width as u32),
             camera_shortcut: CameraShortcut::new(),
@@ -293,11 +505,13 @@ impl<R: Requests, S: AppState> LeftPanel<R, S> {
     }
 
     pub fn has_keyboard_priority(&self) -> bool {
-        self.sequence_input.has_keyboard_priority()
-            || self.contextual_panel.has_keyboard_priority()
+        self.contextual_panel.has_keyboard_priority()
             || self.organizer.has_keyboard_priority()
             || self.sequence_tab.has_keyboard_priority()
             || self.camera_shortcut.has_keyboard_priority()
+            || self.edition_tab.has_keyboard_priority()
+            || self.parameters_tab.has_keyboard_priority()
+            || self.camera_tab.has_keyboard_priority()
     }
 }
 
@@ -308,15 +522,59 @@ impl<R: Requests, S: AppState> Program for LeftPanel<R, S> {
     fn update(&mut self, message: Message<S>) -> Command<Message<S>> {
         match message {
             Message::SequenceChanged(s) => {
+                let mut sequence = sequence_input::clean_sequence(&s);
+                let strand_length = match self.application_state.get_selection() {
+                    [Selection::Strand(_, s_id)] => self
+                        .application_state
+                        .get_reader()
+                        .get_strand_length(*s_id as usize),
+                    _ => None,
+                };
+                let truncated_length = strand_length
+                    .filter(|length| sequence_input::truncate_sequence(&mut sequence, *length));
                 self.requests
                     .lock()
                     .unwrap()
-                    .set_selected_strand_sequence(s.clone());
-                self.sequence_input.update_sequence(s);
+                    .set_selected_strand_sequence(sequence.clone());
+                self.edition_tab.update_sequence(sequence, truncated_length);
+            }
+            Message::CheckHairpin(check_hairpin) => {
+                self.edition_tab.set_check_hairpin(check_hairpin)
             }
             Message::StrandNameChanged(s_id, name) => {
                 self.requests.lock().unwrap().set_strand_name(s_id, name)
             }
+            Message::EditNucleotideAnnotation(nucl) => {
+                let label = self
+                    .application_state
+                    .get_reader()
+                    .nucleotide_annotation(nucl)
+                    .unwrap_or_default();
+                self.contextual_panel.edit_annotation(nucl, label);
+            }
+            Message::NucleotideAnnotationChanged(nucl, label) => {
+                self.contextual_panel.update_annotation(nucl, label)
+            }
+            Message::NucleotideAnnotationSubmitted(nucl) => {
+                if let Some(label) = self.contextual_panel.take_annotation(nucl) {
+                    let old_label = self
+                        .application_state
+                        .get_reader()
+                        .nucleotide_annotation(nucl)
+                        .unwrap_or_default();
+                    if label != old_label {
+                        self.requests
+                            .lock()
+                            .unwrap()
+                            .annotate_nucleotide(nucl, label);
+                    }
+                }
+            }
+            Message::StrandModificationPicked(s_id, end, modification) => self
+                .requests
+                .lock()
+                .unwrap()
+                .set_strand_modification(s_id, end, modification),
             Message::SequenceFileRequested => {
                 let dialog = rfd::AsyncFileDialog::new().pick_file();
                 let requests = self.requests.clone();
@@ -326,10 +584,9 @@ impl<R: Requests, S: AppState> Program for LeftPanel<R, S> {
                         if let Some(handle) = file {
                             let content = std::fs::read_to_string(handle.path());
                             if let Ok(content) = content {
-                                requests
-                                    .lock()
-                                    .unwrap()
-                                    .set_selected_strand_sequence(content);
+                                requests.lock().unwrap().set_selected_strand_sequence(
+                                    sequence_input::clean_sequence(&content),
+                                );
                             }
                         }
                     };
@@ -406,10 +663,20 @@ impl<R: Requests, S: AppState> Program for LeftPanel<R, S> {
                     self.requests.lock().unwrap().set_scaffold_shift(n);
                 }
             }
+            Message::StaplePoolStepsInput(steps_str) => {
+                self.sequence_tab.update_pool_steps_str(steps_str)
+            }
+            Message::StaplePoolsRequested(n_steps) => {
+                self.requests.lock().unwrap().download_staple_pools(n_steps)
+            }
             Message::ShowTorsion(b) => {
                 self.requests.lock().unwrap().set_torsion_visibility(b);
                 self.show_torsion = b;
             }
+            Message::ShowLocalTwist(b) => {
+                self.requests.lock().unwrap().set_local_twist_visibility(b);
+                self.edition_tab.show_local_twist = b;
+            }
             Message::FogLength(length) => {
                 self.camera_tab.fog_length(length);
                 let request = self.camera_tab.get_fog_request();
@@ -434,7 +701,20 @@ impl<R: Requests, S: AppState> Program for LeftPanel<R, S> {
                 self.camera_tab.fog_visible(visble);
                 self.camera_tab.fog_dark(dark);
                 let request = self.camera_tab.get_fog_request();
-                self.requests.lock().unwrap().set_fog_parameters(request);
+                let mut requests = self.requests.lock().unwrap();
+                requests.set_fog_parameters(request);
+                // Choosing the fog again discards the center chosen with the "Center fog on
+                // selection" button
+                requests.reset_fog_center();
+            }
+            Message::CenterFogOnSelection => {
+                // The fog can only be centered on an arbitrary point if it is not attached to
+                // the camera
+                self.camera_tab.fog_camera(false);
+                let request = self.camera_tab.get_fog_request();
+                let mut requests = self.requests.lock().unwrap();
+                requests.set_fog_parameters(request);
+                requests.center_fog_on_selection();
             }
             Message::DescreteValue {
                 factory_id,
@@ -452,6 +732,45 @@ impl<R: Requests, S: AppState> Program for LeftPanel<R, S> {
                             .update_scroll_sensitivity(request);
                     }
                 }
+                FactoryId::MinStrandLength => {
+                    let mut request = None;
+                    self.parameters_tab.update_min_strand_length_request(
+                        value_id,
+                        value,
+                        &mut request,
+                    );
+                    if let Some(length) = request {
+                        let parameters = self
+                            .application_state
+                            .get_strand_building_parameters()
+                            .with_min_strand_length(length);
+                        self.requests
+                            .lock()
+                            .unwrap()
+                            .set_strand_building_parameters(parameters);
+                    }
+                }
+                FactoryId::EditHistorySize => {
+                    let mut request = None;
+                    self.parameters_tab.update_edit_history_size_request(
+                        value_id,
+                        value,
+                        &mut request,
+                    );
+                    if let Some(size) = request {
+                        self.requests.lock().unwrap().set_edit_history_size(size);
+                    }
+                }
+                FactoryId::WorkspaceTransform => {
+                    if let Some((design_id, transform)) =
+                        self.workspace_tab.update_transform_request(value_id, value)
+                    {
+                        self.requests
+                            .lock()
+                            .unwrap()
+                            .set_workspace_design_transform(design_id, transform);
+                    }
+                }
                 FactoryId::HelixRoll => {
                     let mut request = None;
                     self.edition_tab
@@ -474,6 +793,9 @@ impl<R: Requests, S: AppState> Program for LeftPanel<R, S> {
                             .update_current_hyperboloid(request);
                     }
                 }
+                FactoryId::TorusKnot => {
+                    self.grid_tab.update_torus_knot_request(value_id, value);
+                }
                 FactoryId::RigidBody => {
                     let mut request = None;
                     self.simulation_tab
@@ -519,6 +841,17 @@ impl<R: Requests, S: AppState> Program for LeftPanel<R, S> {
                         .update_rigid_body_simulation_parameters(request);
                 }
             }
+            Message::FreezeSsdna(b) => {
+                self.simulation_tab.set_freeze_ssdna(b);
+                let mut request: Option<RigidBodyParametersRequest> = None;
+                self.simulation_tab.make_rigid_body_request(&mut request);
+                if let Some(request) = request {
+                    self.requests
+                        .lock()
+                        .unwrap()
+                        .update_rigid_body_simulation_parameters(request);
+                }
+            }
             Message::NewHyperboloid => {
                 let mut request: Option<HyperboloidRequest> = None;
                 self.grid_tab.new_hyperboloid(&mut request);
@@ -529,6 +862,11 @@ impl<R: Requests, S: AppState> Program for LeftPanel<R, S> {
                         .create_new_hyperboloid(request);
                 }
             }
+            Message::NewTorusKnot => {
+                if let Some(knot) = self.grid_tab.get_torus_knot() {
+                    self.requests.lock().unwrap().add_torus_knot(knot);
+                }
+            }
             Message::FinalizeHyperboloid => {
                 self.requests.lock().unwrap().finalize_hyperboloid();
             }
@@ -561,6 +899,45 @@ impl<R: Requests, S: AppState> Program for LeftPanel<R, S> {
                 }
             }
             Message::MakeGrids => self.requests.lock().unwrap().make_grid_from_selection(),
+            Message::GridDuplicationOffsetInput(coord, value) => {
+                self.grid_tab.update_duplication_offset(coord, value)
+            }
+            Message::DuplicateGrid { grid_id, offset } => self
+                .requests
+                .lock()
+                .unwrap()
+                .duplicate_grid(grid_id, offset),
+            Message::MirrorAxisPicked(axis) => {
+                self.grid_tab.set_mirror_axis(Some(axis));
+                self.requests.lock().unwrap().show_mirror_plane(Some(axis));
+            }
+            Message::GridTwistOffset { grid_id, offset } => {
+                let initial = self
+                    .application_state
+                    .get_reader()
+                    .get_grid_twist_offset(grid_id)
+                    .unwrap_or_default();
+                let offset = offset.to_radians();
+                self.grid_tab.drag_twist_offset(grid_id, initial, offset);
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .preview_grid_twist_offset(grid_id, offset);
+            }
+            Message::GridTwistOffsetReleased => {
+                if let Some((grid_id, initial, offset)) = self.grid_tab.end_twist_offset_drag() {
+                    self.requests
+                        .lock()
+                        .unwrap()
+                        .set_grid_twist_offset(grid_id, initial, offset);
+                }
+            }
+            Message::MirrorGrid { grid_id, axis } => {
+                self.grid_tab.set_mirror_axis(None);
+                let mut requests = self.requests.lock().unwrap();
+                requests.mirror_grid(grid_id, axis);
+                requests.show_mirror_plane(None);
+            }
             Message::RollTargeted(b) => {
                 let selection = self.application_state.get_selection_as_dnaelement();
                 if b {
@@ -576,25 +953,35 @@ impl<R: Requests, S: AppState> Program for LeftPanel<R, S> {
                 }
             }
             Message::TabSelected(n) => {
-                if let ActionMode::BuildHelix { .. } = self.application_state.get_action_mode() {
-                    if n != 0 {
-                        let action_mode = ActionMode::Normal;
+                let tab = if let Some(tab) = TabId::from_index(n) {
+                    tab
+                } else {
+                    log::error!("There is no tab with index {}", n);
+                    return Command::none();
+                };
+                // Nucleotides can only be measured while the measure tab is open
+                if let ActionMode::Measure(_) = self.application_state.get_action_mode() {
+                    if tab != TabId::Measure {
                         self.requests
                             .lock()
                             .unwrap()
-                            .change_action_mode(action_mode);
+                            .change_action_mode(ActionMode::Normal);
                     }
                 }
-                if n != 0 {
+                if tab != TabId::Grid {
                     if self.application_state.is_building_hyperboloid() {
                         self.requests.lock().unwrap().finalize_hyperboloid();
                     }
+                    if self.grid_tab.is_showing_mirror_plane() {
+                        self.grid_tab.set_mirror_axis(None);
+                        self.requests.lock().unwrap().show_mirror_plane(None);
+                    }
                 }
-                if self.selected_tab == 3 && n != 3 {
+                if self.selected_tab == TabId::Simulation && tab != TabId::Simulation {
                     self.simulation_tab
                         .leave_tab(self.requests.clone(), &self.application_state);
                 }
-                self.selected_tab = n;
+                self.selected_tab = tab;
             }
             Message::OrganizerMessage(m) => {
                 let next_message = self.organizer_message(m);
@@ -613,13 +1000,57 @@ impl<R: Requests, S: AppState> Program for LeftPanel<R, S> {
                     .unwrap()
                     .set_scaffold_sequence(self.sequence_tab.get_scaffold_shift());
             }
-            Message::StapplesRequested => self.requests.lock().unwrap().download_stapples(),
+            Message::StapplesRequested(order_format) => self
+                .requests
+                .lock()
+                .unwrap()
+                .download_stapples(order_format),
+            Message::StaplesCsvRequested(order_format) => self
+                .requests
+                .lock()
+                .unwrap()
+                .download_staples_csv(order_format),
+            Message::OrderFormatPicked(order_format) => {
+                self.sequence_tab.set_order_format(order_format)
+            }
+            Message::PredictSecondaryStructure(s_id) => self
+                .requests
+                .lock()
+                .unwrap()
+                .predict_secondary_structure(s_id),
             Message::ToggleText(b) => {
                 self.requests
                     .lock()
                     .unwrap()
                     .set_dna_sequences_visibility(b);
                 self.sequence_tab.toggle_text_value(b);
+                self.camera_tab.show_base_letters = b;
+            }
+            Message::ShowHelixNumbers(b) => {
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .set_helix_numbers_visibility(b);
+                self.camera_tab.show_helix_numbers = b;
+            }
+            Message::ShowRuler(b) => {
+                self.requests.lock().unwrap().set_2d_ruler_visibility(b);
+                self.sequence_tab.show_ruler = b;
+            }
+            Message::RulerInput(field, value) => {
+                if let Some(parameters) = self.sequence_tab.update_ruler_input(field, value) {
+                    self.requests
+                        .lock()
+                        .unwrap()
+                        .set_2d_ruler_parameters(parameters);
+                }
+            }
+            Message::ShowLatticeGrid(b) => {
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .set_2d_lattice_grid_visibility(b);
+                self.grid_tab.show_lattice_grid = b;
             }
             Message::CleanRequested => self.requests.lock().unwrap().remove_empty_domains(),
             Message::AddDoubleStrandHelix(b) => {
@@ -633,10 +1064,278 @@ impl<R: Requests, S: AppState> Program for LeftPanel<R, S> {
             Message::ToggleVisibility(b) => self.requests.lock().unwrap().toggle_visibility(b),
             Message::AllVisible => self.requests.lock().unwrap().make_all_elements_visible(),
             Message::Redim2dHelices(b) => self.requests.lock().unwrap().resize_2d_helices(b),
+            Message::HighlightShortestPath => {
+                self.requests.lock().unwrap().highlight_shortest_path()
+            }
+            Message::MeasureMode(b) => {
+                let action_mode = if b {
+                    ActionMode::Measure(self.measure_tab.measure_kind)
+                } else {
+                    ActionMode::Normal
+                };
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .change_action_mode(action_mode);
+            }
+            Message::MeasureKindPicked(kind) => {
+                self.measure_tab.measure_kind = kind;
+                if let ActionMode::Measure(_) = self.application_state.get_action_mode() {
+                    self.requests
+                        .lock()
+                        .unwrap()
+                        .change_action_mode(ActionMode::Measure(kind));
+                }
+            }
+            Message::ClearMeasurements => self.requests.lock().unwrap().clear_measurements(),
+            Message::LoadTrajectory => self.requests.lock().unwrap().load_oxdna_trajectory(),
+            Message::PlayTrajectory(playing) => {
+                self.requests.lock().unwrap().play_trajectory(playing)
+            }
+            Message::TrajectoryFrame(frame) => self
+                .requests
+                .lock()
+                .unwrap()
+                .set_trajectory_frame(frame as usize),
+            Message::CloseTrajectory => self.requests.lock().unwrap().close_trajectory(),
+            Message::RelaxDesign => self.requests.lock().unwrap().relax_design(),
+            Message::OpenDesignInWorkspace => {
+                self.requests.lock().unwrap().open_design_in_workspace()
+            }
+            Message::MainDesignVisibility(visible) => self
+                .requests
+                .lock()
+                .unwrap()
+                .set_main_design_visibility(visible),
+            Message::WorkspaceDesignVisibility(design_id, visible) => self
+                .requests
+                .lock()
+                .unwrap()
+                .set_workspace_design_visibility(design_id, visible),
+            Message::SelectWorkspaceDesign(design_id) => {
+                if let Some(design) = self
+                    .application_state
+                    .get_workspace_designs()
+                    .get(design_id)
+                {
+                    self.workspace_tab
+                        .select_design(design_id, design.transform);
+                }
+            }
+            Message::MergeWorkspaceDesign(design_id) => {
+                self.workspace_tab.design_removed(design_id);
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .merge_workspace_design(design_id);
+            }
+            Message::CloseWorkspaceDesign(design_id) => {
+                self.workspace_tab.design_removed(design_id);
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .close_workspace_design(design_id);
+            }
+            Message::DockedHelixPicked(helix) => self.workspace_tab.set_docked_helix(helix),
+            Message::DockWorkspaceDesign { design_id, helix } => {
+                if let Some(design) = self
+                    .application_state
+                    .get_workspace_designs()
+                    .get(design_id)
+                {
+                    self.workspace_tab
+                        .docking_requested(design_id, design.transform);
+                }
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .dock_workspace_design(design_id, helix);
+            }
+            Message::SelectStrand(s_id) => self.requests.lock().unwrap().set_selected_keys(
+                vec![DnaElementKey::Strand(s_id)],
+                None,
+                false,
+            ),
+            Message::StaplePageChanged(page) => self.analysis_tab.set_staple_page(page),
+            Message::AnalysisColoringPicked(coloring) => {
+                self.analysis_tab.analysis_coloring = coloring;
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .set_analysis_coloring(coloring);
+            }
+            Message::StrandSortKeyPicked(key) => self.edition_tab.set_strand_sort_key(key),
+            Message::ReorderStrands(key) => self.requests.lock().unwrap().reorder_strands(key),
+            Message::StrandPalettePicked(palette) => self.edition_tab.set_strand_palette(palette),
+            Message::RecolorStrands(palette) => {
+                self.requests.lock().unwrap().recolor_strands(palette)
+            }
+            Message::SelectStrandEnds => {
+                let end = self.edition_tab.get_strand_end();
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .change_selection_mode(SelectionMode::StrandEnd(end))
+            }
+            Message::SelectDomains => self
+                .requests
+                .lock()
+                .unwrap()
+                .change_selection_mode(SelectionMode::Domain),
+            Message::ToggleStrandEnd => {
+                let end = self.edition_tab.toggle_strand_end();
+                if let SelectionMode::StrandEnd(_) = self.application_state.get_selection_mode() {
+                    self.requests
+                        .lock()
+                        .unwrap()
+                        .change_selection_mode(SelectionMode::StrandEnd(end))
+                }
+            }
+            Message::GoToXoverInput(idx, value) => self.edition_tab.update_xover_input(idx, value),
+            Message::GoToXover(a, b) => self.requests.lock().unwrap().go_to_xover(a, b),
+            Message::SelectionGroupNameInput(name) => {
+                self.edition_tab.update_selection_group_name(name)
+            }
+            Message::SaveSelectionGroup(name) => {
+                self.requests.lock().unwrap().save_selection_group(name)
+            }
+            Message::RecallSelectionGroup(name, combination) => self
+                .requests
+                .lock()
+                .unwrap()
+                .recall_selection_group(name, combination),
+            Message::DeleteSelectionGroup(name) => {
+                self.requests.lock().unwrap().delete_selection_group(name)
+            }
+            Message::LoopLengthInput(value) => self.edition_tab.update_loop_length_input(value),
+            Message::InsertLoop { at, length } => {
+                self.requests.lock().unwrap().insert_loop(at, length)
+            }
+            Message::HairpinStemLengthInput(value) => {
+                self.edition_tab.update_hairpin_stem_length_input(value)
+            }
+            Message::HairpinLoopLengthInput(value) => {
+                self.edition_tab.update_hairpin_loop_length_input(value)
+            }
+            Message::CreateHairpin {
+                at,
+                stem_length,
+                loop_length,
+            } => self
+                .requests
+                .lock()
+                .unwrap()
+                .create_hairpin(at, stem_length, loop_length),
+            Message::BreakScaffoldAt(at) => self.requests.lock().unwrap().break_scaffold_at(at),
+            Message::Start2dXoverTool => self.requests.lock().unwrap().start_2d_xover_tool(),
+            Message::LigateEnds(end_a, end_b) => {
+                self.requests.lock().unwrap().connect_strands(end_a, end_b)
+            }
+            Message::FlipStrand(s_id) => self.requests.lock().unwrap().flip_strand(s_id),
+            Message::OptimizeRolls => {
+                if self
+                    .application_state
+                    .get_simulation_state()
+                    .is_optimizing_rolls()
+                {
+                    self.requests.lock().unwrap().stop_simulations()
+                } else {
+                    let selection = self.application_state.get_selection_as_dnaelement();
+                    let target_helices = self.edition_tab.get_roll_optimization_targets(&selection);
+                    self.requests.lock().unwrap().optimize_rolls(target_helices)
+                }
+            }
+            Message::LockSelection(locked) => {
+                self.requests.lock().unwrap().set_selection_lock(locked)
+            }
+            Message::BuildHelixMode(b) => {
+                let action_mode = if b {
+                    self.contextual_panel.get_build_helix_mode()
+                } else {
+                    ActionMode::Normal
+                };
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .change_action_mode(action_mode);
+            }
             Message::InvertScroll(b) => {
                 self.requests.lock().unwrap().invert_scroll(b);
                 self.parameters_tab.invert_y_scroll = b;
             }
+            Message::SonificationEnabled(enabled) => {
+                self.parameters_tab.sonification.enabled = enabled;
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .set_sonification(self.parameters_tab.sonification);
+            }
+            Message::SonificationVolume(volume) => {
+                self.parameters_tab.sonification.volume = volume;
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .set_sonification(self.parameters_tab.sonification);
+            }
+            Message::PostProcessingQualityPicked(quality) => {
+                self.parameters_tab.post_processing_quality = quality;
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .set_post_processing_quality(quality);
+            }
+            Message::KeyBindingsChanged(bindings) => {
+                self.parameters_tab.update_key_bindings(&bindings)
+            }
+            Message::RecordKeyBinding(action, previous) => {
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .record_key_binding(action, previous);
+                self.parameters_tab
+                    .start_recording_key_binding(action, previous);
+            }
+            Message::CancelKeyBindingRecording => {
+                self.requests.lock().unwrap().cancel_key_binding_recording();
+                self.parameters_tab.cancel_key_binding_recording();
+            }
+            Message::PluginActionsChanged(actions) => {
+                self.parameters_tab.update_plugin_actions(actions)
+            }
+            Message::ColorPaletteChanged(palette) => {
+                self.parameters_tab.update_color_palette(palette)
+            }
+            Message::EditHistorySizeLoaded(size) => self.parameters_tab.set_edit_history_size(size),
+            Message::SessionLogEnabled(enabled) => {
+                self.parameters_tab.session_log_enabled = enabled;
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .set_session_log_enabled(enabled);
+            }
+            Message::SessionLogEnabledLoaded(enabled) => {
+                self.parameters_tab.session_log_enabled = enabled
+            }
+            Message::StrandBuildingParametersLoaded(parameters) => self
+                .parameters_tab
+                .set_min_strand_length(parameters.min_strand_length),
+            Message::PaletteColorNameInput(name) => {
+                self.parameters_tab.update_palette_color_name(name)
+            }
+            Message::AddPaletteColor => {
+                if let Some(name) = self.parameters_tab.take_palette_color_name() {
+                    let color = self.edition_tab.strand_color_change();
+                    self.requests.lock().unwrap().add_palette_color(name, color);
+                }
+            }
+            Message::ExportColorPalette => self.requests.lock().unwrap().export_color_palette(),
+            Message::ImportColorPalette => self.requests.lock().unwrap().import_color_palette(),
+            Message::PluginMenuAction(plugin_id, name) => {
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .trigger_plugin_action(plugin_id, name);
+            }
             Message::CancelHyperboloid => {
                 self.requests.lock().unwrap().cancel_hyperboloid();
             }
@@ -667,6 +1366,135 @@ impl<R: Requests, S: AppState> Program for LeftPanel<R, S> {
                     .change_3d_background(bg.clone());
                 self.camera_tab.background3d = bg;
             }
+            Message::ColorThemeLoaded(theme) => self.camera_tab.set_color_theme(theme),
+            Message::ColorByPicked(color_by) => {
+                self.camera_tab.color_theme.color_by = color_by;
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .set_color_theme(self.camera_tab.color_theme.clone());
+            }
+            Message::ShowScaffoldRoute(show) => {
+                self.camera_tab.color_theme.scaffold_route = show;
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .set_color_theme(self.camera_tab.color_theme.clone());
+            }
+            Message::ThemeColorInput(theme_color, value) => {
+                if self.camera_tab.update_theme_color_input(theme_color, value) {
+                    self.requests
+                        .lock()
+                        .unwrap()
+                        .set_color_theme(self.camera_tab.color_theme.clone());
+                }
+            }
+            Message::DofFocalDistance(distance) => {
+                self.camera_tab.depth_of_field.focal_distance = distance;
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .set_depth_of_field(self.camera_tab.depth_of_field);
+            }
+            Message::DofAperture(aperture) => {
+                self.camera_tab.depth_of_field.aperture = aperture;
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .set_depth_of_field(self.camera_tab.depth_of_field);
+            }
+            Message::FlyThrough(enabled) => {
+                self.camera_tab.fly_through.enabled = enabled;
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .set_fly_through(self.camera_tab.fly_through);
+            }
+            Message::FlySpeed(speed) => {
+                self.camera_tab.fly_through.speed = speed;
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .set_fly_through(self.camera_tab.fly_through);
+            }
+            Message::EdlStrength(strength) => {
+                self.camera_tab.eye_dome_lighting.strength = strength;
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .set_eye_dome_lighting(self.camera_tab.eye_dome_lighting);
+            }
+            Message::EdlRadius(radius) => {
+                self.camera_tab.eye_dome_lighting.radius = radius;
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .set_eye_dome_lighting(self.camera_tab.eye_dome_lighting);
+            }
+            Message::AoStrength(strength) => {
+                self.camera_tab.ambient_occlusion.strength = strength;
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .set_ambient_occlusion(self.camera_tab.ambient_occlusion);
+            }
+            Message::AoRadius(radius) => {
+                self.camera_tab.ambient_occlusion.radius = radius;
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .set_ambient_occlusion(self.camera_tab.ambient_occlusion);
+            }
+            Message::ToneMappingOperator(operator) => {
+                self.camera_tab.tone_mapping.operator = operator;
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .set_tone_mapping(self.camera_tab.tone_mapping);
+            }
+            Message::ToneMappingExposure(exposure) => {
+                self.camera_tab.tone_mapping.exposure = exposure;
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .set_tone_mapping(self.camera_tab.tone_mapping);
+            }
+            Message::SelectLight(light) => self.camera_tab.select_light(light),
+            Message::LightAzimuth(azimuth) => {
+                self.camera_tab.set_light_azimuth(azimuth);
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .set_lighting(self.camera_tab.lighting);
+            }
+            Message::LightElevation(elevation) => {
+                self.camera_tab.set_light_elevation(elevation);
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .set_lighting(self.camera_tab.lighting);
+            }
+            Message::LightHue(hue) => {
+                self.camera_tab.set_light_hue(hue);
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .set_lighting(self.camera_tab.lighting);
+            }
+            Message::LightSaturation(saturation) => {
+                self.camera_tab.set_light_saturation(saturation);
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .set_lighting(self.camera_tab.lighting);
+            }
+            Message::LightIntensity(intensity) => {
+                self.camera_tab.set_light_intensity(intensity);
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .set_lighting(self.camera_tab.lighting);
+            }
             Message::ForceHelp => {
                 self.contextual_panel.force_help = true;
                 self.contextual_panel.show_tutorial = false;
@@ -683,6 +1511,8 @@ impl<R: Requests, S: AppState> Program for LeftPanel<R, S> {
                 if state.design_was_modified(&self.application_state) {
                     let reader = state.get_reader();
                     self.organizer.update_elements(reader.get_dna_elements());
+                    self.analysis_tab
+                        .update_staples(reader.get_staple_statistics());
                     self.contextual_panel.state_updated();
                 }
                 if state.selection_was_updated(&self.application_state) {
@@ -725,6 +1555,27 @@ impl<R: Requests, S: AppState> Program for LeftPanel<R, S> {
                 self.requests.lock().unwrap().create_new_camera();
                 self.camera_shortcut.scroll_down()
             }
+            Message::ExportCameraView => self.requests.lock().unwrap().export_camera_view(),
+            Message::ExportConnectivityGraph => {
+                self.requests.lock().unwrap().export_connectivity_graph()
+            }
+            Message::CleanupOrphans => self.requests.lock().unwrap().cleanup_orphans(),
+            Message::ImportCameraView => self.requests.lock().unwrap().import_camera_view(),
+            Message::ScreenshotWidthInput(value) => {
+                self.camera_tab.update_screenshot_width_input(value)
+            }
+            Message::ScreenshotHeightInput(value) => {
+                self.camera_tab.update_screenshot_height_input(value)
+            }
+            Message::Screenshot { size } => self.requests.lock().unwrap().export_scene_png(size),
+            Message::AnimationKindPicked(kind) => self.camera_tab.animation_kind = kind,
+            Message::AnimationFramesInput(value) => self.camera_tab.update_nb_frames_input(value),
+            Message::RecordAnimation(parameters) => {
+                self.requests.lock().unwrap().record_animation(parameters)
+            }
+            Message::ExportSelectionAsModule => {
+                self.requests.lock().unwrap().export_selection_as_module()
+            }
             Message::UpdateCamera(camera_id) => {
                 self.requests.lock().unwrap().update_camera(camera_id)
             }
@@ -748,7 +1599,8 @@ impl<R: Requests, S: AppState> Program for LeftPanel<R, S> {
 
     fn view(&mut self) -> Element<Message<S>> {
         let width = self.logical_size.cast::<u16>().width;
-        let tabs: Tabs<Message<S>, Backend> = Tabs::new(self.selected_tab, Message::TabSelected)
+        let selected_tab = self.selected_tab.index();
+        let tabs: Tabs<Message<S>, Backend> = Tabs::new(selected_tab, Message::TabSelected)
             .push(
                 TabLabel::Text(format!("{}", icon_to_char(MaterialIcon::GridOn))),
                 self.grid_tab
@@ -756,8 +1608,12 @@ impl<R: Requests, S: AppState> Program for LeftPanel<R, S> {
             )
             .push(
                 TabLabel::Text(format!("{}", icon_to_char(MaterialIcon::Edit))),
-                self.edition_tab
-                    .view(self.ui_size.clone(), width, &self.application_state),
+                self.edition_tab.view(
+                    self.ui_size.clone(),
+                    width,
+                    &self.application_state,
+                    self.contextual_panel.get_build_helix_mode(),
+                ),
             )
             .push(
                 TabLabel::Text(format!("{}", icon_to_char(MaterialIcon::Videocam))),
@@ -773,6 +1629,26 @@ impl<R: Requests, S: AppState> Program for LeftPanel<R, S> {
                 self.sequence_tab
                     .view(self.ui_size.clone(), &self.application_state),
             )
+            .push(
+                TabLabel::Text(format!("{}", icon_to_char(MaterialIcon::Straighten))),
+                self.measure_tab
+                    .view(self.ui_size.clone(), &self.application_state),
+            )
+            .push(
+                TabLabel::Text(format!("{}", icon_to_char(MaterialIcon::Assessment))),
+                self.analysis_tab
+                    .view(self.ui_size.clone(), &self.application_state),
+            )
+            .push(
+                TabLabel::Text(format!("{}", icon_to_char(MaterialIcon::Movie))),
+                self.trajectory_tab
+                    .view(self.ui_size.clone(), &self.application_state),
+            )
+            .push(
+                TabLabel::Text(format!("{}", icon_to_char(MaterialIcon::Layers))),
+                self.workspace_tab
+                    .view(self.ui_size.clone(), &self.application_state),
+            )
             .push(
                 TabLabel::Text(format!("{}", icon_to_char(MaterialIcon::Settings))),
                 self.parameters_tab
@@ -1163,6 +2039,68 @@ impl Requestable for Hyperboloid_ {
     }
 }
 
+pub struct TorusKnot_ {}
+
+impl Requestable for TorusKnot_ {
+    type Request = TorusKnot;
+    fn request_from_values(&self, values: &[f32]) -> TorusKnot {
+        TorusKnot {
+            p: values[0].round() as u32,
+            q: values[1].round() as u32,
+            radius: values[2],
+            tube_radius: values[3],
+        }
+    }
+    fn nb_values(&self) -> usize {
+        4
+    }
+    fn initial_value(&self, n: usize) -> f32 {
+        match n {
+            0 => 2f32,
+            1 => 3f32,
+            2 => 20f32,
+            3 => 8f32,
+            _ => unreachable!(),
+        }
+    }
+    fn min_val(&self, n: usize) -> f32 {
+        match n {
+            0 => 1f32,
+            1 => 1f32,
+            2 => 5f32,
+            3 => 1f32,
+            _ => unreachable!(),
+        }
+    }
+    fn max_val(&self, n: usize) -> f32 {
+        match n {
+            0 => 10f32,
+            1 => 10f32,
+            2 => 100f32,
+            3 => 50f32,
+            _ => unreachable!(),
+        }
+    }
+    fn step_val(&self, n: usize) -> f32 {
+        match n {
+            0 => 1f32,
+            1 => 1f32,
+            2 => 1f32,
+            3 => 1f32,
+            _ => unreachable!(),
+        }
+    }
+    fn name_val(&self, n: usize) -> String {
+        match n {
+            0 => String::from("p"),
+            1 => String::from("q"),
+            2 => String::from("Radius (nm)"),
+            3 => String::from("Tube radius (nm)"),
+            _ => unreachable!(),
+        }
+    }
+}
+
 struct ScrollSentivity {}
 
 impl Requestable for ScrollSentivity {
@@ -1210,6 +2148,161 @@ impl Requestable for ScrollSentivity {
     }
 }
 
+struct MinStrandLength {}
+
+impl Requestable for MinStrandLength {
+    type Request = usize;
+    fn request_from_values(&self, values: &[f32]) -> usize {
+        values[0].round() as usize
+    }
+    fn nb_values(&self) -> usize {
+        1
+    }
+    fn initial_value(&self, n: usize) -> f32 {
+        if n == 0 {
+            StrandBuildingParameters::default().min_strand_length as f32
+        } else {
+            unreachable!()
+        }
+    }
+    fn min_val(&self, n: usize) -> f32 {
+        if n == 0 {
+            1f32
+        } else {
+            unreachable!()
+        }
+    }
+    fn max_val(&self, n: usize) -> f32 {
+        if n == 0 {
+            100f32
+        } else {
+            unreachable!()
+        }
+    }
+    fn step_val(&self, n: usize) -> f32 {
+        if n == 0 {
+            1f32
+        } else {
+            unreachable!()
+        }
+    }
+    fn name_val(&self, n: usize) -> String {
+        if n == 0 {
+            String::from("Minimum length (nt)")
+        } else {
+            unreachable!()
+        }
+    }
+}
+
+struct EditHistorySize {}
+
+impl Requestable for EditHistorySize {
+    type Request = usize;
+    fn request_from_values(&self, values: &[f32]) -> usize {
+        values[0].round() as usize
+    }
+    fn nb_values(&self) -> usize {
+        1
+    }
+    fn initial_value(&self, n: usize) -> f32 {
+        if n == 0 {
+            0f32
+        } else {
+            unreachable!()
+        }
+    }
+    fn min_val(&self, n: usize) -> f32 {
+        if n == 0 {
+            0f32
+        } else {
+            unreachable!()
+        }
+    }
+    fn max_val(&self, n: usize) -> f32 {
+        if n == 0 {
+            crate::edit_history::MAX_EDIT_HISTORY_SIZE as f32
+        } else {
+            unreachable!()
+        }
+    }
+    fn step_val(&self, n: usize) -> f32 {
+        if n == 0 {
+            1f32
+        } else {
+            unreachable!()
+        }
+    }
+    fn name_val(&self, n: usize) -> String {
+        if n == 0 {
+            String::from("Saved undo steps")
+        } else {
+            unreachable!()
+        }
+    }
+}
+
+struct WorkspaceTransformFactory {
+    transform: WorkspaceTransform,
+}
+
+impl Requestable for WorkspaceTransformFactory {
+    type Request = WorkspaceTransform;
+    fn request_from_values(&self, values: &[f32]) -> WorkspaceTransform {
+        WorkspaceTransform {
+            translation: Vec3::new(values[0], values[1], values[2]),
+            angles: Vec3::new(values[3], values[4], values[5]),
+            ..self.transform
+        }
+    }
+    fn nb_values(&self) -> usize {
+        6
+    }
+    fn initial_value(&self, n: usize) -> f32 {
+        match n {
+            0 => self.transform.translation.x,
+            1 => self.transform.translation.y,
+            2 => self.transform.translation.z,
+            3 => self.transform.angles.x,
+            4 => self.transform.angles.y,
+            5 => self.transform.angles.z,
+            _ => unreachable!(),
+        }
+    }
+    fn min_val(&self, n: usize) -> f32 {
+        match n {
+            0..=2 => -500f32,
+            3..=5 => -180f32,
+            _ => unreachable!(),
+        }
+    }
+    fn max_val(&self, n: usize) -> f32 {
+        match n {
+            0..=2 => 500f32,
+            3..=5 => 180f32,
+            _ => unreachable!(),
+        }
+    }
+    fn step_val(&self, n: usize) -> f32 {
+        match n {
+            0..=2 => 1f32,
+            3..=5 => 5f32,
+            _ => unreachable!(),
+        }
+    }
+    fn name_val(&self, n: usize) -> String {
+        match n {
+            0 => String::from("x (nm)"),
+            1 => String::from("y (nm)"),
+            2 => String::from("z (nm)"),
+            3 => String::from("Angle x (°)"),
+            4 => String::from("Angle y (°)"),
+            5 => String::from("Angle z (°)"),
+            _ => unreachable!(),
+        }
+    }
+}
+
 struct HelixRoll {}
 
 impl Requestable for HelixRoll {
@@ -1260,6 +2353,7 @@ pub struct RigidBodyParametersRequest {
     pub k_friction: f32,
     pub mass_factor: f32,
     pub volume_exclusion: bool,
+    pub freeze_ssdna: bool,
     pub brownian_motion: bool,
     pub brownian_rate: f32,
     pub brownian_amplitude: f32,
@@ -1267,6 +2361,7 @@ pub struct RigidBodyParametersRequest {
 
 struct RigidBodyFactory {
     pub volume_exclusion: bool,
+    pub freeze_ssdna: bool,
     pub brownian_motion: bool,
     pub brownian_parameters: BrownianParametersFactory,
 }
@@ -1339,6 +2434,7 @@ impl Requestable for RigidBodyFactory {
             k_friction: values[1],
             mass_factor: values[2],
             volume_exclusion: self.volume_exclusion,
+            freeze_ssdna: self.freeze_ssdna,
             brownian_motion: self.brownian_motion,
             brownian_rate: self.brownian_parameters.rate,
             brownian_amplitude: self.brownian_parameters.amplitude,
@@ -136,6 +136,11 @@ pub(super) struct ContextualPanel<S: AppState> {
     ens_nano_website: button::State,
     add_strand_menu: AddStrandMenu,
     strand_name_state: text_input::State,
+    annotation_state: text_input::State,
+    /// The label being typed by the user for a nucleotide. It is only applied to the design when
+    /// it is submitted.
+    annotation_draft: Option<(Nucl, String)>,
+    export_module_btn: button::State,
     builder: Option<InstantiatedBuilder<S>>,
 }
 
@@ -150,6 +155,9 @@ impl<S: AppState> ContextualPanel<S> {
             ens_nano_website: Default::default(),
             add_strand_menu: Default::default(),
             strand_name_state: Default::default(),
+            annotation_state: Default::default(),
+            annotation_draft: None,
+            export_module_btn: Default::default(),
             builder: None,
         }
     }
@@ -189,6 +197,8 @@ impl<S: AppState> ContextualPanel<S> {
             app_state.get_reader().as_ref(),
         );
         let info_values = values_of_selection(selection, app_state.get_reader().as_ref());
+        let selection_is_helices =
+            ensnano_interactor::list_of_helices(app_state.get_selection()).is_some();
         if self.show_tutorial {
             column = column.push(
                 Text::new("Tutorials")
@@ -221,6 +231,9 @@ impl<S: AppState> ContextualPanel<S> {
                     .push(iced::Space::with_width(Length::FillPortion(1))),
             );
             column = column.push(Text::new(format!("{} objects selected", nb_selected)));
+            if selection_is_helices {
+                column = add_export_module_btn(column, &mut self.export_module_btn, ui_size);
+            }
         } else {
             let help_btn =
                 text_btn(&mut self.help_btn, "Help", ui_size.clone()).on_press(Message::ForceHelp);
@@ -246,12 +259,36 @@ impl<S: AppState> ContextualPanel<S> {
                         ui_size.clone(),
                     )
                 }
-                Selection::Nucleotide(_, _) => {
+                Selection::Nucleotide(_, nucl) => {
                     let anchor = info_values[0].clone();
                     column = column.push(Text::new(format!("Anchor {}", anchor)));
+                    let label = self
+                        .annotation_draft
+                        .as_ref()
+                        .filter(|(n, _)| n == nucl)
+                        .map(|(_, label)| label.clone())
+                        .unwrap_or_else(|| info_values[1].clone());
+                    let nucl = *nucl;
+                    column = column.push(
+                        Row::new()
+                            .push(Text::new("Annotation").size(ui_size.main_text()))
+                            .push(
+                                TextInput::new(
+                                    &mut self.annotation_state,
+                                    "Label",
+                                    &label,
+                                    move |label| Message::NucleotideAnnotationChanged(nucl, label),
+                                )
+                                .size(ui_size.main_text())
+                                .on_submit(Message::NucleotideAnnotationSubmitted(nucl)),
+                            ),
+                    );
                 }
                 _ => (),
             }
+            if selection_is_helices {
+                column = add_export_module_btn(column, &mut self.export_module_btn, ui_size);
+            }
             if let Some(builder) = &mut self.builder {
                 column = column.push(builder.builder.view(ui_size))
             }
@@ -286,6 +323,26 @@ impl<S: AppState> ContextualPanel<S> {
         }
     }
 
+    /// Focus the text input in which the label attached to `nucl` is edited
+    pub fn edit_annotation(&mut self, nucl: Nucl, label: String) {
+        self.annotation_draft = Some((nucl, label));
+        self.annotation_state.focus();
+        self.annotation_state.select_all();
+    }
+
+    pub fn update_annotation(&mut self, nucl: Nucl, label: String) {
+        self.annotation_draft = Some((nucl, label));
+    }
+
+    /// Stop editing the label attached to `nucl` and return the label typed by the user, if any
+    pub fn take_annotation(&mut self, nucl: Nucl) -> Option<String> {
+        self.annotation_state.unfocus();
+        self.annotation_draft
+            .take()
+            .filter(|(n, _)| *n == nucl)
+            .map(|(_, label)| label)
+    }
+
     pub fn state_updated(&mut self) {
         self.force_help = false;
         self.show_tutorial = false;
@@ -302,6 +359,7 @@ impl<S: AppState> ContextualPanel<S> {
     pub fn has_keyboard_priority(&self) -> bool {
         self.add_strand_menu.has_keyboard_priority()
             || self.strand_name_state.is_focused()
+            || self.annotation_state.is_focused()
             || self.builder_has_keyboard_priority()
     }
 
@@ -400,6 +458,17 @@ fn add_strand_content<'a, S: AppState, I: std::ops::Deref<Target = str>>(
     column
 }
 
+fn add_export_module_btn<'a, S: AppState>(
+    column: Column<'a, Message<S>>,
+    button_state: &'a mut button::State,
+    ui_size: UiSize,
+) -> Column<'a, Message<S>> {
+    column.push(
+        text_btn(button_state, "Export selection as module", ui_size)
+            .on_press(Message::ExportSelectionAsModule),
+    )
+}
+
 fn bool_to_string(b: bool) -> String {
     if b {
         String::from("true")
@@ -555,6 +624,10 @@ fn view_2d_help() -> Vec<(String, String)> {
             format!("{} Drag", LCLICK),
             "Rectangular selection".to_owned(),
         ),
+        (
+            format!("{} + {}", CTRL, RCLICK),
+            "Actions on the selection".to_owned(),
+        ),
         (String::new(), String::new()),
         ("On helix numbers".to_owned(), String::new()),
         (format!("{}", LCLICK), "Select helix".to_owned()),
@@ -636,7 +709,10 @@ fn values_of_selection(selection: &Selection, reader: &dyn DesignReader) -> Vec<
             reader.strand_name(*s_id as usize),
         ],
         Selection::Nucleotide(_, nucl) => {
-            vec![format!("{}", reader.nucl_is_anchor(*nucl))]
+            vec![
+                format!("{}", reader.nucl_is_anchor(*nucl)),
+                reader.nucleotide_annotation(*nucl).unwrap_or_default(),
+            ]
         }
         _ => Vec::new(),
     }
@@ -51,7 +51,11 @@ pub struct RequestFactory<R: Requestable> {
 pub enum FactoryId {
     HelixRoll,
     Hyperboloid,
+    TorusKnot,
     Scroll,
+    MinStrandLength,
+    EditHistorySize,
+    WorkspaceTransform,
     RigidBody,
     Brownian,
 }
@@ -15,14 +15,25 @@ ENSnano, a 3d graphical application for DNA nanostructures.
     You should have received a copy of the GNU General Public License
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
-use super::{AppState, Message};
-use iced::{button, text_input, Button, Row, Text, TextInput};
+use super::{icon, AppState, MaterialIcon, Message, UiSize};
+use ensnano_design::HairpinStem;
+use iced::{button, text_input, Button, Checkbox, Column, Row, Text, TextInput};
+
+/// The minimum number of base pairs of a predicted stem for which a warning is shown
+const MIN_WARNED_STEM_LENGTH: usize = 5;
+/// Hairpins are not predicted for longer sequences because the prediction is too slow to be
+/// done at each keystroke
+const MAX_HAIRPIN_CHECK_LENGTH: usize = 300;
 
 pub struct SequenceInput {
     input: text_input::State,
-    #[allow(dead_code)]
     button_state: button::State,
     sequence: String,
+    check_hairpin: bool,
+    /// The longest stem predicted in the secondary structure of the sequence
+    hairpin: Option<HairpinStem>,
+    /// The length to which the last input was truncated because it was longer than the strand
+    truncated_length: Option<usize>,
 }
 
 impl SequenceInput {
@@ -31,11 +42,13 @@ impl SequenceInput {
             input: Default::default(),
             sequence: String::new(),
             button_state: Default::default(),
+            check_hairpin: false,
+            hairpin: None,
+            truncated_length: None,
         }
     }
 
-    #[allow(dead_code)]
-    pub fn view<S: AppState>(&mut self) -> Row<Message<S>> {
+    pub fn view<S: AppState>(&mut self, ui_size: UiSize) -> Column<Message<S>> {
         let sequence_input = Row::new()
             .spacing(5)
             .push(TextInput::new(
@@ -48,14 +61,129 @@ impl SequenceInput {
                 Button::new(&mut self.button_state, Text::new("Load File"))
                     .on_press(Message::SequenceFileRequested),
             );
-        sequence_input
+        let mut ret = Column::new().spacing(5).push(sequence_input).push(
+            Checkbox::new(self.check_hairpin, "Check hairpins", Message::CheckHairpin)
+                .size(ui_size.checkbox())
+                .text_size(ui_size.main_text()),
+        );
+        if let Some(length) = self.truncated_length {
+            ret = ret.push(
+                Row::new()
+                    .spacing(5)
+                    .push(icon(MaterialIcon::Warning, &ui_size))
+                    .push(
+                        Text::new(format!("Sequence truncated to {} nt", length))
+                            .size(ui_size.main_text()),
+                    ),
+            );
+        }
+        if let Some(stem) = self.hairpin.filter(|_| self.check_hairpin) {
+            let (prime5, prime3) = stem.arms(&self.sequence);
+            ret = ret.push(
+                Row::new()
+                    .spacing(5)
+                    .push(icon(MaterialIcon::Warning, &ui_size))
+                    .push(
+                        Text::new(format!(
+                            "Predicted stem of {} bp: {} / {}",
+                            stem.length, prime5, prime3
+                        ))
+                        .size(ui_size.main_text()),
+                    ),
+            );
+        }
+        ret
     }
 
-    pub fn update_sequence(&mut self, sequence: String) {
+    pub fn update_sequence(&mut self, sequence: String, truncated_length: Option<usize>) {
         self.sequence = sequence;
+        self.truncated_length = truncated_length;
+        self.update_hairpin();
+    }
+
+    pub fn set_check_hairpin(&mut self, check_hairpin: bool) {
+        self.check_hairpin = check_hairpin;
+        self.update_hairpin();
+    }
+
+    fn update_hairpin(&mut self) {
+        self.hairpin = if self.check_hairpin && self.sequence.len() <= MAX_HAIRPIN_CHECK_LENGTH {
+            ensnano_design::predict_longest_stem(&self.sequence)
+                .filter(|stem| stem.length >= MIN_WARNED_STEM_LENGTH)
+        } else {
+            None
+        };
     }
 
     pub fn has_keyboard_priority(&self) -> bool {
         self.input.is_focused()
     }
 }
+
+/// If `input` is in FASTA format, return the sequence of its first record without the header
+/// lines. Otherwise return `input`.
+///
+/// The returned sequence may still contain line breaks and must be stripped of its whitespaces.
+pub fn parse_fasta_or_raw(input: &str) -> &str {
+    let input = input.trim_start();
+    if !input.starts_with('>') {
+        return input.trim_end();
+    }
+    let mut sequence = input;
+    // Skip the header line(s)
+    while sequence.starts_with('>') || sequence.starts_with(';') {
+        sequence = sequence
+            .find('\n')
+            .map(|i| sequence[i + 1..].trim_start())
+            .unwrap_or("");
+    }
+    // Only keep the first record
+    if let Some(next_record) = sequence.find('>') {
+        sequence = &sequence[..next_record];
+    }
+    sequence.trim_end()
+}
+
+/// Remove the FASTA header and the whitespaces of a sequence that was pasted or loaded from a
+/// file.
+pub fn clean_sequence(input: &str) -> String {
+    parse_fasta_or_raw(input)
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect()
+}
+
+/// Keep only the first `length` characters of `sequence`. Return true if characters were
+/// removed.
+pub fn truncate_sequence(sequence: &mut String, length: usize) -> bool {
+    if let Some((end, _)) = sequence.char_indices().nth(length) {
+        sequence.truncate(end);
+        true
+    } else {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raw_sequence_is_kept() {
+        assert_eq!(parse_fasta_or_raw(" ATTGC\n"), "ATTGC");
+    }
+
+    #[test]
+    fn fasta_header_is_removed() {
+        let fasta = ">seq1 some description\nATTG\nCCGA\n>seq2\nTTTT\n";
+        assert_eq!(clean_sequence(fasta), "ATTGCCGA");
+    }
+
+    #[test]
+    fn truncation_counts_characters() {
+        let mut sequence = String::from("AéTGC");
+        assert!(truncate_sequence(&mut sequence, 3));
+        assert_eq!(sequence, "AéT");
+        assert!(!truncate_sequence(&mut sequence, 3));
+    }
+}
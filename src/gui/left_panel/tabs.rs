@@ -17,8 +17,10 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 */
 use super::color_picker::{ColorSquare, ColorState};
 use super::*;
-use ensnano_design::CameraId;
-use ensnano_interactor::{RollRequest, SimulationState};
+use ensnano_design::{CameraId, StrandModification};
+use ensnano_interactor::{
+    ColorBlindPalette, MirrorAxis, OrderFormat, RollRequest, SimulationState, SortKey, StrandEnd,
+};
 use iced::scrollable;
 use std::collections::VecDeque;
 
@@ -75,13 +77,21 @@ pub use grids_tab::GridTab;
 mod camera_shortcut;
 pub use camera_shortcut::CameraShortcut;
 mod camera_tab;
-pub use camera_tab::{CameraTab, FogChoice};
+pub use camera_tab::{CameraTab, FogChoice, LightId, ThemeColor};
 mod simulation_tab;
 pub use simulation_tab::SimulationTab;
 mod parameters_tab;
 pub use parameters_tab::ParametersTab;
 mod sequence_tab;
-pub use sequence_tab::SequenceTab;
+pub use sequence_tab::{RulerField, SequenceTab};
+mod measure_tab;
+pub use measure_tab::MeasureTab;
+mod analysis_tab;
+pub use analysis_tab::AnalysisTab;
+mod trajectory_tab;
+pub use trajectory_tab::TrajectoryTab;
+mod workspace_tab;
+pub use workspace_tab::WorkspaceTab;
 
 struct GoStop<S: AppState> {
     go_stop_button: button::State,
@@ -0,0 +1,184 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::*;
+use ensnano_interactor::graphics::{AnalysisColoring, ALL_ANALYSIS_COLORING};
+use ensnano_interactor::StapleStatistics;
+
+pub struct AnalysisTab {
+    scroll: scrollable::State,
+    /// The analysis whose result is shown in the 3D view
+    pub analysis_coloring: AnalysisColoring,
+    analysis_coloring_picklist: pick_list::State<AnalysisColoring>,
+    /// The statistics of the staples of the design, updated when the design is modified
+    staples: Vec<StapleStatistics>,
+    /// The page of the staple list that is shown
+    staple_page: usize,
+    /// The buttons selecting the staples of the shown page, in the order of the list
+    staple_buttons: Vec<button::State>,
+    previous_page_button: button::State,
+    next_page_button: button::State,
+}
+
+/// The number of staples shown on each page of the staple list
+const STAPLES_PER_PAGE: usize = 50;
+
+impl AnalysisTab {
+    pub fn new() -> Self {
+        Self {
+            scroll: Default::default(),
+            analysis_coloring: Default::default(),
+            analysis_coloring_picklist: Default::default(),
+            staples: Vec::new(),
+            staple_page: 0,
+            staple_buttons: Vec::new(),
+            previous_page_button: Default::default(),
+            next_page_button: Default::default(),
+        }
+    }
+
+    pub fn update_staples(&mut self, staples: Vec<StapleStatistics>) {
+        self.staples = staples;
+        self.staple_page = self.staple_page.min(self.last_page());
+    }
+
+    pub fn set_staple_page(&mut self, page: usize) {
+        self.staple_page = page.min(self.last_page());
+    }
+
+    fn last_page(&self) -> usize {
+        self.staples.len().saturating_sub(1) / STAPLES_PER_PAGE
+    }
+
+    pub fn view<'a, S: AppState>(
+        &'a mut self,
+        ui_size: UiSize,
+        app_state: &'a S,
+    ) -> Element<'a, Message<S>> {
+        let mut ret = Column::new().spacing(5);
+        section!(ret, ui_size, "Analysis");
+        subsection!(ret, ui_size, "Coloring");
+        ret = ret.push(PickList::new(
+            &mut self.analysis_coloring_picklist,
+            &ALL_ANALYSIS_COLORING[..],
+            Some(self.analysis_coloring),
+            Message::AnalysisColoringPicked,
+        ));
+        ret = ret.push(Text::new(description(self.analysis_coloring)).size(ui_size.main_text()));
+        extra_jump!(ret);
+
+        subsection!(ret, ui_size, "Staples");
+        let min_length = app_state.get_strand_building_parameters().min_strand_length;
+        if self.staples.is_empty() {
+            ret = ret.push(Text::new("No staple").color(innactive_color()));
+        } else {
+            let nb_flagged = self
+                .staples
+                .iter()
+                .filter(|s| !s.flags(min_length).is_empty())
+                .count();
+            ret = ret.push(
+                Text::new(format!(
+                    "{} staples, {} with potential problems",
+                    self.staples.len(),
+                    nb_flagged
+                ))
+                .size(ui_size.main_text()),
+            );
+        }
+        let last_page = self.last_page();
+        if last_page > 0 {
+            let mut previous_button =
+                text_btn(&mut self.previous_page_button, "Previous", ui_size.clone());
+            if self.staple_page > 0 {
+                previous_button =
+                    previous_button.on_press(Message::StaplePageChanged(self.staple_page - 1));
+            }
+            let mut next_button = text_btn(&mut self.next_page_button, "Next", ui_size.clone());
+            if self.staple_page < last_page {
+                next_button =
+                    next_button.on_press(Message::StaplePageChanged(self.staple_page + 1));
+            }
+            ret = ret.push(
+                Row::new()
+                    .spacing(5)
+                    .align_items(iced::Alignment::Center)
+                    .push(previous_button)
+                    .push(
+                        Text::new(format!("Page {}/{}", self.staple_page + 1, last_page + 1))
+                            .size(ui_size.main_text()),
+                    )
+                    .push(next_button),
+            );
+        }
+        let page_start = self.staple_page * STAPLES_PER_PAGE;
+        let page = self.staples.iter().skip(page_start).take(STAPLES_PER_PAGE);
+        self.staple_buttons
+            .resize_with(STAPLES_PER_PAGE, Default::default);
+        for (staple, button) in page.zip(self.staple_buttons.iter_mut()) {
+            let mut content =
+                Column::new().push(Text::new(staple_description(staple)).size(ui_size.main_text()));
+            let flags = staple.flags(min_length);
+            if !flags.is_empty() {
+                content = content.push(
+                    Text::new(flags.join(", "))
+                        .size(ui_size.main_text())
+                        .color(FLAG_COLOR),
+                );
+            }
+            ret = ret.push(
+                Button::new(button, content)
+                    .width(Length::Fill)
+                    .on_press(Message::SelectStrand(staple.strand_id)),
+            );
+        }
+        Scrollable::new(&mut self.scroll).push(ret).into()
+    }
+}
+
+const FLAG_COLOR: Color = Color::from_rgb(0.8, 0.2, 0.2);
+
+fn staple_description(staple: &StapleStatistics) -> String {
+    let melting_temperature = staple
+        .melting_temperature
+        .map(|tm| format!("{:.0}°C", tm))
+        .unwrap_or_else(|| String::from("unknown"));
+    format!(
+        "Strand {}: {} nt, {} domains, Tm {}",
+        staple.strand_id, staple.length, staple.nb_domains, melting_temperature
+    )
+}
+
+fn description(coloring: AnalysisColoring) -> &'static str {
+    match coloring {
+        AnalysisColoring::None => "The nucleotides of the 3D view have the color of their strand",
+        AnalysisColoring::CrossoverDensity => {
+            "Nucleotides with few cross-overs near them on their helix are red, and those with \
+             many cross-overs near them are blue"
+        }
+        AnalysisColoring::UncrossedLength => {
+            "Nucleotides on long portions of helix without cross-over are red, and those on short \
+             portions are blue"
+        }
+        AnalysisColoring::MeltingTemperature => {
+            "The domains of the staples are colored according to their melting temperature, \
+             estimated with the nearest-neighbor model. Domains melting below 20°C are red, and \
+             those melting above 60°C are blue. Domains without sequence are not colored."
+        }
+    }
+}
@@ -80,9 +80,11 @@ macro_rules! add_rotate_buttons {
 
 macro_rules! add_custom_camera_row {
     ($ret: ident, $self: ident, $ui_size: ident) => {
-        let new_camera_button =
-            light_icon_btn(&mut $self.new_camera_button, LightIcon::AddAPhoto, $ui_size)
-                .on_press(Message::NewCustomCamera);
+        let mut new_camera_button =
+            light_icon_btn(&mut $self.new_camera_button, LightIcon::AddAPhoto, $ui_size);
+        if $self.camera_widgets.len() < ensnano_design::MAX_NB_CAMERAS {
+            new_camera_button = new_camera_button.on_press(Message::NewCustomCamera);
+        }
         let custom_cameras_row = Row::new()
             .push(Text::new("Custom cameras").size($ui_size.head_text()))
             .push(iced::Space::with_width(Length::Fill))
@@ -191,7 +193,8 @@ impl CameraShortcut {
             .get_reader()
             .get_all_cameras()
             .iter()
-            .map(|cam| {
+            .enumerate()
+            .map(|(n, cam)| {
                 let favourite = favourite_camera == Some(cam.0);
                 let being_edited = self.camera_being_edited == Some(cam.0);
                 let name = if being_edited {
@@ -202,7 +205,13 @@ impl CameraShortcut {
                 } else {
                     cam.1
                 };
-                CameraWidget::new(name.to_string(), favourite, being_edited, cam.0)
+                CameraWidget::new(
+                    name.to_string(),
+                    favourite,
+                    being_edited,
+                    cam.0,
+                    number_key(n),
+                )
             })
             .collect();
     }
@@ -240,6 +249,17 @@ struct CameraWidget {
     favourite: bool,
     being_edited: bool,
     camera_id: CameraId,
+    /// The number key that brings the camera to this point of view
+    key: Option<char>,
+}
+
+/// The number key that selects the `n`-th camera of the design
+fn number_key(n: usize) -> Option<char> {
+    match n {
+        0..=8 => std::char::from_digit(n as u32 + 1, 10),
+        9 => Some('0'),
+        _ => None,
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -253,12 +273,19 @@ struct CameraWidgetState {
 }
 
 impl CameraWidget {
-    fn new(name: String, favourite: bool, being_edited: bool, camera_id: CameraId) -> Self {
+    fn new(
+        name: String,
+        favourite: bool,
+        being_edited: bool,
+        camera_id: CameraId,
+        key: Option<char>,
+    ) -> Self {
         Self {
             name,
             favourite,
             being_edited,
             camera_id,
+            key,
         }
     }
 
@@ -307,7 +334,10 @@ impl CameraWidget {
         let delete_button = light_icon_btn(&mut state.delete_btn, LightIcon::Delete, ui_size)
             .on_press(Message::DeleteCamera(self.camera_id));
 
+        let key = self.key.map(|c| format!("{} ", c)).unwrap_or_default();
+
         Row::new()
+            .push(Text::new(key))
             .push(name)
             .push(iced::Space::with_width(iced::Length::Units(3)))
             .push(edit_button)
@@ -18,19 +18,117 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 
 use super::*;
 use ensnano_interactor::graphics::{
-    Background3D, RenderingMode, ALL_BACKGROUND3D, ALL_RENDERING_MODE,
+    AmbientOcclusionParameters, AnimationKind, AnimationParameters, Background3D, ColorBy,
+    ColorTheme, DepthOfFieldParameters, EyeDomeLightingParameters, FlyThroughParameters,
+    LightConfig, LightingParameters, RenderingMode, ToneMappingOperator, ToneMappingParameters,
+    ALL_ANIMATION_KIND, ALL_BACKGROUND3D, ALL_COLOR_BY, ALL_RENDERING_MODE,
+    ALL_TONE_MAPPING_OPERATOR, MAX_NB_LIGHTS,
 };
 
+/// The default width and height in pixels of a screenshot of the 3D view
+const DEFAULT_SCREENSHOT_SIZE: (u32, u32) = (3840, 2160);
+
+/// The default number of frames of a recorded animation
+const DEFAULT_NB_ANIMATION_FRAMES: u32 = 120;
+
+/// Read a width or a height of a screenshot, in pixels
+fn parse_screenshot_dimension(value: &str) -> Option<u32> {
+    value
+        .parse::<u32>()
+        .ok()
+        .filter(|d| (1..=MAX_EXPORT_DIMENSION).contains(d))
+}
+
+/// Read a color written as `RRGGBB` or `#RRGGBB`
+fn parse_hex_color(value: &str) -> Option<u32> {
+    let hex = value.trim().trim_start_matches('#');
+    if hex.len() == 6 {
+        u32::from_str_radix(hex, 16).ok()
+    } else {
+        None
+    }
+}
+
+/// A color of the color theme that can be typed in the camera tab
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThemeColor {
+    Selection,
+    Candidate,
+    Background,
+}
+
+impl ThemeColor {
+    const ALL: [Self; 3] = [Self::Selection, Self::Candidate, Self::Background];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Selection => "Selection",
+            Self::Candidate => "Candidate",
+            Self::Background => "Background",
+        }
+    }
+
+    fn get(&self, theme: &ColorTheme) -> u32 {
+        match self {
+            Self::Selection => theme.selection,
+            Self::Candidate => theme.candidate,
+            Self::Background => theme.background,
+        }
+    }
+
+    fn get_mut<'a>(&self, theme: &'a mut ColorTheme) -> &'a mut u32 {
+        match self {
+            Self::Selection => &mut theme.selection,
+            Self::Candidate => &mut theme.candidate,
+            Self::Background => &mut theme.background,
+        }
+    }
+}
+
 pub struct CameraTab {
     fog: FogParameters,
     scroll: scrollable::State,
     selection_visibility_btn: button::State,
     compl_visibility_btn: button::State,
     all_visible_btn: button::State,
+    pub show_base_letters: bool,
+    pub show_helix_numbers: bool,
+    pub fly_through: FlyThroughParameters,
+    fly_speed_slider: slider::State,
     pub background3d: Background3D,
     background3d_picklist: pick_list::State<Background3D>,
+    pub color_theme: ColorTheme,
+    color_by_picklist: pick_list::State<ColorBy>,
+    theme_color_inputs: [text_input::State; 3],
+    theme_color_strs: [String; 3],
     pub rendering_mode: RenderingMode,
     rendering_mode_picklist: pick_list::State<RenderingMode>,
+    pub depth_of_field: DepthOfFieldParameters,
+    focal_distance_slider: slider::State,
+    aperture_slider: slider::State,
+    pub eye_dome_lighting: EyeDomeLightingParameters,
+    edl_strength_slider: slider::State,
+    edl_radius_slider: slider::State,
+    pub ambient_occlusion: AmbientOcclusionParameters,
+    ao_strength_slider: slider::State,
+    ao_radius_slider: slider::State,
+    pub lighting: LightingParameters,
+    lighting_section: LightingSection,
+    pub tone_mapping: ToneMappingParameters,
+    tone_mapping_picklist: pick_list::State<ToneMappingOperator>,
+    exposure_slider: slider::State,
+    export_view_btn: button::State,
+    import_view_btn: button::State,
+    screenshot_width_input: text_input::State,
+    screenshot_width_str: String,
+    screenshot_height_input: text_input::State,
+    screenshot_height_str: String,
+    screenshot_btn: button::State,
+    pub animation_kind: AnimationKind,
+    animation_kind_picklist: pick_list::State<AnimationKind>,
+    nb_frames_input: text_input::State,
+    nb_frames_str: String,
+    record_btn: button::State,
 }
 
 impl CameraTab {
@@ -41,10 +139,44 @@ impl CameraTab {
             selection_visibility_btn: Default::default(),
             compl_visibility_btn: Default::default(),
             all_visible_btn: Default::default(),
+            show_base_letters: false,
+            show_helix_numbers: false,
+            fly_through: Default::default(),
+            fly_speed_slider: Default::default(),
             background3d: Default::default(),
             background3d_picklist: Default::default(),
+            color_theme: Default::default(),
+            color_by_picklist: Default::default(),
+            theme_color_inputs: Default::default(),
+            theme_color_strs: Default::default(),
             rendering_mode: Default::default(),
             rendering_mode_picklist: Default::default(),
+            depth_of_field: Default::default(),
+            focal_distance_slider: Default::default(),
+            aperture_slider: Default::default(),
+            eye_dome_lighting: Default::default(),
+            edl_strength_slider: Default::default(),
+            edl_radius_slider: Default::default(),
+            ambient_occlusion: Default::default(),
+            ao_strength_slider: Default::default(),
+            ao_radius_slider: Default::default(),
+            lighting: Default::default(),
+            lighting_section: Default::default(),
+            tone_mapping: Default::default(),
+            tone_mapping_picklist: Default::default(),
+            exposure_slider: Default::default(),
+            export_view_btn: Default::default(),
+            import_view_btn: Default::default(),
+            screenshot_width_input: Default::default(),
+            screenshot_width_str: DEFAULT_SCREENSHOT_SIZE.0.to_string(),
+            screenshot_height_input: Default::default(),
+            screenshot_height_str: DEFAULT_SCREENSHOT_SIZE.1.to_string(),
+            screenshot_btn: Default::default(),
+            animation_kind: Default::default(),
+            animation_kind_picklist: Default::default(),
+            nb_frames_input: Default::default(),
+            nb_frames_str: DEFAULT_NB_ANIMATION_FRAMES.to_string(),
+            record_btn: Default::default(),
         }
     }
 
@@ -76,8 +208,43 @@ impl CameraTab {
             )
             .on_press(Message::AllVisible),
         );
+        ret = ret.push(right_checkbox(
+            self.show_base_letters,
+            "Show base letters",
+            Message::ToggleText,
+            ui_size.clone(),
+        ));
+        ret = ret.push(right_checkbox(
+            self.show_helix_numbers,
+            "Show helix numbers",
+            Message::ShowHelixNumbers,
+            ui_size.clone(),
+        ));
         ret = ret.push(self.fog.view(&ui_size));
 
+        subsection!(ret, ui_size, "First-person mode");
+        ret = ret.push(right_checkbox(
+            self.fly_through.enabled,
+            "Fly through",
+            Message::FlyThrough,
+            ui_size.clone(),
+        ));
+        ret = ret.push(
+            Row::new()
+                .spacing(5)
+                .push(Text::new("Speed"))
+                .push(Slider::new(
+                    &mut self.fly_speed_slider,
+                    1f32..=100f32,
+                    self.fly_through.speed,
+                    Message::FlySpeed,
+                )),
+        );
+        ret = ret.push(
+            Text::new("W/S: move forward/backward, A/D: move sideways, drag: look around")
+                .size(ui_size.main_text()),
+        );
+
         subsection!(ret, ui_size, "Rendering");
         ret = ret.push(Text::new("Style"));
         ret = ret.push(PickList::new(
@@ -94,9 +261,269 @@ impl CameraTab {
             Message::Background3D,
         ));
 
+        subsection!(ret, ui_size, "Colors");
+        ret = ret.push(
+            Row::new()
+                .spacing(5)
+                .push(Text::new("Color by"))
+                .push(PickList::new(
+                    &mut self.color_by_picklist,
+                    &ALL_COLOR_BY[..],
+                    Some(self.color_theme.color_by),
+                    Message::ColorByPicked,
+                )),
+        );
+        ret = ret.push(right_checkbox(
+            self.color_theme.scaffold_route,
+            "Show scaffold route",
+            Message::ShowScaffoldRoute,
+            ui_size.clone(),
+        ));
+        for ((theme_color, input), value_str) in ThemeColor::ALL
+            .iter()
+            .zip(self.theme_color_inputs.iter_mut())
+            .zip(self.theme_color_strs.iter())
+        {
+            let theme_color = *theme_color;
+            ret = ret.push(
+                Row::new()
+                    .spacing(5)
+                    .push(Text::new(theme_color.name()))
+                    .push(
+                        TextInput::new(input, "#RRGGBB", value_str, move |s| {
+                            Message::ThemeColorInput(theme_color, s)
+                        })
+                        .style(BadValue(parse_hex_color(value_str).is_some())),
+                    ),
+            );
+        }
+
+        subsection!(ret, ui_size, "Depth of field");
+        ret = ret.push(
+            Row::new()
+                .spacing(5)
+                .push(Text::new("Focal distance"))
+                .push(Slider::new(
+                    &mut self.focal_distance_slider,
+                    1f32..=200f32,
+                    self.depth_of_field.focal_distance,
+                    Message::DofFocalDistance,
+                )),
+        );
+        ret = ret.push(
+            Row::new()
+                .spacing(5)
+                .push(Text::new("Aperture"))
+                .push(Slider::new(
+                    &mut self.aperture_slider,
+                    0f32..=20f32,
+                    self.depth_of_field.aperture,
+                    Message::DofAperture,
+                )),
+        );
+
+        subsection!(ret, ui_size, "Eye-dome lighting");
+        ret = ret.push(
+            Row::new().spacing(5).push(Text::new("Strength")).push(
+                Slider::new(
+                    &mut self.edl_strength_slider,
+                    0f32..=5f32,
+                    self.eye_dome_lighting.strength,
+                    Message::EdlStrength,
+                )
+                .step(0.05),
+            ),
+        );
+        ret = ret.push(
+            Row::new().spacing(5).push(Text::new("Radius")).push(
+                Slider::new(
+                    &mut self.edl_radius_slider,
+                    0.5f32..=5f32,
+                    self.eye_dome_lighting.radius,
+                    Message::EdlRadius,
+                )
+                .step(0.1),
+            ),
+        );
+
+        subsection!(ret, ui_size, "Ambient occlusion");
+        ret = ret.push(
+            Row::new().spacing(5).push(Text::new("Strength")).push(
+                Slider::new(
+                    &mut self.ao_strength_slider,
+                    0f32..=2f32,
+                    self.ambient_occlusion.strength,
+                    Message::AoStrength,
+                )
+                .step(0.05),
+            ),
+        );
+        ret = ret.push(
+            Row::new().spacing(5).push(Text::new("Radius")).push(
+                Slider::new(
+                    &mut self.ao_radius_slider,
+                    0.5f32..=10f32,
+                    self.ambient_occlusion.radius,
+                    Message::AoRadius,
+                )
+                .step(0.1),
+            ),
+        );
+
+        subsection!(ret, ui_size, "Tone mapping");
+        ret = ret.push(PickList::new(
+            &mut self.tone_mapping_picklist,
+            &ALL_TONE_MAPPING_OPERATOR[..],
+            Some(self.tone_mapping.operator),
+            Message::ToneMappingOperator,
+        ));
+        ret = ret.push(
+            Row::new().spacing(5).push(Text::new("Exposure")).push(
+                Slider::new(
+                    &mut self.exposure_slider,
+                    0.1f32..=4f32,
+                    self.tone_mapping.exposure,
+                    Message::ToneMappingExposure,
+                )
+                .step(0.05),
+            ),
+        );
+
+        subsection!(ret, ui_size, "Lighting");
+        ret = ret.push(self.lighting_section.view(&self.lighting));
+
+        subsection!(ret, ui_size, "Share view");
+        ret = ret.push(
+            Row::new()
+                .spacing(5)
+                .push(
+                    text_btn(&mut self.export_view_btn, "Export View", ui_size.clone())
+                        .on_press(Message::ExportCameraView),
+                )
+                .push(
+                    text_btn(&mut self.import_view_btn, "Import View", ui_size.clone())
+                        .on_press(Message::ImportCameraView),
+                ),
+        );
+
+        subsection!(ret, ui_size, "Screenshot");
+        let width = parse_screenshot_dimension(&self.screenshot_width_str);
+        let height = parse_screenshot_dimension(&self.screenshot_height_str);
+        let mut screenshot_btn = text_btn(&mut self.screenshot_btn, "Screenshot", ui_size.clone());
+        if let (Some(width), Some(height)) = (width, height) {
+            screenshot_btn = screenshot_btn.on_press(Message::Screenshot {
+                size: (width, height),
+            });
+        }
+        ret = ret.push(
+            Row::new()
+                .spacing(3)
+                .push(Text::new("Width").size(ui_size.main_text()))
+                .push(
+                    TextInput::new(
+                        &mut self.screenshot_width_input,
+                        "",
+                        &self.screenshot_width_str,
+                        Message::ScreenshotWidthInput,
+                    )
+                    .style(BadValue(width.is_some())),
+                )
+                .push(Text::new("Height").size(ui_size.main_text()))
+                .push(
+                    TextInput::new(
+                        &mut self.screenshot_height_input,
+                        "",
+                        &self.screenshot_height_str,
+                        Message::ScreenshotHeightInput,
+                    )
+                    .style(BadValue(height.is_some())),
+                ),
+        );
+        ret = ret.push(screenshot_btn);
+
+        subsection!(ret, ui_size, "Record");
+        ret = ret.push(PickList::new(
+            &mut self.animation_kind_picklist,
+            &ALL_ANIMATION_KIND[..],
+            Some(self.animation_kind),
+            Message::AnimationKindPicked,
+        ));
+        let nb_frames = self.nb_frames_str.parse::<u32>().ok().filter(|n| *n > 0);
+        ret = ret.push(
+            Row::new()
+                .spacing(3)
+                .push(Text::new("Frames").size(ui_size.main_text()))
+                .push(
+                    TextInput::new(
+                        &mut self.nb_frames_input,
+                        "",
+                        &self.nb_frames_str,
+                        Message::AnimationFramesInput,
+                    )
+                    .style(BadValue(nb_frames.is_some())),
+                ),
+        );
+        let mut record_btn = text_btn(&mut self.record_btn, "Record", ui_size.clone());
+        if let (Some(nb_frames), Some(width), Some(height)) = (nb_frames, width, height) {
+            record_btn = record_btn.on_press(Message::RecordAnimation(AnimationParameters {
+                kind: self.animation_kind,
+                nb_frames,
+                size: (width, height),
+            }));
+        }
+        ret = ret.push(record_btn);
+        ret = ret.push(
+            Text::new("The frames have the size of the screenshots")
+                .size(ui_size.main_text())
+                .color(innactive_color()),
+        );
+
         Scrollable::new(&mut self.scroll).push(ret).into()
     }
 
+    pub fn update_screenshot_width_input(&mut self, value: String) {
+        self.screenshot_width_str = value;
+    }
+
+    pub fn update_screenshot_height_input(&mut self, value: String) {
+        self.screenshot_height_str = value;
+    }
+
+    pub fn update_nb_frames_input(&mut self, value: String) {
+        self.nb_frames_str = value;
+    }
+
+    pub fn has_keyboard_priority(&self) -> bool {
+        self.screenshot_width_input.is_focused()
+            || self.screenshot_height_input.is_focused()
+            || self.nb_frames_input.is_focused()
+            || self.theme_color_inputs.iter().any(|i| i.is_focused())
+    }
+
+    /// Replace the color theme by the one saved in the preferences of the user
+    pub fn set_color_theme(&mut self, theme: ColorTheme) {
+        for (theme_color, value_str) in ThemeColor::ALL.iter().zip(self.theme_color_strs.iter_mut())
+        {
+            *value_str = format!("#{:06X}", theme_color.get(&theme));
+        }
+        self.color_theme = theme;
+    }
+
+    /// Update the input of a color of the theme. Return true if the input is a valid color, in
+    /// which case the theme is modified.
+    pub fn update_theme_color_input(&mut self, theme_color: ThemeColor, value: String) -> bool {
+        let color = parse_hex_color(&value);
+        if let Some(idx) = ThemeColor::ALL.iter().position(|c| *c == theme_color) {
+            self.theme_color_strs[idx] = value;
+        }
+        if let Some(color) = color {
+            *theme_color.get_mut(&mut self.color_theme) = color;
+            true
+        } else {
+            false
+        }
+    }
+
     pub fn fog_visible(&mut self, visible: bool) {
         self.fog.visible = visible
     }
@@ -120,6 +547,160 @@ impl CameraTab {
     pub fn get_fog_request(&self) -> Fog {
         self.fog.request()
     }
+
+    pub fn select_light(&mut self, light: LightId) {
+        self.lighting_section.selected = light
+    }
+
+    pub fn set_light_azimuth(&mut self, azimuth: f32) {
+        self.selected_light_mut().azimuth = azimuth.to_radians()
+    }
+
+    pub fn set_light_elevation(&mut self, elevation: f32) {
+        self.selected_light_mut().elevation = elevation.to_radians()
+    }
+
+    pub fn set_light_intensity(&mut self, intensity: f32) {
+        self.selected_light_mut().intensity = intensity
+    }
+
+    pub fn set_light_hue(&mut self, hue: f32) {
+        let light = self.lighting_section.selected.0;
+        self.lighting_section.colors[light].hue = hue;
+        self.update_light_color();
+    }
+
+    pub fn set_light_saturation(&mut self, saturation: f32) {
+        let light = self.lighting_section.selected.0;
+        self.lighting_section.colors[light].saturation = saturation;
+        self.update_light_color();
+    }
+
+    fn update_light_color(&mut self) {
+        let light = self.lighting_section.selected.0;
+        let color = self.lighting_section.colors[light].rgb();
+        self.lighting.lights[light].color = color;
+    }
+
+    fn selected_light_mut(&mut self) -> &mut LightConfig {
+        &mut self.lighting.lights[self.lighting_section.selected.0]
+    }
+}
+
+/// The widgets used to edit the light sources of the 3D scene. Only the light selected in the
+/// pick list can be edited.
+#[derive(Default)]
+struct LightingSection {
+    selected: LightId,
+    picklist: pick_list::State<LightId>,
+    /// The hue and saturation of each light, kept here because they cannot be recovered from a
+    /// white light's colour
+    colors: [LightColor; MAX_NB_LIGHTS],
+    azimuth_slider: slider::State,
+    elevation_slider: slider::State,
+    hue_slider: slider::State,
+    saturation_slider: slider::State,
+    intensity_slider: slider::State,
+}
+
+impl LightingSection {
+    fn view<S: AppState>(&mut self, lighting: &LightingParameters) -> Column<Message<S>> {
+        let light = &lighting.lights[self.selected.0];
+        let color = self.colors[self.selected.0];
+        Column::new()
+            .spacing(5)
+            .push(PickList::new(
+                &mut self.picklist,
+                &ALL_LIGHT_ID[..],
+                Some(self.selected),
+                Message::SelectLight,
+            ))
+            .push(
+                Row::new()
+                    .spacing(5)
+                    .push(Text::new("Azimuth"))
+                    .push(Slider::new(
+                        &mut self.azimuth_slider,
+                        -180f32..=180f32,
+                        light.azimuth.to_degrees(),
+                        Message::LightAzimuth,
+                    )),
+            )
+            .push(
+                Row::new()
+                    .spacing(5)
+                    .push(Text::new("Elevation"))
+                    .push(Slider::new(
+                        &mut self.elevation_slider,
+                        -90f32..=90f32,
+                        light.elevation.to_degrees(),
+                        Message::LightElevation,
+                    )),
+            )
+            .push(
+                Row::new()
+                    .spacing(5)
+                    .push(Text::new("Hue"))
+                    .push(Slider::new(
+                        &mut self.hue_slider,
+                        0f32..=360f32,
+                        color.hue,
+                        Message::LightHue,
+                    )),
+            )
+            .push(
+                Row::new().spacing(5).push(Text::new("Saturation")).push(
+                    Slider::new(
+                        &mut self.saturation_slider,
+                        0f32..=1f32,
+                        color.saturation,
+                        Message::LightSaturation,
+                    )
+                    .step(0.01),
+                ),
+            )
+            .push(
+                Row::new().spacing(5).push(Text::new("Intensity")).push(
+                    Slider::new(
+                        &mut self.intensity_slider,
+                        0f32..=2f32,
+                        light.intensity,
+                        Message::LightIntensity,
+                    )
+                    .step(0.01),
+                ),
+            )
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct LightColor {
+    hue: f32,
+    saturation: f32,
+}
+
+impl LightColor {
+    fn rgb(&self) -> [f32; 3] {
+        use color_space::{Hsv, Rgb};
+        let rgb = Rgb::from(Hsv::new(self.hue as f64, self.saturation as f64, 1.));
+        [
+            rgb.r as f32 / 255.,
+            rgb.g as f32 / 255.,
+            rgb.b as f32 / 255.,
+        ]
+    }
+}
+
+/// The index of a light source, displayed starting from 1
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct LightId(usize);
+
+const ALL_LIGHT_ID: [LightId; MAX_NB_LIGHTS] = [LightId(0), LightId(1), LightId(2), LightId(3)];
+
+impl std::fmt::Display for LightId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Light {}", self.0 + 1)
+    }
 }
 
 struct FogParameters {
@@ -131,6 +712,7 @@ struct FogParameters {
     length: f32,
     length_slider: slider::State,
     picklist: pick_list::State<FogChoice>,
+    center_on_selection_btn: button::State,
 }
 
 impl FogParameters {
@@ -196,6 +778,16 @@ impl FogParameters {
                     .push(gradient_text)
                     .push(softness_slider),
             );
+
+        let mut center_btn = text_btn(
+            &mut self.center_on_selection_btn,
+            "Center fog on selection",
+            ui_size.clone(),
+        );
+        if self.visible {
+            center_btn = center_btn.on_press(Message::CenterFogOnSelection);
+        }
+        column = column.push(center_btn);
         column
     }
 
@@ -221,6 +813,7 @@ impl Default for FogParameters {
             radius_slider: Default::default(),
             from_camera: true,
             picklist: Default::default(),
+            center_on_selection_btn: Default::default(),
         }
     }
 }
@@ -17,16 +17,83 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 */
 use super::*;
 
+/// A choice of chemical modification for one end of a strand, as displayed in the pick lists
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct EndModification(Option<StrandModification>);
+
+const END_MODIFICATIONS: [EndModification; 5] = [
+    EndModification(None),
+    EndModification(Some(StrandModification::Phosphate)),
+    EndModification(Some(StrandModification::Biotin)),
+    EndModification(Some(StrandModification::Cy3)),
+    EndModification(Some(StrandModification::Cy5)),
+];
+
+impl std::fmt::Display for EndModification {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(modification) = self.0 {
+            write!(f, "{}", modification)
+        } else {
+            write!(f, "None")
+        }
+    }
+}
+
 pub struct EditionTab<S: AppState> {
     scroll: iced::scrollable::State,
     helix_roll_factory: RequestFactory<HelixRoll>,
     color_picker: ColorPicker,
-    _sequence_input: SequenceInput,
+    sequence_input: SequenceInput,
     redim_helices_button: button::State,
     redim_all_helices_button: button::State,
+    shortest_path_button: button::State,
+    go_to_xover_button: button::State,
+    xover_tool_button: button::State,
+    strand_end_mode_button: button::State,
+    strand_end_toggle_button: button::State,
+    ligate_ends_button: button::State,
+    flip_strand_button: button::State,
+    domain_mode_button: button::State,
+    strand_end: StrandEnd,
+    strand_sort_key: SortKey,
+    strand_sort_picklist: pick_list::State<SortKey>,
+    reorder_strands_button: button::State,
+    strand_palette: ColorBlindPalette,
+    strand_palette_picklist: pick_list::State<ColorBlindPalette>,
+    recolor_strands_button: button::State,
+    xover_inputs: [text_input::State; 4],
+    xover_input_str: [String; 4],
+    loop_length_input: text_input::State,
+    loop_length_str: String,
+    insert_loop_button: button::State,
+    hairpin_stem_length_input: text_input::State,
+    hairpin_stem_length_str: String,
+    hairpin_loop_length_input: text_input::State,
+    hairpin_loop_length_str: String,
+    create_hairpin_button: button::State,
+    break_scaffold_button: button::State,
+    local_twist_button: button::State,
+    pub show_local_twist: bool,
     roll_target_btn: GoStop<S>,
+    optimize_rolls_button: button::State,
     color_square_state: ColorState,
     memory_color_squares: VecDeque<MemoryColorSquare>,
+    strand_name_input: text_input::State,
+    modification_5prime_picklist: pick_list::State<EndModification>,
+    modification_3prime_picklist: pick_list::State<EndModification>,
+    selection_group_name_input: text_input::State,
+    selection_group_name: String,
+    save_selection_group_button: button::State,
+    selection_group_buttons: Vec<SelectionGroupButtons>,
+}
+
+/// The buttons to recall or delete a selection group
+#[derive(Default)]
+struct SelectionGroupButtons {
+    select: button::State,
+    union: button::State,
+    difference: button::State,
+    delete: button::State,
 }
 
 struct MemoryColorSquare {
@@ -84,6 +151,38 @@ fn memory_color_column<'a, S: AppState>(
     }
     ret
 }
+macro_rules! add_build_helix_checkbox {
+    ($ret: ident, $app_state: ident, $build_helix_mode: ident, $ui_size: ident) => {
+        let has_grid = $app_state
+            .get_reader()
+            .get_dna_elements()
+            .iter()
+            .any(|e| matches!(e, DnaElement::Grid { .. }));
+        if has_grid {
+            let building_helices =
+                matches!($app_state.get_action_mode(), ActionMode::BuildHelix { .. });
+            $ret = $ret.push(right_checkbox(
+                building_helices,
+                "Build helices on grids",
+                Message::BuildHelixMode,
+                $ui_size.clone(),
+            ));
+            if let ActionMode::BuildHelix { position, length } = $build_helix_mode {
+                if length > 0 {
+                    $ret = $ret.push(
+                        Text::new(format!(
+                            "Double strand from {} to {}",
+                            position,
+                            position + length as isize - 1
+                        ))
+                        .size($ui_size.main_text()),
+                    );
+                }
+            }
+        }
+    };
+}
+
 macro_rules! add_roll_slider {
     ($ret:ident, $self:ident, $app_state: ident, $ui_size: ident) => {
         let selection = $app_state.get_selection_as_dnaelement();
@@ -111,6 +210,27 @@ macro_rules! add_autoroll_button {
     };
 }
 
+macro_rules! add_optimize_rolls_button {
+    ($ret:ident, $self:ident, $app_state: ident, $ui_size: ident) => {
+        let optimizing = $app_state.get_simulation_state().is_optimizing_rolls();
+        let label = if optimizing {
+            "Stop optimization"
+        } else {
+            "Optimize Rolls"
+        };
+        let mut button = text_btn(&mut $self.optimize_rolls_button, label, $ui_size.clone());
+        if optimizing || !$app_state.get_simulation_state().is_runing() {
+            button = button.on_press(Message::OptimizeRolls);
+        }
+        let mut row = Row::new().push(button).spacing(5);
+        if let Some(iteration) = $app_state.get_roll_optimization_iteration() {
+            row =
+                row.push(Text::new(format!("Iteration {}", iteration)).size($ui_size.main_text()));
+        }
+        $ret = $ret.push(row);
+    };
+}
+
 macro_rules! add_color_square {
     ($ret: ident, $self: ident, $color_square: ident) => {
         $ret = $ret.push($self.color_picker.view()).push(
@@ -180,21 +300,390 @@ macro_rules! add_suggestion_parameters_checkboxes {
     };
 }
 
+macro_rules! add_shortest_path_button {
+    ($ret: ident, $self: ident, $app_state: ident, $ui_size: ident) => {
+        let mut shortest_path_button = text_btn(
+            &mut $self.shortest_path_button,
+            "Between selected nucleotides",
+            $ui_size.clone(),
+        );
+        if ensnano_interactor::pair_of_nucleotides($app_state.get_selection()).is_some() {
+            shortest_path_button = shortest_path_button.on_press(Message::HighlightShortestPath);
+        }
+        $ret = $ret.push(shortest_path_button);
+    };
+}
+
+macro_rules! add_strand_end_buttons {
+    ($ret: ident, $self: ident, $app_state: ident, $ui_size: ident) => {
+        let mut strand_end_mode_button = text_btn(
+            &mut $self.strand_end_mode_button,
+            "Select strand ends",
+            $ui_size.clone(),
+        );
+        if $app_state.get_selection_mode() != SelectionMode::StrandEnd($self.strand_end) {
+            strand_end_mode_button = strand_end_mode_button.on_press(Message::SelectStrandEnds);
+        }
+        let end_label = match $self.strand_end {
+            StrandEnd::FivePrime => "5'",
+            StrandEnd::ThreePrime => "3'",
+        };
+        let strand_end_toggle_button = text_btn(
+            &mut $self.strand_end_toggle_button,
+            end_label,
+            $ui_size.clone(),
+        )
+        .on_press(Message::ToggleStrandEnd);
+        $ret = $ret.push(
+            Row::new()
+                .spacing(3)
+                .push(strand_end_mode_button)
+                .push(strand_end_toggle_button),
+        );
+        let mut ligate_ends_button =
+            text_btn(&mut $self.ligate_ends_button, "Ligate Ends", $ui_size.clone());
+        if let Some((end_a, end_b)) = crate::gui::context_menu::selected_strand_ends($app_state) {
+            ligate_ends_button = ligate_ends_button.on_press(Message::LigateEnds(end_a, end_b));
+        }
+        $ret = $ret.push(ligate_ends_button);
+    };
+}
+
+macro_rules! add_domain_rows {
+    ($ret: ident, $self: ident, $app_state: ident, $ui_size: ident) => {
+        let mut domain_mode_button = text_btn(
+            &mut $self.domain_mode_button,
+            "Select domains",
+            $ui_size.clone(),
+        );
+        if $app_state.get_selection_mode() != SelectionMode::Domain {
+            domain_mode_button = domain_mode_button.on_press(Message::SelectDomains);
+        }
+        $ret = $ret.push(domain_mode_button);
+        if let [Selection::Domain(_, domain_id)] = $app_state.get_selection() {
+            let reader = $app_state.get_reader();
+            let domain_id = *domain_id as usize;
+            if let Some(length) = reader.scaffold_domain_length(domain_id) {
+                $ret = $ret
+                    .push(Text::new(format!("Length: {} bp", length)).size($ui_size.main_text()));
+            }
+            let gc_content = reader
+                .scaffold_domain_gc_content(domain_id)
+                .map(|gc| format!("{:.1}%", 100. * gc))
+                .unwrap_or_else(|| String::from("unknown"));
+            $ret = $ret
+                .push(Text::new(format!("GC content: {}", gc_content)).size($ui_size.main_text()));
+        }
+    };
+}
+
+macro_rules! add_reorder_strands_row {
+    ($ret: ident, $self: ident, $ui_size: ident) => {
+        let picklist = PickList::new(
+            &mut $self.strand_sort_picklist,
+            &SortKey::ALL[..],
+            Some($self.strand_sort_key),
+            Message::StrandSortKeyPicked,
+        );
+        let reorder_strands_button = text_btn(
+            &mut $self.reorder_strands_button,
+            "Reorder",
+            $ui_size.clone(),
+        )
+        .on_press(Message::ReorderStrands($self.strand_sort_key));
+        $ret = $ret.push(
+            Row::new()
+                .spacing(3)
+                .push(picklist)
+                .push(reorder_strands_button),
+        );
+    };
+}
+
+macro_rules! add_recolor_strands_row {
+    ($ret: ident, $self: ident, $ui_size: ident) => {
+        let picklist = PickList::new(
+            &mut $self.strand_palette_picklist,
+            &ColorBlindPalette::ALL[..],
+            Some($self.strand_palette),
+            Message::StrandPalettePicked,
+        );
+        let recolor_strands_button = text_btn(
+            &mut $self.recolor_strands_button,
+            "Recolor staples",
+            $ui_size.clone(),
+        )
+        .on_press(Message::RecolorStrands($self.strand_palette));
+        $ret = $ret.push(
+            Row::new()
+                .spacing(3)
+                .push(picklist)
+                .push(recolor_strands_button),
+        );
+    };
+}
+
+macro_rules! add_strand_modification_row {
+    ($ret: ident, $picklist_state: expr, $label: expr, $modification: expr, $end: expr, $s_id: ident, $ui_size: ident) => {
+        let end = $end;
+        let picklist = PickList::new(
+            &mut $picklist_state,
+            &END_MODIFICATIONS[..],
+            Some(EndModification($modification)),
+            move |m: EndModification| Message::StrandModificationPicked($s_id, end, m.0),
+        );
+        $ret = $ret.push(
+            Row::new()
+                .spacing(3)
+                .push(Text::new($label).size($ui_size.main_text()))
+                .push(picklist),
+        );
+    };
+}
+
+macro_rules! add_go_to_xover_inputs {
+    ($ret: ident, $self: ident, $ui_size: ident) => {
+        let labels = ["Strand A", "Pos A", "Strand B", "Pos B"];
+        let mut inputs = $self
+            .xover_inputs
+            .iter_mut()
+            .zip($self.xover_input_str.iter())
+            .enumerate();
+        for _ in 0..2 {
+            let mut row = Row::new().spacing(3);
+            for (idx, (input, value_str)) in inputs.by_ref().take(2) {
+                row = row
+                    .push(Text::new(labels[idx]).size($ui_size.main_text()))
+                    .push(
+                        TextInput::new(input, "", value_str, move |s| {
+                            Message::GoToXoverInput(idx, s)
+                        })
+                        .style(BadValue(value_str.parse::<usize>().is_ok())),
+                    );
+            }
+            $ret = $ret.push(row);
+        }
+
+        let mut go_to_xover_button = text_btn(
+            &mut $self.go_to_xover_button,
+            "Go to crossover",
+            $ui_size.clone(),
+        );
+        if let Some((a, b)) = Self::parse_xover_input(&$self.xover_input_str) {
+            go_to_xover_button = go_to_xover_button.on_press(Message::GoToXover(a, b));
+        }
+        $ret = $ret.push(go_to_xover_button);
+    };
+}
+
+macro_rules! add_selection_group_rows {
+    ($ret: ident, $self: ident, $app_state: ident, $ui_size: ident) => {
+        let name = $self.selection_group_name.trim().to_string();
+        let mut save_button = text_btn(
+            &mut $self.save_selection_group_button,
+            "Save selection",
+            $ui_size.clone(),
+        );
+        if !name.is_empty() && !$app_state.get_selection().is_empty() {
+            save_button = save_button.on_press(Message::SaveSelectionGroup(name));
+        }
+        $ret = $ret.push(
+            Row::new()
+                .spacing(3)
+                .push(
+                    TextInput::new(
+                        &mut $self.selection_group_name_input,
+                        "Name",
+                        &$self.selection_group_name,
+                        Message::SelectionGroupNameInput,
+                    )
+                    .size($ui_size.main_text()),
+                )
+                .push(save_button),
+        );
+        let groups = $app_state.get_reader().get_selection_groups();
+        $self
+            .selection_group_buttons
+            .resize_with(groups.len(), Default::default);
+        if groups.is_empty() {
+            $ret = $ret.push(Text::new("No selection group").color(innactive_color()));
+        }
+        for ((name, nb_elements), buttons) in
+            groups.iter().zip($self.selection_group_buttons.iter_mut())
+        {
+            $ret = $ret.push(
+                Text::new(format!("{} ({} elements)", name, nb_elements))
+                    .size($ui_size.main_text()),
+            );
+            $ret = $ret.push(
+                Row::new()
+                    .spacing(3)
+                    .push(
+                        text_btn(&mut buttons.select, "Select", $ui_size.clone()).on_press(
+                            Message::RecallSelectionGroup(
+                                name.clone(),
+                                SelectionGroupCombination::Replace,
+                            ),
+                        ),
+                    )
+                    .push(
+                        text_btn(&mut buttons.union, "Add", $ui_size.clone()).on_press(
+                            Message::RecallSelectionGroup(
+                                name.clone(),
+                                SelectionGroupCombination::Union,
+                            ),
+                        ),
+                    )
+                    .push(
+                        text_btn(&mut buttons.difference, "Remove", $ui_size.clone()).on_press(
+                            Message::RecallSelectionGroup(
+                                name.clone(),
+                                SelectionGroupCombination::Difference,
+                            ),
+                        ),
+                    )
+                    .push(
+                        text_btn(&mut buttons.delete, "Delete", $ui_size.clone())
+                            .on_press(Message::DeleteSelectionGroup(name.clone())),
+                    ),
+            );
+        }
+    };
+}
+
+macro_rules! add_insert_loop_row {
+    ($ret: ident, $self: ident, $nucl: ident, $ui_size: ident) => {
+        let loop_length = $self
+            .loop_length_str
+            .parse::<usize>()
+            .ok()
+            .filter(|l| *l > 0);
+        let mut insert_loop_button = text_btn(
+            &mut $self.insert_loop_button,
+            "Insert Loop",
+            $ui_size.clone(),
+        );
+        if let Some(length) = loop_length {
+            insert_loop_button =
+                insert_loop_button.on_press(Message::InsertLoop { at: $nucl, length });
+        }
+        $ret = $ret.push(
+            Row::new()
+                .spacing(3)
+                .push(Text::new("Length").size($ui_size.main_text()))
+                .push(
+                    TextInput::new(
+                        &mut $self.loop_length_input,
+                        "",
+                        &$self.loop_length_str,
+                        Message::LoopLengthInput,
+                    )
+                    .style(BadValue(loop_length.is_some())),
+                )
+                .push(insert_loop_button),
+        );
+    };
+}
+
+macro_rules! add_hairpin_rows {
+    ($ret: ident, $self: ident, $nucl: ident, $ui_size: ident) => {
+        let stem_length = $self
+            .hairpin_stem_length_str
+            .parse::<usize>()
+            .ok()
+            .filter(|l| *l > 0);
+        let loop_length = $self.hairpin_loop_length_str.parse::<usize>().ok();
+        let mut create_hairpin_button = text_btn(
+            &mut $self.create_hairpin_button,
+            "Create Hairpin",
+            $ui_size.clone(),
+        );
+        if let (Some(stem_length), Some(loop_length)) = (stem_length, loop_length) {
+            create_hairpin_button = create_hairpin_button.on_press(Message::CreateHairpin {
+                at: $nucl,
+                stem_length,
+                loop_length,
+            });
+        }
+        $ret = $ret.push(
+            Row::new()
+                .spacing(3)
+                .push(Text::new("Stem").size($ui_size.main_text()))
+                .push(
+                    TextInput::new(
+                        &mut $self.hairpin_stem_length_input,
+                        "",
+                        &$self.hairpin_stem_length_str,
+                        Message::HairpinStemLengthInput,
+                    )
+                    .style(BadValue(stem_length.is_some())),
+                )
+                .push(Text::new("Loop").size($ui_size.main_text()))
+                .push(
+                    TextInput::new(
+                        &mut $self.hairpin_loop_length_input,
+                        "",
+                        &$self.hairpin_loop_length_str,
+                        Message::HairpinLoopLengthInput,
+                    )
+                    .style(BadValue(loop_length.is_some())),
+                ),
+        );
+        $ret = $ret.push(create_hairpin_button);
+    };
+}
+
 impl<S: AppState> EditionTab<S> {
     pub fn new() -> Self {
         Self {
             scroll: Default::default(),
             helix_roll_factory: RequestFactory::new(FactoryId::HelixRoll, HelixRoll {}),
             color_picker: ColorPicker::new(),
-            _sequence_input: SequenceInput::new(),
+            sequence_input: SequenceInput::new(),
             redim_helices_button: Default::default(),
             redim_all_helices_button: Default::default(),
+            shortest_path_button: Default::default(),
+            go_to_xover_button: Default::default(),
+            xover_tool_button: Default::default(),
+            strand_end_mode_button: Default::default(),
+            strand_end_toggle_button: Default::default(),
+            ligate_ends_button: Default::default(),
+            flip_strand_button: Default::default(),
+            domain_mode_button: Default::default(),
+            strand_end: StrandEnd::FivePrime,
+            strand_sort_key: SortKey::ByLength,
+            strand_sort_picklist: Default::default(),
+            reorder_strands_button: Default::default(),
+            strand_palette: Default::default(),
+            strand_palette_picklist: Default::default(),
+            recolor_strands_button: Default::default(),
+            xover_inputs: Default::default(),
+            xover_input_str: Default::default(),
+            loop_length_input: Default::default(),
+            loop_length_str: String::from("5"),
+            insert_loop_button: Default::default(),
+            hairpin_stem_length_input: Default::default(),
+            hairpin_stem_length_str: String::from("8"),
+            hairpin_loop_length_input: Default::default(),
+            hairpin_loop_length_str: String::from("4"),
+            create_hairpin_button: Default::default(),
+            break_scaffold_button: Default::default(),
+            local_twist_button: Default::default(),
+            show_local_twist: false,
             roll_target_btn: GoStop::new(
                 "Autoroll selected helices".to_owned(),
                 Message::RollTargeted,
             ),
+            optimize_rolls_button: Default::default(),
             color_square_state: Default::default(),
             memory_color_squares: VecDeque::new(),
+            strand_name_input: Default::default(),
+            modification_5prime_picklist: Default::default(),
+            modification_3prime_picklist: Default::default(),
+            selection_group_name_input: Default::default(),
+            selection_group_name: String::new(),
+            save_selection_group_button: Default::default(),
+            selection_group_buttons: Vec::new(),
         }
     }
 
@@ -203,28 +692,219 @@ impl<S: AppState> EditionTab<S> {
         ui_size: UiSize,
         _width: u16,
         app_state: &S,
+        build_helix_mode: ActionMode,
     ) -> Element<'a, Message<S>> {
         let mut ret = Column::new().spacing(5);
         let selection = app_state.get_selection_as_dnaelement();
         let roll_target_helices = self.get_roll_target_helices(&selection);
         section!(ret, ui_size, "Edition");
+        ret = ret.push(right_checkbox(
+            app_state.is_selection_locked(),
+            "Lock Selection",
+            Message::LockSelection,
+            ui_size.clone(),
+        ));
+        add_build_helix_checkbox!(ret, app_state, build_helix_mode, ui_size);
         add_roll_slider!(ret, self, app_state, ui_size);
         add_autoroll_button!(ret, self, app_state, roll_target_helices);
+        add_optimize_rolls_button!(ret, self, app_state, ui_size);
 
         let color_square = self.color_picker.color_square(&mut self.color_square_state);
         if app_state.get_selection_mode() == SelectionMode::Strand {
             add_color_square!(ret, self, color_square);
+            if let [Selection::Strand(_, s_id)] = app_state.get_selection() {
+                let s_id = *s_id as usize;
+                let name = app_state.get_reader().strand_name(s_id);
+                ret = ret.push(
+                    Row::new()
+                        .spacing(3)
+                        .push(Text::new("Name").size(ui_size.main_text()))
+                        .push(
+                            TextInput::new(&mut self.strand_name_input, "Name", &name, move |n| {
+                                Message::StrandNameChanged(s_id, n)
+                            })
+                            .size(ui_size.main_text()),
+                        ),
+                );
+                let (modification_5prime, modification_3prime) =
+                    app_state.get_reader().strand_modifications(s_id);
+                add_strand_modification_row!(
+                    ret,
+                    self.modification_5prime_picklist,
+                    "5' modification",
+                    modification_5prime,
+                    StrandEnd::FivePrime,
+                    s_id,
+                    ui_size
+                );
+                add_strand_modification_row!(
+                    ret,
+                    self.modification_3prime_picklist,
+                    "3' modification",
+                    modification_3prime,
+                    StrandEnd::ThreePrime,
+                    s_id,
+                    ui_size
+                );
+                ret = ret.push(self.sequence_input.view(ui_size));
+            }
         }
 
+        subsection!(ret, ui_size, "Selection groups");
+        add_selection_group_rows!(ret, self, app_state, ui_size);
+
         subsection!(ret, ui_size, "Suggestions Parameters");
         add_suggestion_parameters_checkboxes!(ret, self, app_state, ui_size);
 
         subsection!(ret, ui_size, "Tighten 2D helices");
         add_tighten_helices_button!(ret, self, app_state, ui_size, roll_target_helices);
 
+        subsection!(ret, ui_size, "Shortest path");
+        add_shortest_path_button!(ret, self, app_state, ui_size);
+
+        subsection!(ret, ui_size, "Strand ends");
+        add_strand_end_buttons!(ret, self, app_state, ui_size);
+
+        subsection!(ret, ui_size, "Strand direction");
+        let mut flip_strand_button = text_btn(
+            &mut self.flip_strand_button,
+            "Flip Direction",
+            ui_size.clone(),
+        );
+        if let [Selection::Strand(_, s_id)] = app_state.get_selection() {
+            flip_strand_button = flip_strand_button.on_press(Message::FlipStrand(*s_id as usize));
+        }
+        ret = ret.push(flip_strand_button);
+
+        subsection!(ret, ui_size, "Local twist");
+        let local_twist_text = if self.show_local_twist {
+            "Hide Local Twist"
+        } else {
+            "Show Local Twist"
+        };
+        ret = ret.push(
+            text_btn(
+                &mut self.local_twist_button,
+                local_twist_text,
+                ui_size.clone(),
+            )
+            .on_press(Message::ShowLocalTwist(!self.show_local_twist)),
+        );
+
+        subsection!(ret, ui_size, "Domains");
+        add_domain_rows!(ret, self, app_state, ui_size);
+
+        subsection!(ret, ui_size, "Strand numbering");
+        add_reorder_strands_row!(ret, self, ui_size);
+
+        subsection!(ret, ui_size, "Strand colors");
+        add_recolor_strands_row!(ret, self, ui_size);
+
+        subsection!(ret, ui_size, "Add crossover");
+        ret = ret.push(
+            text_btn(
+                &mut self.xover_tool_button,
+                "Click two nucleotides (2D)",
+                ui_size.clone(),
+            )
+            .on_press(Message::Start2dXoverTool),
+        );
+
+        subsection!(ret, ui_size, "Go to crossover");
+        add_go_to_xover_inputs!(ret, self, ui_size);
+
+        if let [Selection::Nucleotide(_, nucl)] = app_state.get_selection() {
+            let nucl = *nucl;
+            subsection!(ret, ui_size, "Insert loop after nucleotide");
+            add_insert_loop_row!(ret, self, nucl, ui_size);
+            if app_state
+                .get_reader()
+                .strand_end_of_nucl(nucl)
+                .map(|(_, end)| end)
+                == Some(StrandEnd::ThreePrime)
+            {
+                subsection!(ret, ui_size, "Hairpin after 3' end");
+                add_hairpin_rows!(ret, self, nucl, ui_size);
+            }
+            if app_state.get_reader().nucl_is_on_cyclic_scaffold(nucl) {
+                subsection!(ret, ui_size, "Linear scaffold");
+                ret = ret.push(
+                    text_btn(
+                        &mut self.break_scaffold_button,
+                        "Open scaffold here",
+                        ui_size.clone(),
+                    )
+                    .on_press(Message::BreakScaffoldAt(nucl)),
+                );
+            }
+        }
+
         Scrollable::new(&mut self.scroll).push(ret).into()
     }
 
+    pub fn has_keyboard_priority(&self) -> bool {
+        self.strand_name_input.is_focused()
+            || self.selection_group_name_input.is_focused()
+            || self.sequence_input.has_keyboard_priority()
+    }
+
+    pub fn update_sequence(&mut self, sequence: String, truncated_length: Option<usize>) {
+        self.sequence_input
+            .update_sequence(sequence, truncated_length)
+    }
+
+    pub fn set_check_hairpin(&mut self, check_hairpin: bool) {
+        self.sequence_input.set_check_hairpin(check_hairpin)
+    }
+
+    pub fn set_strand_sort_key(&mut self, key: SortKey) {
+        self.strand_sort_key = key;
+    }
+
+    pub fn set_strand_palette(&mut self, palette: ColorBlindPalette) {
+        self.strand_palette = palette;
+    }
+
+    pub fn get_strand_end(&self) -> StrandEnd {
+        self.strand_end
+    }
+
+    /// Switch between the 5' and 3' ends and return the new value
+    pub fn toggle_strand_end(&mut self) -> StrandEnd {
+        self.strand_end = self.strand_end.other();
+        self.strand_end
+    }
+
+    pub fn update_xover_input(&mut self, idx: usize, value: String) {
+        if let Some(value_str) = self.xover_input_str.get_mut(idx) {
+            *value_str = value;
+        }
+    }
+
+    pub fn update_selection_group_name(&mut self, name: String) {
+        self.selection_group_name = name;
+    }
+
+    pub fn update_loop_length_input(&mut self, value: String) {
+        self.loop_length_str = value;
+    }
+
+    pub fn update_hairpin_stem_length_input(&mut self, value: String) {
+        self.hairpin_stem_length_str = value;
+    }
+
+    pub fn update_hairpin_loop_length_input(&mut self, value: String) {
+        self.hairpin_loop_length_str = value;
+    }
+
+    fn parse_xover_input(input_str: &[String; 4]) -> Option<((usize, usize), (usize, usize))> {
+        let strand_a = input_str[0].parse::<usize>().ok()?;
+        let pos_a = input_str[1].parse::<usize>().ok()?;
+        let strand_b = input_str[2].parse::<usize>().ok()?;
+        let pos_b = input_str[3].parse::<usize>().ok()?;
+        Some(((strand_a, pos_a), (strand_b, pos_b)))
+    }
+
     fn get_roll_target_helices(&self, selection: &[DnaElementKey]) -> Vec<usize> {
         let mut ret = vec![];
         for s in selection.iter() {
@@ -235,6 +915,12 @@ impl<S: AppState> EditionTab<S> {
         ret
     }
 
+    /// The helices whose roll must be optimized: the selected helices if there are some, all the
+    /// helices otherwise.
+    pub fn get_roll_optimization_targets(&self, selection: &[DnaElementKey]) -> Option<Vec<usize>> {
+        Some(self.get_roll_target_helices(selection)).filter(|helices| !helices.is_empty())
+    }
+
     pub fn update_roll_request(
         &mut self,
         value_id: ValueId,
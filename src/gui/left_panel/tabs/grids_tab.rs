@@ -25,7 +25,22 @@ pub struct GridTab {
     make_honeycomb_grid_btn: button::State,
     hyperboloid_factory: RequestFactory<Hyperboloid_>,
     start_hyperboloid_btn: button::State,
+    torus_knot_factory: RequestFactory<TorusKnot_>,
+    add_torus_knot_btn: button::State,
     make_grid_btn: button::State,
+    duplicate_grid_btn: button::State,
+    duplication_offset_inputs: [text_input::State; 3],
+    duplication_offset_str: [String; 3],
+    /// The plane across which the selected grid will be mirrored. It is displayed in the 3D
+    /// scene while it is `Some`.
+    mirror_axis: Option<MirrorAxis>,
+    mirror_axis_picklist: pick_list::State<MirrorAxis>,
+    mirror_grid_btn: button::State,
+    twist_offset_slider: slider::State,
+    /// The grid whose twist offset is being dragged, with the twist offsets, in radians, that it
+    /// had before and has during the drag.
+    twist_offset_drag: Option<(usize, f32, f32)>,
+    pub show_lattice_grid: bool,
 }
 
 macro_rules! add_grid_buttons {
@@ -94,6 +109,26 @@ macro_rules! add_hyperboloid_sliders {
     };
 }
 
+macro_rules! add_torus_knot_button {
+    ($ret: ident, $self: ident, $ui_size: ident) => {
+        for view in $self
+            .torus_knot_factory
+            .view(true, $ui_size.main_text())
+            .into_iter()
+        {
+            $ret = $ret.push(view);
+        }
+        $ret = $ret.push(
+            text_btn(
+                &mut $self.add_torus_knot_btn,
+                "Torus knot",
+                $ui_size.clone(),
+            )
+            .on_press(Message::NewTorusKnot),
+        );
+    };
+}
+
 macro_rules! add_guess_grid_button {
     ($ret: ident, $self: ident, $ui_size: ident, $app_state: ident) => {
         let mut button_make_grid =
@@ -109,6 +144,97 @@ macro_rules! add_guess_grid_button {
     };
 }
 
+macro_rules! add_duplicate_grid_button {
+    ($ret: ident, $self: ident, $ui_size: ident, $app_state: ident) => {
+        let mut offset_row = Row::new().spacing(3);
+        let labels = ["x", "y", "z"];
+        for (coord, (input, value_str)) in $self
+            .duplication_offset_inputs
+            .iter_mut()
+            .zip($self.duplication_offset_str.iter())
+            .enumerate()
+        {
+            offset_row = offset_row
+                .push(Text::new(labels[coord]).size($ui_size.main_text()))
+                .push(
+                    TextInput::new(input, "0", value_str, move |s| {
+                        Message::GridDuplicationOffsetInput(coord, s)
+                    })
+                    .style(BadValue(value_str.parse::<f32>().is_ok())),
+                );
+        }
+        $ret = $ret.push(Text::new("Offset (nm)").size($ui_size.main_text()));
+        $ret = $ret.push(offset_row);
+
+        let mut duplicate_grid_btn = text_btn(
+            &mut $self.duplicate_grid_btn,
+            "Duplicate Grid",
+            $ui_size.clone(),
+        );
+        let grids = ensnano_interactor::extract_grids($app_state.get_selection());
+        let offset = Self::parse_offset(&$self.duplication_offset_str);
+        if let (&[grid_id], Some(offset)) = (grids.as_slice(), offset) {
+            duplicate_grid_btn =
+                duplicate_grid_btn.on_press(Message::DuplicateGrid { grid_id, offset });
+        }
+        $ret = $ret.push(duplicate_grid_btn);
+        $ret = $ret.push(Text::new("Select one grid").size($ui_size.main_text()));
+    };
+}
+
+macro_rules! add_mirror_grid_row {
+    ($ret: ident, $self: ident, $ui_size: ident, $app_state: ident) => {
+        let picklist = PickList::new(
+            &mut $self.mirror_axis_picklist,
+            &MirrorAxis::ALL[..],
+            $self.mirror_axis,
+            Message::MirrorAxisPicked,
+        );
+        let mut mirror_grid_btn =
+            text_btn(&mut $self.mirror_grid_btn, "Mirror Grid", $ui_size.clone());
+        let grids = ensnano_interactor::extract_grids($app_state.get_selection());
+        if let (&[grid_id], Some(axis)) = (grids.as_slice(), $self.mirror_axis) {
+            mirror_grid_btn = mirror_grid_btn.on_press(Message::MirrorGrid { grid_id, axis });
+        }
+        $ret = $ret.push(Row::new().spacing(3).push(picklist).push(mirror_grid_btn));
+        $ret = $ret.push(Text::new("Select one grid and a plane").size($ui_size.main_text()));
+    };
+}
+
+macro_rules! add_twist_offset_slider {
+    ($ret: ident, $self: ident, $ui_size: ident, $app_state: ident) => {
+        let grids = ensnano_interactor::extract_grids($app_state.get_selection());
+        let twist_offset = if let &[grid_id] = grids.as_slice() {
+            $app_state
+                .get_reader()
+                .get_grid_twist_offset(grid_id)
+                .map(|offset| (grid_id, offset.to_degrees()))
+        } else {
+            None
+        };
+        if let Some((grid_id, offset)) = twist_offset {
+            $ret = $ret.push(
+                Row::new()
+                    .spacing(5)
+                    .push(Text::new(format!("{:.0}°", offset)).size($ui_size.main_text()))
+                    .push(
+                        Slider::new(
+                            &mut $self.twist_offset_slider,
+                            -180f32..=180f32,
+                            offset,
+                            move |offset| Message::GridTwistOffset { grid_id, offset },
+                        )
+                        .on_release(Message::GridTwistOffsetReleased)
+                        .step(1.),
+                    ),
+            );
+            $ret = $ret.push(Text::new("Roll of new helices").size($ui_size.main_text()));
+        } else {
+            $ret = $ret.push(Text::new("Select one grid").size($ui_size.main_text()));
+        }
+    };
+}
+
 impl GridTab {
     pub fn new() -> Self {
         Self {
@@ -118,7 +244,18 @@ impl GridTab {
             hyperboloid_factory: RequestFactory::new(FactoryId::Hyperboloid, Hyperboloid_ {}),
             finalize_hyperboloid_btn: Default::default(),
             start_hyperboloid_btn: Default::default(),
+            torus_knot_factory: RequestFactory::new(FactoryId::TorusKnot, TorusKnot_ {}),
+            add_torus_knot_btn: Default::default(),
             make_grid_btn: Default::default(),
+            duplicate_grid_btn: Default::default(),
+            duplication_offset_inputs: Default::default(),
+            duplication_offset_str: ["0".to_string(), "0".to_string(), "0".to_string()],
+            mirror_axis: None,
+            mirror_axis_picklist: Default::default(),
+            mirror_grid_btn: Default::default(),
+            twist_offset_slider: Default::default(),
+            twist_offset_drag: None,
+            show_lattice_grid: false,
         }
     }
 
@@ -137,6 +274,12 @@ impl GridTab {
 
         extra_jump!(ret);
 
+        subsection!(ret, ui_size, "Twist offset");
+
+        add_twist_offset_slider!(ret, self, ui_size, app_state);
+
+        extra_jump!(ret);
+
         subsection!(ret, ui_size, "New nanotube");
 
         add_start_cancel_hyperboloid_button!(ret, self, ui_size, app_state);
@@ -145,10 +288,37 @@ impl GridTab {
 
         extra_jump!(ret);
 
+        subsection!(ret, ui_size, "New torus knot");
+
+        add_torus_knot_button!(ret, self, ui_size);
+
+        extra_jump!(ret);
+
         subsection!(ret, ui_size, "Guess grid");
 
         add_guess_grid_button!(ret, self, ui_size, app_state);
 
+        extra_jump!(ret);
+
+        subsection!(ret, ui_size, "Duplicate grid");
+
+        add_duplicate_grid_button!(ret, self, ui_size, app_state);
+
+        extra_jump!(ret);
+
+        subsection!(ret, ui_size, "Mirror grid");
+
+        add_mirror_grid_row!(ret, self, ui_size, app_state);
+
+        extra_jump!(ret);
+
+        ret = ret.push(right_checkbox(
+            self.show_lattice_grid,
+            "Show Lattice Grid (2D)",
+            Message::ShowLatticeGrid,
+            ui_size.clone(),
+        ));
+
         Scrollable::new(&mut self.scroll).push(ret).into()
     }
 
@@ -166,4 +336,55 @@ impl GridTab {
         self.hyperboloid_factory
             .update_request(value_id, value, request);
     }
+
+    pub fn update_torus_knot_request(&mut self, value_id: ValueId, value: f32) {
+        let mut request = None;
+        self.torus_knot_factory
+            .update_request(value_id, value, &mut request);
+    }
+
+    pub fn get_torus_knot(&self) -> Option<TorusKnot> {
+        let mut request = None;
+        self.torus_knot_factory.make_request(&mut request);
+        request
+    }
+
+    pub fn update_duplication_offset(&mut self, coord: usize, value: String) {
+        if let Some(value_str) = self.duplication_offset_str.get_mut(coord) {
+            *value_str = value;
+        }
+    }
+
+    pub fn set_mirror_axis(&mut self, axis: Option<MirrorAxis>) {
+        self.mirror_axis = axis;
+    }
+
+    /// Record the twist offset given to a grid while its slider is dragged. `initial` is the twist
+    /// offset that the grid had before the drag started.
+    pub fn drag_twist_offset(&mut self, grid_id: usize, initial: f32, twist_offset: f32) {
+        let initial = self
+            .twist_offset_drag
+            .filter(|(g_id, _, _)| *g_id == grid_id)
+            .map(|(_, initial, _)| initial)
+            .unwrap_or(initial);
+        self.twist_offset_drag = Some((grid_id, initial, twist_offset));
+    }
+
+    /// End the drag of the twist offset slider and return the grid that was modified with its
+    /// twist offsets before and after the drag.
+    pub fn end_twist_offset_drag(&mut self) -> Option<(usize, f32, f32)> {
+        self.twist_offset_drag.take()
+    }
+
+    /// Return true if the mirror plane is currently displayed in the 3D scene
+    pub fn is_showing_mirror_plane(&self) -> bool {
+        self.mirror_axis.is_some()
+    }
+
+    fn parse_offset(offset_str: &[String; 3]) -> Option<Vec3> {
+        let x = offset_str[0].parse::<f32>().ok()?;
+        let y = offset_str[1].parse::<f32>().ok()?;
+        let z = offset_str[2].parse::<f32>().ok()?;
+        Some(Vec3::new(x, y, z))
+    }
 }
@@ -0,0 +1,108 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::*;
+use ensnano_interactor::{MeasureKind, Measurement};
+
+pub struct MeasureTab {
+    scroll: scrollable::State,
+    clear_btn: button::State,
+    /// The kind of quantity that is measured in the measure mode
+    pub measure_kind: MeasureKind,
+    measure_kind_picklist: pick_list::State<MeasureKind>,
+}
+
+impl MeasureTab {
+    pub fn new() -> Self {
+        Self {
+            scroll: Default::default(),
+            clear_btn: Default::default(),
+            measure_kind: Default::default(),
+            measure_kind_picklist: Default::default(),
+        }
+    }
+
+    pub fn view<'a, S: AppState>(
+        &'a mut self,
+        ui_size: UiSize,
+        app_state: &'a S,
+    ) -> Element<'a, Message<S>> {
+        let mut ret = Column::new().spacing(5);
+        section!(ret, ui_size, "Measure");
+        ret = ret.push(right_checkbox(
+            matches!(app_state.get_action_mode(), ActionMode::Measure(_)),
+            "Measure mode",
+            Message::MeasureMode,
+            ui_size.clone(),
+        ));
+        ret = ret.push(PickList::new(
+            &mut self.measure_kind_picklist,
+            &MeasureKind::ALL[..],
+            Some(self.measure_kind),
+            Message::MeasureKindPicked,
+        ));
+        ret = ret.push(Text::new(instructions(self.measure_kind)).size(ui_size.main_text()));
+        extra_jump!(ret);
+
+        subsection!(ret, ui_size, "Measurements");
+        let measurements = app_state.get_measurements();
+        if measurements.is_empty() {
+            ret = ret.push(Text::new("No measurement").color(innactive_color()));
+        }
+        let parameters = app_state.get_dna_parameters();
+        for measurement in measurements.iter() {
+            ret = ret.push(Text::new(format!(
+                "{}\n   {}",
+                measured_elements(measurement),
+                measurement.value_text(&parameters)
+            )));
+        }
+        extra_jump!(ret);
+        let mut clear_btn = text_btn(&mut self.clear_btn, "Clear", ui_size.clone());
+        if !measurements.is_empty() {
+            clear_btn = clear_btn.on_press(Message::ClearMeasurements);
+        }
+        ret = ret.push(clear_btn);
+        Scrollable::new(&mut self.scroll).push(ret).into()
+    }
+}
+
+fn instructions(kind: MeasureKind) -> &'static str {
+    match kind {
+        MeasureKind::Distance => {
+            "Click on two nucleotides of the 3D view to measure their distance"
+        }
+        MeasureKind::Angle => {
+            "Click on three nucleotides of the 3D view to measure the angle at the second one"
+        }
+        MeasureKind::HelixAngle => {
+            "Click on nucleotides of two helices of the 3D view to measure the angle between their \
+             axes"
+        }
+    }
+}
+
+fn measured_elements(measurement: &Measurement) -> String {
+    match measurement {
+        Measurement::Distance { nucls, .. } => format!("{} — {}", nucls.0, nucls.1),
+        Measurement::Angle { nucls, .. } => format!("{} — {} — {}", nucls.0, nucls.1, nucls.2),
+        Measurement::HelixAngle { nucls, .. } => {
+            format!("Helices {} — {}", nucls.0.helix, nucls.1.helix)
+        }
+    }
+}
@@ -17,12 +17,43 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 */
 
 use super::*;
+use crate::color_palette::ColorPalette;
+use crate::key_bindings::combination_to_string;
+use ensnano_interactor::graphics::{
+    PostProcessingQuality, SonificationMode, ALL_POST_PROCESSING_QUALITY,
+};
+
+const KEY_BINDINGS_TABLE_HEIGHT: u32 = 200;
+const PALETTE_COLUMNS: usize = 8;
 
 pub struct ParametersTab {
     size_pick_list: pick_list::State<UiSize>,
     scroll: scrollable::State,
     scroll_sensitivity_factory: RequestFactory<ScrollSentivity>,
+    min_strand_length_factory: RequestFactory<MinStrandLength>,
+    edit_history_size_factory: RequestFactory<EditHistorySize>,
+    pub session_log_enabled: bool,
     pub invert_y_scroll: bool,
+    export_graph_btn: button::State,
+    cleanup_orphans_btn: button::State,
+    key_bindings: Vec<(Option<KeyCombination>, HotkeyAction)>,
+    key_binding_buttons: Vec<button::State>,
+    key_bindings_scroll: scrollable::State,
+    /// The binding that will be replaced by the next key combination pressed by the user
+    recording_key_binding: Option<(HotkeyAction, Option<KeyCombination>)>,
+    plugin_actions: Vec<PluginMenuAction>,
+    plugin_action_buttons: Vec<button::State>,
+    pub sonification: SonificationMode,
+    sonification_volume_slider: slider::State,
+    pub post_processing_quality: PostProcessingQuality,
+    post_processing_quality_picklist: pick_list::State<PostProcessingQuality>,
+    color_palette: ColorPalette,
+    palette_squares: Vec<ColorState>,
+    palette_color_name_input: text_input::State,
+    palette_color_name: String,
+    add_palette_color_btn: button::State,
+    export_palette_btn: button::State,
+    import_palette_btn: button::State,
 }
 
 impl ParametersTab {
@@ -31,10 +62,81 @@ impl ParametersTab {
             size_pick_list: Default::default(),
             scroll: Default::default(),
             scroll_sensitivity_factory: RequestFactory::new(FactoryId::Scroll, ScrollSentivity {}),
+            min_strand_length_factory: RequestFactory::new(
+                FactoryId::MinStrandLength,
+                MinStrandLength {},
+            ),
+            edit_history_size_factory: RequestFactory::new(
+                FactoryId::EditHistorySize,
+                EditHistorySize {},
+            ),
+            session_log_enabled: true,
             invert_y_scroll: false,
+            export_graph_btn: Default::default(),
+            cleanup_orphans_btn: Default::default(),
+            key_bindings: Vec::new(),
+            key_binding_buttons: Vec::new(),
+            key_bindings_scroll: Default::default(),
+            recording_key_binding: None,
+            plugin_actions: Vec::new(),
+            plugin_action_buttons: Vec::new(),
+            sonification: Default::default(),
+            sonification_volume_slider: Default::default(),
+            post_processing_quality: Default::default(),
+            post_processing_quality_picklist: Default::default(),
+            color_palette: Default::default(),
+            palette_squares: Vec::new(),
+            palette_color_name_input: Default::default(),
+            palette_color_name: String::new(),
+            add_palette_color_btn: Default::default(),
+            export_palette_btn: Default::default(),
+            import_palette_btn: Default::default(),
         }
     }
 
+    pub fn update_color_palette(&mut self, palette: ColorPalette) {
+        self.palette_squares = vec![Default::default(); palette.colors.len()];
+        self.color_palette = palette;
+    }
+
+    pub fn update_palette_color_name(&mut self, name: String) {
+        self.palette_color_name = name;
+    }
+
+    /// Return the name typed for the next color of the palette and clear the input, or `None` if
+    /// no name was typed
+    pub fn take_palette_color_name(&mut self) -> Option<String> {
+        let name = std::mem::take(&mut self.palette_color_name);
+        Some(name.trim().to_string()).filter(|n| !n.is_empty())
+    }
+
+    pub fn has_keyboard_priority(&self) -> bool {
+        self.palette_color_name_input.is_focused()
+    }
+
+    pub fn update_key_bindings(&mut self, bindings: &KeyBindings) {
+        self.key_bindings = bindings.sorted_bindings();
+        self.key_binding_buttons = vec![Default::default(); self.key_bindings.len()];
+        self.recording_key_binding = None;
+    }
+
+    pub fn start_recording_key_binding(
+        &mut self,
+        action: HotkeyAction,
+        previous: Option<KeyCombination>,
+    ) {
+        self.recording_key_binding = Some((action, previous));
+    }
+
+    pub fn cancel_key_binding_recording(&mut self) {
+        self.recording_key_binding = None;
+    }
+
+    pub fn update_plugin_actions(&mut self, actions: Vec<PluginMenuAction>) {
+        self.plugin_action_buttons = vec![Default::default(); actions.len()];
+        self.plugin_actions = actions;
+    }
+
     pub fn view<'a, S: AppState>(
         &'a mut self,
         ui_size: UiSize,
@@ -68,6 +170,221 @@ impl ParametersTab {
             ui_size.clone(),
         ));
 
+        extra_jump!(ret);
+        subsection!(ret, ui_size, "Strand building");
+        for view in self
+            .min_strand_length_factory
+            .view(true, ui_size.main_text())
+            .into_iter()
+        {
+            ret = ret.push(view);
+        }
+
+        extra_jump!(ret);
+        subsection!(ret, ui_size, "Edit history");
+        ret = ret.push(
+            Text::new(
+                "Number of undo steps saved with the design, that can still be undone after \
+                reopening it",
+            )
+            .size(ui_size.main_text()),
+        );
+        for view in self
+            .edit_history_size_factory
+            .view(true, ui_size.main_text())
+            .into_iter()
+        {
+            ret = ret.push(view);
+        }
+        ret = ret.push(right_checkbox(
+            self.session_log_enabled,
+            "Log the operations of the session",
+            Message::SessionLogEnabled,
+            ui_size.clone(),
+        ));
+
+        extra_jump!(ret);
+        subsection!(ret, ui_size, "Color palette");
+        ret = ret.push(
+            Text::new("Click on a color to apply it to the selected strands")
+                .size(ui_size.main_text()),
+        );
+        let mut row = Row::new();
+        let mut nb_in_row = 0;
+        for (state, (name, color)) in self
+            .palette_squares
+            .iter_mut()
+            .zip(self.color_palette.colors.iter())
+        {
+            let color = Color::from_rgb8((color >> 16) as u8, (color >> 8) as u8, *color as u8);
+            row = row.push(
+                Column::new()
+                    .width(Length::FillPortion(1))
+                    .push(ColorSquare::new(
+                        color,
+                        state,
+                        Message::ColorPicked,
+                        Message::FinishChangingColor,
+                    ))
+                    .push(Text::new(name.as_str()).size(ui_size.main_text())),
+            );
+            nb_in_row += 1;
+            if nb_in_row == PALETTE_COLUMNS {
+                ret = ret.push(row);
+                row = Row::new();
+                nb_in_row = 0;
+            }
+        }
+        if nb_in_row > 0 {
+            row = row.push(iced::Space::with_width(Length::FillPortion(
+                (PALETTE_COLUMNS - nb_in_row) as u16,
+            )));
+            ret = ret.push(row);
+        }
+        let mut add_color_btn = text_btn(&mut self.add_palette_color_btn, "+", ui_size.clone());
+        if !self.palette_color_name.trim().is_empty() {
+            add_color_btn = add_color_btn.on_press(Message::AddPaletteColor);
+        }
+        ret = ret.push(
+            Row::new()
+                .spacing(5)
+                .push(
+                    TextInput::new(
+                        &mut self.palette_color_name_input,
+                        "Color name",
+                        &self.palette_color_name,
+                        Message::PaletteColorNameInput,
+                    )
+                    .size(ui_size.main_text())
+                    .on_submit(Message::AddPaletteColor),
+                )
+                .push(add_color_btn),
+        );
+        ret = ret.push(
+            Row::new()
+                .spacing(5)
+                .push(
+                    text_btn(&mut self.export_palette_btn, "Export", ui_size.clone())
+                        .on_press(Message::ExportColorPalette),
+                )
+                .push(
+                    text_btn(&mut self.import_palette_btn, "Import", ui_size.clone())
+                        .on_press(Message::ImportColorPalette),
+                ),
+        );
+
+        extra_jump!(ret);
+        subsection!(ret, ui_size, "Sound feedback");
+        ret = ret.push(right_checkbox(
+            self.sonification.enabled,
+            "Play design sound",
+            Message::SonificationEnabled,
+            ui_size.clone(),
+        ));
+        ret = ret.push(
+            Row::new().spacing(5).push(Text::new("Volume")).push(
+                Slider::new(
+                    &mut self.sonification_volume_slider,
+                    0f32..=1f32,
+                    self.sonification.volume,
+                    Message::SonificationVolume,
+                )
+                .step(0.05),
+            ),
+        );
+
+        extra_jump!(ret);
+        subsection!(ret, ui_size, "Rendering quality");
+        ret = ret.push(
+            Text::new("Quality of the depth of field and ambient occlusion effects")
+                .size(ui_size.main_text()),
+        );
+        ret = ret.push(PickList::new(
+            &mut self.post_processing_quality_picklist,
+            &ALL_POST_PROCESSING_QUALITY[..],
+            Some(self.post_processing_quality),
+            Message::PostProcessingQualityPicked,
+        ));
+
+        extra_jump!(ret);
+        subsection!(ret, ui_size, "Keyboard shortcuts");
+        ret = ret.push(
+            Text::new(
+                "Click on a shortcut and press a new key combination, or click again to cancel",
+            )
+            .size(ui_size.main_text()),
+        );
+        let mut table =
+            Scrollable::new(&mut self.key_bindings_scroll).max_height(KEY_BINDINGS_TABLE_HEIGHT);
+        for (state, (combination, action)) in self
+            .key_binding_buttons
+            .iter_mut()
+            .zip(self.key_bindings.iter())
+        {
+            let recording = self.recording_key_binding == Some((*action, *combination));
+            let binding_text = if recording {
+                "Press a key...".to_owned()
+            } else if let Some(combination) = combination {
+                combination_to_string(combination)
+            } else {
+                "Unbound".to_owned()
+            };
+            let row = Row::new()
+                .push(
+                    Text::new(action.to_string())
+                        .size(ui_size.main_text())
+                        .width(Length::FillPortion(1)),
+                )
+                .push(
+                    Text::new(binding_text)
+                        .size(ui_size.main_text())
+                        .width(Length::FillPortion(1)),
+                );
+            table = table.push(Button::new(state, row).width(Length::Fill).on_press(
+                if recording {
+                    Message::CancelKeyBindingRecording
+                } else {
+                    Message::RecordKeyBinding(*action, *combination)
+                },
+            ));
+        }
+        ret = ret.push(table);
+
+        if !self.plugin_actions.is_empty() {
+            extra_jump!(ret);
+            subsection!(ret, ui_size, "Plugins");
+            for (state, action) in self
+                .plugin_action_buttons
+                .iter_mut()
+                .zip(self.plugin_actions.iter())
+            {
+                let label = format!("{}: {}", action.plugin_name, action.name);
+                ret = ret.push(
+                    Button::new(state, Text::new(label).size(ui_size.main_text())).on_press(
+                        Message::PluginMenuAction(action.plugin_id, action.name.clone()),
+                    ),
+                );
+            }
+        }
+
+        extra_jump!(ret);
+        subsection!(ret, ui_size, "Connectivity graph");
+        ret = ret.push(
+            text_btn(
+                &mut self.export_graph_btn,
+                "Export Graph JSON",
+                ui_size.clone(),
+            )
+            .on_press(Message::ExportConnectivityGraph),
+        );
+
+        extra_jump!(ret);
+        subsection!(ret, ui_size, "Empty helices and strands");
+        ret = ret.push(
+            text_btn(&mut self.cleanup_orphans_btn, "Clean Up", ui_size.clone())
+                .on_press(Message::CleanupOrphans),
+        );
+
         extra_jump!(10, ret);
         section!(ret, ui_size, "DNA parameters");
         for line in app_state.get_dna_parameters().formated_string().lines() {
@@ -102,4 +419,36 @@ impl ParametersTab {
         self.scroll_sensitivity_factory
             .update_request(value_id, value, request);
     }
+
+    pub fn update_min_strand_length_request(
+        &mut self,
+        value_id: ValueId,
+        value: f32,
+        request: &mut Option<usize>,
+    ) {
+        self.min_strand_length_factory
+            .update_request(value_id, value, request);
+    }
+
+    pub fn update_edit_history_size_request(
+        &mut self,
+        value_id: ValueId,
+        value: f32,
+        request: &mut Option<usize>,
+    ) {
+        self.edit_history_size_factory
+            .update_request(value_id, value, request);
+    }
+
+    /// Display the minimum strand length read from the preferences of the user
+    pub fn set_min_strand_length(&mut self, length: usize) {
+        self.min_strand_length_factory
+            .update_value(ValueId(0), length as f32);
+    }
+
+    /// Display the history size read from the preferences of the user
+    pub fn set_edit_history_size(&mut self, size: usize) {
+        self.edit_history_size_factory
+            .update_value(ValueId(0), size as f32);
+    }
 }
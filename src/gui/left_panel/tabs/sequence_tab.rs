@@ -16,31 +16,99 @@ ENSnano, a 3d graphical application for DNA nanostructures.
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 use super::*;
+use ensnano_interactor::graphics::RulerParameters;
+
+/// The default number of pools in which the staples are partitioned for a step-wise annealing
+const DEFAULT_POOL_STEPS: usize = 3;
 
 pub struct SequenceTab {
     scroll: scrollable::State,
     button_scaffold: button::State,
     button_stapples: button::State,
+    button_staples_csv: button::State,
+    order_format: OrderFormat,
+    order_format_picklist: pick_list::State<OrderFormat>,
+    button_staple_pools: button::State,
+    pool_steps_str: String,
+    pool_steps_input: text_input::State,
     toggle_text_value: bool,
     scaffold_position_str: String,
     scaffold_position: usize,
     scaffold_input: text_input::State,
     button_selection_from_scaffold: button::State,
     button_selection_to_scaffold: button::State,
-    button_show_sequence: button::State,
+    button_secondary_structure: button::State,
+    pub show_ruler: bool,
+    ruler_parameters: RulerParameters,
+    ruler_inputs: [text_input::State; 4],
+    ruler_strs: [String; 4],
 }
 
-macro_rules! add_show_sequence_button {
-    ($ret: ident, $self: ident, $ui_size: ident) => {
-        let button_show_sequence = if $self.toggle_text_value {
-            text_btn(&mut $self.button_show_sequence, "Hide Sequences", $ui_size)
-                .on_press(Message::ToggleText(false))
-        } else {
-            text_btn(&mut $self.button_show_sequence, "Show Sequences", $ui_size)
-                .on_press(Message::ToggleText(true))
-        };
-        $ret = $ret.push(button_show_sequence);
-    };
+/// A parameter of the nucleotide ruler that can be typed in the sequence tab
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RulerField {
+    MinorPeriod,
+    MinorColor,
+    MajorPeriod,
+    MajorColor,
+}
+
+impl RulerField {
+    const ALL: [Self; 4] = [
+        Self::MinorPeriod,
+        Self::MinorColor,
+        Self::MajorPeriod,
+        Self::MajorColor,
+    ];
+
+    fn placeholder(&self) -> &'static str {
+        match self {
+            Self::MinorPeriod | Self::MajorPeriod => "Period",
+            Self::MinorColor | Self::MajorColor => "#AARRGGBB",
+        }
+    }
+
+    fn value_str(&self, parameters: &RulerParameters) -> String {
+        match self {
+            Self::MinorPeriod => parameters.minor_period.to_string(),
+            Self::MajorPeriod => parameters.major_period.to_string(),
+            Self::MinorColor => format!("#{:08X}", parameters.minor_color),
+            Self::MajorColor => format!("#{:08X}", parameters.major_color),
+        }
+    }
+
+    /// Set the parameter of `parameters` described by `value`. Return false if `value` is not
+    /// valid.
+    fn parse(&self, value: &str, parameters: &mut RulerParameters) -> bool {
+        match self {
+            Self::MinorPeriod | Self::MajorPeriod => {
+                if let Ok(period) = value.trim().parse::<usize>() {
+                    if *self == Self::MinorPeriod {
+                        parameters.minor_period = period;
+                    } else {
+                        parameters.major_period = period;
+                    }
+                    true
+                } else {
+                    false
+                }
+            }
+            Self::MinorColor | Self::MajorColor => {
+                let hex = value.trim().trim_start_matches('#');
+                match u32::from_str_radix(hex, 16) {
+                    Ok(color) if hex.len() == 8 => {
+                        if *self == Self::MinorColor {
+                            parameters.minor_color = color;
+                        } else {
+                            parameters.major_color = color;
+                        }
+                        true
+                    }
+                    _ => false,
+                }
+            }
+        }
+    }
 }
 
 macro_rules! add_scaffold_from_to_selection_buttons {
@@ -166,21 +234,92 @@ macro_rules! add_scaffold_start_position {
 
 macro_rules! add_download_staples_button {
     ($ret: ident, $self: ident, $ui_size: ident) => {
+        let order_format_picklist = PickList::new(
+            &mut $self.order_format_picklist,
+            &OrderFormat::ALL[..],
+            Some($self.order_format),
+            Message::OrderFormatPicked,
+        );
+        $ret = $ret.push(
+            Row::new()
+                .spacing(3)
+                .push(Text::new("Order format").size($ui_size.main_text()))
+                .push(order_format_picklist),
+        );
         let button_stapples = Button::new(
             &mut $self.button_stapples,
             iced::Text::new("Export Staples"),
         )
         .height(Length::Units($ui_size.button()))
-        .on_press(Message::StapplesRequested);
+        .on_press(Message::StapplesRequested($self.order_format));
         $ret = $ret.push(button_stapples);
+        let button_staples_csv = Button::new(
+            &mut $self.button_staples_csv,
+            iced::Text::new("Export Staples (csv)"),
+        )
+        .height(Length::Units($ui_size.button()))
+        .on_press(Message::StaplesCsvRequested($self.order_format));
+        $ret = $ret.push(button_staples_csv);
+    };
+}
+
+macro_rules! add_staple_pools_row {
+    ($ret: ident, $self: ident, $ui_size: ident) => {
+        let n_steps = $self
+            .pool_steps_str
+            .parse::<usize>()
+            .ok()
+            .filter(|n| *n > 0);
+        let mut button_staple_pools =
+            text_btn(&mut $self.button_staple_pools, "Pool Export", $ui_size);
+        if let Some(n) = n_steps {
+            button_staple_pools = button_staple_pools.on_press(Message::StaplePoolsRequested(n));
+        }
+        let pool_row = Row::new()
+            .push(Text::new("Annealing steps").width(Length::FillPortion(2)))
+            .push(
+                TextInput::new(
+                    &mut $self.pool_steps_input,
+                    "Steps",
+                    &$self.pool_steps_str,
+                    Message::StaplePoolStepsInput,
+                )
+                .style(BadValue(n_steps.is_some()))
+                .width(iced::Length::FillPortion(1)),
+            );
+        $ret = $ret.push(pool_row);
+        $ret = $ret.push(button_staple_pools);
+    };
+}
+
+macro_rules! add_secondary_structure_button {
+    ($ret: ident, $self: ident, $ui_size: ident, $app_state: ident) => {
+        let mut button_secondary_structure = text_btn(
+            &mut $self.button_secondary_structure,
+            "Predict with RNAfold",
+            $ui_size,
+        );
+        let selection = $app_state.get_selection_as_dnaelement();
+        if let Some(s_id) = Self::get_candidate_scaffold(&selection) {
+            button_secondary_structure =
+                button_secondary_structure.on_press(Message::PredictSecondaryStructure(s_id));
+        }
+        $ret = $ret.push(button_secondary_structure);
+        $ret = $ret.push(Text::new("Select one strand").size($ui_size.main_text()));
     };
 }
 
 impl SequenceTab {
     pub fn new() -> Self {
-        Self {
+        let mut ret = Self {
             scroll: Default::default(),
             button_stapples: Default::default(),
+            button_staples_csv: Default::default(),
+            order_format: Default::default(),
+            order_format_picklist: Default::default(),
+            button_staple_pools: Default::default(),
+            pool_steps_str: DEFAULT_POOL_STEPS.to_string(),
+            pool_steps_input: Default::default(),
             button_scaffold: Default::default(),
             toggle_text_value: false,
             scaffold_position_str: "0".to_string(),
@@ -188,8 +327,16 @@ impl SequenceTab {
             scaffold_input: Default::default(),
             button_selection_from_scaffold: Default::default(),
             button_selection_to_scaffold: Default::default(),
-            button_show_sequence: Default::default(),
+            button_secondary_structure: Default::default(),
+            show_ruler: false,
+            ruler_parameters: Default::default(),
+            ruler_inputs: Default::default(),
+            ruler_strs: Default::default(),
+        };
+        for (field, value_str) in RulerField::ALL.iter().zip(ret.ruler_strs.iter_mut()) {
+            *value_str = field.value_str(&ret.ruler_parameters);
         }
+        ret
     }
 
     pub fn view<'a, S: AppState>(
@@ -206,7 +353,39 @@ impl SequenceTab {
         let mut ret = Column::new();
         section!(ret, ui_size, "Sequence");
         extra_jump!(ret);
-        add_show_sequence_button!(ret, self, ui_size);
+        ret = ret.push(right_checkbox(
+            self.toggle_text_value,
+            "Show Sequence",
+            Message::ToggleText,
+            ui_size.clone(),
+        ));
+        extra_jump!(ret);
+        ret = ret.push(right_checkbox(
+            self.show_ruler,
+            "Nucleotide ruler (2D)",
+            Message::ShowRuler,
+            ui_size.clone(),
+        ));
+        // One row for the period and color of the minor bars, and one for the major bars
+        let mut ruler_inputs = RulerField::ALL
+            .iter()
+            .zip(self.ruler_inputs.iter_mut())
+            .zip(self.ruler_strs.iter());
+        for name in ["Minor bars", "Major bars"].iter() {
+            let mut row = Row::new().spacing(5).push(Text::new(*name));
+            for ((field, input), value_str) in ruler_inputs.by_ref().take(2) {
+                let field = *field;
+                let mut parameters = self.ruler_parameters;
+                let valid = field.parse(value_str, &mut parameters);
+                row = row.push(
+                    TextInput::new(input, field.placeholder(), value_str, move |s| {
+                        Message::RulerInput(field, s)
+                    })
+                    .style(BadValue(valid)),
+                );
+            }
+            ret = ret.push(row);
+        }
         extra_jump!(ret);
         section!(ret, ui_size, "Scaffold");
         extra_jump!(ret);
@@ -224,9 +403,23 @@ impl SequenceTab {
         section!(ret, ui_size, "Staples");
         extra_jump!(ret);
         add_download_staples_button!(ret, self, ui_size);
+        extra_jump!(ret);
+        add_staple_pools_row!(ret, self, ui_size);
+        extra_jump!(ret);
+        section!(ret, ui_size, "Secondary structure");
+        extra_jump!(ret);
+        add_secondary_structure_button!(ret, self, ui_size, app_state);
         Scrollable::new(&mut self.scroll).push(ret).into()
     }
 
+    pub fn update_pool_steps_str(&mut self, steps_str: String) {
+        self.pool_steps_str = steps_str;
+    }
+
+    pub fn set_order_format(&mut self, order_format: OrderFormat) {
+        self.order_format = order_format;
+    }
+
     pub fn toggle_text_value(&mut self, b: bool) {
         self.toggle_text_value = b;
     }
@@ -241,8 +434,26 @@ impl SequenceTab {
         }
     }
 
+    /// Update the input of a parameter of the nucleotide ruler. Return the new parameters of the
+    /// ruler if the input is valid.
+    pub fn update_ruler_input(
+        &mut self,
+        field: RulerField,
+        value: String,
+    ) -> Option<RulerParameters> {
+        let valid = field.parse(&value, &mut self.ruler_parameters);
+        if let Some(idx) = RulerField::ALL.iter().position(|f| *f == field) {
+            self.ruler_strs[idx] = value;
+        }
+        if valid {
+            Some(self.ruler_parameters)
+        } else {
+            None
+        }
+    }
+
     pub fn has_keyboard_priority(&self) -> bool {
-        self.scaffold_input.is_focused()
+        self.scaffold_input.is_focused() || self.ruler_inputs.iter().any(|i| i.is_focused())
     }
 
     fn get_candidate_scaffold(selection: &[DnaElementKey]) -> Option<usize> {
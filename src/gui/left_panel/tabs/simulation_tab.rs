@@ -39,6 +39,7 @@ impl<S: AppState> SimulationTab<S> {
                 FactoryId::RigidBody,
                 RigidBodyFactory {
                     volume_exclusion: false,
+                    freeze_ssdna: false,
                     brownian_motion: false,
                     brownian_parameters: init_brownian.clone(),
                 },
@@ -83,6 +84,7 @@ impl<S: AppState> SimulationTab<S> {
             ));
 
         let volume_exclusion = self.rigid_body_factory.requestable.volume_exclusion;
+        let freeze_ssdna = self.rigid_body_factory.requestable.freeze_ssdna;
         let brownian_motion = self.rigid_body_factory.requestable.brownian_motion;
         subsection!(ret, ui_size, "Parameters for helices simulation");
         for view in self
@@ -98,6 +100,12 @@ impl<S: AppState> SimulationTab<S> {
             Message::VolumeExclusion,
             ui_size,
         ));
+        ret = ret.push(right_checkbox(
+            freeze_ssdna,
+            "Freeze Strands",
+            Message::FreezeSsdna,
+            ui_size,
+        ));
         ret = ret.push(right_checkbox(
             brownian_motion,
             "Unmatched nt jiggling",
@@ -140,6 +148,10 @@ impl<S: AppState> SimulationTab<S> {
         self.rigid_body_factory.requestable.volume_exclusion = volume_exclusion;
     }
 
+    pub fn set_freeze_ssdna(&mut self, freeze_ssdna: bool) {
+        self.rigid_body_factory.requestable.freeze_ssdna = freeze_ssdna;
+    }
+
     pub fn set_brownian_motion(&mut self, brownian_motion: bool) {
         self.rigid_body_factory.requestable.brownian_motion = brownian_motion;
     }
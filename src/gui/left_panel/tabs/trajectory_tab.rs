@@ -0,0 +1,109 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::*;
+
+pub struct TrajectoryTab {
+    scroll: scrollable::State,
+    load_btn: button::State,
+    relax_btn: button::State,
+    play_btn: button::State,
+    close_btn: button::State,
+    frame_slider: slider::State,
+}
+
+impl TrajectoryTab {
+    pub fn new() -> Self {
+        Self {
+            scroll: Default::default(),
+            load_btn: Default::default(),
+            relax_btn: Default::default(),
+            play_btn: Default::default(),
+            close_btn: Default::default(),
+            frame_slider: Default::default(),
+        }
+    }
+
+    pub fn view<'a, S: AppState>(
+        &'a mut self,
+        ui_size: UiSize,
+        app_state: &'a S,
+    ) -> Element<'a, Message<S>> {
+        let mut ret = Column::new().spacing(5);
+        section!(ret, ui_size, "Trajectory");
+        ret = ret.push(
+            Text::new(
+                "Load the topology and a configuration or trajectory file of an oxDNA export of \
+                 the current design",
+            )
+            .size(ui_size.main_text()),
+        );
+        ret = ret.push(
+            text_btn(&mut self.load_btn, "Load oxDNA files", ui_size.clone())
+                .on_press(Message::LoadTrajectory),
+        );
+        extra_jump!(ret);
+
+        subsection!(ret, ui_size, "Relaxation");
+        ret = ret.push(
+            Text::new(
+                "Relax the design with oxDNA and move its helices to the relaxed conformation. \
+                 The oxDNA executable must be in the PATH, or given by the ENSNANO_OXDNA \
+                 environment variable.",
+            )
+            .size(ui_size.main_text()),
+        );
+        ret = ret.push(
+            text_btn(&mut self.relax_btn, "Relax with oxDNA", ui_size.clone())
+                .on_press(Message::RelaxDesign),
+        );
+        extra_jump!(ret);
+
+        subsection!(ret, ui_size, "Playback");
+        if let Some(trajectory) = app_state.get_trajectory() {
+            let last_frame = trajectory.nb_frames().saturating_sub(1) as u32;
+            ret = ret.push(Text::new(format!(
+                "Frame {} / {}",
+                trajectory.current_frame + 1,
+                trajectory.nb_frames()
+            )));
+            ret = ret.push(Slider::new(
+                &mut self.frame_slider,
+                0..=last_frame,
+                trajectory.current_frame as u32,
+                Message::TrajectoryFrame,
+            ));
+            let play_text = if trajectory.playing { "Pause" } else { "Play" };
+            ret = ret.push(
+                Row::new()
+                    .spacing(5)
+                    .push(
+                        text_btn(&mut self.play_btn, play_text, ui_size.clone())
+                            .on_press(Message::PlayTrajectory(!trajectory.playing)),
+                    )
+                    .push(
+                        text_btn(&mut self.close_btn, "Close", ui_size.clone())
+                            .on_press(Message::CloseTrajectory),
+                    ),
+            );
+        } else {
+            ret = ret.push(Text::new("No trajectory loaded").color(innactive_color()));
+        }
+        Scrollable::new(&mut self.scroll).push(ret).into()
+    }
+}
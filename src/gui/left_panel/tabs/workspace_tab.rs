@@ -0,0 +1,220 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::*;
+
+#[derive(Default)]
+struct DesignButtons {
+    select: button::State,
+    merge: button::State,
+    close: button::State,
+}
+
+pub struct WorkspaceTab {
+    scroll: scrollable::State,
+    open_btn: button::State,
+    design_buttons: Vec<DesignButtons>,
+    /// The design of the workspace whose position is edited, with the sliders that edit it
+    selected_design: Option<(usize, RequestFactory<WorkspaceTransformFactory>)>,
+    /// The helix of the selected design that is docked on the selected helix of the current
+    /// design
+    docked_helix: Option<usize>,
+    docked_helix_picklist: pick_list::State<usize>,
+    dock_btn: button::State,
+    /// The position of the selected design before it was docked. The sliders are reset once the
+    /// design has moved.
+    pending_dock: Option<WorkspaceTransform>,
+}
+
+impl WorkspaceTab {
+    pub fn new() -> Self {
+        Self {
+            scroll: Default::default(),
+            open_btn: Default::default(),
+            design_buttons: Vec::new(),
+            selected_design: None,
+            docked_helix: None,
+            docked_helix_picklist: Default::default(),
+            dock_btn: Default::default(),
+            pending_dock: None,
+        }
+    }
+
+    pub fn view<'a, S: AppState>(
+        &'a mut self,
+        ui_size: UiSize,
+        app_state: &'a S,
+    ) -> Element<'a, Message<S>> {
+        let designs = app_state.get_workspace_designs();
+        let mut ret = Column::new().spacing(5);
+        section!(ret, ui_size, "Workspace");
+        ret = ret.push(
+            Text::new(
+                "Designs of the workspace are displayed next to the current design. They cannot \
+                 be edited, but they can be merged into the current design.",
+            )
+            .size(ui_size.main_text()),
+        );
+        ret = ret.push(
+            text_btn(&mut self.open_btn, "Open design", ui_size.clone())
+                .on_press(Message::OpenDesignInWorkspace),
+        );
+        extra_jump!(ret);
+
+        subsection!(ret, ui_size, "Designs");
+        ret = ret.push(right_checkbox(
+            app_state.is_main_design_visible(),
+            "Current design",
+            Message::MainDesignVisibility,
+            ui_size.clone(),
+        ));
+        self.design_buttons
+            .resize_with(designs.len(), Default::default);
+        for (design_id, (design, buttons)) in designs
+            .iter()
+            .zip(self.design_buttons.iter_mut())
+            .enumerate()
+        {
+            ret = ret.push(right_checkbox(
+                design.visible,
+                design.name.clone(),
+                move |visible| Message::WorkspaceDesignVisibility(design_id, visible),
+                ui_size.clone(),
+            ));
+            ret = ret.push(
+                Row::new()
+                    .spacing(5)
+                    .push(
+                        text_btn(&mut buttons.select, "Move", ui_size.clone())
+                            .on_press(Message::SelectWorkspaceDesign(design_id)),
+                    )
+                    .push(
+                        text_btn(&mut buttons.merge, "Merge", ui_size.clone())
+                            .on_press(Message::MergeWorkspaceDesign(design_id)),
+                    )
+                    .push(
+                        text_btn(&mut buttons.close, "Close", ui_size.clone())
+                            .on_press(Message::CloseWorkspaceDesign(design_id)),
+                    ),
+            );
+        }
+        extra_jump!(ret);
+
+        if let Some(design_id) = self.selected_design.as_ref().map(|(id, _)| *id) {
+            if let Some(design) = designs.get(design_id) {
+                if let Some(before_dock) = self.pending_dock {
+                    if before_dock != design.transform {
+                        self.pending_dock = None;
+                        let docked_helix = self.docked_helix;
+                        self.select_design(design_id, design.transform);
+                        self.docked_helix = docked_helix;
+                    }
+                }
+            }
+        }
+
+        if let Some((design_id, factory)) = self.selected_design.as_mut() {
+            if let Some(design) = designs.get(*design_id) {
+                subsection!(ret, ui_size, "Position");
+                ret = ret.push(Text::new(design.name.clone()).size(ui_size.main_text()));
+                for view in factory.view(true, ui_size.main_text()).into_iter() {
+                    ret = ret.push(view);
+                }
+                extra_jump!(ret);
+
+                subsection!(ret, ui_size, "Docking");
+                ret = ret.push(
+                    Text::new(
+                        "Place a helix of this design next to the selected helix of the current \
+                         design, parallel to it.",
+                    )
+                    .size(ui_size.main_text()),
+                );
+                ret = ret.push(
+                    Row::new()
+                        .spacing(5)
+                        .push(Text::new("Helix").size(ui_size.main_text()))
+                        .push(PickList::new(
+                            &mut self.docked_helix_picklist,
+                            design.helices.clone(),
+                            self.docked_helix,
+                            Message::DockedHelixPicked,
+                        )),
+                );
+                let mut dock_btn = text_btn(
+                    &mut self.dock_btn,
+                    "Dock on selected helix",
+                    ui_size.clone(),
+                );
+                if let Some(helix) = self.docked_helix {
+                    dock_btn = dock_btn.on_press(Message::DockWorkspaceDesign {
+                        design_id: *design_id,
+                        helix,
+                    });
+                }
+                ret = ret.push(dock_btn);
+            }
+        }
+        Scrollable::new(&mut self.scroll).push(ret).into()
+    }
+
+    /// Edit the position of the design `design_id`, whose current position is `transform`
+    pub fn select_design(&mut self, design_id: usize, transform: WorkspaceTransform) {
+        self.selected_design = Some((
+            design_id,
+            RequestFactory::new(
+                FactoryId::WorkspaceTransform,
+                WorkspaceTransformFactory { transform },
+            ),
+        ));
+        self.docked_helix = None;
+        self.pending_dock = None;
+    }
+
+    pub fn set_docked_helix(&mut self, helix: usize) {
+        self.docked_helix = Some(helix);
+    }
+
+    /// Reset the sliders once the design `design_id`, whose current position is `transform`, has
+    /// been docked
+    pub fn docking_requested(&mut self, design_id: usize, transform: WorkspaceTransform) {
+        if self.selected_design.as_ref().map(|(id, _)| *id) == Some(design_id) {
+            self.pending_dock = Some(transform);
+        }
+    }
+
+    /// Update the identifier of the selected design after the design `design_id` was removed
+    /// from the workspace
+    pub fn design_removed(&mut self, design_id: usize) {
+        match self.selected_design.as_mut() {
+            Some((selected, _)) if *selected == design_id => self.selected_design = None,
+            Some((selected, _)) if *selected > design_id => *selected -= 1,
+            _ => (),
+        }
+    }
+
+    pub fn update_transform_request(
+        &mut self,
+        value_id: ValueId,
+        value: f32,
+    ) -> Option<(usize, WorkspaceTransform)> {
+        self.selected_design
+            .as_mut()
+            .map(|(design_id, factory)| (*design_id, factory.update_value(value_id, value)))
+    }
+}
@@ -29,26 +29,39 @@ pub use top_bar::TopBar;
 /// Draw the left pannel of the GUI
 pub mod left_panel;
 pub use left_panel::{ColorOverlay, LeftPanel, RigidBodyParametersRequest};
+mod context_menu;
 pub mod status_bar;
+use context_menu::ContextMenu;
+pub use context_menu::ContextMenuEntry;
 mod ui_size;
 pub use ui_size::*;
 mod material_icons_light;
 pub use ensnano_design::{Camera, CameraId};
-pub use status_bar::{CurentOpState, StrandBuildingStatus};
+pub use status_bar::{CurentOpState, JobProgress, StrandBuildingStatus};
 
 mod icon;
 
 use status_bar::StatusBar;
 
+use crate::color_palette::ColorPalette;
+use crate::key_bindings::{HotkeyAction, KeyBindings, KeyCombination};
+use crate::plugins::PluginMenuAction;
 use crate::scene::FogParameters;
 use ensnano_design::{
     elements::{DnaAttribute, DnaElement, DnaElementKey},
     grid::GridTypeDescr,
-    Nucl, Parameters,
+    Nucl, Parameters, StrandModification, TorusKnot,
 };
 use ensnano_interactor::{
-    graphics::{Background3D, DrawArea, ElementType, RenderingMode, SplitMode},
-    Selection, SimulationState, SuggestionParameters, WidgetBasis,
+    graphics::{
+        AmbientOcclusionParameters, AnalysisColoring, AnimationParameters, Background3D,
+        ColorTheme, DepthOfFieldParameters, DrawArea, ElementType, EyeDomeLightingParameters,
+        FlyThroughParameters, LightingParameters, PostProcessingQuality, RenderingMode,
+        RulerParameters, SonificationMode, SplitMode, ToneMappingParameters,
+    },
+    Measurement, MirrorAxis, OrderFormat, Selection, SelectionGroupCombination, SimulationState,
+    StrandBuildingParameters, StrandEnd, SuggestionParameters, Trajectory, WidgetBasis,
+    WorkspaceDesignInfo, WorkspaceTransform,
 };
 use ensnano_interactor::{operation::Operation, ScaffoldInfo};
 use ensnano_interactor::{ActionMode, HyperboloidRequest, RollRequest, SelectionMode};
@@ -74,14 +87,39 @@ pub trait Requests: 'static + Send {
     fn change_strand_color(&mut self, color: u32);
     /// Change the background of the 3D scene
     fn change_3d_background(&mut self, bg: Background3D);
+    /// Save the colors of the 3D view chosen by the user and apply them
+    fn set_color_theme(&mut self, theme: ColorTheme);
     /// Change the rendering mode
     fn change_3d_rendering_mode(&mut self, rendering_mode: RenderingMode);
+    /// Change the parameters of the depth of field effect of the 3D scene
+    fn set_depth_of_field(&mut self, parameters: DepthOfFieldParameters);
+    /// Enter, leave or change the speed of the first-person mode of the 3D camera
+    fn set_fly_through(&mut self, parameters: FlyThroughParameters);
+    /// Change the parameters of the eye-dome lighting of the 3D scene
+    fn set_eye_dome_lighting(&mut self, parameters: EyeDomeLightingParameters);
+    /// Change the parameters of the ambient occlusion of the 3D scene
+    fn set_ambient_occlusion(&mut self, parameters: AmbientOcclusionParameters);
+    /// Change the number of samples read by the post-processing passes of the 3D scene
+    fn set_post_processing_quality(&mut self, quality: PostProcessingQuality);
+    /// Change the light sources of the 3D scene
+    fn set_lighting(&mut self, parameters: LightingParameters);
+    /// Change the tone mapping of the 3D scene
+    fn set_tone_mapping(&mut self, parameters: ToneMappingParameters);
+    /// Change the audio feedback of the 3D scene
+    fn set_sonification(&mut self, mode: SonificationMode);
     /// Set the selected strand as the scaffold
     fn set_scaffold_from_selection(&mut self);
     /// Cancel the current hyperboloid construction
     fn cancel_hyperboloid(&mut self);
     /// Change the scrolling direction
     fn invert_scroll(&mut self, invert: bool);
+    /// Bind `action` to the next key combination pressed by the user, replacing the binding
+    /// `previous` if it is `Some`
+    fn record_key_binding(&mut self, action: HotkeyAction, previous: Option<KeyCombination>);
+    /// Stop waiting for the key combination of a new binding
+    fn cancel_key_binding_recording(&mut self);
+    /// Trigger the action `name` of the plugin `plugin_id`
+    fn trigger_plugin_action(&mut self, plugin_id: usize, name: String);
     /// Resize all the 2D helices, or only the selected ones
     fn resize_2d_helices(&mut self, all: bool);
     /// Make all elements of the design visible
@@ -92,12 +130,60 @@ pub trait Requests: 'static + Send {
     fn remove_empty_domains(&mut self);
     fn change_action_mode(&mut self, action_mode: ActionMode);
     fn change_selection_mode(&mut self, selection_mode: SelectionMode);
+    /// Prevent the selection from being cleared when clicking on empty space. The lock is
+    /// released when the selection mode changes
+    fn set_selection_lock(&mut self, locked: bool);
     /// Switch widget basis between world and object
     fn toggle_widget_basis(&mut self);
     /// Show/hide the DNA sequences
     fn set_dna_sequences_visibility(&mut self, visible: bool);
     /// Download the stapples as an xlsx file
-    fn download_stapples(&mut self);
+    fn download_stapples(&mut self, order_format: OrderFormat);
+    /// Download the stapples as a csv file
+    fn download_staples_csv(&mut self, order_format: OrderFormat);
+    /// Download the stapples as an xlsx file, partitioned in `n_steps` pools of decreasing
+    /// melting temperature
+    fn download_staple_pools(&mut self, n_steps: usize);
+    /// Predict the secondary structure of a strand with RNAfold
+    fn predict_secondary_structure(&mut self, s_id: usize);
+    /// Highlight a shortest path between the two selected nucleotides
+    fn highlight_shortest_path(&mut self);
+    /// Clear the list of distances measured in the 3D view
+    fn clear_measurements(&mut self);
+    /// Read an oxDNA trajectory of the current design to display its frames in the 3D view
+    fn load_oxdna_trajectory(&mut self);
+    /// Start or stop the automatic playback of the loaded trajectory
+    fn play_trajectory(&mut self, playing: bool);
+    /// Display a frame of the loaded trajectory
+    fn set_trajectory_frame(&mut self, frame: usize);
+    /// Stop displaying the loaded trajectory
+    fn close_trajectory(&mut self);
+    /// Read a design file and display it next to the current design
+    fn open_design_in_workspace(&mut self);
+    /// Show or hide the design being edited in the 3D view
+    fn set_main_design_visibility(&mut self, visible: bool);
+    fn set_workspace_design_visibility(&mut self, design_id: usize, visible: bool);
+    /// Move a design of the workspace in the 3D view
+    fn set_workspace_design_transform(&mut self, design_id: usize, transform: WorkspaceTransform);
+    fn close_workspace_design(&mut self, design_id: usize);
+    /// Add the content of a design of the workspace to the current design
+    fn merge_workspace_design(&mut self, design_id: usize);
+    /// Move a design of the workspace so that its helix `helix` lies next to the selected helix
+    /// of the current design
+    fn dock_workspace_design(&mut self, design_id: usize, helix: usize);
+    /// Relax the design with oxDNA and move its helices to the relaxed conformation
+    fn relax_design(&mut self);
+    /// Stop a running background job
+    fn cancel_job(&mut self, job_id: usize);
+    /// Save the current selection in the selection group `name`, replacing the previous content
+    /// of the group if it exists
+    fn save_selection_group(&mut self, name: String);
+    /// Combine the selection group `name` with the current selection
+    fn recall_selection_group(&mut self, name: String, combination: SelectionGroupCombination);
+    fn delete_selection_group(&mut self, name: String);
+    /// Center the 2D view on the cross-over between the `a.1`-th nucleotide of strand `a.0` and
+    /// the `b.1`-th nucleotide of strand `b.0`
+    fn go_to_xover(&mut self, a: (usize, usize), b: (usize, usize));
     fn set_selected_strand_sequence(&mut self, sequence: String);
     fn set_scaffold_sequence(&mut self, shift: usize);
     fn set_scaffold_shift(&mut self, shift: usize);
@@ -121,8 +207,24 @@ pub trait Requests: 'static + Send {
     fn update_roll_of_selected_helices(&mut self, roll: f32);
     fn update_scroll_sensitivity(&mut self, sensitivity: f32);
     fn set_fog_parameters(&mut self, parameters: FogParameters);
+    /// Center the fog of the 3D view on the selected elements
+    fn center_fog_on_selection(&mut self);
+    /// Make the fog of the 3D view follow the pivot again after it was centered on the selection
+    fn reset_fog_center(&mut self);
     /// Show/hide the torsion indications
     fn set_torsion_visibility(&mut self, visible: bool);
+    /// Show/hide the coloring of the nucleotides according to the local twist of their helix
+    fn set_local_twist_visibility(&mut self, visible: bool);
+    /// Color the nucleotides of the 3D view according to the result of an analysis of the design
+    fn set_analysis_coloring(&mut self, coloring: AnalysisColoring);
+    /// Show/hide the identifier of the helices in the 3D view
+    fn set_helix_numbers_visibility(&mut self, visible: bool);
+    /// Show/hide the nucleotide ruler of the 2D view
+    fn set_2d_ruler_visibility(&mut self, visible: bool);
+    /// Set the periods and colors of the nucleotide ruler of the 2D view
+    fn set_2d_ruler_parameters(&mut self, parameters: RulerParameters);
+    /// Show/hide the lattice grid of the 2D view
+    fn set_2d_lattice_grid_visibility(&mut self, visible: bool);
     /// Set the direction and up vector of the 3D camera
     fn set_camera_dir_up_vec(&mut self, direction: Vec3, up: Vec3);
     fn perform_camera_rotation(&mut self, xz: f32, yz: f32, xy: f32);
@@ -144,6 +246,21 @@ pub trait Requests: 'static + Send {
     );
     fn change_split_mode(&mut self, split_mode: SplitMode);
     fn export_to_oxdna(&mut self);
+    /// Write a coarse atomic model of the design in the PDB format
+    fn export_to_pdb(&mut self);
+    /// Write the design in the cadnano v2 format
+    fn export_to_cadnano(&mut self);
+    /// Write the 3D representation of the design as a triangle mesh
+    fn export_mesh(&mut self);
+    /// Write the graph whose nodes are the helices and whose edges are the cross-overs, in json
+    /// or in the DOT format
+    fn export_connectivity_graph(&mut self);
+    /// Write a PNG image of the 2D view, measuring `size_mm` millimeters at `dpi` dots per inch
+    fn export_helix_map_png(&mut self, dpi: u32, size_mm: (f32, f32));
+    /// Write a PNG image of `size` pixels of the 3D view
+    fn export_scene_png(&mut self, size: (u32, u32));
+    /// Write the frames of an animation of the 3D view as numbered PNG images
+    fn record_animation(&mut self, parameters: AnimationParameters);
     /// Split/Unsplit the 2D view
     fn toggle_2d_view_split(&mut self);
     fn undo(&mut self);
@@ -175,16 +292,88 @@ pub trait Requests: 'static + Send {
     fn reload_file(&mut self);
     fn add_double_strand_on_new_helix(&mut self, parameters: Option<(isize, usize)>);
     fn set_strand_name(&mut self, s_id: usize, name: String);
+    /// Attach a label to a nucleotide. An empty label removes the annotation.
+    fn annotate_nucleotide(&mut self, nucl: Nucl, label: String);
+    /// Set the chemical modification attached to one end of a strand
+    fn set_strand_modification(
+        &mut self,
+        s_id: usize,
+        end: StrandEnd,
+        modification: Option<StrandModification>,
+    );
     fn create_new_camera(&mut self);
+    /// Write the current point of view of the 3D scene in a json file
+    fn export_camera_view(&mut self);
+    /// Move the camera of the 3D scene to a point of view read from a json file
+    fn import_camera_view(&mut self);
+    /// Write the selected helices, with the strands that lie on them, in a design file
+    fn export_selection_as_module(&mut self);
+    /// Add the content of a design file to the current design
+    fn import_module(&mut self);
+    /// Add a named color to the palette of the user
+    fn add_palette_color(&mut self, name: String, color: u32);
+    /// Set the number of undoable states embedded in the saved files
+    fn set_edit_history_size(&mut self, size: usize);
+    /// Start or stop logging the design operations
+    fn set_session_log_enabled(&mut self, enabled: bool);
+    /// Write the palette of named colors in a json file
+    fn export_color_palette(&mut self);
+    /// Replace the palette of named colors by the one read from a json file
+    fn import_color_palette(&mut self);
     fn delete_camera(&mut self, cam_id: CameraId);
     fn select_camera(&mut self, cam_id: CameraId);
     fn set_favourite_camera(&mut self, cam_id: CameraId);
     fn update_camera(&mut self, cam_id: CameraId);
     fn set_camera_name(&mut self, cam_id: CameraId, name: String);
     fn set_suggestion_parameters(&mut self, param: SuggestionParameters);
+    fn set_strand_building_parameters(&mut self, param: StrandBuildingParameters);
     fn set_grid_position(&mut self, grid_id: usize, position: Vec3);
     fn set_grid_orientation(&mut self, grid_id: usize, orientation: Rotor3);
+    /// Add a copy of a grid and its helices, translated by `offset`
+    fn duplicate_grid(&mut self, grid_id: usize, offset: Vec3);
+    /// Add a new grid containing the mirror image of a grid and its helices across `axis`
+    fn mirror_grid(&mut self, grid_id: usize, axis: MirrorAxis);
+    /// Set the roll, in radians, given to the helices that are added to a grid, without creating
+    /// an undoable step
+    fn preview_grid_twist_offset(&mut self, grid_id: usize, twist_offset: f32);
+    /// Set the roll, in radians, given to the helices that are added to a grid. Undoing this
+    /// operation restores the roll `initial`.
+    fn set_grid_twist_offset(&mut self, grid_id: usize, initial: f32, twist_offset: f32);
+    /// Show the plane across which a grid would be mirrored, or hide it if `axis` is `None`
+    fn show_mirror_plane(&mut self, axis: Option<MirrorAxis>);
+    /// Renumber the strands of the design in increasing order of `key`
+    fn reorder_strands(&mut self, key: ensnano_interactor::SortKey);
+    /// Color the staples with the colors of a color-blind-safe palette
+    fn recolor_strands(&mut self, palette: ensnano_interactor::ColorBlindPalette);
+    /// Insert `length` unpaired nucleotides in a strand, right after `at`
+    fn insert_loop(&mut self, at: Nucl, length: usize);
+    /// Extend the strand whose 3' end is `at` with a hairpin
+    fn create_hairpin(&mut self, at: Nucl, stem_length: usize, loop_length: usize);
+    /// Turn the cyclic scaffold into a linear strand whose 5' end is `at`
+    fn break_scaffold_at(&mut self, at: Nucl);
+    /// Remove the strands that have no nucleotide and the helices on which no nucleotide lies
+    fn cleanup_orphans(&mut self);
+    /// Join two strand ends with a phosphodiester bond
+    fn connect_strands(&mut self, end_a: (usize, StrandEnd), end_b: (usize, StrandEnd));
+    fn close_context_menu(&mut self);
+    /// Look for the roll of the helices that minimises the strain at the cross-overs. If
+    /// `target_helices` is `Some`, only these helices are rolled.
+    fn optimize_rolls(&mut self, target_helices: Option<Vec<usize>>);
+    /// Add a torus knot, and a cyclic strand following it, to the design
+    fn add_torus_knot(&mut self, knot: TorusKnot);
+    /// Reverse the 5' -> 3' direction of a strand
+    fn flip_strand(&mut self, s_id: usize);
     fn flip_split_views(&mut self);
+    /// Let the user draw a cross-over in the 2D view by clicking on two nucleotides
+    fn start_2d_xover_tool(&mut self);
+    /// Start recording the design operations in a macro
+    fn start_macro_recording(&mut self);
+    /// Stop recording the current macro and ask the user where to save it
+    fn stop_macro_recording(&mut self);
+    /// Ask the user for a macro file and apply its operations
+    fn play_macro(&mut self);
+    /// Ask the user for a session log and apply its operations
+    fn replay_session(&mut self);
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -196,6 +385,7 @@ enum GuiState<R: Requests, S: AppState> {
     TopBar(iced_winit::program::State<TopBar<R, S>>),
     LeftPanel(iced_winit::program::State<LeftPanel<R, S>>),
     StatusBar(iced_winit::program::State<StatusBar<R, S>>),
+    ContextMenu(iced_winit::program::State<ContextMenu<R>>),
 }
 
 impl<R: Requests, S: AppState> GuiState<R, S> {
@@ -211,12 +401,14 @@ impl<R: Requests, S: AppState> GuiState<R, S> {
                 }
                 GuiState::TopBar(_) => (),
                 GuiState::LeftPanel(_) => (),
+                GuiState::ContextMenu(_) => (),
             }
         } else {
             match self {
                 GuiState::TopBar(state) => state.queue_event(event),
                 GuiState::LeftPanel(state) => state.queue_event(event),
                 GuiState::StatusBar(state) => state.queue_event(event),
+                GuiState::ContextMenu(state) => state.queue_event(event),
             }
         }
     }
@@ -259,7 +451,7 @@ impl<R: Requests, S: AppState> GuiState<R, S> {
                     area.position.to_logical(window.scale_factor()),
                 ))
             }
-            GuiState::StatusBar(_) => {}
+            GuiState::StatusBar(_) | GuiState::ContextMenu(_) => {}
         }
     }
 
@@ -268,6 +460,7 @@ impl<R: Requests, S: AppState> GuiState<R, S> {
             GuiState::TopBar(state) => state.is_queue_empty(),
             GuiState::LeftPanel(state) => state.is_queue_empty(),
             GuiState::StatusBar(state) => state.is_queue_empty(),
+            GuiState::ContextMenu(state) => state.is_queue_empty(),
         }
     }
 
@@ -289,6 +482,9 @@ impl<R: Requests, S: AppState> GuiState<R, S> {
             GuiState::StatusBar(state) => {
                 state.update(size, cursor_position, renderer, &mut clipboard, debug);
             }
+            GuiState::ContextMenu(state) => {
+                state.update(size, cursor_position, renderer, &mut clipboard, debug);
+            }
         }
     }
 
@@ -343,14 +539,29 @@ impl<R: Requests, S: AppState> GuiState<R, S> {
                     *mouse_interaction = icon;
                 }
             }
+            GuiState::ContextMenu(ref state) => {
+                let icon = renderer.backend_mut().draw(
+                    device,
+                    staging_belt,
+                    encoder,
+                    target,
+                    viewport,
+                    state.primitive(),
+                    &debug.overlay(),
+                );
+                if icon > *mouse_interaction {
+                    *mouse_interaction = icon;
+                }
+            }
         }
     }
 
     fn has_keyboard_priority(&self) -> bool {
         match self {
-            Self::TopBar(_) => false,
+            Self::TopBar(top_bar) => top_bar.program().has_keyboard_priority(),
             Self::LeftPanel(left_panel) => left_panel.program().has_keyboard_priority(),
             Self::StatusBar(status_bar) => status_bar.program().has_keyboard_priority(),
+            Self::ContextMenu(_) => false,
         }
     }
 }
@@ -450,6 +661,35 @@ impl<R: Requests, S: AppState> GuiElement<R, S> {
         }
     }
 
+    /// Initialize a context menu, on the first overlay of the multiplexer
+    fn context_menu(
+        renderer: &mut Renderer,
+        window: &Window,
+        multiplexer: &dyn Multiplexer,
+        requests: Arc<Mutex<R>>,
+        entries: Vec<ContextMenuEntry>,
+        ui_size: UiSize,
+    ) -> Self {
+        let cursor_position = PhysicalPosition::new(-1., -1.);
+        let element_type = ElementType::Overlay(0);
+        let area = multiplexer.get_draw_area(element_type).unwrap();
+        let context_menu = ContextMenu::new(requests, entries, ui_size);
+        let mut context_menu_debug = Debug::new();
+        let context_menu_state = program::State::new(
+            context_menu,
+            convert_size(area.size),
+            conversion::cursor_position(cursor_position, window.scale_factor()),
+            renderer,
+            &mut context_menu_debug,
+        );
+        Self {
+            state: GuiState::ContextMenu(context_menu_state),
+            debug: context_menu_debug,
+            redraw: true,
+            element_type,
+        }
+    }
+
     fn forward_event(&mut self, event: Event) {
         self.state.queue_event(event)
     }
@@ -581,7 +821,43 @@ impl<R: Requests, S: AppState> Gui<R, S> {
 
     /// Forward an event to the appropriate gui component
     pub fn forward_event(&mut self, area: ElementType, event: iced_native::Event) {
-        self.elements.get_mut(&area).unwrap().forward_event(event);
+        if let Some(element) = self.elements.get_mut(&area) {
+            element.forward_event(event);
+        } else {
+            log::error!(
+                "Received an event for {:?} which has no gui component",
+                area
+            );
+        }
+    }
+
+    /// The physical size of a context menu with `nb_entries` entries
+    pub fn context_menu_size(&self, nb_entries: usize, window: &Window) -> PhysicalSize<u32> {
+        ContextMenu::<R>::logical_size(nb_entries, self.ui_size).to_physical(window.scale_factor())
+    }
+
+    /// Show a context menu with `entries` on the first overlay of the multiplexer
+    pub fn open_context_menu(
+        &mut self,
+        entries: Vec<ContextMenuEntry>,
+        window: &Window,
+        multiplexer: &dyn Multiplexer,
+    ) {
+        self.elements.insert(
+            ElementType::Overlay(0),
+            GuiElement::context_menu(
+                &mut self.renderer,
+                window,
+                multiplexer,
+                self.requests.clone(),
+                entries,
+                self.ui_size,
+            ),
+        );
+    }
+
+    pub fn close_context_menu(&mut self) {
+        self.elements.remove(&ElementType::Overlay(0));
     }
 
     /// Clear the foccus of all components of the GUI
@@ -831,17 +1107,10 @@ impl<S: AppState> IcedMessages<S> {
         }
     }
 
-    pub fn push_progress(&mut self, progress_name: String, progress: f32) {
+    /// Display the progress of the running background jobs in the status bar
+    pub fn push_jobs_progress(&mut self, jobs: Vec<JobProgress>) {
         self.status_bar
-            .push_back(status_bar::Message::Progress(Some((
-                progress_name,
-                progress,
-            ))))
-    }
-
-    pub fn finish_progess(&mut self) {
-        self.status_bar
-            .push_back(status_bar::Message::Progress(None))
+            .push_back(status_bar::Message::JobsProgress(jobs))
     }
 
     pub fn update_modifiers(&mut self, modifiers: ModifiersState) {
@@ -849,6 +1118,43 @@ impl<S: AppState> IcedMessages<S> {
             .push_back(left_panel::Message::ModifiersChanged(modifiers))
     }
 
+    pub fn push_key_bindings(&mut self, bindings: KeyBindings) {
+        self.left_panel
+            .push_back(left_panel::Message::KeyBindingsChanged(bindings));
+    }
+
+    pub fn push_plugin_actions(&mut self, actions: Vec<PluginMenuAction>) {
+        self.left_panel
+            .push_back(left_panel::Message::PluginActionsChanged(actions));
+    }
+
+    pub fn push_color_palette(&mut self, palette: ColorPalette) {
+        self.left_panel
+            .push_back(left_panel::Message::ColorPaletteChanged(palette));
+    }
+
+    pub fn push_edit_history_size(&mut self, size: usize) {
+        self.left_panel
+            .push_back(left_panel::Message::EditHistorySizeLoaded(size));
+    }
+
+    pub fn push_session_log_enabled(&mut self, enabled: bool) {
+        self.left_panel
+            .push_back(left_panel::Message::SessionLogEnabledLoaded(enabled));
+    }
+
+    pub fn push_strand_building_parameters(&mut self, parameters: StrandBuildingParameters) {
+        self.left_panel
+            .push_back(left_panel::Message::StrandBuildingParametersLoaded(
+                parameters,
+            ));
+    }
+
+    pub fn push_color_theme(&mut self, theme: ColorTheme) {
+        self.left_panel
+            .push_back(left_panel::Message::ColorThemeLoaded(theme));
+    }
+
     pub fn new_ui_size(&mut self, ui_size: UiSize) {
         self.left_panel
             .push_back(left_panel::Message::UiSizeChanged(ui_size));
@@ -866,6 +1172,11 @@ impl<S: AppState> IcedMessages<S> {
         self.left_panel.push_back(left_panel::Message::ForceHelp);
     }
 
+    pub fn push_annotation_edition(&mut self, nucl: Nucl) {
+        self.left_panel
+            .push_back(left_panel::Message::EditNucleotideAnnotation(nucl));
+    }
+
     pub fn push_application_state(&mut self, state: S, main_state: MainState) {
         log::trace!("Old ptr {:p}, new ptr {:p}", state, self.application_state);
         self.application_state = state.clone();
@@ -884,9 +1195,14 @@ impl<S: AppState> IcedMessages<S> {
                     can_reload: main_state.can_reload,
                     can_split2d: main_state.can_split2d,
                     splited_2d: main_state.splited_2d,
+                    recording_macro: main_state.recording_macro,
                 }));
             self.status_bar
                 .push_back(status_bar::Message::NewApplicationState(state.clone()));
+            self.status_bar
+                .push_back(status_bar::Message::MacroRecording(
+                    main_state.recording_macro,
+                ));
         }
     }
 }
@@ -903,11 +1219,15 @@ pub trait AppState:
     Default + PartialEq + Clone + 'static + Send + std::fmt::Debug + std::fmt::Pointer
 {
     fn get_selection_mode(&self) -> SelectionMode;
+    fn is_selection_locked(&self) -> bool;
     fn get_action_mode(&self) -> ActionMode;
     fn get_build_helix_mode(&self) -> ActionMode;
     fn has_double_strand_on_new_helix(&self) -> bool;
     fn get_widget_basis(&self) -> WidgetBasis;
     fn get_simulation_state(&self) -> SimulationState;
+    /// Return the number of iterations performed by the optimization of the helices' rolls if it
+    /// is running
+    fn get_roll_optimization_iteration(&self) -> Option<usize>;
     fn get_dna_parameters(&self) -> Parameters;
     fn is_building_hyperboloid(&self) -> bool;
     fn get_scaffold_info(&self) -> Option<ScaffoldInfo>;
@@ -921,6 +1241,14 @@ pub trait AppState:
     fn get_strand_building_state(&self) -> Option<StrandBuildingStatus>;
     fn get_selected_group(&self) -> Option<GroupId>;
     fn get_suggestion_parameters(&self) -> &SuggestionParameters;
+    fn get_strand_building_parameters(&self) -> &StrandBuildingParameters;
+    /// The distances between pairs of nucleotides measured in the 3D view
+    fn get_measurements(&self) -> &[Measurement];
+    /// The oxDNA trajectory whose frames are displayed in the 3D view, if any
+    fn get_trajectory(&self) -> Option<&Trajectory>;
+    /// The designs displayed next to the design being edited
+    fn get_workspace_designs(&self) -> Vec<WorkspaceDesignInfo>;
+    fn is_main_design_visible(&self) -> bool;
 }
 
 pub trait DesignReader: 'static {
@@ -931,12 +1259,31 @@ pub trait DesignReader: 'static {
     fn is_id_of_scaffold(&self, s_id: usize) -> bool;
     fn length_decomposition(&self, s_id: usize) -> String;
     fn nucl_is_anchor(&self, nucl: Nucl) -> bool;
+    fn nucleotide_annotation(&self, nucl: Nucl) -> Option<String>;
+    /// If `nucl` is an end of a strand, return the identifier of that strand and the end
+    fn strand_end_of_nucl(&self, nucl: Nucl) -> Option<(usize, StrandEnd)>;
+    fn nucl_is_on_cyclic_scaffold(&self, nucl: Nucl) -> bool;
     fn get_dna_elements(&self) -> &[DnaElement];
     fn get_organizer_tree(&self) -> Option<Arc<ensnano_design::EnsnTree>>;
     fn strand_name(&self, s_id: usize) -> String;
+    /// Return the chemical modifications attached to the 5' and 3' ends of a strand
+    fn strand_modifications(
+        &self,
+        s_id: usize,
+    ) -> (Option<StrandModification>, Option<StrandModification>);
     fn get_all_cameras(&self) -> Vec<(CameraId, &str)>;
     fn get_favourite_camera(&self) -> Option<CameraId>;
     fn get_grid_position_and_orientation(&self, g_id: usize) -> Option<(Vec3, Rotor3)>;
+    /// Return the roll, in radians, given to the helices that are added to grid `g_id`
+    fn get_grid_twist_offset(&self, g_id: usize) -> Option<f32>;
+    /// Return the number of base pairs of a scaffold domain
+    fn scaffold_domain_length(&self, domain_id: usize) -> Option<usize>;
+    /// Return the proportion of G and C bases in a scaffold domain, if its sequence is known
+    fn scaffold_domain_gc_content(&self, domain_id: usize) -> Option<f64>;
+    /// Return the length, number of domains and melting temperature of each staple
+    fn get_staple_statistics(&self) -> Vec<ensnano_interactor::StapleStatistics>;
+    /// The names of the selection groups stored in the design, and their number of elements
+    fn get_selection_groups(&self) -> Vec<(String, usize)>;
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
@@ -947,4 +1294,5 @@ pub struct MainState {
     pub can_reload: bool,
     pub can_split2d: bool,
     pub splited_2d: bool,
+    pub recording_macro: bool,
 }
@@ -18,13 +18,14 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 use super::{AppState, Requests, UiSize};
 use ensnano_interactor::operation::{Operation, ParameterField};
 pub use ensnano_interactor::StrandBuildingStatus;
-use iced::{container, slider, Background, Container, Length};
+use iced::{button, container, slider, Background, Button, Container, Length, ProgressBar};
 use iced_native::{pick_list, text_input, Color, PickList, TextInput};
 use iced_winit::{Column, Command, Element, Program, Row, Space, Text};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 
 const GOLD_ORANGE: iced::Color = iced::Color::from_rgb(0.84, 0.57, 0.20);
+const RECORDING_RED: iced::Color = iced::Color::from_rgb(0.9, 0.1, 0.1);
 
 #[derive(Debug)]
 enum StatusParameter {
@@ -79,15 +80,26 @@ impl StatusParameter {
     }
 }
 
+/// The progress of a background job
+#[derive(Debug, Clone, PartialEq)]
+pub struct JobProgress {
+    pub id: usize,
+    pub name: String,
+    /// The fraction, between 0 and 1, of the job that has been done
+    pub progress: f32,
+}
+
 pub struct StatusBar<R: Requests, S: AppState> {
     info_values: Vec<String>,
     operation: Option<OperationInput>,
     requests: Arc<Mutex<R>>,
-    progress: Option<(String, f32)>,
+    jobs: Vec<JobProgress>,
+    cancel_job_buttons: Vec<button::State>,
     #[allow(dead_code)]
     slider_state: slider::State,
     app_state: S,
     ui_size: UiSize,
+    recording_macro: bool,
 }
 
 impl<R: Requests, S: AppState> StatusBar<R, S> {
@@ -96,10 +108,12 @@ impl<R: Requests, S: AppState> StatusBar<R, S> {
             info_values: Vec::new(),
             operation: None,
             requests,
-            progress: None,
+            jobs: Vec::new(),
+            cancel_job_buttons: Vec::new(),
             slider_state: Default::default(),
             app_state: Default::default(),
             ui_size: Default::default(),
+            recording_macro: false,
         }
     }
 
@@ -119,13 +133,30 @@ impl<R: Requests, S: AppState> StatusBar<R, S> {
         }
     }
 
+    fn set_jobs(&mut self, jobs: Vec<JobProgress>) {
+        self.cancel_job_buttons
+            .resize_with(jobs.len(), Default::default);
+        self.jobs = jobs;
+    }
+
     fn view_progress(&mut self) -> Element<Message<S>, iced_wgpu::Renderer> {
-        let mut row = Row::new();
-        let progress = self.progress.as_ref().unwrap();
-        row = row.push(
-            Text::new(format!("{}, {:.1}%", progress.0, progress.1 * 100.))
-                .size(self.ui_size.main_text()),
-        );
+        let mut row = Row::new().spacing(10);
+        let text_size = self.ui_size.main_text();
+        for (job, button) in self.jobs.iter().zip(self.cancel_job_buttons.iter_mut()) {
+            row = row
+                .push(
+                    Text::new(format!("{}: {:.1}%", job.name, job.progress * 100.)).size(text_size),
+                )
+                .push(
+                    ProgressBar::new(0.0..=1.0, job.progress)
+                        .width(Length::Units(150))
+                        .height(Length::Units(text_size)),
+                )
+                .push(
+                    Button::new(button, Text::new("Cancel").size(text_size))
+                        .on_press(Message::CancelJob(job.id)),
+                );
+        }
 
         row.into()
     }
@@ -161,12 +192,14 @@ impl<R: Requests, S: AppState> StatusBar<R, S> {
 pub enum Message<S: AppState> {
     ValueStrChanged(usize, String),
     ValueSet(usize, String),
-    Progress(Option<(String, f32)>),
+    JobsProgress(Vec<JobProgress>),
     #[allow(dead_code)]
     SetShift(f32),
     NewApplicationState(S),
     UiSizeChanged(UiSize),
     TabPressed,
+    MacroRecording(bool),
+    CancelJob(usize),
 }
 
 impl<R: Requests, S: AppState> Program for StatusBar<R, S> {
@@ -190,7 +223,7 @@ impl<R: Requests, S: AppState> Program for StatusBar<R, S> {
                     }
                 }
             }
-            Message::Progress(progress) => self.progress = progress,
+            Message::JobsProgress(jobs) => self.set_jobs(jobs),
             Message::SetShift(f) => {
                 self.info_values[2] = f.to_string();
                 self.requests.lock().unwrap().update_hyperboloid_shift(f);
@@ -198,13 +231,17 @@ impl<R: Requests, S: AppState> Program for StatusBar<R, S> {
             Message::NewApplicationState(state) => self.app_state = state,
             Message::UiSizeChanged(ui_size) => self.set_ui_size(ui_size),
             Message::TabPressed => self.process_tab(),
+            Message::MacroRecording(recording) => self.recording_macro = recording,
+            Message::CancelJob(job_id) => self.requests.lock().unwrap().cancel_job(job_id),
         }
         Command::none()
     }
 
     fn view(&mut self) -> Element<Message<S>, iced_wgpu::Renderer> {
         self.update_operation();
-        let content = if self.progress.is_some() {
+        let recording_macro = self.recording_macro;
+        let text_size = self.ui_size.main_text();
+        let content = if !self.jobs.is_empty() {
             self.view_progress()
         } else if let Some(building_info) = self.app_state.get_strand_building_state() {
             Row::new()
@@ -218,6 +255,16 @@ impl<R: Requests, S: AppState> Program for StatusBar<R, S> {
             Row::new().into() //TODO
         };
 
+        let content: Element<Message<S>, iced_wgpu::Renderer> = if recording_macro {
+            Row::new()
+                .spacing(10)
+                .push(Text::new("● REC").size(text_size).color(RECORDING_RED))
+                .push(content)
+                .into()
+        } else {
+            content
+        };
+
         let column = Column::new()
             .push(Space::new(Length::Fill, Length::Units(3)))
             .push(content);
@@ -20,7 +20,9 @@ use ensnano_interactor::{ActionMode, SelectionMode};
 use iced::{container, Background, Container};
 use iced_wgpu::Renderer;
 use iced_winit::winit::dpi::LogicalSize;
-use iced_winit::{button, Button, Color, Command, Element, Length, Program, Row};
+use iced_winit::{
+    button, text_input, Button, Color, Command, Element, Length, Program, Row, TextInput,
+};
 use std::collections::BTreeMap;
 use std::sync::{Arc, Mutex};
 
@@ -74,12 +76,29 @@ pub struct TopBar<R: Requests, S: AppState> {
     button_2d: button::State,
     button_split: button::State,
     button_oxdna: button::State,
+    button_pdb: button::State,
+    button_cadnano: button::State,
+    button_mesh: button::State,
+    button_import_module: button::State,
     button_split_2d: button::State,
     button_flip_split: button::State,
     button_help: button::State,
     button_tutorial: button::State,
     button_reload: button::State,
     button_new_empty_design: button::State,
+    button_record_macro: button::State,
+    button_play_macro: button::State,
+    button_replay_session: button::State,
+    button_export_helix_map: button::State,
+    helix_map_dpi_input: text_input::State,
+    helix_map_width_input: text_input::State,
+    helix_map_height_input: text_input::State,
+    /// The resolution, in dots per inch, of the exported images of the 2D view
+    helix_map_dpi: String,
+    /// The width, in millimeters, of the exported images of the 2D view
+    helix_map_width: String,
+    /// The height, in millimeters, of the exported images of the 2D view
+    helix_map_height: String,
     requests: Arc<Mutex<R>>,
     logical_size: LogicalSize<f64>,
     action_mode_state: ActionModeState,
@@ -97,6 +116,7 @@ pub struct MainState<S: AppState> {
     pub can_reload: bool,
     pub can_split2d: bool,
     pub splited_2d: bool,
+    pub recording_macro: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -109,6 +129,10 @@ pub enum Message<S: AppState> {
     ToggleView(SplitMode),
     UiSizeChanged(UiSize),
     OxDNARequested,
+    PdbRequested,
+    CadnanoRequested,
+    MeshRequested,
+    ImportModuleRequested,
     Split2d,
     NewApplicationState(MainState<S>),
     ForceHelp,
@@ -120,6 +144,13 @@ pub enum Message<S: AppState> {
     SelectionModeChanged(SelectionMode),
     Reload,
     FlipSplitViews,
+    ToggleMacroRecording,
+    PlayMacro,
+    ReplaySession,
+    HelixMapDpiInput(String),
+    HelixMapWidthInput(String),
+    HelixMapHeightInput(String),
+    ExportHelixMapPng,
 }
 
 impl<R: Requests, S: AppState> TopBar<R, S> {
@@ -135,12 +166,26 @@ impl<R: Requests, S: AppState> TopBar<R, S> {
             button_3d: Default::default(),
             button_split: Default::default(),
             button_oxdna: Default::default(),
+            button_pdb: Default::default(),
+            button_cadnano: Default::default(),
+            button_mesh: Default::default(),
+            button_import_module: Default::default(),
             button_split_2d: Default::default(),
             button_flip_split: Default::default(),
             button_help: Default::default(),
             button_tutorial: Default::default(),
             button_new_empty_design: Default::default(),
             button_reload: Default::default(),
+            button_record_macro: Default::default(),
+            button_play_macro: Default::default(),
+            button_replay_session: Default::default(),
+            button_export_helix_map: Default::default(),
+            helix_map_dpi_input: Default::default(),
+            helix_map_width_input: Default::default(),
+            helix_map_height_input: Default::default(),
+            helix_map_dpi: DEFAULT_HELIX_MAP_DPI.to_string(),
+            helix_map_width: DEFAULT_HELIX_MAP_WIDTH_MM.to_string(),
+            helix_map_height: DEFAULT_HELIX_MAP_HEIGHT_MM.to_string(),
             requests,
             logical_size,
             action_mode_state: Default::default(),
@@ -157,8 +202,39 @@ impl<R: Requests, S: AppState> TopBar<R, S> {
     fn get_build_helix_mode(&self) -> ActionMode {
         self.application_state.app_state.get_build_helix_mode()
     }
+
+    pub fn has_keyboard_priority(&self) -> bool {
+        self.helix_map_dpi_input.is_focused()
+            || self.helix_map_width_input.is_focused()
+            || self.helix_map_height_input.is_focused()
+    }
+
+    /// The resolution and the size in millimeters of the exported images of the 2D view, if
+    /// they are valid
+    fn helix_map_parameters(&self) -> Option<(u32, (f32, f32))> {
+        let dpi = self
+            .helix_map_dpi
+            .parse::<u32>()
+            .ok()
+            .filter(|dpi| *dpi > 0)?;
+        let width = self
+            .helix_map_width
+            .parse::<f32>()
+            .ok()
+            .filter(|w| *w > 0.)?;
+        let height = self
+            .helix_map_height
+            .parse::<f32>()
+            .ok()
+            .filter(|h| *h > 0.)?;
+        Some((dpi, (width, height)))
+    }
 }
 
+const DEFAULT_HELIX_MAP_DPI: u32 = 300;
+const DEFAULT_HELIX_MAP_WIDTH_MM: u32 = 180;
+const DEFAULT_HELIX_MAP_HEIGHT_MM: u32 = 120;
+
 impl<R: Requests, S: AppState> Program for TopBar<R, S> {
     type Renderer = Renderer;
     type Message = Message<S>;
@@ -181,6 +257,10 @@ impl<R: Requests, S: AppState> Program for TopBar<R, S> {
             Message::ToggleView(b) => self.requests.lock().unwrap().change_split_mode(b),
             Message::UiSizeChanged(ui_size) => self.ui_size = ui_size,
             Message::OxDNARequested => self.requests.lock().unwrap().export_to_oxdna(),
+            Message::PdbRequested => self.requests.lock().unwrap().export_to_pdb(),
+            Message::CadnanoRequested => self.requests.lock().unwrap().export_to_cadnano(),
+            Message::MeshRequested => self.requests.lock().unwrap().export_mesh(),
+            Message::ImportModuleRequested => self.requests.lock().unwrap().import_module(),
             Message::Split2d => self.requests.lock().unwrap().toggle_2d_view_split(),
             Message::NewApplicationState(state) => self.application_state = state,
             Message::Undo => self.requests.lock().unwrap().undo(),
@@ -213,6 +293,26 @@ impl<R: Requests, S: AppState> Program for TopBar<R, S> {
                 }
             }
             Message::FlipSplitViews => self.requests.lock().unwrap().flip_split_views(),
+            Message::ToggleMacroRecording => {
+                if self.application_state.recording_macro {
+                    self.requests.lock().unwrap().stop_macro_recording()
+                } else {
+                    self.requests.lock().unwrap().start_macro_recording()
+                }
+            }
+            Message::PlayMacro => self.requests.lock().unwrap().play_macro(),
+            Message::ReplaySession => self.requests.lock().unwrap().replay_session(),
+            Message::HelixMapDpiInput(dpi) => self.helix_map_dpi = dpi,
+            Message::HelixMapWidthInput(width) => self.helix_map_width = width,
+            Message::HelixMapHeightInput(height) => self.helix_map_height = height,
+            Message::ExportHelixMapPng => {
+                if let Some((dpi, size_mm)) = self.helix_map_parameters() {
+                    self.requests
+                        .lock()
+                        .unwrap()
+                        .export_helix_map_png(dpi, size_mm)
+                }
+            }
         };
         Command::none()
     }
@@ -321,6 +421,22 @@ impl<R: Requests, S: AppState> Program for TopBar<R, S> {
             .height(Length::Units(self.ui_size.button()))
             .on_press(Message::OxDNARequested);
         let oxdna_tooltip = button_oxdna;
+        let button_pdb = Button::new(&mut self.button_pdb, iced::Text::new("Export PDB"))
+            .height(Length::Units(self.ui_size.button()))
+            .on_press(Message::PdbRequested);
+        let button_cadnano =
+            Button::new(&mut self.button_cadnano, iced::Text::new("Export cadnano"))
+                .height(Length::Units(self.ui_size.button()))
+                .on_press(Message::CadnanoRequested);
+        let button_mesh = Button::new(&mut self.button_mesh, iced::Text::new("Export mesh"))
+            .height(Length::Units(self.ui_size.button()))
+            .on_press(Message::MeshRequested);
+        let button_import_module = Button::new(
+            &mut self.button_import_module,
+            iced::Text::new("Import module"),
+        )
+        .height(Length::Units(self.ui_size.button()))
+        .on_press(Message::ImportModuleRequested);
 
         let split_icon = if self.application_state.splited_2d {
             LightIcon::BorderOuter
@@ -347,6 +463,41 @@ impl<R: Requests, S: AppState> Program for TopBar<R, S> {
             button_flip_split = button_flip_split.on_press(Message::FlipSplitViews);
         }
 
+        let helix_map_parameters = self.helix_map_parameters();
+        let mut button_export_helix_map = Button::new(
+            &mut self.button_export_helix_map,
+            iced::Text::new("Export Helix Map PNG"),
+        )
+        .height(Length::Units(self.ui_size.button()));
+        if self.application_state.can_split2d && helix_map_parameters.is_some() {
+            button_export_helix_map = button_export_helix_map.on_press(Message::ExportHelixMapPng);
+        }
+        let input_width = Length::Units(4 * self.ui_size.button());
+        let helix_map_dpi_input = TextInput::new(
+            &mut self.helix_map_dpi_input,
+            "dpi",
+            &self.helix_map_dpi,
+            Message::HelixMapDpiInput,
+        )
+        .width(input_width)
+        .size(self.ui_size.main_text());
+        let helix_map_width_input = TextInput::new(
+            &mut self.helix_map_width_input,
+            "width (mm)",
+            &self.helix_map_width,
+            Message::HelixMapWidthInput,
+        )
+        .width(input_width)
+        .size(self.ui_size.main_text());
+        let helix_map_height_input = TextInput::new(
+            &mut self.helix_map_height_input,
+            "height (mm)",
+            &self.helix_map_height,
+            Message::HelixMapHeightInput,
+        )
+        .width(input_width)
+        .size(self.ui_size.main_text());
+
         let button_help = Button::new(&mut self.button_help, iced::Text::new("Help"))
             .height(Length::Units(self.ui_size.button()))
             .on_press(Message::ForceHelp);
@@ -355,21 +506,57 @@ impl<R: Requests, S: AppState> Program for TopBar<R, S> {
             .height(Length::Units(self.ui_size.button()))
             .on_press(Message::ShowTutorial);
 
+        let record_label = if self.application_state.recording_macro {
+            "Stop recording"
+        } else {
+            "Record macro"
+        };
+        let button_record_macro =
+            Button::new(&mut self.button_record_macro, iced::Text::new(record_label))
+                .height(Length::Units(self.ui_size.button()))
+                .on_press(Message::ToggleMacroRecording);
+
+        let mut button_play_macro =
+            Button::new(&mut self.button_play_macro, iced::Text::new("Play macro"))
+                .height(Length::Units(self.ui_size.button()));
+        if !self.application_state.recording_macro {
+            button_play_macro = button_play_macro.on_press(Message::PlayMacro);
+        }
+
+        let button_replay_session = Button::new(
+            &mut self.button_replay_session,
+            iced::Text::new("Replay session"),
+        )
+        .height(Length::Units(self.ui_size.button()))
+        .on_press(Message::ReplaySession);
+
         let app_state = &self.application_state.app_state;
         let ui_size = self.ui_size.clone();
-        let action_buttons: Vec<Button<Message<S>, _>> = self
+        let min_strand_length = app_state.get_strand_building_parameters().min_strand_length;
+        let action_buttons: Vec<Element<Message<S>, _>> = self
             .action_mode_state
             .get_states(build_helix_mode)
             .into_iter()
             .filter(|(m, _)| action_modes.contains(m))
             .map(|(mode, state)| {
-                action_mode_btn(
+                let button = action_mode_btn(
                     state,
                     mode,
                     app_state.get_action_mode(),
                     ui_size.button(),
                     app_state.get_widget_basis().is_axis_aligned(),
-                )
+                );
+                if let ActionMode::BuildHelix { .. } = mode {
+                    iced::Tooltip::new(
+                        button,
+                        format!("Minimum strand length: {} nt", min_strand_length),
+                        iced::tooltip::Position::Bottom,
+                    )
+                    .style(ToolTipStyle)
+                    .into()
+                } else {
+                    button.into()
+                }
             })
             .collect();
 
@@ -403,6 +590,10 @@ impl<R: Requests, S: AppState> Program for TopBar<R, S> {
             .push(button_save)
             .push(button_save_as)
             .push(oxdna_tooltip)
+            .push(button_pdb)
+            .push(button_cadnano)
+            .push(button_mesh)
+            .push(button_import_module)
             .push(iced::Space::with_width(Length::Units(10)))
             .push(button_3d)
             .push(button_2d)
@@ -410,6 +601,16 @@ impl<R: Requests, S: AppState> Program for TopBar<R, S> {
             .push(button_split_2d)
             .push(button_flip_split)
             .push(iced::Space::with_width(Length::Units(10)))
+            .push(button_export_helix_map)
+            .push(iced::Space::with_width(Length::Units(2)))
+            .push(helix_map_dpi_input)
+            .push(iced::Text::new("dpi").size(self.ui_size.main_text()))
+            .push(iced::Space::with_width(Length::Units(2)))
+            .push(helix_map_width_input)
+            .push(iced::Text::new("x").size(self.ui_size.main_text()))
+            .push(helix_map_height_input)
+            .push(iced::Text::new("mm").size(self.ui_size.main_text()))
+            .push(iced::Space::with_width(Length::Units(10)))
             .push(button_fit)
             .push(iced::Space::with_width(Length::Units(10)))
             .push(button_undo)
@@ -426,8 +627,24 @@ impl<R: Requests, S: AppState> Program for TopBar<R, S> {
             buttons = buttons.push(button);
         }
 
+        if app_state.is_selection_locked() {
+            buttons = buttons.push(
+                light_icon(LightIcon::Lock, self.ui_size.clone())
+                    .height(Length::Units(height))
+                    .vertical_alignment(iced::alignment::Vertical::Center),
+            );
+        }
+
         buttons = buttons.push(iced::Space::with_width(Length::Units(10)));
 
+        buttons = buttons
+            .push(button_record_macro)
+            .push(iced::Space::with_width(Length::Units(2)))
+            .push(button_play_macro)
+            .push(iced::Space::with_width(Length::Units(2)))
+            .push(button_replay_session)
+            .push(iced::Space::with_width(Length::Units(10)));
+
         buttons = buttons
             .push(button_help)
             .push(iced::Space::with_width(Length::Units(2)))
@@ -475,6 +692,7 @@ impl iced::container::StyleSheet for ToolTipStyle {
     fn style(&self) -> iced::container::Style {
         iced::container::Style {
             text_color: Some(iced::Color::BLACK),
+            background: Some(Background::Color(iced::Color::WHITE)),
             ..Default::default()
         }
     }
@@ -0,0 +1,535 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! The keyboard shortcuts of the application.
+//!
+//! The bindings are read from `~/.ensnano/keybindings.json` at start up, and fall back to the
+//! default bindings if this file does not exist. They are written back to this file each time
+//! the user rebinds an action.
+
+use crate::consts::ENSNANO_USER_DIR;
+use iced_winit::winit::event::{ModifiersState, VirtualKeyCode};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+const KEY_BINDINGS_FILE: &str = "keybindings.json";
+
+/// A key together with the modifiers that must be held when it is pressed.
+///
+/// The only modifiers that are taken into account are shift, alt and the "ctrl" modifier, which
+/// is the logo key on macOS.
+pub type KeyCombination = (VirtualKeyCode, ModifiersState);
+
+/// An action that can be triggered by a keyboard shortcut
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HotkeyAction {
+    Undo,
+    Redo,
+    Copy,
+    Paste,
+    Duplicate,
+    Anchor,
+    NormalMode,
+    TranslateMode,
+    RotateMode,
+    SelectNucleotide,
+    SelectHelix,
+    SelectStrand,
+    Save,
+    Open,
+    Exit,
+    RecolorStaples,
+    DeleteSelection,
+    UndoCameraMove,
+    RedoCameraMove,
+    ToggleWidgetBasis,
+    TurnCameraLeft,
+    TurnCameraRight,
+    TurnCameraUp,
+    TurnCameraDown,
+}
+
+impl HotkeyAction {
+    pub const ALL: &'static [Self] = &[
+        Self::Undo,
+        Self::Redo,
+        Self::Copy,
+        Self::Paste,
+        Self::Duplicate,
+        Self::Anchor,
+        Self::NormalMode,
+        Self::TranslateMode,
+        Self::RotateMode,
+        Self::SelectNucleotide,
+        Self::SelectHelix,
+        Self::SelectStrand,
+        Self::Save,
+        Self::Open,
+        Self::Exit,
+        Self::RecolorStaples,
+        Self::DeleteSelection,
+        Self::UndoCameraMove,
+        Self::RedoCameraMove,
+        Self::ToggleWidgetBasis,
+        Self::TurnCameraLeft,
+        Self::TurnCameraRight,
+        Self::TurnCameraUp,
+        Self::TurnCameraDown,
+    ];
+}
+
+impl std::fmt::Display for HotkeyAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let description = match self {
+            Self::Undo => "Undo",
+            Self::Redo => "Redo",
+            Self::Copy => "Copy",
+            Self::Paste => "Paste",
+            Self::Duplicate => "Duplicate",
+            Self::Anchor => "Toggle anchor",
+            Self::NormalMode => "Select mode",
+            Self::TranslateMode => "Translation mode",
+            Self::RotateMode => "Rotation mode",
+            Self::SelectNucleotide => "Select nucleotides",
+            Self::SelectHelix => "Select helices",
+            Self::SelectStrand => "Select strands",
+            Self::Save => "Save",
+            Self::Open => "Open",
+            Self::Exit => "Exit",
+            Self::RecolorStaples => "Recolor staples",
+            Self::DeleteSelection => "Delete selection",
+            Self::UndoCameraMove => "Previous camera",
+            Self::RedoCameraMove => "Next camera",
+            Self::ToggleWidgetBasis => "Toggle widget basis",
+            Self::TurnCameraLeft => "Turn camera left",
+            Self::TurnCameraRight => "Turn camera right",
+            Self::TurnCameraUp => "Turn camera up",
+            Self::TurnCameraDown => "Turn camera down",
+        };
+        write!(f, "{}", description)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyBindings {
+    bindings: HashMap<KeyCombination, HotkeyAction>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        let ctrl = command_modifier();
+        let none = ModifiersState::empty();
+        let mut bindings = HashMap::new();
+        bindings.insert((VirtualKeyCode::Escape, none), HotkeyAction::NormalMode);
+        bindings.insert((VirtualKeyCode::Z, ctrl), HotkeyAction::Undo);
+        bindings.insert((VirtualKeyCode::R, ctrl), HotkeyAction::Redo);
+        bindings.insert((VirtualKeyCode::C, ctrl), HotkeyAction::Copy);
+        bindings.insert((VirtualKeyCode::V, ctrl), HotkeyAction::Paste);
+        bindings.insert((VirtualKeyCode::J, ctrl), HotkeyAction::Duplicate);
+        bindings.insert((VirtualKeyCode::L, ctrl), HotkeyAction::Anchor);
+        bindings.insert((VirtualKeyCode::R, none), HotkeyAction::RotateMode);
+        bindings.insert((VirtualKeyCode::T, none), HotkeyAction::TranslateMode);
+        bindings.insert((VirtualKeyCode::N, none), HotkeyAction::SelectNucleotide);
+        bindings.insert((VirtualKeyCode::H, none), HotkeyAction::SelectHelix);
+        bindings.insert((VirtualKeyCode::S, none), HotkeyAction::SelectStrand);
+        bindings.insert((VirtualKeyCode::S, ctrl), HotkeyAction::Save);
+        bindings.insert((VirtualKeyCode::O, ctrl), HotkeyAction::Open);
+        if cfg!(target_os = "macos") {
+            bindings.insert((VirtualKeyCode::Q, ctrl), HotkeyAction::Exit);
+        }
+        bindings.insert((VirtualKeyCode::K, none), HotkeyAction::RecolorStaples);
+        bindings.insert(
+            (VirtualKeyCode::Delete, none),
+            HotkeyAction::DeleteSelection,
+        );
+        bindings.insert((VirtualKeyCode::Back, none), HotkeyAction::DeleteSelection);
+        let ctrl_shift = ctrl | ModifiersState::SHIFT;
+        bindings.insert(
+            (VirtualKeyCode::Z, ctrl_shift),
+            HotkeyAction::UndoCameraMove,
+        );
+        bindings.insert(
+            (VirtualKeyCode::R, ctrl_shift),
+            HotkeyAction::RedoCameraMove,
+        );
+        bindings.insert(
+            (VirtualKeyCode::Space, none),
+            HotkeyAction::ToggleWidgetBasis,
+        );
+        let alt = ModifiersState::ALT;
+        bindings.insert((VirtualKeyCode::H, alt), HotkeyAction::TurnCameraLeft);
+        bindings.insert((VirtualKeyCode::L, alt), HotkeyAction::TurnCameraRight);
+        bindings.insert((VirtualKeyCode::K, alt), HotkeyAction::TurnCameraUp);
+        bindings.insert((VirtualKeyCode::J, alt), HotkeyAction::TurnCameraDown);
+        Self { bindings }
+    }
+}
+
+impl KeyBindings {
+    /// Read the bindings saved by the user, or return the default bindings if there are none.
+    pub fn load() -> Self {
+        let path = if let Some(path) = key_bindings_path() {
+            path
+        } else {
+            return Default::default();
+        };
+        if !path.exists() {
+            return Default::default();
+        }
+        match std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|json| Self::from_json(&json))
+        {
+            Ok(bindings) => bindings,
+            Err(e) => {
+                log::error!("Could not read key bindings from {:?}: {}", path, e);
+                Default::default()
+            }
+        }
+    }
+
+    /// Write the bindings in `~/.ensnano/keybindings.json`
+    pub fn save(&self) -> std::io::Result<()> {
+        let path = key_bindings_path().ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "Could not find the home directory",
+            )
+        })?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(&self.to_saved_bindings())?;
+        std::fs::write(path, json)
+    }
+
+    fn from_json(json: &str) -> Result<Self, String> {
+        let saved: Vec<SavedBinding> = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        let mut bindings = HashMap::new();
+        for binding in saved {
+            let key = key_from_name(&binding.key)
+                .ok_or_else(|| format!("Unknown key {}", binding.key))?;
+            let mut modifiers = ModifiersState::empty();
+            if binding.ctrl {
+                modifiers |= command_modifier();
+            }
+            if binding.shift {
+                modifiers |= ModifiersState::SHIFT;
+            }
+            if binding.alt {
+                modifiers |= ModifiersState::ALT;
+            }
+            bindings.insert((key, modifiers), binding.action);
+        }
+        Ok(Self { bindings })
+    }
+
+    fn to_saved_bindings(&self) -> Vec<SavedBinding> {
+        self.sorted_bindings()
+            .into_iter()
+            .filter_map(|(combination, action)| {
+                combination.map(|(key, modifiers)| SavedBinding {
+                    key: key_name(key).to_owned(),
+                    ctrl: modifiers.intersects(command_modifier()),
+                    shift: modifiers.shift(),
+                    alt: modifiers.alt(),
+                    action,
+                })
+            })
+            .collect()
+    }
+
+    /// Return the action bound to `key` when `modifiers` are held.
+    ///
+    /// If shift is the only modifier held and the combination is not bound, the action bound to
+    /// `key` alone is returned, so that these shortcuts keep working while shift is held.
+    pub fn get_action(
+        &self,
+        key: VirtualKeyCode,
+        modifiers: ModifiersState,
+    ) -> Option<HotkeyAction> {
+        let modifiers = normalize_modifiers(modifiers);
+        self.bindings
+            .get(&(key, modifiers))
+            .or_else(|| {
+                if modifiers == ModifiersState::SHIFT {
+                    self.bindings.get(&(key, ModifiersState::empty()))
+                } else {
+                    None
+                }
+            })
+            .cloned()
+    }
+
+    /// Return all the bindings, ordered by actions. Actions that are not bound to any key
+    /// combination appear with `None`.
+    pub fn sorted_bindings(&self) -> Vec<(Option<KeyCombination>, HotkeyAction)> {
+        let mut ret = Vec::new();
+        for action in HotkeyAction::ALL.iter() {
+            let mut combinations: Vec<KeyCombination> = self
+                .bindings
+                .iter()
+                .filter(|(_, a)| *a == action)
+                .map(|(c, _)| *c)
+                .collect();
+            combinations.sort_by_key(|(key, modifiers)| (*key as u32, modifiers.bits()));
+            if combinations.is_empty() {
+                ret.push((None, *action));
+            } else {
+                ret.extend(combinations.into_iter().map(|c| (Some(c), *action)));
+            }
+        }
+        ret
+    }
+
+    /// Bind `action` to `combination`, replacing the binding `previous` if it is `Some`.
+    ///
+    /// If `combination` was bound to an other action, this action loses this binding and is
+    /// returned.
+    pub fn bind(
+        &mut self,
+        combination: KeyCombination,
+        action: HotkeyAction,
+        previous: Option<KeyCombination>,
+    ) -> Option<HotkeyAction> {
+        let combination = (combination.0, normalize_modifiers(combination.1));
+        if let Some(previous) = previous {
+            self.bindings.remove(&previous);
+        }
+        self.bindings
+            .insert(combination, action)
+            .filter(|conflict| *conflict != action)
+    }
+}
+
+/// Format a key combination in a human readable way, e.g. "Ctrl + Shift + Z"
+pub fn combination_to_string(combination: &KeyCombination) -> String {
+    let (key, modifiers) = combination;
+    let mut ret = String::new();
+    if modifiers.intersects(command_modifier()) {
+        if cfg!(target_os = "macos") {
+            ret.push_str("Cmd + ");
+        } else {
+            ret.push_str("Ctrl + ");
+        }
+    }
+    if modifiers.alt() {
+        ret.push_str("Alt + ");
+    }
+    if modifiers.shift() {
+        ret.push_str("Shift + ");
+    }
+    ret.push_str(key_name(*key));
+    ret
+}
+
+/// Return true if `key` is a modifier key, that cannot be bound to an action on its own.
+pub fn is_modifier_key(key: VirtualKeyCode) -> bool {
+    matches!(
+        key,
+        VirtualKeyCode::LShift
+            | VirtualKeyCode::RShift
+            | VirtualKeyCode::LControl
+            | VirtualKeyCode::RControl
+            | VirtualKeyCode::LAlt
+            | VirtualKeyCode::RAlt
+            | VirtualKeyCode::LWin
+            | VirtualKeyCode::RWin
+    )
+}
+
+/// The modifier used for the "ctrl + key" shortcuts
+fn command_modifier() -> ModifiersState {
+    if cfg!(target_os = "macos") {
+        ModifiersState::LOGO
+    } else {
+        ModifiersState::CTRL
+    }
+}
+
+fn normalize_modifiers(modifiers: ModifiersState) -> ModifiersState {
+    modifiers & (command_modifier() | ModifiersState::SHIFT | ModifiersState::ALT)
+}
+
+fn key_bindings_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(ENSNANO_USER_DIR).join(KEY_BINDINGS_FILE))
+}
+
+/// A key binding as it is written in the key bindings file
+#[derive(Serialize, Deserialize)]
+struct SavedBinding {
+    key: String,
+    #[serde(default)]
+    ctrl: bool,
+    #[serde(default)]
+    shift: bool,
+    #[serde(default)]
+    alt: bool,
+    action: HotkeyAction,
+}
+
+/// Define the names under which the keys are written in the key bindings file.
+///
+/// The match in `key_name` has no wildcard arm, so every key must be listed for the program to
+/// compile.
+macro_rules! key_names {
+    ($($key:ident),* $(,)?) => {
+        fn key_from_name(name: &str) -> Option<VirtualKeyCode> {
+            match name {
+                $(stringify!($key) => Some(VirtualKeyCode::$key),)*
+                _ => None,
+            }
+        }
+
+        fn key_name(key: VirtualKeyCode) -> &'static str {
+            match key {
+                $(VirtualKeyCode::$key => stringify!($key),)*
+            }
+        }
+
+        #[cfg(test)]
+        const ALL_KEYS: &[VirtualKeyCode] = &[$(VirtualKeyCode::$key,)*];
+    };
+}
+
+key_names! {
+    Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9, Key0, A, B, C, D, E, F, G, H, I, J, K,
+    L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z, Escape, F1, F2, F3, F4, F5, F6, F7, F8, F9, F10,
+    F11, F12, F13, F14, F15, F16, F17, F18, F19, F20, F21, F22, F23, F24, Snapshot, Scroll, Pause,
+    Insert, Home, Delete, End, PageDown, PageUp, Left, Up, Right, Down, Back, Return, Space,
+    Compose, Caret, Numlock, Numpad0, Numpad1, Numpad2, Numpad3, Numpad4, Numpad5, Numpad6,
+    Numpad7, Numpad8, Numpad9, NumpadAdd, NumpadDivide, NumpadDecimal, NumpadComma, NumpadEnter,
+    NumpadEquals, NumpadMultiply, NumpadSubtract, AbntC1, AbntC2, Apostrophe, Apps, Asterisk, At,
+    Ax, Backslash, Calculator, Capital, Colon, Comma, Convert, Equals, Grave, Kana, Kanji, LAlt,
+    LBracket, LControl, LShift, LWin, Mail, MediaSelect, MediaStop, Minus, Mute, MyComputer,
+    NavigateForward, NavigateBackward, NextTrack, NoConvert, OEM102, Period, PlayPause, Plus,
+    Power, PrevTrack, RAlt, RBracket, RControl, RShift, RWin, Semicolon, Slash, Sleep, Stop, Sysrq,
+    Tab, Underline, Unlabeled, VolumeDown, VolumeUp, Wake, WebBack, WebFavorites, WebForward,
+    WebHome, WebRefresh, WebSearch, WebStop, Yen, Copy, Paste, Cut,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_bindings_survive_json_round_trip() {
+        let bindings = KeyBindings::default();
+        let json = serde_json::to_string(&bindings.to_saved_bindings()).unwrap();
+        assert_eq!(KeyBindings::from_json(&json).unwrap(), bindings);
+    }
+
+    #[test]
+    fn rebinding_to_used_combination_reports_conflict() {
+        let mut bindings = KeyBindings::default();
+        let ctrl = command_modifier();
+        let conflict = bindings.bind(
+            (VirtualKeyCode::Z, ctrl),
+            HotkeyAction::Redo,
+            Some((VirtualKeyCode::R, ctrl)),
+        );
+        assert_eq!(conflict, Some(HotkeyAction::Undo));
+        assert_eq!(
+            bindings.get_action(VirtualKeyCode::Z, ctrl),
+            Some(HotkeyAction::Redo)
+        );
+        assert_eq!(bindings.get_action(VirtualKeyCode::R, ctrl), None);
+        assert!(bindings
+            .sorted_bindings()
+            .contains(&(None, HotkeyAction::Undo)));
+    }
+
+    #[test]
+    fn camera_history_is_bound_to_ctrl_shift() {
+        let bindings = KeyBindings::default();
+        let ctrl = command_modifier();
+        assert_eq!(
+            bindings.get_action(VirtualKeyCode::Z, ctrl | ModifiersState::SHIFT),
+            Some(HotkeyAction::UndoCameraMove)
+        );
+        assert_eq!(
+            bindings.get_action(VirtualKeyCode::Z, ctrl),
+            Some(HotkeyAction::Undo)
+        );
+    }
+
+    #[test]
+    fn scene_shortcuts_are_bound_by_default() {
+        let bindings = KeyBindings::default();
+        assert_eq!(
+            bindings.get_action(VirtualKeyCode::Space, ModifiersState::empty()),
+            Some(HotkeyAction::ToggleWidgetBasis)
+        );
+        assert_eq!(
+            bindings.get_action(VirtualKeyCode::H, ModifiersState::ALT),
+            Some(HotkeyAction::TurnCameraLeft)
+        );
+        assert_eq!(
+            bindings.get_action(VirtualKeyCode::H, ModifiersState::empty()),
+            Some(HotkeyAction::SelectHelix)
+        );
+    }
+
+    #[test]
+    fn all_key_names_survive_round_trip() {
+        for key in ALL_KEYS {
+            assert_eq!(key_from_name(key_name(*key)), Some(*key));
+            assert_eq!(key_name(*key), format!("{:?}", key));
+        }
+    }
+
+    #[test]
+    fn unused_shift_is_ignored() {
+        let mut bindings = KeyBindings::default();
+        let shift = ModifiersState::SHIFT;
+        assert_eq!(
+            bindings.get_action(VirtualKeyCode::T, shift),
+            Some(HotkeyAction::TranslateMode)
+        );
+        assert_eq!(
+            bindings.get_action(VirtualKeyCode::N, shift),
+            Some(HotkeyAction::SelectNucleotide)
+        );
+        bindings.bind((VirtualKeyCode::T, shift), HotkeyAction::RotateMode, None);
+        assert_eq!(
+            bindings.get_action(VirtualKeyCode::T, shift),
+            Some(HotkeyAction::RotateMode)
+        );
+        assert_eq!(
+            bindings.get_action(VirtualKeyCode::T, ModifiersState::ALT),
+            None
+        );
+    }
+
+    #[test]
+    fn escape_can_be_rebound() {
+        let mut bindings = KeyBindings::default();
+        let none = ModifiersState::empty();
+        bindings.bind(
+            (VirtualKeyCode::Escape, none),
+            HotkeyAction::DeleteSelection,
+            Some((VirtualKeyCode::Delete, none)),
+        );
+        let json = serde_json::to_string(&bindings.to_saved_bindings()).unwrap();
+        let read = KeyBindings::from_json(&json).unwrap();
+        assert_eq!(
+            read.get_action(VirtualKeyCode::Escape, none),
+            Some(HotkeyAction::DeleteSelection)
+        );
+    }
+}
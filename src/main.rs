@@ -74,7 +74,7 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 //!  permitted by the current state of the program. For example an error is returned if the user
 //!  try to modify the design durring a simulation.
 //!
-use std::collections::{HashMap, VecDeque};
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::env;
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
@@ -83,11 +83,15 @@ use std::time::{Duration, Instant};
 pub type PhySize = iced_winit::winit::dpi::PhysicalSize<u32>;
 const TEXTURE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Bgra8UnormSrgb;
 
-use controller::{ChanelReader, ChanelReaderUpdate, SimulationRequest};
+use controller::{
+    BackgroundJob, ChanelReader, ChanelReaderUpdate, FinishedJob, JobError, JobKind, JobOutput,
+    NucleotideColor, RelaxationResult, SimulationRequest,
+};
 use ensnano_design::{Camera, Nucl};
 use ensnano_interactor::application::{Application, Notification};
 use ensnano_interactor::{
-    CenterOfSelection, DesignOperation, DesignReader, RigidBodyConstants, SuggestionParameters,
+    CenterOfSelection, DesignOperation, DesignReader, DesignToMerge, RigidBodyConstants,
+    StrandBuildingParameters, SuggestionParameters, Trajectory, WorkspaceTransform,
 };
 use iced_native::Event as IcedEvent;
 use iced_wgpu::{wgpu, Backend, Renderer, Settings, Viewport};
@@ -117,21 +121,40 @@ use jemallocator::Jemalloc;
 #[global_allocator]
 static GLOBAL: Jemalloc = Jemalloc;
 
+/// Computations of physical properties of the design
+mod analysis;
+/// Named colors defined by the user
+mod color_palette;
+/// Colors of the 3D view chosen by the user
+mod color_theme;
 mod consts;
+/// Number of previous states saved with the designs
+mod edit_history;
 /// Design handling
 //mod design;
 /// Graphical interface drawing
 mod gui;
 //use design::Design;
 //mod mediator;
+/// Keyboard shortcuts
+mod key_bindings;
 /// Separation of the window into drawing regions
 mod multiplexer;
+/// WebAssembly plugins modifying the design
+mod plugins;
+/// Preferences of the user saved between sessions
+mod preferences;
 /// 3D scene drawing
 mod scene;
+/// Constraints on the strands built by the user
+mod strand_building;
 use ensnano_interactor::{
-    graphics::{DrawArea, ElementType, SplitMode},
+    graphics::{
+        AnimationKind, AnimationParameters, CameraAnimation, ColorTheme, DrawArea, ElementType,
+        SplitMode,
+    },
     operation::Operation,
-    ActionMode, Selection, SelectionMode,
+    ActionMode, Selection, SelectionGroupCombination, SelectionMode,
 };
 mod flatscene;
 mod scheduler;
@@ -154,9 +177,12 @@ pub use requests::Requests;
 
 mod dialog;
 
+use color_palette::ColorPalette;
 use flatscene::FlatScene;
 use gui::{ColorOverlay, Gui, IcedMessages, OverlayType, UiSize};
+use key_bindings::{HotkeyAction, KeyBindings, KeyCombination};
 use multiplexer::{Multiplexer, Overlay};
+use plugins::PluginManager;
 use scene::Scene;
 
 fn convert_size(size: PhySize) -> Size<f32> {
@@ -298,6 +324,10 @@ fn main() {
         requests.clone(),
     );
     multiplexer.change_split(SplitMode::Both);
+    messages
+        .lock()
+        .unwrap()
+        .push_key_bindings(multiplexer.key_bindings().clone());
 
     // Initialize the scenes
     let mut encoder =
@@ -353,6 +383,14 @@ fn main() {
     main_state
         .applications
         .insert(ElementType::FlatScene, flat_scene.clone());
+    scene
+        .lock()
+        .unwrap()
+        .subscribe_to_chanel_reader(main_state.chanel_reader.subscribe("scene"));
+    flat_scene
+        .lock()
+        .unwrap()
+        .subscribe_to_chanel_reader(main_state.chanel_reader.subscribe("flat scene"));
 
     // Add a design to the scene if one was given as a command line arguement
     if path.is_some() {
@@ -439,6 +477,23 @@ fn main() {
                     }
                 }
             }
+            Event::WindowEvent {
+                event: WindowEvent::KeyboardInput { .. },
+                ..
+            }
+            | Event::WindowEvent {
+                event: WindowEvent::ReceivedCharacter(_),
+                ..
+            } if scheduler.has_keyboard_priority(ElementType::Scene) => {
+                // The strand sequence being typed in the 3D view bypasses the key bindings
+                if let Event::WindowEvent { event, .. } = event {
+                    if let Some(event) = event.to_static() {
+                        let cursor_position = multiplexer.get_cursor_position();
+                        let state = main_state.get_app_state();
+                        scheduler.forward_event(&event, ElementType::Scene, cursor_position, state);
+                    }
+                }
+            }
             Event::WindowEvent { event, .. } => {
                 //let modifiers = multiplexer.modifiers();
                 if let Some(event) = event.to_static() {
@@ -458,6 +513,19 @@ fn main() {
                             main_state.focussed_element = Some(area);
                             main_state.update_candidates(vec![]);
                         }
+                        if multiplexer.has_overlays()
+                            && !matches!(area, ElementType::Overlay(_))
+                            && matches!(
+                                event,
+                                winit::event::WindowEvent::MouseInput {
+                                    state: winit::event::ElementState::Pressed,
+                                    ..
+                                }
+                            )
+                        {
+                            // A click outside of the context menu closes it
+                            main_state.push_action(Action::CloseContextMenu);
+                        }
                         match area {
                             area if area.is_gui() => {
                                 let event = iced_winit::conversion::window_event(
@@ -469,14 +537,14 @@ fn main() {
                                     gui.forward_event(area, event);
                                 }
                             }
-                            ElementType::Overlay(n) => {
+                            ElementType::Overlay(_) => {
                                 let event = iced_winit::conversion::window_event(
                                     &event,
                                     window.scale_factor(),
                                     modifiers,
                                 );
                                 if let Some(event) = event {
-                                    overlay_manager.forward_event(event, n);
+                                    gui.forward_event(area, event);
                                 }
                             }
                             area if area.is_scene() => {
@@ -517,41 +585,39 @@ fn main() {
                     main_state_view.main_state.wants_fit = false;
                 }
                 controller.make_progress(&mut main_state_view);
+                // Process all the actions of a batch before the applications get updated
+                while main_state_view.main_state.batch_in_progress {
+                    let nb_pending = main_state_view.main_state.pending_actions.len();
+                    if nb_pending == 0 {
+                        main_state_view.main_state.batch_in_progress = false;
+                    } else {
+                        controller.make_progress(&mut main_state_view);
+                        if main_state_view.main_state.pending_actions.len() >= nb_pending {
+                            // The controller is waiting for an input from the user, the remaining
+                            // actions will be processed during the next frames
+                            main_state_view.main_state.batch_in_progress = false;
+                        }
+                    }
+                }
                 resized |= main_state_view.resized;
                 resized |= first_iteration;
                 first_iteration = false;
 
                 for update in main_state.chanel_reader.get_updates() {
-                    if let ChanelReaderUpdate::ScaffoldShiftOptimizationProgress(x) = update {
-                        main_state
-                            .messages
-                            .lock()
-                            .unwrap()
-                            .push_progress("Optimizing: ".to_string(), x);
-                    } else if let ChanelReaderUpdate::ScaffoldShiftOptimizationResult(result) =
-                        update
-                    {
-                        main_state.messages.lock().unwrap().finish_progess();
-                        if let Ok(result) = result {
-                            main_state.apply_operation(DesignOperation::SetScaffoldShift(
-                                result.position,
-                            ));
-                            let msg = format!(
-                                "Scaffold position set to {}\n {}",
-                                result.position, result.score
-                            );
-                            main_state.pending_actions.push_back(Action::ErrorMsg(msg));
-                        } else {
-                            // unwrap because in this block, result is necessarilly an Err
-                            log::warn!("{:?}", result.err().unwrap());
-                        }
+                    if let ChanelReaderUpdate::JobsProgress(jobs) = update {
+                        main_state.messages.lock().unwrap().push_jobs_progress(jobs);
+                    } else if let ChanelReaderUpdate::JobFinished(job) = update {
+                        main_state.load_job_result(job)
                     } else if let ChanelReaderUpdate::SimulationUpdate(update) = update {
                         main_state.app_state.apply_simulation_update(update)
                     } else if let ChanelReaderUpdate::SimulationExpired = update {
                         main_state.update_simulation(SimulationRequest::Stop)
+                    } else if let ChanelReaderUpdate::NucleotideColors(colors) = update {
+                        main_state.apply_nucleotide_colors(colors)
                     }
                 }
 
+                main_state.step_trajectory();
                 main_state.update();
                 let new_title = if let Some(path) = main_state.get_current_file_name() {
                     let path_str = formated_path_end(path);
@@ -736,6 +802,7 @@ impl OverlayManager {
         }
     }
 
+    #[allow(dead_code)]
     fn forward_event(&mut self, event: IcedEvent, n: usize) {
         match self.overlay_types.get(n) {
             None => {
@@ -918,6 +985,18 @@ pub(crate) struct MainState {
     file_name: Option<PathBuf>,
     wants_fit: bool,
     last_backup_date: Instant,
+    /// True if the pending actions must be processed without intermediate redraws
+    batch_in_progress: bool,
+    /// The recorder of the design operations, if a macro is being recorded
+    macro_recorder: Option<MacroRecorder>,
+    /// The log of all the design operations applied during the session
+    session_log: SessionLog,
+    plugins: PluginManager,
+    color_palette: ColorPalette,
+    /// The date at which the last frame of the trajectory that is being played was displayed
+    last_trajectory_step: Instant,
+    /// The maximum number of undoable states embedded in the saved files
+    edit_history_size: usize,
 }
 
 struct MainStateConstructor {
@@ -927,13 +1006,54 @@ struct MainStateConstructor {
 use controller::SaveDesignError;
 impl MainState {
     fn new(constructor: MainStateConstructor) -> Self {
-        let app_state = AppState::default();
+        let strand_building_parameters = strand_building::load();
+        let app_state =
+            AppState::default().with_strand_building_parameters(strand_building_parameters.clone());
+        let mut chanel_reader = ChanelReader::default();
+        chanel_reader.listen_to_nucleotide_colors();
+        let plugins = PluginManager::load();
+        constructor
+            .messages
+            .lock()
+            .unwrap()
+            .push_plugin_actions(plugins.menu_actions());
+        let color_palette = ColorPalette::load();
+        let edit_history_size = edit_history::load_size();
+        constructor
+            .messages
+            .lock()
+            .unwrap()
+            .push_edit_history_size(edit_history_size);
+        constructor
+            .messages
+            .lock()
+            .unwrap()
+            .push_session_log_enabled(SessionLog::is_enabled());
+        constructor
+            .messages
+            .lock()
+            .unwrap()
+            .push_strand_building_parameters(strand_building_parameters);
+        constructor
+            .messages
+            .lock()
+            .unwrap()
+            .push_color_palette(color_palette.clone());
+        let color_theme = color_theme::load();
+        constructor
+            .messages
+            .lock()
+            .unwrap()
+            .push_color_theme(color_theme.clone());
+        let mut pending_actions = VecDeque::new();
+        // The applications are notified once they are registered
+        pending_actions.push_back(Action::NotifyApps(Notification::ColorTheme(color_theme)));
         Self {
             app_state: app_state.clone(),
-            pending_actions: VecDeque::new(),
+            pending_actions,
             undo_stack: Vec::new(),
             redo_stack: Vec::new(),
-            chanel_reader: Default::default(),
+            chanel_reader,
             messages: constructor.messages,
             applications: Default::default(),
             focussed_element: None,
@@ -942,9 +1062,29 @@ impl MainState {
             file_name: None,
             wants_fit: false,
             last_backup_date: Instant::now(),
+            batch_in_progress: false,
+            macro_recorder: None,
+            session_log: SessionLog::open(),
+            plugins,
+            color_palette,
+            last_trajectory_step: Instant::now(),
+            edit_history_size,
         }
     }
 
+    /// Replace the palette of named colors, save it in the preferences of the user and show it
+    /// in the GUI
+    fn set_color_palette(&mut self, palette: ColorPalette) {
+        if let Err(e) = palette.save() {
+            log::error!("Could not save color palette: {}", e);
+        }
+        self.messages
+            .lock()
+            .unwrap()
+            .push_color_palette(palette.clone());
+        self.color_palette = palette;
+    }
+
     fn push_action(&mut self, action: Action) {
         self.pending_actions.push_back(action)
     }
@@ -960,6 +1100,10 @@ impl MainState {
     }
 
     fn clear_app_state(&mut self, new_state: AppState) {
+        // The strand building parameters are preferences of the user, not of the design
+        let new_state = new_state.with_strand_building_parameters(
+            self.app_state.get_strand_building_parameters().clone(),
+        );
         self.undo_stack.clear();
         self.redo_stack.clear();
         self.app_state = new_state.clone();
@@ -990,6 +1134,20 @@ impl MainState {
         selection: Vec<Selection>,
         group_id: Option<ensnano_organizer::GroupId>,
     ) {
+        if !self.plugins.is_empty() {
+            let nucls: Vec<Nucl> = selection
+                .iter()
+                .filter_map(|s| {
+                    if let Selection::Nucleotide(_, nucl) = s {
+                        Some(*nucl)
+                    } else {
+                        None
+                    }
+                })
+                .collect();
+            let job = self.plugins.on_selection_changed(nucls);
+            self.chanel_reader.attach_job(JobKind::Plugin, job);
+        }
         self.modify_state(|s| s.with_selection(selection, group_id), true);
     }
 
@@ -1003,6 +1161,13 @@ impl MainState {
     }
 
     fn apply_operation(&mut self, operation: DesignOperation) {
+        if let Err(e) = self.try_apply_operation(operation) {
+            log::warn!("{:?}", e);
+        }
+    }
+
+    /// Apply `operation` and return the error that prevented it from being applied, if any
+    fn try_apply_operation(&mut self, operation: DesignOperation) -> Result<(), ErrOperation> {
         log::debug!("Applying operation {:?}", operation);
         let result = self.app_state.apply_design_op(operation.clone());
         if let Err(ErrOperation::FinishFirst) = result {
@@ -1010,10 +1175,57 @@ impl MainState {
                 |s| s.notified(app_state::InteractorNotification::FinishOperation),
                 false,
             );
-            self.apply_operation(operation);
+            self.try_apply_operation(operation)
         } else {
-            self.apply_operation_result(result);
+            let old_state = result?;
+            self.session_log.record(&operation);
+            if let Some(old_state) = old_state {
+                self.save_old_state(old_state);
+            }
+            Ok(())
+        }
+    }
+
+    /// Open the cyclic scaffold at `at` and return the continuity of the resulting scaffold
+    fn break_scaffold_at(
+        &mut self,
+        at: Nucl,
+    ) -> Result<ensnano_design::ScaffoldContinuityResult, ErrOperation> {
+        let operation = DesignOperation::BreakScaffoldAt { at };
+        let result = self.app_state.apply_design_op(operation.clone())?;
+        self.session_log.record(&operation);
+        if let Some(old_state) = result {
+            self.save_old_state(old_state);
         }
+        // Update the state so that the continuity is computed on the opened scaffold
+        self.app_state.update();
+        Ok(self.app_state.get_design_reader().get_scaffold_continuity())
+    }
+
+    /// Merge the design `design_id` of the workspace with `operation` and remove it from the
+    /// workspace. The workspace is left untouched if the merge fails.
+    fn merge_workspace_design(
+        &mut self,
+        design_id: usize,
+        operation: DesignOperation,
+    ) -> Result<(), ErrOperation> {
+        let result = self.app_state.apply_design_op(operation.clone())?;
+        self.session_log.record(&operation);
+        if let Some(old_state) = result {
+            self.save_old_state(old_state);
+        }
+        self.modify_state(|s| s.without_workspace_design(design_id), false);
+        Ok(())
+    }
+
+    /// Apply `operations` in order so that they are undone in one step
+    fn apply_operations(&mut self, operations: Vec<DesignOperation>) {
+        let nb_undoable_states = self.undo_stack.len();
+        for operation in operations {
+            self.apply_operation(operation);
+        }
+        // Only keep the state that preceded the first operation
+        self.undo_stack.truncate(nb_undoable_states + 1);
     }
 
     fn start_helix_simulation(&mut self, parameters: RigidBodyConstants) {
@@ -1043,6 +1255,15 @@ impl MainState {
         self.apply_operation_result(result)
     }
 
+    fn optimize_rolls(&mut self, target_helices: Option<Vec<usize>>) {
+        let result = self.app_state.start_simulation(
+            Default::default(),
+            &mut self.chanel_reader,
+            SimulationTarget::OptimizeRolls { target_helices },
+        );
+        self.apply_operation_result(result)
+    }
+
     fn update_simulation(&mut self, request: SimulationRequest) {
         let result = self.app_state.update_simulation(request);
         self.apply_operation_result(result);
@@ -1107,7 +1328,186 @@ impl MainState {
         }
     }
 
+    /// Apply `modification` to the loaded trajectory, if any
+    fn update_trajectory<F>(&mut self, modification: F)
+    where
+        F: FnOnce(&mut Trajectory),
+    {
+        if let Some(mut trajectory) = self.app_state.get_trajectory().cloned() {
+            modification(&mut trajectory);
+            self.modify_state(|s| s.with_trajectory(Some(trajectory)), false);
+        }
+    }
+
+    /// Display the next frame of the loaded trajectory if it is being played and if the
+    /// previous frame has been displayed long enough
+    fn step_trajectory(&mut self) {
+        let playing = self
+            .app_state
+            .get_trajectory()
+            .map(|t| t.playing)
+            .unwrap_or(false);
+        let frame_duration = Duration::from_millis(crate::consts::TRAJECTORY_FRAME_DURATION_MS);
+        if playing && self.last_trajectory_step.elapsed() >= frame_duration {
+            self.last_trajectory_step = Instant::now();
+            self.update_trajectory(Trajectory::step);
+        }
+    }
+
+    fn load_job_result(&mut self, job: FinishedJob) {
+        match job.result {
+            Ok(JobOutput::ScaffoldShift(result)) => {
+                self.apply_operation(DesignOperation::SetScaffoldShift(result.position));
+                let msg = format!(
+                    "Scaffold position set to {}\n {}",
+                    result.position, result.score
+                );
+                self.pending_actions.push_back(Action::ErrorMsg(msg));
+            }
+            Ok(JobOutput::Relaxation(result)) => self.load_relaxation_result(result),
+            Ok(JobOutput::Trajectory(trajectory)) => self.load_trajectory(trajectory),
+            Ok(JobOutput::SecondaryStructure(structure)) => {
+                self.load_secondary_structure(structure)
+            }
+            Ok(JobOutput::Animation {
+                nb_frames,
+                directory,
+            }) => {
+                let msg = format!(
+                    "Successfully wrote {} frames in {}",
+                    nb_frames,
+                    directory.to_string_lossy()
+                );
+                self.push_action(Action::InfoMsg(msg));
+            }
+            Ok(JobOutput::StapleExport(path)) => {
+                let msg = format!("Successfully wrote staples in {}", path.to_string_lossy());
+                self.push_action(Action::InfoMsg(msg));
+            }
+            Ok(JobOutput::Plugin(output)) => {
+                // The operations are pushed as an action so that they are recorded in the macros
+                // like the ones performed by the user.
+                if !output.operations.is_empty() {
+                    self.push_action(Action::DesignOperations(output.operations));
+                }
+                if !output.errors.is_empty() {
+                    self.push_action(Action::ErrorMsg(output.errors.join("\n")));
+                }
+            }
+            Err(e) => {
+                if job.kind == JobKind::ScaffoldShiftOptimization {
+                    self.modify_state(
+                        |s| s.notified(app_state::InteractorNotification::ShiftOptimizationAborted),
+                        false,
+                    );
+                }
+                if let JobError::Canceled = e {
+                    log::info!("{} canceled", job.kind.name());
+                } else {
+                    let msg = format!("{} did not complete: {}", job.kind.name(), e);
+                    self.pending_actions.push_back(Action::ErrorMsg(msg));
+                }
+            }
+        }
+    }
+
+    /// Display a trajectory that was read by a background job, unless the design was modified
+    /// while it was read
+    fn load_trajectory(&mut self, trajectory: Trajectory) {
+        if self
+            .app_state
+            .oxdna_trajectory_reader()
+            .matches(&trajectory)
+        {
+            let msg = format!(
+                "Loaded a trajectory of {} frames.\n\
+                Use the trajectory tab to play it.",
+                trajectory.nb_frames()
+            );
+            self.modify_state(|s| s.with_trajectory(Some(trajectory)), false);
+            self.push_action(Action::InfoMsg(msg));
+        } else {
+            self.push_action(Action::ErrorMsg(
+                "The design was modified while the trajectory was read".to_string(),
+            ));
+        }
+    }
+
+    /// Fit the helices of the design to the relaxed conformation, which is the last frame of the
+    /// trajectory of the relaxation
+    fn load_relaxation_result(&mut self, result: RelaxationResult) {
+        let mut trajectory = result.trajectory;
+        if !self
+            .app_state
+            .oxdna_trajectory_reader()
+            .matches(&trajectory)
+        {
+            self.push_action(Action::ErrorMsg(
+                "The design was modified during the relaxation.\n\
+                The relaxed conformation was not imported"
+                    .to_string(),
+            ));
+            return;
+        }
+        trajectory.go_to_frame(trajectory.nb_frames());
+        let placements = self.app_state.helices_fitted_to_trajectory(&trajectory);
+        // The operation is pushed as an action so that it is recorded in the macros like the
+        // ones performed by the user.
+        self.push_action(Action::DesignOperation(DesignOperation::PlaceHelices(
+            placements,
+        )));
+        self.push_action(Action::InfoMsg(
+            "Relaxation finished.\n\
+            The helices were moved to the relaxed conformation"
+                .to_string(),
+        ));
+    }
+
+    /// Color the unpaired nucleotides of a strand whose secondary structure was predicted
+    fn load_secondary_structure(&mut self, structure: controller::SecondaryStructure) {
+        let nucls = &structure.nucls;
+        let nb_pairs_in_design = structure
+            .pairs
+            .iter()
+            .filter(|(i, j)| nucls[*i].compl() == nucls[*j])
+            .count();
+        let mut nucl_colors = self.app_state.get_nucleotide_colors().clone();
+        for nucl in nucls.iter() {
+            nucl_colors.remove(nucl);
+        }
+        for i in structure.unpaired.iter() {
+            nucl_colors.insert(nucls[*i], crate::consts::UNPAIRED_NUCL_COLOR);
+        }
+        self.modify_state(|s| s.with_nucleotide_colors(nucl_colors), false);
+        let prediction = controller::SecondaryStructurePrediction {
+            nb_pairs: structure.pairs.len(),
+            nb_pairs_in_design,
+            nb_unpaired: structure.unpaired.len(),
+        };
+        self.push_action(Action::InfoMsg(prediction.to_string()));
+    }
+
+    fn apply_nucleotide_colors(&mut self, colors: Vec<NucleotideColor>) {
+        let reader = self.app_state.get_design_reader();
+        let mut nucl_colors = self.app_state.get_nucleotide_colors().clone();
+        for request in colors {
+            if let Some(nucl) = reader.get_nth_nucl_of_strand(request.strand, request.pos) {
+                nucl_colors.insert(nucl, request.color);
+            } else {
+                log::warn!(
+                    "Strand {} has no nucleotide at position {}",
+                    request.strand,
+                    request.pos
+                );
+            }
+        }
+        self.modify_state(|s| s.with_nucleotide_colors(nucl_colors), false);
+    }
+
     fn update_pending_operation(&mut self, operation: Arc<dyn Operation>) {
+        // The operation is applied to the design on which its previous effect was applied
+        let replaces_previous =
+            operation.replace_previous() && !self.app_state.is_in_stable_state();
         let result = self.app_state.update_pending_operation(operation.clone());
         if let Err(ErrOperation::FinishFirst) = result {
             self.modify_state(
@@ -1115,8 +1515,14 @@ impl MainState {
                 false,
             );
             self.update_pending_operation(operation)
+        } else {
+            if result.is_ok() {
+                if let Some(recorder) = self.macro_recorder.as_mut() {
+                    recorder.record_pending(operation.effect(), replaces_previous);
+                }
+            }
+            self.apply_operation_result(result);
         }
-        self.apply_operation_result(result);
     }
 
     fn optimize_shift(&mut self) {
@@ -1175,7 +1581,9 @@ impl MainState {
         }
     }
 
-    fn save_design(&mut self, path: &PathBuf) -> Result<(), SaveDesignError> {
+    /// The information saved with the design: the camera of the 3D view and the most recent
+    /// undoable states of the design
+    fn saving_information(&self) -> ensnano_design::SavingInformation {
         let camera = self
             .applications
             .get(&ElementType::Scene)
@@ -1186,7 +1594,31 @@ impl MainState {
                 position,
                 orientation,
             });
-        let save_info = ensnano_design::SavingInformation { camera };
+        let nb_skipped = self.undo_stack.len().saturating_sub(self.edit_history_size);
+        let edit_history = self.undo_stack[nb_skipped..]
+            .iter()
+            .map(|state| state.get_design_reader().get_design_snapshot())
+            .collect();
+        ensnano_design::SavingInformation {
+            camera,
+            edit_history,
+        }
+    }
+
+    /// Replace the current design by the one read from `path`. The states saved with the design
+    /// are put on the undo stack so that they can be restored by undoing.
+    fn import_design_with_history(
+        &mut self,
+        path: &PathBuf,
+    ) -> Result<(), app_state::ParseDesignError> {
+        let (state, history) = AppState::import_design_with_history(path)?;
+        self.clear_app_state(state);
+        self.undo_stack = history;
+        Ok(())
+    }
+
+    fn save_design(&mut self, path: &PathBuf) -> Result<(), SaveDesignError> {
+        let save_info = self.saving_information();
         self.app_state
             .get_design_reader()
             .save_design(path, save_info)?;
@@ -1197,17 +1629,7 @@ impl MainState {
     }
 
     fn save_backup(&mut self) -> Result<(), SaveDesignError> {
-        let camera = self
-            .applications
-            .get(&ElementType::Scene)
-            .and_then(|s| s.lock().unwrap().get_camera())
-            .map(|(position, orientation)| Camera {
-                id: Default::default(),
-                name: String::from("Saved Camera"),
-                position,
-                orientation,
-            });
-        let save_info = ensnano_design::SavingInformation { camera };
+        let save_info = self.saving_information();
         let path = if let Some(mut path) = self.path_to_current_design.clone() {
             path.set_extension(crate::consts::ENS_BACKUP_EXTENSION);
             path
@@ -1233,6 +1655,10 @@ impl MainState {
         self.modify_state(|s| s.with_selection_mode(mode), false)
     }
 
+    fn set_selection_lock(&mut self, locked: bool) {
+        self.modify_state(|s| s.with_selection_lock(locked), false)
+    }
+
     fn change_action_mode(&mut self, mode: ActionMode) {
         self.modify_state(|s| s.with_action_mode(mode), false)
     }
@@ -1270,6 +1696,24 @@ impl MainState {
         self.modify_state(|s| s.with_suggestion_parameters(param), false)
     }
 
+    /// Set the visible ranges of some helices of the 2D view. The modification is recorded in the
+    /// undo stack, from which the ranges `old` are restored.
+    fn set_helix_2d_ranges(
+        &mut self,
+        old: BTreeMap<usize, (isize, isize)>,
+        new: BTreeMap<usize, (isize, isize)>,
+    ) {
+        self.modify_state(|s| s.with_helix_2d_ranges(old), false);
+        self.modify_state(|s| s.with_helix_2d_ranges(new), true);
+    }
+
+    fn set_strand_building_parameters(&mut self, param: StrandBuildingParameters) {
+        if let Err(e) = strand_building::save(&param) {
+            log::error!("Could not save strand building parameters: {}", e);
+        }
+        self.modify_state(|s| s.with_strand_building_parameters(param), false)
+    }
+
     fn gui_state(&self, multiplexer: &Multiplexer) -> gui::MainState {
         gui::MainState {
             can_undo: !self.undo_stack.is_empty(),
@@ -1277,6 +1721,7 @@ impl MainState {
             need_save: self.need_save(),
             can_reload: self.get_current_file_name().is_some(),
             can_split2d: multiplexer.is_showing(&ElementType::FlatScene),
+            recording_macro: self.macro_recorder.is_some(),
             splited_2d: self
                 .applications
                 .get(&ElementType::FlatScene)
@@ -1297,7 +1742,10 @@ struct MainStateView<'a> {
     resized: bool,
 }
 
-use controller::{LoadDesignError, MainState as MainStateInteface, StaplesDownloader};
+use controller::{
+    CameraViewError, LoadDesignError, MacroRecorder, MainState as MainStateInteface,
+    OperationMacro, SessionLog, StaplesDownloader,
+};
 impl<'a> MainStateInteface for MainStateView<'a> {
     fn pop_action(&mut self) -> Option<Action> {
         if self.main_state.pending_actions.len() > 0 {
@@ -1312,6 +1760,7 @@ impl<'a> MainStateInteface for MainStateView<'a> {
     }
 
     fn exit_control_flow(&mut self) {
+        self.main_state.session_log.close();
         *self.control_flow = ControlFlow::Exit
     }
 
@@ -1323,12 +1772,232 @@ impl<'a> MainStateInteface for MainStateView<'a> {
         self.main_state.app_state.oxdna_export(path)
     }
 
+    fn pdb_export(&mut self, path: &PathBuf) -> std::io::Result<()> {
+        self.main_state.app_state.pdb_export(path)
+    }
+
+    fn export_subdesign(&mut self, helix_ids: &[usize], path: &PathBuf) -> std::io::Result<()> {
+        self.main_state.app_state.export_subdesign(helix_ids, path)
+    }
+
+    fn oxdna_trajectory_reader(&self) -> app_state::OxDnaTrajectoryReader {
+        self.main_state.app_state.oxdna_trajectory_reader()
+    }
+
+    fn play_trajectory(&mut self, playing: bool) {
+        self.main_state
+            .update_trajectory(|trajectory| trajectory.set_playing(playing))
+    }
+
+    fn set_trajectory_frame(&mut self, frame: usize) {
+        self.main_state
+            .update_trajectory(|trajectory| trajectory.go_to_frame(frame))
+    }
+
+    fn close_trajectory(&mut self) {
+        self.main_state
+            .modify_state(|s| s.with_trajectory(None), false)
+    }
+
+    fn open_design_in_workspace(&mut self, path: PathBuf) -> Result<(), LoadDesignError> {
+        let state = self
+            .main_state
+            .app_state
+            .with_design_in_workspace(&path)
+            .map_err(|_| {
+                LoadDesignError::from(format!("could not read {}", path.to_string_lossy()))
+            })?;
+        self.main_state.modify_state(|_| state, false);
+        Ok(())
+    }
+
+    fn read_design_to_merge(&self, path: &PathBuf) -> Result<DesignToMerge, LoadDesignError> {
+        let error = || LoadDesignError::from(format!("could not read {}", path.to_string_lossy()));
+        if !path.is_file() {
+            return Err(error());
+        }
+        app_state::read_design_to_merge(path)
+            .map(|design| DesignToMerge(Box::new(design)))
+            .map_err(|_| error())
+    }
+
+    fn set_main_design_visibility(&mut self, visible: bool) {
+        self.main_state
+            .modify_state(|s| s.with_main_design_visibility(visible), false)
+    }
+
+    fn set_workspace_design_visibility(&mut self, design_id: usize, visible: bool) {
+        self.main_state.modify_state(
+            |s| s.with_workspace_design_visibility(design_id, visible),
+            false,
+        )
+    }
+
+    fn set_workspace_design_transform(&mut self, design_id: usize, transform: WorkspaceTransform) {
+        self.main_state.modify_state(
+            |s| s.with_workspace_design_transform(design_id, transform),
+            false,
+        )
+    }
+
+    fn close_workspace_design(&mut self, design_id: usize) {
+        self.main_state
+            .modify_state(|s| s.without_workspace_design(design_id), false)
+    }
+
+    fn merge_workspace_design(&mut self, design_id: usize) -> Result<(), ErrOperation> {
+        if let Some(operation) = self
+            .main_state
+            .app_state
+            .get_workspace_merge_operation(design_id)
+        {
+            if let Some(recorder) = self.main_state.macro_recorder.as_mut() {
+                recorder.record(operation.clone());
+            }
+            self.main_state.merge_workspace_design(design_id, operation)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn dock_workspace_design(&mut self, design_id: usize, helix: usize) -> bool {
+        let target_helix =
+            ensnano_interactor::list_of_helices(self.main_state.app_state.get_selection().as_ref())
+                .and_then(|(_, helices)| helices.first().cloned());
+        let state = target_helix.and_then(|target_helix| {
+            self.main_state
+                .app_state
+                .with_workspace_design_docked(design_id, helix, target_helix)
+        });
+        if let Some(state) = state {
+            self.main_state.modify_state(|_| state, false);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn is_job_running(&self, kind: JobKind) -> bool {
+        self.main_state.chanel_reader.is_job_running(kind)
+    }
+
+    fn attach_job(&mut self, kind: JobKind, job: BackgroundJob<JobOutput>) {
+        self.main_state.chanel_reader.attach_job(kind, job)
+    }
+
+    fn cancel_job(&mut self, job_id: usize) {
+        self.main_state.chanel_reader.cancel_job(job_id)
+    }
+
+    fn cadnano_export(&mut self, path: &PathBuf) -> Result<(), app_state::CadnanoExportError> {
+        self.main_state.app_state.cadnano_export(path)
+    }
+
+    fn connectivity_graph_export(&mut self, path: &PathBuf) -> std::io::Result<()> {
+        self.main_state.app_state.connectivity_graph_export(path)
+    }
+
+    fn export_helix_map_png(
+        &mut self,
+        dpi: u32,
+        size_mm: (f32, f32),
+        path: &PathBuf,
+    ) -> std::io::Result<()> {
+        let flat_scene = self
+            .main_state
+            .applications
+            .get(&ElementType::FlatScene)
+            .ok_or_else(|| {
+                std::io::Error::new(std::io::ErrorKind::Other, "The 2D view is not available")
+            })?;
+        flat_scene
+            .lock()
+            .unwrap()
+            .export_helix_map_png(dpi, size_mm, path)
+    }
+
+    fn export_scene_png(&mut self, size: (u32, u32), path: &PathBuf) -> std::io::Result<()> {
+        let scene = self
+            .main_state
+            .applications
+            .get(&ElementType::Scene)
+            .ok_or_else(|| {
+                std::io::Error::new(std::io::ErrorKind::Other, "The 3D view is not available")
+            })?;
+        scene.lock().unwrap().export_scene_png(size, path)
+    }
+
+    fn animation_viewpoints(
+        &mut self,
+        parameters: AnimationParameters,
+    ) -> std::io::Result<Vec<(Vec3, Rotor3)>> {
+        let animation = match parameters.kind {
+            AnimationKind::Turntable => CameraAnimation::Turntable,
+            AnimationKind::Keyframes => {
+                let keyframes = self
+                    .main_state
+                    .app_state
+                    .get_design_reader()
+                    .get_all_camera_viewpoints();
+                if keyframes.is_empty() {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::Other,
+                        "The design has no stored camera",
+                    ));
+                }
+                CameraAnimation::Keyframes(keyframes)
+            }
+        };
+        let scene = self
+            .main_state
+            .applications
+            .get(&ElementType::Scene)
+            .ok_or_else(|| {
+                std::io::Error::new(std::io::ErrorKind::Other, "The 3D view is not available")
+            })?;
+        let viewpoints = scene
+            .lock()
+            .unwrap()
+            .animation_viewpoints(&animation, parameters.nb_frames);
+        Ok(viewpoints)
+    }
+
+    fn render_scene_viewpoint(
+        &mut self,
+        viewpoint: (Vec3, Rotor3),
+        size: (u32, u32),
+    ) -> std::io::Result<Vec<u8>> {
+        let scene = self
+            .main_state
+            .applications
+            .get(&ElementType::Scene)
+            .ok_or_else(|| {
+                std::io::Error::new(std::io::ErrorKind::Other, "The 3D view is not available")
+            })?;
+        scene.lock().unwrap().render_viewpoint(viewpoint, size)
+    }
+
+    fn export_mesh(&mut self, path: &PathBuf) -> std::io::Result<()> {
+        let scene = self
+            .main_state
+            .applications
+            .get(&ElementType::Scene)
+            .ok_or_else(|| {
+                std::io::Error::new(std::io::ErrorKind::Other, "The 3D view is not available")
+            })?;
+        scene.lock().unwrap().export_mesh(path)
+    }
+
     fn load_design(&mut self, mut path: PathBuf) -> Result<(), LoadDesignError> {
-        if let Ok(state) = AppState::import_design(&path) {
-            self.main_state.clear_app_state(state);
-            if path.extension().map(|s| s.to_string_lossy())
-                == Some(crate::consts::ENS_BACKUP_EXTENSION.into())
+        if self.main_state.import_design_with_history(&path).is_ok() {
+            let extension = path.extension().map(|s| s.to_string_lossy().into_owned());
+            if extension.as_deref() == Some(crate::consts::ENS_BACKUP_EXTENSION)
+                || extension
+                    .as_deref()
+                    .map(|e| crate::consts::SCADNANO_EXTENSIONS.contains(&e))
+                    .unwrap_or(false)
             {
+                // Imported scadnano designs are saved in ENSnano files next to the original one
                 path.set_extension(crate::consts::ENS_EXTENSION);
             }
             self.main_state.path_to_current_design = Some(path.clone());
@@ -1354,13 +2023,46 @@ impl<'a> MainStateInteface for MainStateView<'a> {
     }
 
     fn apply_operation(&mut self, operation: DesignOperation) {
+        if let Some(recorder) = self.main_state.macro_recorder.as_mut() {
+            recorder.record(operation.clone());
+        }
         self.main_state.apply_operation(operation)
     }
 
+    fn try_apply_operation(&mut self, operation: DesignOperation) -> Result<(), ErrOperation> {
+        if let Some(recorder) = self.main_state.macro_recorder.as_mut() {
+            recorder.record(operation.clone());
+        }
+        self.main_state.try_apply_operation(operation)
+    }
+
+    fn apply_operations(&mut self, operations: Vec<DesignOperation>) {
+        for operation in operations.iter() {
+            if let Some(recorder) = self.main_state.macro_recorder.as_mut() {
+                recorder.record(operation.clone());
+            }
+        }
+        self.main_state.apply_operations(operations)
+    }
+
     fn apply_silent_operation(&mut self, operation: DesignOperation) {
+        if let Some(recorder) = self.main_state.macro_recorder.as_mut() {
+            recorder.record(operation.clone());
+        }
         self.main_state.apply_silent_operation(operation)
     }
 
+    fn break_scaffold_at(
+        &mut self,
+        at: Nucl,
+    ) -> Result<ensnano_design::ScaffoldContinuityResult, ErrOperation> {
+        let operation = DesignOperation::BreakScaffoldAt { at };
+        if let Some(recorder) = self.main_state.macro_recorder.as_mut() {
+            recorder.record(operation.clone());
+        }
+        self.main_state.break_scaffold_at(at)
+    }
+
     fn undo(&mut self) {
         self.main_state.undo();
     }
@@ -1369,6 +2071,13 @@ impl<'a> MainStateInteface for MainStateView<'a> {
         self.main_state.redo();
     }
 
+    fn reject_short_built_strands(&mut self) -> Result<(), ErrOperation> {
+        let mut new_state = self.main_state.app_state.clone();
+        let result = new_state.reject_short_built_strands();
+        self.main_state.modify_state(|_| new_state, false);
+        result
+    }
+
     fn get_staple_downloader(&self) -> Box<dyn StaplesDownloader> {
         Box::new(self.main_state.app_state.get_design_reader())
     }
@@ -1392,6 +2101,37 @@ impl<'a> MainStateInteface for MainStateView<'a> {
         self.gui.resize(self.multiplexer, self.window);
     }
 
+    fn open_context_menu(&mut self) {
+        let entries = gui::ContextMenuEntry::for_selection(&self.main_state.app_state);
+        if entries.is_empty() {
+            log::info!("No action can be applied to the selection");
+            return;
+        }
+        let size = self.gui.context_menu_size(entries.len(), self.window);
+        // The cursor position is given in the coordinates of the foccused element
+        let origin = self
+            .multiplexer
+            .foccused_element()
+            .and_then(|element| self.multiplexer.get_draw_area(element))
+            .map(|area| area.position)
+            .unwrap_or_else(|| PhysicalPosition::new(0, 0));
+        let cursor = self.multiplexer.get_cursor_position().cast::<u32>();
+        let window_size = self.window.inner_size();
+        let position = PhysicalPosition::new(
+            (origin.x + cursor.x).min(window_size.width.saturating_sub(size.width)),
+            (origin.y + cursor.y).min(window_size.height.saturating_sub(size.height)),
+        );
+        self.multiplexer
+            .set_overlays(vec![Overlay { position, size }]);
+        self.gui
+            .open_context_menu(entries, self.window, self.multiplexer);
+    }
+
+    fn close_context_menu(&mut self) {
+        self.gui.close_context_menu();
+        self.multiplexer.set_overlays(vec![]);
+    }
+
     fn change_ui_size(&mut self, ui_size: UiSize) {
         self.gui
             .new_ui_size(ui_size.clone(), self.window, self.multiplexer);
@@ -1410,6 +2150,30 @@ impl<'a> MainStateInteface for MainStateView<'a> {
         self.multiplexer.invert_y_scroll = inverted;
     }
 
+    fn record_key_binding(&mut self, action: HotkeyAction, previous: Option<KeyCombination>) {
+        self.multiplexer
+            .start_recording_key_binding(action, previous);
+    }
+
+    fn cancel_key_binding_recording(&mut self) {
+        self.multiplexer.cancel_key_binding_recording();
+    }
+
+    fn update_key_bindings(&mut self, bindings: KeyBindings) {
+        self.main_state
+            .messages
+            .lock()
+            .unwrap()
+            .push_key_bindings(bindings);
+    }
+
+    fn trigger_plugin_action(&mut self, plugin_id: usize, name: String) {
+        let job = self.main_state.plugins.on_menu_action(plugin_id, name);
+        self.main_state
+            .chanel_reader
+            .attach_job(JobKind::Plugin, job);
+    }
+
     fn notify_apps(&mut self, notificiation: Notification) {
         for app in self.main_state.applications.values_mut() {
             app.lock().unwrap().on_notify(notificiation.clone())
@@ -1498,6 +2262,69 @@ impl<'a> MainStateInteface for MainStateView<'a> {
         }
     }
 
+    fn highlight_shortest_path(&mut self) -> Option<usize> {
+        let (from, to) = ensnano_interactor::pair_of_nucleotides(
+            self.main_state.app_state.get_selection().as_ref(),
+        )?;
+        let path = self
+            .main_state
+            .app_state
+            .get_design_reader()
+            .find_shortest_path(from, to)?;
+        let length = path.len();
+        self.main_state
+            .modify_state(|s| s.with_highlighted_path(path), false);
+        Some(length)
+    }
+
+    fn go_to_xover(&mut self, a: (usize, usize), b: (usize, usize)) -> bool {
+        let reader = self.main_state.app_state.get_design_reader();
+        let nucls = reader
+            .get_nth_nucl_of_strand(a.0, a.1)
+            .zip(reader.get_nth_nucl_of_strand(b.0, b.1));
+        // The nucleotides may be given in any order
+        let xover = nucls.and_then(|(n1, n2)| {
+            reader
+                .get_xover_id(&(n1, n2))
+                .map(|id| (id, n1, n2))
+                .or_else(|| reader.get_xover_id(&(n2, n1)).map(|id| (id, n2, n1)))
+        });
+        if let Some((xover_id, prime5, prime3)) = xover {
+            let selection = Selection::Xover(0, xover_id);
+            self.main_state.update_selection(vec![selection], None);
+            self.notify_apps(Notification::CenterSelection(
+                selection,
+                ensnano_interactor::application::AppId::Mediator,
+            ));
+            self.notify_apps(Notification::FlashXover(prime5, prime3));
+            true
+        } else {
+            false
+        }
+    }
+
+    fn predict_secondary_structure(&mut self, s_id: usize) -> Result<(), controller::MfeError> {
+        let reader = self.main_state.app_state.get_design_reader();
+        let length = reader
+            .get_strand_with_id(s_id)
+            .ok_or(controller::MfeError::StrandNotFound(s_id))?
+            .length();
+        // Insertions have no position on a helix and are skipped
+        let nucls: Vec<Nucl> = (0..length)
+            .filter_map(|n| reader.get_nth_nucl_of_strand(s_id, n))
+            .collect();
+        let basis_map = flatscene::DesignReader::get_basis_map(&reader);
+        let sequence: String = nucls
+            .iter()
+            .map(|nucl| basis_map.get(nucl).ok_or(controller::MfeError::NoSequence))
+            .collect::<Result<_, _>>()?;
+        self.main_state.chanel_reader.attach_job(
+            JobKind::SecondaryStructure,
+            controller::secondary_structure_job(nucls, sequence),
+        );
+        Ok(())
+    }
+
     fn start_helix_simulation(&mut self, parameters: RigidBodyConstants) {
         self.main_state.start_helix_simulation(parameters);
     }
@@ -1510,6 +2337,10 @@ impl<'a> MainStateInteface for MainStateView<'a> {
         self.main_state.start_roll_simulation(target_helices);
     }
 
+    fn optimize_rolls(&mut self, target_helices: Option<Vec<usize>>) {
+        self.main_state.optimize_rolls(target_helices);
+    }
+
     fn update_simulation(&mut self, request: SimulationRequest) {
         self.main_state.update_simulation(request)
     }
@@ -1535,6 +2366,51 @@ impl<'a> MainStateInteface for MainStateView<'a> {
         self.main_state.set_visibility_sieve(vec![], true);
     }
 
+    fn add_measurement(&mut self, measurement: ensnano_interactor::Measurement) {
+        let mut measurements = self.main_state.app_state.get_measurements().to_vec();
+        measurements.push(measurement);
+        self.main_state
+            .modify_state(|s| s.with_measurements(measurements), false);
+    }
+
+    fn clear_measurements(&mut self) {
+        self.main_state
+            .modify_state(|s| s.with_measurements(vec![]), false);
+    }
+
+    fn save_selection_group(&mut self, name: String) {
+        use ensnano_design::elements::DnaElementKey;
+        use ensnano_interactor::SelectionConversion;
+        let elements = self
+            .main_state
+            .app_state
+            .get_selection()
+            .as_ref()
+            .iter()
+            .filter_map(|s| DnaElementKey::from_selection(s, 0))
+            .collect();
+        self.apply_operation(DesignOperation::SetSelectionGroup { name, elements });
+    }
+
+    fn recall_selection_group(&mut self, name: String, combination: SelectionGroupCombination) {
+        let group = self
+            .main_state
+            .app_state
+            .get_design_reader()
+            .get_selection_group(&name);
+        if let Some(group) = group {
+            let selection = self.get_selection();
+            let new_selection = combination.apply(selection.as_ref().as_ref(), &group);
+            self.main_state.update_selection(new_selection, None);
+        } else {
+            log::error!("No selection group named {}", name);
+        }
+    }
+
+    fn delete_selection_group(&mut self, name: String) {
+        self.apply_operation(DesignOperation::DeleteSelectionGroup(name));
+    }
+
     fn need_save(&self) -> bool {
         self.main_state.need_save()
     }
@@ -1650,9 +2526,88 @@ impl<'a> MainStateInteface for MainStateView<'a> {
         }
     }
 
+    fn export_camera_view(&mut self, path: &PathBuf) -> Result<(), CameraViewError> {
+        let ((position, rotor), fovy) = self
+            .main_state
+            .applications
+            .get(&ElementType::Scene)
+            .and_then(|s| {
+                let scene = s.lock().unwrap();
+                scene.get_camera().zip(scene.get_fovy())
+            })
+            .ok_or_else(|| "Could not get current camera position".to_string())?;
+        let json = scene::SceneCamera::new(position, rotor).to_json(fovy);
+        let content = serde_json::to_string_pretty(&json).map_err(|e| e.to_string())?;
+        std::fs::write(path, content).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn import_camera_view(&mut self, path: &PathBuf) -> Result<(), CameraViewError> {
+        let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let json: serde_json::Value = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+        let (camera, fovy) = scene::SceneCamera::from_json(&json).map_err(|e| e.to_string())?;
+        self.notify_apps(Notification::TeleportCamera(camera.position, camera.rotor));
+        self.notify_apps(Notification::CameraFovy(fovy));
+        Ok(())
+    }
+
+    fn add_palette_color(&mut self, name: String, color: u32) {
+        let mut palette = self.main_state.color_palette.clone();
+        palette.add(name, color);
+        self.main_state.set_color_palette(palette);
+    }
+
+    fn set_color_theme(&mut self, theme: ColorTheme) {
+        if let Err(e) = color_theme::save(&theme) {
+            log::error!("Could not save color theme: {}", e);
+        }
+        self.notify_apps(Notification::ColorTheme(theme));
+    }
+
+    fn set_edit_history_size(&mut self, size: usize) {
+        if let Err(e) = edit_history::save_size(size) {
+            log::error!("Could not save edit history size: {}", e);
+        }
+        self.main_state.edit_history_size = size;
+    }
+
+    fn set_session_log_enabled(&mut self, enabled: bool) {
+        if let Err(e) = SessionLog::save_enabled(enabled) {
+            log::error!("Could not save session log preference: {}", e);
+        }
+        self.main_state.session_log.set_enabled(enabled);
+    }
+
+    fn export_color_palette(&mut self, path: &PathBuf) -> Result<(), String> {
+        let json = self.main_state.color_palette.to_json()?;
+        std::fs::write(path, json).map_err(|e| e.to_string())
+    }
+
+    fn import_color_palette(&mut self, path: &PathBuf) -> Result<(), String> {
+        let json = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let palette = ColorPalette::from_json(&json)?;
+        self.main_state.set_color_palette(palette);
+        Ok(())
+    }
+
     fn flip_split_views(&mut self) {
         self.notify_apps(Notification::FlipSplitViews)
     }
+
+    fn start_2d_xover_tool(&mut self) {
+        self.notify_apps(Notification::Start2dXoverTool)
+    }
+
+    fn start_recording_macro(&mut self) {
+        self.main_state.macro_recorder = Some(Default::default());
+    }
+
+    fn stop_recording_macro(&mut self) -> Option<OperationMacro> {
+        self.main_state
+            .macro_recorder
+            .take()
+            .map(MacroRecorder::finish)
+    }
 }
 
 use controller::{SetScaffoldSequenceError, SetScaffoldSequenceOk};
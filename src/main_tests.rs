@@ -379,3 +379,39 @@ fn no_need_to_save_after_new_design() {
     main_state.update();
     assert!(!main_state.need_save(), "Need save after update");
 }
+
+#[test]
+fn edit_history_is_restored_in_undo_stack() {
+    let scaffold_shift = |state: &MainState| {
+        state
+            .app_state
+            .get_design_reader()
+            .get_design_snapshot()
+            .scaffold_shift
+    };
+    let mut main_state = new_state();
+    main_state.clear_app_state(pastable_design());
+    main_state.edit_history_size = 10;
+    let initial_shift = scaffold_shift(&main_state);
+    main_state.apply_operation(DesignOperation::SetScaffoldShift(3));
+    main_state.update();
+    main_state.apply_operation(DesignOperation::SetScaffoldShift(7));
+    main_state.update();
+    let nb_undoable_states = main_state.undo_stack.len();
+    assert!(nb_undoable_states >= 2);
+    let directory = crate::utils::unique_temp_dir("ensnano_edit_history_test").unwrap();
+    let path = directory.join("design.ens");
+    main_state.save_design(&path).unwrap();
+
+    let mut loaded_state = new_state();
+    loaded_state.import_design_with_history(&path).unwrap();
+    assert_eq!(loaded_state.undo_stack.len(), nb_undoable_states);
+    assert_eq!(scaffold_shift(&loaded_state), Some(7));
+    loaded_state.undo();
+    assert_eq!(scaffold_shift(&loaded_state), Some(3));
+    while !loaded_state.undo_stack.is_empty() {
+        loaded_state.undo();
+    }
+    assert_eq!(scaffold_shift(&loaded_state), initial_shift);
+    std::fs::remove_dir_all(directory).unwrap();
+}
@@ -32,9 +32,10 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 //! The multiplexer is also in charge of drawing to the frame.
 use super::{Action, Requests};
 use crate::gui::UiSize;
+use crate::key_bindings::{is_modifier_key, HotkeyAction, KeyBindings, KeyCombination};
 use crate::utils::texture::SampledTexture;
 use crate::PhySize;
-use ensnano_interactor::{ActionMode, SelectionMode};
+use ensnano_interactor::{application::Notification, ActionMode, SelectionMode};
 use iced_wgpu::wgpu;
 use iced_winit::winit;
 use iced_winit::winit::event::*;
@@ -93,10 +94,22 @@ pub struct Multiplexer {
     ui_size: UiSize,
     pub invert_y_scroll: bool,
     pub icon: Option<CursorIcon>,
+    key_bindings: KeyBindings,
+    /// The action that will be bound to the next key combination pressed by the user, and the
+    /// binding that it will replace
+    recording_key_binding: Option<(HotkeyAction, Option<KeyCombination>)>,
 }
 
 const MAX_LEFT_PANNEL_WIDTH: f64 = 200.;
 const MAX_STATUS_BAR_HEIGHT: f64 = 50.;
+/// The angle by which the 3D camera turns each time a camera turning shortcut is pressed
+const CAMERA_TURN_STEP: f32 = std::f32::consts::FRAC_PI_2 / 20.;
+
+fn turn_camera(requests: &mut Requests, xz: f32, yz: f32) {
+    requests
+        .keep_proceed
+        .push_back(Action::NotifyApps(Notification::TurnCamera(xz, yz)))
+}
 
 impl Multiplexer {
     /// Create a new multiplexer for a window with size `window_size`.
@@ -157,6 +170,8 @@ impl Multiplexer {
             ui_size,
             invert_y_scroll: false,
             icon: None,
+            key_bindings: KeyBindings::load(),
+            recording_key_binding: None,
         };
         ret.generate_textures();
         ret
@@ -237,6 +252,8 @@ impl Multiplexer {
             depth_stencil_attachment: None,
         });
         if self.window_size.width > 0 && self.window_size.height > 0 {
+            // The overlays are drawn last so that they float above the other elements
+            let overlays = (0..self.overlays_textures.len()).map(ElementType::Overlay);
             for element in [
                 ElementType::TopBar,
                 ElementType::LeftPanel,
@@ -246,9 +263,11 @@ impl Multiplexer {
                 ElementType::StatusBar,
             ]
             .iter()
+            .cloned()
+            .chain(overlays)
             {
-                if let Some(area) = self.get_texture_size(*element) {
-                    render_pass.set_bind_group(0, self.get_bind_group(element), &[]);
+                if let Some(area) = self.get_texture_size(element) {
+                    render_pass.set_bind_group(0, self.get_bind_group(&element), &[]);
 
                     render_pass.set_viewport(
                         area.position.x as f32,
@@ -298,7 +317,8 @@ impl Multiplexer {
     pub fn get_draw_area(&self, element_type: ElementType) -> Option<DrawArea> {
         use ElementType::Overlay;
         let (position, size) = if let Overlay(n) = element_type {
-            (self.overlays[n].position, self.overlays[n].size)
+            let overlay = self.overlays.get(n)?;
+            (overlay.position, overlay.size)
         } else {
             let (left, top, right, bottom) = self.layout_manager.get_area(element_type)?;
             let top = top * self.window_size.height as f64;
@@ -462,76 +482,18 @@ impl Multiplexer {
                 ..
             } => {
                 captured = true;
-                match *key {
-                    VirtualKeyCode::Escape => {
-                        self.requests.lock().unwrap().action_mode = Some(ActionMode::Normal)
-                    }
-                    VirtualKeyCode::Z if ctrl(&self.modifiers) => {
-                        self.requests.lock().unwrap().undo = Some(());
-                    }
-                    VirtualKeyCode::R if ctrl(&self.modifiers) => {
-                        self.requests.lock().unwrap().redo = Some(());
-                    }
-                    VirtualKeyCode::C if ctrl(&self.modifiers) => {
-                        self.requests.lock().unwrap().copy = Some(());
-                    }
-                    VirtualKeyCode::V if ctrl(&self.modifiers) => {
-                        self.requests.lock().unwrap().paste = Some(());
-                    }
-                    VirtualKeyCode::J if ctrl(&self.modifiers) => {
-                        self.requests.lock().unwrap().duplication = Some(());
-                    }
-                    VirtualKeyCode::L if ctrl(&self.modifiers) => {
-                        self.requests.lock().unwrap().anchor = Some(());
-                    }
-                    VirtualKeyCode::R if !ctrl(&self.modifiers) => {
-                        self.requests.lock().unwrap().action_mode = Some(ActionMode::Rotate)
-                    }
-                    VirtualKeyCode::T => {
-                        self.requests.lock().unwrap().action_mode = Some(ActionMode::Translate)
-                    }
-                    VirtualKeyCode::N => {
-                        self.requests.lock().unwrap().selection_mode =
-                            Some(SelectionMode::Nucleotide)
-                    }
-                    VirtualKeyCode::H => {
-                        self.requests.lock().unwrap().selection_mode = Some(SelectionMode::Helix)
-                    }
-                    VirtualKeyCode::S if ctrl(&self.modifiers) => {
-                        self.requests.lock().unwrap().save_shortcut = Some(());
-                    }
-                    VirtualKeyCode::O if ctrl(&self.modifiers) => {
-                        self.requests
-                            .lock()
-                            .unwrap()
-                            .keep_proceed
-                            .push_back(Action::LoadDesign(None));
-                    }
-                    VirtualKeyCode::Q if ctrl(&self.modifiers) && cfg!(target_os = "macos") => {
-                        self.requests
-                            .lock()
-                            .unwrap()
-                            .keep_proceed
-                            .push_back(Action::Exit);
-                    }
-                    keycode if keycode_to_num(keycode).is_some() => {
-                        let n_camera = keycode_to_num(keycode).unwrap();
-                        self.requests
-                            .lock()
-                            .unwrap()
-                            .keep_proceed
-                            .push_back(Action::SelectFavoriteCamera(n_camera));
-                    }
-                    VirtualKeyCode::S => {
-                        self.requests.lock().unwrap().selection_mode = Some(SelectionMode::Strand)
-                    }
-                    VirtualKeyCode::K => {
-                        self.requests.lock().unwrap().recolor_stapples = Some(());
-                    }
-                    VirtualKeyCode::Delete | VirtualKeyCode::Back => {
-                        self.requests.lock().unwrap().delete_selection = Some(());
-                    }
-                    _ => captured = false,
+                if let Some((action, previous)) = self.recording_key_binding.take() {
+                    self.record_key_binding(*key, action, previous);
+                } else if let Some(action) = self.key_bindings.get_action(*key, self.modifiers) {
+                    self.trigger_hotkey(action)
+                } else if let Some(n_camera) = keycode_to_num(*key) {
+                    self.requests
+                        .lock()
+                        .unwrap()
+                        .keep_proceed
+                        .push_back(Action::SelectFavoriteCamera(n_camera));
+                } else {
+                    captured = false
                 }
             }
             WindowEvent::MouseWheel { delta, .. } => {
@@ -556,6 +518,88 @@ impl Multiplexer {
         }
     }
 
+    pub fn key_bindings(&self) -> &KeyBindings {
+        &self.key_bindings
+    }
+
+    /// Bind `action` to the next key combination pressed by the user, replacing the binding
+    /// `previous` if it is `Some`.
+    pub fn start_recording_key_binding(
+        &mut self,
+        action: HotkeyAction,
+        previous: Option<KeyCombination>,
+    ) {
+        self.recording_key_binding = Some((action, previous));
+    }
+
+    /// Stop waiting for the key combination of a new binding
+    pub fn cancel_key_binding_recording(&mut self) {
+        self.recording_key_binding = None;
+    }
+
+    fn record_key_binding(
+        &mut self,
+        key: VirtualKeyCode,
+        action: HotkeyAction,
+        previous: Option<KeyCombination>,
+    ) {
+        if is_modifier_key(key) {
+            // Wait for the key that is pressed together with the modifiers
+            self.recording_key_binding = Some((action, previous));
+            return;
+        }
+        let conflict = self
+            .key_bindings
+            .bind((key, self.modifiers), action, previous);
+        if let Err(e) = self.key_bindings.save() {
+            log::error!("Could not save key bindings: {}", e);
+        }
+        self.requests
+            .lock()
+            .unwrap()
+            .keep_proceed
+            .push_back(Action::KeyBindingsChanged {
+                bindings: self.key_bindings.clone(),
+                conflict: conflict.map(|conflict| (action, conflict)),
+            });
+    }
+
+    fn trigger_hotkey(&self, action: HotkeyAction) {
+        let mut requests = self.requests.lock().unwrap();
+        match action {
+            HotkeyAction::Undo => requests.undo = Some(()),
+            HotkeyAction::Redo => requests.redo = Some(()),
+            HotkeyAction::Copy => requests.copy = Some(()),
+            HotkeyAction::Paste => requests.paste = Some(()),
+            HotkeyAction::Duplicate => requests.duplication = Some(()),
+            HotkeyAction::Anchor => requests.anchor = Some(()),
+            HotkeyAction::NormalMode => requests.action_mode = Some(ActionMode::Normal),
+            HotkeyAction::TranslateMode => requests.action_mode = Some(ActionMode::Translate),
+            HotkeyAction::RotateMode => requests.action_mode = Some(ActionMode::Rotate),
+            HotkeyAction::SelectNucleotide => {
+                requests.selection_mode = Some(SelectionMode::Nucleotide)
+            }
+            HotkeyAction::SelectHelix => requests.selection_mode = Some(SelectionMode::Helix),
+            HotkeyAction::SelectStrand => requests.selection_mode = Some(SelectionMode::Strand),
+            HotkeyAction::Save => requests.save_shortcut = Some(()),
+            HotkeyAction::Open => requests.keep_proceed.push_back(Action::LoadDesign(None)),
+            HotkeyAction::Exit => requests.keep_proceed.push_back(Action::Exit),
+            HotkeyAction::RecolorStaples => requests.recolor_stapples = Some(()),
+            HotkeyAction::DeleteSelection => requests.delete_selection = Some(()),
+            HotkeyAction::UndoCameraMove => requests
+                .keep_proceed
+                .push_back(Action::NotifyApps(Notification::UndoCameraMove)),
+            HotkeyAction::RedoCameraMove => requests
+                .keep_proceed
+                .push_back(Action::NotifyApps(Notification::RedoCameraMove)),
+            HotkeyAction::ToggleWidgetBasis => requests.toggle_widget_basis = Some(()),
+            HotkeyAction::TurnCameraLeft => turn_camera(&mut requests, CAMERA_TURN_STEP, 0.),
+            HotkeyAction::TurnCameraRight => turn_camera(&mut requests, -CAMERA_TURN_STEP, 0.),
+            HotkeyAction::TurnCameraUp => turn_camera(&mut requests, 0., -CAMERA_TURN_STEP),
+            HotkeyAction::TurnCameraDown => turn_camera(&mut requests, 0., CAMERA_TURN_STEP),
+        }
+    }
+
     pub fn change_ui_size(&mut self, ui_size: UiSize, window: &iced_winit::winit::window::Window) {
         self.ui_size = ui_size;
         self.resize(window.inner_size(), window.scale_factor());
@@ -681,6 +725,10 @@ impl Multiplexer {
         self.focus
     }
 
+    pub fn has_overlays(&self) -> bool {
+        !self.overlays.is_empty()
+    }
+
     pub fn set_overlays(&mut self, overlays: Vec<Overlay>) {
         self.overlays = overlays;
         self.overlays_textures.clear();
@@ -811,14 +859,6 @@ impl State {
     }
 }
 
-fn ctrl(modifiers: &ModifiersState) -> bool {
-    if cfg!(target_os = "macos") {
-        modifiers.logo()
-    } else {
-        modifiers.ctrl()
-    }
-}
-
 use crate::gui::Multiplexer as GuiMultiplexer;
 
 impl GuiMultiplexer for Multiplexer {
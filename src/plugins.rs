@@ -0,0 +1,378 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! Plugins that modify the design in reaction to the user's actions.
+//!
+//! Plugins are WebAssembly modules read from `~/.ensnano/plugins`. They communicate with ENSnano
+//! through JSON strings written in their linear memory. A plugin module must export:
+//!
+//! * `memory`, its linear memory.
+//! * `ensnano_alloc(len: i32) -> i32`, which returns a pointer to `len` bytes in which ENSnano
+//! writes the inputs of the other functions.
+//!
+//! and may export:
+//!
+//! * `ensnano_menu_actions() -> i64`, which returns the JSON list of the names of the actions
+//! that the user can trigger from the interface.
+//! * `ensnano_on_selection_changed(ptr: i32, len: i32) -> i64`, called with the JSON list of the
+//! selected nucleotides.
+//! * `ensnano_on_menu_action(ptr: i32, len: i32) -> i64`, called with the name of the action
+//! triggered by the user.
+//!
+//! The functions that return an `i64` return a pointer to a JSON string in the 32 most
+//! significant bits, and the length of this string in the 32 least significant bits. The two
+//! last functions return a JSON list of `DesignOperation`, that are applied to the design as if
+//! they were performed by the user. The operations returned by one call are undone in one step.
+//!
+//! Plugins are run in background jobs, and each call to a function of a module can execute a
+//! bounded number of instructions so that a faulty plugin cannot run forever.
+
+use crate::consts::ENSNANO_USER_DIR;
+use crate::controller::{BackgroundJob, JobError, JobOutput};
+use ensnano_design::Nucl;
+use ensnano_interactor::DesignOperation;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use wasmtime::{Config, Engine, Instance, Memory, Module, Store, TypedFunc};
+
+const PLUGINS_DIR: &str = "plugins";
+const PLUGIN_EXTENSION: &str = "wasm";
+/// The amount of fuel, roughly the number of wasm instructions, that a call to a function of a
+/// plugin can consume
+const PLUGIN_FUEL: u64 = 1_000_000_000;
+
+/// A plugin that modifies the design
+pub trait DesignPlugin: Send {
+    fn name(&self) -> &str;
+    /// The names of the actions that the user can trigger from the interface
+    fn menu_actions(&self) -> &[String];
+    /// Called each time the selection is modified
+    fn on_selection_changed(
+        &mut self,
+        selection: &[Nucl],
+    ) -> Result<Vec<DesignOperation>, PluginError>;
+    /// Called when the user triggers the action `name`
+    fn on_menu_action(&mut self, name: &str) -> Result<Vec<DesignOperation>, PluginError>;
+}
+
+#[derive(Debug)]
+pub enum PluginError {
+    /// The module could not be compiled or instantiated
+    Loading(String),
+    /// The module does not export a required function
+    MissingExport(&'static str),
+    /// A function of the module trapped
+    Execution(String),
+    /// The data returned by the module could not be read
+    InvalidOutput(String),
+}
+
+impl std::fmt::Display for PluginError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Loading(e) => write!(f, "Could not load plugin: {}", e),
+            Self::MissingExport(name) => write!(f, "Plugin does not export {}", name),
+            Self::Execution(e) => write!(f, "Plugin execution failed: {}", e),
+            Self::InvalidOutput(e) => write!(f, "Invalid output of plugin: {}", e),
+        }
+    }
+}
+
+/// A plugin implemented by a WebAssembly module
+pub struct WasmPlugin {
+    name: String,
+    menu_actions: Vec<String>,
+    store: Store<()>,
+    /// The total amount of fuel given to the store
+    fuel_added: u64,
+    memory: Memory,
+    alloc: TypedFunc<u32, u32>,
+    on_selection_changed: Option<TypedFunc<(u32, u32), u64>>,
+    on_menu_action: Option<TypedFunc<(u32, u32), u64>>,
+}
+
+impl WasmPlugin {
+    pub fn load<P: AsRef<Path>>(engine: &Engine, path: P) -> Result<Self, PluginError> {
+        let name = path
+            .as_ref()
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let module = Module::from_file(engine, path.as_ref())
+            .map_err(|e| PluginError::Loading(e.to_string()))?;
+        let mut store = Store::new(engine, ());
+        // The instantiation may run the start function of the module
+        store
+            .add_fuel(PLUGIN_FUEL)
+            .map_err(|e| PluginError::Loading(e.to_string()))?;
+        let instance = Instance::new(&mut store, &module, &[])
+            .map_err(|e| PluginError::Loading(e.to_string()))?;
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or(PluginError::MissingExport("memory"))?;
+        let alloc = instance
+            .get_typed_func::<u32, u32, _>(&mut store, "ensnano_alloc")
+            .map_err(|_| PluginError::MissingExport("ensnano_alloc"))?;
+        let on_selection_changed = instance
+            .get_typed_func::<(u32, u32), u64, _>(&mut store, "ensnano_on_selection_changed")
+            .ok();
+        let on_menu_action = instance
+            .get_typed_func::<(u32, u32), u64, _>(&mut store, "ensnano_on_menu_action")
+            .ok();
+        let mut ret = Self {
+            name,
+            menu_actions: Vec::new(),
+            store,
+            fuel_added: PLUGIN_FUEL,
+            memory,
+            alloc,
+            on_selection_changed,
+            on_menu_action,
+        };
+        if let Ok(menu_actions) =
+            instance.get_typed_func::<(), u64, _>(&mut ret.store, "ensnano_menu_actions")
+        {
+            ret.refuel()?;
+            let output = menu_actions
+                .call(&mut ret.store, ())
+                .map_err(|e| PluginError::Execution(e.to_string()))?;
+            let json = ret.read_output(output)?;
+            ret.menu_actions = serde_json::from_str(&json)
+                .map_err(|e| PluginError::InvalidOutput(e.to_string()))?;
+        }
+        Ok(ret)
+    }
+
+    /// Give the store enough fuel for one call to a function of the module
+    fn refuel(&mut self) -> Result<(), PluginError> {
+        let consumed = self.store.fuel_consumed().unwrap_or(0);
+        let remaining = self.fuel_added.saturating_sub(consumed);
+        let fuel = PLUGIN_FUEL.saturating_sub(remaining);
+        self.store
+            .add_fuel(fuel)
+            .map_err(|e| PluginError::Execution(e.to_string()))?;
+        self.fuel_added += fuel;
+        Ok(())
+    }
+
+    /// Write `input` in the memory of the module and return its position and length
+    fn write_input(&mut self, input: &[u8]) -> Result<(u32, u32), PluginError> {
+        let len = input.len() as u32;
+        let ptr = self
+            .alloc
+            .call(&mut self.store, len)
+            .map_err(|e| PluginError::Execution(e.to_string()))?;
+        self.memory
+            .write(&mut self.store, ptr as usize, input)
+            .map_err(|e| PluginError::Execution(e.to_string()))?;
+        Ok((ptr, len))
+    }
+
+    /// Read the string whose position and length are packed in `output`
+    fn read_output(&mut self, output: u64) -> Result<String, PluginError> {
+        let ptr = (output >> 32) as usize;
+        let len = (output & 0xFFFF_FFFF) as usize;
+        let mut buffer = vec![0; len];
+        self.memory
+            .read(&self.store, ptr, &mut buffer)
+            .map_err(|e| PluginError::InvalidOutput(e.to_string()))?;
+        String::from_utf8(buffer).map_err(|e| PluginError::InvalidOutput(e.to_string()))
+    }
+
+    fn call_with_input(
+        &mut self,
+        function: TypedFunc<(u32, u32), u64>,
+        input: &[u8],
+    ) -> Result<Vec<DesignOperation>, PluginError> {
+        self.refuel()?;
+        let (ptr, len) = self.write_input(input)?;
+        let output = function
+            .call(&mut self.store, (ptr, len))
+            .map_err(|e| PluginError::Execution(e.to_string()))?;
+        let json = self.read_output(output)?;
+        serde_json::from_str(&json).map_err(|e| PluginError::InvalidOutput(e.to_string()))
+    }
+}
+
+impl DesignPlugin for WasmPlugin {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn menu_actions(&self) -> &[String] {
+        &self.menu_actions
+    }
+
+    fn on_selection_changed(
+        &mut self,
+        selection: &[Nucl],
+    ) -> Result<Vec<DesignOperation>, PluginError> {
+        if let Some(function) = self.on_selection_changed.clone() {
+            let input =
+                serde_json::to_vec(selection).map_err(|e| PluginError::Execution(e.to_string()))?;
+            self.call_with_input(function, &input)
+        } else {
+            Ok(Vec::new())
+        }
+    }
+
+    fn on_menu_action(&mut self, name: &str) -> Result<Vec<DesignOperation>, PluginError> {
+        if let Some(function) = self.on_menu_action.clone() {
+            self.call_with_input(function, name.as_bytes())
+        } else {
+            Err(PluginError::MissingExport("ensnano_on_menu_action"))
+        }
+    }
+}
+
+/// An action of a plugin that can be triggered from the interface
+#[derive(Debug, Clone)]
+pub struct PluginMenuAction {
+    pub plugin_id: usize,
+    pub plugin_name: String,
+    pub name: String,
+}
+
+/// The operations requested by plugins, and the errors of the plugins that failed
+pub struct PluginOutput {
+    pub operations: Vec<DesignOperation>,
+    pub errors: Vec<String>,
+}
+
+#[derive(Default)]
+pub struct PluginManager {
+    /// The plugins are shared with the background jobs in which they run
+    plugins: Arc<Mutex<Vec<Box<dyn DesignPlugin>>>>,
+    nb_plugins: usize,
+    menu_actions: Vec<PluginMenuAction>,
+}
+
+impl PluginManager {
+    /// Load all the plugins of the `~/.ensnano/plugins` directory
+    pub fn load() -> Self {
+        let dir = if let Some(dir) = plugins_dir().filter(|d| d.is_dir()) {
+            dir
+        } else {
+            return Self::default();
+        };
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                log::error!("Could not read plugins directory {:?}: {}", dir, e);
+                return Self::default();
+            }
+        };
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        let engine = match Engine::new(&config) {
+            Ok(engine) => engine,
+            Err(e) => {
+                log::error!("Could not create plugin engine: {}", e);
+                return Self::default();
+            }
+        };
+        let mut plugins: Vec<Box<dyn DesignPlugin>> = Vec::new();
+        for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+            if path.extension().and_then(|ext| ext.to_str()) != Some(PLUGIN_EXTENSION) {
+                continue;
+            }
+            match WasmPlugin::load(&engine, &path) {
+                Ok(plugin) => {
+                    log::info!("Loaded plugin {}", plugin.name());
+                    plugins.push(Box::new(plugin));
+                }
+                Err(e) => log::error!("{:?}: {}", path, e),
+            }
+        }
+        let mut menu_actions = Vec::new();
+        for (plugin_id, plugin) in plugins.iter().enumerate() {
+            for name in plugin.menu_actions() {
+                menu_actions.push(PluginMenuAction {
+                    plugin_id,
+                    plugin_name: plugin.name().to_owned(),
+                    name: name.clone(),
+                });
+            }
+        }
+        Self {
+            nb_plugins: plugins.len(),
+            plugins: Arc::new(Mutex::new(plugins)),
+            menu_actions,
+        }
+    }
+
+    pub fn menu_actions(&self) -> Vec<PluginMenuAction> {
+        self.menu_actions.clone()
+    }
+
+    /// Return a job that notifies all plugins of a new selection and collects the operations
+    /// that they request.
+    pub fn on_selection_changed(&self, selection: Vec<Nucl>) -> BackgroundJob<JobOutput> {
+        let plugins = self.plugins.clone();
+        BackgroundJob::spawn(move |context| {
+            let mut output = PluginOutput {
+                operations: Vec::new(),
+                errors: Vec::new(),
+            };
+            for plugin in plugins.lock().unwrap().iter_mut() {
+                if context.is_canceled() {
+                    return Err(JobError::Canceled);
+                }
+                match plugin.on_selection_changed(&selection) {
+                    Ok(operations) => output.operations.extend(operations),
+                    Err(e) => output
+                        .errors
+                        .push(format!("Plugin {}: {}", plugin.name(), e)),
+                }
+            }
+            Ok(JobOutput::Plugin(output))
+        })
+    }
+
+    /// Return a job that triggers the action `name` of the plugin `plugin_id`
+    pub fn on_menu_action(&self, plugin_id: usize, name: String) -> BackgroundJob<JobOutput> {
+        let plugins = self.plugins.clone();
+        BackgroundJob::spawn(move |context| {
+            let mut output = PluginOutput {
+                operations: Vec::new(),
+                errors: Vec::new(),
+            };
+            if let Some(plugin) = plugins.lock().unwrap().get_mut(plugin_id) {
+                match plugin.on_menu_action(&name) {
+                    Ok(operations) => output.operations = operations,
+                    Err(e) => output
+                        .errors
+                        .push(format!("Plugin {}: {}", plugin.name(), e)),
+                }
+            }
+            if context.is_canceled() {
+                Err(JobError::Canceled)
+            } else {
+                Ok(JobOutput::Plugin(output))
+            }
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        // The plugins are not locked because they may be used by a running job
+        self.nb_plugins == 0
+    }
+}
+
+fn plugins_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(ENSNANO_USER_DIR).join(PLUGINS_DIR))
+}
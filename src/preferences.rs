@@ -0,0 +1,85 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! The preferences of the user, stored in `~/.ensnano/preferences.json`.
+//!
+//! Each kind of preference is stored in its own field of the json object, so that writing one of
+//! them keeps the others.
+
+use crate::consts::ENSNANO_USER_DIR;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+const PREFERENCES_FILE: &str = "preferences.json";
+
+/// Read the value stored in `field`, or return the default value if there is none.
+pub fn read_field<T: DeserializeOwned + Default>(field: &str) -> T {
+    let path = if let Some(path) = preferences_path() {
+        path
+    } else {
+        return Default::default();
+    };
+    if !path.exists() {
+        return Default::default();
+    }
+    match read_preferences(&path).and_then(|preferences| {
+        preferences
+            .get(field)
+            .cloned()
+            .map(|value| serde_json::from_value(value).map_err(|e| e.to_string()))
+            .unwrap_or_else(|| Ok(Default::default()))
+    }) {
+        Ok(value) => value,
+        Err(e) => {
+            log::error!("Could not read {} from {:?}: {}", field, path, e);
+            Default::default()
+        }
+    }
+}
+
+/// Write `value` in the field `field` of `~/.ensnano/preferences.json`. The other fields stored
+/// in this file are kept, and the file is created if it does not exist yet.
+pub fn write_field<T: Serialize>(field: &str, value: &T) -> std::io::Result<()> {
+    let path = preferences_path().ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "Could not find the home directory",
+        )
+    })?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut preferences = if path.exists() {
+        read_preferences(&path)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?
+    } else {
+        serde_json::Map::new()
+    };
+    preferences.insert(field.to_string(), serde_json::to_value(value)?);
+    let json = serde_json::to_string_pretty(&preferences)?;
+    std::fs::write(path, json)
+}
+
+fn preferences_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(ENSNANO_USER_DIR).join(PREFERENCES_FILE))
+}
+
+fn read_preferences(path: &Path) -> Result<serde_json::Map<String, serde_json::Value>, String> {
+    let json = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&json).map_err(|e| e.to_string())
+}
@@ -36,11 +36,17 @@ use ensnano_design::{
     Nucl,
 };
 use ensnano_interactor::{
-    graphics::{Background3D, RenderingMode},
-    HyperboloidRequest, RigidBodyConstants, SuggestionParameters,
+    graphics::{
+        AmbientOcclusionParameters, AnalysisColoring, AnimationParameters, Background3D,
+        ColorTheme, DepthOfFieldParameters, EyeDomeLightingParameters, FlyThroughParameters,
+        LightingParameters, PostProcessingQuality, RenderingMode, RulerParameters,
+        SonificationMode, ToneMappingParameters,
+    },
+    HyperboloidRequest, MirrorAxis, RigidBodyConstants, StrandBuildingParameters,
+    SuggestionParameters,
 };
 
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, VecDeque};
 
 /// A structure that contains all the requests that can be made through the GUI.
 #[derive(Default)]
@@ -49,6 +55,8 @@ pub struct Requests {
     pub action_mode: Option<ActionMode>,
     /// A change of the selection mode
     pub selection_mode: Option<SelectionMode>,
+    /// A request to lock or unlock the current selection
+    pub selection_lock: Option<bool>,
     /// A request to move the camera so that the frustrum fits the desgin
     pub fitting: Option<()>,
     /// A request to save the selected design
@@ -72,7 +80,23 @@ pub struct Requests {
     pub recolor_stapples: Option<()>,
     pub roll_request: Option<RollRequest>,
     pub show_torsion_request: Option<bool>,
+    /// A request to show/hide the local twist of the helices in the 3D view
+    pub show_local_twist_request: Option<bool>,
+    /// A request to color the nucleotides of the 3D view according to an analysis of the design
+    pub analysis_coloring_request: Option<AnalysisColoring>,
+    /// A request to show/hide the identifier of the helices in the 3D view
+    pub show_helix_numbers_request: Option<bool>,
+    /// A request to show/hide the nucleotide ruler of the 2D view
+    pub show_ruler_request: Option<bool>,
+    /// A request to modify the periods and colors of the nucleotide ruler of the 2D view
+    pub ruler_parameters_request: Option<RulerParameters>,
+    /// A request to show/hide the lattice grid of the 2D view
+    pub show_lattice_grid_request: Option<bool>,
     pub fog: Option<FogParameters>,
+    /// A request to center the fog of the 3D view on the selected elements
+    pub center_fog_on_selection: Option<()>,
+    /// A request to make the fog of the 3D view follow the pivot again
+    pub reset_fog_center: Option<()>,
     pub hyperboloid_update: Option<HyperboloidRequest>,
     pub new_hyperboloid: Option<HyperboloidRequest>,
     pub finalize_hyperboloid: Option<()>,
@@ -86,6 +110,9 @@ pub struct Requests {
     pub anchor: Option<()>,
     pub rigid_body_parameters: Option<RigidBodyConstants>,
     pub keep_proceed: VecDeque<Action>,
+    /// True if the last actions pushed in `keep_proceed` must be processed without intermediate
+    /// redraws
+    pub batch_in_progress: bool,
     pub new_shift_hyperboloid: Option<f32>,
     pub organizer_selection: Option<(Vec<DnaElementKey>, Option<ensnano_organizer::GroupId>, bool)>,
     pub organizer_candidates: Option<Vec<DnaElementKey>>,
@@ -100,6 +127,15 @@ pub struct Requests {
     pub scaffold_shift: Option<usize>,
     pub rendering_mode: Option<RenderingMode>,
     pub background3d: Option<Background3D>,
+    pub depth_of_field: Option<DepthOfFieldParameters>,
+    pub fly_through: Option<FlyThroughParameters>,
+    pub eye_dome_lighting: Option<EyeDomeLightingParameters>,
+    pub ambient_occlusion: Option<AmbientOcclusionParameters>,
+    pub post_processing_quality: Option<PostProcessingQuality>,
+    pub lighting: Option<LightingParameters>,
+    pub tone_mapping: Option<ToneMappingParameters>,
+    pub sonification: Option<SonificationMode>,
+    pub mirror_plane: Option<Option<MirrorAxis>>,
     pub undo: Option<()>,
     pub redo: Option<()>,
     pub save_shortcut: Option<()>,
@@ -109,8 +145,12 @@ pub struct Requests {
     pub clean_requests: Option<()>,
     pub new_candidates: Option<Vec<Selection>>,
     pub new_selection: Option<Vec<Selection>>,
+    /// A selection made in the 3D view, that the 2D view must show
+    pub sync_3d_selection: Option<Vec<Selection>>,
     pub suspend_op: Option<()>,
     pub center_selection: Option<(Selection, AppId)>,
+    /// A nucleotide whose annotation the user wants to edit
+    pub edited_annotation: Option<Nucl>,
     pub centering_on_nucl: Option<(Nucl, usize)>,
     pub toggle_widget_basis: Option<()>,
     pub stop_roll: Option<()>,
@@ -118,4 +158,22 @@ pub struct Requests {
     pub new_double_strand_parameters: Option<Option<(isize, usize)>>,
     pub new_center_of_selection: Option<Option<CenterOfSelection>>,
     pub new_suggestion_parameters: Option<SuggestionParameters>,
+    pub new_strand_building_parameters: Option<StrandBuildingParameters>,
+    /// The visible ranges of some helices of the 2D view before and after their modification
+    pub new_helix_2d_ranges: Option<(
+        BTreeMap<usize, (isize, isize)>,
+        BTreeMap<usize, (isize, isize)>,
+    )>,
+}
+
+impl Requests {
+    /// Enqueue several actions that must all be processed in the same frame, so that the
+    /// applications are only updated once all of them have been applied.
+    pub fn batch_push(&mut self, actions: Vec<Action>) {
+        if actions.is_empty() {
+            return;
+        }
+        self.keep_proceed.extend(actions);
+        self.batch_in_progress = true;
+    }
 }
@@ -43,8 +43,7 @@ impl FlatSceneRequests for Requests {
     }
 
     fn attempt_paste(&mut self, nucl: Option<Nucl>) {
-        self.keep_proceed.push_back(Action::PasteCandidate(nucl));
-        self.keep_proceed.push_back(Action::ApplyPaste);
+        self.batch_push(vec![Action::PasteCandidate(nucl), Action::ApplyPaste]);
     }
 
     fn request_centering_on_nucl(&mut self, nucl: Nucl, design_id: usize) {
@@ -61,6 +60,12 @@ impl FlatSceneRequests for Requests {
         ))
     }
 
+    fn set_helix_display_order(&mut self, order: Vec<usize>, isometries: Vec<(usize, Isometry2)>) {
+        self.keep_proceed.push_back(Action::DesignOperation(
+            DesignOperation::SetHelixDisplayOrder { order, isometries },
+        ))
+    }
+
     fn set_visibility_helix(&mut self, helix: usize, visibility: bool) {
         self.keep_proceed.push_back(Action::DesignOperation(
             DesignOperation::SetVisibilityHelix {
@@ -81,6 +86,14 @@ impl FlatSceneRequests for Requests {
         self.keep_proceed.push_back(Action::SuspendOp);
     }
 
+    fn set_helix_2d_ranges(
+        &mut self,
+        old: BTreeMap<usize, (isize, isize)>,
+        new: BTreeMap<usize, (isize, isize)>,
+    ) {
+        self.new_helix_2d_ranges = Some((old, new));
+    }
+
     fn apply_design_operation(&mut self, op: DesignOperation) {
         self.keep_proceed.push_back(Action::DesignOperation(op))
     }
@@ -88,4 +101,8 @@ impl FlatSceneRequests for Requests {
     fn set_paste_candidate(&mut self, candidate: Option<Nucl>) {
         self.new_paste_candiate = Some(candidate);
     }
+
+    fn open_context_menu(&mut self) {
+        self.keep_proceed.push_back(Action::OpenContextMenu);
+    }
 }
@@ -17,7 +17,12 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 */
 
 use crate::gui::{Requests as GuiRequests, RigidBodyParametersRequest};
-use ensnano_interactor::{RigidBodyConstants, RollRequest};
+use crate::key_bindings::{HotkeyAction, KeyCombination};
+use ensnano_design::{StrandModification, TorusKnot};
+use ensnano_interactor::{
+    ColorBlindPalette, MirrorAxis, OrderFormat, RigidBodyConstants, RollRequest,
+    SelectionGroupCombination, SortKey, StrandEnd, WorkspaceTransform,
+};
 use std::collections::BTreeSet;
 
 use super::*;
@@ -41,10 +46,46 @@ impl GuiRequests for Requests {
         self.background3d = Some(bg);
     }
 
+    fn set_color_theme(&mut self, theme: ColorTheme) {
+        self.keep_proceed.push_back(Action::SetColorTheme(theme))
+    }
+
     fn change_3d_rendering_mode(&mut self, mode: RenderingMode) {
         self.rendering_mode = Some(mode);
     }
 
+    fn set_depth_of_field(&mut self, parameters: DepthOfFieldParameters) {
+        self.depth_of_field = Some(parameters);
+    }
+
+    fn set_fly_through(&mut self, parameters: FlyThroughParameters) {
+        self.fly_through = Some(parameters);
+    }
+
+    fn set_eye_dome_lighting(&mut self, parameters: EyeDomeLightingParameters) {
+        self.eye_dome_lighting = Some(parameters);
+    }
+
+    fn set_ambient_occlusion(&mut self, parameters: AmbientOcclusionParameters) {
+        self.ambient_occlusion = Some(parameters);
+    }
+
+    fn set_post_processing_quality(&mut self, quality: PostProcessingQuality) {
+        self.post_processing_quality = Some(quality);
+    }
+
+    fn set_lighting(&mut self, parameters: LightingParameters) {
+        self.lighting = Some(parameters);
+    }
+
+    fn set_tone_mapping(&mut self, parameters: ToneMappingParameters) {
+        self.tone_mapping = Some(parameters);
+    }
+
+    fn set_sonification(&mut self, mode: SonificationMode) {
+        self.sonification = Some(mode);
+    }
+
     fn set_scaffold_from_selection(&mut self) {
         self.select_scaffold = Some(())
     }
@@ -57,6 +98,21 @@ impl GuiRequests for Requests {
         self.keep_proceed.push_back(Action::InvertScrollY(inverted));
     }
 
+    fn record_key_binding(&mut self, action: HotkeyAction, previous: Option<KeyCombination>) {
+        self.keep_proceed
+            .push_back(Action::RecordKeyBinding { action, previous });
+    }
+
+    fn cancel_key_binding_recording(&mut self) {
+        self.keep_proceed
+            .push_back(Action::CancelKeyBindingRecording);
+    }
+
+    fn trigger_plugin_action(&mut self, plugin_id: usize, name: String) {
+        self.keep_proceed
+            .push_back(Action::PluginMenuAction { plugin_id, name });
+    }
+
     fn resize_2d_helices(&mut self, all: bool) {
         self.redim_2d_helices = Some(all);
     }
@@ -81,6 +137,10 @@ impl GuiRequests for Requests {
         self.selection_mode = Some(selection_mode);
     }
 
+    fn set_selection_lock(&mut self, locked: bool) {
+        self.selection_lock = Some(locked);
+    }
+
     fn toggle_widget_basis(&mut self) {
         self.toggle_widget_basis = Some(())
     }
@@ -89,8 +149,113 @@ impl GuiRequests for Requests {
         self.toggle_text = Some(visible);
     }
 
-    fn download_stapples(&mut self) {
-        self.keep_proceed.push_back(Action::DownloadStaplesRequest)
+    fn download_stapples(&mut self, order_format: OrderFormat) {
+        self.keep_proceed
+            .push_back(Action::DownloadStaplesRequest(order_format))
+    }
+
+    fn download_staples_csv(&mut self, order_format: OrderFormat) {
+        self.keep_proceed
+            .push_back(Action::DownloadStaplesCsvRequest(order_format))
+    }
+
+    fn download_staple_pools(&mut self, n_steps: usize) {
+        self.keep_proceed
+            .push_back(Action::DownloadStaplePoolsRequest(n_steps))
+    }
+
+    fn predict_secondary_structure(&mut self, s_id: usize) {
+        self.keep_proceed
+            .push_back(Action::PredictSecondaryStructure(s_id))
+    }
+
+    fn highlight_shortest_path(&mut self) {
+        self.keep_proceed.push_back(Action::HighlightShortestPath)
+    }
+
+    fn clear_measurements(&mut self) {
+        self.keep_proceed.push_back(Action::ClearMeasurements)
+    }
+
+    fn load_oxdna_trajectory(&mut self) {
+        self.keep_proceed.push_back(Action::LoadOxDnaTrajectory)
+    }
+
+    fn play_trajectory(&mut self, playing: bool) {
+        self.keep_proceed.push_back(Action::PlayTrajectory(playing))
+    }
+
+    fn set_trajectory_frame(&mut self, frame: usize) {
+        self.keep_proceed
+            .push_back(Action::SetTrajectoryFrame(frame))
+    }
+
+    fn close_trajectory(&mut self) {
+        self.keep_proceed.push_back(Action::CloseTrajectory)
+    }
+
+    fn open_design_in_workspace(&mut self) {
+        self.keep_proceed.push_back(Action::OpenDesignInWorkspace)
+    }
+
+    fn set_main_design_visibility(&mut self, visible: bool) {
+        self.keep_proceed
+            .push_back(Action::SetMainDesignVisibility(visible))
+    }
+
+    fn set_workspace_design_visibility(&mut self, design_id: usize, visible: bool) {
+        self.keep_proceed
+            .push_back(Action::SetWorkspaceDesignVisibility { design_id, visible })
+    }
+
+    fn set_workspace_design_transform(&mut self, design_id: usize, transform: WorkspaceTransform) {
+        self.keep_proceed
+            .push_back(Action::SetWorkspaceDesignTransform {
+                design_id,
+                transform,
+            })
+    }
+
+    fn close_workspace_design(&mut self, design_id: usize) {
+        self.keep_proceed
+            .push_back(Action::CloseWorkspaceDesign(design_id))
+    }
+
+    fn merge_workspace_design(&mut self, design_id: usize) {
+        self.keep_proceed
+            .push_back(Action::MergeWorkspaceDesign(design_id))
+    }
+
+    fn dock_workspace_design(&mut self, design_id: usize, helix: usize) {
+        self.keep_proceed
+            .push_back(Action::DockWorkspaceDesign { design_id, helix })
+    }
+
+    fn relax_design(&mut self) {
+        self.keep_proceed.push_back(Action::RelaxDesign)
+    }
+
+    fn cancel_job(&mut self, job_id: usize) {
+        self.keep_proceed.push_back(Action::CancelJob(job_id))
+    }
+
+    fn save_selection_group(&mut self, name: String) {
+        self.keep_proceed
+            .push_back(Action::SaveSelectionGroup(name))
+    }
+
+    fn recall_selection_group(&mut self, name: String, combination: SelectionGroupCombination) {
+        self.keep_proceed
+            .push_back(Action::RecallSelectionGroup { name, combination })
+    }
+
+    fn delete_selection_group(&mut self, name: String) {
+        self.keep_proceed
+            .push_back(Action::DeleteSelectionGroup(name))
+    }
+
+    fn go_to_xover(&mut self, a: (usize, usize), b: (usize, usize)) {
+        self.keep_proceed.push_back(Action::GoToXover(a, b))
     }
 
     fn set_selected_strand_sequence(&mut self, sequence: String) {
@@ -161,10 +326,42 @@ impl GuiRequests for Requests {
         self.fog = Some(parameters);
     }
 
+    fn center_fog_on_selection(&mut self) {
+        self.center_fog_on_selection = Some(());
+    }
+
+    fn reset_fog_center(&mut self) {
+        self.reset_fog_center = Some(());
+    }
+
     fn set_torsion_visibility(&mut self, visible: bool) {
         self.show_torsion_request = Some(visible);
     }
 
+    fn set_local_twist_visibility(&mut self, visible: bool) {
+        self.show_local_twist_request = Some(visible);
+    }
+
+    fn set_analysis_coloring(&mut self, coloring: AnalysisColoring) {
+        self.analysis_coloring_request = Some(coloring);
+    }
+
+    fn set_helix_numbers_visibility(&mut self, visible: bool) {
+        self.show_helix_numbers_request = Some(visible);
+    }
+
+    fn set_2d_ruler_visibility(&mut self, visible: bool) {
+        self.show_ruler_request = Some(visible);
+    }
+
+    fn set_2d_ruler_parameters(&mut self, parameters: RulerParameters) {
+        self.ruler_parameters_request = Some(parameters);
+    }
+
+    fn set_2d_lattice_grid_visibility(&mut self, visible: bool) {
+        self.show_lattice_grid_request = Some(visible);
+    }
+
     fn set_camera_dir_up_vec(&mut self, direction: Vec3, up: Vec3) {
         self.camera_target = Some((direction, up));
     }
@@ -210,6 +407,36 @@ impl GuiRequests for Requests {
         self.keep_proceed.push_back(Action::OxDnaExport)
     }
 
+    fn export_to_pdb(&mut self) {
+        self.keep_proceed.push_back(Action::PdbExport)
+    }
+
+    fn export_to_cadnano(&mut self) {
+        self.keep_proceed.push_back(Action::CadnanoExport)
+    }
+
+    fn export_mesh(&mut self) {
+        self.keep_proceed.push_back(Action::ExportMesh)
+    }
+
+    fn export_connectivity_graph(&mut self) {
+        self.keep_proceed.push_back(Action::ConnectivityGraphExport)
+    }
+
+    fn export_helix_map_png(&mut self, dpi: u32, size_mm: (f32, f32)) {
+        self.keep_proceed
+            .push_back(Action::ExportHelixMapPng { dpi, size_mm })
+    }
+
+    fn export_scene_png(&mut self, size: (u32, u32)) {
+        self.keep_proceed.push_back(Action::ExportScenePng { size })
+    }
+
+    fn record_animation(&mut self, parameters: AnimationParameters) {
+        self.keep_proceed
+            .push_back(Action::RecordAnimation(parameters))
+    }
+
     fn toggle_2d_view_split(&mut self) {
         self.split2d = Some(());
     }
@@ -303,10 +530,70 @@ impl GuiRequests for Requests {
             }));
     }
 
+    fn annotate_nucleotide(&mut self, nucl: Nucl, label: String) {
+        self.keep_proceed.push_back(Action::DesignOperation(
+            DesignOperation::AnnotateNucleotide { nucl, label },
+        ));
+    }
+
+    fn set_strand_modification(
+        &mut self,
+        s_id: usize,
+        end: StrandEnd,
+        modification: Option<StrandModification>,
+    ) {
+        self.keep_proceed.push_back(Action::DesignOperation(
+            DesignOperation::SetStrandModification {
+                s_id,
+                end,
+                modification,
+            },
+        ));
+    }
+
     fn create_new_camera(&mut self) {
         self.keep_proceed.push_back(Action::NewCamera);
     }
 
+    fn export_camera_view(&mut self) {
+        self.keep_proceed.push_back(Action::ExportCameraView);
+    }
+
+    fn import_camera_view(&mut self) {
+        self.keep_proceed.push_back(Action::ImportCameraView);
+    }
+
+    fn add_palette_color(&mut self, name: String, color: u32) {
+        self.keep_proceed
+            .push_back(Action::AddPaletteColor { name, color });
+    }
+
+    fn set_edit_history_size(&mut self, size: usize) {
+        self.keep_proceed
+            .push_back(Action::SetEditHistorySize(size))
+    }
+
+    fn set_session_log_enabled(&mut self, enabled: bool) {
+        self.keep_proceed
+            .push_back(Action::SetSessionLogEnabled(enabled))
+    }
+
+    fn export_color_palette(&mut self) {
+        self.keep_proceed.push_back(Action::ExportColorPalette);
+    }
+
+    fn import_color_palette(&mut self) {
+        self.keep_proceed.push_back(Action::ImportColorPalette);
+    }
+
+    fn export_selection_as_module(&mut self) {
+        self.keep_proceed.push_back(Action::ExportSelectionAsModule);
+    }
+
+    fn import_module(&mut self) {
+        self.keep_proceed.push_back(Action::ImportModule);
+    }
+
     fn delete_camera(&mut self, cam_id: ensnano_design::CameraId) {
         self.keep_proceed
             .push_back(Action::DesignOperation(DesignOperation::DeleteCamera(
@@ -340,6 +627,10 @@ impl GuiRequests for Requests {
         self.new_suggestion_parameters = Some(param);
     }
 
+    fn set_strand_building_parameters(&mut self, param: StrandBuildingParameters) {
+        self.new_strand_building_parameters = Some(param);
+    }
+
     fn set_grid_position(&mut self, grid_id: usize, position: Vec3) {
         self.keep_proceed
             .push_back(Action::DesignOperation(DesignOperation::SetGridPosition {
@@ -357,9 +648,155 @@ impl GuiRequests for Requests {
         ))
     }
 
+    fn reorder_strands(&mut self, key: SortKey) {
+        self.keep_proceed
+            .push_back(Action::DesignOperation(DesignOperation::ReorderStrands(
+                key,
+            )))
+    }
+
+    fn recolor_strands(&mut self, palette: ColorBlindPalette) {
+        self.keep_proceed
+            .push_back(Action::DesignOperation(DesignOperation::RecolorStrands {
+                palette,
+            }))
+    }
+
+    fn insert_loop(&mut self, at: Nucl, length: usize) {
+        self.keep_proceed
+            .push_back(Action::DesignOperation(DesignOperation::AddLoop {
+                at,
+                length,
+            }))
+    }
+
+    fn create_hairpin(&mut self, at: Nucl, stem_length: usize, loop_length: usize) {
+        self.keep_proceed
+            .push_back(Action::DesignOperation(DesignOperation::CreateHairpin {
+                at,
+                stem_length,
+                loop_length,
+            }))
+    }
+
+    fn break_scaffold_at(&mut self, at: Nucl) {
+        self.keep_proceed.push_back(Action::BreakScaffoldAt(at))
+    }
+
+    fn cleanup_orphans(&mut self) {
+        self.keep_proceed.push_back(Action::CleanupOrphans)
+    }
+
+    fn connect_strands(
+        &mut self,
+        (strand_a, end_a): (usize, StrandEnd),
+        (strand_b, end_b): (usize, StrandEnd),
+    ) {
+        self.keep_proceed.push_back(Action::TryDesignOperation(
+            DesignOperation::ConnectStrands {
+                strand_a,
+                end_a,
+                strand_b,
+                end_b,
+            },
+        ))
+    }
+
+    fn optimize_rolls(&mut self, target_helices: Option<Vec<usize>>) {
+        self.keep_proceed
+            .push_back(Action::OptimizeRolls(target_helices))
+    }
+
+    fn add_torus_knot(&mut self, knot: TorusKnot) {
+        self.keep_proceed
+            .push_back(Action::TryDesignOperation(DesignOperation::AddTorusKnot {
+                p: knot.p,
+                q: knot.q,
+                radius: knot.radius,
+                tube_radius: knot.tube_radius,
+            }))
+    }
+
+    fn flip_strand(&mut self, strand_id: usize) {
+        self.keep_proceed
+            .push_back(Action::DesignOperation(DesignOperation::FlipStrand {
+                strand_id,
+            }))
+    }
+
+    fn close_context_menu(&mut self) {
+        self.keep_proceed.push_back(Action::CloseContextMenu);
+    }
+
+    fn duplicate_grid(&mut self, grid_id: usize, offset: Vec3) {
+        self.keep_proceed
+            .push_back(Action::DesignOperation(DesignOperation::DuplicateGrid {
+                grid_id,
+                offset,
+            }))
+    }
+
+    fn mirror_grid(&mut self, grid_id: usize, axis: MirrorAxis) {
+        self.keep_proceed
+            .push_back(Action::DesignOperation(DesignOperation::MirrorGrid {
+                grid_id,
+                axis,
+            }))
+    }
+
+    fn preview_grid_twist_offset(&mut self, grid_id: usize, twist_offset: f32) {
+        self.keep_proceed.push_back(Action::SilentDesignOperation(
+            DesignOperation::SetGridTwistOffset {
+                grid_id,
+                twist_offset,
+            },
+        ))
+    }
+
+    fn set_grid_twist_offset(&mut self, grid_id: usize, initial: f32, twist_offset: f32) {
+        // The previews have modified the design without creating an undoable step, so the
+        // initial twist offset is restored first for it to be the one saved on the undo stack.
+        self.keep_proceed.push_back(Action::SilentDesignOperation(
+            DesignOperation::SetGridTwistOffset {
+                grid_id,
+                twist_offset: initial,
+            },
+        ));
+        self.keep_proceed.push_back(Action::DesignOperation(
+            DesignOperation::SetGridTwistOffset {
+                grid_id,
+                twist_offset,
+            },
+        ))
+    }
+
+    fn show_mirror_plane(&mut self, axis: Option<MirrorAxis>) {
+        self.mirror_plane = Some(axis);
+    }
+
     fn flip_split_views(&mut self) {
         self.keep_proceed.push_back(Action::FlipSplitViews);
     }
+
+    fn start_2d_xover_tool(&mut self) {
+        self.keep_proceed.push_back(Action::Start2dXoverTool);
+    }
+
+    fn start_macro_recording(&mut self) {
+        self.keep_proceed.push_back(Action::StartRecording);
+    }
+
+    fn stop_macro_recording(&mut self) {
+        self.keep_proceed.push_back(Action::StopRecording);
+    }
+
+    fn play_macro(&mut self) {
+        self.keep_proceed.push_back(Action::PlayMacro);
+    }
+
+    fn replay_session(&mut self) {
+        self.keep_proceed.push_back(Action::ReplaySession);
+    }
 }
 
 fn rigid_parameters(parameters: RigidBodyParametersRequest) -> RigidBodyConstants {
@@ -368,6 +805,7 @@ fn rigid_parameters(parameters: RigidBodyParametersRequest) -> RigidBodyConstant
         k_friction: 10f32.powf(parameters.k_friction),
         mass: 10f32.powf(parameters.mass_factor),
         volume_exclusion: parameters.volume_exclusion,
+        freeze_ssdna: parameters.freeze_ssdna,
         brownian_motion: parameters.brownian_motion,
         brownian_rate: 10f32.powf(parameters.brownian_rate),
         brownian_amplitude: parameters.brownian_amplitude,
@@ -33,6 +33,7 @@ impl SceneRequests for Requests {
         selection: Vec<Selection>,
         center_of_selection: Option<ensnano_interactor::CenterOfSelection>,
     ) {
+        self.sync_3d_selection = Some(selection.clone());
         self.new_selection = Some(selection);
         self.new_center_of_selection = Some(center_of_selection);
     }
@@ -62,14 +63,6 @@ impl SceneRequests for Requests {
         self.center_selection = Some((selection, app_id));
     }
 
-    fn undo(&mut self) {
-        self.undo = Some(());
-    }
-
-    fn redo(&mut self) {
-        self.redo = Some(());
-    }
-
     fn update_builder_position(&mut self, position: isize) {
         self.keep_proceed
             .push_back(Action::DesignOperation(DesignOperation::MoveBuilders(
@@ -106,4 +99,13 @@ impl SceneRequests for Requests {
                 .push_back(Action::RotateGroupPivot(rotation))
         }
     }
+
+    fn add_measurement(&mut self, measurement: ensnano_interactor::Measurement) {
+        self.keep_proceed
+            .push_back(Action::AddMeasurement(measurement))
+    }
+
+    fn edit_nucleotide_annotation(&mut self, nucl: Nucl) {
+        self.edited_annotation = Some(nucl);
+    }
 }
@@ -84,6 +84,10 @@ pub(crate) fn poll_all<R: DerefMut<Target = Requests>>(
         main_state.change_selection_mode(selection_mode)
     }
 
+    if let Some(locked) = requests.selection_lock.take() {
+        main_state.set_selection_lock(locked)
+    }
+
     if let Some(action_mode) = requests.action_mode.take() {
         main_state.change_action_mode(action_mode)
     }
@@ -155,10 +159,44 @@ pub(crate) fn poll_all<R: DerefMut<Target = Requests>>(
         main_state.push_action(Action::NotifyApps(Notification::ShowTorsion(b)))
     }
 
+    if let Some(b) = requests.show_local_twist_request.take() {
+        main_state.push_action(Action::NotifyApps(Notification::ShowLocalTwist(b)))
+    }
+
+    if let Some(coloring) = requests.analysis_coloring_request.take() {
+        main_state.push_action(Action::NotifyApps(Notification::AnalysisColoring(coloring)))
+    }
+
+    if let Some(b) = requests.show_helix_numbers_request.take() {
+        main_state.push_action(Action::NotifyApps(Notification::ShowHelixNumbers(b)))
+    }
+
+    if let Some(b) = requests.show_ruler_request.take() {
+        main_state.push_action(Action::NotifyApps(Notification::ShowRuler(b)))
+    }
+
+    if let Some(parameters) = requests.ruler_parameters_request.take() {
+        main_state.push_action(Action::NotifyApps(Notification::RulerParameters(
+            parameters,
+        )))
+    }
+
+    if let Some(b) = requests.show_lattice_grid_request.take() {
+        main_state.push_action(Action::NotifyApps(Notification::ShowLatticeGrid(b)))
+    }
+
     if let Some(fog) = requests.fog.take() {
         main_state.push_action(Action::Fog(fog))
     }
 
+    if requests.center_fog_on_selection.take().is_some() {
+        main_state.push_action(Action::NotifyApps(Notification::CenterFogOnSelection))
+    }
+
+    if requests.reset_fog_center.take().is_some() {
+        main_state.push_action(Action::NotifyApps(Notification::ResetFogCenter))
+    }
+
     if let Some(hyperboloid) = requests.new_hyperboloid.take() {
         main_state.push_action(Action::NewHyperboloid(hyperboloid))
     }
@@ -309,6 +347,48 @@ pub(crate) fn poll_all<R: DerefMut<Target = Requests>>(
         main_state.push_action(Action::NotifyApps(Notification::Background3D(bg)))
     }
 
+    if let Some(parameters) = requests.depth_of_field.take() {
+        main_state.push_action(Action::NotifyApps(Notification::DepthOfField(parameters)))
+    }
+
+    if let Some(parameters) = requests.fly_through.take() {
+        main_state.push_action(Action::NotifyApps(Notification::FlyThrough(parameters)))
+    }
+
+    if let Some(parameters) = requests.eye_dome_lighting.take() {
+        main_state.push_action(Action::NotifyApps(Notification::EyeDomeLighting(
+            parameters,
+        )))
+    }
+
+    if let Some(parameters) = requests.ambient_occlusion.take() {
+        main_state.push_action(Action::NotifyApps(Notification::AmbientOcclusion(
+            parameters,
+        )))
+    }
+
+    if let Some(quality) = requests.post_processing_quality.take() {
+        main_state.push_action(Action::NotifyApps(Notification::PostProcessingQuality(
+            quality,
+        )))
+    }
+
+    if let Some(parameters) = requests.lighting.take() {
+        main_state.push_action(Action::NotifyApps(Notification::Lighting(parameters)))
+    }
+
+    if let Some(parameters) = requests.tone_mapping.take() {
+        main_state.push_action(Action::NotifyApps(Notification::ToneMapping(parameters)))
+    }
+
+    if let Some(mode) = requests.sonification.take() {
+        main_state.push_action(Action::NotifyApps(Notification::Sonification(mode)))
+    }
+
+    if let Some(axis) = requests.mirror_plane.take() {
+        main_state.push_action(Action::NotifyApps(Notification::ShowMirrorPlane(axis)))
+    }
+
     if requests.undo.take().is_some() {
         main_state.push_action(Action::Undo);
     }
@@ -329,6 +409,14 @@ pub(crate) fn poll_all<R: DerefMut<Target = Requests>>(
         main_state.messages.lock().unwrap().show_help()
     }
 
+    if let Some(nucl) = requests.edited_annotation.take() {
+        main_state
+            .messages
+            .lock()
+            .unwrap()
+            .push_annotation_edition(nucl)
+    }
+
     if let Some(candidates) = requests.new_candidates.take() {
         main_state.update_candidates(candidates);
     }
@@ -340,6 +428,10 @@ pub(crate) fn poll_all<R: DerefMut<Target = Requests>>(
         }
     }
 
+    if let Some(selection) = requests.sync_3d_selection.take() {
+        main_state.push_action(Action::NotifyApps(Notification::Sync3dSelection(selection)))
+    }
+
     if requests.toggle_widget_basis.take().is_some() {
         main_state.toggle_widget_basis()
     }
@@ -377,8 +469,20 @@ pub(crate) fn poll_all<R: DerefMut<Target = Requests>>(
     for action in requests.keep_proceed.drain(..) {
         main_state.pending_actions.push_back(action)
     }
+    if requests.batch_in_progress {
+        main_state.batch_in_progress = true;
+        requests.batch_in_progress = false;
+    }
 
     if let Some(param) = requests.new_suggestion_parameters.take() {
         main_state.set_suggestion_parameters(param);
     }
+
+    if let Some(param) = requests.new_strand_building_parameters.take() {
+        main_state.set_strand_building_parameters(param);
+    }
+
+    if let Some((old, new)) = requests.new_helix_2d_ranges.take() {
+        main_state.set_helix_2d_ranges(old, new);
+    }
 }
@@ -15,31 +15,38 @@ ENSnano, a 3d graphical application for DNA nanostructures.
     You should have received a copy of the GNU General Public License
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
+use crate::controller::ChanelReaderUpdate;
 use iced_wgpu::wgpu;
 use iced_winit::winit;
 use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::PathBuf;
 use std::rc::Rc;
-use std::sync::{Arc, Mutex};
+use std::sync::{mpsc, Arc, Mutex};
 use std::time::Duration;
 use ultraviolet::{Mat4, Rotor3, Vec3};
 
+use crate::consts::{MAX_EXPORT_DIMENSION, SEQUENCE_BASES};
 use crate::scene::camera::FiniteVec3;
 use crate::utils;
 use crate::{DrawArea, PhySize, WindowEvent};
 use ensnano_design::{group_attributes::GroupPivot, Nucl};
 use ensnano_interactor::{
     application::{AppId, Application, Notification},
+    graphics::CameraAnimation,
     operation::*,
-    ActionMode, CenterOfSelection, DesignOperation, Selection, SelectionMode, StrandBuilder,
-    WidgetBasis,
+    ActionMode, CenterOfSelection, DesignOperation, Measurement, Selection, SelectionMode,
+    StrandBuilder, Trajectory, WidgetBasis,
 };
 use instance::Instance;
 use utils::instance;
 use wgpu::{Device, Queue};
 use winit::dpi::PhysicalPosition;
+use winit::event::{ElementState, KeyboardInput, VirtualKeyCode};
 
 /// Computation of the view and projection matrix.
 mod camera;
+pub use camera::Camera as SceneCamera;
 /// Display of the scene
 mod view;
 use view::{
@@ -50,15 +57,22 @@ use view::{
 pub use view::{FogParameters, GridInstance};
 /// Handling of inputs and notifications
 mod controller;
-use controller::{Consequence, Controller, WidgetTarget};
+use controller::{ClickModifier, Consequence, Controller, WidgetTarget};
 /// Handling of designs and internal data
 mod data;
 pub use controller::ClickMode;
 use data::Data;
 pub use data::DesignReader;
+/// Camera paths of the recorded animations
+mod animation;
 mod element_selector;
 use element_selector::{ElementSelector, SceneElement};
 mod maths_3d;
+/// Export of the designs as triangle meshes
+mod mesh_export;
+/// Audio feedback on the design and on the running simulation
+mod sonification;
+use sonification::Sonification;
 
 type ViewPtr = Rc<RefCell<View>>;
 type DataPtr<R> = Rc<RefCell<Data<R>>>;
@@ -79,6 +93,26 @@ pub struct Scene<S: AppState> {
     element_selector: ElementSelector,
     older_state: S,
     requests: Arc<Mutex<dyn Requests>>,
+    /// The updates broadcasted by the main state's `ChanelReader`
+    chanel_updates: Option<mpsc::Receiver<ChanelReaderUpdate>>,
+    sonification: Sonification,
+    /// The sequence being typed on the selected strand, if any
+    sequence_edition: Option<SequenceEdition>,
+    /// Mouse events whose handling waits for the picking textures to be read
+    delayed_inputs: Vec<(WindowEvent<'static>, PhysicalPosition<f64>)>,
+    /// True if the user centered the fog on the selection, in which case the fog does not follow
+    /// the pivot until the user resets its center
+    fog_center_is_fixed: bool,
+}
+
+/// The state of the edition of a strand's sequence by typing its bases in the 3D view
+struct SequenceEdition {
+    s_id: usize,
+    sequence: String,
+    /// The number of nucleotides of the strand, which bounds the length of the sequence
+    length: usize,
+    /// Whether the letters were displayed before the edition started
+    draw_letter: bool,
 }
 
 impl<S: AppState> Scene<S> {
@@ -131,7 +165,30 @@ impl<S: AppState> Scene<S> {
             requests,
             element_selector,
             older_state: inital_state,
+            chanel_updates: None,
+            sonification: Sonification::new(),
+            sequence_edition: None,
+            delayed_inputs: Vec::new(),
+            fog_center_is_fixed: false,
+        }
+    }
+
+    /// Start receiving the updates broadcasted by the main state's `ChanelReader`
+    pub fn subscribe_to_chanel_reader(&mut self, chanel: mpsc::Receiver<ChanelReaderUpdate>) {
+        self.chanel_updates = Some(chanel);
+    }
+
+    /// Return true if an update that can modify the appearance of the scene was received
+    fn read_chanel_updates(&self) -> bool {
+        let mut ret = false;
+        if let Some(chanel) = self.chanel_updates.as_ref() {
+            for update in chanel.try_iter() {
+                if let ChanelReaderUpdate::NucleotideColors(_) = update {
+                    ret = true;
+                }
+            }
         }
+        ret
     }
 
     /*
@@ -152,6 +209,41 @@ impl<S: AppState> Scene<S> {
         event: &WindowEvent,
         cursor_position: PhysicalPosition<f64>,
         app_state: &S,
+    ) {
+        if self.sequence_edition.is_some() {
+            if self.edited_strand_is_selected(app_state) {
+                if Self::is_keyboard_event(event) {
+                    self.edit_sequence(event);
+                    return;
+                }
+            } else {
+                self.stop_sequence_edition();
+            }
+        } else if Self::is_key_press(event, VirtualKeyCode::Return)
+            && self.start_sequence_edition(app_state)
+        {
+            return;
+        }
+        // A click is handled once the picking textures match the view, so that the clicked
+        // element is not read on outdated pixels. The mouse events that follow a delayed click are
+        // delayed as well, to be handled in the right order.
+        if !self.delayed_inputs.is_empty()
+            || (Self::is_click(event) && !self.element_selector.is_up_to_date())
+        {
+            if let Some(event) = Self::copy_mouse_event(event) {
+                self.element_selector.request_update();
+                self.delayed_inputs.push((event, cursor_position));
+                return;
+            }
+        }
+        self.handle_input(event, cursor_position, app_state);
+    }
+
+    fn handle_input(
+        &mut self,
+        event: &WindowEvent,
+        cursor_position: PhysicalPosition<f64>,
+        app_state: &S,
     ) {
         let consequence = self.controller.input(
             event,
@@ -162,6 +254,190 @@ impl<S: AppState> Scene<S> {
         self.read_consequence(consequence, app_state);
     }
 
+    /// Handle the delayed mouse events once the picking textures requested after them have been
+    /// read.
+    ///
+    /// The view may have changed again in the meantime, for example during a simulation, in
+    /// which case waiting for up-to-date textures could delay the events forever.
+    fn handle_delayed_inputs(&mut self, app_state: &S) {
+        if self.delayed_inputs.is_empty() {
+            return;
+        }
+        if self.element_selector.poll() || self.element_selector.is_up_to_date() {
+            for (event, cursor_position) in std::mem::take(&mut self.delayed_inputs) {
+                self.handle_input(&event, cursor_position, app_state);
+            }
+        } else {
+            self.element_selector.request_update();
+        }
+    }
+
+    /// Update the candidate when new pixels of the fake textures are read, so that the element
+    /// under a still mouse cursor is highlighted when the view changes.
+    fn refresh_hover(&mut self, app_state: &S) {
+        if self.delayed_inputs.is_empty() && self.element_selector.poll() {
+            let consequence = self
+                .controller
+                .refresh_hover(&mut self.element_selector, app_state);
+            self.read_consequence(consequence, app_state);
+        }
+    }
+
+    fn is_click(event: &WindowEvent) -> bool {
+        matches!(event, WindowEvent::MouseInput { .. })
+    }
+
+    /// Return a copy of `event` if it is a mouse event whose handling can be delayed
+    #[allow(deprecated)]
+    fn copy_mouse_event(event: &WindowEvent) -> Option<WindowEvent<'static>> {
+        match event {
+            WindowEvent::CursorMoved {
+                device_id,
+                position,
+                modifiers,
+            } => Some(WindowEvent::CursorMoved {
+                device_id: *device_id,
+                position: *position,
+                modifiers: *modifiers,
+            }),
+            WindowEvent::MouseInput {
+                device_id,
+                state,
+                button,
+                modifiers,
+            } => Some(WindowEvent::MouseInput {
+                device_id: *device_id,
+                state: *state,
+                button: *button,
+                modifiers: *modifiers,
+            }),
+            WindowEvent::MouseWheel {
+                device_id,
+                delta,
+                phase,
+                modifiers,
+            } => Some(WindowEvent::MouseWheel {
+                device_id: *device_id,
+                delta: *delta,
+                phase: *phase,
+                modifiers: *modifiers,
+            }),
+            _ => None,
+        }
+    }
+
+    fn is_keyboard_event(event: &WindowEvent) -> bool {
+        matches!(
+            event,
+            WindowEvent::KeyboardInput { .. } | WindowEvent::ReceivedCharacter(_)
+        )
+    }
+
+    fn is_key_press(event: &WindowEvent, key: VirtualKeyCode) -> bool {
+        matches!(
+            event,
+            WindowEvent::KeyboardInput {
+                input: KeyboardInput {
+                    virtual_keycode: Some(k),
+                    state: ElementState::Pressed,
+                    ..
+                },
+                ..
+            } if *k == key
+        )
+    }
+
+    fn edited_strand_is_selected(&self, app_state: &S) -> bool {
+        match (app_state.get_selection(), self.sequence_edition.as_ref()) {
+            ([Selection::Strand(_, s_id)], Some(edition)) => *s_id as usize == edition.s_id,
+            _ => false,
+        }
+    }
+
+    /// Start typing the sequence of the selected strand. Return true iff exactly one strand is
+    /// selected in strand selection mode.
+    fn start_sequence_edition(&mut self, app_state: &S) -> bool {
+        if app_state.get_selection_mode() != SelectionMode::Strand {
+            return false;
+        }
+        let s_id = if let [Selection::Strand(_, s_id)] = app_state.get_selection() {
+            *s_id as usize
+        } else {
+            return false;
+        };
+        let reader = app_state.get_design_reader();
+        let strand = if let Some(strand) = reader.get_strand_with_id(s_id) {
+            strand
+        } else {
+            return false;
+        };
+        let length = strand.length();
+        let sequence = strand
+            .sequence
+            .as_deref()
+            .unwrap_or("")
+            .chars()
+            .take(length)
+            .collect();
+        let draw_letter = self.view.borrow().get_draw_letter();
+        self.view.borrow_mut().set_draw_letter(true);
+        self.data.borrow_mut().set_edited_strand(Some(s_id));
+        self.sequence_edition = Some(SequenceEdition {
+            s_id,
+            sequence,
+            length,
+            draw_letter,
+        });
+        true
+    }
+
+    fn stop_sequence_edition(&mut self) {
+        if let Some(edition) = self.sequence_edition.take() {
+            self.view.borrow_mut().set_draw_letter(edition.draw_letter);
+            self.data.borrow_mut().set_edited_strand(None);
+            self.requests.lock().unwrap().suspend_op();
+        }
+    }
+
+    /// Append the typed base to the sequence being edited, or remove its last base on Backspace.
+    /// Return and Escape end the edition.
+    fn edit_sequence(&mut self, event: &WindowEvent) {
+        if Self::is_key_press(event, VirtualKeyCode::Return)
+            || Self::is_key_press(event, VirtualKeyCode::Escape)
+        {
+            self.stop_sequence_edition();
+            return;
+        }
+        let edition = if let Some(edition) = self.sequence_edition.as_mut() {
+            edition
+        } else {
+            return;
+        };
+        let modified = if Self::is_key_press(event, VirtualKeyCode::Back) {
+            edition.sequence.pop().is_some()
+        } else if let WindowEvent::ReceivedCharacter(c) = event {
+            let base = c.to_ascii_uppercase();
+            if SEQUENCE_BASES.contains(base) && edition.sequence.len() < edition.length {
+                edition.sequence.push(base);
+                true
+            } else {
+                false
+            }
+        } else {
+            false
+        };
+        if modified {
+            let operation = DesignOperation::SetStrandSequence {
+                s_id: edition.s_id,
+                sequence: edition.sequence.clone(),
+            };
+            self.requests
+                .lock()
+                .unwrap()
+                .apply_design_operation(operation);
+        }
+    }
+
     fn check_timers(&mut self, app_state: &S) {
         let consequence = self.controller.check_timers();
         self.read_consequence(consequence, app_state);
@@ -286,29 +562,51 @@ impl<S: AppState> Scene<S> {
                 self.controller.swing(-x, -y);
                 self.notify(SceneNotification::CameraMoved);
             }
-            Consequence::ToggleWidget => self.requests.lock().unwrap().toggle_widget_basis(),
-            Consequence::BuildEnded => self.requests.lock().unwrap().suspend_op(),
-            Consequence::Undo => self.requests.lock().unwrap().undo(),
-            Consequence::Redo => self.requests.lock().unwrap().redo(),
-            Consequence::Building(position) => {
-                self.requests
-                    .lock()
-                    .unwrap()
-                    .update_builder_position(position);
+            Consequence::BuildEnded(position) => {
+                self.data.borrow_mut().clear_strand_builders_preview();
+                let mut requests = self.requests.lock().unwrap();
+                if let Some(position) = position {
+                    requests.update_builder_position(position);
+                }
+                requests.suspend_op();
+            }
+            Consequence::BuildingPreview(position) => {
+                self.data
+                    .borrow_mut()
+                    .show_strand_builders_preview(app_state.get_strand_builders(), position);
             }
             Consequence::Candidate(element) => self.set_candidate(element, app_state),
             Consequence::PivotElement(element) => {
                 self.data.borrow_mut().set_pivot_element(element, app_state);
-                let pivot = self.data.borrow().get_pivot_position();
-                self.view.borrow_mut().update(ViewUpdate::FogCenter(pivot));
-            }
-            Consequence::ElementSelected(element, adding) => {
-                if adding {
-                    self.add_selection(element, app_state.get_selection(), app_state)
-                } else {
-                    self.select(element, app_state)
+                if !self.fog_center_is_fixed {
+                    let pivot = self.data.borrow().get_pivot_position();
+                    self.view.borrow_mut().update(ViewUpdate::FogCenter(pivot));
                 }
             }
+            Consequence::RectangleDragged(corner1, corner2) => self
+                .view
+                .borrow_mut()
+                .update(ViewUpdate::SelectionRectangle(Some([corner1, corner2]))),
+            Consequence::RectangleSelected(corner1, corner2, adding) => {
+                self.view
+                    .borrow_mut()
+                    .update(ViewUpdate::SelectionRectangle(None));
+                self.select_rectangle(corner1, corner2, adding, app_state)
+            }
+            Consequence::ElementSelected(element, _)
+                if matches!(app_state.get_action_mode().0, ActionMode::Measure(_)) =>
+            {
+                self.measure(element, app_state)
+            }
+            Consequence::ElementSelected(element, modifier) => match modifier {
+                ClickModifier::Replace => self.select(element, app_state),
+                ClickModifier::Append => {
+                    self.add_selection(element, app_state.get_selection(), false, app_state)
+                }
+                ClickModifier::Toggle => {
+                    self.add_selection(element, app_state.get_selection(), true, app_state)
+                }
+            },
             Consequence::InitFreeXover(nucl, d_id, position) => {
                 self.data.borrow_mut().init_free_xover(nucl, position, d_id)
             }
@@ -341,6 +639,7 @@ impl<S: AppState> Scene<S> {
             Consequence::PasteCandidate(element) => self.pasting_candidate(element),
             Consequence::Paste(element) => self.attempt_paste(element),
             Consequence::DoubleClick(element) => {
+                let nucl = self.data.borrow().element_to_nucl(&element, true);
                 let selection = self.data.borrow().to_selection(element, app_state);
                 if let Some(selection) = selection {
                     self.requests
@@ -348,6 +647,12 @@ impl<S: AppState> Scene<S> {
                         .unwrap()
                         .request_center_selection(selection, AppId::Scene);
                 }
+                if let Some((nucl, _)) = nucl {
+                    self.requests
+                        .lock()
+                        .unwrap()
+                        .edit_nucleotide_annotation(nucl);
+                }
             }
             Consequence::InitBuild(nucl) => self.requests.lock().unwrap().apply_design_operation(
                 DesignOperation::RequestStrandBuilders { nucls: vec![nucl] },
@@ -389,16 +694,56 @@ impl<S: AppState> Scene<S> {
         }
     }
 
+    /// Add the clicked nucleotide to the measurement being made
+    fn measure(&mut self, element: Option<SceneElement>, app_state: &S) {
+        let kind = if let ActionMode::Measure(kind) = app_state.get_action_mode().0 {
+            kind
+        } else {
+            return;
+        };
+        let measurement = self.data.borrow_mut().add_measured_element(element, kind);
+        if let Some(measurement) = measurement {
+            self.requests.lock().unwrap().add_measurement(measurement);
+        }
+    }
+
+    /// Select the nucleotides inside a rectangle. See `Data::get_nucls_in_rectangle`.
+    fn select_rectangle(
+        &mut self,
+        corner1: (f32, f32),
+        corner2: (f32, f32),
+        adding: bool,
+        app_state: &S,
+    ) {
+        let in_rectangle = self.data.borrow().get_nucls_in_rectangle(corner1, corner2);
+        let mut selection = if adding {
+            app_state.get_selection().to_vec()
+        } else {
+            Vec::new()
+        };
+        for nucl in in_rectangle {
+            if !selection.contains(&nucl) {
+                selection.push(nucl);
+            }
+        }
+        self.requests.lock().unwrap().set_selection(selection, None);
+    }
+
+    /// Add `element` to the current selection. If `toggle` is true and `element` is already
+    /// selected, it is removed from the selection instead.
     fn add_selection(
         &mut self,
         element: Option<SceneElement>,
         current_selection: &[Selection],
+        toggle: bool,
         app_state: &S,
     ) {
-        let selection =
-            self.data
-                .borrow_mut()
-                .add_to_selection(element, current_selection, app_state);
+        let selection = self.data.borrow_mut().add_to_selection(
+            element,
+            current_selection,
+            toggle,
+            app_state,
+        );
         if let Some((selection, center_of_selection)) = selection {
             self.requests
                 .lock()
@@ -589,6 +934,8 @@ impl<S: AppState> Scene<S> {
 
     fn need_redraw(&mut self, dt: Duration, new_state: S) -> bool {
         self.check_timers(&new_state);
+        self.handle_delayed_inputs(&new_state);
+        self.refresh_hover(&new_state);
         if self.controller.camera_is_moving() {
             self.notify(SceneNotification::CameraMoved);
         }
@@ -596,14 +943,33 @@ impl<S: AppState> Scene<S> {
         if self.update.need_update {
             self.perform_update(dt, &new_state);
         }
-        self.data
-            .borrow_mut()
-            .update_design(new_state.get_design_reader());
+        let design_generation = new_state.get_design_generation();
+        if self.update.design_generation != Some(design_generation) {
+            self.data
+                .borrow_mut()
+                .update_design(new_state.get_design_reader());
+            self.update.design_generation = Some(design_generation);
+            self.update.nb_nucleotides = None;
+        }
+        if new_state.workspace_was_updated(&self.older_state) {
+            self.data
+                .borrow_mut()
+                .update_workspace_designs(new_state.get_workspace_designs());
+        }
         self.data
             .borrow_mut()
             .update_view(&new_state, &self.older_state);
+        if self.sonification.needs_update() {
+            let nb_nucleotides = *self
+                .update
+                .nb_nucleotides
+                .get_or_insert_with(|| new_state.get_design_reader().get_all_nucl_ids().len());
+            self.sonification
+                .update(nb_nucleotides, new_state.get_simulation_kinetic_energy());
+        }
         self.older_state = new_state;
-        let ret = self.view.borrow().need_redraw();
+        let colors_changed = self.read_chanel_updates();
+        let ret = self.view.borrow().need_redraw() || colors_changed;
         if ret {
             log::debug!("Scene requests redraw");
         }
@@ -701,6 +1067,12 @@ pub struct SceneUpdate {
     pub model_matrices: Option<Vec<Mat4>>,
     pub need_update: bool,
     pub camera_update: bool,
+    /// The generation of the design that was last given to the data. The design is only read
+    /// again when the generation of the app state differs from this one.
+    pub design_generation: Option<u64>,
+    /// The number of nucleotides of the design of generation `design_generation`, if it was
+    /// already computed.
+    pub nb_nucleotides: Option<usize>,
 }
 
 impl SceneUpdate {
@@ -717,6 +1089,8 @@ impl SceneUpdate {
             need_update: false,
             camera_update: false,
             model_matrices: None,
+            design_generation: None,
+            nb_nucleotides: None,
         }
     }
 }
@@ -755,6 +1129,79 @@ impl<S: AppState> Scene<S> {
         self.update.need_update = true;
     }
 
+    /// Write a PNG image of `size` pixels of the scene, as seen from the current camera.
+    pub fn export_scene_png(&mut self, size: (u32, u32), path: &PathBuf) -> std::io::Result<()> {
+        use std::io::{Error, ErrorKind};
+        let pixels = self.render_scene_rgba(size)?;
+        let (width, height) = size;
+        let image = image::RgbaImage::from_raw(width, height, pixels)
+            .ok_or_else(|| Error::new(ErrorKind::Other, "Could not read the rendered image"))?;
+        image
+            .save_with_format(path, image::ImageFormat::Png)
+            .map_err(|e| Error::new(ErrorKind::Other, e.to_string()))
+    }
+
+    /// Render an image of `size` pixels of the scene, as seen from the current point of view, and
+    /// return its RGBA pixels.
+    fn render_scene_rgba(&mut self, size: (u32, u32)) -> std::io::Result<Vec<u8>> {
+        use std::io::{Error, ErrorKind};
+        let (width, height) = size;
+        if width == 0 || height == 0 {
+            return Err(Error::new(ErrorKind::InvalidInput, "The image is empty"));
+        }
+        if width > MAX_EXPORT_DIMENSION || height > MAX_EXPORT_DIMENSION {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "The image would measure {}x{} pixels, the maximum is {}x{}",
+                    width, height, MAX_EXPORT_DIMENSION, MAX_EXPORT_DIMENSION
+                ),
+            ));
+        }
+        // The exported image may not have the proportions of the 3D view, so objects outside of
+        // the view may be visible on it
+        let culling = self.data.borrow_mut().set_culling(false, &self.older_state);
+        let pixels = self.view.borrow_mut().render_to_rgba(
+            PhySize::new(width, height),
+            self.controller.get_window_size(),
+            self.area.size,
+        );
+        self.data
+            .borrow_mut()
+            .set_culling(culling, &self.older_state);
+        pixels
+    }
+
+    /// The positions and orientations of the camera at each of the `nb_frames` frames of
+    /// `animation`
+    pub fn animation_viewpoints(
+        &self,
+        animation: &CameraAnimation,
+        nb_frames: u32,
+    ) -> Vec<(Vec3, Rotor3)> {
+        let camera = self.view.borrow().get_camera().borrow().clone();
+        let pivot = self.data.borrow().get_pivot_position();
+        animation::animation_viewpoints(animation, &camera, pivot, nb_frames)
+            .into_iter()
+            .map(|viewpoint| (viewpoint.position, viewpoint.rotor))
+            .collect()
+    }
+
+    /// Render an image of `size` pixels of the scene, as seen from `viewpoint`, and return its
+    /// RGBA pixels. The camera is moved back to its current viewpoint afterwards.
+    pub fn render_viewpoint(
+        &mut self,
+        viewpoint: (Vec3, Rotor3),
+        size: (u32, u32),
+    ) -> std::io::Result<Vec<u8>> {
+        let camera = self.view.borrow().get_camera().borrow().clone();
+        self.controller.teleport_camera(viewpoint.0, viewpoint.1);
+        let result = self.render_scene_rgba(size);
+        self.controller
+            .teleport_camera(camera.position, camera.rotor);
+        result
+    }
+
     fn resize(&mut self, window_size: PhySize) {
         self.view.borrow_mut().update(ViewUpdate::Size(window_size));
         self.controller.resize(window_size, self.area.size);
@@ -774,23 +1221,40 @@ impl<S: AppState> Application for Scene<S> {
         let older_state = self.older_state.clone();
         match notification {
             Notification::ClearDesigns => self.clear_design(),
-            Notification::ToggleText(value) => self.view.borrow_mut().set_draw_letter(value),
-            Notification::FitRequest => self.fit_design(),
+            Notification::ToggleText(value) => {
+                if let Some(edition) = self.sequence_edition.as_mut() {
+                    edition.draw_letter = value;
+                } else {
+                    self.view.borrow_mut().set_draw_letter(value)
+                }
+            }
+            Notification::FitRequest => {
+                self.controller.save_camera_viewpoint();
+                self.fit_design()
+            }
             Notification::NewSensitivity(x) => self.change_sensitivity(x),
             Notification::Save(_) => (),
             Notification::CameraTarget((target, up)) => {
+                self.controller.save_camera_viewpoint();
                 self.set_camera_target(target, up, &older_state);
                 self.notify(SceneNotification::CameraMoved);
             }
             Notification::TeleportCamera(position, orientation) => {
+                self.controller.save_camera_viewpoint();
                 self.controller.teleport_camera(position, orientation);
                 self.notify(SceneNotification::CameraMoved);
             }
+            Notification::TurnCamera(xz, yz) => {
+                self.controller.turn_camera(xz, yz);
+                self.notify(SceneNotification::CameraMoved);
+            }
             Notification::CameraRotation(xz, yz, xy) => {
+                self.controller.save_camera_viewpoint();
                 self.request_camera_rotation(xz, yz, xy, &older_state);
                 self.notify(SceneNotification::CameraMoved);
             }
             Notification::Centering(nucl, design_id) => {
+                self.controller.save_camera_viewpoint();
                 if let Some(position) = self.data.borrow().get_nucl_position(nucl, design_id) {
                     self.controller.center_camera(position);
                 }
@@ -798,6 +1262,7 @@ impl<S: AppState> Application for Scene<S> {
             }
             Notification::CenterSelection(selection, app_id) => {
                 if app_id != AppId::Scene {
+                    self.controller.save_camera_viewpoint();
                     self.data
                         .borrow_mut()
                         .notify_selection(vec![selection].as_slice());
@@ -812,14 +1277,75 @@ impl<S: AppState> Application for Scene<S> {
                 }
             }
             Notification::ShowTorsion(_) => (),
+            Notification::ShowLocalTwist(b) => self.data.borrow_mut().show_local_twist(b),
+            Notification::AnalysisColoring(coloring) => {
+                self.data.borrow_mut().set_analysis_coloring(coloring)
+            }
+            Notification::ShowHelixNumbers(b) => self.data.borrow_mut().show_helix_numbers(b),
+            Notification::ShowRuler(_) => (),
+            Notification::RulerParameters(_) => (),
+            Notification::ShowLatticeGrid(_) => (),
             Notification::ModifersChanged(modifiers) => self.controller.update_modifiers(modifiers),
             Notification::Split2d => (),
             Notification::Redim2dHelices(_) => (),
             Notification::RenderingMode(mode) => self.view.borrow_mut().rendering_mode(mode),
             Notification::Background3D(bg) => self.view.borrow_mut().background3d(bg),
+            Notification::ColorTheme(theme) => {
+                self.view
+                    .borrow_mut()
+                    .set_background_color(theme.background);
+                self.data.borrow_mut().set_color_theme(theme);
+            }
             Notification::Fog(fog) => self.fog_request(fog),
+            Notification::CenterFogOnSelection => {
+                if let Some(position) = self.data.borrow().get_selected_position() {
+                    self.fog_center_is_fixed = true;
+                    self.view
+                        .borrow_mut()
+                        .update(ViewUpdate::FogCenter(Some(position)));
+                }
+            }
+            Notification::ResetFogCenter => {
+                self.fog_center_is_fixed = false;
+                let pivot = self.data.borrow().get_pivot_position();
+                self.view.borrow_mut().update(ViewUpdate::FogCenter(pivot));
+            }
+            Notification::DepthOfField(parameters) => {
+                self.view.borrow_mut().set_depth_of_field(parameters)
+            }
+            Notification::FlyThrough(parameters) => self.controller.set_fly_through(parameters),
+            Notification::EyeDomeLighting(parameters) => {
+                self.view.borrow_mut().set_eye_dome_lighting(parameters)
+            }
+            Notification::AmbientOcclusion(parameters) => {
+                self.view.borrow_mut().set_ambient_occlusion(parameters)
+            }
+            Notification::PostProcessingQuality(quality) => {
+                self.view.borrow_mut().set_post_processing_quality(quality)
+            }
+            Notification::Lighting(parameters) => self.view.borrow_mut().set_lighting(parameters),
+            Notification::ToneMapping(parameters) => {
+                self.view.borrow_mut().set_tone_mapping(parameters)
+            }
+            Notification::Sonification(mode) => self.sonification.set_mode(mode),
+            Notification::ShowMirrorPlane(axis) => self.view.borrow_mut().show_mirror_plane(axis),
+            Notification::Sync3dSelection(_) => (),
             Notification::WindowFocusLost => self.controller.stop_camera_movement(),
             Notification::FlipSplitViews => (),
+            Notification::FlashXover(_, _) => (),
+            Notification::Start2dXoverTool => (),
+            Notification::UndoCameraMove => {
+                self.controller.undo_camera_move();
+                self.notify(SceneNotification::CameraMoved);
+            }
+            Notification::RedoCameraMove => {
+                self.controller.redo_camera_move();
+                self.notify(SceneNotification::CameraMoved);
+            }
+            Notification::CameraFovy(fovy) => {
+                self.view.borrow_mut().set_fovy(fovy);
+                self.notify(SceneNotification::CameraMoved);
+            }
         }
     }
 
@@ -865,6 +1391,10 @@ impl<S: AppState> Application for Scene<S> {
         ret
     }
 
+    fn get_fovy(&self) -> Option<f32> {
+        Some(self.view.borrow().get_projection().borrow().get_fovy())
+    }
+
     fn get_current_selection_pivot(&self) -> Option<GroupPivot> {
         self.view.borrow().get_current_pivot()
     }
@@ -872,6 +1402,34 @@ impl<S: AppState> Application for Scene<S> {
     fn is_splited(&self) -> bool {
         false
     }
+
+    fn has_keyboard_priority(&self) -> bool {
+        self.sequence_edition.is_some()
+    }
+
+    fn export_scene_png(&mut self, size: (u32, u32), path: &PathBuf) -> std::io::Result<()> {
+        Scene::export_scene_png(self, size, path)
+    }
+
+    fn animation_viewpoints(
+        &self,
+        animation: &CameraAnimation,
+        nb_frames: u32,
+    ) -> Vec<(Vec3, Rotor3)> {
+        Scene::animation_viewpoints(self, animation, nb_frames)
+    }
+
+    fn render_viewpoint(
+        &mut self,
+        viewpoint: (Vec3, Rotor3),
+        size: (u32, u32),
+    ) -> std::io::Result<Vec<u8>> {
+        Scene::render_viewpoint(self, viewpoint, size)
+    }
+
+    fn export_mesh(&mut self, path: &PathBuf) -> std::io::Result<()> {
+        self.data.borrow().export_mesh(path)
+    }
 }
 
 pub trait AppState: Clone {
@@ -883,6 +1441,8 @@ pub trait AppState: Clone {
     fn design_was_modified(&self, other: &Self) -> bool;
     fn design_model_matrix_was_updated(&self, other: &Self) -> bool;
     fn get_selection_mode(&self) -> SelectionMode;
+    /// True if clicking on empty space must not clear the selection
+    fn is_selection_locked(&self) -> bool;
     fn get_action_mode(&self) -> (ActionMode, WidgetBasis);
     fn get_design_reader(&self) -> Self::DesignReader;
     fn get_strand_builders(&self) -> &[StrandBuilder];
@@ -893,6 +1453,26 @@ pub trait AppState: Clone {
     fn get_current_group_pivot(&self) -> Option<ensnano_design::group_attributes::GroupPivot>;
     fn get_current_group_id(&self) -> Option<ensnano_design::GroupId>;
     fn suggestion_parameters_were_updated(&self, other: &Self) -> bool;
+    /// The nucleotides of the path that must be highlighted
+    fn get_highlighted_path(&self) -> &[Nucl];
+    fn highlighted_path_was_updated(&self, other: &Self) -> bool;
+    /// Colors of individual nucleotides that override the color of their strand
+    fn get_nucleotide_colors(&self) -> &HashMap<Nucl, [f32; 3]>;
+    fn nucleotide_colors_were_updated(&self, other: &Self) -> bool;
+    /// The distances between pairs of nucleotides measured by the user
+    fn get_measurements(&self) -> &[Measurement];
+    fn measurements_were_updated(&self, other: &Self) -> bool;
+    /// The loaded trajectory whose current frame is displayed, if any
+    fn get_trajectory(&self) -> Option<&Trajectory>;
+    fn trajectory_was_updated(&self, other: &Self) -> bool;
+    /// The kinetic energy of the system of the running simulation, if any
+    fn get_simulation_kinetic_energy(&self) -> Option<f32>;
+    /// An identifier of the version of the design. It changes each time the design is modified.
+    fn get_design_generation(&self) -> u64;
+    /// The visible designs of the workspace, with the model matrices that place them in the scene
+    fn get_workspace_designs(&self) -> Vec<(Self::DesignReader, Mat4)>;
+    fn is_main_design_visible(&self) -> bool;
+    fn workspace_was_updated(&self, other: &Self) -> bool;
 }
 
 pub trait Requests {
@@ -909,11 +1489,13 @@ pub trait Requests {
     fn xover_request(&mut self, source: Nucl, target: Nucl, design_id: usize);
     fn suspend_op(&mut self);
     fn request_center_selection(&mut self, selection: Selection, app_id: AppId);
-    fn undo(&mut self);
-    fn redo(&mut self);
     fn update_builder_position(&mut self, position: isize);
     fn toggle_widget_basis(&mut self);
     fn set_current_group_pivot(&mut self, pivot: GroupPivot);
     fn translate_group_pivot(&mut self, translation: Vec3);
     fn rotate_group_pivot(&mut self, rotation: Rotor3);
+    /// Add the distance between two nucleotides to the list of measurements
+    fn add_measurement(&mut self, measurement: Measurement);
+    /// Open the editor of the label attached to `nucl`
+    fn edit_nucleotide_annotation(&mut self, nucl: Nucl);
 }
@@ -0,0 +1,156 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! The viewpoints of the camera along a recorded animation of the scene
+
+use super::camera::Camera;
+use ensnano_interactor::graphics::CameraAnimation;
+use std::f32::consts::PI;
+use ultraviolet::{Bivec3, Rotor3, Vec3};
+
+/// The distance between the camera and the center of a turntable when the camera is not looking
+/// towards the pivot point
+const DEFAULT_TURNTABLE_RADIUS: f32 = 20.;
+
+/// The viewpoints of the `nb_frames` frames of `animation`.
+///
+/// A turntable starts from `camera` and turns around the point of its line of sight that faces
+/// `pivot`.
+pub fn animation_viewpoints(
+    animation: &CameraAnimation,
+    camera: &Camera,
+    pivot: Option<Vec3>,
+    nb_frames: u32,
+) -> Vec<Camera> {
+    match animation {
+        CameraAnimation::Turntable => turntable(camera, pivot, nb_frames),
+        CameraAnimation::Keyframes(keyframes) => keyframes_path(keyframes, nb_frames),
+    }
+}
+
+fn turntable(camera: &Camera, pivot: Option<Vec3>, nb_frames: u32) -> Vec<Camera> {
+    let radius = pivot
+        .map(|p| (p - camera.position).dot(camera.direction()))
+        .filter(|r| *r > 0.)
+        .unwrap_or(DEFAULT_TURNTABLE_RADIUS);
+    let center = camera.position + radius * camera.direction();
+    (0..nb_frames)
+        .map(|i| {
+            // The last frame stops one step before the first one so that the video can loop
+            let angle = 2. * PI * i as f32 / nb_frames as f32;
+            let mut frame = Camera::new(
+                camera.position,
+                Rotor3::from_rotation_xz(angle) * camera.rotor,
+            );
+            frame.position = center - radius * frame.direction();
+            frame
+        })
+        .collect()
+}
+
+/// Go through the keyframes at constant speed, spending the same time between two consecutive
+/// keyframes
+fn keyframes_path(keyframes: &[(Vec3, Rotor3)], nb_frames: u32) -> Vec<Camera> {
+    if keyframes.is_empty() {
+        return Vec::new();
+    }
+    let nb_segments = keyframes.len() - 1;
+    (0..nb_frames)
+        .map(|i| {
+            let t = if nb_frames > 1 {
+                i as f32 / (nb_frames - 1) as f32 * nb_segments as f32
+            } else {
+                0.
+            };
+            let segment = (t.floor() as usize).min(nb_segments.saturating_sub(1));
+            let (position_0, rotor_0) = keyframes[segment];
+            let (position_1, rotor_1) = keyframes[(segment + 1).min(nb_segments)];
+            let s = t - segment as f32;
+            Camera::new(
+                position_0 + s * (position_1 - position_0),
+                interpolate_rotors(rotor_0, rotor_1, s),
+            )
+        })
+        .collect()
+}
+
+/// Normalized linear interpolation between two rotors, following the shortest path
+fn interpolate_rotors(from: Rotor3, to: Rotor3, t: f32) -> Rotor3 {
+    let dot = from.s * to.s + from.bv.xy * to.bv.xy + from.bv.xz * to.bv.xz + from.bv.yz * to.bv.yz;
+    let sign = if dot < 0. { -1. } else { 1. };
+    let lerp = |a: f32, b: f32| (1. - t) * a + t * sign * b;
+    Rotor3::new(
+        lerp(from.s, to.s),
+        Bivec3::new(
+            lerp(from.bv.xy, to.bv.xy),
+            lerp(from.bv.xz, to.bv.xz),
+            lerp(from.bv.yz, to.bv.yz),
+        ),
+    )
+    .normalized()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn turntable_turns_around_the_pivot() {
+        let camera = Camera::new((0., 0., 10.), Rotor3::identity());
+        let pivot = Vec3::zero();
+        let frames = turntable(&camera, Some(pivot), 4);
+        assert_eq!(frames.len(), 4);
+        assert!((frames[0].position - camera.position).mag() < 1e-5);
+        for frame in frames.iter() {
+            assert!(((frame.position - pivot).mag() - 10.).abs() < 1e-4);
+            assert!((frame.position + 10. * frame.direction() - pivot).mag() < 1e-4);
+        }
+        // A quarter of a revolution between two consecutive frames
+        assert!(frames[0].direction().dot(frames[1].direction()).abs() < 1e-4);
+    }
+
+    #[test]
+    fn keyframes_path_goes_through_the_keyframes() {
+        let keyframes = vec![
+            (Vec3::zero(), Rotor3::identity()),
+            (Vec3::new(2., 0., 0.), Rotor3::from_rotation_xz(1.)),
+            (Vec3::new(2., 4., 0.), Rotor3::from_rotation_xz(-1.)),
+        ];
+        let frames = keyframes_path(&keyframes, 5);
+        assert_eq!(frames.len(), 5);
+        for (frame, (position, rotor)) in [&frames[0], &frames[2], &frames[4]]
+            .iter()
+            .zip(keyframes.iter())
+        {
+            assert!((frame.position - *position).mag() < 1e-5);
+            let camera = Camera::new(*position, *rotor);
+            assert!((frame.direction() - camera.direction()).mag() < 1e-5);
+        }
+        assert!((frames[1].position - Vec3::new(1., 0., 0.)).mag() < 1e-5);
+    }
+
+    #[test]
+    fn single_keyframe_gives_a_still_camera() {
+        let keyframes = vec![(Vec3::new(1., 2., 3.), Rotor3::from_rotation_yz(0.3))];
+        let frames = keyframes_path(&keyframes, 3);
+        assert_eq!(frames.len(), 3);
+        for frame in frames.iter() {
+            assert!((frame.position - Vec3::new(1., 2., 3.)).mag() < 1e-5);
+        }
+    }
+}
@@ -19,6 +19,7 @@ use super::maths_3d;
 use super::{ClickMode, PhySize};
 use iced_winit::winit;
 use std::cell::RefCell;
+use std::collections::VecDeque;
 use std::f32::consts::{FRAC_PI_2, PI};
 use std::rc::Rc;
 use std::time::Duration;
@@ -72,6 +73,102 @@ impl Camera {
     pub fn get_basis(&self) -> maths_3d::Basis3D {
         maths_3d::Basis3D::from_vecs(self.right_vec(), self.up_vec(), -self.direction())
     }
+
+    /// Describe the camera, whose vertical field of view is `fovy` radians, as a json value that
+    /// can be shared with other users.
+    ///
+    /// The rotation is given as a quaternion, and the field of view in degrees.
+    pub fn to_json(&self, fovy: f32) -> serde_json::Value {
+        let [x, y, z, w] = self.rotor.into_quaternion_array();
+        serde_json::json!({
+            "position": [self.position.x, self.position.y, self.position.z],
+            "rotation": { "x": x, "y": y, "z": z, "w": w },
+            "fov": fovy.to_degrees(),
+            "projection": PERSPECTIVE_PROJECTION,
+        })
+    }
+
+    /// Read a camera and its vertical field of view, in radians, from a json value produced by
+    /// `Camera::to_json`.
+    pub fn from_json(v: &serde_json::Value) -> Result<(Self, f32), CameraJsonError> {
+        let float = |v: &serde_json::Value, field: &'static str| {
+            v.as_f64()
+                .map(|x| x as f32)
+                .filter(|x| x.is_finite())
+                .ok_or(CameraJsonError::BadValue(field))
+        };
+        let get = |v: &serde_json::Value, field: &'static str| {
+            v.get(field)
+                .cloned()
+                .ok_or(CameraJsonError::MissingField(field))
+        };
+
+        let position = get(v, "position")?;
+        let position = position
+            .as_array()
+            .filter(|coords| coords.len() == 3)
+            .ok_or(CameraJsonError::BadValue("position"))?;
+        let position = Vec3::new(
+            float(&position[0], "position")?,
+            float(&position[1], "position")?,
+            float(&position[2], "position")?,
+        );
+
+        let rotation = get(v, "rotation")?;
+        let quaternion = [
+            float(&get(&rotation, "x")?, "rotation")?,
+            float(&get(&rotation, "y")?, "rotation")?,
+            float(&get(&rotation, "z")?, "rotation")?,
+            float(&get(&rotation, "w")?, "rotation")?,
+        ];
+        let rotor = Rotor3::from_quaternion_array(quaternion);
+        if rotor.mag() < 1e-5 {
+            return Err(CameraJsonError::BadValue("rotation"));
+        }
+
+        let fov = float(&get(v, "fov")?, "fov")?;
+        if fov <= 0. || fov >= 180. {
+            return Err(CameraJsonError::BadValue("fov"));
+        }
+        let projection = get(v, "projection")?;
+        let projection = projection
+            .as_str()
+            .ok_or(CameraJsonError::BadValue("projection"))?;
+        if projection != PERSPECTIVE_PROJECTION {
+            return Err(CameraJsonError::UnsupportedProjection(
+                projection.to_string(),
+            ));
+        }
+
+        Ok((Self::new(position, rotor.normalized()), fov.to_radians()))
+    }
+}
+
+/// The vertical field of view of the 3D scene, in degrees.
+pub(super) const FOVY_DEGREES: f32 = 70.;
+const PERSPECTIVE_PROJECTION: &str = "perspective";
+
+/// An error that occured when reading a camera from a json value
+#[derive(Debug)]
+pub enum CameraJsonError {
+    /// A required field is absent
+    MissingField(&'static str),
+    /// A field has a value that cannot be interpreted
+    BadValue(&'static str),
+    /// The projection of the camera is not supported
+    UnsupportedProjection(String),
+}
+
+impl std::fmt::Display for CameraJsonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingField(field) => write!(f, "missing field \"{}\"", field),
+            Self::BadValue(field) => write!(f, "invalid value for field \"{}\"", field),
+            Self::UnsupportedProjection(projection) => {
+                write!(f, "unsupported projection \"{}\"", projection)
+            }
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -114,6 +211,18 @@ impl Projection {
         self.fovy
     }
 
+    pub fn set_fovy(&mut self, fovy: f32) {
+        self.fovy = fovy;
+    }
+
+    pub fn get_znear(&self) -> f32 {
+        self.znear
+    }
+
+    pub fn get_zfar(&self) -> f32 {
+        self.zfar
+    }
+
     pub fn get_ratio(&self) -> f32 {
         self.aspect
     }
@@ -123,6 +232,10 @@ impl Projection {
     }
 }
 
+/// The maximum number of viewpoints that can be restored with
+/// [CameraController::undo_camera_move]
+const CAMERA_HISTORY_LENGTH: usize = 50;
+
 pub struct CameraController {
     speed: f32,
     pub sensitivity: f32,
@@ -143,6 +256,13 @@ pub struct CameraController {
     zoom_plane: Option<Plane>,
     x_scroll: f32,
     y_scroll: f32,
+    /// The viewpoints that the camera left, the most recent one being at the back
+    previous_cameras: VecDeque<Camera>,
+    /// The viewpoints that were left by undoing camera moves
+    next_cameras: Vec<Camera>,
+    /// The speed of the camera in the first-person mode, or `None` if the camera is not in the
+    /// first-person mode
+    fly_speed: Option<f32>,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -193,9 +313,19 @@ impl CameraController {
             zoom_plane: None,
             x_scroll: 0.,
             y_scroll: 0.,
+            previous_cameras: VecDeque::new(),
+            next_cameras: Vec::new(),
+            fly_speed: None,
         }
     }
 
+    /// Enter the first-person mode with the given speed, or leave it if `speed` is `None`. In the
+    /// first-person mode, the up and down keys move the camera forward and backward instead of
+    /// up and down.
+    pub fn set_fly_speed(&mut self, speed: Option<f32>) {
+        self.fly_speed = speed;
+    }
+
     pub fn process_keyboard(&mut self, key: VirtualKeyCode, state: ElementState) -> bool {
         let amount = if state == ElementState::Pressed {
             1.0
@@ -219,46 +349,16 @@ impl CameraController {
                 self.amount_right = amount;
                 true
             }
-            VirtualKeyCode::H if amount > 0. => {
-                self.rotate_camera_around(
-                    FRAC_PI_2 / 20.,
-                    0.,
-                    self.pivot_point.unwrap_or_else(FiniteVec3::zero),
-                );
-                self.cam0 = self.camera.borrow().clone();
-                true
-            }
-            VirtualKeyCode::L if amount > 0. => {
-                self.rotate_camera_around(
-                    -FRAC_PI_2 / 20.,
-                    0.,
-                    self.pivot_point.unwrap_or_else(FiniteVec3::zero),
-                );
-                self.cam0 = self.camera.borrow().clone();
-                true
-            }
-            VirtualKeyCode::J if amount > 0. => {
-                self.rotate_camera_around(
-                    0.,
-                    FRAC_PI_2 / 20.,
-                    self.pivot_point.unwrap_or_else(FiniteVec3::zero),
-                );
-                self.cam0 = self.camera.borrow().clone();
-                true
-            }
-            VirtualKeyCode::K if amount > 0. => {
-                self.rotate_camera_around(
-                    0.,
-                    -FRAC_PI_2 / 20.,
-                    self.pivot_point.unwrap_or_else(FiniteVec3::zero),
-                );
-                self.cam0 = self.camera.borrow().clone();
-                true
-            }
             _ => false,
         }
     }
 
+    /// Turn the camera around the pivot point, or around the origin if there is no pivot point
+    pub fn turn_camera(&mut self, xz: f32, yz: f32) {
+        self.rotate_camera_around(xz, yz, self.pivot_point.unwrap_or_else(FiniteVec3::zero));
+        self.cam0 = self.camera.borrow().clone();
+    }
+
     pub fn is_moving(&self) -> bool {
         self.amount_down > 0.
             || self.amount_up > 0.
@@ -367,12 +467,17 @@ impl CameraController {
 
         // Move forward/backward and left/right
         let right = self.camera.borrow().right_vec();
-        let up_vec = self.camera.borrow().up_vec();
+        // In the first-person mode, the up and down keys move the camera forward and backward
+        let (vertical, speed) = if let Some(speed) = self.fly_speed {
+            (self.camera.borrow().direction(), speed)
+        } else {
+            (self.camera.borrow().up_vec(), self.speed)
+        };
 
         {
             let mut camera = self.camera.borrow_mut();
-            camera.position += right * (self.amount_right - self.amount_left) * self.speed * dt;
-            camera.position += up_vec * (self.amount_up - self.amount_down) * self.speed * dt;
+            camera.position += right * (self.amount_right - self.amount_left) * speed * dt;
+            camera.position += vertical * (self.amount_up - self.amount_down) * speed * dt;
         }
 
         let pivot = self.zoom_plane.as_ref().and_then(|plane| {
@@ -575,6 +680,48 @@ impl CameraController {
         let orientation = self.camera.borrow().rotor;
         self.teleport_camera(new_position, orientation);
     }
+
+    /// Remember the current viewpoint so that it can be restored by `self.undo_camera_move()`.
+    /// This must be called before moving the camera.
+    pub fn save_viewpoint(&mut self) {
+        let camera = self.camera.borrow().clone();
+        let already_saved = self
+            .previous_cameras
+            .back()
+            .filter(|c| c.position == camera.position && c.rotor == camera.rotor)
+            .is_some();
+        if !already_saved {
+            if self.previous_cameras.len() == CAMERA_HISTORY_LENGTH {
+                self.previous_cameras.pop_front();
+            }
+            self.previous_cameras.push_back(camera);
+            self.next_cameras.clear();
+        }
+    }
+
+    /// Bring the camera back to the last saved viewpoint. Return false if there is none.
+    pub fn undo_camera_move(&mut self) -> bool {
+        if let Some(camera) = self.previous_cameras.pop_back() {
+            self.next_cameras.push(self.camera.borrow().clone());
+            self.teleport_camera(camera.position, camera.rotor);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Cancel the last call to `self.undo_camera_move()`. Return false if there is nothing to
+    /// redo.
+    pub fn redo_camera_move(&mut self) -> bool {
+        if let Some(camera) = self.next_cameras.pop() {
+            self.previous_cameras
+                .push_back(self.camera.borrow().clone());
+            self.teleport_camera(camera.position, camera.rotor);
+            true
+        } else {
+            false
+        }
+    }
 }
 
 /// A plane in space defined by an origin and a normal
@@ -583,3 +730,63 @@ struct Plane {
     origin: Vec3,
     normal: Vec3,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_round_trip() {
+        let camera = Camera::new((1., -2., 3.5), Rotor3::from_rotation_xz(0.7));
+        let (copy, fovy) = Camera::from_json(&camera.to_json(0.8)).unwrap();
+        assert!((fovy - 0.8).abs() < 1e-5);
+        assert!((copy.position - camera.position).mag() < 1e-5);
+        assert!((copy.direction() - camera.direction()).mag() < 1e-5);
+        assert!((copy.up_vec() - camera.up_vec()).mag() < 1e-5);
+    }
+
+    #[test]
+    fn json_with_unknown_projection() {
+        let mut json = Camera::new((0., 0., 0.), Rotor3::identity()).to_json(0.8);
+        json["projection"] = "orthographic".into();
+        assert!(matches!(
+            Camera::from_json(&json),
+            Err(CameraJsonError::UnsupportedProjection(_))
+        ));
+    }
+
+    #[test]
+    fn camera_moves_can_be_undone_and_redone() {
+        let camera = Rc::new(RefCell::new(Camera::new((0., 0., 0.), Rotor3::identity())));
+        let projection = Rc::new(RefCell::new(Projection::new(100, 100, 0.8, 0.1, 100.)));
+        let mut controller = CameraController::new(4., 1., camera.clone(), projection);
+        controller.save_viewpoint();
+        controller.teleport_camera(Vec3::new(1., 2., 3.), Rotor3::from_rotation_xz(0.5));
+        controller.save_viewpoint();
+        controller.teleport_camera(Vec3::new(4., 5., 6.), Rotor3::identity());
+
+        assert!(controller.undo_camera_move());
+        assert_eq!(camera.borrow().position, Vec3::new(1., 2., 3.));
+        assert!(controller.undo_camera_move());
+        assert_eq!(camera.borrow().position, Vec3::zero());
+        assert!(!controller.undo_camera_move());
+        assert!(controller.redo_camera_move());
+        assert_eq!(camera.borrow().position, Vec3::new(1., 2., 3.));
+
+        // A new move forgets the moves that were undone
+        controller.save_viewpoint();
+        controller.teleport_camera(Vec3::new(7., 8., 9.), Rotor3::identity());
+        assert!(!controller.redo_camera_move());
+    }
+
+    #[test]
+    fn fly_through_moves_forward() {
+        let camera = Rc::new(RefCell::new(Camera::new((0., 0., 0.), Rotor3::identity())));
+        let projection = Rc::new(RefCell::new(Projection::new(100, 100, 0.8, 0.1, 100.)));
+        let mut controller = CameraController::new(4., 1., camera.clone(), projection);
+        controller.set_fly_speed(Some(2.));
+        controller.process_keyboard(VirtualKeyCode::W, ElementState::Pressed);
+        controller.update_camera(Duration::from_secs(1), ClickMode::RotateCam);
+        assert!((camera.borrow().position - 2. * camera.borrow().direction()).mag() < 1e-5);
+    }
+}
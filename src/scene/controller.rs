@@ -23,6 +23,7 @@ use super::{
 use crate::consts::*;
 use crate::{PhySize, PhysicalPosition, WindowEvent};
 use ensnano_design::Nucl;
+use ensnano_interactor::graphics::FlyThroughParameters;
 use iced_winit::winit::event::*;
 use std::cell::RefCell;
 use ultraviolet::{Rotor3, Vec3};
@@ -39,10 +40,34 @@ use automata::{NormalState, State, Transition};
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum ClickMode {
     TranslateCam,
-    #[allow(dead_code)]
+    /// Turn the head of the camera, used in the first-person mode
     RotateCam,
 }
 
+/// The effect that clicking on an element has on the current selection
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ClickModifier {
+    /// The clicked element replaces the selection
+    Replace,
+    /// The clicked element is added to the selection if it is not already selected (Ctrl+click)
+    Append,
+    /// The clicked element is removed from the selection if it is already selected and added to
+    /// it otherwise (Shift+click)
+    Toggle,
+}
+
+impl ClickModifier {
+    fn from_modifiers(modifiers: &ModifiersState) -> Self {
+        if modifiers.shift() {
+            Self::Toggle
+        } else if ctrl(modifiers) {
+            Self::Append
+        } else {
+            Self::Replace
+        }
+    }
+}
+
 use std::rc::Rc;
 type DataPtr = Rc<RefCell<dyn Data>>;
 
@@ -76,14 +101,13 @@ pub enum Consequence {
     InitTranslation(f64, f64, WidgetTarget),
     Swing(f64, f64),
     Nothing,
-    ToggleWidget,
-    BuildEnded,
-    Building(isize),
-    Undo,
-    Redo,
+    /// The building of strands ended, the moving ends must be moved to the given position
+    BuildEnded(Option<isize>),
+    /// The moving ends of the strand builders are being dragged to the given position
+    BuildingPreview(isize),
     Candidate(Option<super::SceneElement>),
     PivotElement(Option<super::SceneElement>),
-    ElementSelected(Option<super::SceneElement>, bool),
+    ElementSelected(Option<super::SceneElement>, ClickModifier),
     InitFreeXover(Nucl, usize, Vec3),
     MoveFreeXover(Option<super::SceneElement>, Vec3),
     EndFreeXover,
@@ -106,6 +130,11 @@ pub enum Consequence {
         y: isize,
     },
     HelixSelected(usize),
+    /// The rectangle with the given opposite corners is being dragged to select nucleotides
+    RectangleDragged((f32, f32), (f32, f32)),
+    /// The nucleotides inside the rectangle with the given opposite corners must be selected. If
+    /// the boolean is true, they are added to the current selection.
+    RectangleSelected((f32, f32), (f32, f32), bool),
 }
 
 enum TransistionConsequence {
@@ -217,27 +246,11 @@ impl<S: AppState> Controller<S> {
             ..
         } = event
         {
-            let csq = match *key {
-                VirtualKeyCode::Z
-                    if ctrl(&self.current_modifiers) && *state == ElementState::Pressed =>
-                {
-                    Consequence::Undo
-                }
-                VirtualKeyCode::R
-                    if ctrl(&self.current_modifiers) && *state == ElementState::Pressed =>
-                {
-                    Consequence::Redo
-                }
-                VirtualKeyCode::Space if *state == ElementState::Pressed => {
-                    Consequence::ToggleWidget
-                }
-                _ => {
-                    if self.camera_controller.process_keyboard(*key, *state) {
-                        Consequence::CameraMoved
-                    } else {
-                        Consequence::Nothing
-                    }
-                }
+            // The other shortcuts are handled by the key bindings of the multiplexer
+            let csq = if self.camera_controller.process_keyboard(*key, *state) {
+                Consequence::CameraMoved
+            } else {
+                Consequence::Nothing
             };
             Transition::consequence(csq)
         } else {
@@ -257,6 +270,19 @@ impl<S: AppState> Controller<S> {
         transition.consequences
     }
 
+    /// Update the candidate under the mouse cursor, after new pixels were read by the element
+    /// selector.
+    pub fn refresh_hover(
+        &mut self,
+        pixel_reader: &mut ElementSelector,
+        app_state: &S,
+    ) -> Consequence {
+        self.state
+            .borrow_mut()
+            .refresh_hover(&self, pixel_reader, app_state)
+            .unwrap_or(Consequence::Nothing)
+    }
+
     fn transition_consequence(&mut self, csq: TransistionConsequence) {
         match csq {
             TransistionConsequence::Nothing => (),
@@ -296,18 +322,57 @@ impl<S: AppState> Controller<S> {
             .update(super::view::ViewUpdate::Size(window_size));
     }
 
+    /// Convert a position in the drawing area into fractions of the size of the drawing area
+    fn normalized_position(&self, position: PhysicalPosition<f64>) -> (f32, f32) {
+        (
+            (position.x / self.area_size.width as f64) as f32,
+            (position.y / self.area_size.height as f64) as f32,
+        )
+    }
+
     pub fn get_window_size(&self) -> PhySize {
         self.window_size
     }
 
     fn init_movement(&mut self) {
+        self.camera_controller.save_viewpoint();
         self.camera_controller.init_movement();
     }
 
+    /// Remember the current viewpoint so that it can be restored by `self.undo_camera_move()`
+    pub fn save_camera_viewpoint(&mut self) {
+        self.camera_controller.save_viewpoint()
+    }
+
+    /// Bring the camera back to its previous viewpoint
+    pub fn undo_camera_move(&mut self) {
+        if self.camera_controller.undo_camera_move() {
+            self.end_movement();
+        }
+    }
+
+    pub fn redo_camera_move(&mut self) {
+        if self.camera_controller.redo_camera_move() {
+            self.end_movement();
+        }
+    }
+
     fn end_movement(&mut self) {
         self.camera_controller.end_movement();
     }
 
+    /// Enter or leave the first-person mode, in which dragging the mouse turns the head of the
+    /// camera and the keyboard moves it forward and sideways
+    pub fn set_fly_through(&mut self, parameters: FlyThroughParameters) {
+        self.click_mode = if parameters.enabled {
+            ClickMode::RotateCam
+        } else {
+            ClickMode::TranslateCam
+        };
+        self.camera_controller
+            .set_fly_speed(Some(parameters.speed).filter(|_| parameters.enabled));
+    }
+
     pub fn change_sensitivity(&mut self, sensitivity: f32) {
         self.camera_controller.sensitivity = 10f32.powf(sensitivity / 10.) * BASE_SCROLL_SENSITIVITY
     }
@@ -322,6 +387,11 @@ impl<S: AppState> Controller<S> {
         self.camera_controller.process_mouse(dx, dy)
     }
 
+    /// Turn the camera around its pivot point, or around the origin if there is no pivot point
+    pub fn turn_camera(&mut self, xz: f32, yz: f32) {
+        self.camera_controller.turn_camera(xz, yz)
+    }
+
     pub fn rotate_camera(&mut self, xz: f32, yz: f32, xy: f32, pivot: Option<Vec3>) {
         self.camera_controller.rotate_camera(xz, yz, pivot);
         self.camera_controller.tilt_camera(xy);
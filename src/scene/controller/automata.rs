@@ -80,6 +80,17 @@ pub(super) trait ControllerState<S: AppState> {
     fn handles_color_system(&self) -> Option<HandleColors> {
         None
     }
+
+    /// Update the candidate under the mouse cursor after the pixels of the element selector
+    /// changed, if the candidate is shown in this state.
+    fn refresh_hover(
+        &mut self,
+        _controller: &Controller<S>,
+        _pixel_reader: &mut ElementSelector,
+        _app_state: &S,
+    ) -> Option<Consequence> {
+        None
+    }
 }
 
 pub struct NormalState {
@@ -96,35 +107,9 @@ impl<S: AppState> ControllerState<S> for NormalState {
         app_state: &S,
     ) -> Transition<S> {
         match event {
-            WindowEvent::CursorMoved { .. } if app_state.is_pasting() => {
-                self.mouse_position = position;
-                let element = pixel_reader.set_selected_id(position);
-                Transition::consequence(Consequence::PasteCandidate(element))
-            }
             WindowEvent::CursorMoved { .. } => {
                 self.mouse_position = position;
-                let element = pixel_reader.set_selected_id(position);
-                if let Some(SceneElement::Grid(d_id, _)) = element {
-                    let mouse_x = position.x / controller.area_size.width as f64;
-                    let mouse_y = position.y / controller.area_size.height as f64;
-                    let candidate = if let Some(intersection) = controller
-                        .view
-                        .borrow()
-                        .grid_intersection(mouse_x as f32, mouse_y as f32)
-                    {
-                        Some(SceneElement::GridCircle(
-                            d_id,
-                            intersection.grid_id,
-                            intersection.x,
-                            intersection.y,
-                        ))
-                    } else {
-                        element
-                    };
-                    Transition::consequence(Consequence::Candidate(candidate))
-                } else {
-                    Transition::consequence(Consequence::Candidate(element))
-                }
+                Transition::consequence(Self::hover(position, controller, pixel_reader, app_state))
             }
             WindowEvent::MouseInput {
                 state: ElementState::Pressed,
@@ -185,8 +170,9 @@ impl<S: AppState> ControllerState<S> for NormalState {
                                     clicked_position: position,
                                     mouse_position: position,
                                     click_date: Instant::now(),
-                                    adding: controller.current_modifiers.shift()
-                                        | ctrl(&controller.current_modifiers),
+                                    modifier: ClickModifier::from_modifiers(
+                                        &controller.current_modifiers,
+                                    ),
                                 })),
                                 consequences: Consequence::Nothing,
                             }
@@ -240,8 +226,9 @@ impl<S: AppState> ControllerState<S> for NormalState {
                                         clicked_position: position,
                                         mouse_position: position,
                                         click_date: Instant::now(),
-                                        adding: controller.current_modifiers.shift()
-                                            | ctrl(&controller.current_modifiers),
+                                        modifier: ClickModifier::from_modifiers(
+                                            &controller.current_modifiers,
+                                        ),
                                     })),
                                     consequences: Consequence::Nothing,
                                 }
@@ -284,8 +271,9 @@ impl<S: AppState> ControllerState<S> for NormalState {
                                         clicked_position: position,
                                         mouse_position: position,
                                         click_date: Instant::now(),
-                                        adding: controller.current_modifiers.shift()
-                                            | ctrl(&controller.current_modifiers),
+                                        modifier: ClickModifier::from_modifiers(
+                                            &controller.current_modifiers,
+                                        ),
                                     })),
                                     consequences: Consequence::Nothing,
                                 }
@@ -341,8 +329,7 @@ impl<S: AppState> ControllerState<S> for NormalState {
                             clicked_position: position,
                             mouse_position: position,
                             click_date: Instant::now(),
-                            adding: controller.current_modifiers.shift()
-                                | ctrl(&controller.current_modifiers),
+                            modifier: ClickModifier::from_modifiers(&controller.current_modifiers),
                         })),
                         consequences: Consequence::Nothing,
                     },
@@ -389,6 +376,59 @@ impl<S: AppState> ControllerState<S> for NormalState {
     fn display(&self) -> Cow<'static, str> {
         "Normal".into()
     }
+
+    fn refresh_hover(
+        &mut self,
+        controller: &Controller<S>,
+        pixel_reader: &mut ElementSelector,
+        app_state: &S,
+    ) -> Option<Consequence> {
+        // The mouse position is negative when the window lost the focus
+        if self.mouse_position.x < 0. || self.mouse_position.y < 0. {
+            return None;
+        }
+        Some(Self::hover(
+            self.mouse_position,
+            controller,
+            pixel_reader,
+            app_state,
+        ))
+    }
+}
+
+impl NormalState {
+    /// The candidate under the mouse cursor
+    fn hover<S: AppState>(
+        position: PhysicalPosition<f64>,
+        controller: &Controller<S>,
+        pixel_reader: &mut ElementSelector,
+        app_state: &S,
+    ) -> Consequence {
+        let element = pixel_reader.set_selected_id(position);
+        if app_state.is_pasting() {
+            Consequence::PasteCandidate(element)
+        } else if let Some(SceneElement::Grid(d_id, _)) = element {
+            let mouse_x = position.x / controller.area_size.width as f64;
+            let mouse_y = position.y / controller.area_size.height as f64;
+            let candidate = if let Some(intersection) = controller
+                .view
+                .borrow()
+                .grid_intersection(mouse_x as f32, mouse_y as f32)
+            {
+                Some(SceneElement::GridCircle(
+                    d_id,
+                    intersection.grid_id,
+                    intersection.x,
+                    intersection.y,
+                ))
+            } else {
+                element
+            };
+            Consequence::Candidate(candidate)
+        } else {
+            Consequence::Candidate(element)
+        }
+    }
 }
 
 struct TranslatingCamera {
@@ -569,7 +609,7 @@ struct Selecting {
     clicked_position: PhysicalPosition<f64>,
     element: Option<SceneElement>,
     click_date: Instant,
-    adding: bool,
+    modifier: ClickModifier,
 }
 
 impl<S: AppState> ControllerState<S> for Selecting {
@@ -604,6 +644,9 @@ impl<S: AppState> ControllerState<S> for Selecting {
                     })),
                     consequences: Consequence::InitFreeXover(nucl, d_id, projected_pos),
                 }
+            } else if self.element.is_none() {
+                // The user may still drag a selection rectangle
+                Transition::nothing()
             } else {
                 Transition {
                     new_state: Some(Box::new(NormalState {
@@ -630,9 +673,21 @@ impl<S: AppState> ControllerState<S> for Selecting {
                 if position_difference(position, self.clicked_position) > 5. {
                     if let Some(nucl) = controller.data.borrow().can_start_builder(self.element) {
                         Transition {
-                            new_state: Some(Box::new(BuildingStrand)),
+                            new_state: Some(Box::new(BuildingStrand::default())),
                             consequences: Consequence::InitBuild(nucl),
                         }
+                    } else if self.element.is_none() {
+                        let corner = controller.normalized_position(self.clicked_position);
+                        Transition {
+                            new_state: Some(Box::new(SelectingRectangle {
+                                clicked_position: self.clicked_position,
+                                adding: self.modifier != ClickModifier::Replace,
+                            })),
+                            consequences: Consequence::RectangleDragged(
+                                corner,
+                                controller.normalized_position(position),
+                            ),
+                        }
                     } else {
                         Transition {
                             new_state: Some(Box::new(NormalState {
@@ -659,7 +714,7 @@ impl<S: AppState> ControllerState<S> for Selecting {
                         mouse_position: position,
                         clicked_position: self.clicked_position,
                     })),
-                    consequences: Consequence::ElementSelected(self.element, self.adding),
+                    consequences: Consequence::ElementSelected(self.element, self.modifier),
                 }
             }
             _ => Transition::nothing(),
@@ -667,6 +722,49 @@ impl<S: AppState> ControllerState<S> for Selecting {
     }
 }
 
+/// The user is dragging a rectangle to select the nucleotides that are inside it
+struct SelectingRectangle {
+    clicked_position: PhysicalPosition<f64>,
+    adding: bool,
+}
+
+impl<S: AppState> ControllerState<S> for SelectingRectangle {
+    fn display(&self) -> Cow<'static, str> {
+        "Selecting Rectangle".into()
+    }
+
+    fn input(
+        &mut self,
+        event: &WindowEvent,
+        position: PhysicalPosition<f64>,
+        controller: &Controller<S>,
+        _pixel_reader: &mut ElementSelector,
+        _app_state: &S,
+    ) -> Transition<S> {
+        let corner = controller.normalized_position(self.clicked_position);
+        match event {
+            WindowEvent::CursorMoved { .. } => Transition::consequence(
+                Consequence::RectangleDragged(corner, controller.normalized_position(position)),
+            ),
+            WindowEvent::MouseInput {
+                state: ElementState::Released,
+                button: MouseButton::Left,
+                ..
+            } => Transition {
+                new_state: Some(Box::new(NormalState {
+                    mouse_position: position,
+                })),
+                consequences: Consequence::RectangleSelected(
+                    corner,
+                    controller.normalized_position(position),
+                    self.adding,
+                ),
+            },
+            _ => Transition::nothing(),
+        }
+    }
+}
+
 struct WaitDoubleClick {
     click_date: Instant,
     element: Option<SceneElement>,
@@ -913,7 +1011,14 @@ impl<S: AppState> ControllerState<S> for RotatingWidget {
     }
 }
 
-struct BuildingStrand;
+/// The user is dragging the moving ends of strand builders. The design is only modified when the
+/// mouse button is released, in the meantime the result is displayed as a preview.
+#[derive(Default)]
+struct BuildingStrand {
+    /// The position to which the moving end will be moved when the building ends
+    end_position: Option<isize>,
+}
+
 impl<S: AppState> ControllerState<S> for BuildingStrand {
     fn display(&self) -> Cow<'static, str> {
         "Building Strand".into()
@@ -936,7 +1041,7 @@ impl<S: AppState> ControllerState<S> for BuildingStrand {
                 new_state: Some(Box::new(NormalState {
                     mouse_position: position,
                 })),
-                consequences: Consequence::BuildEnded,
+                consequences: Consequence::BuildEnded(self.end_position),
             },
             WindowEvent::CursorMoved { .. } => {
                 if let Some(builder) = app_state.get_strand_builders().get(0) {
@@ -948,7 +1053,8 @@ impl<S: AppState> ControllerState<S> for BuildingStrand {
                         mouse_y,
                     );
                     let consequence = if let Some(position) = position {
-                        Consequence::Building(position)
+                        self.end_position = Some(position);
+                        Consequence::BuildingPreview(position)
                     } else {
                         Consequence::Nothing
                     };
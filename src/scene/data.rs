@@ -25,16 +25,20 @@ use super::{
 };
 use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
+use std::path::Path;
 use std::rc::Rc;
 
-use ultraviolet::{Rotor3, Vec3};
+use ultraviolet::{Mat4, Rotor3, Vec3};
 
+use super::maths_3d::{angle_between, world_to_ndc, CullingCone};
+use super::mesh_export::TriangleMesh;
 use super::view::Mesh;
 use crate::consts::*;
 use ensnano_design::Nucl;
+use ensnano_interactor::graphics::{AnalysisColoring, ColorTheme};
 use ensnano_interactor::{
-    ActionMode, CenterOfSelection, ObjectType, PhantomElement, Referential, Selection,
-    SelectionMode,
+    ActionMode, CenterOfSelection, MeasureKind, Measurement, ObjectType, PhantomElement,
+    Referential, Selection, SelectionMode, StrandBuilder,
 };
 
 use super::AppState;
@@ -50,6 +54,8 @@ pub struct Data<R: DesignReader> {
     view: ViewPtr,
     /// A `Design3D` is associated to each design.
     designs: Vec<Design3D<R>>,
+    /// The designs of the workspace, with the model matrices that place them in the scene
+    workspace_designs: Vec<(Design3D<R>, Mat4)>,
     /// The set of candidates elements
     candidate_element: Option<SceneElement>,
     /// The kind of selection being performed if app_state.get_selection_mode() is SelectionMode::Nucl.
@@ -70,6 +76,34 @@ pub struct Data<R: DesignReader> {
     last_candidate_disc: Option<SceneElement>,
     rotating_pivot: bool,
     handle_colors: HandleColors,
+    /// When true, clicking on empty space does not clear the selection
+    selection_locked: bool,
+    /// When true, the nucleotides are colored according to the local twist of their helix
+    show_local_twist: bool,
+    local_twist_update: bool,
+    /// The analysis of the design whose result is shown by the color of the nucleotides
+    analysis_coloring: AnalysisColoring,
+    analysis_coloring_update: bool,
+    /// The strand whose sequence is being typed in the 3D view
+    edited_strand: Option<usize>,
+    edited_strand_update: bool,
+    /// When true, the identifier of each helix is displayed next to it
+    show_helix_numbers: bool,
+    helix_numbers_update: bool,
+    /// The nucleotides of the measurement being made, and the identifiers of their designs
+    measured_nucls: Vec<(Nucl, usize)>,
+    measured_nucls_update: bool,
+    /// The cone that contained the instances sent to the view at their last update, or `None` if
+    /// all the instances were sent
+    culling_cone: Option<CullingCone>,
+    /// When false, the instances that cannot be visible are sent to the view anyway
+    culling_enabled: bool,
+    /// The colors chosen by the user
+    color_theme: ColorTheme,
+    color_theme_update: bool,
+    /// The generation of the design whose instances were last sent to the view. The instances
+    /// are only computed again for a design of an other generation.
+    instances_generation: Option<u64>,
 }
 
 impl<R: DesignReader> Data<R> {
@@ -77,6 +111,7 @@ impl<R: DesignReader> Data<R> {
         Self {
             view,
             designs: vec![Design3D::new(reader, 0)],
+            workspace_designs: Vec::new(),
             candidate_element: None,
             sub_selection_mode: SelectionMode::Nucleotide,
             selected_position: None,
@@ -89,6 +124,22 @@ impl<R: DesignReader> Data<R> {
             last_candidate_disc: None,
             rotating_pivot: false,
             handle_colors: HandleColors::Rgb,
+            selection_locked: false,
+            show_local_twist: false,
+            local_twist_update: false,
+            analysis_coloring: Default::default(),
+            analysis_coloring_update: false,
+            edited_strand: None,
+            edited_strand_update: false,
+            show_helix_numbers: false,
+            helix_numbers_update: false,
+            measured_nucls: Vec::new(),
+            measured_nucls_update: false,
+            culling_cone: None,
+            culling_enabled: true,
+            color_theme: Default::default(),
+            color_theme_update: false,
+            instances_generation: None,
         }
     }
 
@@ -97,6 +148,15 @@ impl<R: DesignReader> Data<R> {
         self.designs[0] = Design3D::new(design, 0);
     }
 
+    /// Set the designs of the workspace that are drawn next to the edited design
+    pub fn update_workspace_designs(&mut self, designs: Vec<(R, Mat4)>) {
+        self.workspace_designs = designs
+            .into_iter()
+            .enumerate()
+            .map(|(n, (design, matrix))| (Design3D::new(design, n as u32 + 1), matrix))
+            .collect();
+    }
+
     /// Remove all designs to be drawn
     pub fn clear_designs(&mut self) {
         self.candidate_element = None;
@@ -111,12 +171,34 @@ impl<R: DesignReader> Data<R> {
 impl<R: DesignReader> Data<R> {
     /// Forwards all needed update to the view
     pub fn update_view<S: AppState>(&mut self, app_state: &S, older_app_state: &S) {
+        self.lock_selection(app_state.is_selection_locked());
         if self.discs_need_update(app_state, older_app_state) {
             self.update_discs(app_state);
         }
-        if app_state.design_was_modified(older_app_state)
+        if app_state.get_action_mode() != older_app_state.get_action_mode() {
+            self.clear_measured_nucls();
+        }
+        let measurements_updated =
+            app_state.measurements_were_updated(older_app_state) || self.measured_nucls_update;
+        let design_generation = app_state.get_design_generation();
+        if self.instances_generation != Some(design_generation)
             || app_state.suggestion_parameters_were_updated(older_app_state)
+            || app_state.nucleotide_colors_were_updated(older_app_state)
+            || self.local_twist_update
+            || self.analysis_coloring_update
+            || self.edited_strand_update
+            || self.helix_numbers_update
+            || measurements_updated
+            || app_state.trajectory_was_updated(older_app_state)
+            || self.culling_needs_update(app_state, older_app_state)
+            || self.color_theme_update
+            || app_state.workspace_was_updated(older_app_state)
         {
+            self.local_twist_update = false;
+            self.analysis_coloring_update = false;
+            self.edited_strand_update = false;
+            self.helix_numbers_update = false;
+            self.instances_generation = Some(design_generation);
             self.update_instances(app_state);
         }
 
@@ -125,6 +207,7 @@ impl<R: DesignReader> Data<R> {
             self.update_selection(&[], app_state)
         } else if app_state.selection_was_updated(older_app_state)
             || app_state.design_was_modified(older_app_state)
+            || self.color_theme_update
         {
             self.update_selection(app_state.get_selection(), app_state);
         }
@@ -135,9 +218,10 @@ impl<R: DesignReader> Data<R> {
             self.update_handle(app_state);
             self.handle_need_opdate = false;
         }
-        if app_state.candidates_set_was_updated(older_app_state) {
+        if app_state.candidates_set_was_updated(older_app_state) || self.color_theme_update {
             self.update_candidate(app_state.get_candidates());
         }
+        self.color_theme_update = false;
         if self.pivot_update {
             self.update_pivot();
             self.pivot_update = false;
@@ -150,6 +234,17 @@ impl<R: DesignReader> Data<R> {
         if app_state.design_model_matrix_was_updated(older_app_state) {
             self.update_matrices();
         }
+
+        if app_state.highlighted_path_was_updated(older_app_state)
+            || app_state.design_was_modified(older_app_state)
+        {
+            self.update_highlighted_path(app_state.get_highlighted_path());
+        }
+
+        if measurements_updated || app_state.design_was_modified(older_app_state) {
+            self.update_measurements(app_state.get_measurements());
+            self.measured_nucls_update = false;
+        }
     }
 
     fn discs_need_update<S: AppState>(&mut self, app_state: &S, older_app_state: &S) -> bool {
@@ -247,15 +342,21 @@ impl<R: DesignReader> Data<R> {
         object_type: ObjectType,
         selection: &Selection,
     ) -> Vec<SceneElement> {
-        let d_id = selection.get_design();
-        if d_id.is_none() {
+        let d_id = if let Some(d_id) = selection.get_design() {
+            d_id as usize
+        } else {
             return vec![];
-        }
-        let d_id = d_id.unwrap() as usize;
+        };
+        let design = if let Some(design) = self.designs.get(d_id) {
+            design
+        } else {
+            // The selection may refer to a design that has been removed
+            return vec![];
+        };
         let mut ret = Vec::new();
         if let Selection::Nucleotide(d_id, nucl) = selection {
             if !object_type.is_bound() {
-                if let Some(n_id) = self.designs[*d_id as usize].get_identifier_nucl(nucl) {
+                if let Some(n_id) = design.get_identifier_nucl(nucl) {
                     ret.push(SceneElement::DesignElement(*d_id, n_id))
                 } else {
                     ret.push(SceneElement::PhantomElement(PhantomElement {
@@ -269,7 +370,7 @@ impl<R: DesignReader> Data<R> {
             }
         } else if let Selection::Bound(d_id, n1, n2) = selection {
             if object_type.is_bound() {
-                if let Some(b_id) = self.designs[*d_id as usize].get_identifier_bound(*n1, *n2) {
+                if let Some(b_id) = design.get_identifier_bound(*n1, *n2) {
                     ret.push(SceneElement::DesignElement(*d_id, b_id))
                 } else {
                     ret.push(SceneElement::PhantomElement(PhantomElement {
@@ -283,16 +384,14 @@ impl<R: DesignReader> Data<R> {
             }
         } else if let Selection::Xover(d_id, xover_id) = selection {
             if object_type.is_bound() {
-                if let Some(b_id) =
-                    self.designs[*d_id as usize].get_element_identifier_from_xover_id(*xover_id)
-                {
+                if let Some(b_id) = design.get_element_identifier_from_xover_id(*xover_id) {
                     ret.push(SceneElement::DesignElement(*d_id, b_id))
                 }
             }
         } else {
             let group = self.get_group_member(selection);
             for elt in group.iter() {
-                if self.designs[d_id]
+                if design
                     .get_element_type(*elt)
                     .map(|elt| elt.same_type(object_type))
                     .unwrap_or(false)
@@ -355,7 +454,7 @@ impl<R: DesignReader> Data<R> {
                     SceneElement::DesignElement(d_id, id) => {
                         if let Some(instance) = self.designs[*d_id as usize].make_instance(
                             *id,
-                            SELECTED_COLOR,
+                            self.selected_color(),
                             SELECT_SCALE_FACTOR,
                         ) {
                             ret.push(instance)
@@ -368,7 +467,7 @@ impl<R: DesignReader> Data<R> {
                             .and_then(|d| {
                                 d.make_instance_phantom(
                                     phantom_element,
-                                    SELECTED_COLOR,
+                                    self.selected_color(),
                                     SELECT_SCALE_FACTOR,
                                 )
                             })
@@ -395,7 +494,7 @@ impl<R: DesignReader> Data<R> {
                     SceneElement::DesignElement(d_id, id) => {
                         if let Some(instance) = self.designs[*d_id as usize].make_instance(
                             *id,
-                            SELECTED_COLOR,
+                            self.selected_color(),
                             SELECT_SCALE_FACTOR,
                         ) {
                             ret.push(instance)
@@ -408,7 +507,7 @@ impl<R: DesignReader> Data<R> {
                             .and_then(|d| {
                                 d.make_instance_phantom(
                                     phantom_element,
-                                    SELECTED_COLOR,
+                                    self.selected_color(),
                                     SELECT_SCALE_FACTOR,
                                 )
                             })
@@ -435,7 +534,7 @@ impl<R: DesignReader> Data<R> {
                     SceneElement::DesignElement(d_id, id) => {
                         if let Some(instance) = self.designs[*d_id as usize].make_instance(
                             *id,
-                            CANDIDATE_COLOR,
+                            self.candidate_color(),
                             SELECT_SCALE_FACTOR,
                         ) {
                             ret.push(instance)
@@ -448,7 +547,7 @@ impl<R: DesignReader> Data<R> {
                             .and_then(|d| {
                                 d.make_instance_phantom(
                                     phantom_element,
-                                    CANDIDATE_COLOR,
+                                    self.candidate_color(),
                                     SELECT_SCALE_FACTOR,
                                 )
                             })
@@ -475,7 +574,7 @@ impl<R: DesignReader> Data<R> {
                     SceneElement::DesignElement(d_id, id) => {
                         if let Some(instance) = self.designs[*d_id as usize].make_instance(
                             *id,
-                            CANDIDATE_COLOR,
+                            self.candidate_color(),
                             SELECT_SCALE_FACTOR,
                         ) {
                             ret.push(instance)
@@ -488,7 +587,7 @@ impl<R: DesignReader> Data<R> {
                             .and_then(|d| {
                                 d.make_instance_phantom(
                                     phantom_element,
-                                    CANDIDATE_COLOR,
+                                    self.candidate_color(),
                                     SELECT_SCALE_FACTOR,
                                 )
                             })
@@ -534,6 +633,12 @@ impl<R: DesignReader> Data<R> {
                 .get_helix(element_id)
                 .map(|x| x as u32),
             SelectionMode::Grid => Some(element_id),
+            SelectionMode::StrandEnd(end) => {
+                self.designs[design_id as usize].get_strand_end_identifier(element_id, end)
+            }
+            SelectionMode::Domain => {
+                self.designs[design_id as usize].get_domain_identifier(element_id)
+            }
         }
     }
 
@@ -563,6 +668,11 @@ impl<R: DesignReader> Data<R> {
             }),
             SelectionMode::Helix => Some(phantom_element.helix_id),
             SelectionMode::Grid => None,
+            SelectionMode::StrandEnd(end) => element_id
+                .and_then(|e| self.designs[design_id as usize].get_strand_end_identifier(e, end)),
+            SelectionMode::Domain => {
+                element_id.and_then(|e| self.designs[design_id as usize].get_domain_identifier(e))
+            }
         }
     }
 
@@ -594,6 +704,9 @@ impl<R: DesignReader> Data<R> {
             Selection::Strand(d_id, s_id) => {
                 self.designs[*d_id as usize].get_strand_elements(*s_id)
             }
+            Selection::Domain(d_id, domain_id) => {
+                self.designs[*d_id as usize].get_domain_elements(*domain_id)
+            }
             Selection::Grid(_, _) => HashSet::new(), // A grid is not made of atomic elements
             Selection::Phantom(_) => HashSet::new(),
             Selection::Nothing => HashSet::new(),
@@ -617,7 +730,9 @@ impl<R: DesignReader> Data<R> {
             SelectionMode::Nucleotide
             | SelectionMode::Strand
             | SelectionMode::Design
-            | SelectionMode::Grid => design.get_element_position(element, referential),
+            | SelectionMode::Grid
+            | SelectionMode::StrandEnd(_)
+            | SelectionMode::Domain => design.get_element_position(element, referential),
         }
     }
 
@@ -648,6 +763,9 @@ impl<R: DesignReader> Data<R> {
         if let Some(SceneElement::WidgetElement(_)) = element {
             return (None, None);
         }
+        if element.is_none() && self.selection_locked {
+            return (None, None);
+        }
         log::debug!("selected {:?}", element);
         let future_selection = element.clone();
         let new_center_of_selection =
@@ -688,10 +806,16 @@ impl<R: DesignReader> Data<R> {
         Some(selection).filter(|s| *s != Selection::Nothing)
     }
 
+    /// Return the selection obtained by adding `element` to `selection`. If `toggle` is true
+    /// and `element` is already in `selection`, it is removed from the selection instead.
+    ///
+    /// The elements of the selection may be of different kinds (nucleotides, strands,
+    /// helices...)
     pub fn add_to_selection<S: AppState>(
         &mut self,
         element: Option<SceneElement>,
         selection: &[Selection],
+        toggle: bool,
         app_state: &S,
     ) -> Option<(Vec<Selection>, Option<CenterOfSelection>)> {
         if let Some(SceneElement::WidgetElement(_)) = element {
@@ -712,7 +836,9 @@ impl<R: DesignReader> Data<R> {
         } else {
             let mut new_selection = selection.to_vec();
             if let Some(pos) = new_selection.iter().position(|x| *x == selected) {
-                new_selection.remove(pos);
+                if toggle {
+                    new_selection.remove(pos);
+                }
             } else {
                 new_selection.push(selected);
             }
@@ -772,9 +898,58 @@ impl<R: DesignReader> Data<R> {
         };
     }
 
-    /// Clear self.selected
+    /// Clear self.selected. This has no effect while the selection is locked.
     pub fn reset_selection(&mut self) {
-        self.selected_position = None;
+        if !self.selection_locked {
+            self.selected_position = None;
+        }
+    }
+
+    /// Lock or unlock the selection. While the selection is locked, clicking on empty space does
+    /// not clear the selection.
+    pub fn lock_selection(&mut self, locked: bool) {
+        self.selection_locked = locked;
+    }
+
+    /// Color the nucleotides according to the local twist of their helix instead of the color
+    /// of their strand
+    pub fn show_local_twist(&mut self, show: bool) {
+        self.local_twist_update |= self.show_local_twist != show;
+        self.show_local_twist = show;
+    }
+
+    /// Color the nucleotides according to the result of an analysis of the design instead of
+    /// the color of their strand
+    pub fn set_analysis_coloring(&mut self, coloring: AnalysisColoring) {
+        self.analysis_coloring_update |= self.analysis_coloring != coloring;
+        self.analysis_coloring = coloring;
+    }
+
+    pub fn set_color_theme(&mut self, theme: ColorTheme) {
+        self.color_theme_update |= self.color_theme != theme;
+        self.color_theme = theme;
+    }
+
+    /// The color of the selected elements, with the transparency of highlighted elements
+    fn selected_color(&self) -> u32 {
+        HIGHLIGHT_ALPHA | self.color_theme.selection
+    }
+
+    /// The color of the candidate elements, with the transparency of highlighted elements
+    fn candidate_color(&self) -> u32 {
+        HIGHLIGHT_ALPHA | self.color_theme.candidate
+    }
+
+    /// Show/hide the identifier of each helix next to it
+    pub fn show_helix_numbers(&mut self, show: bool) {
+        self.helix_numbers_update |= self.show_helix_numbers != show;
+        self.show_helix_numbers = show;
+    }
+
+    /// Set the strand whose sequence is being typed. Its sequence is displayed next to its 5' end
+    pub fn set_edited_strand(&mut self, s_id: Option<usize>) {
+        self.edited_strand_update |= self.edited_strand != s_id;
+        self.edited_strand = s_id;
     }
 
     /// Notify the view that the selected elements have been modified
@@ -944,6 +1119,11 @@ impl<R: DesignReader> Data<R> {
                         }
                         SelectionMode::Helix => Selection::Helix(*design_id, group_id),
                         SelectionMode::Grid => Selection::Grid(*design_id, group_id as usize),
+                        SelectionMode::StrandEnd(_) => self.designs[*design_id as usize]
+                            .get_nucl(group_id)
+                            .map(|nucl| Selection::Nucleotide(*design_id, nucl))
+                            .unwrap_or(Selection::Nothing),
+                        SelectionMode::Domain => Selection::Domain(*design_id, group_id),
                     }
                 } else {
                     Selection::Nothing
@@ -1071,6 +1251,116 @@ impl<R: DesignReader> Data<R> {
         self.view.borrow_mut().set_candidate_grid(grids);
     }
 
+    fn update_highlighted_path(&mut self, path: &[Nucl]) {
+        let mut spheres = Vec::with_capacity(path.len());
+        let mut tubes = Vec::with_capacity(path.len());
+        if let Some(design) = self.designs.get(0) {
+            for nucl in path.iter() {
+                if let Some(instance) = design.get_identifier_nucl(nucl).and_then(|id| {
+                    design.make_instance(id, HIGHLIGHTED_PATH_COLOR, SELECT_SCALE_FACTOR)
+                }) {
+                    spheres.push(instance)
+                }
+            }
+            for bond in path.windows(2) {
+                // Watson-Crick pairs of the path have no identifier and are skipped
+                let id = design
+                    .get_identifier_bound(bond[0], bond[1])
+                    .or_else(|| design.get_identifier_bound(bond[1], bond[0]));
+                if let Some(instance) = id.and_then(|id| {
+                    design.make_instance(id, HIGHLIGHTED_PATH_COLOR, SELECT_SCALE_FACTOR)
+                }) {
+                    tubes.push(instance)
+                }
+            }
+        }
+        self.view.borrow_mut().update(ViewUpdate::HighlightPath {
+            spheres: Rc::new(spheres),
+            tubes: Rc::new(tubes),
+        });
+    }
+
+    /// Show the measured nucleotides and the segments joining them
+    fn update_measurements(&mut self, measurements: &[Measurement]) {
+        let mut spheres = Vec::new();
+        let mut tubes = Vec::new();
+        if let Some(design) = self.designs.get(0) {
+            for measurement in measurements.iter() {
+                if let Some(positions) = design.get_measurement_positions(measurement) {
+                    spheres.extend(positions.iter().map(|p| Design3D::<R>::measure_sphere(*p)));
+                    // The angle between two helices is not materialized by segments
+                    if !matches!(measurement, Measurement::HelixAngle { .. }) {
+                        tubes.extend(
+                            positions
+                                .windows(2)
+                                .map(|w| Design3D::<R>::measure_tube(w[0], w[1])),
+                        );
+                    }
+                }
+            }
+        }
+        for (nucl, design_id) in self.measured_nucls.iter() {
+            if let Some(position) = self.get_nucl_position(*nucl, *design_id) {
+                spheres.push(Design3D::<R>::measure_sphere(position));
+            }
+        }
+        self.view
+            .borrow_mut()
+            .update(ViewUpdate::RawDna(Mesh::MeasureSphere, Rc::new(spheres)));
+        self.view
+            .borrow_mut()
+            .update(ViewUpdate::RawDna(Mesh::MeasureTube, Rc::new(tubes)));
+    }
+
+    fn clear_measured_nucls(&mut self) {
+        self.measured_nucls_update |= !self.measured_nucls.is_empty();
+        self.measured_nucls.clear();
+    }
+
+    /// Add the nucleotide `element` to the measurement being made. If enough nucleotides have been
+    /// clicked to measure a quantity of kind `kind`, return the measurement.
+    pub fn add_measured_element(
+        &mut self,
+        element: Option<SceneElement>,
+        kind: MeasureKind,
+    ) -> Option<Measurement> {
+        let nucl = self.element_to_nucl(&element, true)?;
+        self.measured_nucls.push(nucl);
+        self.measured_nucls_update = true;
+        if self.measured_nucls.len() < kind.nb_nucls() {
+            return None;
+        }
+        let nucls = std::mem::take(&mut self.measured_nucls);
+        let positions = nucls
+            .iter()
+            .map(|(nucl, design_id)| self.get_nucl_position(*nucl, *design_id))
+            .collect::<Option<Vec<_>>>()?;
+        match kind {
+            MeasureKind::Distance => Some(Measurement::Distance {
+                nucls: (nucls[0].0, nucls[1].0),
+                distance: (positions[1] - positions[0]).mag(),
+            }),
+            MeasureKind::Angle => Some(Measurement::Angle {
+                nucls: (nucls[0].0, nucls[1].0, nucls[2].0),
+                angle: angle_between(positions[0] - positions[1], positions[2] - positions[1])?,
+            }),
+            MeasureKind::HelixAngle => {
+                let axes = nucls
+                    .iter()
+                    .map(|(nucl, design_id)| {
+                        self.designs
+                            .get(*design_id)?
+                            .get_helix_axis_direction(nucl.helix)
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                Some(Measurement::HelixAngle {
+                    nucls: (nucls[0].0, nucls[1].0),
+                    angle: angle_between(axes[0], axes[1])?,
+                })
+            }
+        }
+    }
+
     fn update_pivot(&mut self) {
         let spheres = if let Some(pivot) = self.pivot_position {
             vec![Design3D::<R>::pivot_sphere(pivot)]
@@ -1131,8 +1421,68 @@ impl<R: DesignReader> Data<R> {
         }
     }
 
+    /// The cone containing the instances that may be visible from the current camera, or `None`
+    /// if all the instances must be sent to the view
+    fn current_culling_cone(&self) -> Option<CullingCone> {
+        if !self.culling_enabled {
+            return None;
+        }
+        let view = self.view.borrow();
+        let camera = view.get_camera();
+        let camera = camera.borrow();
+        let projection = view.get_projection();
+        let projection = projection.borrow();
+        // Turning around the pivot by an angle `a` moves the camera by at most `a` times its
+        // distance to the pivot
+        let pivot = self.get_pivot_position().unwrap_or_else(Vec3::zero);
+        let margin = CULLING_MARGIN.max((camera.position - pivot).mag() * CULLING_MARGIN_ANGLE);
+        CullingCone::new(
+            camera.position,
+            camera.direction(),
+            projection.get_fovy(),
+            projection.get_ratio(),
+            margin,
+            CULLING_MARGIN_ANGLE,
+        )
+    }
+
+    /// Return true if some instances that were not sent to the view may have become visible
+    fn culling_needs_update<S: AppState>(&self, app_state: &S, older_app_state: &S) -> bool {
+        if let Some(cone) = self.culling_cone.as_ref() {
+            let view = self.view.borrow();
+            let camera = view.get_camera();
+            let camera = camera.borrow();
+            let projection = view.get_projection();
+            let projection = projection.borrow();
+            app_state.design_model_matrix_was_updated(older_app_state)
+                || !cone.contains_view(
+                    camera.position,
+                    camera.direction(),
+                    projection.get_fovy(),
+                    projection.get_ratio(),
+                )
+        } else {
+            false
+        }
+    }
+
+    /// Choose whether the instances that cannot be visible from the camera are sent to the view.
+    /// Return the previous choice.
+    pub fn set_culling<S: AppState>(&mut self, enabled: bool, app_state: &S) -> bool {
+        let was_enabled = self.culling_enabled;
+        if enabled != was_enabled {
+            self.culling_enabled = enabled;
+            self.update_instances(app_state);
+        }
+        was_enabled
+    }
+
     /// Notify the view that the set of instances have been modified.
+    ///
+    /// Only the spheres and tubes that may be visible from the camera are sent.
     fn update_instances<S: AppState>(&mut self, app_state: &S) {
+        self.culling_cone = self.current_culling_cone();
+        let culling_cone = self.culling_cone;
         let mut spheres = Vec::with_capacity(self.get_number_spheres());
         let mut tubes = Vec::with_capacity(self.get_number_tubes());
         let mut suggested_spheres = Vec::with_capacity(1000);
@@ -1143,12 +1493,49 @@ impl<R: DesignReader> Data<R> {
         let mut letters = Vec::new();
         let mut grids = Vec::new();
         let mut cones = Vec::new();
-        for design in self.designs.iter() {
-            for sphere in design.get_spheres_raw().iter() {
-                spheres.push(*sphere);
+        let mut anchors = Vec::new();
+        let mut annotations = Vec::new();
+        let analysis_function = design3d::analysis_coloring_function(self.analysis_coloring);
+        let trajectory = app_state.get_trajectory();
+        let main_design_visible = app_state.is_main_design_visible();
+        for design in self.designs.iter().filter(|_| main_design_visible) {
+            let theme_colors = design.get_theme_colors(&self.color_theme);
+            let analysis_colors = analysis_function
+                .as_ref()
+                .map(|function| design.get_function_colors(function.as_ref()));
+            let colored_spheres = if self.show_local_twist {
+                design.get_colored_spheres_raw(&design.get_local_twist_colors(), trajectory)
+            } else if let Some(colors) = analysis_colors.as_ref() {
+                design.get_colored_spheres_raw(colors, trajectory)
+            } else {
+                // The colors set by external programs take precedence over the ones of the theme
+                let mut nucl_colors = theme_colors.clone();
+                nucl_colors.extend(app_state.get_nucleotide_colors());
+                design.get_colored_spheres_raw(&nucl_colors, trajectory)
+            };
+            let colored_tubes = if let Some(colors) = analysis_colors.as_ref() {
+                Rc::new(design.get_colored_tubes_raw(colors, trajectory))
+            } else if theme_colors.is_empty() && trajectory.is_none() {
+                design.get_tubes_raw()
+            } else {
+                Rc::new(design.get_colored_tubes_raw(&theme_colors, trajectory))
+            };
+            let model_matrix = design.get_model_matrix();
+            let is_kept = |instance: &RawDnaInstance| {
+                culling_cone
+                    .as_ref()
+                    .map(|cone| may_be_visible(instance, model_matrix, cone))
+                    .unwrap_or(true)
+            };
+            for sphere in colored_spheres {
+                if is_kept(&sphere) {
+                    spheres.push(sphere);
+                }
             }
-            for tube in design.get_tubes_raw().iter() {
-                tubes.push(*tube);
+            for tube in colored_tubes.iter() {
+                if is_kept(tube) {
+                    tubes.push(*tube);
+                }
             }
             letters = design.get_letter_instances();
             for grid in design.get_grid().iter().filter(|g| g.visible) {
@@ -1160,7 +1547,8 @@ impl<R: DesignReader> Data<R> {
             for tube in design.get_suggested_tubes() {
                 suggested_tubes.push(tube)
             }
-            let (spheres, tubes) = design.get_pasted_strand();
+            let (spheres, tubes) =
+                design.get_pasted_strand(self.candidate_color(), self.selected_color());
             for sphere in spheres {
                 pasted_spheres.push(sphere);
             }
@@ -1170,6 +1558,34 @@ impl<R: DesignReader> Data<R> {
             for cone in design.get_all_prime3_cone() {
                 cones.push(cone);
             }
+            let (spheres_insertion, tubes_insertion) = design.get_insertion_instances();
+            spheres.extend(spheres_insertion.into_iter().filter(|s| is_kept(s)));
+            tubes.extend(tubes_insertion.into_iter().filter(|t| is_kept(t)));
+            for sphere in design.get_anchor_spheres() {
+                anchors.push(sphere);
+            }
+            annotations.extend(design.get_annotations());
+            if let Some(s_id) = self.edited_strand {
+                annotations.extend(design.get_edited_sequence_label(s_id));
+            }
+            if self.show_helix_numbers {
+                annotations.extend(design.get_helix_number_labels());
+            }
+            annotations.extend(design.get_measurement_labels(app_state.get_measurements()));
+        }
+        let mut workspace_spheres = Vec::new();
+        let mut workspace_tubes = Vec::new();
+        for (design, matrix) in self.workspace_designs.iter() {
+            let place = |instance: &RawDnaInstance| {
+                let model = *matrix * instance.model;
+                RawDnaInstance {
+                    model,
+                    inversed_model: model.inversed(),
+                    ..*instance
+                }
+            };
+            workspace_spheres.extend(design.get_spheres_raw().iter().map(place));
+            workspace_tubes.extend(design.get_tubes_raw().iter().map(place));
         }
         self.update_free_xover(app_state.get_candidates());
         self.view
@@ -1193,6 +1609,14 @@ impl<R: DesignReader> Data<R> {
         self.view
             .borrow_mut()
             .update(ViewUpdate::RawDna(Mesh::PastedTube, Rc::new(pasted_tubes)));
+        self.view.borrow_mut().update(ViewUpdate::RawDna(
+            Mesh::WorkspaceSphere,
+            Rc::new(workspace_spheres),
+        ));
+        self.view.borrow_mut().update(ViewUpdate::RawDna(
+            Mesh::WorkspaceTube,
+            Rc::new(workspace_tubes),
+        ));
         self.view.borrow_mut().update(ViewUpdate::Letter(letters));
         self.view
             .borrow_mut()
@@ -1200,11 +1624,17 @@ impl<R: DesignReader> Data<R> {
         self.view
             .borrow_mut()
             .update(ViewUpdate::RawDna(Mesh::Prime3Cone, Rc::new(cones)));
+        self.view
+            .borrow_mut()
+            .update(ViewUpdate::AnchorInstances(Rc::new(anchors)));
+        self.view
+            .borrow_mut()
+            .update(ViewUpdate::Annotations(annotations));
     }
 
     fn update_discs<S: AppState>(&mut self, app_state: &S) {
         let mut discs = Vec::new();
-        let mut letters: Vec<Vec<LetterInstance>> = vec![vec![]; 10];
+        let mut letters: Vec<LetterInstance> = Vec::new();
         let right = self.view.borrow().get_camera().borrow().right_vec();
         let up = self.view.borrow().get_camera().borrow().up_vec();
         let mut selected_discs: Vec<(usize, isize, isize)> = Vec::new();
@@ -1317,28 +1747,34 @@ impl<R: DesignReader> Data<R> {
 
     fn get_selected_basis<S: AppState>(&self, app_state: &S) -> Option<Rotor3> {
         let from_selected_element = match self.selected_element(app_state) {
-            Some(SceneElement::DesignElement(d_id, _)) => match self
-                .get_sub_selection_mode(app_state)
-            {
-                SelectionMode::Nucleotide | SelectionMode::Design | SelectionMode::Strand => None,
-                SelectionMode::Grid => Some(self.designs[d_id as usize].get_basis()),
-                SelectionMode::Helix => {
-                    let h_id = self.get_selected_group(app_state)?;
-                    if let Some(grid_position) =
-                        self.designs[d_id as usize].get_helix_grid_position(h_id)
-                    {
-                        self.designs[d_id as usize].get_grid_basis(grid_position.grid)
-                    } else {
-                        self.designs[d_id as usize].get_helix_basis(h_id)
+            Some(SceneElement::DesignElement(d_id, _)) => {
+                match self.get_sub_selection_mode(app_state) {
+                    SelectionMode::Nucleotide
+                    | SelectionMode::Design
+                    | SelectionMode::Strand
+                    | SelectionMode::StrandEnd(_)
+                    | SelectionMode::Domain => None,
+                    SelectionMode::Grid => Some(self.designs[d_id as usize].get_basis()),
+                    SelectionMode::Helix => {
+                        let h_id = self.get_selected_group(app_state)?;
+                        if let Some(grid_position) =
+                            self.designs[d_id as usize].get_helix_grid_position(h_id)
+                        {
+                            self.designs[d_id as usize].get_grid_basis(grid_position.grid)
+                        } else {
+                            self.designs[d_id as usize].get_helix_basis(h_id)
+                        }
                     }
                 }
-            },
+            }
             Some(SceneElement::PhantomElement(phantom_element)) => {
                 let d_id = phantom_element.design_id;
                 match self.get_sub_selection_mode(app_state) {
-                    SelectionMode::Nucleotide | SelectionMode::Design | SelectionMode::Strand => {
-                        None
-                    }
+                    SelectionMode::Nucleotide
+                    | SelectionMode::Design
+                    | SelectionMode::Strand
+                    | SelectionMode::StrandEnd(_)
+                    | SelectionMode::Domain => None,
                     SelectionMode::Grid => Some(self.designs[d_id as usize].get_basis()),
                     SelectionMode::Helix => {
                         let h_id = phantom_element.helix_id;
@@ -1354,24 +1790,38 @@ impl<R: DesignReader> Data<R> {
             }
             _ => None,
         };
-        let from_selection = match app_state.get_selection().get(0) {
-            Some(Selection::Grid(d_id, g_id)) => self
+        // When the selected elements do not share the same basis, the widget is aligned with the
+        // world's axis
+        let mut bases = app_state
+            .get_selection()
+            .iter()
+            .map(|s| self.get_selection_basis(s));
+        let from_selection = bases.next().and_then(|first| {
+            if bases.all(|basis| basis == first) {
+                first
+            } else {
+                Some(Rotor3::identity())
+            }
+        });
+        from_selection.or(from_selected_element)
+    }
+
+    fn get_selection_basis(&self, selection: &Selection) -> Option<Rotor3> {
+        match selection {
+            Selection::Grid(d_id, g_id) => self
                 .designs
                 .get(*d_id as usize)
                 .and_then(|d| d.get_grid_basis(*g_id)),
-            Some(Selection::Helix(d_id, h_id)) => {
-                if let Some(grid_position) =
-                    self.designs[*d_id as usize].get_helix_grid_position(*h_id)
-                {
-                    self.designs[*d_id as usize].get_grid_basis(grid_position.grid)
+            Selection::Helix(d_id, h_id) => {
+                let design = self.designs.get(*d_id as usize)?;
+                if let Some(grid_position) = design.get_helix_grid_position(*h_id) {
+                    design.get_grid_basis(grid_position.grid)
                 } else {
-                    self.designs[*d_id as usize].get_helix_basis(*h_id)
+                    design.get_helix_basis(*h_id)
                 }
             }
-            Some(_) => Some(Rotor3::identity()),
-            None => None,
-        };
-        from_selection.or(from_selected_element)
+            _ => Some(Rotor3::identity()),
+        }
     }
 
     pub fn can_start_builder(&self, element: Option<SceneElement>) -> Option<Nucl> {
@@ -1402,11 +1852,114 @@ impl<R: DesignReader> Data<R> {
         }
     }
 
+    /// Write the visible nucleotides and bounds of the designs as a triangle mesh in the OBJ
+    /// format.
+    pub fn export_mesh(&self, path: &Path) -> std::io::Result<()> {
+        let mut mesh = TriangleMesh::new();
+        for design in self.designs.iter() {
+            let model_matrix = design.get_model_matrix();
+            mesh.add_spheres(design.get_spheres_raw().as_ref(), model_matrix);
+            mesh.add_tubes(design.get_tubes_raw().as_ref(), model_matrix);
+        }
+        if mesh.nb_triangles() == 0 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "There is nothing to export",
+            ));
+        }
+        mesh.write_obj(path)
+    }
+
+    /// Return the visible nucleotides whose projection on the screen lies in the rectangle
+    /// with opposite corners `corner1` and `corner2`. The corners are given as fractions of the
+    /// size of the drawing area.
+    pub fn get_nucls_in_rectangle(
+        &self,
+        corner1: (f32, f32),
+        corner2: (f32, f32),
+    ) -> Vec<Selection> {
+        let (min_x, max_x) = (corner1.0.min(corner2.0), corner1.0.max(corner2.0));
+        let (min_y, max_y) = (corner1.1.min(corner2.1), corner1.1.max(corner2.1));
+        let camera = self.view.borrow().get_camera();
+        let projection = self.view.borrow().get_projection();
+        let in_rectangle = |position: Vec3| {
+            world_to_ndc(position, camera.clone(), projection.clone())
+                .map(|(x, y)| x >= min_x && x <= max_x && y >= min_y && y <= max_y)
+                .unwrap_or(false)
+        };
+        let mut ret = Vec::new();
+        for (d_id, design) in self.designs.iter().enumerate() {
+            ret.extend(
+                design
+                    .get_visible_nucls_such_that(&in_rectangle)
+                    .into_iter()
+                    .map(|nucl| Selection::Nucleotide(d_id as u32, nucl)),
+            );
+        }
+        ret
+    }
+
     pub fn get_nucl_position(&self, nucl: Nucl, design_id: usize) -> Option<Vec3> {
         let design = self.designs.get(design_id)?;
         design.get_nucl_position(nucl)
     }
 
+    /// Return the positions of the nucleotides that the moving end of `builder` would go through
+    /// if it was moved to `end_position`. The design is not modified.
+    pub fn get_strand_builder_preview(
+        &self,
+        builder: &StrandBuilder,
+        end_position: isize,
+    ) -> Vec<Vec3> {
+        let design_id = builder.get_design_id() as usize;
+        builder
+            .get_candidate_nucls(end_position)
+            .into_iter()
+            .filter_map(|nucl| self.get_nucl_position(nucl, design_id))
+            .collect()
+    }
+
+    /// Display the strands that would be obtained by moving the first of `builders` to
+    /// `end_position`, and the other ones by the same amount, as ghost nucleotides.
+    pub fn show_strand_builders_preview(
+        &mut self,
+        builders: &[StrandBuilder],
+        end_position: isize,
+    ) {
+        let mut spheres = vec![];
+        let mut tubes = vec![];
+        let delta = builders
+            .get(0)
+            .map(|b| end_position - b.get_moving_end_position())
+            .unwrap_or(0);
+        for builder in builders.iter() {
+            let mut previous_position = self.get_nucl_position(
+                builder.get_moving_end_nucl(),
+                builder.get_design_id() as usize,
+            );
+            for position in
+                self.get_strand_builder_preview(builder, builder.get_moving_end_position() + delta)
+            {
+                spheres.push(Design3D::<R>::builder_preview_sphere(position));
+                if let Some(previous) = previous_position {
+                    tubes.push(Design3D::<R>::builder_preview_tube(previous, position));
+                }
+                previous_position = Some(position);
+            }
+        }
+        self.view.borrow_mut().update(ViewUpdate::RawDna(
+            Mesh::BuilderPreviewSphere,
+            Rc::new(spheres),
+        ));
+        self.view
+            .borrow_mut()
+            .update(ViewUpdate::RawDna(Mesh::BuilderPreviewTube, Rc::new(tubes)));
+    }
+
+    pub fn clear_strand_builders_preview(&mut self) {
+        self.show_strand_builders_preview(&[], 0)
+    }
+
     /*
     /// Set the selection to a given nucleotide if it exists in the design.
     pub fn select_nucl(&mut self, nucl: Nucl, design_id: usize) {
@@ -1772,3 +2325,11 @@ fn candidate_xover(candidates: &[Selection]) -> Option<FreeXover> {
         None
     }
 }
+
+/// Return true if `instance`, drawn with `model_matrix`, may intersect `cone`
+fn may_be_visible(instance: &RawDnaInstance, model_matrix: Mat4, cone: &CullingCone) -> bool {
+    let center = (model_matrix * instance.model).transform_point3(Vec3::zero());
+    // The meshes of the spheres and tubes fit in a ball whose radius is their largest scale
+    let scale = instance.scale;
+    cone.contains_ball(center, scale.x.max(scale.y).max(scale.z))
+}
@@ -23,33 +23,34 @@ use super::super::GridInstance;
 use super::{LetterInstance, SceneElement};
 use crate::consts::*;
 use crate::utils::instance::Instance;
-use ensnano_design::{grid::GridPosition, Nucl};
+use ensnano_design::{grid::GridPosition, Nucl, Parameters};
+use ensnano_interactor::graphics::{ColorBy, ColorTheme};
 use ensnano_interactor::{
-    phantom_helix_encoder_bound, phantom_helix_encoder_nucl, ObjectType, PhantomElement,
-    Referential, PHANTOM_RANGE,
+    phantom_helix_encoder_bound, phantom_helix_encoder_nucl, Measurement, ObjectType,
+    PhantomElement, Referential, StrandEnd, Trajectory, PHANTOM_RANGE,
 };
 use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 use ultraviolet::{Mat4, Rotor3, Vec3};
 
+mod analysis;
+pub use analysis::analysis_coloring_function;
+
+/// A function that gives a color to some nucleotides of a design, for example to show the result
+/// of an analysis of the design in the 3D view
+pub trait ColoringFunction<R: DesignReader> {
+    fn nucl_colors(&self, design: &R) -> HashMap<Nucl, [f32; 3]>;
+}
+
 /// An object that handles the 3d graphcial representation of a `Design`
 pub struct Design3D<R: DesignReader> {
     design: R,
     id: u32,
-    symbol_map: HashMap<char, usize>,
 }
 
 impl<R: DesignReader> Design3D<R> {
     pub fn new(design: R, id: u32) -> Self {
-        let mut symbol_map = HashMap::new();
-        for (s_id, s) in BASIS_SYMBOLS.iter().enumerate() {
-            symbol_map.insert(*s, s_id);
-        }
-        Self {
-            design,
-            id,
-            symbol_map,
-        }
+        Self { design, id }
     }
 
     /*
@@ -89,16 +90,58 @@ impl<R: DesignReader> Design3D<R> {
         Rc::new(self.id_to_raw_instances(ids))
     }
 
-    pub fn get_pasted_strand(&self) -> (Vec<RawDnaInstance>, Vec<RawDnaInstance>) {
+    /// Return the list of raw sphere instances to be displayed to represent the design, where
+    /// the nucleotides in `nucl_colors` are given a custom color instead of the color of their
+    /// strand.
+    ///
+    /// If a `trajectory` is given, the nucleotides that it contains are displayed at their
+    /// position in its current frame.
+    pub fn get_colored_spheres_raw(
+        &self,
+        nucl_colors: &HashMap<Nucl, [f32; 3]>,
+        trajectory: Option<&Trajectory>,
+    ) -> Vec<RawDnaInstance> {
+        let ids = self.design.get_all_visible_nucl_ids();
+        if nucl_colors.is_empty() {
+            return ids
+                .into_iter()
+                .filter_map(|id| self.make_raw_instance_in_frame(id, trajectory))
+                .collect();
+        }
+        ids.into_iter()
+            .filter_map(|id| {
+                let mut instance = self.make_raw_instance_in_frame(id, trajectory)?;
+                if let Some(color) = self
+                    .design
+                    .get_nucl_with_id(id)
+                    .and_then(|nucl| nucl_colors.get(&nucl))
+                {
+                    instance.color = ultraviolet::Vec4::new(
+                        color[0].clamp(0., 1.),
+                        color[1].clamp(0., 1.),
+                        color[2].clamp(0., 1.),
+                        1.,
+                    );
+                }
+                Some(instance)
+            })
+            .collect()
+    }
+
+    pub fn get_pasted_strand(
+        &self,
+        pastable_color: u32,
+        unpastable_color: u32,
+    ) -> (Vec<RawDnaInstance>, Vec<RawDnaInstance>) {
         let mut spheres = Vec::new();
         let mut tubes = Vec::new();
         let positions = self.design.get_pasted_position();
         for (positions, pastable) in positions {
             let mut previous_postion = None;
             let color = if pastable {
-                CANDIDATE_COLOR
+                pastable_color
             } else {
-                SELECTED_COLOR
+                unpastable_color
             };
             let color_vec4 = Instance::color_from_au32(color);
             for position in positions.iter() {
@@ -120,26 +163,27 @@ impl<R: DesignReader> Design3D<R> {
         (spheres, tubes)
     }
 
-    pub fn get_letter_instances(&self) -> Vec<Vec<LetterInstance>> {
+    pub fn get_letter_instances(&self) -> Vec<LetterInstance> {
         let ids = self.design.get_all_nucl_ids();
-        let mut vecs = vec![Vec::new(); NB_BASIS_SYMBOLS];
+        let mut ret = Vec::with_capacity(ids.len());
         for id in ids {
             let pos = self.design.get_symbol_position(id);
             let symbol = self.design.get_symbol(id);
             if let Some((pos, symbol)) = pos.zip(symbol) {
-                if let Some(id) = self.symbol_map.get(&symbol) {
-                    let instance = LetterInstance {
+                if BASIS_SYMBOLS.contains(&symbol) {
+                    ret.push(LetterInstance {
                         position: pos,
                         color: ultraviolet::Vec4::new(0., 0., 0., 1.),
                         design_id: self.id,
                         scale: 1.,
                         shift: Vec3::zero(),
-                    };
-                    vecs[*id].push(instance);
+                        fade: true,
+                        character: symbol,
+                    });
                 }
             }
         }
-        vecs
+        ret
     }
 
     /*
@@ -156,6 +200,100 @@ impl<R: DesignReader> Design3D<R> {
         Rc::new(self.id_to_raw_instances(ids))
     }
 
+    /// Return the list of raw tube instances to be displayed to represent the design, where the
+    /// tubes whose 5' nucleotide is in `nucl_colors` are given a custom color instead of the
+    /// color of their strand.
+    ///
+    /// If a `trajectory` is given, the tubes join the positions of their nucleotides in its
+    /// current frame.
+    pub fn get_colored_tubes_raw(
+        &self,
+        nucl_colors: &HashMap<Nucl, [f32; 3]>,
+        trajectory: Option<&Trajectory>,
+    ) -> Vec<RawDnaInstance> {
+        let ids = self.design.get_all_visible_bound_ids();
+        ids.into_iter()
+            .filter_map(|id| {
+                let mut instance = self.make_raw_instance_in_frame(id, trajectory)?;
+                if let Some(color) = self
+                    .design
+                    .get_nucl_with_id_relaxed(id)
+                    .and_then(|nucl| nucl_colors.get(&nucl))
+                {
+                    instance.color = ultraviolet::Vec4::new(color[0], color[1], color[2], 1.);
+                }
+                Some(instance)
+            })
+            .collect()
+    }
+
+    /// Return the colors given to the nucleotides of the design by `function`
+    pub fn get_function_colors(
+        &self,
+        function: &dyn ColoringFunction<R>,
+    ) -> HashMap<Nucl, [f32; 3]> {
+        function.nucl_colors(&self.design)
+    }
+
+    /// Return the color of the nucleotides that are not colored according to their strand by
+    /// `theme`
+    pub fn get_theme_colors(&self, theme: &ColorTheme) -> HashMap<Nucl, [f32; 3]> {
+        let nucls = self
+            .design
+            .get_all_nucl_ids()
+            .into_iter()
+            .filter_map(|id| Some((id, self.design.get_nucl_with_id(id)?)));
+        let categorical = |idx: usize| theme.categorical_color(idx).map(color_components);
+        let mut ret = match theme.color_by {
+            ColorBy::Strand => HashMap::new(),
+            ColorBy::Helix => nucls
+                .filter_map(|(_, nucl)| Some((nucl, categorical(nucl.helix)?)))
+                .collect(),
+            // Consecutive domains of a strand have different colors
+            ColorBy::Domain => self
+                .design
+                .get_domains_of_nucls()
+                .into_iter()
+                .filter_map(|(nucl, (s_id, d_id))| Some((nucl, categorical(s_id + d_id)?)))
+                .collect(),
+            ColorBy::GcContent => {
+                // For each strand, the number of G/C bases and the number of known bases
+                let mut gc_counts: HashMap<usize, (usize, usize)> = HashMap::new();
+                let mut strand_of_nucls = Vec::new();
+                for (id, nucl) in nucls {
+                    if let Some(s_id) = self.design.get_id_of_strand_containing(id) {
+                        strand_of_nucls.push((nucl, s_id));
+                        let count = gc_counts.entry(s_id).or_default();
+                        match self.design.get_symbol(id).map(|c| c.to_ascii_uppercase()) {
+                            Some('G') | Some('C') => {
+                                count.0 += 1;
+                                count.1 += 1;
+                            }
+                            Some('A') | Some('T') => count.1 += 1,
+                            _ => (),
+                        }
+                    }
+                }
+                strand_of_nucls
+                    .into_iter()
+                    .filter_map(|(nucl, s_id)| {
+                        let (gc, known) = gc_counts.get(&s_id).filter(|c| c.1 > 0)?;
+                        Some((nucl, gc_content_color(*gc as f32 / *known as f32)))
+                    })
+                    .collect()
+            }
+        };
+        if theme.scaffold_route {
+            let scaffold_nucls = self.design.get_scaffold_nucls();
+            let nb_nucls = scaffold_nucls.len();
+            for (i, nucl) in scaffold_nucls.into_iter().enumerate() {
+                let t = i as f32 / (nb_nucls.max(2) - 1) as f32;
+                ret.insert(nucl, route_color(t));
+            }
+        }
+        ret
+    }
+
     pub fn get_model_matrix(&self) -> Mat4 {
         self.design.get_model_matrix()
     }
@@ -197,19 +335,35 @@ impl<R: DesignReader> Design3D<R> {
 
     /// Convert return an instance representing the object with identifier `id`
     pub fn make_raw_instance(&self, id: u32) -> Option<RawDnaInstance> {
+        self.make_raw_instance_in_frame(id, None)
+    }
+
+    /// The position of a nucleotide in the current frame of a trajectory, or its position in the
+    /// design if it is not in the trajectory
+    fn get_position_in_frame(&self, id: u32, trajectory: Option<&Trajectory>) -> Option<Vec3> {
+        trajectory
+            .zip(self.design.get_nucl_with_id(id))
+            .and_then(|(trajectory, nucl)| trajectory.current_position(&nucl))
+            .or_else(|| self.get_design_element_position(id, Referential::Model))
+    }
+
+    fn make_raw_instance_in_frame(
+        &self,
+        id: u32,
+        trajectory: Option<&Trajectory>,
+    ) -> Option<RawDnaInstance> {
         let kind = self.get_object_type(id)?;
-        let referential = Referential::Model;
         let raw_instance = match kind {
             ObjectType::Bound(id1, id2) => {
-                let pos1 = self.get_design_element_position(id1, referential)?;
-                let pos2 = self.get_design_element_position(id2, referential)?;
+                let pos1 = self.get_position_in_frame(id1, trajectory)?;
+                let pos2 = self.get_position_in_frame(id2, trajectory)?;
                 let color = self.get_color(id).unwrap_or(0);
                 let id = id | self.id << 24;
                 let tube = create_dna_bound(pos1, pos2, color, id, false);
                 tube.to_raw_instance()
             }
             ObjectType::Nucleotide(id) => {
-                let position = self.get_design_element_position(id, referential)?;
+                let position = self.get_position_in_frame(id, trajectory)?;
                 let color = self.get_color(id)?;
                 let color = Instance::color_from_u32(color);
                 let id = id | self.id << 24;
@@ -265,6 +419,227 @@ impl<R: DesignReader> Design3D<R> {
         ret
     }
 
+    /// Return the instances of the spheres marking the anchored nucleotides
+    pub fn get_anchor_spheres(&self) -> Vec<RawDnaInstance> {
+        let mut ret = vec![];
+        for anchor in self.design.get_anchors() {
+            if let Some(position) =
+                self.design
+                    .get_position_of_nucl_on_helix(anchor, Referential::Model, false)
+            {
+                let instance = SphereInstance {
+                    color: Instance::color_from_au32(ANCHOR_COLOR),
+                    position,
+                    id: 0,
+                    radius: SELECT_SCALE_FACTOR,
+                }
+                .to_raw_instance();
+                ret.push(instance);
+            }
+        }
+        ret
+    }
+
+    /// Return the spheres and the tubes representing the nucleotides of the insertions. The
+    /// nucleotides of an insertion are laid out on an arc that leaves the nucleotide preceding
+    /// the insertion in the direction of its strand.
+    pub fn get_insertion_instances(&self) -> (Vec<RawDnaInstance>, Vec<RawDnaInstance>) {
+        let mut spheres = Vec::new();
+        let mut tubes = Vec::new();
+        let position = |nucl: Nucl| {
+            self.design
+                .get_position_of_nucl_on_helix(nucl, Referential::Model, false)
+        };
+        for (prime5, prime3, length, color) in self.design.get_insertions() {
+            let (source, dest, previous) = match (
+                position(prime5),
+                position(prime3),
+                position(prime5.prime5()),
+            ) {
+                (Some(source), Some(dest), Some(previous)) => (source, dest, previous),
+                _ => continue,
+            };
+            let color_vec4 = Instance::color_from_u32(color);
+            let mut previous_position = source;
+            for arc_position in insertion_arc(source, dest, previous, length) {
+                let sphere = SphereInstance {
+                    position: arc_position,
+                    color: color_vec4,
+                    id: 0,
+                    radius: 1.,
+                }
+                .to_raw_instance();
+                spheres.push(sphere);
+                let tube = create_dna_bound(previous_position, arc_position, color, 0, false);
+                tubes.push(tube.to_raw_instance());
+                previous_position = arc_position;
+            }
+            let tube = create_dna_bound(previous_position, dest, color, 0, false);
+            tubes.push(tube.to_raw_instance());
+        }
+        (spheres, tubes)
+    }
+
+    /// Return the twist angle, in degrees, between each pair of consecutive base pairs of
+    /// `helix` in the range `min..=max`.
+    fn local_twist_on_range(&self, helix: usize, min: isize, max: isize) -> Vec<f32> {
+        // The position of the helix axis and the direction from the axis to the forward
+        // nucleotide at each position of the range
+        let frames: Option<Vec<(Vec3, Vec3)>> = (min..=max)
+            .map(|position| {
+                let nucl = Nucl {
+                    helix,
+                    position,
+                    forward: true,
+                };
+                let axis =
+                    self.design
+                        .get_position_of_nucl_on_helix(nucl, Referential::Model, true)?;
+                let nucl_position =
+                    self.design
+                        .get_position_of_nucl_on_helix(nucl, Referential::Model, false)?;
+                Some((axis, nucl_position - axis))
+            })
+            .collect();
+        frames
+            .map(|frames| frames.windows(2).map(|w| twist_angle(w[0], w[1])).collect())
+            .unwrap_or_default()
+    }
+
+    /// Return a color for each nucleotide, that indicates how much the helix is twisted around
+    /// it. Undertwisted base pairs are blue, ideal ones are white and overtwisted ones are red.
+    pub fn get_local_twist_colors(&self) -> HashMap<Nucl, [f32; 3]> {
+        let ideal_twist = 360. / self.design.get_dna_parameters().bases_per_turn;
+        let mut nucls_by_helix: HashMap<usize, Vec<Nucl>> = HashMap::new();
+        for nucl in self
+            .design
+            .get_all_nucl_ids()
+            .into_iter()
+            .filter_map(|id| self.design.get_nucl_with_id(id))
+        {
+            nucls_by_helix.entry(nucl.helix).or_default().push(nucl);
+        }
+        let mut ret = HashMap::new();
+        for (helix, nucls) in nucls_by_helix.into_iter() {
+            let (min, max) = if let Some(range) = min_max(nucls.iter().map(|n| n.position)) {
+                range
+            } else {
+                continue;
+            };
+            let twists = self.local_twist_on_range(helix, min, max);
+            for nucl in nucls {
+                // A nucleotide is given the mean of the twists with its two neighbours
+                let i = (nucl.position - min) as usize;
+                let neighbour_twists: Vec<f32> = i
+                    .checked_sub(1)
+                    .and_then(|prev| twists.get(prev))
+                    .into_iter()
+                    .chain(twists.get(i))
+                    .cloned()
+                    .collect();
+                if !neighbour_twists.is_empty() {
+                    let twist =
+                        neighbour_twists.iter().sum::<f32>() / neighbour_twists.len() as f32;
+                    ret.insert(nucl, twist_color(twist - ideal_twist));
+                }
+            }
+        }
+        ret
+    }
+
+    /// Return the labels attached to nucleotides, together with the position at which they must
+    /// be displayed
+    pub fn get_annotations(&self) -> Vec<(Vec3, String)> {
+        let mut ret = vec![];
+        for (nucl, label) in self.design.get_annotations() {
+            if let Some(position) =
+                self.design
+                    .get_position_of_nucl_on_helix(nucl, Referential::Model, false)
+            {
+                ret.push((position, label));
+            }
+        }
+        ret
+    }
+
+    /// Return the label showing the sequence of the strand `s_id` while it is being typed,
+    /// together with the position of the 5' end of the strand
+    pub fn get_edited_sequence_label(&self, s_id: usize) -> Option<(Vec3, String)> {
+        let strand = self.design.get_strand_with_id(s_id)?;
+        let position = self.design.get_position_of_nucl_on_helix(
+            strand.get_5prime()?,
+            Referential::Model,
+            false,
+        )?;
+        let sequence = strand.sequence.as_deref().unwrap_or("");
+        let label = format!("{}_ {}/{}", sequence, sequence.len(), strand.length());
+        Some((position, label))
+    }
+
+    /// Return the identifier of each helix, together with the position on the axis of the helix
+    /// just before its lowest visible nucleotide. Helices without visible nucleotides are not
+    /// labeled.
+    pub fn get_helix_number_labels(&self) -> Vec<(Vec3, String)> {
+        let mut lowest_positions: HashMap<usize, isize> = HashMap::new();
+        for nucl in self
+            .design
+            .get_all_visible_nucl_ids()
+            .into_iter()
+            .filter_map(|id| self.design.get_nucl_with_id(id))
+        {
+            let lowest = lowest_positions.entry(nucl.helix).or_insert(nucl.position);
+            *lowest = (*lowest).min(nucl.position);
+        }
+        let mut ret = vec![];
+        for (helix, position) in lowest_positions.into_iter() {
+            let nucl = Nucl {
+                helix,
+                position: position - 1,
+                forward: true,
+            };
+            if let Some(position) =
+                self.design
+                    .get_position_of_nucl_on_helix(nucl, Referential::Model, true)
+            {
+                ret.push((position, helix.to_string()));
+            }
+        }
+        ret
+    }
+
+    /// Return the labels showing the measured values, together with the position at which they
+    /// must be displayed. Angles are displayed at their vertex and distances in the middle of the
+    /// measured segment.
+    pub fn get_measurement_labels(&self, measurements: &[Measurement]) -> Vec<(Vec3, String)> {
+        let parameters = self.design.get_dna_parameters();
+        measurements
+            .iter()
+            .filter_map(|measurement| {
+                let positions = self.get_measurement_positions(measurement)?;
+                let position = match measurement {
+                    Measurement::Angle { .. } => positions[1],
+                    _ => (positions[0] + positions[1]) / 2.,
+                };
+                Some((position, measurement.value_text(&parameters)))
+            })
+            .collect()
+    }
+
+    /// Return the positions of the nucleotides of a measurement
+    pub fn get_measurement_positions(&self, measurement: &Measurement) -> Option<Vec<Vec3>> {
+        measurement
+            .nucls()
+            .into_iter()
+            .map(|nucl| self.get_nucl_position(nucl))
+            .collect()
+    }
+
+    /// Return the direction of the axis of helix `h_id`
+    pub fn get_helix_axis_direction(&self, h_id: usize) -> Option<Vec3> {
+        self.get_helix_basis(h_id as u32)
+            .map(|basis| Vec3::unit_x().rotated_by(basis))
+    }
+
     pub fn get_suggested_tubes(&self) -> Vec<RawDnaInstance> {
         let suggestion = self.design.get_suggestions();
         let mut ret = vec![];
@@ -628,6 +1003,48 @@ impl<R: DesignReader> Design3D<R> {
         self.design.get_id_of_strand_containing(element_id)
     }
 
+    /// Return the identifier of the nucleotide at the `end` extremity of the strand containing
+    /// `element_id`
+    pub fn get_strand_end_identifier(&self, element_id: u32, end: StrandEnd) -> Option<u32> {
+        let s_id = self.get_strand(element_id)?;
+        let nucl = end.of_strand(self.design.get_strand_with_id(s_id)?)?;
+        self.get_identifier_nucl(&nucl)
+    }
+
+    /// Return the identifier of the scaffold domain containing `element_id`
+    pub fn get_domain_identifier(&self, element_id: u32) -> Option<u32> {
+        let nucl = self.design.get_nucl_with_id_relaxed(element_id)?;
+        self.design
+            .get_scaffold_domain_id(&nucl)
+            .map(|d_id| d_id as u32)
+    }
+
+    /// Return the nucleotides of a scaffold domain, of their complements and the bonds between
+    /// them
+    pub fn get_domain_elements(&self, domain_id: u32) -> HashSet<u32> {
+        let nucls: HashSet<Nucl> = self
+            .design
+            .get_scaffold_domain_nucls(domain_id as usize)
+            .unwrap_or_default()
+            .into_iter()
+            .collect();
+        let mut ret = HashSet::new();
+        for nucl in nucls.iter() {
+            if let Some(id) = self.get_identifier_nucl(nucl) {
+                ret.insert(id);
+            }
+            if nucls.contains(&nucl.right()) {
+                let bound = self
+                    .get_identifier_bound(*nucl, nucl.right())
+                    .or_else(|| self.get_identifier_bound(nucl.right(), *nucl));
+                if let Some(id) = bound {
+                    ret.insert(id);
+                }
+            }
+        }
+        ret
+    }
+
     pub fn get_helix(&self, element_id: u32) -> Option<usize> {
         self.design.get_id_of_helix_containing(element_id)
     }
@@ -752,6 +1169,21 @@ impl<R: DesignReader> Design3D<R> {
         self.design.get_helix_grid_position(h_id)
     }
 
+    /// Return the visible nucleotides whose position, in the world's coordinates, satisfies
+    /// `predicate`
+    pub fn get_visible_nucls_such_that<P: Fn(Vec3) -> bool>(&self, predicate: P) -> Vec<Nucl> {
+        self.design
+            .get_all_visible_nucl_ids()
+            .into_iter()
+            .filter(|e_id| {
+                self.get_design_element_position(*e_id, Referential::World)
+                    .map(&predicate)
+                    .unwrap_or(false)
+            })
+            .filter_map(|e_id| self.get_nucl(e_id))
+            .collect()
+    }
+
     pub fn get_nucl_position(&self, nucl: Nucl) -> Option<Vec3> {
         self.design
             .get_position_of_nucl_on_helix(nucl, Referential::World, false)
@@ -781,6 +1213,36 @@ impl<R: DesignReader> Design3D<R> {
         create_dna_bound(pos1, pos2, FREE_XOVER_COLOR, 0, true).to_raw_instance()
     }
 
+    pub fn builder_preview_sphere(position: Vec3) -> RawDnaInstance {
+        SphereInstance {
+            position,
+            id: 0,
+            radius: 1.,
+            color: Instance::color_from_au32(BUILDER_PREVIEW_COLOR),
+        }
+        .to_raw_instance()
+    }
+
+    pub fn builder_preview_tube(pos1: Vec3, pos2: Vec3) -> RawDnaInstance {
+        create_dna_bound(pos1, pos2, BUILDER_PREVIEW_COLOR, 0, true).to_raw_instance()
+    }
+
+    pub fn measure_sphere(position: Vec3) -> RawDnaInstance {
+        SphereInstance {
+            position,
+            id: 0,
+            radius: SELECT_SCALE_FACTOR,
+            color: Instance::color_from_au32(MEASURE_COLOR),
+        }
+        .to_raw_instance()
+    }
+
+    pub fn measure_tube(pos1: Vec3, pos2: Vec3) -> RawDnaInstance {
+        create_dna_bound(pos1, pos2, MEASURE_COLOR, 0, true)
+            .with_radius(0.5)
+            .to_raw_instance()
+    }
+
     pub fn has_nucl(&self, nucl: &Nucl) -> bool {
         self.design.get_identifier_nucl(nucl).is_some()
     }
@@ -833,6 +1295,118 @@ fn create_dna_bound(
     }
 }
 
+/// Return the positions of the `length` nucleotides of an insertion that goes from `source` to
+/// `dest`, where `previous` is the position of the nucleotide preceding `source`.
+///
+/// The positions lie on a half ellipse whose length is that of `length + 1` consecutive bounds
+/// of the strand, and that bulges in the direction going from `previous` to `source`.
+fn insertion_arc(source: Vec3, dest: Vec3, previous: Vec3, length: usize) -> Vec<Vec3> {
+    let step = (source - previous).mag();
+    let chord = dest - source;
+    let half_chord = chord.mag() / 2.;
+    let arc_length = (length + 1) as f32 * step;
+    // Solve π * sqrt((a² + b²) / 2) = arc_length for the half axis b of the ellipse
+    let bulge = (2. * (arc_length / std::f32::consts::PI).powi(2) - half_chord.powi(2))
+        .max(0.)
+        .sqrt();
+    let mut out = source - previous;
+    if half_chord > 1e-5 {
+        let chord_dir = chord.normalized();
+        out -= out.dot(chord_dir) * chord_dir;
+    }
+    let out = if out.mag() > 1e-5 {
+        out.normalized()
+    } else {
+        Vec3::unit_y()
+    };
+    let center = (source + dest) / 2.;
+    (1..=length)
+        .map(|k| {
+            let theta = std::f32::consts::PI * k as f32 / (length + 1) as f32;
+            center - theta.cos() * chord / 2. + theta.sin() * bulge * out
+        })
+        .collect()
+}
+
+/// The deviation from the ideal twist, in degrees, at which the local twist colors saturate
+const MAX_TWIST_DEVIATION: f32 = 5.;
+
+/// Return the angle, in degrees, between two consecutive base pairs. Each base pair is given by
+/// the position of the helix axis and the direction from the axis to its forward nucleotide.
+fn twist_angle((axis_a, dir_a): (Vec3, Vec3), (axis_b, dir_b): (Vec3, Vec3)) -> f32 {
+    let axis = axis_b - axis_a;
+    let project = |dir: Vec3| {
+        if axis.mag() > 1e-5 {
+            let axis = axis.normalized();
+            dir - dir.dot(axis) * axis
+        } else {
+            dir
+        }
+    };
+    let (a, b) = (project(dir_a), project(dir_b));
+    if a.mag() < 1e-5 || b.mag() < 1e-5 {
+        return 0.;
+    }
+    a.normalized()
+        .dot(b.normalized())
+        .clamp(-1., 1.)
+        .acos()
+        .to_degrees()
+}
+
+/// Map a deviation from the ideal twist to a diverging color scale going from blue (undertwisted)
+/// to white (ideal) to red (overtwisted)
+fn twist_color(deviation: f32) -> [f32; 3] {
+    diverging_color(deviation / MAX_TWIST_DEVIATION)
+}
+
+/// Map a GC content to a diverging color scale going from blue (no G/C bases) to white (half of
+/// the bases are G/C) to red (only G/C bases)
+fn gc_content_color(gc_content: f32) -> [f32; 3] {
+    diverging_color(2. * gc_content - 1.)
+}
+
+/// Map a value between 0 and 1 to a color of the viridis color map, going from purple to yellow
+fn route_color(t: f32) -> [f32; 3] {
+    const STOPS: [u32; 5] = [0x440154, 0x3B528B, 0x21918C, 0x5EC962, 0xFDE725];
+    let x = t.clamp(0., 1.) * (STOPS.len() - 1) as f32;
+    let idx = (x.floor() as usize).min(STOPS.len() - 2);
+    let frac = x - idx as f32;
+    let start = color_components(STOPS[idx]);
+    let end = color_components(STOPS[idx + 1]);
+    [
+        start[0] + frac * (end[0] - start[0]),
+        start[1] + frac * (end[1] - start[1]),
+        start[2] + frac * (end[2] - start[2]),
+    ]
+}
+
+/// Map a value between -1 and 1 to a color going from blue to white to red
+fn diverging_color(t: f32) -> [f32; 3] {
+    let t = t.clamp(-1., 1.);
+    if t < 0. {
+        [1. + t, 1. + t, 1.]
+    } else {
+        [1., 1. - t, 1. - t]
+    }
+}
+
+/// The red, green and blue components, between 0 and 1, of a color encoded as `0xRRGGBB`
+fn color_components(color: u32) -> [f32; 3] {
+    [
+        ((color >> 16) & 0xFF) as f32 / 255.,
+        ((color >> 8) & 0xFF) as f32 / 255.,
+        (color & 0xFF) as f32 / 255.,
+    ]
+}
+
+fn min_max<I: Iterator<Item = isize>>(values: I) -> Option<(isize, isize)> {
+    values.fold(None, |range, v| match range {
+        None => Some((v, v)),
+        Some((min, max)) => Some((min.min(v), max.max(v))),
+    })
+}
+
 fn create_prime3_cone(source: Vec3, dest: Vec3, color: u32) -> RawDnaInstance {
     let color = Instance::color_from_u32(color);
     let rotor = Rotor3::from_rotation_between(Vec3::unit_x(), (dest - source).normalized());
@@ -869,6 +1443,13 @@ pub trait DesignReader: 'static + ensnano_interactor::DesignReader {
     fn has_small_spheres_nucl_id(&self, e_id: u32) -> bool;
     /// Return the list of pairs of nucleotides that can be linked by a cross-over
     fn get_suggestions(&self) -> Vec<(Nucl, Nucl)>;
+    /// Return the list of nucleotides that must keep their position during simulations
+    fn get_anchors(&self) -> Vec<Nucl>;
+    /// Return the list of nucleotides that have a label attached to them
+    fn get_annotations(&self) -> Vec<(Nucl, String)>;
+    /// Return, for each insertion that lies between two nucleotides of a strand, the nucleotides
+    /// before and after the insertion, its number of nucleotides and the color of its strand
+    fn get_insertions(&self) -> Vec<(Nucl, Nucl, usize, u32)>;
     fn get_position_of_nucl_on_helix(
         &self,
         nucl: Nucl,
@@ -904,4 +1485,50 @@ pub trait DesignReader: 'static + ensnano_interactor::DesignReader {
     fn prime5_of_which_strand(&self, nucl: Nucl) -> Option<usize>;
     fn prime3_of_which_strand(&self, nucl: Nucl) -> Option<usize>;
     fn get_all_prime3_nucl(&self) -> Vec<(Vec3, Vec3, u32)>;
+    /// Return the identifier of the scaffold domain that contains `nucl` or its complement
+    fn get_scaffold_domain_id(&self, nucl: &Nucl) -> Option<usize>;
+    /// Return, for each nucleotide on a helix, the identifier of its strand and the index of its
+    /// domain in this strand
+    fn get_domains_of_nucls(&self) -> HashMap<Nucl, (usize, usize)>;
+    /// Return the nucleotides of the scaffold that are on a helix, from its 5' end to its 3' end
+    fn get_scaffold_nucls(&self) -> Vec<Nucl>;
+    /// Return the pairs of nucleotides that are linked by a cross-over
+    fn get_xovers(&self) -> Vec<(Nucl, Nucl)>;
+    /// Return, for each nucleotide of the staples, the melting temperature of its domain
+    fn get_melting_temperatures(&self) -> HashMap<Nucl, f32>;
+    fn get_dna_parameters(&self) -> Parameters;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ensnano_design::Helix;
+
+    #[test]
+    fn twist_of_straight_helix_is_ideal() {
+        let parameters = Parameters::DEFAULT;
+        let helix = Helix::new(Vec3::zero(), Rotor3::identity());
+        let frame = |n: isize| {
+            let axis = helix.axis_position(&parameters, n);
+            (axis, helix.space_pos(&parameters, n, true) - axis)
+        };
+        let ideal_twist = 360. / parameters.bases_per_turn;
+        for n in -5..5 {
+            let twist = twist_angle(frame(n), frame(n + 1));
+            assert!(
+                (twist - ideal_twist).abs() < 1e-2,
+                "twist {} at {}, expected {}",
+                twist,
+                n,
+                ideal_twist
+            );
+        }
+    }
+
+    #[test]
+    fn twist_colors() {
+        assert_eq!(twist_color(0.), [1., 1., 1.]);
+        assert_eq!(twist_color(MAX_TWIST_DEVIATION), [1., 0., 0.]);
+        assert_eq!(twist_color(-2. * MAX_TWIST_DEVIATION), [0., 0., 1.]);
+    }
 }
@@ -0,0 +1,157 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! Coloring functions that show the result of an analysis of the design.
+//!
+//! The portions of helices that are connected to the rest of the structure by few cross-overs are
+//! mechanically weak. These functions color them in red, and the well connected ones in blue.
+
+use super::*;
+use ensnano_interactor::graphics::AnalysisColoring;
+
+/// The number of helix turns on each side of a nucleotide in which cross-overs are counted
+const DENSITY_WINDOW_TURNS: f32 = 2.;
+/// The number of helix turns above which a portion of helix without cross-over is considered
+/// too long
+const LONG_UNCROSSED_TURNS: f32 = 4.;
+
+/// Return the coloring function that shows the result of `coloring`, or `None` if no analysis
+/// must be shown
+pub fn analysis_coloring_function<R: DesignReader>(
+    coloring: AnalysisColoring,
+) -> Option<Box<dyn ColoringFunction<R>>> {
+    match coloring {
+        AnalysisColoring::None => None,
+        AnalysisColoring::CrossoverDensity => Some(Box::new(CrossoverDensity)),
+        AnalysisColoring::UncrossedLength => Some(Box::new(UncrossedLength)),
+        AnalysisColoring::MeltingTemperature => Some(Box::new(MeltingTemperature)),
+    }
+}
+
+/// Colors the nucleotides according to the number of cross-overs near them on their helix,
+/// relatively to the highest number of cross-overs near a nucleotide of the design
+struct CrossoverDensity;
+
+impl<R: DesignReader> ColoringFunction<R> for CrossoverDensity {
+    fn nucl_colors(&self, design: &R) -> HashMap<Nucl, [f32; 3]> {
+        let xover_positions = xover_positions_by_helix(design);
+        let window =
+            (DENSITY_WINDOW_TURNS * design.get_dna_parameters().bases_per_turn).round() as isize;
+        let densities: Vec<(Nucl, usize)> = nucls_on_helices(design)
+            .into_iter()
+            .map(|nucl| {
+                let density = xover_positions
+                    .get(&nucl.helix)
+                    .map(|positions| {
+                        positions
+                            .iter()
+                            .filter(|p| (*p - nucl.position).abs() <= window)
+                            .count()
+                    })
+                    .unwrap_or(0);
+                (nucl, density)
+            })
+            .collect();
+        let max_density = densities.iter().map(|(_, d)| *d).max().unwrap_or(0).max(1);
+        densities
+            .into_iter()
+            .map(|(nucl, d)| {
+                let density = d as f32 / max_density as f32;
+                (nucl, diverging_color(1. - 2. * density))
+            })
+            .collect()
+    }
+}
+
+/// Colors the nucleotides according to the length of the portion of their helix that contains
+/// them and that is delimited by two cross-overs or by the ends of the helix
+struct UncrossedLength;
+
+impl<R: DesignReader> ColoringFunction<R> for UncrossedLength {
+    fn nucl_colors(&self, design: &R) -> HashMap<Nucl, [f32; 3]> {
+        let xover_positions = xover_positions_by_helix(design);
+        let long_length = LONG_UNCROSSED_TURNS * design.get_dna_parameters().bases_per_turn;
+        let nucls = nucls_on_helices(design);
+        let mut helix_ranges: HashMap<usize, (isize, isize)> = HashMap::new();
+        for nucl in nucls.iter() {
+            let range = helix_ranges
+                .entry(nucl.helix)
+                .or_insert((nucl.position, nucl.position));
+            range.0 = range.0.min(nucl.position);
+            range.1 = range.1.max(nucl.position);
+        }
+        nucls
+            .into_iter()
+            .filter_map(|nucl| {
+                let (helix_min, helix_max) = helix_ranges.get(&nucl.helix)?;
+                let positions = xover_positions
+                    .get(&nucl.helix)
+                    .map(|v| v.as_slice())
+                    .unwrap_or(&[]);
+                let start = positions
+                    .iter()
+                    .filter(|p| **p <= nucl.position)
+                    .max()
+                    .unwrap_or(helix_min);
+                let end = positions
+                    .iter()
+                    .filter(|p| **p > nucl.position)
+                    .min()
+                    .unwrap_or(helix_max);
+                let length = (end - start + 1) as f32 / long_length;
+                Some((nucl, diverging_color(2. * length.min(1.) - 1.)))
+            })
+            .collect()
+    }
+}
+
+/// Colors the nucleotides of the staples according to the melting temperature of their domain
+struct MeltingTemperature;
+
+impl<R: DesignReader> ColoringFunction<R> for MeltingTemperature {
+    fn nucl_colors(&self, design: &R) -> HashMap<Nucl, [f32; 3]> {
+        design
+            .get_melting_temperatures()
+            .into_iter()
+            .map(|(nucl, tm)| {
+                let color = crate::analysis::thermo::temperature_color(tm);
+                (nucl, color_components(color))
+            })
+            .collect()
+    }
+}
+
+/// Return the nucleotides of the design that are on a helix
+fn nucls_on_helices<R: DesignReader>(design: &R) -> Vec<Nucl> {
+    design
+        .get_all_nucl_ids()
+        .into_iter()
+        .filter_map(|id| design.get_nucl_with_id(id))
+        .collect()
+}
+
+/// Return, for each helix, the positions of the nucleotides of the helix that are the end of a
+/// cross-over
+fn xover_positions_by_helix<R: DesignReader>(design: &R) -> HashMap<usize, Vec<isize>> {
+    let mut ret: HashMap<usize, Vec<isize>> = HashMap::new();
+    for (n1, n2) in design.get_xovers() {
+        ret.entry(n1.helix).or_default().push(n1.position);
+        ret.entry(n2.helix).or_default().push(n2.position);
+    }
+    ret
+}
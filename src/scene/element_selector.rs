@@ -15,17 +15,28 @@ ENSnano, a 3d graphical application for DNA nanostructures.
     You should have received a copy of the GNU General Public License
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
+//! The element selector maps pixels of the 3D view to the elements drawn on them.
+//!
+//! The elements are drawn on "fake" textures, in which the color of each pixel encodes the
+//! identifier of an element. These textures are copied to the CPU asynchronously: the copy is
+//! submitted when the textures are outdated, and the pixels are collected on a later frame, once
+//! the copy is completed. In the meantime, the previous pixels are used.
+
+use std::future::Future;
+use std::pin::Pin;
 use std::rc::Rc;
 
 use super::{Device, DrawArea, DrawType, Queue, ViewPtr};
 use crate::utils;
 use ensnano_interactor::{phantom_helix_decoder, PhantomElement};
-use futures::executor;
+use futures::FutureExt;
 use iced_wgpu::wgpu;
 use iced_winit::winit::dpi::{PhysicalPosition, PhysicalSize};
 use std::convert::TryInto;
 use utils::BufferDimensions;
 
+type MapFuture = Pin<Box<dyn Future<Output = Result<(), wgpu::BufferAsyncError>>>>;
+
 pub struct ElementSelector {
     device: Rc<Device>,
     queue: Rc<Queue>,
@@ -33,6 +44,17 @@ pub struct ElementSelector {
     window_size: PhysicalSize<u32>,
     view: ViewPtr,
     area: DrawArea,
+    /// The copy of the fake textures to the CPU that is being performed, if any
+    readback: Option<Readback>,
+}
+
+/// A copy of the fake textures into buffers that are being mapped
+struct Readback {
+    /// The buffers in which the textures are copied, in the same order as the readers
+    buffers: Vec<wgpu::Buffer>,
+    /// The futures that complete when the buffers are mapped, or `None` once they are completed
+    map_futures: Vec<Option<MapFuture>>,
+    buffer_dimensions: BufferDimensions,
 }
 
 impl ElementSelector {
@@ -56,26 +78,101 @@ impl ElementSelector {
             readers,
             view,
             area,
+            readback: None,
         }
     }
 
     pub fn resize(&mut self, window_size: PhysicalSize<u32>, area: DrawArea) {
         self.area = area;
         self.window_size = window_size;
+        // The pixels that were read, or are being read, do not have the new dimensions
+        self.readback = None;
+        for reader in self.readers.iter_mut() {
+            reader.pixels = None;
+        }
     }
 
+    /// Return the element drawn on `clicked_pixel`.
+    ///
+    /// This never waits for the GPU. If the fake textures are outdated, their copy is requested
+    /// and the element is read on the previous pixels.
     pub fn set_selected_id(
         &mut self,
         clicked_pixel: PhysicalPosition<f64>,
     ) -> Option<SceneElement> {
-        if self.readers[0].pixels.is_none() || self.view.borrow().need_redraw_fake() {
-            for i in 0..self.readers.len() {
-                let pixels = self.update_fake_pixels(self.readers[i].draw_type);
-                self.readers[i].pixels = Some(pixels)
-            }
+        self.request_update();
+        self.get_highest_priority_element(clicked_pixel)
+    }
+
+    /// Return true if the pixels match the current content of the 3D view
+    pub fn is_up_to_date(&self) -> bool {
+        self.readback.is_none()
+            && self.readers.iter().all(|r| r.pixels.is_some())
+            && !self.view.borrow().need_redraw_fake()
+    }
+
+    /// Submit the copy of the fake textures if they are outdated and no copy is being performed
+    pub fn request_update(&mut self) {
+        if self.readback.is_some() || self.is_up_to_date() {
+            return;
         }
+        log::debug!("update fake pixels");
+        let buffer_dimensions = BufferDimensions::new(
+            self.window_size.width as usize,
+            self.window_size.height as usize,
+        );
+        let buffers: Vec<wgpu::Buffer> = self
+            .readers
+            .iter()
+            .map(|r| self.submit_fake_texture_copy(r.draw_type, &buffer_dimensions))
+            .collect();
+        let map_futures = buffers
+            .iter()
+            .map(|buffer| {
+                let future: MapFuture = Box::pin(buffer.slice(..).map_async(wgpu::MapMode::Read));
+                Some(future)
+            })
+            .collect();
+        self.readback = Some(Readback {
+            buffers,
+            map_futures,
+            buffer_dimensions,
+        });
+    }
 
-        self.get_highest_priority_element(clicked_pixel)
+    /// Collect the pixels of the fake textures if their copy is completed. Return true if new
+    /// pixels were collected.
+    pub fn poll(&mut self) -> bool {
+        let readback = if let Some(readback) = self.readback.as_mut() {
+            readback
+        } else {
+            return false;
+        };
+        self.device.poll(wgpu::Maintain::Poll);
+        let mut failed = false;
+        for slot in readback.map_futures.iter_mut() {
+            if let Some(result) = slot.as_mut().and_then(|f| f.as_mut().now_or_never()) {
+                *slot = None;
+                if let Err(err) = result {
+                    log::error!("Could not read fake texture: {:?}", err);
+                    failed = true;
+                }
+            }
+        }
+        if failed {
+            // The copy will be requested again when the pixels are needed
+            self.readback = None;
+            return false;
+        }
+        if readback.map_futures.iter().any(Option::is_some) {
+            return false;
+        }
+        if let Some(readback) = self.readback.take() {
+            for (reader, buffer) in self.readers.iter_mut().zip(readback.buffers.iter()) {
+                reader.pixels = Some(read_mapped_buffer(buffer, &readback.buffer_dimensions));
+            }
+        }
+        true
     }
 
     fn get_highest_priority_element(
@@ -106,8 +203,13 @@ impl ElementSelector {
         None
     }
 
-    fn update_fake_pixels(&self, draw_type: DrawType) -> Vec<u8> {
-        log::debug!("update fake pixels");
+    /// Draw the fake texture of `draw_type` and submit its copy to a new buffer. The buffer is
+    /// returned unmapped.
+    fn submit_fake_texture_copy(
+        &self,
+        draw_type: DrawType,
+        buffer_dimensions: &BufferDimensions,
+    ) -> wgpu::Buffer {
         let size = wgpu::Extent3d {
             width: self.window_size.width,
             height: self.window_size.height,
@@ -125,13 +227,6 @@ impl ElementSelector {
             .draw(&mut encoder, &texture_view, draw_type, self.area);
 
         // create a buffer and fill it with the texture
-        let extent = wgpu::Extent3d {
-            width: size.width,
-            height: size.height,
-            depth_or_array_layers: 1,
-        };
-        let buffer_dimensions =
-            BufferDimensions::new(extent.width as usize, extent.height as usize);
         let buf_size = buffer_dimensions.padded_bytes_per_row * buffer_dimensions.height;
         let staging_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
             size: buf_size as u64,
@@ -157,30 +252,9 @@ impl ElementSelector {
             aspect: Default::default(),
         };
 
-        encoder.copy_texture_to_buffer(texture_copy_view, buffer_copy_view, extent);
+        encoder.copy_texture_to_buffer(texture_copy_view, buffer_copy_view, size);
         self.queue.submit(Some(encoder.finish()));
-
-        let buffer_slice = staging_buffer.slice(..);
-        let buffer_future = buffer_slice.map_async(wgpu::MapMode::Read);
-        self.device.poll(wgpu::Maintain::Wait);
-
-        let pixels = async {
-            if let Ok(()) = buffer_future.await {
-                let pixels_slice = buffer_slice.get_mapped_range();
-                let mut pixels = Vec::with_capacity((size.height * size.width) as usize);
-                for chunck in pixels_slice.chunks(buffer_dimensions.padded_bytes_per_row) {
-                    for byte in chunck[..buffer_dimensions.unpadded_bytes_per_row].iter() {
-                        pixels.push(*byte);
-                    }
-                }
-                drop(pixels_slice);
-                staging_buffer.unmap();
-                pixels
-            } else {
-                panic!("could not read fake texture");
-            }
-        };
-        executor::block_on(pixels)
+        staging_buffer
     }
 
     fn create_fake_scene_texture(
@@ -259,7 +333,7 @@ impl SceneReader {
     }
 
     fn read_pixel(&self, byte0: usize) -> Option<SceneElement> {
-        let pixels = self.pixels.as_ref().unwrap();
+        let pixels = self.pixels.as_ref()?;
         let a = *pixels.get(byte0 + 3)? as u32;
         let r = (*pixels.get(byte0 + 2)? as u32) << 16;
         let g = (*pixels.get(byte0 + 1)? as u32) << 8;
@@ -287,3 +361,18 @@ impl SceneReader {
         }
     }
 }
+
+/// Read the content of a mapped buffer holding a copy of a texture, without the padding at the
+/// end of the rows, and unmap it.
+fn read_mapped_buffer(buffer: &wgpu::Buffer, buffer_dimensions: &BufferDimensions) -> Vec<u8> {
+    let buffer_slice = buffer.slice(..);
+    let pixels_slice = buffer_slice.get_mapped_range();
+    let mut pixels =
+        Vec::with_capacity(buffer_dimensions.unpadded_bytes_per_row * buffer_dimensions.height);
+    for chunck in pixels_slice.chunks(buffer_dimensions.padded_bytes_per_row) {
+        pixels.extend_from_slice(&chunck[..buffer_dimensions.unpadded_bytes_per_row]);
+    }
+    drop(pixels_slice);
+    buffer.unmap();
+    pixels
+}
@@ -106,6 +106,37 @@ fn ndc_to_world(x_ndc: f32, y_ndc: f32, camera: CameraPtr, projection: Projectio
     p2
 }
 
+/// Project a point of the world on the screen. This is the inverse of `ndc_to_world`.
+///
+/// Return `None` if the point is behind the camera.
+pub fn world_to_ndc(
+    point: Vec3,
+    camera: CameraPtr,
+    projection: ProjectionPtr,
+) -> Option<(f32, f32)> {
+    let camera = camera.borrow();
+    let to_point = point - camera.position;
+    let depth = to_point.dot(camera.direction());
+    if depth <= 0. {
+        return None;
+    }
+    let correction = (projection.borrow().get_fovy() / 2.).tan() * depth;
+    let x_screen =
+        to_point.dot(camera.right_vec()) / (correction * projection.borrow().get_ratio());
+    let y_screen = to_point.dot(camera.up_vec()) / correction;
+    Some(((x_screen + 1.) / 2., (1. - y_screen) / 2.))
+}
+
+/// Return the angle, in degrees, between two vectors, or `None` if one of them is null.
+pub fn angle_between(u: Vec3, v: Vec3) -> Option<f32> {
+    let norms = u.mag() * v.mag();
+    if norms < 1e-6 {
+        None
+    } else {
+        Some((u.dot(v) / norms).max(-1.).min(1.).acos().to_degrees())
+    }
+}
+
 pub fn cast_ray(
     x_ndc: f32,
     y_ndc: f32,
@@ -211,3 +242,143 @@ impl UnalignedBoundaries {
         Some(middle + x_back.max(10.) * self.basis.unit_z)
     }
 }
+
+/// A cone that contains everything that a camera can see, as long as the camera does not move by
+/// more than `margin` and does not rotate by more than `margin_angle`.
+///
+/// It is used to skip the objects that cannot be visible without having to recompute the set of
+/// visible objects each time the camera moves.
+#[derive(Debug, Clone, Copy)]
+pub struct CullingCone {
+    apex: Vec3,
+    axis: Vec3,
+    half_angle: f32,
+    camera_position: Vec3,
+    /// The angle between the direction of the camera and the corners of the screen
+    view_half_angle: f32,
+    margin: f32,
+    margin_angle: f32,
+}
+
+impl CullingCone {
+    /// Return the cone for a camera at `position` looking in `direction`, or `None` if the cone
+    /// would be too wide for culling to be possible. `margin_angle` is in radiants.
+    pub fn new(
+        position: Vec3,
+        direction: Vec3,
+        fovy: f32,
+        ratio: f32,
+        margin: f32,
+        margin_angle: f32,
+    ) -> Option<Self> {
+        let view_half_angle = Self::view_half_angle(fovy, ratio);
+        let half_angle = view_half_angle + margin_angle;
+        if half_angle >= std::f32::consts::FRAC_PI_2 {
+            return None;
+        }
+        let axis = direction.normalized();
+        // Moving the apex back ensures that all the rays starting at most `margin` away from
+        // `position` are inside the cone
+        let apex = position - margin / half_angle.sin() * axis;
+        Some(Self {
+            apex,
+            axis,
+            half_angle,
+            camera_position: position,
+            view_half_angle,
+            margin,
+            margin_angle,
+        })
+    }
+
+    fn view_half_angle(fovy: f32, ratio: f32) -> f32 {
+        ((fovy / 2.).tan() * (1. + ratio * ratio).sqrt()).atan()
+    }
+
+    /// Return true if the ball of center `center` and radius `radius` intersects the cone.
+    pub fn contains_ball(&self, center: Vec3, radius: f32) -> bool {
+        let to_center = center - self.apex;
+        let dist = to_center.mag();
+        if dist <= radius {
+            return true;
+        }
+        let angle = (to_center.dot(self.axis) / dist).max(-1.).min(1.).acos();
+        angle <= self.half_angle + (radius / dist).asin()
+    }
+
+    /// Return true if everything that can be seen by a camera at `position` looking in `direction`
+    /// is inside the cone.
+    pub fn contains_view(&self, position: Vec3, direction: Vec3, fovy: f32, ratio: f32) -> bool {
+        let rotation = angle_between(direction, self.axis)
+            .unwrap_or(0.)
+            .to_radians();
+        (position - self.camera_position).mag() <= self.margin
+            && rotation <= self.margin_angle
+            && Self::view_half_angle(fovy, ratio) <= self.view_half_angle
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::camera::{Camera, Projection};
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use ultraviolet::Rotor3;
+
+    #[test]
+    fn projection_of_unprojected_point() {
+        let camera = Rc::new(RefCell::new(Camera::new(
+            (1., 2., 3.),
+            Rotor3::from_rotation_xz(0.3) * Rotor3::from_rotation_yz(-0.2),
+        )));
+        let projection = Rc::new(RefCell::new(Projection::new(800, 600, 0.8, 0.1, 1000.)));
+        let point = ndc_to_world(0.2, 0.7, camera.clone(), projection.clone());
+        let (x, y) = world_to_ndc(point, camera.clone(), projection.clone()).unwrap();
+        assert!((x - 0.2).abs() < 1e-4);
+        assert!((y - 0.7).abs() < 1e-4);
+
+        let behind = camera.borrow().position - camera.borrow().direction();
+        assert!(world_to_ndc(behind, camera, projection).is_none());
+    }
+
+    #[test]
+    fn angles_between_vectors() {
+        let angle = angle_between(Vec3::unit_x(), Vec3::new(1., 1., 0.)).unwrap();
+        assert!((angle - 45.).abs() < 1e-4);
+        let angle = angle_between(Vec3::unit_y(), -2. * Vec3::unit_y()).unwrap();
+        assert!((angle - 180.).abs() < 1e-4);
+        assert!(angle_between(Vec3::zero(), Vec3::unit_z()).is_none());
+    }
+
+    #[test]
+    fn culling_cone_contains_moved_views() {
+        let fovy = 70f32.to_radians();
+        let cone = CullingCone::new(Vec3::zero(), -Vec3::unit_z(), fovy, 1.5, 10., 0.25).unwrap();
+        assert!(cone.contains_ball(Vec3::new(0., 0., -50.), 0.));
+        assert!(!cone.contains_ball(Vec3::new(0., 0., 50.), 1.));
+        assert!(!cone.contains_ball(Vec3::new(200., 0., -10.), 1.));
+        assert!(cone.contains_ball(Vec3::new(200., 0., -10.), 200.));
+
+        // A point seen in a corner of the screen of a camera that moved within the margins
+        let position = Vec3::new(8., -5., 3.) / 10f32.sqrt();
+        let direction = Rotor3::from_rotation_xz(0.2) * -Vec3::unit_z();
+        assert!(cone.contains_view(position, direction, fovy, 1.5));
+        let corner = Vec3::new(
+            (fovy / 2.).tan() * 1.5 * 0.99,
+            (fovy / 2.).tan() * 0.99,
+            -1.,
+        );
+        let corner = Rotor3::from_rotation_xz(0.2) * corner;
+        assert!(cone.contains_ball(position + 100. * corner, 0.));
+
+        assert!(!cone.contains_view(Vec3::new(11., 0., 0.), -Vec3::unit_z(), fovy, 1.5));
+        assert!(!cone.contains_view(Vec3::zero(), Vec3::unit_x(), fovy, 1.5));
+        assert!(!cone.contains_view(Vec3::zero(), -Vec3::unit_z(), fovy, 2.));
+    }
+
+    #[test]
+    fn no_culling_cone_for_wide_views() {
+        assert!(CullingCone::new(Vec3::zero(), Vec3::unit_x(), 2.5, 1., 1., 0.25).is_none());
+    }
+}
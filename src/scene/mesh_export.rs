@@ -0,0 +1,201 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! Conversion of the spheres and tubes of the 3D view into a single triangle mesh that can be
+//! written in the OBJ format.
+
+use super::view::{DnaVertex, Instanciable, RawDnaInstance, SphereInstance, TubeInstance};
+use std::io::Write;
+use std::path::Path;
+use ultraviolet::{Mat4, Vec3};
+
+/// A triangle mesh made of copies of the meshes used to draw the nucleotides and the bounds
+#[derive(Default)]
+pub struct TriangleMesh {
+    positions: Vec<Vec3>,
+    colors: Vec<Vec3>,
+    triangles: Vec<[u32; 3]>,
+}
+
+/// The vertices and triangles of one of the shapes instanciated in the mesh
+struct Shape {
+    vertices: Vec<Vec3>,
+    triangles: Vec<[u32; 3]>,
+}
+
+impl Shape {
+    fn from_list(vertices: Vec<DnaVertex>, indices: Vec<u16>) -> Self {
+        Self {
+            vertices: vertices.iter().map(DnaVertex::position).collect(),
+            triangles: indices
+                .chunks_exact(3)
+                .map(|t| [t[0] as u32, t[1] as u32, t[2] as u32])
+                .collect(),
+        }
+    }
+
+    fn from_strip(vertices: Vec<DnaVertex>, indices: Vec<u16>) -> Self {
+        Self {
+            vertices: vertices.iter().map(DnaVertex::position).collect(),
+            triangles: strip_to_triangles(&indices),
+        }
+    }
+}
+
+impl TriangleMesh {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Add a copy of the sphere mesh for each instance of `spheres`. The instances are
+    /// positioned by `model_matrix`.
+    pub fn add_spheres(&mut self, spheres: &[RawDnaInstance], model_matrix: Mat4) {
+        let shape = Shape::from_list(SphereInstance::vertices(), SphereInstance::indices());
+        for instance in spheres.iter() {
+            self.add_instance(&shape, instance, model_matrix)
+        }
+    }
+
+    /// Add a copy of the tube mesh for each instance of `tubes`. The instances are positioned by
+    /// `model_matrix`.
+    pub fn add_tubes(&mut self, tubes: &[RawDnaInstance], model_matrix: Mat4) {
+        let shape = Shape::from_strip(TubeInstance::vertices(), TubeInstance::indices());
+        for instance in tubes.iter() {
+            self.add_instance(&shape, instance, model_matrix)
+        }
+    }
+
+    fn add_instance(&mut self, shape: &Shape, instance: &RawDnaInstance, model_matrix: Mat4) {
+        let offset = self.positions.len() as u32;
+        let matrix = model_matrix * instance.model;
+        let color = Vec3::new(instance.color.x, instance.color.y, instance.color.z);
+        for vertex in shape.vertices.iter() {
+            let scaled = Vec3::new(
+                vertex.x * instance.scale.x,
+                vertex.y * instance.scale.y,
+                vertex.z * instance.scale.z,
+            );
+            self.positions.push(matrix.transform_point3(scaled));
+            self.colors.push(color);
+        }
+        self.triangles.extend(
+            shape
+                .triangles
+                .iter()
+                .map(|[a, b, c]| [a + offset, b + offset, c + offset]),
+        );
+    }
+
+    pub fn nb_triangles(&self) -> usize {
+        self.triangles.len()
+    }
+
+    /// Write the mesh at `path` in the OBJ format. The colors of the vertices are written after
+    /// their coordinates, which is understood by Blender and MeshLab.
+    pub fn write_obj(&self, path: &Path) -> std::io::Result<()> {
+        let mut out = std::io::BufWriter::new(std::fs::File::create(path)?);
+        self.write_obj_to(&mut out)
+    }
+
+    fn write_obj_to<W: Write>(&self, out: &mut W) -> std::io::Result<()> {
+        writeln!(out, "# Exported from ENSnano")?;
+        for (position, color) in self.positions.iter().zip(self.colors.iter()) {
+            writeln!(
+                out,
+                "v {} {} {} {} {} {}",
+                position.x, position.y, position.z, color.x, color.y, color.z
+            )?;
+        }
+        for [a, b, c] in self.triangles.iter() {
+            // Indices start at 1 in the OBJ format
+            writeln!(out, "f {} {} {}", a + 1, b + 1, c + 1)?;
+        }
+        out.flush()
+    }
+}
+
+/// Convert the indices of a triangle strip into a list of triangles with a consistent
+/// orientation. Degenerated triangles are skipped.
+fn strip_to_triangles(indices: &[u16]) -> Vec<[u32; 3]> {
+    indices
+        .windows(3)
+        .enumerate()
+        .filter_map(|(i, w)| {
+            let (a, b, c) = (w[0] as u32, w[1] as u32, w[2] as u32);
+            if a == b || b == c || a == c {
+                None
+            } else if i % 2 == 0 {
+                Some([a, b, c])
+            } else {
+                Some([b, a, c])
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consts::NB_RAY_TUBE;
+    use ultraviolet::{Rotor3, Vec4};
+
+    #[test]
+    fn strip_orientation_alternates() {
+        let triangles = strip_to_triangles(&[0, 1, 2, 3, 0, 1]);
+        assert_eq!(triangles, vec![[0, 1, 2], [2, 1, 3], [2, 3, 0], [0, 3, 1]]);
+    }
+
+    #[test]
+    fn obj_has_one_line_per_vertex_and_triangle() {
+        let sphere = SphereInstance {
+            position: Vec3::new(1., 2., 3.),
+            color: Vec4::one(),
+            id: 0,
+            radius: 1.,
+        }
+        .to_raw_instance();
+        let tube = TubeInstance {
+            position: Vec3::zero(),
+            rotor: Rotor3::identity(),
+            color: Vec4::one(),
+            id: 1,
+            radius: 1.,
+            length: 2.,
+        }
+        .to_raw_instance();
+        let mut mesh = TriangleMesh::new();
+        mesh.add_spheres(&[sphere, sphere], Mat4::identity());
+        mesh.add_tubes(&[tube], Mat4::identity());
+        let mut obj = Vec::new();
+        mesh.write_obj_to(&mut obj).unwrap();
+        let obj = String::from_utf8(obj).unwrap();
+        let nb_vertices = 2 * SphereInstance::vertices().len() + TubeInstance::vertices().len();
+        assert_eq!(
+            obj.lines().filter(|l| l.starts_with("v ")).count(),
+            nb_vertices
+        );
+        assert_eq!(
+            obj.lines().filter(|l| l.starts_with("f ")).count(),
+            mesh.nb_triangles()
+        );
+        // Every vertex of the tubes is used by the triangles made from the strip
+        assert_eq!(
+            strip_to_triangles(&TubeInstance::indices()).len(),
+            2 * NB_RAY_TUBE
+        );
+    }
+}
@@ -0,0 +1,193 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! Audio feedback on the state of the scene.
+//!
+//! A continuous tone is played on the default output device. Its pitch and its volume are
+//! recomputed at most every `UPDATE_PERIOD`. The samples are produced on the audio thread of
+//! `cpal`, which only reads the target pitch and volume from atomic variables, so that the
+//! render loop never waits for the audio device.
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use ensnano_interactor::graphics::SonificationMode;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const UPDATE_PERIOD: Duration = Duration::from_millis(100);
+/// The time, in seconds, that the tone takes to reach its target pitch and volume
+const SMOOTHING_TIME: f32 = 0.05;
+
+/// The pitch and the volume that the tone must reach. The values are stored as the bits of
+/// `f32`.
+#[derive(Default)]
+struct ToneTarget {
+    frequency: AtomicU32,
+    amplitude: AtomicU32,
+}
+
+impl ToneTarget {
+    fn set(&self, frequency: f32, amplitude: f32) {
+        self.frequency.store(frequency.to_bits(), Ordering::Relaxed);
+        self.amplitude.store(amplitude.to_bits(), Ordering::Relaxed);
+    }
+
+    fn get(&self) -> (f32, f32) {
+        (
+            f32::from_bits(self.frequency.load(Ordering::Relaxed)),
+            f32::from_bits(self.amplitude.load(Ordering::Relaxed)),
+        )
+    }
+}
+
+pub struct Sonification {
+    mode: SonificationMode,
+    target: Arc<ToneTarget>,
+    /// The output stream, created the first time that the sonification is enabled
+    stream: Option<cpal::Stream>,
+    last_update: Option<Instant>,
+}
+
+impl Sonification {
+    pub fn new() -> Self {
+        Self {
+            mode: Default::default(),
+            target: Default::default(),
+            stream: None,
+            last_update: None,
+        }
+    }
+
+    pub fn set_mode(&mut self, mode: SonificationMode) {
+        self.mode = mode;
+        // Force the next update so that the new volume is applied immediately
+        self.last_update = None;
+        if mode.enabled {
+            if let Some(stream) = self.stream.as_ref() {
+                if let Err(e) = stream.play() {
+                    log::error!("Could not resume audio output: {}", e);
+                }
+            } else {
+                match start_stream(self.target.clone()) {
+                    Ok(stream) => self.stream = Some(stream),
+                    Err(e) => log::error!("Could not start audio output: {}", e),
+                }
+            }
+        } else {
+            let (frequency, _) = self.target.get();
+            self.target.set(frequency, 0.);
+            if let Some(stream) = self.stream.as_ref() {
+                if let Err(e) = stream.pause() {
+                    log::error!("Could not pause audio output: {}", e);
+                }
+            }
+        }
+    }
+
+    /// Return true if the tone is playing and was last updated more than `UPDATE_PERIOD` ago
+    pub fn needs_update(&self) -> bool {
+        self.mode.enabled
+            && self.stream.is_some()
+            && self
+                .last_update
+                .map(|t| t.elapsed() >= UPDATE_PERIOD)
+                .unwrap_or(true)
+    }
+
+    /// Set the pitch and the volume of the tone according to the number of nucleotides of the
+    /// design and to the kinetic energy of the running simulation.
+    pub fn update(&mut self, nb_nucleotides: usize, kinetic_energy: Option<f32>) {
+        let map = &self.mode.map;
+        let amplitude = if self.mode.enabled {
+            self.mode.volume * map.volume(kinetic_energy)
+        } else {
+            0.
+        };
+        self.target.set(map.frequency(nb_nucleotides), amplitude);
+        self.last_update = Some(Instant::now());
+    }
+}
+
+fn start_stream(target: Arc<ToneTarget>) -> Result<cpal::Stream, String> {
+    let device = cpal::default_host()
+        .default_output_device()
+        .ok_or_else(|| String::from("no output device available"))?;
+    let config = device.default_output_config().map_err(|e| e.to_string())?;
+    let sample_format = config.sample_format();
+    let config: cpal::StreamConfig = config.into();
+    let stream = match sample_format {
+        cpal::SampleFormat::F32 => build_stream::<f32>(&device, &config, target),
+        cpal::SampleFormat::I16 => build_stream::<i16>(&device, &config, target),
+        cpal::SampleFormat::U16 => build_stream::<u16>(&device, &config, target),
+    }
+    .map_err(|e| e.to_string())?;
+    stream.play().map_err(|e| e.to_string())?;
+    Ok(stream)
+}
+
+fn build_stream<T: cpal::Sample>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    target: Arc<ToneTarget>,
+) -> Result<cpal::Stream, cpal::BuildStreamError> {
+    let channels = config.channels as usize;
+    let mut oscillator = Oscillator::new(config.sample_rate.0 as f32);
+    device.build_output_stream(
+        config,
+        move |data: &mut [T], _: &cpal::OutputCallbackInfo| {
+            let (frequency, amplitude) = target.get();
+            for frame in data.chunks_mut(channels) {
+                let value: T = cpal::Sample::from(&oscillator.next_sample(frequency, amplitude));
+                for sample in frame.iter_mut() {
+                    *sample = value;
+                }
+            }
+        },
+        |e| log::error!("Audio output error: {}", e),
+    )
+}
+
+/// A sine oscillator whose frequency and amplitude smoothly follow their targets, so that
+/// the updates of the tone do not produce clicks.
+struct Oscillator {
+    sample_rate: f32,
+    /// The phase of the oscillator, as a fraction of a period
+    phase: f32,
+    frequency: f32,
+    amplitude: f32,
+    /// The fraction of the distance to the targets that is covered at each sample
+    smoothing: f32,
+}
+
+impl Oscillator {
+    fn new(sample_rate: f32) -> Self {
+        Self {
+            sample_rate,
+            phase: 0.,
+            frequency: 0.,
+            amplitude: 0.,
+            smoothing: 1. - (-1. / (SMOOTHING_TIME * sample_rate)).exp(),
+        }
+    }
+
+    fn next_sample(&mut self, target_frequency: f32, target_amplitude: f32) -> f32 {
+        self.frequency += self.smoothing * (target_frequency - self.frequency);
+        self.amplitude += self.smoothing * (target_amplitude - self.amplitude);
+        self.phase = (self.phase + self.frequency / self.sample_rate).fract();
+        self.amplitude * (2. * std::f32::consts::PI * self.phase).sin()
+    }
+}
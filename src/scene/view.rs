@@ -22,10 +22,11 @@ use super::camera;
 use crate::consts::*;
 use crate::utils::{bindgroup_manager, texture};
 use crate::{DrawArea, PhySize};
-use camera::{Camera, CameraPtr, Projection, ProjectionPtr};
+use camera::{Camera, CameraPtr, Projection, ProjectionPtr, FOVY_DEGREES};
 use ensnano_design::group_attributes::GroupPivot;
 use ensnano_design::Axis;
 use iced_wgpu::wgpu;
+use iced_winit::winit::dpi::PhysicalPosition;
 use std::cell::RefCell;
 use std::rc::Rc;
 use texture::Texture;
@@ -36,39 +37,65 @@ use wgpu::{Device, Queue};
 mod uniforms;
 pub use uniforms::FogParameters;
 use uniforms::Uniforms;
+/// A post-processing pass that darkens the creases and cavities of the scene
+mod ambient_occlusion;
+/// A post-processing pass that blurs the objects far from a focal plane
+mod depth_of_field;
 mod direction_cube;
 mod dna_obj;
 /// This modules defines a trait for drawing widget made of several meshes.
 mod drawable;
+mod eye_dome_lighting;
 mod grid;
 mod grid_disc;
 /// A HandleDrawer draws the widget for translating objects
 mod handle_drawer;
 mod instances_drawer;
 mod letter;
+/// Level of detail for the spheres and tubes that are far from the camera
+mod level_of_detail;
+mod mirror_plane;
 /// A RotationWidget draws the widget for rotating objects
 mod rotation_widget;
+/// The rectangle in which the user selects nucleotides
+mod selection_rectangle;
+/// A post-processing pass that maps the colors of the scene to the range of the screen
+mod tone_mapping;
 
 use super::maths_3d;
-use crate::text::Letter;
+use crate::text::GlyphAtlas;
+use ambient_occlusion::AmbientOcclusion;
 use bindgroup_manager::{DynamicBindGroup, UniformBindGroup};
+use depth_of_field::DepthOfField;
 use direction_cube::*;
-pub use dna_obj::{ConeInstance, DnaObject, RawDnaInstance, SphereInstance, TubeInstance};
+pub use dna_obj::{
+    ConeInstance, DnaObject, DnaVertex, RawDnaInstance, SphereInstance, TubeInstance,
+};
+use dna_obj::{LowPolySphereInstance, LowPolyTubeInstance};
 use drawable::{Drawable, Drawer, Vertex};
+use eye_dome_lighting::EyeDomeLighting;
 pub use grid::{GridInstance, GridIntersection};
 use grid::{GridManager, GridTextures};
 pub use grid_disc::GridDisc;
 use handle_drawer::HandlesDrawer;
 pub use handle_drawer::{HandleColors, HandleDir, HandleOrientation, HandlesDescriptor};
 pub use instances_drawer::Instanciable;
-use instances_drawer::{InstanceDrawer, RawDrawer};
+use instances_drawer::{BlendMode, IndirectCommandBuffer, InstanceDrawer, RawDrawer};
+use letter::GlyphInstance;
 pub use letter::LetterInstance;
+use level_of_detail::LodDrawer;
 use maths_3d::unproject_point_on_line;
+use mirror_plane::MirrorPlane;
 use rotation_widget::RotationWidget;
 pub use rotation_widget::{RotationMode, RotationWidgetDescriptor, RotationWidgetOrientation};
+use selection_rectangle::SelectionRectangle;
+use tone_mapping::ToneMapping;
 //use plane_drawer::PlaneDrawer;
 //pub use plane_drawer::Plane;
 
+/// The format of the texture in which the scene is drawn before being tone mapped
+const HDR_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
 static MODEL_BG_ENTRY: &'static [wgpu::BindGroupLayoutEntry] = &[wgpu::BindGroupLayoutEntry {
     binding: 0,
     visibility: wgpu::ShaderStages::from_bits_truncate(wgpu::ShaderStages::VERTEX.bits()),
@@ -80,7 +107,11 @@ static MODEL_BG_ENTRY: &'static [wgpu::BindGroupLayoutEntry] = &[wgpu::BindGroup
     count: None,
 }];
 
-use ensnano_interactor::graphics::{Background3D, RenderingMode};
+use ensnano_interactor::graphics::{
+    AmbientOcclusionParameters, Background3D, DepthOfFieldParameters, EyeDomeLightingParameters,
+    LightingParameters, PostProcessingQuality, RenderingMode, ToneMappingParameters,
+};
+use ensnano_interactor::MirrorAxis;
 
 /// An object that handles the communication with the GPU to draw the scene.
 pub struct View {
@@ -92,6 +123,9 @@ pub struct View {
     /// The fake depth texture is updated every time the size of the drawing area is modified and
     /// has a sample count of 1
     fake_depth_texture: Texture,
+    /// The depth texture used to draw the direction cube when the depth of the scene must be
+    /// preserved for a post-processing pass
+    cube_depth_texture: Texture,
     /// The handle drawers draw handles to translate the elements
     handle_drawers: HandlesDrawer,
     /// The rotation widget draw the widget to rotate the elements
@@ -99,10 +133,15 @@ pub struct View {
     /// A possible update of the size of the drawing area, must be taken into account before
     /// drawing the next frame
     new_size: Option<PhySize>,
-    /// The pipilines that draw the basis symbols
-    letter_drawer: Vec<InstanceDrawer<LetterInstance>>,
-    helix_letter_drawer: Vec<InstanceDrawer<LetterInstance>>,
+    /// The texture in which the characters of `ANNOTATION_SYMBOLS` are rasterized
+    glyph_atlas: Rc<GlyphAtlas>,
+    /// The pipiline that draws the basis symbols
+    letter_drawer: InstanceDrawer<GlyphInstance>,
+    helix_letter_drawer: InstanceDrawer<GlyphInstance>,
+    /// The pipeline that draws the labels attached to nucleotides
+    annotation_drawer: InstanceDrawer<GlyphInstance>,
     device: Rc<Device>,
+    queue: Rc<Queue>,
     /// A bind group associated to the uniform buffer containing the view and projection matrices.
     //TODO this is currently only passed to the widgets, it could be passed to the mesh pipeline as
     //well.
@@ -110,17 +149,35 @@ pub struct View {
     models: DynamicBindGroup,
     redraw_twice: bool,
     need_redraw: bool,
+    /// True if the fake scene used for picking elements must be drawn again because instances,
+    /// widgets or the camera were modified since it was last drawn. The pixels of the fake scene
+    /// are cached otherwise.
     need_redraw_fake: bool,
     draw_letter: bool,
     msaa_texture: Option<wgpu::TextureView>,
     grid_manager: GridManager,
     disc_drawer: InstanceDrawer<GridDisc>,
+    /// Draws the plane across which a grid is about to be mirrored
+    mirror_plane_drawer: InstanceDrawer<MirrorPlane>,
     dna_drawers: DnaDrawers,
+    /// The arguments of the draw calls of the dna drawers
+    indirect_commands: IndirectCommandBuffer,
     direction_cube: InstanceDrawer<DirectionCube>,
     skybox_cube: InstanceDrawer<SkyBox>,
     fog_parameters: FogParameters,
+    lighting_parameters: LightingParameters,
     rendering_mode: RenderingMode,
     background3d: Background3D,
+    /// The color of the plain background, encoded as `0xRRGGBB`
+    background_color: u32,
+    depth_of_field: DepthOfField,
+    eye_dome_lighting: EyeDomeLighting,
+    ambient_occlusion: AmbientOcclusion,
+    tone_mapping: ToneMapping,
+    selection_rectangle: SelectionRectangle,
+    /// True while the scene is rendered in an exported image. The widgets and the direction cube
+    /// are not drawn in that case.
+    exporting: bool,
 }
 
 impl View {
@@ -138,7 +195,7 @@ impl View {
         let projection = Rc::new(RefCell::new(Projection::new(
             area_size.width,
             area_size.height,
-            70f32.to_radians(),
+            FOVY_DEGREES.to_radians(),
             0.1,
             1000.0,
         )));
@@ -151,49 +208,48 @@ impl View {
             entries: MODEL_BG_ENTRY,
             label: None,
         };
-        log::info!("Create letter drawer");
-        let letter_drawer = BASIS_SYMBOLS
-            .iter()
-            .map(|c| {
-                let letter = Letter::new(*c, device.clone(), queue.clone());
-                InstanceDrawer::new(
-                    device.clone(),
-                    queue.clone(),
-                    &viewer.get_layout_desc(),
-                    &model_bg_desc,
-                    letter,
-                    false,
-                    "letters",
-                )
-            })
-            .collect();
-        log::info!("Create helix letter drawer");
-        let helix_letter_drawer = ['0', '1', '2', '3', '4', '5', '6', '7', '8', '9']
-            .iter()
-            .map(|c| {
-                let letter = Letter::new(*c, device.clone(), queue.clone());
-                InstanceDrawer::new(
-                    device.clone(),
-                    queue.clone(),
-                    &viewer.get_layout_desc(),
-                    &model_bg_desc,
-                    letter,
-                    false,
-                    "helix letters",
-                )
-            })
-            .collect();
+        log::info!("Create glyph atlas");
+        let glyph_atlas = Rc::new(GlyphAtlas::new(
+            ANNOTATION_SYMBOLS
+                .chars()
+                .chain(BASIS_SYMBOLS.iter().cloned()),
+            device.as_ref(),
+            queue.as_ref(),
+        ));
+        let new_letter_drawer = |label: &str| {
+            InstanceDrawer::new(
+                device.clone(),
+                queue.clone(),
+                &viewer.get_layout_desc(),
+                &model_bg_desc,
+                glyph_atlas.clone(),
+                false,
+                label,
+            )
+        };
+        let letter_drawer = new_letter_drawer("letters");
+        let helix_letter_drawer = new_letter_drawer("helix letters");
+        let annotation_drawer = new_letter_drawer("annotations");
 
         let depth_texture =
             texture::Texture::create_depth_texture(device.as_ref(), &area_size, SAMPLE_COUNT);
         let fake_depth_texture =
             texture::Texture::create_depth_texture(device.as_ref(), &window_size, 1);
+        let cube_depth_texture =
+            texture::Texture::create_depth_texture(device.as_ref(), &area_size, SAMPLE_COUNT);
+        let depth_of_field =
+            DepthOfField::new(device.clone(), queue.clone(), &area_size, &depth_texture);
+        let eye_dome_lighting = EyeDomeLighting::new(device.clone(), queue.clone(), &depth_texture);
+        let ambient_occlusion =
+            AmbientOcclusion::new(device.clone(), queue.clone(), &depth_texture);
+        let tone_mapping = ToneMapping::new(device.clone(), queue.clone(), &area_size);
+        let selection_rectangle = SelectionRectangle::new(device.clone(), queue.clone());
         let msaa_texture = if SAMPLE_COUNT > 1 {
             Some(crate::utils::texture::Texture::create_msaa_texture(
                 device.clone().as_ref(),
                 &area_size,
                 SAMPLE_COUNT,
-                wgpu::TextureFormat::Bgra8UnormSrgb,
+                HDR_FORMAT,
             ))
         } else {
             None
@@ -234,6 +290,15 @@ impl View {
             false,
             "disc drawer",
         );
+        let mirror_plane_drawer = InstanceDrawer::new(
+            device.clone(),
+            queue.clone(),
+            &viewer.get_layout_desc(),
+            &model_bg_desc,
+            (),
+            false,
+            "mirror plane drawer",
+        );
 
         log::info!("Create dna drawer");
         let dna_drawers = DnaDrawers::new(
@@ -268,19 +333,25 @@ impl View {
         );
         skybox_cube.new_instances(vec![SkyBox::new(500.)]);
 
+        let indirect_commands = IndirectCommandBuffer::new(device.clone(), queue.clone());
+
         Self {
             camera,
             projection,
             depth_texture,
             fake_depth_texture,
+            cube_depth_texture,
             new_size: None,
             device: device.clone(),
+            queue,
             viewer,
             models,
             handle_drawers: HandlesDrawer::new(device.clone()),
             rotation_widget: RotationWidget::new(device),
+            glyph_atlas,
             letter_drawer,
             helix_letter_drawer,
+            annotation_drawer,
             redraw_twice: false,
             need_redraw: true,
             need_redraw_fake: true,
@@ -288,12 +359,22 @@ impl View {
             msaa_texture,
             grid_manager,
             disc_drawer,
+            mirror_plane_drawer,
             dna_drawers,
+            indirect_commands,
             direction_cube,
             skybox_cube,
             fog_parameters: FogParameters::new(),
+            lighting_parameters: Default::default(),
             rendering_mode: Default::default(),
             background3d: Default::default(),
+            background_color: 0xFF_FF_FF,
+            depth_of_field,
+            eye_dome_lighting,
+            ambient_occlusion,
+            tone_mapping,
+            selection_rectangle,
+            exporting: false,
         }
     }
 
@@ -301,16 +382,19 @@ impl View {
     /// it needs to be redrawn or not.
     pub fn update(&mut self, view_update: ViewUpdate) {
         self.need_redraw = true;
+        if view_update.modifies_picking() {
+            self.need_redraw_fake = true;
+        }
         match view_update {
             ViewUpdate::Size(size) => {
                 self.new_size = Some(size);
-                self.need_redraw_fake = true;
             }
             ViewUpdate::Camera => {
                 self.viewer.update(&Uniforms::from_view_proj_fog(
                     self.camera.clone(),
                     self.projection.clone(),
                     &self.fog_parameters,
+                    &self.lighting_parameters,
                 ));
                 self.handle_drawers
                     .update_camera(self.camera.clone(), self.projection.clone());
@@ -326,6 +410,7 @@ impl View {
                     self.camera.clone(),
                     self.projection.clone(),
                     &self.fog_parameters,
+                    &self.lighting_parameters,
                 ));
             }
             ViewUpdate::Handles(descr) => {
@@ -345,17 +430,14 @@ impl View {
             }
             ViewUpdate::ModelMatrices(ref matrices) => {
                 self.models.update(matrices.clone().as_slice());
+                self.dna_drawers.update_models(matrices.as_slice());
             }
-            ViewUpdate::Letter(letter) => {
-                for (i, instance) in letter.into_iter().enumerate() {
-                    self.letter_drawer[i].new_instances(instance);
-                }
-            }
-            ViewUpdate::GridLetter(letter) => {
-                for (i, instance) in letter.into_iter().enumerate() {
-                    self.helix_letter_drawer[i].new_instances(instance);
-                }
-            }
+            ViewUpdate::Letter(letters) => self
+                .letter_drawer
+                .new_instances(GlyphInstance::from_letters(letters, &self.glyph_atlas)),
+            ViewUpdate::GridLetter(letters) => self
+                .helix_letter_drawer
+                .new_instances(GlyphInstance::from_letters(letters, &self.glyph_atlas)),
             ViewUpdate::Grids(grid) => self.grid_manager.new_instances(grid),
             ViewUpdate::GridDiscs(instances) => self.disc_drawer.new_instances(instances),
             ViewUpdate::RawDna(mesh, instances) => {
@@ -379,17 +461,64 @@ impl View {
                         .new_instances_raw(instances.as_ref());
                 }
             }
+            ViewUpdate::HighlightPath { spheres, tubes } => {
+                self.dna_drawers
+                    .get_mut(Mesh::PathSphere)
+                    .new_instances_raw(spheres.as_ref());
+                self.dna_drawers
+                    .get_mut(Mesh::PathTube)
+                    .new_instances_raw(tubes.as_ref());
+            }
+            ViewUpdate::SelectionRectangle(corners) => {
+                self.selection_rectangle.update_corners(corners)
+            }
+            ViewUpdate::AnchorInstances(instances) => {
+                self.dna_drawers
+                    .get_mut(Mesh::AnchorSphere)
+                    .new_instances_raw(instances.as_ref());
+            }
+            ViewUpdate::Annotations(annotations) => self.update_annotations(annotations),
             ViewUpdate::FogCenter(center) => {
                 self.fog_parameters.alt_fog_center = center;
                 self.viewer.update(&Uniforms::from_view_proj_fog(
                     self.camera.clone(),
                     self.projection.clone(),
                     &self.fog_parameters,
+                    &self.lighting_parameters,
                 ));
             }
         }
     }
 
+    /// Lay out the characters of the labels so that they are written from left to right, above
+    /// the nucleotide they are attached to
+    fn update_annotations(&mut self, annotations: Vec<(Vec3, String)>) {
+        // Characters that cannot be drawn are replaced by a blank space
+        let blank_advance = self.glyph_atlas.glyph('A').map(|g| g.advance).unwrap_or(0.);
+        let mut letters = Vec::new();
+        for (position, label) in annotations.iter() {
+            let mut x = 0.;
+            for c in label.chars() {
+                if let Some(glyph) = self.glyph_atlas.glyph(c) {
+                    letters.push(LetterInstance {
+                        position: *position,
+                        color: ultraviolet::Vec4::new(0., 0., 0., 1.),
+                        design_id: 0,
+                        scale: ANNOTATION_SCALE,
+                        shift: Vec3::new(x, -1.5, 0.),
+                        fade: false,
+                        character: c,
+                    });
+                    x += glyph.advance;
+                } else {
+                    x += blank_advance;
+                }
+            }
+        }
+        self.annotation_drawer
+            .new_instances(GlyphInstance::from_letters(letters, &self.glyph_atlas));
+    }
+
     pub fn need_redraw_fake(&self) -> bool {
         self.need_redraw_fake
     }
@@ -411,24 +540,38 @@ impl View {
             self.depth_texture =
                 Texture::create_depth_texture(self.device.as_ref(), &area.size, SAMPLE_COUNT);
             self.fake_depth_texture = Texture::create_depth_texture(self.device.as_ref(), &size, 1);
+            self.cube_depth_texture =
+                Texture::create_depth_texture(self.device.as_ref(), &area.size, SAMPLE_COUNT);
+            self.depth_of_field.resize(&area.size, &self.depth_texture);
+            self.eye_dome_lighting.resize(&self.depth_texture);
+            self.ambient_occlusion.resize(&self.depth_texture);
+            self.tone_mapping.resize(&area.size);
             self.msaa_texture = if SAMPLE_COUNT > 1 {
                 Some(crate::utils::texture::Texture::create_msaa_texture(
                     self.device.clone().as_ref(),
                     &area.size,
                     SAMPLE_COUNT,
-                    wgpu::TextureFormat::Bgra8UnormSrgb,
+                    HDR_FORMAT,
                 ))
             } else {
                 None
             };
         }
-        let clear_color = if fake_color || self.background3d == Background3D::White {
+        let clear_color = if fake_color {
             wgpu::Color {
                 r: 1.,
                 g: 1.,
                 b: 1.,
                 a: 1.,
             }
+        } else if self.background3d == Background3D::White {
+            // The scene is rendered in linear space while the theme colors are sRGB colors
+            wgpu::Color {
+                r: srgb_to_linear((self.background_color >> 16) & 0xFF),
+                g: srgb_to_linear((self.background_color >> 8) & 0xFF),
+                b: srgb_to_linear(self.background_color & 0xFF),
+                a: 1.,
+            }
         } else {
             wgpu::Color {
                 r: 0.,
@@ -441,18 +584,31 @@ impl View {
         let viewer_bind_group = viewer.get_bindgroup();
         let viewer_bind_group_layout = viewer.get_layout();
 
+        // The depth of field is never applied to the fake color pass
+        let use_dof = draw_type == DrawType::Scene && self.depth_of_field.is_enabled();
+        let use_edl = draw_type == DrawType::Scene && self.eye_dome_lighting.is_enabled();
+        let use_ssao = draw_type == DrawType::Scene && self.ambient_occlusion.is_enabled();
+        // The scene is drawn in a floating point texture that is tone mapped in the target
+        let scene_target = if use_dof {
+            self.depth_of_field.source_view()
+        } else if !fake_color {
+            self.tone_mapping.source_view()
+        } else {
+            target
+        };
+
         let attachment = if !fake_color {
             if let Some(ref msaa) = self.msaa_texture {
                 msaa
             } else {
-                target
+                scene_target
             }
         } else {
             target
         };
 
         let resolve_target = if !fake_color && self.msaa_texture.is_some() {
-            Some(target)
+            Some(scene_target)
         } else {
             None
         };
@@ -463,6 +619,20 @@ impl View {
             &self.fake_depth_texture
         };
 
+        if draw_type == DrawType::Scene {
+            self.dna_drawers
+                .select_lod(encoder, self.camera.borrow().position);
+        }
+        let dna_drawers = match draw_type {
+            DrawType::Design => self.dna_drawers.fakes(),
+            DrawType::Scene => self.dna_drawers.reals(self.rendering_mode),
+            DrawType::Phantom => self.dna_drawers.phantoms(),
+            // Draw design elements and phantoms, to fill the depth buffer
+            DrawType::Grid => self.dna_drawers.fakes_and_phantoms(),
+            _ => vec![],
+        };
+        self.indirect_commands.write_commands(&dna_drawers);
+
         {
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: None,
@@ -503,56 +673,37 @@ impl View {
                 );
             }
 
-            if draw_type == DrawType::Design {
-                for drawer in self.dna_drawers.fakes() {
-                    drawer.draw(
-                        &mut render_pass,
-                        self.viewer.get_bindgroup(),
-                        self.models.get_bindgroup(),
-                    )
-                }
-            } else if draw_type == DrawType::Scene {
-                if self.background3d == Background3D::Sky {
-                    self.skybox_cube.draw(
-                        &mut render_pass,
-                        self.viewer.get_bindgroup(),
-                        self.models.get_bindgroup(),
-                    );
-                }
-                for drawer in self.dna_drawers.reals(self.rendering_mode) {
-                    drawer.draw(
-                        &mut render_pass,
-                        self.viewer.get_bindgroup(),
-                        self.models.get_bindgroup(),
-                    )
-                }
-            } else if draw_type == DrawType::Phantom {
-                for drawer in self.dna_drawers.phantoms() {
-                    drawer.draw(
-                        &mut render_pass,
-                        self.viewer.get_bindgroup(),
-                        self.models.get_bindgroup(),
-                    )
-                }
-            } else if draw_type == DrawType::Grid {
-                // Draw design elements and phantoms, to fill the depth buffer
-                for drawer in self.dna_drawers.fakes_and_phantoms() {
-                    drawer.draw(
-                        &mut render_pass,
-                        self.viewer.get_bindgroup(),
-                        self.models.get_bindgroup(),
-                    )
-                }
+            if draw_type == DrawType::Scene && self.background3d == Background3D::Sky {
+                self.skybox_cube.draw(
+                    &mut render_pass,
+                    self.viewer.get_bindgroup(),
+                    self.models.get_bindgroup(),
+                );
+            }
+            for (i, drawer) in dna_drawers.into_iter().enumerate() {
+                drawer.draw_indirect(
+                    &mut render_pass,
+                    self.viewer.get_bindgroup(),
+                    self.models.get_bindgroup(),
+                    self.indirect_commands.get_buffer(),
+                    IndirectCommandBuffer::offset(i),
+                )
             }
 
             if !fake_color && self.draw_letter {
-                for drawer in self.letter_drawer.iter_mut() {
-                    drawer.draw(
-                        &mut render_pass,
-                        viewer_bind_group,
-                        self.models.get_bindgroup(),
-                    )
-                }
+                self.letter_drawer.draw(
+                    &mut render_pass,
+                    viewer_bind_group,
+                    self.models.get_bindgroup(),
+                );
+            }
+
+            if !fake_color {
+                self.annotation_drawer.draw(
+                    &mut render_pass,
+                    viewer_bind_group,
+                    self.models.get_bindgroup(),
+                );
             }
 
             if !fake_color {
@@ -567,16 +718,19 @@ impl View {
                     viewer_bind_group,
                     self.models.get_bindgroup(),
                 );
-                for drawer in self.helix_letter_drawer.iter_mut() {
-                    drawer.draw(
-                        &mut render_pass,
-                        viewer_bind_group,
-                        self.models.get_bindgroup(),
-                    )
-                }
+                self.mirror_plane_drawer.draw(
+                    &mut render_pass,
+                    viewer_bind_group,
+                    self.models.get_bindgroup(),
+                );
+                self.helix_letter_drawer.draw(
+                    &mut render_pass,
+                    viewer_bind_group,
+                    self.models.get_bindgroup(),
+                );
             }
 
-            if draw_type.wants_widget() {
+            if draw_type.wants_widget() && !self.exporting {
                 self.handle_drawers.draw(
                     &mut render_pass,
                     viewer_bind_group,
@@ -599,49 +753,95 @@ impl View {
                 self.need_redraw = true;
             } else {
                 self.need_redraw = false;
-                self.need_redraw_fake = true;
             }
         }
         if !fake_color {
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: None,
-                color_attachments: &[wgpu::RenderPassColorAttachment {
-                    view: attachment,
-                    resolve_target,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Load,
-                        store: true,
-                    },
-                }],
-                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-                    view: &depth_attachement.view,
-                    depth_ops: Some(wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(1.),
-                        store: true,
-                    }),
-                    stencil_ops: Some(wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(0),
-                        store: true,
+            let cube_x = area.size.width as f32 / 20.;
+            let cube_y = 0.;
+            let cube_width = (area.size.width as f32 / 10. * 1.5)
+                .max(100.)
+                .min(area.size.width as f32);
+            let cube_height = (area.size.height as f32 / 10. * 1.5)
+                .max((100. * area.size.height as f32 / area.size.width as f32) as f32)
+                .min(area.size.height as f32);
+            // When post-processing passes are applied, the depth of the scene must be preserved
+            // for them
+            let cube_depth = if use_dof || use_edl || use_ssao {
+                &self.cube_depth_texture
+            } else {
+                depth_attachement
+            };
+            if !self.exporting {
+                let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: None,
+                    color_attachments: &[wgpu::RenderPassColorAttachment {
+                        view: attachment,
+                        resolve_target,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Load,
+                            store: true,
+                        },
+                    }],
+                    depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                        view: &cube_depth.view,
+                        depth_ops: Some(wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(1.),
+                            store: true,
+                        }),
+                        stencil_ops: Some(wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(0),
+                            store: true,
+                        }),
                     }),
-                }),
-            });
-            render_pass.set_viewport(
-                area.size.width as f32 / 20.,
-                0.,
-                (area.size.width as f32 / 10. * 1.5)
-                    .max(100.)
-                    .min(area.size.width as f32),
-                (area.size.height as f32 / 10. * 1.5)
-                    .max((100. * area.size.height as f32 / area.size.width as f32) as f32)
-                    .min(area.size.height as f32),
-                0.0,
-                1.0,
-            );
-            self.direction_cube.draw(
-                &mut render_pass,
-                viewer_bind_group,
-                self.models.get_bindgroup(),
-            )
+                });
+                render_pass.set_viewport(cube_x, cube_y, cube_width, cube_height, 0.0, 1.0);
+                self.direction_cube.draw(
+                    &mut render_pass,
+                    viewer_bind_group,
+                    self.models.get_bindgroup(),
+                )
+            }
+            // No area is left untouched by the post-processing when the cube is not drawn
+            let cube_rect = if self.exporting {
+                [-1.; 4]
+            } else {
+                [cube_x, cube_y, cube_x + cube_width, cube_y + cube_height]
+            };
+            if use_ssao {
+                let projection = self.projection.borrow();
+                self.ambient_occlusion.draw(
+                    encoder,
+                    scene_target,
+                    projection.get_znear(),
+                    projection.get_zfar(),
+                    projection.get_fovy(),
+                    cube_rect,
+                );
+            }
+            if use_edl {
+                let projection = self.projection.borrow();
+                self.eye_dome_lighting.draw(
+                    encoder,
+                    scene_target,
+                    projection.get_znear(),
+                    projection.get_zfar(),
+                    cube_rect,
+                );
+            }
+            if use_dof {
+                let projection = self.projection.borrow();
+                self.depth_of_field.draw(
+                    encoder,
+                    self.tone_mapping.source_view(),
+                    projection.get_znear(),
+                    projection.get_zfar(),
+                    cube_rect,
+                );
+            }
+            self.tone_mapping.draw(encoder, target);
+            if !self.exporting {
+                self.selection_rectangle.draw(encoder, target);
+            }
         } else if draw_type == DrawType::Grid {
             // render pass to draw the grids
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
@@ -690,6 +890,46 @@ impl View {
         }
     }
 
+    /// Draw the scene, as seen by the current camera, in an image of `size` pixels and return the
+    /// pixels of the image in RGBA format.
+    ///
+    /// The widgets and the direction cube are not drawn. `window_size` and `area_size` are the
+    /// current sizes of the window and of the drawing area, to which the view is restored after
+    /// the call.
+    pub fn render_to_rgba(
+        &mut self,
+        size: PhySize,
+        window_size: PhySize,
+        area_size: PhySize,
+    ) -> std::io::Result<Vec<u8>> {
+        self.exporting = true;
+        self.projection.borrow_mut().resize(size.width, size.height);
+        self.update(ViewUpdate::Camera);
+        self.update(ViewUpdate::Size(size));
+
+        let device = self.device.clone();
+        let queue = self.queue.clone();
+        let pixels = crate::utils::render_to_rgba(&device, &queue, size, |encoder, target| {
+            self.draw(
+                encoder,
+                target,
+                DrawType::Scene,
+                DrawArea {
+                    position: PhysicalPosition::new(0, 0),
+                    size,
+                },
+            )
+        });
+
+        self.exporting = false;
+        self.projection
+            .borrow_mut()
+            .resize(area_size.width, area_size.height);
+        self.update(ViewUpdate::Camera);
+        self.update(ViewUpdate::Size(window_size));
+        pixels
+    }
+
     pub fn get_current_pivot(&self) -> Option<GroupPivot> {
         self.handle_drawers
             .get_pivot_position()
@@ -706,12 +946,23 @@ impl View {
         self.projection.clone()
     }
 
+    /// Set the vertical field of view, in radians, of the camera
+    pub fn set_fovy(&mut self, fovy: f32) {
+        self.projection.borrow_mut().set_fovy(fovy);
+        self.update(ViewUpdate::Camera);
+    }
+
     pub fn set_draw_letter(&mut self, value: bool) {
         self.need_redraw = true;
         self.draw_letter = value;
     }
 
+    pub fn get_draw_letter(&self) -> bool {
+        self.draw_letter
+    }
+
     pub fn end_movement(&mut self) {
+        self.need_redraw_fake = true;
         self.handle_drawers.end_movement()
     }
 
@@ -747,6 +998,7 @@ impl View {
     /// Translate the widgets when the associated objects are translated.
     pub fn translate_widgets(&mut self, translation: Vec3) {
         self.need_redraw = true;
+        self.need_redraw_fake = true;
         self.handle_drawers.translate(translation);
         self.rotation_widget.translate(translation);
     }
@@ -827,6 +1079,55 @@ impl View {
         self.need_redraw = true;
     }
 
+    pub fn set_background_color(&mut self, color: u32) {
+        self.background_color = color;
+        self.need_redraw = true;
+    }
+
+    pub fn set_depth_of_field(&mut self, parameters: DepthOfFieldParameters) {
+        self.depth_of_field.set_parameters(parameters);
+        self.need_redraw = true;
+    }
+
+    pub fn set_eye_dome_lighting(&mut self, parameters: EyeDomeLightingParameters) {
+        self.eye_dome_lighting.set_parameters(parameters);
+        self.need_redraw = true;
+    }
+
+    pub fn set_ambient_occlusion(&mut self, parameters: AmbientOcclusionParameters) {
+        self.ambient_occlusion.set_parameters(parameters);
+        self.need_redraw = true;
+    }
+
+    pub fn set_post_processing_quality(&mut self, quality: PostProcessingQuality) {
+        self.depth_of_field.set_quality(quality);
+        self.ambient_occlusion.set_quality(quality);
+        self.need_redraw = true;
+    }
+
+    pub fn set_tone_mapping(&mut self, parameters: ToneMappingParameters) {
+        self.tone_mapping.set_parameters(parameters);
+        self.need_redraw = true;
+    }
+
+    /// Show the plane across which a grid will be mirrored, or hide it if `axis` is `None`
+    pub fn show_mirror_plane(&mut self, axis: Option<MirrorAxis>) {
+        let instances = axis.map(|axis| MirrorPlane { axis }).into_iter().collect();
+        self.mirror_plane_drawer.new_instances(instances);
+        self.need_redraw = true;
+    }
+
+    pub fn set_lighting(&mut self, parameters: LightingParameters) {
+        self.lighting_parameters = parameters;
+        self.viewer.update(&Uniforms::from_view_proj_fog(
+            self.camera.clone(),
+            self.projection.clone(),
+            &self.fog_parameters,
+            &self.lighting_parameters,
+        ));
+        self.need_redraw = true;
+    }
+
     pub fn get_group_pivot(&self) -> Option<GroupPivot> {
         self.handle_drawers
             .get_pivot_position()
@@ -846,13 +1147,49 @@ pub enum ViewUpdate {
     /// The set of phantom instances has been modified
     Handles(Option<HandlesDescriptor>),
     RotationWidget(Option<RotationWidgetDescriptor>),
-    Letter(Vec<Vec<LetterInstance>>),
-    GridLetter(Vec<Vec<LetterInstance>>),
+    Letter(Vec<LetterInstance>),
+    GridLetter(Vec<LetterInstance>),
     Grids(Rc<Vec<GridInstance>>),
     GridDiscs(Vec<GridDisc>),
     RawDna(Mesh, Rc<Vec<RawDnaInstance>>),
     Fog(FogParameters),
     FogCenter(Option<Vec3>),
+    /// The set of instances representing a highlighted path of nucleotides has been modified
+    HighlightPath {
+        spheres: Rc<Vec<RawDnaInstance>>,
+        tubes: Rc<Vec<RawDnaInstance>>,
+    },
+    /// The set of instances marking the anchored nucleotides has been modified
+    AnchorInstances(Rc<Vec<RawDnaInstance>>),
+    /// The labels attached to nucleotides, with the position at which they must be displayed
+    Annotations(Vec<(Vec3, String)>),
+    /// The opposite corners, as fractions of the size of the drawing area, of the rectangle in
+    /// which nucleotides are being selected, or `None` if no rectangle is being dragged
+    SelectionRectangle(Option<[(f32, f32); 2]>),
+}
+
+impl ViewUpdate {
+    /// Return true if the update modifies the fake scene used for picking elements
+    fn modifies_picking(&self) -> bool {
+        match self {
+            Self::Camera
+            | Self::Size(_)
+            | Self::ModelMatrices(_)
+            | Self::Handles(_)
+            | Self::RotationWidget(_)
+            | Self::Grids(_)
+            | Self::Fog(_)
+            | Self::FogCenter(_) => true,
+            Self::RawDna(mesh, _) => mesh.to_fake().is_some(),
+            Self::Letter(_)
+            | Self::GridLetter(_)
+            | Self::GridDiscs(_)
+            | Self::HighlightPath { .. }
+            | Self::AnchorInstances(_)
+            | Self::Annotations(_)
+            | Self::SelectionRectangle(_) => false,
+        }
+    }
 }
 
 #[derive(Eq, PartialEq, Debug, Copy, Clone, Hash)]
@@ -875,11 +1212,20 @@ pub enum Mesh {
     SuggestionTube,
     PastedSphere,
     PastedTube,
+    WorkspaceSphere,
+    WorkspaceTube,
     PivotSphere,
     XoverSphere,
+    PathSphere,
+    PathTube,
+    AnchorSphere,
     XoverTube,
     Prime3Cone,
     Prime3ConeOutline,
+    BuilderPreviewSphere,
+    BuilderPreviewTube,
+    MeasureSphere,
+    MeasureTube,
 }
 
 impl Mesh {
@@ -904,8 +1250,8 @@ impl Mesh {
 }
 
 struct DnaDrawers {
-    sphere: InstanceDrawer<SphereInstance>,
-    tube: InstanceDrawer<TubeInstance>,
+    sphere: LodDrawer<SphereInstance, LowPolySphereInstance>,
+    tube: LodDrawer<TubeInstance, LowPolyTubeInstance>,
     outline_sphere: InstanceDrawer<SphereInstance>,
     outline_tube: InstanceDrawer<TubeInstance>,
     candidate_sphere: InstanceDrawer<SphereInstance>,
@@ -922,11 +1268,20 @@ struct DnaDrawers {
     suggestion_tube: InstanceDrawer<TubeInstance>,
     pasted_sphere: InstanceDrawer<SphereInstance>,
     pasted_tube: InstanceDrawer<TubeInstance>,
+    workspace_sphere: InstanceDrawer<SphereInstance>,
+    workspace_tube: InstanceDrawer<TubeInstance>,
     pivot_sphere: InstanceDrawer<SphereInstance>,
+    path_sphere: InstanceDrawer<SphereInstance>,
+    path_tube: InstanceDrawer<TubeInstance>,
+    anchor_sphere: InstanceDrawer<SphereInstance>,
     xover_sphere: InstanceDrawer<SphereInstance>,
     xover_tube: InstanceDrawer<TubeInstance>,
     prime3_cones: InstanceDrawer<dna_obj::ConeInstance>,
     outline_prime3_cones: InstanceDrawer<dna_obj::ConeInstance>,
+    builder_preview_sphere: InstanceDrawer<SphereInstance>,
+    builder_preview_tube: InstanceDrawer<TubeInstance>,
+    measure_sphere: InstanceDrawer<SphereInstance>,
+    measure_tube: InstanceDrawer<TubeInstance>,
 }
 
 impl DnaDrawers {
@@ -950,11 +1305,20 @@ impl DnaDrawers {
             Mesh::SuggestionSphere => &mut self.suggestion_sphere,
             Mesh::PastedSphere => &mut self.pasted_sphere,
             Mesh::PastedTube => &mut self.pasted_tube,
+            Mesh::WorkspaceSphere => &mut self.workspace_sphere,
+            Mesh::WorkspaceTube => &mut self.workspace_tube,
             Mesh::PivotSphere => &mut self.pivot_sphere,
+            Mesh::PathSphere => &mut self.path_sphere,
+            Mesh::PathTube => &mut self.path_tube,
+            Mesh::AnchorSphere => &mut self.anchor_sphere,
             Mesh::XoverSphere => &mut self.xover_sphere,
             Mesh::XoverTube => &mut self.xover_tube,
             Mesh::Prime3Cone => &mut self.prime3_cones,
             Mesh::Prime3ConeOutline => &mut self.outline_prime3_cones,
+            Mesh::BuilderPreviewSphere => &mut self.builder_preview_sphere,
+            Mesh::BuilderPreviewTube => &mut self.builder_preview_tube,
+            Mesh::MeasureSphere => &mut self.measure_sphere,
+            Mesh::MeasureTube => &mut self.measure_tube,
         }
     }
 
@@ -976,9 +1340,18 @@ impl DnaDrawers {
             &mut self.suggestion_tube,
             &mut self.pasted_tube,
             &mut self.pasted_sphere,
+            &mut self.workspace_tube,
+            &mut self.workspace_sphere,
             &mut self.pivot_sphere,
+            &mut self.path_sphere,
+            &mut self.path_tube,
+            &mut self.anchor_sphere,
             &mut self.xover_sphere,
             &mut self.xover_tube,
+            &mut self.builder_preview_sphere,
+            &mut self.builder_preview_tube,
+            &mut self.measure_sphere,
+            &mut self.measure_tube,
         ];
         if rendering_mode == RenderingMode::Cartoon {
             ret.insert(3, &mut self.outline_tube);
@@ -989,6 +1362,18 @@ impl DnaDrawers {
         ret
     }
 
+    /// Choose the meshes used to draw the spheres and tubes of the design according to their
+    /// distance to the camera
+    pub fn select_lod(&self, encoder: &mut wgpu::CommandEncoder, camera_position: Vec3) {
+        self.sphere.select(encoder, camera_position);
+        self.tube.select(encoder, camera_position);
+    }
+
+    pub fn update_models(&mut self, matrices: &[Mat4]) {
+        self.sphere.update_models(matrices);
+        self.tube.update_models(matrices);
+    }
+
     pub fn fakes(&mut self) -> Vec<&mut dyn RawDrawer<RawInstance = RawDnaInstance>> {
         vec![&mut self.fake_sphere, &mut self.fake_tube]
     }
@@ -1012,23 +1397,19 @@ impl DnaDrawers {
         viewer_desc: &wgpu::BindGroupLayoutDescriptor<'static>,
         model_desc: &wgpu::BindGroupLayoutDescriptor<'static>,
     ) -> Self {
-        Self {
-            sphere: InstanceDrawer::new(
+        let mut ret = Self {
+            sphere: LodDrawer::new(
                 device.clone(),
                 queue.clone(),
                 viewer_desc,
                 model_desc,
-                (),
-                false,
                 "sphere",
             ),
-            tube: InstanceDrawer::new(
+            tube: LodDrawer::new(
                 device.clone(),
                 queue.clone(),
                 viewer_desc,
                 model_desc,
-                (),
-                false,
                 "tube",
             ),
             prime3_cones: InstanceDrawer::new(
@@ -1136,6 +1517,24 @@ impl DnaDrawers {
                 false,
                 "pasted tube",
             ),
+            workspace_sphere: InstanceDrawer::new(
+                device.clone(),
+                queue.clone(),
+                viewer_desc,
+                model_desc,
+                (),
+                false,
+                "workspace sphere",
+            ),
+            workspace_tube: InstanceDrawer::new(
+                device.clone(),
+                queue.clone(),
+                viewer_desc,
+                model_desc,
+                (),
+                false,
+                "workspace tube",
+            ),
             selected_sphere: InstanceDrawer::new(
                 device.clone(),
                 queue.clone(),
@@ -1163,6 +1562,33 @@ impl DnaDrawers {
                 false,
                 "pivot sphere",
             ),
+            path_sphere: InstanceDrawer::new(
+                device.clone(),
+                queue.clone(),
+                viewer_desc,
+                model_desc,
+                (),
+                false,
+                "path sphere",
+            ),
+            path_tube: InstanceDrawer::new(
+                device.clone(),
+                queue.clone(),
+                viewer_desc,
+                model_desc,
+                (),
+                false,
+                "path tube",
+            ),
+            anchor_sphere: InstanceDrawer::new(
+                device.clone(),
+                queue.clone(),
+                viewer_desc,
+                model_desc,
+                (),
+                false,
+                "anchor sphere",
+            ),
             phantom_sphere: InstanceDrawer::new_wireframe(
                 device.clone(),
                 queue.clone(),
@@ -1181,6 +1607,42 @@ impl DnaDrawers {
                 false,
                 "phantom tube",
             ),
+            builder_preview_sphere: InstanceDrawer::new_wireframe(
+                device.clone(),
+                queue.clone(),
+                viewer_desc,
+                model_desc,
+                (),
+                false,
+                "builder preview sphere",
+            ),
+            builder_preview_tube: InstanceDrawer::new_wireframe(
+                device.clone(),
+                queue.clone(),
+                viewer_desc,
+                model_desc,
+                (),
+                false,
+                "builder preview tube",
+            ),
+            measure_sphere: InstanceDrawer::new(
+                device.clone(),
+                queue.clone(),
+                viewer_desc,
+                model_desc,
+                (),
+                false,
+                "measure sphere",
+            ),
+            measure_tube: InstanceDrawer::new(
+                device.clone(),
+                queue.clone(),
+                viewer_desc,
+                model_desc,
+                (),
+                false,
+                "measure tube",
+            ),
             fake_sphere: InstanceDrawer::new(
                 device.clone(),
                 queue.clone(),
@@ -1217,7 +1679,18 @@ impl DnaDrawers {
                 true,
                 "fake phantom tube",
             ),
-        }
+        };
+        // Phantom helices and the preview of the strand being built are drawn as glowing ghosts so
+        // that they remain visible behind the design, while suggested crossovers are faded
+        // according to their transparency.
+        ret.phantom_sphere.set_blend_mode(BlendMode::Additive);
+        ret.phantom_tube.set_blend_mode(BlendMode::Additive);
+        ret.builder_preview_sphere
+            .set_blend_mode(BlendMode::Additive);
+        ret.builder_preview_tube.set_blend_mode(BlendMode::Additive);
+        ret.suggestion_sphere.set_blend_mode(BlendMode::Alpha);
+        ret.suggestion_tube.set_blend_mode(BlendMode::Alpha);
+        ret
     }
 }
 
@@ -1245,3 +1718,13 @@ impl DrawType {
         }
     }
 }
+
+/// Convert a component of an sRGB color, between 0 and 255, to a linear intensity between 0 and 1
+fn srgb_to_linear(component: u32) -> f64 {
+    let component = component as f64 / 255.;
+    if component <= 0.04045 {
+        component / 12.92
+    } else {
+        ((component + 0.055) / 1.055).powf(2.4)
+    }
+}
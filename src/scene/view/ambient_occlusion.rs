@@ -0,0 +1,246 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! A post-processing pass that darkens the creases and the cavities of the scene.
+//!
+//! The occlusion is estimated from the depth buffer only: around each pixel, pairs of opposite
+//! samples are read and the pixel is darkened when both samples of a pair are closer to the
+//! camera than the pixel itself.
+
+use super::texture::Texture;
+use super::HDR_FORMAT;
+use crate::consts::SAMPLE_COUNT;
+use bytemuck::Zeroable;
+use ensnano_interactor::graphics::{AmbientOcclusionParameters, PostProcessingQuality};
+use iced_wgpu::wgpu;
+use std::rc::Rc;
+use wgpu::util::DeviceExt;
+use wgpu::{Device, Queue};
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct SsaoUniforms {
+    strength: f32,
+    radius: f32,
+    znear: f32,
+    zfar: f32,
+    /// (x_min, y_min, x_max, y_max) of the area in which the direction cube is drawn
+    cube_rect: [f32; 4],
+    /// The tangent of half the vertical field of view of the camera
+    tan_half_fovy: f32,
+    nb_pairs: u32,
+    _padding: [u32; 2],
+}
+
+/// The number of pairs of samples read around each pixel
+fn nb_pairs(quality: PostProcessingQuality) -> u32 {
+    match quality {
+        PostProcessingQuality::Low => 4,
+        PostProcessingQuality::Medium => 8,
+        PostProcessingQuality::High => 16,
+    }
+}
+
+pub struct AmbientOcclusion {
+    device: Rc<Device>,
+    queue: Rc<Queue>,
+    parameters: AmbientOcclusionParameters,
+    quality: PostProcessingQuality,
+    uniforms: wgpu::Buffer,
+    bg_layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl AmbientOcclusion {
+    pub fn new(device: Rc<Device>, queue: Rc<Queue>, scene_depth: &Texture) -> Self {
+        let uniforms = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("ambient occlusion uniforms"),
+            contents: bytemuck::cast_slice(&[SsaoUniforms::zeroed()]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let bg_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: SAMPLE_COUNT > 1,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Depth,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+            label: Some("ambient occlusion layout"),
+        });
+        let bind_group =
+            Self::create_bind_group(device.as_ref(), &bg_layout, scene_depth, &uniforms);
+
+        let shader = device.create_shader_module(&wgpu::include_wgsl!("ambient_occlusion.wgsl"));
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("ambient occlusion pipeline layout"),
+            bind_group_layouts: &[&bg_layout],
+            push_constant_ranges: &[],
+        });
+        // Like for the eye-dome lighting, the output of the fragment shader is multiplied with the
+        // colors of the scene
+        let multiply = wgpu::BlendComponent {
+            src_factor: wgpu::BlendFactor::Zero,
+            dst_factor: wgpu::BlendFactor::Src,
+            operation: wgpu::BlendOperation::Add,
+        };
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("ambient occlusion"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[wgpu::ColorTargetState {
+                    format: HDR_FORMAT,
+                    blend: Some(wgpu::BlendState {
+                        color: multiply,
+                        alpha: multiply,
+                    }),
+                    write_mask: wgpu::ColorWrites::ALL,
+                }],
+            }),
+            primitive: Default::default(),
+            depth_stencil: None,
+            multisample: Default::default(),
+        });
+
+        Self {
+            device,
+            queue,
+            parameters: Default::default(),
+            quality: Default::default(),
+            uniforms,
+            bg_layout,
+            bind_group,
+            pipeline,
+        }
+    }
+
+    fn create_bind_group(
+        device: &Device,
+        layout: &wgpu::BindGroupLayout,
+        scene_depth: &Texture,
+        uniforms: &wgpu::Buffer,
+    ) -> wgpu::BindGroup {
+        let depth_view = scene_depth
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor {
+                label: Some("ambient occlusion depth view"),
+                aspect: wgpu::TextureAspect::DepthOnly,
+                ..Default::default()
+            });
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&depth_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: uniforms.as_entire_binding(),
+                },
+            ],
+            label: Some("ambient occlusion bind group"),
+        })
+    }
+
+    /// Must be called when the depth texture of the scene changes.
+    pub fn resize(&mut self, scene_depth: &Texture) {
+        self.bind_group = Self::create_bind_group(
+            self.device.as_ref(),
+            &self.bg_layout,
+            scene_depth,
+            &self.uniforms,
+        );
+    }
+
+    pub fn set_parameters(&mut self, parameters: AmbientOcclusionParameters) {
+        self.parameters = parameters;
+    }
+
+    pub fn set_quality(&mut self, quality: PostProcessingQuality) {
+        self.quality = quality;
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.parameters.is_enabled()
+    }
+
+    /// Darken the occluded regions of `target`, which must hold the resolved colors of the scene.
+    ///
+    /// `cube_rect` is the area, in pixels, in which the direction cube was drawn. This area is
+    /// not darkened.
+    pub fn draw(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::TextureView,
+        znear: f32,
+        zfar: f32,
+        fovy: f32,
+        cube_rect: [f32; 4],
+    ) {
+        let uniforms = SsaoUniforms {
+            strength: self.parameters.strength,
+            radius: self.parameters.radius,
+            znear,
+            zfar,
+            cube_rect,
+            tan_half_fovy: (fovy / 2.).tan(),
+            nb_pairs: nb_pairs(self.quality),
+            _padding: [0; 2],
+        };
+        self.queue
+            .write_buffer(&self.uniforms, 0, bytemuck::cast_slice(&[uniforms]));
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("ambient occlusion"),
+            color_attachments: &[wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: true,
+                },
+            }],
+            depth_stencil_attachment: None,
+        });
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
@@ -0,0 +1,97 @@
+// Screen-space ambient occlusion post-processing pass.
+//
+// The output of this pass is a shading factor that multiplies the colors of the scene. Pairs of
+// opposite samples are read around each pixel. When both samples of a pair are closer to the
+// camera than the pixel, the pixel lies at the bottom of a crease or of a cavity and is darkened.
+// On a flat surface, one sample of each pair is closer and the other one is farther, so that
+// their contributions cancel out.
+
+[[block]]
+struct SsaoUniforms {
+    strength: f32;
+    // The distance, in nanometers, up to which the objects occlude each other
+    radius: f32;
+    znear: f32;
+    zfar: f32;
+    // (x_min, y_min, x_max, y_max) of the area in which the direction cube is drawn. This area
+    // is never shaded.
+    cube_rect: vec4<f32>;
+    tan_half_fovy: f32;
+    nb_pairs: u32;
+};
+
+[[group(0), binding(0)]]
+var depth_texture: texture_depth_multisampled_2d;
+[[group(0), binding(1)]]
+var<uniform> uniforms: SsaoUniforms;
+
+// Maximum distance, in pixels, between a pixel and its samples
+let MAX_RADIUS_PX: f32 = 64.0;
+let GOLDEN_ANGLE: f32 = 2.39996322973;
+
+struct VertexOutput {
+    [[builtin(position)]] position: vec4<f32>;
+};
+
+[[stage(vertex)]]
+fn vs_main([[builtin(vertex_index)]] idx: u32) -> VertexOutput {
+    // A single triangle that covers the whole target
+    let x = f32((idx << 1u) & 2u) * 2.0 - 1.0;
+    let y = f32(idx & 2u) * 2.0 - 1.0;
+    var out: VertexOutput;
+    out.position = vec4<f32>(x, y, 0.0, 1.0);
+    return out;
+}
+
+fn linear_depth(coords: vec2<i32>) -> f32 {
+    let depth = textureLoad(depth_texture, coords, 0);
+    return uniforms.znear * uniforms.zfar
+        / (uniforms.zfar - depth * (uniforms.zfar - uniforms.znear));
+}
+
+[[stage(fragment)]]
+fn fs_main(input: VertexOutput) -> [[location(0)]] vec4<f32> {
+    let p = input.position.xy;
+    if (p.x >= uniforms.cube_rect.x && p.x <= uniforms.cube_rect.z
+        && p.y >= uniforms.cube_rect.y && p.y <= uniforms.cube_rect.w) {
+        return vec4<f32>(1.0, 1.0, 1.0, 1.0);
+    }
+    let coords = vec2<i32>(p);
+    let dimensions = textureDimensions(depth_texture);
+    let max_coords = dimensions - vec2<i32>(1, 1);
+    let center = linear_depth(coords);
+    if (center >= uniforms.zfar * 0.99) {
+        // Background
+        return vec4<f32>(1.0, 1.0, 1.0, 1.0);
+    }
+    // Size in pixels of the radius at the depth of the pixel
+    let focal_px = f32(dimensions.y) / (2.0 * uniforms.tan_half_fovy);
+    let radius_px = min(uniforms.radius * focal_px / center, MAX_RADIUS_PX);
+    if (radius_px < 1.0) {
+        return vec4<f32>(1.0, 1.0, 1.0, 1.0);
+    }
+
+    var occlusion = 0.0;
+    var i: u32 = 0u;
+    loop {
+        if (i >= uniforms.nb_pairs) {
+            break;
+        }
+        // The samples are spread on a spiral so that they cover the whole disc
+        let t = (f32(i) + 0.5) / f32(uniforms.nb_pairs);
+        let angle = f32(i) * GOLDEN_ANGLE;
+        let offset = vec2<i32>(round(vec2<f32>(cos(angle), sin(angle)) * radius_px * sqrt(t)));
+        let delta_1 = center - linear_depth(clamp(coords + offset, vec2<i32>(0, 0), max_coords));
+        let delta_2 = center - linear_depth(clamp(coords - offset, vec2<i32>(0, 0), max_coords));
+        // Objects that are far in front of the pixel do not occlude it
+        if (max(delta_1, delta_2) < 2.0 * uniforms.radius) {
+            occlusion = occlusion + clamp((delta_1 + delta_2) / uniforms.radius, 0.0, 1.0);
+        }
+        continuing {
+            i = i + 1u;
+        }
+    }
+    occlusion = occlusion / f32(uniforms.nb_pairs);
+    let shade = clamp(1.0 - uniforms.strength * occlusion, 0.0, 1.0);
+    return vec4<f32>(shade, shade, shade, 1.0);
+}
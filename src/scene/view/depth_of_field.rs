@@ -0,0 +1,275 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! A post-processing pass that blurs the objects that are far from a focal plane.
+//!
+//! When the effect is enabled, the scene is resolved into an offscreen texture instead of the
+//! target. The pass then reads this texture and the depth buffer of the scene and writes the
+//! blurred image in the target. Both textures hold the colors of the scene before tone mapping.
+
+use super::texture::{SampledTexture, Texture};
+use super::HDR_FORMAT;
+use crate::consts::SAMPLE_COUNT;
+use crate::PhySize;
+use bytemuck::Zeroable;
+use ensnano_interactor::graphics::{DepthOfFieldParameters, PostProcessingQuality};
+use iced_wgpu::wgpu;
+use std::rc::Rc;
+use wgpu::util::DeviceExt;
+use wgpu::{Device, Queue};
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct DofUniforms {
+    focal_distance: f32,
+    aperture: f32,
+    znear: f32,
+    zfar: f32,
+    /// (x_min, y_min, x_max, y_max) of the area in which the direction cube is drawn
+    cube_rect: [f32; 4],
+    /// Number of samples on each side of the center pixel, in each direction
+    kernel_radius: i32,
+    _padding: [u32; 3],
+}
+
+fn kernel_radius(quality: PostProcessingQuality) -> i32 {
+    match quality {
+        PostProcessingQuality::Low => 3,
+        PostProcessingQuality::Medium => 6,
+        PostProcessingQuality::High => 10,
+    }
+}
+
+pub struct DepthOfField {
+    device: Rc<Device>,
+    queue: Rc<Queue>,
+    parameters: DepthOfFieldParameters,
+    quality: PostProcessingQuality,
+    /// The texture in which the scene is resolved before being blurred
+    source: SampledTexture,
+    uniforms: wgpu::Buffer,
+    bg_layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl DepthOfField {
+    pub fn new(
+        device: Rc<Device>,
+        queue: Rc<Queue>,
+        area_size: &PhySize,
+        scene_depth: &Texture,
+    ) -> Self {
+        let source = SampledTexture::create_target_texture_with_format(
+            device.as_ref(),
+            area_size,
+            HDR_FORMAT,
+        );
+        let uniforms = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("depth of field uniforms"),
+            contents: bytemuck::cast_slice(&[DofUniforms::zeroed()]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let bg_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: SAMPLE_COUNT > 1,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Depth,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+            label: Some("depth of field layout"),
+        });
+        let bind_group =
+            Self::create_bind_group(device.as_ref(), &bg_layout, &source, scene_depth, &uniforms);
+
+        let shader = device.create_shader_module(&wgpu::include_wgsl!("depth_of_field.wgsl"));
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("depth of field pipeline layout"),
+            bind_group_layouts: &[&bg_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("depth of field"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[wgpu::ColorTargetState {
+                    format: HDR_FORMAT,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                }],
+            }),
+            primitive: Default::default(),
+            depth_stencil: None,
+            multisample: Default::default(),
+        });
+
+        Self {
+            device,
+            queue,
+            parameters: Default::default(),
+            quality: Default::default(),
+            source,
+            uniforms,
+            bg_layout,
+            bind_group,
+            pipeline,
+        }
+    }
+
+    fn create_bind_group(
+        device: &Device,
+        layout: &wgpu::BindGroupLayout,
+        source: &SampledTexture,
+        scene_depth: &Texture,
+        uniforms: &wgpu::Buffer,
+    ) -> wgpu::BindGroup {
+        // The view of the depth texture that is used as attachment has a comparison sampler, so
+        // we create a new view to read the raw depth values.
+        let depth_view = scene_depth
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor {
+                label: Some("depth of field depth view"),
+                aspect: wgpu::TextureAspect::DepthOnly,
+                ..Default::default()
+            });
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&source.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&depth_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: uniforms.as_entire_binding(),
+                },
+            ],
+            label: Some("depth of field bind group"),
+        })
+    }
+
+    /// Must be called when the size of the drawing area or the depth texture of the scene
+    /// changes.
+    pub fn resize(&mut self, area_size: &PhySize, scene_depth: &Texture) {
+        self.source = SampledTexture::create_target_texture_with_format(
+            self.device.as_ref(),
+            area_size,
+            HDR_FORMAT,
+        );
+        self.bind_group = Self::create_bind_group(
+            self.device.as_ref(),
+            &self.bg_layout,
+            &self.source,
+            scene_depth,
+            &self.uniforms,
+        );
+    }
+
+    pub fn set_parameters(&mut self, parameters: DepthOfFieldParameters) {
+        self.parameters = parameters;
+    }
+
+    pub fn set_quality(&mut self, quality: PostProcessingQuality) {
+        self.quality = quality;
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.parameters.is_enabled()
+    }
+
+    /// The texture in which the scene must be resolved before applying the blur
+    pub fn source_view(&self) -> &wgpu::TextureView {
+        &self.source.view
+    }
+
+    /// Blur the content of the source texture and write the result in `target`.
+    ///
+    /// `cube_rect` is the area, in pixels, in which the direction cube was drawn. This area is
+    /// not blurred.
+    pub fn draw(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::TextureView,
+        znear: f32,
+        zfar: f32,
+        cube_rect: [f32; 4],
+    ) {
+        let uniforms = DofUniforms {
+            focal_distance: self.parameters.focal_distance,
+            aperture: self.parameters.aperture,
+            znear,
+            zfar,
+            cube_rect,
+            kernel_radius: kernel_radius(self.quality),
+            _padding: [0; 3],
+        };
+        self.queue
+            .write_buffer(&self.uniforms, 0, bytemuck::cast_slice(&[uniforms]));
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("depth of field"),
+            color_attachments: &[wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: true,
+                },
+            }],
+            depth_stencil_attachment: None,
+        });
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
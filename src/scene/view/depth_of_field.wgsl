@@ -0,0 +1,99 @@
+// Depth of field post-processing pass.
+//
+// Each pixel is blurred with a gaussian kernel whose radius is the circle of confusion of the
+// pixel, computed from its distance to the focal plane.
+
+[[block]]
+struct DofUniforms {
+    focal_distance: f32;
+    aperture: f32;
+    znear: f32;
+    zfar: f32;
+    // (x_min, y_min, x_max, y_max) of the area in which the direction cube is drawn. This area
+    // is never blurred.
+    cube_rect: vec4<f32>;
+    // Number of samples on each side of the center pixel, in each direction
+    kernel_radius: i32;
+};
+
+[[group(0), binding(0)]]
+var color_texture: texture_2d<f32>;
+[[group(0), binding(1)]]
+var depth_texture: texture_depth_multisampled_2d;
+[[group(0), binding(2)]]
+var<uniform> uniforms: DofUniforms;
+
+// Maximum radius of the circle of confusion in pixels
+let MAX_COC: f32 = 8.0;
+
+struct VertexOutput {
+    [[builtin(position)]] position: vec4<f32>;
+};
+
+[[stage(vertex)]]
+fn vs_main([[builtin(vertex_index)]] idx: u32) -> VertexOutput {
+    // A single triangle that covers the whole target
+    let x = f32((idx << 1u) & 2u) * 2.0 - 1.0;
+    let y = f32(idx & 2u) * 2.0 - 1.0;
+    var out: VertexOutput;
+    out.position = vec4<f32>(x, y, 0.0, 1.0);
+    return out;
+}
+
+fn linear_depth(depth: f32) -> f32 {
+    return uniforms.znear * uniforms.zfar
+        / (uniforms.zfar - depth * (uniforms.zfar - uniforms.znear));
+}
+
+fn circle_of_confusion(coords: vec2<i32>) -> f32 {
+    let dist = linear_depth(textureLoad(depth_texture, coords, 0));
+    let coc = uniforms.aperture * abs(dist - uniforms.focal_distance) / dist;
+    return clamp(coc, 0.0, MAX_COC);
+}
+
+[[stage(fragment)]]
+fn fs_main(input: VertexOutput) -> [[location(0)]] vec4<f32> {
+    let p = input.position.xy;
+    let coords = vec2<i32>(p);
+    let center = textureLoad(color_texture, coords, 0);
+    if (p.x >= uniforms.cube_rect.x && p.x <= uniforms.cube_rect.z
+        && p.y >= uniforms.cube_rect.y && p.y <= uniforms.cube_rect.w) {
+        return center;
+    }
+    let coc = circle_of_confusion(coords);
+    if (coc < 0.5) {
+        return center;
+    }
+    let max_coords = textureDimensions(color_texture) - vec2<i32>(1, 1);
+    let sigma = coc / 2.0;
+    var total = vec4<f32>(0.0, 0.0, 0.0, 0.0);
+    var weight_sum = 0.0;
+    var i: i32 = -uniforms.kernel_radius;
+    loop {
+        if (i > uniforms.kernel_radius) {
+            break;
+        }
+        var j: i32 = -uniforms.kernel_radius;
+        loop {
+            if (j > uniforms.kernel_radius) {
+                break;
+            }
+            let offset = vec2<f32>(f32(i), f32(j)) * coc / f32(uniforms.kernel_radius);
+            let r = length(offset);
+            if (r <= coc) {
+                let shifted = coords + vec2<i32>(round(offset));
+                let sample_coords = clamp(shifted, vec2<i32>(0, 0), max_coords);
+                let w = exp(-r * r / (2.0 * sigma * sigma));
+                total = total + w * textureLoad(color_texture, sample_coords, 0);
+                weight_sum = weight_sum + w;
+            }
+            continuing {
+                j = j + 1;
+            }
+        }
+        continuing {
+            i = i + 1;
+        }
+    }
+    return total / weight_sum;
+}
@@ -31,6 +31,12 @@ pub struct DnaVertex {
     normal: [f32; 3],
 }
 
+impl DnaVertex {
+    pub fn position(&self) -> Vec3 {
+        self.position.into()
+    }
+}
+
 pub trait DnaObject:
     Instanciable<Ressource = (), Vertex = DnaVertex, RawInstance = RawDnaInstance>
 {
@@ -78,55 +84,11 @@ impl Instanciable for SphereInstance {
     type Ressource = ();
 
     fn vertices() -> Vec<DnaVertex> {
-        let mut vertices = Vec::new();
-
-        let stack_step = PI / NB_STACK_SPHERE as f32;
-        let sector_step = 2. * PI / NB_SECTOR_SPHERE as f32;
-        for i in 0..=NB_STACK_SPHERE {
-            // 0..=x means that x is included
-            let stack_angle = PI / 2. - (i as f32) * stack_step;
-            let radius = SPHERE_RADIUS;
-            let xy = radius * stack_angle.cos();
-            let z = radius * stack_angle.sin();
-
-            for j in 0..=NB_SECTOR_SPHERE {
-                let sector_angle = j as f32 * sector_step;
-
-                let x = xy * sector_angle.cos();
-                let y = xy * sector_angle.sin();
-                let position = [x, y, z];
-                let normal = [x, y, z];
-
-                vertices.push(DnaVertex { position, normal })
-            }
-        }
-        vertices
+        sphere_vertices(NB_STACK_SPHERE, NB_SECTOR_SPHERE)
     }
 
     fn indices() -> Vec<u16> {
-        let mut indices = Vec::new();
-
-        for i in 0..NB_STACK_SPHERE {
-            let mut k1: u16 = i * (NB_SECTOR_SPHERE + 1); // begining of ith stack
-            let mut k2: u16 = k1 + NB_SECTOR_SPHERE + 1; // begining of (i + 1)th stack
-
-            for _ in 0..NB_SECTOR_SPHERE {
-                if i > 0 {
-                    indices.push(k1);
-                    indices.push(k2);
-                    indices.push(k1 + 1);
-                }
-
-                if i < NB_STACK_SPHERE - 1 {
-                    indices.push(k1 + 1);
-                    indices.push(k2);
-                    indices.push(k2 + 1);
-                }
-                k1 += 1;
-                k2 += 1;
-            }
-        }
-        indices
+        sphere_indices(NB_STACK_SPHERE, NB_SECTOR_SPHERE)
     }
 
     fn primitive_topology() -> wgpu::PrimitiveTopology {
@@ -149,7 +111,7 @@ impl Instanciable for SphereInstance {
     }
 
     fn fragment_module(device: &wgpu::Device) -> wgpu::ShaderModule {
-        device.create_shader_module(&wgpu::include_spirv!("dna_obj.frag.spv"))
+        device.create_shader_module(&wgpu::include_wgsl!("dna_obj_frag.wgsl"))
     }
 
     fn fake_fragment_module(device: &wgpu::Device) -> Option<wgpu::ShaderModule> {
@@ -188,29 +150,11 @@ impl Instanciable for TubeInstance {
     type Ressource = ();
 
     fn vertices() -> Vec<DnaVertex> {
-        let radius = BOUND_RADIUS;
-        (0..(2 * NB_RAY_TUBE))
-            .map(|i| {
-                let point = i / 2;
-                let side = if i % 2 == 0 { -1. } else { 1. };
-                let theta = (point as f32) * 2. * PI / NB_RAY_TUBE as f32;
-                let position = [
-                    side * BOUND_LENGTH / 2.,
-                    theta.sin() * radius,
-                    theta.cos() * radius,
-                ];
-
-                let normal = [0., theta.sin(), theta.cos()];
-                DnaVertex { position, normal }
-            })
-            .collect()
+        tube_vertices(NB_RAY_TUBE)
     }
 
     fn indices() -> Vec<u16> {
-        let mut indices: Vec<_> = (0u16..(2 * NB_RAY_TUBE as u16)).collect();
-        indices.push(0);
-        indices.push(1);
-        indices
+        tube_indices(NB_RAY_TUBE)
     }
 
     fn vertex_module(device: &wgpu::Device) -> wgpu::ShaderModule {
@@ -218,7 +162,7 @@ impl Instanciable for TubeInstance {
     }
 
     fn fragment_module(device: &wgpu::Device) -> wgpu::ShaderModule {
-        device.create_shader_module(&wgpu::include_spirv!("dna_obj.frag.spv"))
+        device.create_shader_module(&wgpu::include_wgsl!("dna_obj_frag.wgsl"))
     }
 
     fn fake_fragment_module(device: &wgpu::Device) -> Option<wgpu::ShaderModule> {
@@ -317,7 +261,7 @@ impl Instanciable for ConeInstance {
     }
 
     fn fragment_module(device: &wgpu::Device) -> wgpu::ShaderModule {
-        device.create_shader_module(&wgpu::include_spirv!("dna_obj.frag.spv"))
+        device.create_shader_module(&wgpu::include_wgsl!("dna_obj_frag.wgsl"))
     }
 
     fn fake_fragment_module(device: &wgpu::Device) -> Option<wgpu::ShaderModule> {
@@ -350,3 +294,151 @@ impl Instanciable for ConeInstance {
 }
 
 impl DnaObject for ConeInstance {}
+
+/// A sphere drawn with fewer triangles, used for the spheres that are far away from the camera.
+pub struct LowPolySphereInstance(pub SphereInstance);
+
+impl Instanciable for LowPolySphereInstance {
+    type Vertex = DnaVertex;
+    type RawInstance = RawDnaInstance;
+    type Ressource = ();
+
+    fn vertices() -> Vec<DnaVertex> {
+        sphere_vertices(NB_STACK_SPHERE_LOW_POLY, NB_SECTOR_SPHERE_LOW_POLY)
+    }
+
+    fn indices() -> Vec<u16> {
+        sphere_indices(NB_STACK_SPHERE_LOW_POLY, NB_SECTOR_SPHERE_LOW_POLY)
+    }
+
+    fn primitive_topology() -> wgpu::PrimitiveTopology {
+        wgpu::PrimitiveTopology::TriangleList
+    }
+
+    fn to_raw_instance(&self) -> RawDnaInstance {
+        self.0.to_raw_instance()
+    }
+
+    fn vertex_module(device: &wgpu::Device) -> wgpu::ShaderModule {
+        device.create_shader_module(&wgpu::include_spirv!("dna_obj.vert.spv"))
+    }
+
+    fn fragment_module(device: &wgpu::Device) -> wgpu::ShaderModule {
+        device.create_shader_module(&wgpu::include_wgsl!("dna_obj_frag.wgsl"))
+    }
+}
+
+impl DnaObject for LowPolySphereInstance {}
+
+/// A tube drawn with fewer triangles, used for the tubes that are far away from the camera.
+pub struct LowPolyTubeInstance(pub TubeInstance);
+
+impl Instanciable for LowPolyTubeInstance {
+    type Vertex = DnaVertex;
+    type RawInstance = RawDnaInstance;
+    type Ressource = ();
+
+    fn vertices() -> Vec<DnaVertex> {
+        tube_vertices(NB_RAY_TUBE_LOW_POLY)
+    }
+
+    fn indices() -> Vec<u16> {
+        tube_indices(NB_RAY_TUBE_LOW_POLY)
+    }
+
+    fn primitive_topology() -> wgpu::PrimitiveTopology {
+        wgpu::PrimitiveTopology::TriangleStrip
+    }
+
+    fn to_raw_instance(&self) -> RawDnaInstance {
+        self.0.to_raw_instance()
+    }
+
+    fn vertex_module(device: &wgpu::Device) -> wgpu::ShaderModule {
+        device.create_shader_module(&wgpu::include_spirv!("dna_obj.vert.spv"))
+    }
+
+    fn fragment_module(device: &wgpu::Device) -> wgpu::ShaderModule {
+        device.create_shader_module(&wgpu::include_wgsl!("dna_obj_frag.wgsl"))
+    }
+}
+
+impl DnaObject for LowPolyTubeInstance {}
+
+fn sphere_vertices(nb_stack: u16, nb_sector: u16) -> Vec<DnaVertex> {
+    let mut vertices = Vec::new();
+
+    let stack_step = PI / nb_stack as f32;
+    let sector_step = 2. * PI / nb_sector as f32;
+    for i in 0..=nb_stack {
+        // 0..=x means that x is included
+        let stack_angle = PI / 2. - (i as f32) * stack_step;
+        let radius = SPHERE_RADIUS;
+        let xy = radius * stack_angle.cos();
+        let z = radius * stack_angle.sin();
+
+        for j in 0..=nb_sector {
+            let sector_angle = j as f32 * sector_step;
+
+            let x = xy * sector_angle.cos();
+            let y = xy * sector_angle.sin();
+            let position = [x, y, z];
+            let normal = [x, y, z];
+
+            vertices.push(DnaVertex { position, normal })
+        }
+    }
+    vertices
+}
+
+fn sphere_indices(nb_stack: u16, nb_sector: u16) -> Vec<u16> {
+    let mut indices = Vec::new();
+
+    for i in 0..nb_stack {
+        let mut k1: u16 = i * (nb_sector + 1); // begining of ith stack
+        let mut k2: u16 = k1 + nb_sector + 1; // begining of (i + 1)th stack
+
+        for _ in 0..nb_sector {
+            if i > 0 {
+                indices.push(k1);
+                indices.push(k2);
+                indices.push(k1 + 1);
+            }
+
+            if i < nb_stack - 1 {
+                indices.push(k1 + 1);
+                indices.push(k2);
+                indices.push(k2 + 1);
+            }
+            k1 += 1;
+            k2 += 1;
+        }
+    }
+    indices
+}
+
+fn tube_vertices(nb_ray: usize) -> Vec<DnaVertex> {
+    let radius = BOUND_RADIUS;
+    (0..(2 * nb_ray))
+        .map(|i| {
+            let point = i / 2;
+            let side = if i % 2 == 0 { -1. } else { 1. };
+            let theta = (point as f32) * 2. * PI / nb_ray as f32;
+            let position = [
+                side * BOUND_LENGTH / 2.,
+                theta.sin() * radius,
+                theta.cos() * radius,
+            ];
+
+            let normal = [0., theta.sin(), theta.cos()];
+            DnaVertex { position, normal }
+        })
+        .collect()
+}
+
+fn tube_indices(nb_ray: usize) -> Vec<u16> {
+    let mut indices: Vec<_> = (0u16..(2 * nb_ray as u16)).collect();
+    indices.push(0);
+    indices.push(1);
+    indices
+}
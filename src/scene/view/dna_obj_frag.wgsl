@@ -0,0 +1,129 @@
+// Fragment shader of the meshes used to draw DNA.
+//
+// Opaque objects are lit by the light sources of the scene, the other ones by a single light
+// placed above the scene.
+
+struct Light {
+    // The direction towards the light, in world coordinates
+    direction: vec3<f32>;
+    intensity: f32;
+    color: vec3<f32>;
+};
+
+[[block]]
+struct Uniforms {
+    camera_position: vec4<f32>;
+    view: mat4x4<f32>;
+    proj: mat4x4<f32>;
+    inversed_view: mat4x4<f32>;
+    fog_radius: f32;
+    fog_length: f32;
+    make_fog: u32;
+    fog_from_camera: u32;
+    fog_center: vec3<f32>;
+    lights: array<Light, 4>;
+};
+
+[[group(0), binding(0)]]
+var<uniform> uniforms: Uniforms;
+
+let NB_LIGHTS: i32 = 4;
+let AMBIENT_STRENGTH: f32 = 0.3;
+
+let SKY_COLOR: vec3<f32> = vec3<f32>(0.207, 0.321, 0.494);
+let SAND_COLOR: vec3<f32> = vec3<f32>(0.368, 0.360, 0.219);
+let HORIZON: vec3<f32> = vec3<f32>(0.917, 0.917, 0.917);
+let DARK_FOG_COLOR: vec3<f32> = vec3<f32>(0.01, 0.01, 0.03);
+
+fn shade(light_dir: vec3<f32>, light_color: vec3<f32>, normal: vec3<f32>, view_dir: vec3<f32>) -> vec3<f32> {
+    let diffuse_strength = max(dot(normal, light_dir), 0.0);
+    let reflect_dir = reflect(-light_dir, normal);
+    let specular_strength = pow(max(dot(view_dir, reflect_dir), 0.0), 32.0);
+    return (diffuse_strength + specular_strength) * light_color;
+}
+
+[[stage(fragment)]]
+fn main(
+    [[location(0)]] v_color: vec4<f32>,
+    [[location(1)]] v_normal: vec3<f32>,
+    [[location(2)]] v_position: vec3<f32>,
+) -> [[location(0)]] vec4<f32> {
+    let normal = normalize(v_normal);
+    let camera_position = uniforms.camera_position.xyz;
+    let view_dir = normalize(camera_position - v_position);
+
+    var f_color: vec4<f32>;
+    if (v_color.w < 0.8 && v_color.w > 0.7) {
+        f_color = v_color;
+    } else {
+        var light_sum = vec3<f32>(AMBIENT_STRENGTH, AMBIENT_STRENGTH, AMBIENT_STRENGTH);
+        if (abs(v_color.w - 1.0) < 1e-3) {
+            var i: i32 = 0;
+            loop {
+                if (i >= NB_LIGHTS) {
+                    break;
+                }
+                let light = uniforms.lights[i];
+                if (light.intensity > 0.0) {
+                    let light_dir = normalize(light.direction);
+                    light_sum = light_sum
+                        + shade(light_dir, light.intensity * light.color, normal, view_dir);
+                }
+                continuing {
+                    i = i + 1;
+                }
+            }
+        } else {
+            let light_dir = normalize(vec3<f32>(0.0, 0.0, 1000.0) - v_position);
+            light_sum = light_sum + shade(light_dir, vec3<f32>(1.0, 1.0, 1.0), normal, view_dir);
+        }
+        f_color = vec4<f32>(light_sum * v_color.xyz, v_color.w);
+    }
+
+    var visibility: f32 = 1.0;
+    if (uniforms.make_fog > 0u) {
+        var dist: f32;
+        if (uniforms.fog_from_camera > 0u) {
+            dist = length(camera_position - v_position);
+        } else {
+            dist = length(uniforms.fog_center - v_position);
+        }
+        visibility = 1.0 - smoothstep(
+            uniforms.fog_length,
+            uniforms.fog_length + uniforms.fog_radius,
+            dist,
+        );
+    }
+
+    if (visibility < 0.1 && uniforms.make_fog == 1u) {
+        discard;
+    }
+
+    var y: f32;
+    if (abs(view_dir.y) > abs(view_dir.x) && abs(view_dir.y) > abs(view_dir.z)) {
+        y = select(0.0, 1.0, view_dir.y > 0.0);
+    } elseif (abs(view_dir.x) > abs(view_dir.z)) {
+        y = (view_dir.y / abs(view_dir.x) + 1.0) / 2.0;
+    } else {
+        y = (view_dir.y / abs(view_dir.z) + 1.0) / 2.0;
+    }
+
+    var fog_color: vec3<f32>;
+    if (uniforms.make_fog == 1u) {
+        if (y < 0.33) {
+            fog_color = SKY_COLOR;
+        } elseif (y < 0.5) {
+            let delta = (y - 0.33) / 0.17;
+            fog_color = mix(SKY_COLOR, HORIZON, vec3<f32>(pow(delta, 3.0)));
+        } elseif (y < 0.66) {
+            let delta = 1.0 - (y - 0.5) / 0.16;
+            fog_color = mix(SAND_COLOR, HORIZON, vec3<f32>(pow(delta, 3.0)));
+        } else {
+            fog_color = SAND_COLOR;
+        }
+    } else {
+        fog_color = DARK_FOG_COLOR;
+    }
+
+    return mix(vec4<f32>(fog_color, 1.0), f_color, vec4<f32>(visibility));
+}
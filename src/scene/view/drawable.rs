@@ -163,7 +163,7 @@ impl<D: Drawable> Drawer<D> {
         let format = if fake {
             wgpu::TextureFormat::Bgra8Unorm
         } else {
-            wgpu::TextureFormat::Bgra8UnormSrgb
+            super::HDR_FORMAT
         };
 
         let blend_state = if fake {
@@ -0,0 +1,224 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! A post-processing pass that shades the scene according to the variations of its depth.
+//!
+//! The pass reads the depth buffer of the scene and multiplies the colors of the scene by a
+//! factor that is smaller where the depth varies, which helps perceiving the shape of regions
+//! drawn with a uniform color.
+
+use super::texture::Texture;
+use super::HDR_FORMAT;
+use crate::consts::SAMPLE_COUNT;
+use bytemuck::Zeroable;
+use ensnano_interactor::graphics::EyeDomeLightingParameters;
+use iced_wgpu::wgpu;
+use std::rc::Rc;
+use wgpu::util::DeviceExt;
+use wgpu::{Device, Queue};
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct EdlUniforms {
+    strength: f32,
+    radius: f32,
+    znear: f32,
+    zfar: f32,
+    /// (x_min, y_min, x_max, y_max) of the area in which the direction cube is drawn
+    cube_rect: [f32; 4],
+}
+
+pub struct EyeDomeLighting {
+    device: Rc<Device>,
+    queue: Rc<Queue>,
+    parameters: EyeDomeLightingParameters,
+    uniforms: wgpu::Buffer,
+    bg_layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl EyeDomeLighting {
+    pub fn new(device: Rc<Device>, queue: Rc<Queue>, scene_depth: &Texture) -> Self {
+        let uniforms = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("eye dome lighting uniforms"),
+            contents: bytemuck::cast_slice(&[EdlUniforms::zeroed()]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let bg_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: SAMPLE_COUNT > 1,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Depth,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+            label: Some("eye dome lighting layout"),
+        });
+        let bind_group =
+            Self::create_bind_group(device.as_ref(), &bg_layout, scene_depth, &uniforms);
+
+        let shader = device.create_shader_module(&wgpu::include_wgsl!("eye_dome_lighting.wgsl"));
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("eye dome lighting pipeline layout"),
+            bind_group_layouts: &[&bg_layout],
+            push_constant_ranges: &[],
+        });
+        // The output of the fragment shader is multiplied with the colors of the scene
+        let multiply = wgpu::BlendComponent {
+            src_factor: wgpu::BlendFactor::Zero,
+            dst_factor: wgpu::BlendFactor::Src,
+            operation: wgpu::BlendOperation::Add,
+        };
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("eye dome lighting"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[wgpu::ColorTargetState {
+                    format: HDR_FORMAT,
+                    blend: Some(wgpu::BlendState {
+                        color: multiply,
+                        alpha: multiply,
+                    }),
+                    write_mask: wgpu::ColorWrites::ALL,
+                }],
+            }),
+            primitive: Default::default(),
+            depth_stencil: None,
+            multisample: Default::default(),
+        });
+
+        Self {
+            device,
+            queue,
+            parameters: Default::default(),
+            uniforms,
+            bg_layout,
+            bind_group,
+            pipeline,
+        }
+    }
+
+    fn create_bind_group(
+        device: &Device,
+        layout: &wgpu::BindGroupLayout,
+        scene_depth: &Texture,
+        uniforms: &wgpu::Buffer,
+    ) -> wgpu::BindGroup {
+        // Like in the depth of field pass, we need a view that gives access to the raw depth
+        // values.
+        let depth_view = scene_depth
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor {
+                label: Some("eye dome lighting depth view"),
+                aspect: wgpu::TextureAspect::DepthOnly,
+                ..Default::default()
+            });
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&depth_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: uniforms.as_entire_binding(),
+                },
+            ],
+            label: Some("eye dome lighting bind group"),
+        })
+    }
+
+    /// Must be called when the depth texture of the scene changes.
+    pub fn resize(&mut self, scene_depth: &Texture) {
+        self.bind_group = Self::create_bind_group(
+            self.device.as_ref(),
+            &self.bg_layout,
+            scene_depth,
+            &self.uniforms,
+        );
+    }
+
+    pub fn set_parameters(&mut self, parameters: EyeDomeLightingParameters) {
+        self.parameters = parameters;
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.parameters.is_enabled()
+    }
+
+    /// Shade the content of `target`, which must hold the resolved colors of the scene.
+    ///
+    /// `cube_rect` is the area, in pixels, in which the direction cube was drawn. This area is
+    /// not shaded.
+    pub fn draw(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::TextureView,
+        znear: f32,
+        zfar: f32,
+        cube_rect: [f32; 4],
+    ) {
+        let uniforms = EdlUniforms {
+            strength: self.parameters.strength,
+            radius: self.parameters.radius,
+            znear,
+            zfar,
+            cube_rect,
+        };
+        self.queue
+            .write_buffer(&self.uniforms, 0, bytemuck::cast_slice(&[uniforms]));
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("eye dome lighting"),
+            color_attachments: &[wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: true,
+                },
+            }],
+            depth_stencil_attachment: None,
+        });
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
@@ -0,0 +1,77 @@
+// Eye-dome lighting post-processing pass.
+//
+// The output of this pass is a shading factor that multiplies the colors of the scene. A pixel
+// is darkened when its neighbours are closer to the camera than itself, which outlines the
+// silhouettes of the objects and makes the relief visible in regions of uniform color.
+
+[[block]]
+struct EdlUniforms {
+    strength: f32;
+    radius: f32;
+    znear: f32;
+    zfar: f32;
+    // (x_min, y_min, x_max, y_max) of the area in which the direction cube is drawn. This area
+    // is never shaded.
+    cube_rect: vec4<f32>;
+};
+
+[[group(0), binding(0)]]
+var depth_texture: texture_depth_multisampled_2d;
+[[group(0), binding(1)]]
+var<uniform> uniforms: EdlUniforms;
+
+// Number of neighbours that are compared to the center pixel
+let NB_NEIGHBOURS: i32 = 8;
+// Scale applied to the response so that a strength of 1 gives a visible but soft shading
+let RESPONSE_SCALE: f32 = 100.0;
+
+struct VertexOutput {
+    [[builtin(position)]] position: vec4<f32>;
+};
+
+[[stage(vertex)]]
+fn vs_main([[builtin(vertex_index)]] idx: u32) -> VertexOutput {
+    // A single triangle that covers the whole target
+    let x = f32((idx << 1u) & 2u) * 2.0 - 1.0;
+    let y = f32(idx & 2u) * 2.0 - 1.0;
+    var out: VertexOutput;
+    out.position = vec4<f32>(x, y, 0.0, 1.0);
+    return out;
+}
+
+fn log_depth(coords: vec2<i32>) -> f32 {
+    let depth = textureLoad(depth_texture, coords, 0);
+    let dist = uniforms.znear * uniforms.zfar
+        / (uniforms.zfar - depth * (uniforms.zfar - uniforms.znear));
+    return log2(dist);
+}
+
+[[stage(fragment)]]
+fn fs_main(input: VertexOutput) -> [[location(0)]] vec4<f32> {
+    let p = input.position.xy;
+    if (p.x >= uniforms.cube_rect.x && p.x <= uniforms.cube_rect.z
+        && p.y >= uniforms.cube_rect.y && p.y <= uniforms.cube_rect.w) {
+        return vec4<f32>(1.0, 1.0, 1.0, 1.0);
+    }
+    let coords = vec2<i32>(p);
+    let max_coords = textureDimensions(depth_texture) - vec2<i32>(1, 1);
+    let center = log_depth(coords);
+    var response = 0.0;
+    var i: i32 = 0;
+    loop {
+        if (i >= NB_NEIGHBOURS) {
+            break;
+        }
+        let angle = f32(i) * 6.28318530718 / f32(NB_NEIGHBOURS);
+        let offset = vec2<f32>(cos(angle), sin(angle)) * uniforms.radius;
+        let shifted = coords + vec2<i32>(round(offset));
+        let neighbour = log_depth(clamp(shifted, vec2<i32>(0, 0), max_coords));
+        response = response + max(0.0, center - neighbour);
+        continuing {
+            i = i + 1;
+        }
+    }
+    response = response / f32(NB_NEIGHBOURS);
+    let shade = exp(-uniforms.strength * RESPONSE_SCALE * response);
+    return vec4<f32>(shade, shade, shade, 1.0);
+}
@@ -67,7 +67,7 @@ impl GridInstance {
         x: isize,
         y: isize,
         h_id: usize,
-        instances: &mut Vec<Vec<LetterInstance>>,
+        instances: &mut Vec<LetterInstance>,
         right: Vec3,
         up: Vec3,
     ) {
@@ -80,9 +80,10 @@ impl GridInstance {
                 design_id: self.design as u32,
                 scale: 3.,
                 shift: Vec3::zero(),
+                fade: false,
+                character: c,
             };
-            let idx = c.to_digit(10).unwrap();
-            instances[idx as usize].push(instance);
+            instances.push(instance);
         }
     }
 
@@ -49,6 +49,12 @@ pub struct GridDiscVertex {
     color: u32,
 }
 
+impl GridDiscVertex {
+    pub fn new(position: Vec3, color: u32) -> Self {
+        Self { position, color }
+    }
+}
+
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct GridDiscVertexRaw {
@@ -213,6 +213,47 @@ pub struct InstanceDrawer<D: Instanciable + ?Sized> {
     nb_indices: u32,
     ressource: D::Ressource,
     device: Rc<Device>,
+    /// The parameters needed to re-create the pipeline
+    pipeline_settings: PipelineSettings,
+}
+
+/// The way the fragments produced by an `InstanceDrawer` are combined with the content of the
+/// frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlendMode {
+    /// The fragments overwrite the content of the frame.
+    Replace,
+    /// The color of the fragments is added to the content of the frame.
+    Additive,
+    /// The fragments are mixed with the content of the frame according to their alpha channel.
+    Alpha,
+}
+
+impl BlendMode {
+    fn blend_state(&self) -> wgpu::BlendState {
+        match self {
+            Self::Replace => wgpu::BlendState::REPLACE,
+            Self::Alpha => wgpu::BlendState::ALPHA_BLENDING,
+            Self::Additive => wgpu::BlendState {
+                color: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::One,
+                    dst_factor: wgpu::BlendFactor::One,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                alpha: wgpu::BlendComponent::OVER,
+            },
+        }
+    }
+}
+
+struct PipelineSettings {
+    viewer_desc: BindGroupLayoutDescriptor<'static>,
+    models_desc: BindGroupLayoutDescriptor<'static>,
+    primitive_topology: PrimitiveTopology,
+    fake: bool,
+    outliner: bool,
+    blend_mode: BlendMode,
+    label: String,
 }
 
 impl<D: Instanciable> InstanceDrawer<D> {
@@ -303,22 +344,6 @@ impl<D: Instanciable> InstanceDrawer<D> {
             wgpu::BufferUsages::VERTEX,
         );
 
-        let vertex_module = if fake {
-            D::fake_vertex_module(&device).unwrap_or_else(|| D::vertex_module(&device))
-        } else if outliner {
-            D::outline_vertex_module(&device).unwrap_or_else(|| D::vertex_module(&device))
-        } else {
-            D::vertex_module(&device)
-        };
-
-        let fragment_module = if fake {
-            D::fake_fragment_module(&device).unwrap_or_else(|| D::fragment_module(&device))
-        } else if outliner {
-            D::outline_fragment_module(&device).unwrap_or_else(|| D::fragment_module(&device))
-        } else {
-            D::fragment_module(&device)
-        };
-
         let primitive_topology = if wireframe {
             match D::primitive_topology() {
                 PrimitiveTopology::TriangleList => PrimitiveTopology::LineList,
@@ -329,17 +354,22 @@ impl<D: Instanciable> InstanceDrawer<D> {
             D::primitive_topology()
         };
 
-        let pipeline = Self::create_pipeline(
-            &device,
-            viewer_desc,
-            models_desc,
-            vertex_module,
-            fragment_module,
+        let pipeline_settings = PipelineSettings {
+            viewer_desc: viewer_desc.clone(),
+            models_desc: models_desc.clone(),
             primitive_topology,
             fake,
             outliner,
-            label,
-        );
+            // The colors written in the fake texture encode the identifier of the drawn objects,
+            // so they must not be mixed with the content of the texture.
+            blend_mode: if fake {
+                BlendMode::Replace
+            } else {
+                BlendMode::Alpha
+            },
+            label: label.as_ref().to_string(),
+        };
+        let pipeline = Self::create_pipeline(&device, &pipeline_settings);
         let instances = DynamicBindGroup::new(device.clone(), queue);
 
         let additional_ressources_layout = D::Ressource::ressources_layout();
@@ -369,6 +399,25 @@ impl<D: Instanciable> InstanceDrawer<D> {
             additional_bind_group,
             ressource,
             device,
+            pipeline_settings,
+        }
+    }
+
+    /// Set the way the fragments produced by `self` are blended with the frame and re-create the
+    /// pipeline accordingly.
+    ///
+    /// Drawers targeting the fake texture always overwrite it, since the colors written there
+    /// encode the identifier of the drawn objects.
+    pub fn set_blend_mode(&mut self, mode: BlendMode) {
+        if self.pipeline_settings.fake {
+            log::warn!(
+                "Ignoring blend mode {:?} for fake drawer {}",
+                mode,
+                self.pipeline_settings.label
+            );
+        } else if self.pipeline_settings.blend_mode != mode {
+            self.pipeline_settings.blend_mode = mode;
+            self.pipeline = Self::create_pipeline(&self.device, &self.pipeline_settings);
         }
     }
 
@@ -394,21 +443,30 @@ impl<D: Instanciable> InstanceDrawer<D> {
         }
     }
 
-    fn create_pipeline<S: AsRef<str>>(
-        device: &Device,
-        viewer_bind_group_layout_desc: &wgpu::BindGroupLayoutDescriptor<'static>,
-        models_bind_group_layout_desc: &wgpu::BindGroupLayoutDescriptor<'static>,
-        vertex_module: ShaderModule,
-        fragment_module: ShaderModule,
-        primitive_topology: PrimitiveTopology,
-        fake: bool,
-        outliner: bool,
-        label: S,
-    ) -> RenderPipeline {
-        let viewer_bind_group_layout =
-            device.create_bind_group_layout(&viewer_bind_group_layout_desc);
-        let models_bind_group_layout =
-            device.create_bind_group_layout(&models_bind_group_layout_desc);
+    fn create_pipeline(device: &Device, settings: &PipelineSettings) -> RenderPipeline {
+        let fake = settings.fake;
+        let outliner = settings.outliner;
+        let primitive_topology = settings.primitive_topology;
+        let label = settings.label.as_str();
+
+        let vertex_module = if fake {
+            D::fake_vertex_module(device).unwrap_or_else(|| D::vertex_module(device))
+        } else if outliner {
+            D::outline_vertex_module(device).unwrap_or_else(|| D::vertex_module(device))
+        } else {
+            D::vertex_module(device)
+        };
+
+        let fragment_module = if fake {
+            D::fake_fragment_module(device).unwrap_or_else(|| D::fragment_module(device))
+        } else if outliner {
+            D::outline_fragment_module(device).unwrap_or_else(|| D::fragment_module(device))
+        } else {
+            D::fragment_module(device)
+        };
+
+        let viewer_bind_group_layout = device.create_bind_group_layout(&settings.viewer_desc);
+        let models_bind_group_layout = device.create_bind_group_layout(&settings.models_desc);
 
         // gather the ressources, [instance, additional ressources]
         let instance_entry = wgpu::BindGroupLayoutEntry {
@@ -422,21 +480,15 @@ impl<D: Instanciable> InstanceDrawer<D> {
             count: None,
         };
 
-        // texture displayed on the frame requires to use srgb, texture used for object
-        // identification must be in linear format
+        // texture displayed on the frame are drawn in a floating point texture that is tone
+        // mapped afterwards, texture used for object identification must be in linear format
         let format = if fake {
             wgpu::TextureFormat::Bgra8Unorm
         } else {
-            wgpu::TextureFormat::Bgra8UnormSrgb
+            super::HDR_FORMAT
         };
 
-        // We use alpha blending on texture displayed on the frame. For fake texture we simply rely
-        // on depth.
-        let blend_state = if fake {
-            wgpu::BlendState::REPLACE
-        } else {
-            wgpu::BlendState::ALPHA_BLENDING
-        };
+        let blend_state = settings.blend_mode.blend_state();
 
         let sample_count = if fake { 1 } else { SAMPLE_COUNT };
 
@@ -460,7 +512,7 @@ impl<D: Instanciable> InstanceDrawer<D> {
                     &additional_bind_group_layout,
                 ],
                 push_constant_ranges: &[],
-                label: Some(label.as_ref()),
+                label: Some(label),
             })
         } else {
             device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
@@ -530,7 +582,7 @@ impl<D: Instanciable> InstanceDrawer<D> {
                 mask: !0,
                 alpha_to_coverage_enabled: !fake,
             },
-            label: Some(label.as_ref()),
+            label: Some(label),
         })
     }
 }
@@ -545,19 +597,28 @@ pub trait RawDrawer {
         model_bind_group: &'a wgpu::BindGroup,
     );
 
-    fn new_instances_raw(&mut self, instances_raw: &Vec<Self::RawInstance>);
-}
+    /// Same as `draw`, but the arguments of the draw call are read from `cmd_buffer` at
+    /// `offset`. These arguments must have been written by an `IndirectCommandBuffer`.
+    fn draw_indirect<'a>(
+        &'a mut self,
+        render_pass: &mut RenderPass<'a>,
+        viewer_bind_group: &'a wgpu::BindGroup,
+        model_bind_group: &'a wgpu::BindGroup,
+        cmd_buffer: &'a wgpu::Buffer,
+        offset: u64,
+    );
 
-impl<D: Instanciable> RawDrawer for InstanceDrawer<D> {
-    type RawInstance = <D as Instanciable>::RawInstance;
+    /// The arguments of the draw call of `self`, or `None` if they are not read from an
+    /// `IndirectCommandBuffer`.
+    fn indirect_command(&self) -> Option<wgpu::util::DrawIndexedIndirect>;
 
-    fn new_instances_raw(&mut self, instances_raw: &Vec<D::RawInstance>) {
-        self.nb_instances = instances_raw.len() as u32;
-        self.instances.update(instances_raw.as_slice());
-    }
+    fn new_instances_raw(&mut self, instances_raw: &Vec<Self::RawInstance>);
+}
 
-    fn draw<'a>(
-        &'a mut self,
+impl<D: Instanciable> InstanceDrawer<D> {
+    /// Set the pipeline, buffers and bind groups needed to draw the instances
+    fn prepare_draw<'a>(
+        &'a self,
         render_pass: &mut RenderPass<'a>,
         viewer_bind_group: &'a wgpu::BindGroup,
         model_bind_group: &'a wgpu::BindGroup,
@@ -582,7 +643,136 @@ impl<D: Instanciable> RawDrawer for InstanceDrawer<D> {
         if let Some(ref additional_bind_group) = self.additional_bind_group {
             render_pass.set_bind_group(3, additional_bind_group, &[]);
         }
+    }
+}
+
+impl<D: Instanciable> InstanceDrawer<D> {
+    /// Draw the mesh of `self` for the instances stored in `instances` instead of the instances
+    /// of `self`. The arguments of the draw call are read from `cmd_buffer` at `offset`.
+    pub fn draw_instances_indirect<'a>(
+        &'a self,
+        render_pass: &mut RenderPass<'a>,
+        viewer_bind_group: &'a wgpu::BindGroup,
+        model_bind_group: &'a wgpu::BindGroup,
+        instances: &'a wgpu::BindGroup,
+        cmd_buffer: &'a wgpu::Buffer,
+        offset: u64,
+    ) {
+        self.prepare_draw(render_pass, viewer_bind_group, model_bind_group);
+        render_pass.set_bind_group(2, instances, &[]);
+        render_pass.draw_indexed_indirect(cmd_buffer, offset);
+    }
+
+    /// The number of vertex indices of the mesh
+    pub fn nb_indices(&self) -> u32 {
+        self.nb_indices
+    }
+}
 
+impl<D: Instanciable> RawDrawer for InstanceDrawer<D> {
+    type RawInstance = <D as Instanciable>::RawInstance;
+
+    fn new_instances_raw(&mut self, instances_raw: &Vec<D::RawInstance>) {
+        self.nb_instances = instances_raw.len() as u32;
+        self.instances.update(instances_raw.as_slice());
+    }
+
+    fn draw<'a>(
+        &'a mut self,
+        render_pass: &mut RenderPass<'a>,
+        viewer_bind_group: &'a wgpu::BindGroup,
+        model_bind_group: &'a wgpu::BindGroup,
+    ) {
+        self.prepare_draw(render_pass, viewer_bind_group, model_bind_group);
         render_pass.draw_indexed(0..self.nb_indices, 0, 0..self.nb_instances);
     }
+
+    fn draw_indirect<'a>(
+        &'a mut self,
+        render_pass: &mut RenderPass<'a>,
+        viewer_bind_group: &'a wgpu::BindGroup,
+        model_bind_group: &'a wgpu::BindGroup,
+        cmd_buffer: &'a wgpu::Buffer,
+        offset: u64,
+    ) {
+        self.prepare_draw(render_pass, viewer_bind_group, model_bind_group);
+        render_pass.draw_indexed_indirect(cmd_buffer, offset);
+    }
+
+    fn indirect_command(&self) -> Option<wgpu::util::DrawIndexedIndirect> {
+        Some(wgpu::util::DrawIndexedIndirect {
+            vertex_count: self.nb_indices,
+            instance_count: self.nb_instances,
+            base_index: 0,
+            vertex_offset: 0,
+            base_instance: 0,
+        })
+    }
+}
+
+/// A buffer holding the arguments of the draw calls of several `RawDrawer`s.
+///
+/// The arguments of all the draw calls are uploaded with a single write to the queue.
+pub struct IndirectCommandBuffer {
+    buffer: wgpu::Buffer,
+    /// The number of commands that fit in `buffer`
+    capacity: usize,
+    device: Rc<Device>,
+    queue: Rc<Queue>,
+}
+
+impl IndirectCommandBuffer {
+    /// The size in bytes of the arguments of an indexed draw call
+    const COMMAND_SIZE: u64 = std::mem::size_of::<wgpu::util::DrawIndexedIndirect>() as u64;
+
+    pub fn new(device: Rc<Device>, queue: Rc<Queue>) -> Self {
+        let capacity = 16;
+        let buffer = Self::create_buffer(device.as_ref(), capacity);
+        Self {
+            buffer,
+            capacity,
+            device,
+            queue,
+        }
+    }
+
+    fn create_buffer(device: &Device, capacity: usize) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("indirect commands"),
+            size: capacity as u64 * Self::COMMAND_SIZE,
+            usage: wgpu::BufferUsages::INDIRECT | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    /// Write the arguments of the draw calls of `drawers`. The arguments of the `i`-th drawer are
+    /// written at `Self::offset(i)`.
+    pub fn write_commands<R>(&mut self, drawers: &[&mut dyn RawDrawer<RawInstance = R>]) {
+        if drawers.is_empty() {
+            return;
+        }
+        if drawers.len() > self.capacity {
+            self.capacity = drawers.len().next_power_of_two();
+            self.buffer = Self::create_buffer(self.device.as_ref(), self.capacity);
+        }
+        let mut bytes = Vec::with_capacity(drawers.len() * Self::COMMAND_SIZE as usize);
+        for drawer in drawers.iter() {
+            if let Some(command) = drawer.indirect_command() {
+                bytes.extend_from_slice(command.as_bytes());
+            } else {
+                // Keep the arguments of the next drawers at their offset
+                bytes.resize(bytes.len() + Self::COMMAND_SIZE as usize, 0);
+            }
+        }
+        self.queue.write_buffer(&self.buffer, 0, bytes.as_slice());
+    }
+
+    /// The offset at which the arguments of the `i`-th draw call are written
+    pub fn offset(i: usize) -> u64 {
+        i as u64 * Self::COMMAND_SIZE
+    }
+
+    pub fn get_buffer(&self) -> &wgpu::Buffer {
+        &self.buffer
+    }
 }
@@ -16,11 +16,12 @@ ENSnano, a 3d graphical application for DNA nanostructures.
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 use iced_wgpu::wgpu;
+use std::rc::Rc;
 use ultraviolet::{Vec2, Vec3, Vec4};
-use wgpu::{include_spirv, Device};
+use wgpu::{include_wgsl, Device};
 
 use super::instances_drawer::{Instanciable, RessourceProvider, Vertexable};
-use crate::text::Letter;
+use crate::text::{Glyph, GlyphAtlas};
 
 #[derive(Debug, Clone)]
 pub struct LetterInstance {
@@ -29,6 +30,30 @@ pub struct LetterInstance {
     pub design_id: u32,
     pub scale: f32,
     pub shift: Vec3,
+    /// When true, the letter fades out as it gets far from the camera
+    pub fade: bool,
+    pub character: char,
+}
+
+/// A letter together with the location of its character in the glyph atlas
+#[derive(Debug, Clone)]
+pub struct GlyphInstance {
+    letter: LetterInstance,
+    glyph: Glyph,
+}
+
+impl GlyphInstance {
+    /// Return the instances drawing `letters`. The letters whose character is not in `atlas` are
+    /// skipped.
+    pub fn from_letters(letters: Vec<LetterInstance>, atlas: &GlyphAtlas) -> Vec<Self> {
+        letters
+            .into_iter()
+            .filter_map(|letter| {
+                let glyph = *atlas.glyph(letter.character)?;
+                Some(Self { letter, glyph })
+            })
+            .collect()
+    }
 }
 
 #[repr(C)]
@@ -39,9 +64,15 @@ pub struct RawLetter {
     pub color: Vec4,
     pub shift: Vec3,
     pub scale: f32,
+    pub quad_min: Vec2,
+    pub quad_max: Vec2,
+    pub uv_min: Vec2,
+    pub uv_max: Vec2,
+    pub fade: u32,
+    _padding: [u32; 3],
 }
 
-impl RessourceProvider for Letter {
+impl RessourceProvider for Rc<GlyphAtlas> {
     fn ressources_layout() -> &'static [wgpu::BindGroupLayoutEntry] {
         &[
             wgpu::BindGroupLayoutEntry {
@@ -66,15 +97,6 @@ impl RessourceProvider for Letter {
         ]
     }
 
-    /// This methods allows the ressource tho provide the vertex buffer. If the return value is
-    /// Some, it takes priority over the Instanciable's vertices.
-    fn vertex_buffer_desc() -> Option<wgpu::VertexBufferLayout<'static>>
-    where
-        Self: Sized,
-    {
-        Some(crate::text::Vertex::desc())
-    }
-
     fn ressources(&self) -> Vec<wgpu::BindGroupEntry> {
         vec![
             wgpu::BindGroupEntry {
@@ -87,14 +109,6 @@ impl RessourceProvider for Letter {
             },
         ]
     }
-
-    fn vertex_buffer(&self) -> Option<&wgpu::Buffer> {
-        Some(&self.vertex_buffer)
-    }
-
-    fn index_buffer(&self) -> Option<&wgpu::Buffer> {
-        Some(&self.index_buffer)
-    }
 }
 
 #[repr(C)]
@@ -119,18 +133,25 @@ impl Vertexable for LetterVertex {
     }
 }
 
-impl Instanciable for LetterInstance {
-    type Ressource = Letter;
+impl Instanciable for GlyphInstance {
+    type Ressource = Rc<GlyphAtlas>;
     type Vertex = LetterVertex;
     type RawInstance = RawLetter;
 
     fn to_raw_instance(&self) -> RawLetter {
+        let letter = &self.letter;
         RawLetter {
-            position: self.position,
-            color: self.color,
-            design_id: self.design_id,
-            scale: self.scale,
-            shift: self.shift,
+            position: letter.position,
+            color: letter.color,
+            design_id: letter.design_id,
+            scale: letter.scale,
+            shift: letter.shift,
+            quad_min: self.glyph.quad_min,
+            quad_max: self.glyph.quad_max,
+            uv_min: self.glyph.uv_min,
+            uv_max: self.glyph.uv_max,
+            fade: letter.fade as u32,
+            _padding: [0; 3],
         }
     }
 
@@ -160,11 +181,11 @@ impl Instanciable for LetterInstance {
     }
 
     fn vertex_module(device: &Device) -> wgpu::ShaderModule {
-        device.create_shader_module(&include_spirv!("letter.vert.spv"))
+        device.create_shader_module(&include_wgsl!("letter_vert.wgsl"))
     }
 
     fn fragment_module(device: &Device) -> wgpu::ShaderModule {
-        device.create_shader_module(&include_spirv!("letter.frag.spv"))
+        device.create_shader_module(&include_wgsl!("letter_frag.wgsl"))
     }
 
     fn alpha_to_coverage_enabled() -> bool {
@@ -0,0 +1,19 @@
+// Fragment shader of the letters drawn in the 3D view.
+
+[[group(3), binding(0)]]
+var t_diffuse: texture_2d<f32>;
+[[group(3), binding(1)]]
+var s_diffuse: sampler;
+
+[[stage(fragment)]]
+fn main(
+    [[location(0)]] v_color: vec4<f32>,
+    [[location(1)]] v_tex_coords: vec2<f32>,
+) -> [[location(0)]] vec4<f32> {
+    let color = textureSample(t_diffuse, s_diffuse, v_tex_coords);
+    let alpha = color.w * v_color.w;
+    if (alpha < 0.01) {
+        discard;
+    }
+    return vec4<f32>(v_color.xyz, alpha);
+}
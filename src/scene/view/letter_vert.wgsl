@@ -0,0 +1,91 @@
+// Vertex shader of the letters drawn in the 3D view.
+//
+// Each instance is a unit quad that is stretched on the glyph of its character in the atlas.
+// The letters always face the camera. The letters of the bases fade out as they get far from
+// the camera, so that they only clutter the view when it is zoomed in.
+
+[[block]]
+struct Uniforms {
+    camera_position: vec4<f32>;
+    view: mat4x4<f32>;
+    proj: mat4x4<f32>;
+    inversed_view: mat4x4<f32>;
+};
+
+[[block]]
+struct ModelMatrices {
+    matrices: array<mat4x4<f32>>;
+};
+
+struct Instance {
+    position: vec3<f32>;
+    id: u32;
+    color: vec4<f32>;
+    shift: vec3<f32>;
+    scale: f32;
+    quad_min: vec2<f32>;
+    quad_max: vec2<f32>;
+    uv_min: vec2<f32>;
+    uv_max: vec2<f32>;
+    fade: u32;
+};
+
+[[block]]
+struct Instances {
+    instances: array<Instance>;
+};
+
+[[group(0), binding(0)]]
+var<uniform> uniforms: Uniforms;
+[[group(1), binding(0)]]
+var<storage, read> models: ModelMatrices;
+[[group(2), binding(0)]]
+var<storage, read> instances: Instances;
+
+// Distances to the camera between which the letters of the bases fade out
+let FADE_START: f32 = 20.0;
+let FADE_END: f32 = 40.0;
+
+struct VertexOutput {
+    [[builtin(position)]] position: vec4<f32>;
+    [[location(0)]] color: vec4<f32>;
+    [[location(1)]] tex_coords: vec2<f32>;
+};
+
+[[stage(vertex)]]
+fn main(
+    [[location(0)]] a_position: vec2<f32>,
+    [[builtin(instance_index)]] instance_idx: u32,
+) -> VertexOutput {
+    let instance = instances.instances[instance_idx];
+
+    let translation = mat4x4<f32>(
+        vec4<f32>(1.0, 0.0, 0.0, 0.0),
+        vec4<f32>(0.0, 1.0, 0.0, 0.0),
+        vec4<f32>(0.0, 0.0, 1.0, 0.0),
+        vec4<f32>(instance.position, 1.0),
+    );
+    let rotation = mat4x4<f32>(
+        vec4<f32>(uniforms.inversed_view[0].xyz, 0.0),
+        vec4<f32>(uniforms.inversed_view[1].xyz, 0.0),
+        vec4<f32>(uniforms.inversed_view[2].xyz, 0.0),
+        vec4<f32>(0.0, 0.0, 0.0, 1.0),
+    );
+    let model_matrix = models.matrices[instance.id] * translation * rotation;
+
+    let corner = mix(instance.quad_min, instance.quad_max, a_position);
+    let local_position = (corner + instance.shift.xy) * instance.scale * vec2<f32>(0.5, -0.5);
+    let model_space = model_matrix * vec4<f32>(local_position, 0.0, 1.0);
+
+    var opacity: f32 = 1.0;
+    if (instance.fade > 0u) {
+        let dist = length(uniforms.camera_position.xyz - model_space.xyz);
+        opacity = 1.0 - smoothstep(FADE_START, FADE_END, dist);
+    }
+
+    var out: VertexOutput;
+    out.position = uniforms.proj * (uniforms.view * model_space + vec4<f32>(0.0, 0.0, 0.25, 0.0));
+    out.color = vec4<f32>(instance.color.xyz, opacity);
+    out.tex_coords = mix(instance.uv_min, instance.uv_max, a_position);
+    return out;
+}
@@ -0,0 +1,432 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! Level of detail for the meshes used to draw DNA.
+//!
+//! A [LodDrawer](LodDrawer) draws its instances with a high-poly mesh when they are close to the
+//! camera and with a low-poly mesh otherwise. The choice is made on the GPU by a compute pass, so
+//! that the instances are uploaded only when they change and not each time the camera moves.
+
+use super::dna_obj::{DnaObject, RawDnaInstance};
+use super::instances_drawer::{InstanceDrawer, RawDrawer};
+use crate::consts::LOW_POLY_DISTANCE;
+use iced_wgpu::wgpu;
+use std::rc::Rc;
+use ultraviolet::{Mat4, Vec3};
+use wgpu::util::DeviceExt;
+use wgpu::{Device, Queue, RenderPass};
+
+const WORKGROUP_SIZE: u32 = 64;
+const INSTANCE_SIZE: u64 = std::mem::size_of::<RawDnaInstance>() as u64;
+const MODEL_SIZE: u64 = std::mem::size_of::<Mat4>() as u64;
+/// The size in bytes of the arguments of an indexed draw call
+const COMMAND_SIZE: u64 = std::mem::size_of::<wgpu::util::DrawIndexedIndirect>() as u64;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct LodUniforms {
+    camera_position: [f32; 3],
+    distance: f32,
+    nb_instances: u32,
+    _padding: [u32; 3],
+}
+
+/// The buffers written by the selection pass and their bind groups
+struct LodBuffers {
+    /// The number of instances that fit in the buffers
+    capacity: u64,
+    source: wgpu::Buffer,
+    selection_bind_group: wgpu::BindGroup,
+    high_bind_group: wgpu::BindGroup,
+    low_bind_group: wgpu::BindGroup,
+}
+
+/// Draws instances with a high-poly mesh `H` near the camera and a low-poly mesh `L` far from it.
+pub struct LodDrawer<H: DnaObject, L: DnaObject> {
+    high_drawer: InstanceDrawer<H>,
+    low_drawer: InstanceDrawer<L>,
+    device: Rc<Device>,
+    queue: Rc<Queue>,
+    pipeline: wgpu::ComputePipeline,
+    selection_layout: wgpu::BindGroupLayout,
+    instances_layout: wgpu::BindGroupLayout,
+    uniforms: wgpu::Buffer,
+    models: wgpu::Buffer,
+    /// The number of model matrices that fit in `models`
+    models_capacity: u64,
+    /// The arguments of the draw calls of the high-poly and low-poly meshes, in that order
+    commands: wgpu::Buffer,
+    /// The arguments of the draw calls with no instances, copied in `commands` before each
+    /// selection
+    initial_commands: wgpu::Buffer,
+    buffers: LodBuffers,
+    nb_instances: u32,
+}
+
+impl<H: DnaObject, L: DnaObject> LodDrawer<H, L> {
+    pub fn new<S: AsRef<str>>(
+        device: Rc<Device>,
+        queue: Rc<Queue>,
+        viewer_desc: &wgpu::BindGroupLayoutDescriptor<'static>,
+        models_desc: &wgpu::BindGroupLayoutDescriptor<'static>,
+        label: S,
+    ) -> Self {
+        let label = label.as_ref();
+        let high_drawer = InstanceDrawer::new(
+            device.clone(),
+            queue.clone(),
+            viewer_desc,
+            models_desc,
+            (),
+            false,
+            label,
+        );
+        let low_drawer = InstanceDrawer::new(
+            device.clone(),
+            queue.clone(),
+            viewer_desc,
+            models_desc,
+            (),
+            false,
+            format!("low poly {}", label),
+        );
+
+        let storage_entry = |binding, read_only| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let selection_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                storage_entry(1, true),
+                storage_entry(2, true),
+                storage_entry(3, false),
+                storage_entry(4, false),
+                storage_entry(5, false),
+            ],
+            label: Some("level of detail layout"),
+        });
+        // Must be identical to the layout of the instances bind group of the `InstanceDrawer`s
+        let instances_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: None,
+        });
+
+        let shader = device.create_shader_module(&wgpu::include_wgsl!("level_of_detail.wgsl"));
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("level of detail pipeline layout"),
+            bind_group_layouts: &[&selection_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("level of detail"),
+            layout: Some(&pipeline_layout),
+            module: &shader,
+            entry_point: "main",
+        });
+
+        let uniforms = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("level of detail uniforms"),
+            size: std::mem::size_of::<LodUniforms>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let models_capacity = 1;
+        let models = Self::create_models_buffer(device.as_ref(), models_capacity);
+        queue.write_buffer(&models, 0, bytemuck::cast_slice(&[Mat4::identity()]));
+
+        let mut initial_commands = Vec::with_capacity(2 * COMMAND_SIZE as usize);
+        for nb_indices in [high_drawer.nb_indices(), low_drawer.nb_indices()] {
+            let command = wgpu::util::DrawIndexedIndirect {
+                vertex_count: nb_indices,
+                instance_count: 0,
+                base_index: 0,
+                vertex_offset: 0,
+                base_instance: 0,
+            };
+            initial_commands.extend_from_slice(command.as_bytes());
+        }
+        let initial_commands = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("level of detail initial commands"),
+            contents: initial_commands.as_slice(),
+            usage: wgpu::BufferUsages::COPY_SRC,
+        });
+        let commands = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("level of detail commands"),
+            size: 2 * COMMAND_SIZE,
+            usage: wgpu::BufferUsages::STORAGE
+                | wgpu::BufferUsages::INDIRECT
+                | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let buffers = Self::create_buffers(
+            device.as_ref(),
+            &selection_layout,
+            &instances_layout,
+            &uniforms,
+            &models,
+            &commands,
+            1,
+        );
+
+        Self {
+            high_drawer,
+            low_drawer,
+            device,
+            queue,
+            pipeline,
+            selection_layout,
+            instances_layout,
+            uniforms,
+            models,
+            models_capacity,
+            commands,
+            initial_commands,
+            buffers,
+            nb_instances: 0,
+        }
+    }
+
+    fn create_models_buffer(device: &Device, capacity: u64) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("level of detail models"),
+            size: capacity * MODEL_SIZE,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    fn create_buffers(
+        device: &Device,
+        selection_layout: &wgpu::BindGroupLayout,
+        instances_layout: &wgpu::BindGroupLayout,
+        uniforms: &wgpu::Buffer,
+        models: &wgpu::Buffer,
+        commands: &wgpu::Buffer,
+        capacity: u64,
+    ) -> LodBuffers {
+        let create_buffer = |label, usage| {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(label),
+                size: capacity * INSTANCE_SIZE,
+                usage,
+                mapped_at_creation: false,
+            })
+        };
+        let source = create_buffer(
+            "level of detail source",
+            wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+        );
+        let high = create_buffer("level of detail high poly", wgpu::BufferUsages::STORAGE);
+        let low = create_buffer("level of detail low poly", wgpu::BufferUsages::STORAGE);
+
+        let selection_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: selection_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniforms.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: models.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: source.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: high.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: low.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 5,
+                    resource: commands.as_entire_binding(),
+                },
+            ],
+            label: Some("level of detail selection"),
+        });
+        let instances_bind_group = |buffer: &wgpu::Buffer| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                layout: instances_layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.as_entire_binding(),
+                }],
+                label: Some("level of detail instances"),
+            })
+        };
+        let high_bind_group = instances_bind_group(&high);
+        let low_bind_group = instances_bind_group(&low);
+
+        LodBuffers {
+            capacity,
+            source,
+            selection_bind_group,
+            high_bind_group,
+            low_bind_group,
+        }
+    }
+
+    fn recreate_buffers(&mut self, capacity: u64) {
+        self.buffers = Self::create_buffers(
+            self.device.as_ref(),
+            &self.selection_layout,
+            &self.instances_layout,
+            &self.uniforms,
+            &self.models,
+            &self.commands,
+            capacity,
+        );
+    }
+
+    /// Update the model matrices of the designs, used to compute the position of the instances
+    pub fn update_models(&mut self, matrices: &[Mat4]) {
+        if matrices.is_empty() {
+            return;
+        }
+        if matrices.len() as u64 > self.models_capacity {
+            self.models_capacity = matrices.len().next_power_of_two() as u64;
+            self.models = Self::create_models_buffer(self.device.as_ref(), self.models_capacity);
+            self.recreate_buffers(self.buffers.capacity);
+        }
+        self.queue
+            .write_buffer(&self.models, 0, bytemuck::cast_slice(matrices));
+    }
+
+    /// Split the instances between the high-poly and the low-poly meshes according to their
+    /// distance to `camera_position`.
+    ///
+    /// This must be called at most once before the queue is submitted: the uniforms are written
+    /// by the queue before any command of `encoder` is executed, so all the selections recorded
+    /// before the submission would use the last `camera_position`.
+    pub fn select(&self, encoder: &mut wgpu::CommandEncoder, camera_position: Vec3) {
+        let uniforms = LodUniforms {
+            camera_position: camera_position.into(),
+            distance: LOW_POLY_DISTANCE,
+            nb_instances: self.nb_instances,
+            _padding: [0; 3],
+        };
+        self.queue
+            .write_buffer(&self.uniforms, 0, bytemuck::cast_slice(&[uniforms]));
+        encoder.copy_buffer_to_buffer(
+            &self.initial_commands,
+            0,
+            &self.commands,
+            0,
+            2 * COMMAND_SIZE,
+        );
+        if self.nb_instances > 0 {
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("level of detail"),
+            });
+            compute_pass.set_pipeline(&self.pipeline);
+            compute_pass.set_bind_group(0, &self.buffers.selection_bind_group, &[]);
+            let nb_workgroups = (self.nb_instances + WORKGROUP_SIZE - 1) / WORKGROUP_SIZE;
+            compute_pass.dispatch(nb_workgroups, 1, 1);
+        }
+    }
+}
+
+impl<H: DnaObject, L: DnaObject> RawDrawer for LodDrawer<H, L> {
+    type RawInstance = RawDnaInstance;
+
+    fn new_instances_raw(&mut self, instances_raw: &Vec<RawDnaInstance>) {
+        let nb_instances = instances_raw.len() as u64;
+        if nb_instances > self.buffers.capacity {
+            self.recreate_buffers(nb_instances.next_power_of_two());
+        }
+        self.nb_instances = instances_raw.len() as u32;
+        if !instances_raw.is_empty() {
+            self.queue.write_buffer(
+                &self.buffers.source,
+                0,
+                bytemuck::cast_slice(instances_raw.as_slice()),
+            );
+        }
+    }
+
+    fn draw<'a>(
+        &'a mut self,
+        render_pass: &mut RenderPass<'a>,
+        viewer_bind_group: &'a wgpu::BindGroup,
+        model_bind_group: &'a wgpu::BindGroup,
+    ) {
+        self.high_drawer.draw_instances_indirect(
+            render_pass,
+            viewer_bind_group,
+            model_bind_group,
+            &self.buffers.high_bind_group,
+            &self.commands,
+            0,
+        );
+        self.low_drawer.draw_instances_indirect(
+            render_pass,
+            viewer_bind_group,
+            model_bind_group,
+            &self.buffers.low_bind_group,
+            &self.commands,
+            COMMAND_SIZE,
+        );
+    }
+
+    /// The arguments of the draw calls of a `LodDrawer` are written by the selection pass, so
+    /// `cmd_buffer` is ignored.
+    fn draw_indirect<'a>(
+        &'a mut self,
+        render_pass: &mut RenderPass<'a>,
+        viewer_bind_group: &'a wgpu::BindGroup,
+        model_bind_group: &'a wgpu::BindGroup,
+        _cmd_buffer: &'a wgpu::Buffer,
+        _offset: u64,
+    ) {
+        self.draw(render_pass, viewer_bind_group, model_bind_group)
+    }
+
+    fn indirect_command(&self) -> Option<wgpu::util::DrawIndexedIndirect> {
+        None
+    }
+}
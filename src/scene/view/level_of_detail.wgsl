@@ -0,0 +1,78 @@
+// Selection of the level of detail of the spheres and tubes.
+//
+// Each instance is copied either in the list of instances drawn with the high-poly mesh or in the
+// list of instances drawn with the low-poly mesh, depending on its distance to the camera. The
+// number of instances of each list is written directly in the arguments of the indirect draw calls
+// that draw them.
+
+struct Instance {
+    model: mat4x4<f32>;
+    color: vec4<f32>;
+    scale: vec3<f32>;
+    id: u32;
+    inversed_model: mat4x4<f32>;
+};
+
+[[block]]
+struct Instances {
+    instances: array<Instance>;
+};
+
+[[block]]
+struct Models {
+    matrices: array<mat4x4<f32>>;
+};
+
+struct DrawCommand {
+    index_count: u32;
+    instance_count: atomic<u32>;
+    first_index: u32;
+    base_vertex: i32;
+    first_instance: u32;
+};
+
+[[block]]
+struct DrawCommands {
+    high: DrawCommand;
+    low: DrawCommand;
+};
+
+[[block]]
+struct LodUniforms {
+    camera_position: vec3<f32>;
+    // Distance to the camera beyond which the low-poly mesh is used
+    distance: f32;
+    nb_instances: u32;
+};
+
+[[group(0), binding(0)]]
+var<uniform> uniforms: LodUniforms;
+[[group(0), binding(1)]]
+var<storage, read> models: Models;
+[[group(0), binding(2)]]
+var<storage, read> source: Instances;
+[[group(0), binding(3)]]
+var<storage, read_write> high: Instances;
+[[group(0), binding(4)]]
+var<storage, read_write> low: Instances;
+[[group(0), binding(5)]]
+var<storage, read_write> commands: DrawCommands;
+
+[[stage(compute), workgroup_size(64)]]
+fn main([[builtin(global_invocation_id)]] global_id: vec3<u32>) {
+    let idx = global_id.x;
+    if (idx >= uniforms.nb_instances) {
+        return;
+    }
+    let instance = source.instances[idx];
+    // The upper byte of the identifier is the identifier of the design
+    let model = models.matrices[instance.id >> 24u];
+    let position = model * instance.model * vec4<f32>(0.0, 0.0, 0.0, 1.0);
+    if (distance(position.xyz, uniforms.camera_position) < uniforms.distance) {
+        let slot = atomicAdd(&commands.high.instance_count, 1u);
+        high.instances[slot] = instance;
+    } else {
+        let slot = atomicAdd(&commands.low.instance_count, 1u);
+        low.instances[slot] = instance;
+    }
+}
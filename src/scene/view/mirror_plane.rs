@@ -0,0 +1,91 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! A semi-transparent square displayed while the user chooses the plane across which a grid is
+//! mirrored.
+
+use iced_wgpu::wgpu;
+use wgpu::{include_spirv, Device, PrimitiveTopology};
+
+use super::grid_disc::GridDiscVertex;
+use super::instances_drawer::Instanciable;
+use ensnano_interactor::MirrorAxis;
+use ultraviolet::{Mat4, Rotor3, Vec3, Vec4};
+
+/// Half the length of the sides of the square, in nanometers
+const MIRROR_PLANE_HALF_SIZE: f32 = 50.;
+const MIRROR_PLANE_COLOR: u32 = 0x50_A0_C0_FF;
+
+#[derive(Debug, Clone)]
+pub struct MirrorPlane {
+    pub axis: MirrorAxis,
+}
+
+/// Has the same layout as the instances of `GridDisc` so that the same shaders can be used
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct MirrorPlaneRaw {
+    model_matrix: Mat4,
+    color: Vec4,
+    half_size: f32,
+    model_id: u32,
+    _padding: [u32; 2],
+}
+
+impl Instanciable for MirrorPlane {
+    type Vertex = GridDiscVertex;
+    type RawInstance = MirrorPlaneRaw;
+    type Ressource = ();
+
+    /// A square of side 2 in the local y/z plane
+    fn vertices() -> Vec<GridDiscVertex> {
+        let color = 0xFF_FF_FF_FF; // we will multiply by the instance's color in the fragment shader
+        vec![
+            GridDiscVertex::new(Vec3::new(0., -1., -1.), color),
+            GridDiscVertex::new(Vec3::new(0., 1., -1.), color),
+            GridDiscVertex::new(Vec3::new(0., 1., 1.), color),
+            GridDiscVertex::new(Vec3::new(0., -1., 1.), color),
+        ]
+    }
+
+    fn indices() -> Vec<u16> {
+        vec![0, 1, 2, 0, 2, 3]
+    }
+
+    fn primitive_topology() -> PrimitiveTopology {
+        PrimitiveTopology::TriangleList
+    }
+
+    fn vertex_module(device: &Device) -> wgpu::ShaderModule {
+        device.create_shader_module(&include_spirv!("grid_disc.vert.spv"))
+    }
+
+    fn fragment_module(device: &Device) -> wgpu::ShaderModule {
+        device.create_shader_module(&include_spirv!("grid_disc.frag.spv"))
+    }
+
+    fn to_raw_instance(&self) -> MirrorPlaneRaw {
+        let orientation = Rotor3::from_rotation_between(Vec3::unit_x(), self.axis.normal());
+        MirrorPlaneRaw {
+            model_matrix: orientation.into_matrix().into_homogeneous(),
+            color: crate::utils::instance::Instance::color_from_au32(MIRROR_PLANE_COLOR),
+            half_size: MIRROR_PLANE_HALF_SIZE,
+            model_id: 0,
+            _padding: [0, 0],
+        }
+    }
+}
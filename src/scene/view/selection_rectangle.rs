@@ -0,0 +1,157 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! The rectangle drawn over the scene while the user drags it to select the nucleotides that it
+//! contains.
+
+use iced_wgpu::wgpu;
+use std::rc::Rc;
+use wgpu::util::DeviceExt;
+use wgpu::{Device, Queue};
+
+const SELECT_COLOR: [f32; 4] = [0.26, 0.64, 0.85, 0.3];
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct RectangleUniforms {
+    /// The corners of the rectangle in normalized device coordinates, as
+    /// `[min_x, min_y, max_x, max_y]`
+    corners: [f32; 4],
+    color: [f32; 4],
+}
+
+pub struct SelectionRectangle {
+    queue: Rc<Queue>,
+    uniforms: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    pipeline: wgpu::RenderPipeline,
+    /// True if the rectangle must be drawn
+    visible: bool,
+}
+
+impl SelectionRectangle {
+    pub fn new(device: Rc<Device>, queue: Rc<Queue>) -> Self {
+        let uniforms = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("selection rectangle uniforms"),
+            contents: bytemuck::cast_slice(&[RectangleUniforms {
+                corners: [0.; 4],
+                color: SELECT_COLOR,
+            }]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let bg_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some("selection rectangle layout"),
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &bg_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniforms.as_entire_binding(),
+            }],
+            label: Some("selection rectangle bind group"),
+        });
+
+        let shader = device.create_shader_module(&wgpu::include_wgsl!("selection_rectangle.wgsl"));
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("selection rectangle pipeline layout"),
+            bind_group_layouts: &[&bg_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("selection rectangle"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[wgpu::ColorTargetState {
+                    format: wgpu::TextureFormat::Bgra8UnormSrgb,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                }],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleStrip,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: Default::default(),
+        });
+
+        Self {
+            queue,
+            uniforms,
+            bind_group,
+            pipeline,
+            visible: false,
+        }
+    }
+
+    /// Set the opposite corners of the rectangle, given as fractions of the size of the drawing
+    /// area, or hide the rectangle if `corners` is `None`.
+    pub fn update_corners(&mut self, corners: Option<[(f32, f32); 2]>) {
+        self.visible = corners.is_some();
+        if let Some([c1, c2]) = corners {
+            // The y axis of the normalized device coordinates points up
+            let to_ndc = |(x, y): (f32, f32)| (2. * x - 1., 1. - 2. * y);
+            let (x1, y1) = to_ndc(c1);
+            let (x2, y2) = to_ndc(c2);
+            let uniforms = RectangleUniforms {
+                corners: [x1.min(x2), y1.min(y2), x1.max(x2), y1.max(y2)],
+                color: SELECT_COLOR,
+            };
+            self.queue
+                .write_buffer(&self.uniforms, 0, bytemuck::cast_slice(&[uniforms]));
+        }
+    }
+
+    /// Draw the rectangle over the content of `target`, if it is visible.
+    pub fn draw(&self, encoder: &mut wgpu::CommandEncoder, target: &wgpu::TextureView) {
+        if !self.visible {
+            return;
+        }
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("selection rectangle"),
+            color_attachments: &[wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: true,
+                },
+            }],
+            depth_stencil_attachment: None,
+        });
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.draw(0..4, 0..1);
+    }
+}
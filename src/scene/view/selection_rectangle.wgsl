@@ -0,0 +1,30 @@
+// Rectangle drawn over the scene while the user drags it to select nucleotides.
+
+[[block]]
+struct RectangleUniforms {
+    // min_x, min_y, max_x, max_y in normalized device coordinates
+    corners: vec4<f32>;
+    color: vec4<f32>;
+};
+
+[[group(0), binding(0)]]
+var<uniform> uniforms: RectangleUniforms;
+
+struct VertexOutput {
+    [[builtin(position)]] position: vec4<f32>;
+};
+
+[[stage(vertex)]]
+fn vs_main([[builtin(vertex_index)]] idx: u32) -> VertexOutput {
+    // The four corners of the rectangle, drawn as a triangle strip
+    let x = select(uniforms.corners.x, uniforms.corners.z, (idx & 1u) == 1u);
+    let y = select(uniforms.corners.y, uniforms.corners.w, (idx & 2u) == 2u);
+    var out: VertexOutput;
+    out.position = vec4<f32>(x, y, 0.0, 1.0);
+    return out;
+}
+
+[[stage(fragment)]]
+fn fs_main() -> [[location(0)]] vec4<f32> {
+    return uniforms.color;
+}
@@ -0,0 +1,214 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! A post-processing pass that maps the colors of the scene to the range of the screen.
+//!
+//! The scene is always resolved into a floating point texture, so that the colors can exceed 1.
+//! This pass reads this texture and writes the tone mapped image in the target.
+
+use super::texture::SampledTexture;
+use super::HDR_FORMAT;
+use crate::PhySize;
+use ensnano_interactor::graphics::{ToneMappingOperator, ToneMappingParameters};
+use iced_wgpu::wgpu;
+use std::rc::Rc;
+use wgpu::util::DeviceExt;
+use wgpu::{Device, Queue};
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct ToneMappingUniforms {
+    operator: u32,
+    exposure: f32,
+    // Uniform buffers must have a size that is a multiple of 16 bytes
+    _padding: [u32; 2],
+}
+
+impl ToneMappingUniforms {
+    fn new(parameters: &ToneMappingParameters) -> Self {
+        let operator = match parameters.operator {
+            ToneMappingOperator::Linear => 0,
+            ToneMappingOperator::Reinhard => 1,
+            ToneMappingOperator::AcesFilmic => 2,
+            ToneMappingOperator::Uncharted2 => 3,
+        };
+        Self {
+            operator,
+            exposure: parameters.exposure,
+            _padding: [0; 2],
+        }
+    }
+}
+
+pub struct ToneMapping {
+    device: Rc<Device>,
+    queue: Rc<Queue>,
+    /// The texture in which the scene is resolved before being tone mapped
+    source: SampledTexture,
+    uniforms: wgpu::Buffer,
+    bg_layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl ToneMapping {
+    pub fn new(device: Rc<Device>, queue: Rc<Queue>, area_size: &PhySize) -> Self {
+        let source = SampledTexture::create_target_texture_with_format(
+            device.as_ref(),
+            area_size,
+            HDR_FORMAT,
+        );
+        let uniforms = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("tone mapping uniforms"),
+            contents: bytemuck::cast_slice(&[ToneMappingUniforms::new(&Default::default())]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let bg_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+            label: Some("tone mapping layout"),
+        });
+        let bind_group = Self::create_bind_group(device.as_ref(), &bg_layout, &source, &uniforms);
+
+        let shader = device.create_shader_module(&wgpu::include_wgsl!("tone_mapping.wgsl"));
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("tone mapping pipeline layout"),
+            bind_group_layouts: &[&bg_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("tone mapping"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[wgpu::ColorTargetState {
+                    format: wgpu::TextureFormat::Bgra8UnormSrgb,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                }],
+            }),
+            primitive: Default::default(),
+            depth_stencil: None,
+            multisample: Default::default(),
+        });
+
+        Self {
+            device,
+            queue,
+            source,
+            uniforms,
+            bg_layout,
+            bind_group,
+            pipeline,
+        }
+    }
+
+    fn create_bind_group(
+        device: &Device,
+        layout: &wgpu::BindGroupLayout,
+        source: &SampledTexture,
+        uniforms: &wgpu::Buffer,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&source.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: uniforms.as_entire_binding(),
+                },
+            ],
+            label: Some("tone mapping bind group"),
+        })
+    }
+
+    /// Must be called when the size of the drawing area changes.
+    pub fn resize(&mut self, area_size: &PhySize) {
+        self.source = SampledTexture::create_target_texture_with_format(
+            self.device.as_ref(),
+            area_size,
+            HDR_FORMAT,
+        );
+        self.bind_group = Self::create_bind_group(
+            self.device.as_ref(),
+            &self.bg_layout,
+            &self.source,
+            &self.uniforms,
+        );
+    }
+
+    pub fn set_parameters(&mut self, parameters: ToneMappingParameters) {
+        self.queue.write_buffer(
+            &self.uniforms,
+            0,
+            bytemuck::cast_slice(&[ToneMappingUniforms::new(&parameters)]),
+        );
+    }
+
+    /// The texture in which the scene must be resolved before being tone mapped
+    pub fn source_view(&self) -> &wgpu::TextureView {
+        &self.source.view
+    }
+
+    /// Tone map the content of the source texture and write the result in `target`.
+    pub fn draw(&self, encoder: &mut wgpu::CommandEncoder, target: &wgpu::TextureView) {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("tone mapping"),
+            color_attachments: &[wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: true,
+                },
+            }],
+            depth_stencil_attachment: None,
+        });
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
@@ -0,0 +1,82 @@
+// Tone mapping post-processing pass.
+//
+// The colors of the scene, which may exceed 1, are multiplied by the exposure and mapped to the
+// [0, 1] range by the selected operator.
+
+[[block]]
+struct ToneMappingUniforms {
+    operator: u32;
+    exposure: f32;
+};
+
+[[group(0), binding(0)]]
+var color_texture: texture_2d<f32>;
+[[group(0), binding(1)]]
+var<uniform> uniforms: ToneMappingUniforms;
+
+let LINEAR: u32 = 0u;
+let REINHARD: u32 = 1u;
+let ACES_FILMIC: u32 = 2u;
+let UNCHARTED2: u32 = 3u;
+
+struct VertexOutput {
+    [[builtin(position)]] position: vec4<f32>;
+};
+
+[[stage(vertex)]]
+fn vs_main([[builtin(vertex_index)]] idx: u32) -> VertexOutput {
+    // A single triangle that covers the whole target
+    let x = f32((idx << 1u) & 2u) * 2.0 - 1.0;
+    let y = f32(idx & 2u) * 2.0 - 1.0;
+    var out: VertexOutput;
+    out.position = vec4<f32>(x, y, 0.0, 1.0);
+    return out;
+}
+
+fn reinhard(color: vec3<f32>) -> vec3<f32> {
+    return color / (vec3<f32>(1.0) + color);
+}
+
+// Krzysztof Narkowicz's fit of the ACES filmic curve
+fn aces_filmic(color: vec3<f32>) -> vec3<f32> {
+    let a = 2.51;
+    let b = 0.03;
+    let c = 2.43;
+    let d = 0.59;
+    let e = 0.14;
+    return (color * (a * color + b)) / (color * (c * color + d) + e);
+}
+
+// John Hable's filmic curve used in Uncharted 2
+fn uncharted2_curve(x: vec3<f32>) -> vec3<f32> {
+    let a = 0.15;
+    let b = 0.50;
+    let c = 0.10;
+    let d = 0.20;
+    let e = 0.02;
+    let f = 0.30;
+    return ((x * (a * x + c * b) + d * e) / (x * (a * x + b) + d * f)) - e / f;
+}
+
+fn uncharted2(color: vec3<f32>) -> vec3<f32> {
+    let exposure_bias = 2.0;
+    let white_point = vec3<f32>(11.2);
+    return uncharted2_curve(exposure_bias * color) / uncharted2_curve(white_point);
+}
+
+[[stage(fragment)]]
+fn fs_main(input: VertexOutput) -> [[location(0)]] vec4<f32> {
+    let texel = textureLoad(color_texture, vec2<i32>(input.position.xy), 0);
+    let color = texel.rgb * uniforms.exposure;
+    var mapped: vec3<f32>;
+    if (uniforms.operator == REINHARD) {
+        mapped = reinhard(color);
+    } elseif (uniforms.operator == ACES_FILMIC) {
+        mapped = aces_filmic(color);
+    } elseif (uniforms.operator == UNCHARTED2) {
+        mapped = uncharted2(color);
+    } else {
+        mapped = color;
+    }
+    return vec4<f32>(clamp(mapped, vec3<f32>(0.0), vec3<f32>(1.0)), texel.a);
+}
@@ -16,7 +16,9 @@ ENSnano, a 3d graphical application for DNA nanostructures.
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 use super::camera::{CameraPtr, ProjectionPtr};
+use bytemuck::Zeroable;
 pub use ensnano_interactor::graphics::FogParameters;
+use ensnano_interactor::graphics::{LightingParameters, MAX_NB_LIGHTS};
 use ultraviolet::{Mat4, Vec3, Vec4};
 
 #[repr(C)] // We need this for Rust to store our data correctly for the shaders
@@ -33,6 +35,33 @@ pub struct Uniforms {
     pub make_fog: u32,
     pub fog_from_camera: u32,
     pub fog_alt_center: Vec3,
+    // The array of lights must be aligned on 16 bytes
+    _padding: u32,
+    pub lights: [LightUniform; MAX_NB_LIGHTS],
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+/// A directional light source, as seen by the shaders
+pub struct LightUniform {
+    /// The direction towards the light, expressed in world coordinates
+    pub direction: Vec3,
+    pub intensity: f32,
+    pub color: Vec3,
+    _padding: f32,
+}
+
+impl LightUniform {
+    fn from_parameters(camera: &CameraPtr, lighting: &LightingParameters) -> [Self; MAX_NB_LIGHTS] {
+        let rotor = camera.borrow().rotor.reversed();
+        let mut ret = [Self::zeroed(); MAX_NB_LIGHTS];
+        for (light, config) in ret.iter_mut().zip(lighting.lights.iter()) {
+            light.direction = rotor * config.direction_from_camera();
+            light.intensity = config.intensity;
+            light.color = config.color.into();
+        }
+        ret
+    }
 }
 
 impl Uniforms {
@@ -47,6 +76,8 @@ impl Uniforms {
             make_fog: false as u32,
             fog_from_camera: false as u32,
             fog_alt_center: Vec3::zero(),
+            _padding: 0,
+            lights: LightUniform::from_parameters(&camera, &Default::default()),
         }
     }
 
@@ -54,6 +85,7 @@ impl Uniforms {
         camera: CameraPtr,
         projection: ProjectionPtr,
         fog: &FogParameters,
+        lighting: &LightingParameters,
     ) -> Self {
         let mut make_fog = fog.fog_kind;
         if !fog.from_camera && fog.alt_fog_center.is_none() {
@@ -69,6 +101,8 @@ impl Uniforms {
             make_fog,
             fog_from_camera: fog.from_camera as u32,
             fog_alt_center: fog.alt_fog_center.unwrap_or(Vec3::zero()),
+            _padding: 0,
+            lights: LightUniform::from_parameters(&camera, lighting),
         }
     }
 }
@@ -50,6 +50,14 @@ impl Scheduler {
         self.applications.insert(element_type, application);
     }
 
+    /// Return true if the application on `area` must receive all the keyboard events
+    pub fn has_keyboard_priority(&self, area: ElementType) -> bool {
+        self.applications
+            .get(&area)
+            .map(|app| app.lock().unwrap().has_keyboard_priority())
+            .unwrap_or(false)
+    }
+
     /// Forwards an event to the appropriate application
     pub fn forward_event(
         &mut self,
@@ -0,0 +1,36 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! The constraints on the strands built by the user.
+//!
+//! They are stored in the `"strand_building"` field of `~/.ensnano/preferences.json`. They are
+//! read at start up and written back to this file each time they are modified.
+
+use crate::preferences;
+use ensnano_interactor::StrandBuildingParameters;
+
+const STRAND_BUILDING_FIELD: &str = "strand_building";
+
+/// Read the parameters saved by the user, or return the default parameters if there are none.
+pub fn load() -> StrandBuildingParameters {
+    preferences::read_field(STRAND_BUILDING_FIELD)
+}
+
+/// Save the strand building parameters with `preferences::write_field`
+pub fn save(parameters: &StrandBuildingParameters) -> std::io::Result<()> {
+    preferences::write_field(STRAND_BUILDING_FIELD, parameters)
+}
@@ -15,154 +15,145 @@ ENSnano, a 3d graphical application for DNA nanostructures.
     You should have received a copy of the GNU General Public License
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
-//! This module provides utilities for drawing text in the applications
+//! This module provides utilities for drawing text in the applications.
+//!
+//! All the characters are rasterized once in a single texture, the [GlyphAtlas](GlyphAtlas).
+//! Text is drawn by instancing a unit quad, each instance carrying the [Glyph](Glyph) of its
+//! character, so that any number of strings can be drawn in a single draw call.
 use fontdue::Font;
 use iced_wgpu::wgpu;
+use std::collections::HashMap;
 use std::convert::TryInto;
-use std::rc::Rc;
-use wgpu::{
-    util::DeviceExt, BindGroup, BindGroupLayout, Device, Extent3d, Queue, Sampler, Texture,
-    TextureView,
-};
+use ultraviolet::Vec2;
+use wgpu::{BindGroup, BindGroupLayout, Device, Extent3d, Queue, Sampler, Texture, TextureView};
 
-#[repr(C)]
-#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
-pub struct Vertex {
-    position: [f32; 2],
-    tex_coords: [f32; 2],
-}
+/// The size in pixels at which the characters are rasterized
+const FONT_SIZE: u32 = 224;
+/// The size in pixels of the square cell of the atlas in which each character is rasterized.
+/// The margin around the characters prevents them from bleeding on their neighbours in the
+/// smaller mip levels.
+const CELL_SIZE: u32 = 256;
+const MIP_LEVEL_COUNT: u32 = 6;
 
-const VERTEX_ATTR_ARRAY: [wgpu::VertexAttribute; 2] =
-    wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2];
-impl Vertex {
-    pub fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
-        use std::mem;
-        wgpu::VertexBufferLayout {
-            array_stride: mem::size_of::<Vertex>() as wgpu::BufferAddress,
-            step_mode: wgpu::VertexStepMode::Vertex,
-            attributes: &VERTEX_ATTR_ARRAY,
-        }
-    }
+/// The location of a character in a [GlyphAtlas](GlyphAtlas)
+#[derive(Debug, Clone, Copy)]
+pub struct Glyph {
+    /// The lower corner of the quad on which the character is drawn, in units of font size
+    pub quad_min: Vec2,
+    /// The upper corner of the quad on which the character is drawn, in units of font size
+    pub quad_max: Vec2,
+    /// The texture coordinates of `quad_min` in the atlas
+    pub uv_min: Vec2,
+    /// The texture coordinates of `quad_max` in the atlas
+    pub uv_max: Vec2,
+    pub advance: f32,
+    pub height: f32,
+    pub advance_height: f32,
 }
 
-const INDICES: &[u16] = &[0, 1, 2, 3];
-
-pub struct Letter {
+/// A texture in which a set of characters are rasterized
+pub struct GlyphAtlas {
     pub texture: Texture,
     pub texture_view: TextureView,
     pub sampler: Sampler,
     pub bind_group: BindGroup,
-    pub size: Extent3d,
-    pub vertex_buffer: wgpu::Buffer,
-    pub index_buffer: wgpu::Buffer,
     pub bind_group_layout: BindGroupLayout,
-    pub advance: f32,
-    pub height: f32,
-    pub advance_height: f32,
+    glyphs: HashMap<char, Glyph>,
 }
 
-const MAX_SIZE: u32 = 9;
-const MIN_SIZE: u32 = 1;
-const MIP_LEVEL_COUNT: u32 = MAX_SIZE - MIN_SIZE + 1;
+impl GlyphAtlas {
+    pub fn new<I: IntoIterator<Item = char>>(
+        characters: I,
+        device: &Device,
+        queue: &Queue,
+    ) -> Self {
+        let mut characters: Vec<char> = characters.into_iter().collect();
+        characters.sort_unstable();
+        characters.dedup();
 
-impl Letter {
-    pub fn new(character: char, device: Rc<Device>, queue: Rc<Queue>) -> Self {
+        let nb_columns = (characters.len() as f32).sqrt().ceil().max(1.) as u32;
+        let nb_rows = ((characters.len() as u32 + nb_columns - 1) / nb_columns).max(1);
         let size = Extent3d {
-            width: 1 << MAX_SIZE,
-            height: 1 << MAX_SIZE,
+            width: nb_columns * CELL_SIZE,
+            height: nb_rows * CELL_SIZE,
             depth_or_array_layers: 1,
         };
 
-        let diffuse_texture = device.create_texture(&wgpu::TextureDescriptor {
-            // All textures are stored as 3d, we represent our 2d texture
-            // by setting depth to 1.
+        let upper_case_font: &[u8] = include_bytes!("../../font/DejaVuSansMono.ttf");
+        let upper_case_font =
+            Font::from_bytes(upper_case_font, fontdue::FontSettings::default()).unwrap();
+        let font: &[u8] = include_bytes!("../../font/Inconsolata-Regular.ttf");
+        let font = Font::from_bytes(font, fontdue::FontSettings::default()).unwrap();
+
+        let font_size = FONT_SIZE as f32;
+        let mut pixels = vec![0u8; (size.width * size.height * 4) as usize];
+        let mut glyphs = HashMap::with_capacity(characters.len());
+        for (idx, c) in characters.iter().enumerate() {
+            let font = if c.is_ascii_uppercase() {
+                &upper_case_font
+            } else {
+                &font
+            };
+            let (metrics, bitmap) = font.rasterize(*c, font_size);
+            let cell_x = (idx as u32 % nb_columns) * CELL_SIZE;
+            let cell_y = (idx as u32 / nb_columns) * CELL_SIZE;
+            let width = metrics.width.min(CELL_SIZE as usize);
+            let height = metrics.height.min(CELL_SIZE as usize);
+            for x in 0..width {
+                for y in 0..height {
+                    let pixel = (cell_y as usize + y) * size.width as usize + cell_x as usize + x;
+                    // We use 4 bytes per pixel because we use BgraUnormSrgb format
+                    for i in 0..4 {
+                        pixels[4 * pixel + i] = bitmap[y * metrics.width + x];
+                    }
+                }
+            }
+
+            let quad_min = Vec2::new(metrics.xmin as f32, metrics.ymin as f32) / font_size;
+            let uv_min = Vec2::new(
+                cell_x as f32 / size.width as f32,
+                cell_y as f32 / size.height as f32,
+            );
+            glyphs.insert(
+                *c,
+                Glyph {
+                    quad_min,
+                    quad_max: quad_min + Vec2::new(width as f32, height as f32) / font_size,
+                    uv_min,
+                    uv_max: uv_min
+                        + Vec2::new(
+                            width as f32 / size.width as f32,
+                            height as f32 / size.height as f32,
+                        ),
+                    advance: metrics.advance_width / font_size,
+                    height: height as f32 / font_size,
+                    advance_height: metrics.ymin as f32 / font_size,
+                },
+            );
+        }
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
             size,
             mip_level_count: MIP_LEVEL_COUNT,
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
             format: crate::TEXTURE_FORMAT,
             usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
-            label: Some("diffuse_texture"),
+            label: Some("glyph_atlas"),
         });
 
-        let font: &[u8] = if character.is_ascii_uppercase() {
-            include_bytes!("../../font/DejaVuSansMono.ttf")
-        } else {
-            include_bytes!("../../font/Inconsolata-Regular.ttf")
-        };
-        let font = Font::from_bytes(font, fontdue::FontSettings::default()).unwrap();
-        let (metrics, _) = font.rasterize(character, size.height as f32);
-
-        let min_x = metrics.xmin as f32 / size.width as f32;
-        let max_x = min_x + metrics.width as f32 / size.width as f32;
-
-        let min_y = metrics.ymin as f32 / size.height as f32;
-        let max_y = min_y + metrics.height as f32 / size.height as f32;
-
-        let vertices: &[Vertex] = &[
-            Vertex {
-                position: [min_x, max_y],
-                tex_coords: [0., metrics.height as f32 / size.height as f32],
-            },
-            Vertex {
-                position: [min_x, min_y],
-                tex_coords: [0., 0.],
-            },
-            Vertex {
-                position: [max_x, max_y],
-                tex_coords: [
-                    metrics.width as f32 / size.width as f32,
-                    metrics.height as f32 / size.height as f32,
-                ],
-            },
-            Vertex {
-                position: [max_x, min_y],
-                tex_coords: [metrics.width as f32 / size.width as f32, 0.],
-            },
-        ];
-
-        let advance = metrics.advance_width / size.width as f32;
-        let height = metrics.height as f32 / size.height as f32;
-        let advance_height = metrics.ymin as f32 / size.height as f32;
-        let mut last_pixels = None;
-
+        let mut level_size = size;
         for mip_level in 0..MIP_LEVEL_COUNT {
-            let size = Extent3d {
-                width: 1 << (MAX_SIZE - mip_level),
-                height: 1 << (MAX_SIZE - mip_level),
-                depth_or_array_layers: 1,
-            };
-            let mut pixels = vec![0u8; (size.width * size.height * 4) as usize];
-
-            if let Some(ref previous) = last_pixels {
-                for x in 0..size.width as usize {
-                    for y in 0..size.height as usize {
-                        // We use 4 bytes per pixel because we use BgraUnormSrgb format
-                        let coverage =
-                            get_average_pixel_value(previous, x, y, 2 * size.width as usize);
-                        for i in 0..4 {
-                            pixels[4 * (y * size.width as usize + x) + i] = coverage
-                        }
-                    }
-                }
-            } else {
-                let (metrics, bitmap) = font.rasterize(character, size.height as f32);
-
-                for x in 0..metrics.width {
-                    for y in 0..metrics.height {
-                        // We use 4 bytes per pixel because we use BgraUnormSrgb format
-                        for i in 0..4 {
-                            pixels[4 * (y * size.width as usize + x) + i] =
-                                bitmap[y * metrics.width + x];
-                        }
-                    }
-                }
+            if mip_level > 0 {
+                let previous_width = level_size.width as usize;
+                level_size.width /= 2;
+                level_size.height /= 2;
+                pixels = downsample(&pixels, previous_width, level_size);
             }
-
             queue.write_texture(
                 // Tells wgpu where to copy the pixel data
                 wgpu::ImageCopyTextureBase {
-                    texture: &diffuse_texture,
+                    texture: &texture,
                     mip_level,
                     origin: wgpu::Origin3d::ZERO,
                     aspect: Default::default(),
@@ -171,18 +162,15 @@ impl Letter {
                 // The layout of the texture
                 wgpu::ImageDataLayout {
                     offset: 0,
-                    bytes_per_row: (4 * size.width).try_into().ok(),
-                    rows_per_image: size.height.try_into().ok(),
+                    bytes_per_row: (4 * level_size.width).try_into().ok(),
+                    rows_per_image: level_size.height.try_into().ok(),
                 },
-                size,
+                level_size,
             );
-
-            last_pixels = Some(pixels);
         }
 
-        let diffuse_texture_view =
-            diffuse_texture.create_view(&wgpu::TextureViewDescriptor::default());
-        let diffuse_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+        let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
             address_mode_u: wgpu::AddressMode::ClampToEdge,
             address_mode_v: wgpu::AddressMode::ClampToEdge,
             address_mode_w: wgpu::AddressMode::ClampToEdge,
@@ -192,77 +180,80 @@ impl Letter {
             ..Default::default()
         });
 
-        let texture_bind_group_layout =
-            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                entries: &[
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 0,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Texture {
-                            multisampled: false,
-                            view_dimension: wgpu::TextureViewDimension::D2,
-                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                        },
-                        count: None,
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
                     },
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 1,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Sampler {
-                            comparison: false,
-                            filtering: true,
-                        },
-                        count: None,
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler {
+                        comparison: false,
+                        filtering: true,
                     },
-                ],
-                label: Some("texture_bind_group_layout"),
-            });
-        let diffuse_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            layout: &texture_bind_group_layout,
+                    count: None,
+                },
+            ],
+            label: Some("glyph_atlas_bind_group_layout"),
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &bind_group_layout,
             entries: &[
                 wgpu::BindGroupEntry {
                     binding: 0,
-                    resource: wgpu::BindingResource::TextureView(&diffuse_texture_view),
+                    resource: wgpu::BindingResource::TextureView(&texture_view),
                 },
                 wgpu::BindGroupEntry {
                     binding: 1,
-                    resource: wgpu::BindingResource::Sampler(&diffuse_sampler),
+                    resource: wgpu::BindingResource::Sampler(&sampler),
                 },
             ],
-            label: Some("diffuse_bind_group"),
-        });
-        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Vertex Buffer"),
-            contents: bytemuck::cast_slice(vertices),
-            usage: wgpu::BufferUsages::VERTEX,
-        });
-        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Index Buffer"),
-            contents: bytemuck::cast_slice(INDICES),
-            usage: wgpu::BufferUsages::INDEX,
+            label: Some("glyph_atlas_bind_group"),
         });
 
         Self {
-            size,
-            texture: diffuse_texture,
-            bind_group: diffuse_bind_group,
-            sampler: diffuse_sampler,
-            texture_view: diffuse_texture_view,
-            vertex_buffer,
-            index_buffer,
-            bind_group_layout: texture_bind_group_layout,
-            advance,
-            height,
-            advance_height,
+            texture,
+            texture_view,
+            sampler,
+            bind_group,
+            bind_group_layout,
+            glyphs,
+        }
+    }
+
+    /// Return the glyph of `character`, or `None` if `character` is not in the atlas
+    pub fn glyph(&self, character: char) -> Option<&Glyph> {
+        self.glyphs.get(&character)
+    }
+}
+
+/// Return the pixels of the mip level of size `size` that follows the level `pixels`
+fn downsample(pixels: &[u8], previous_width: usize, size: Extent3d) -> Vec<u8> {
+    let mut ret = vec![0u8; (size.width * size.height * 4) as usize];
+    for x in 0..size.width as usize {
+        for y in 0..size.height as usize {
+            let coverage = get_average_pixel_value(pixels, x, y, previous_width);
+            for i in 0..4 {
+                ret[4 * (y * size.width as usize + x) + i] = coverage
+            }
         }
     }
+    ret
 }
 
-fn get_average_pixel_value(pixels: &Vec<u8>, x: usize, y: usize, width: usize) -> u8 {
+fn get_average_pixel_value(pixels: &[u8], x: usize, y: usize, width: usize) -> u8 {
     let get = |x, y| pixels[4 * (y * width + x)] as u16;
     let sum = get(2 * x, 2 * y)
         + get(2 * x + 1, 2 * y)
-        + get(2 * x + 1, 2 * y)
+        + get(2 * x, 2 * y + 1)
         + get(2 * x + 1, 2 * y + 1);
     (sum / 4) as u8
 }
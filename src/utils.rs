@@ -65,6 +65,118 @@ impl BufferDimensions {
     }
 }
 
+/// Draw an image of `size` pixels with `draw` and return its pixels in RGBA format.
+///
+/// `draw` must record its commands in the given encoder, with the given texture view, in
+/// `Bgra8UnormSrgb` format, as target. An error is returned if the image is larger than what the
+/// device supports or if it could not be read back.
+pub fn render_to_rgba<F>(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    size: PhysicalSize<u32>,
+    draw: F,
+) -> std::io::Result<Vec<u8>>
+where
+    F: FnOnce(&mut wgpu::CommandEncoder, &wgpu::TextureView),
+{
+    use std::convert::TryInto;
+    use std::io::{Error, ErrorKind};
+    let max_dimension = device.limits().max_texture_dimension_2d;
+    if size.width > max_dimension || size.height > max_dimension {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "The image would measure {}x{} pixels, the graphics card supports at most {}x{}",
+                size.width, size.height, max_dimension, max_dimension
+            ),
+        ));
+    }
+    let extent = wgpu::Extent3d {
+        width: size.width,
+        height: size.height,
+        depth_or_array_layers: 1,
+    };
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        size: extent,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Bgra8UnormSrgb,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        label: Some("export texture"),
+    });
+    let texture_view = texture.create_view(&Default::default());
+    let mut encoder =
+        device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+    draw(&mut encoder, &texture_view);
+
+    let buffer_dimensions = BufferDimensions::new(extent.width as usize, extent.height as usize);
+    let staging_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        size: (buffer_dimensions.padded_bytes_per_row * buffer_dimensions.height) as u64,
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+        label: Some("export staging buffer"),
+    });
+    encoder.copy_texture_to_buffer(
+        wgpu::ImageCopyTexture {
+            texture: &texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d { x: 0, y: 0, z: 0 },
+            aspect: Default::default(),
+        },
+        wgpu::ImageCopyBuffer {
+            buffer: &staging_buffer,
+            layout: wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: (buffer_dimensions.padded_bytes_per_row as u32)
+                    .try_into()
+                    .ok(),
+                rows_per_image: None,
+            },
+        },
+        extent,
+    );
+    queue.submit(Some(encoder.finish()));
+
+    let buffer_slice = staging_buffer.slice(..);
+    let buffer_future = buffer_slice.map_async(wgpu::MapMode::Read);
+    device.poll(wgpu::Maintain::Wait);
+    futures::executor::block_on(buffer_future)
+        .map_err(|_| Error::new(ErrorKind::Other, "Could not read the rendered image"))?;
+    let pixels_slice = buffer_slice.get_mapped_range();
+    let mut pixels =
+        Vec::with_capacity(buffer_dimensions.unpadded_bytes_per_row * buffer_dimensions.height);
+    for row in pixels_slice.chunks(buffer_dimensions.padded_bytes_per_row) {
+        // The texture is in BGRA format
+        for bgra in row[..buffer_dimensions.unpadded_bytes_per_row].chunks(4) {
+            pixels.extend_from_slice(&[bgra[2], bgra[1], bgra[0], bgra[3]]);
+        }
+    }
+    drop(pixels_slice);
+    staging_buffer.unmap();
+    Ok(pixels)
+}
+
+/// Create a new directory in the temporary directory of the system, whose name starts with
+/// `prefix` and is unique to this call.
+pub fn unique_temp_dir(prefix: &str) -> std::io::Result<std::path::PathBuf> {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    loop {
+        let path = std::env::temp_dir().join(format!(
+            "{}_{}_{}",
+            prefix,
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        match std::fs::create_dir(&path) {
+            // The directory may have been left by a previous process with the same id
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => (),
+            result => return result.map(|_| path),
+        }
+    }
+}
+
 pub fn new_color(color_idx: &mut usize) -> u32 {
     let color = {
         let hue = (*color_idx as f64 * (1. + 5f64.sqrt()) / 2.).fract() * 360.;
@@ -0,0 +1,18 @@
+// Fragment shader of the characters drawn in the 2D views.
+
+[[group(2), binding(0)]]
+var t_atlas: texture_2d<f32>;
+[[group(2), binding(1)]]
+var s_atlas: sampler;
+
+[[stage(fragment)]]
+fn main(
+    [[location(0)]] v_tex_coords: vec2<f32>,
+    [[location(1)]] v_color: vec4<f32>,
+) -> [[location(0)]] vec4<f32> {
+    let alpha = textureSample(t_atlas, s_atlas, v_tex_coords).w;
+    if (alpha < 0.01) {
+        discard;
+    }
+    return vec4<f32>(v_color.xyz, alpha);
+}
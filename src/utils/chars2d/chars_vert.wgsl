@@ -0,0 +1,68 @@
+// Vertex shader of the characters drawn in the 2D views.
+//
+// Each instance is a unit quad that is stretched on the glyph of its character in the atlas.
+
+[[block]]
+struct Globals {
+    resolution: vec2<f32>;
+    scroll_offset: vec2<f32>;
+    zoom: f32;
+};
+
+struct Instance {
+    center: vec2<f32>;
+    rotation: mat2x2<f32>;
+    size: f32;
+    z_index: i32;
+    color: vec4<f32>;
+    quad_min: vec2<f32>;
+    quad_max: vec2<f32>;
+    uv_min: vec2<f32>;
+    uv_max: vec2<f32>;
+};
+
+[[block]]
+struct Instances {
+    instances: array<Instance>;
+};
+
+[[group(0), binding(0)]]
+var<uniform> globals: Globals;
+[[group(1), binding(0)]]
+var<storage, read> instances: Instances;
+
+struct VertexOutput {
+    [[builtin(position)]] position: vec4<f32>;
+    [[location(0)]] tex_coords: vec2<f32>;
+    [[location(1)]] color: vec4<f32>;
+};
+
+[[stage(vertex)]]
+fn main(
+    [[location(0)]] a_position: vec2<f32>,
+    [[builtin(instance_index)]] instance_idx: u32,
+) -> VertexOutput {
+    let instance = instances.instances[instance_idx];
+
+    var size: f32 = instance.size;
+    if (globals.zoom < 7.0) {
+        size = size * 2.0;
+    }
+
+    let corner = mix(instance.quad_min, instance.quad_max, a_position);
+    let local_pos = instance.center + corner * size;
+    let world_pos = local_pos - globals.scroll_offset;
+    let zoom_factor = globals.zoom / (vec2<f32>(0.5, 0.5) * globals.resolution);
+    let transformed_pos = world_pos * zoom_factor * vec2<f32>(1.0, -1.0);
+
+    var z: f32 = 0.0000001;
+    if (instance.z_index >= 0) {
+        z = f32(instance.z_index) / 10000.0;
+    }
+
+    var out: VertexOutput;
+    out.position = vec4<f32>(transformed_pos, z, 1.0);
+    out.tex_coords = mix(instance.uv_min, instance.uv_max, a_position);
+    out.color = instance.color;
+    return out;
+}
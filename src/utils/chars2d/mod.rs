@@ -19,15 +19,15 @@ use iced_wgpu::wgpu;
 use std::collections::HashMap;
 use std::rc::Rc;
 use ultraviolet::{Mat2, Vec2, Vec4};
-use wgpu::{include_spirv, BindGroupLayout, Device, Queue, RenderPass, RenderPipeline};
+use wgpu::{include_wgsl, BindGroupLayout, Device, Queue, RenderPass, RenderPipeline};
 
 use crate::consts::*;
-use crate::text::{Letter, Vertex as CharVertex};
+use crate::text::{Glyph, GlyphAtlas};
 use crate::utils::bindgroup_manager::DynamicBindGroup;
+use crate::utils::create_buffer_with_data;
 use crate::utils::texture::Texture;
 
-#[repr(C)]
-#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+#[derive(Clone, Copy)]
 pub struct CharInstance {
     pub center: Vec2,
     pub rotation: Mat2,
@@ -36,18 +36,55 @@ pub struct CharInstance {
     pub color: Vec4,
 }
 
+/// The data sent to the GPU for each character: its instance and the location of its glyph in
+/// the atlas
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct RawCharInstance {
+    center: Vec2,
+    rotation: Mat2,
+    size: f32,
+    z_index: i32,
+    color: Vec4,
+    quad_min: Vec2,
+    quad_max: Vec2,
+    uv_min: Vec2,
+    uv_max: Vec2,
+}
+
+impl RawCharInstance {
+    fn new(instance: &CharInstance, glyph: &Glyph) -> Self {
+        Self {
+            center: instance.center,
+            rotation: instance.rotation,
+            size: instance.size,
+            z_index: instance.z_index,
+            color: instance.color,
+            quad_min: glyph.quad_min,
+            quad_max: glyph.quad_max,
+            uv_min: glyph.uv_min,
+            uv_max: glyph.uv_max,
+        }
+    }
+}
+
+/// The corners of the unit quad that is stretched on the glyph of each instance
+const QUAD_VERTICES: &[[f32; 2]] = &[[0., 0.], [0., 1.], [1., 0.], [1., 1.]];
+
+/// Draws all the characters of a view in a single draw call
 pub struct CharDrawer {
     device: Rc<Device>,
     /// A possible updates to the instances to be drawn. Must be taken into account before drawing
     /// next frame
-    new_instances: Option<Rc<Vec<CharInstance>>>,
+    new_instances: Option<Vec<RawCharInstance>>,
     /// The number of instance to draw.
     number_instances: usize,
     /// The data sent the the GPU
     instances_bg: DynamicBindGroup,
     /// The pipeline created by `self`
     pipeline: Option<RenderPipeline>,
-    letter: Rc<Letter>,
+    atlas: Rc<GlyphAtlas>,
+    vertex_buffer: wgpu::Buffer,
 }
 
 impl CharDrawer {
@@ -55,25 +92,23 @@ impl CharDrawer {
         device: Rc<Device>,
         queue: Rc<Queue>,
         globals_layout: &BindGroupLayout,
-        character: char,
+        atlas: Rc<GlyphAtlas>,
     ) -> Self {
         let instances_bg = DynamicBindGroup::new(device.clone(), queue.clone());
-        let char_texture = Rc::new(Letter::new(character, device.clone(), queue.clone()));
-
-        let new_instances = vec![CharInstance {
-            center: Vec2::zero(),
-            rotation: Mat2::identity(),
-            z_index: -1,
-            size: 1.,
-            color: Vec4::zero(),
-        }];
+        let vertex_buffer = create_buffer_with_data(
+            device.as_ref(),
+            bytemuck::cast_slice(QUAD_VERTICES),
+            wgpu::BufferUsages::VERTEX,
+        );
+
         let mut ret = Self {
             device,
-            new_instances: Some(Rc::new(new_instances)),
+            new_instances: Some(vec![bytemuck::Zeroable::zeroed()]),
             number_instances: 0,
             pipeline: None,
             instances_bg,
-            letter: char_texture.clone(),
+            atlas,
+            vertex_buffer,
         };
         let pipeline = ret.create_pipeline(globals_layout);
         ret.pipeline = Some(pipeline);
@@ -84,25 +119,36 @@ impl CharDrawer {
         self.update_instances();
         render_pass.set_pipeline(self.pipeline.as_ref().unwrap());
         render_pass.set_bind_group(1, self.instances_bg.get_bindgroup(), &[]);
-        render_pass.set_bind_group(TEXTURE_BINDING_ID, &self.letter.bind_group, &[]);
-        render_pass.set_vertex_buffer(0, self.letter.vertex_buffer.slice(..));
+        render_pass.set_bind_group(TEXTURE_BINDING_ID, &self.atlas.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
         render_pass.draw(0..4, 0..self.number_instances as u32);
     }
 
-    pub fn new_instances(&mut self, instances: Rc<Vec<CharInstance>>) {
+    /// Set the characters to be drawn. The characters that are not in the atlas are skipped.
+    pub fn new_instances(&mut self, char_map: &HashMap<char, Vec<CharInstance>>) {
+        let mut instances = Vec::new();
+        for (c, v) in char_map.iter() {
+            if let Some(glyph) = self.atlas.glyph(*c) {
+                instances.extend(v.iter().map(|i| RawCharInstance::new(i, glyph)));
+            }
+        }
         self.new_instances = Some(instances)
     }
 
     fn update_instances(&mut self) {
-        if let Some(ref instances) = self.new_instances {
+        if let Some(instances) = self.new_instances.take() {
             self.number_instances = instances.len();
-            let instances_data: Vec<_> = instances.iter().cloned().collect();
-            self.instances_bg.update(instances_data.as_slice());
+            self.instances_bg.update(instances.as_slice());
         }
     }
 
-    pub fn advancement_x(&self) -> f32 {
-        self.letter.advance
+    /// Return true iff the character is in the atlas of the drawer.
+    pub fn can_draw(&self, character: char) -> bool {
+        self.atlas.glyph(character).is_some()
+    }
+
+    fn glyph(&self, character: char) -> &Glyph {
+        self.atlas.glyph(character).unwrap()
     }
 
     /// Create a render pipepline. This function is meant to be called once, before drawing for the
@@ -110,17 +156,17 @@ impl CharDrawer {
     fn create_pipeline(&self, globals_layout: &BindGroupLayout) -> RenderPipeline {
         let vertex_module = self
             .device
-            .create_shader_module(&include_spirv!("chars.vert.spv"));
+            .create_shader_module(&include_wgsl!("chars_vert.wgsl"));
         let fragment_module = self
             .device
-            .create_shader_module(&include_spirv!("chars.frag.spv"));
+            .create_shader_module(&include_wgsl!("chars_frag.wgsl"));
         let render_pipeline_layout =
             self.device
                 .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
                     bind_group_layouts: &[
                         globals_layout,
                         &self.instances_bg.get_layout(),
-                        &self.letter.bind_group_layout,
+                        &self.atlas.bind_group_layout,
                     ],
                     push_constant_ranges: &[],
                     label: Some("render_pipeline_layout"),
@@ -148,7 +194,11 @@ impl CharDrawer {
                 vertex: wgpu::VertexState {
                     module: &vertex_module,
                     entry_point: "main",
-                    buffers: &[CharVertex::desc()],
+                    buffers: &[wgpu::VertexBufferLayout {
+                        array_stride: std::mem::size_of::<[f32; 2]>() as wgpu::BufferAddress,
+                        step_mode: wgpu::VertexStepMode::Vertex,
+                        attributes: &wgpu::vertex_attr_array![0 => Float32x2],
+                    }],
                 },
                 fragment: Some(wgpu::FragmentState {
                     module: &fragment_module,
@@ -173,34 +223,31 @@ impl CharDrawer {
     }
 }
 
-pub fn char_positions_x(string: &str, drawers: &HashMap<char, CharDrawer>) -> Vec<f32> {
+pub fn char_positions_x(string: &str, drawer: &CharDrawer) -> Vec<f32> {
     let mut ret = vec![0f32];
     let mut x = 0f32;
     for c in string.chars() {
-        x += drawers.get(&c).unwrap().advancement_x();
+        x += drawer.glyph(c).advance;
         ret.push(x);
     }
     ret
 }
 
-pub fn char_positions_y(string: &str, drawers: &HashMap<char, CharDrawer>) -> Vec<f32> {
-    let max_height = height(string, drawers);
+pub fn char_positions_y(string: &str, drawer: &CharDrawer) -> Vec<f32> {
+    let max_height = height(string, drawer);
     let mut ret = vec![];
 
     for c in string.chars() {
-        ret.push(
-            max_height
-                - drawers.get(&c).unwrap().letter.height
-                - drawers.get(&c).unwrap().letter.advance_height,
-        )
+        let glyph = drawer.glyph(c);
+        ret.push(max_height - glyph.height - glyph.advance_height)
     }
     ret
 }
 
-pub fn height(string: &str, drawers: &HashMap<char, CharDrawer>) -> f32 {
+pub fn height(string: &str, drawer: &CharDrawer) -> f32 {
     let mut ret = 0f32;
     for c in string.chars() {
-        ret = ret.max(drawers.get(&c).unwrap().letter.height)
+        ret = ret.max(drawer.glyph(c).height)
     }
     ret
 }
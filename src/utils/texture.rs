@@ -139,6 +139,14 @@ impl Texture {
 
 impl SampledTexture {
     pub fn create_target_texture(device: &wgpu::Device, size: &PhySize) -> Self {
+        Self::create_target_texture_with_format(device, size, wgpu::TextureFormat::Bgra8UnormSrgb)
+    }
+
+    pub fn create_target_texture_with_format(
+        device: &wgpu::Device,
+        size: &PhySize,
+        format: wgpu::TextureFormat,
+    ) -> Self {
         let texture_descriptor = &wgpu::TextureDescriptor {
             label: Some("target texture descriptor"),
             size: wgpu::Extent3d {
@@ -149,7 +157,7 @@ impl SampledTexture {
             mip_level_count: 1,
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Bgra8UnormSrgb,
+            format,
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
         };
 